@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use chrono::Local;
+use serde_json::Value;
+
+use crate::actuator::model::{ActuatorMetric, HealthStatus, JvmThread};
+use crate::error::FetchError;
+
+/// Statuses of the app and its health indicators, read from /actuator/health
+pub fn get_health(address: &str, timeout: u64) -> Result<HealthStatus, FetchError> {
+    get_health_async(address, timeout)
+}
+
+/// Measurements of a metric by statistic (e.g. `COUNT`, `TOTAL_TIME`, `MAX`), read from /actuator/metrics
+pub fn get_metric(address: &str, metric: &ActuatorMetric, timeout: u64) -> Result<Vec<(String, f64)>, FetchError> {
+    get_metric_async(address, metric, timeout)
+}
+
+/// All the threads of the JVM, read from /actuator/threaddump
+pub fn get_thread_dump(address: &str, timeout: u64) -> Result<Vec<JvmThread>, FetchError> {
+    get_thread_dump_async(address, timeout)
+}
+
+fn client(timeout: u64) -> Result<reqwest::Client, FetchError> {
+    Ok(reqwest::Client::builder()
+        .timeout(Duration::from_millis(timeout))
+        .build()?)
+}
+
+fn endpoint(address: &str, path: &str) -> String {
+    format!("{}/{}", address.trim_end_matches('/'), path)
+}
+
+#[tokio::main]
+async fn get_health_async(address: &str, timeout: u64) -> Result<HealthStatus, FetchError> {
+    let response = client(timeout)?.get(&endpoint(address, "health")).send().await?;
+    // apps that are down respond with 503, still reporting their health
+    let status = response.status();
+    let body: Value = response.json().await
+        .map_err(|_| FetchError::of_status(status, format!("Request to get health failed with status {}", status)))?;
+    Ok(parse_health(&body)?)
+}
+
+#[tokio::main]
+async fn get_metric_async(address: &str, metric: &ActuatorMetric, timeout: u64) -> Result<Vec<(String, f64)>, FetchError> {
+    let tags: Vec<(&str, String)> = metric.tags.iter().map(|(k, v)| ("tag", format!("{}:{}", k, v))).collect();
+    let response = client(timeout)?.get(&endpoint(address, &format!("metrics/{}", metric.name)))
+        .query(&tags)
+        .send().await?;
+    if !response.status().is_success() {
+        return Err(FetchError::of_status(response.status(), format!("Request to get metric {} failed with status {}", metric, response.status())));
+    }
+    let body: Value = response.json().await?;
+    Ok(parse_measurements(&body))
+}
+
+#[tokio::main]
+async fn get_thread_dump_async(address: &str, timeout: u64) -> Result<Vec<JvmThread>, FetchError> {
+    let response = client(timeout)?.get(&endpoint(address, "threaddump"))
+        .header("Accept", "application/json")
+        .send().await?;
+    if !response.status().is_success() {
+        return Err(FetchError::of_status(response.status(), format!("Request to get thread dump failed with status {}", response.status())));
+    }
+    let body: Value = response.json().await?;
+    Ok(parse_thread_dump(&body)?)
+}
+
+///
+/// Parses a health response, e.g.
+///
+/// {"status": "UP", "components": {"db": {"status": "UP", "details": {...}}, "disk": {"status": "UP"}}}
+///
+/// Composite indicators nest their components the same way. Spring Boot 2.1 and older call them `details`.
+fn parse_health(json: &Value) -> Result<HealthStatus, String> {
+    let status = json["status"].as_str().ok_or("Health response has no status")?;
+    let mut components = vec![];
+    health_components(json, "", &mut components);
+    Ok(HealthStatus { timestamp: Local::now(), status: status.to_owned(), components })
+}
+
+fn health_components(json: &Value, prefix: &str, components: &mut Vec<(String, String)>) {
+    let children = json["components"].as_object().or_else(|| json["details"].as_object());
+    for (name, c) in children.into_iter().flatten() {
+        if let Some(status) = c["status"].as_str() {
+            let path = format!("{}{}", prefix, name);
+            components.push((path.clone(), status.to_owned()));
+            health_components(c, &format!("{}/", path), components);
+        }
+    }
+}
+
+///
+/// Parses a metric response, e.g.
+///
+/// {"name": "http.server.requests", "measurements": [{"statistic": "COUNT", "value": 12}, {"statistic": "MAX", "value": 0.2}]}
+fn parse_measurements(json: &Value) -> Vec<(String, f64)> {
+    json["measurements"].as_array().map_or(vec![], |measurements| {
+        measurements.iter()
+            .filter_map(|m| Some((m["statistic"].as_str()?.to_owned(), m["value"].as_f64()?)))
+            .collect()
+    })
+}
+
+///
+/// Parses a JSON thread dump, e.g.
+///
+/// {"threads": [{"threadName": "main", "threadId": 1, "threadState": "RUNNABLE",
+///   "stackTrace": [{"className": "java.lang.Thread", "methodName": "sleep", "fileName": "Thread.java", "lineNumber": -2, "nativeMethod": true}],
+///   "lockName": null, "lockOwnerName": null}]}
+fn parse_thread_dump(json: &Value) -> Result<Vec<JvmThread>, String> {
+    let threads = json["threads"].as_array().ok_or("Thread dump has no threads")?;
+    Ok(threads.iter()
+        .map(|t| {
+            let name = t["threadName"].as_str().unwrap_or("-").to_owned();
+            let id = t["threadId"].as_u64().unwrap_or(0);
+            let state = t["threadState"].as_str().unwrap_or("-").to_owned();
+            let mut lines = vec![format!("\"{}\" #{} {}", name, id, state)];
+            for frame in t["stackTrace"].as_array().into_iter().flatten() {
+                let location = match (frame["fileName"].as_str(), frame["lineNumber"].as_i64()) {
+                    _ if frame["nativeMethod"].as_bool() == Some(true) => "Native Method".to_owned(),
+                    (Some(file), Some(line)) if line >= 0 => format!("{}:{}", file, line),
+                    (Some(file), _) => file.to_owned(),
+                    (None, _) => "Unknown Source".to_owned(),
+                };
+                lines.push(format!("    at {}.{}({})",
+                                   frame["className"].as_str().unwrap_or("-"), frame["methodName"].as_str().unwrap_or("-"), location));
+            }
+            if let Some(lock) = t["lockName"].as_str() {
+                match t["lockOwnerName"].as_str() {
+                    Some(owner) => lines.push(format!("    - waiting on <{}> owned by \"{}\"", lock, owner)),
+                    None => lines.push(format!("    - waiting on <{}>", lock)),
+                }
+            }
+            JvmThread { id, name, state, dump: lines.join("\n") }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::actuator::client::{parse_health, parse_measurements, parse_thread_dump};
+
+    #[test]
+    fn nested_health_components_are_flattened() {
+        let json = json!({"status": "DOWN", "components": {
+            "db": {"status": "DOWN", "components": {"primary": {"status": "UP"}, "replica": {"status": "DOWN", "details": {"error": "timeout"}}}},
+            "diskSpace": {"status": "UP", "details": {"free": 1000, "total": 2000}}
+        }});
+        let health = parse_health(&json).unwrap();
+        assert!(!health.is_up());
+        assert_eq!(health.components, vec![
+            ("db".to_owned(), "DOWN".to_owned()),
+            ("db/primary".to_owned(), "UP".to_owned()),
+            ("db/replica".to_owned(), "DOWN".to_owned()),
+            ("diskSpace".to_owned(), "UP".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn measurements_and_thread_dumps_are_parsed() {
+        let metric = json!({"name": "http.server.requests", "measurements": [
+            {"statistic": "COUNT", "value": 12.0}, {"statistic": "TOTAL_TIME", "value": 0.5}
+        ]});
+        assert_eq!(parse_measurements(&metric), vec![("COUNT".to_owned(), 12.0), ("TOTAL_TIME".to_owned(), 0.5)]);
+
+        let dump = json!({"threads": [{
+            "threadName": "worker-1", "threadId": 31, "threadState": "BLOCKED",
+            "lockName": "java.lang.Object@1b2c3d", "lockOwnerName": "worker-2",
+            "stackTrace": [
+                {"className": "com.example.Cache", "methodName": "get", "fileName": "Cache.java", "lineNumber": 42, "nativeMethod": false},
+                {"className": "java.lang.Thread", "methodName": "run", "fileName": "Thread.java", "lineNumber": -1, "nativeMethod": false}
+            ]
+        }]});
+        let threads = parse_thread_dump(&dump).unwrap();
+        assert_eq!((threads[0].id, threads[0].state.as_str()), (31, "BLOCKED"));
+        assert_eq!(threads[0].dump, "\"worker-1\" #31 BLOCKED\n\
+            \x20   at com.example.Cache.get(Cache.java:42)\n\
+            \x20   at java.lang.Thread.run(Thread.java)\n\
+            \x20   - waiting on <java.lang.Object@1b2c3d> owned by \"worker-2\"");
+    }
+}
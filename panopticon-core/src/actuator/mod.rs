@@ -0,0 +1,6 @@
+//! Spring Boot apps, through their Actuator endpoints
+
+/// Health statuses, metrics and thread dumps, with the settings of the source
+pub mod model;
+/// Reads /actuator/health, /actuator/metrics and /actuator/threaddump
+pub mod client;
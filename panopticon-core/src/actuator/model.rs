@@ -0,0 +1,100 @@
+use std::fmt;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Spring Boot app polled through its Actuator endpoints
+#[derive(Clone)]
+pub struct ActuatorSettings {
+    /// Base address of the actuator endpoints, e.g. http://localhost:8080/actuator
+    pub address: String,
+    /// Metrics read from /actuator/metrics on every poll
+    pub metrics: Vec<ActuatorMetric>,
+    /// Time in ms each request has to finish
+    pub timeout: u64,
+}
+
+/// A Micrometer metric, optionally narrowed down by tags, e.g. `http.server.requests{status:500,uri:/api}`
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActuatorMetric {
+    /// Name of the metric, e.g. `jvm.memory.used`
+    pub name: String,
+    /// Tags narrowing the measurements down, as names and values
+    pub tags: Vec<(String, String)>,
+}
+
+impl ActuatorMetric {
+    /// Parses a metric like `name{tag:value,tag:value}`, the tags being optional
+    pub fn parse(s: &str) -> Result<ActuatorMetric, String> {
+        let invalid = || format!("Expected a metric like name{{tag:value}}, got {}", s);
+        let s = s.trim();
+        let (name, tags) = match s.split_once('{') {
+            Some((name, rest)) => (name, rest.strip_suffix('}').ok_or_else(invalid)?),
+            None => (s, ""),
+        };
+        if name.is_empty() {
+            return Err(invalid());
+        }
+        let tags = tags.split(',')
+            .filter(|t| !t.trim().is_empty())
+            .map(|t| t.split_once(':').map(|(k, v)| (k.trim().to_owned(), v.trim().to_owned())).ok_or_else(invalid))
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(ActuatorMetric { name: name.to_owned(), tags })
+    }
+}
+
+impl fmt::Display for ActuatorMetric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.tags.is_empty() {
+            let tags: Vec<String> = self.tags.iter().map(|(k, v)| format!("{}:{}", k, v)).collect();
+            write!(f, "{{{}}}", tags.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Health of the app, read from /actuator/health
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct HealthStatus {
+    /// When the status was read
+    pub timestamp: DateTime<Local>,
+    /// Aggregated status, e.g. UP, DOWN, OUT_OF_SERVICE
+    pub status: String,
+    /// Statuses of health indicators by their path, e.g. `db` or `db/primary` for nested ones
+    pub components: Vec<(String, String)>,
+}
+
+impl HealthStatus {
+    /// Whether the aggregated status is `UP`
+    pub fn is_up(&self) -> bool {
+        self.status == "UP"
+    }
+}
+
+/// A thread of the JVM, from /actuator/threaddump
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct JvmThread {
+    /// Thread id
+    pub id: u64,
+    /// Thread name
+    pub name: String,
+    /// Thread state, e.g. RUNNABLE, BLOCKED or WAITING
+    pub state: String,
+    /// Stack trace and locks, formatted like jstack does
+    pub dump: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actuator::model::ActuatorMetric;
+
+    #[test]
+    fn metrics_are_parsed_with_tags() {
+        let metric = ActuatorMetric::parse("http.server.requests{status:500, uri:/api/orders}").unwrap();
+        assert_eq!(metric.tags, vec![("status".to_owned(), "500".to_owned()), ("uri".to_owned(), "/api/orders".to_owned())]);
+        assert_eq!(metric.to_string(), "http.server.requests{status:500,uri:/api/orders}");
+        assert_eq!(ActuatorMetric::parse("jvm.threads.live").unwrap().tags, vec![]);
+        assert!(ActuatorMetric::parse("jvm.memory.used{area}").is_err());
+    }
+}
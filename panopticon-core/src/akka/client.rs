@@ -0,0 +1,418 @@
+use reqwest;
+use reqwest::RequestBuilder;
+use serde_json::Value;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use chrono::{Local, TimeZone};
+use crate::akka::model::{ActorStats, ActorTree, ActorTreeNode, DeadLetter, DeadLetterMetrics, HistogramTotals, HttpAuth, HttpSettings, KamonMetrics, RemoteTotals};
+use crate::error::FetchError;
+use crate::prometheus::client::parse_sample;
+use std::collections::HashMap;
+use std::time::Duration;
+
+///
+/// Actor tree, or the subtree of the actor at `path` (relative to the actor system) if given.
+///
+/// With a `depth`, only that many levels are requested, for endpoints that can leave out the deeper ones.
+/// It's awaited on the caller's runtime, as assembling the tree of a big actor system takes seconds and the fetch may be aborted.
+pub async fn get_actors(url: &str, timeout: u64, path: Option<&str>, depth: Option<u32>, http: &HttpSettings) -> Result<ActorTree, FetchError> {
+    let url = with_tree_scope(&with_timeout(url, timeout), path, depth)?;
+    let mut response_body: HashMap<String, Value> = get_json(&url, client(http, None)?, http, "actor tree").await?;
+    match path {
+        None => Ok(build_actor_tree(&mut response_body)),
+        Some(_) => {
+            let mut tree = ActorTree::default();
+            build_actor_tree_iter(&Value::Object(response_body.into_iter().collect()), None, "", &mut tree);
+            Ok(tree)
+        }
+    }
+}
+
+/// Number of actors of the actor system
+pub fn get_actor_count(url: &str, timeout: u64, http: &HttpSettings) -> Result<u64, FetchError> {
+    get_actor_count_async(url, timeout, http)
+}
+
+/// Dead letters of the actor system, with the most recent ones
+pub fn get_dead_letters(url: &str, timeout: u64, http: &HttpSettings) -> Result<DeadLetterMetrics, FetchError> {
+    get_dead_letters_async(url, timeout, http)
+}
+
+/// Entity counts per shard of a region hosted on the node, read from its Akka Management endpoint
+pub fn get_shard_region_stats(node: &str, region: &str, timeout: u64, http: &HttpSettings) -> Result<Vec<(String, u64)>, FetchError> {
+    get_shard_region_stats_async(node, region, timeout, http)
+}
+
+/// Mailbox sizes (and message counts, if reported) by actor path, relative to the actor system (e.g. `user/parent/child`)
+pub fn get_actor_stats(url: &str, timeout: u64, http: &HttpSettings) -> Result<HashMap<String, ActorStats>, FetchError> {
+    get_actor_stats_async(url, timeout, http)
+}
+
+/// Actor processing times, mailbox sizes and dead letters from the Prometheus endpoint of the Kamon reporter
+pub fn get_kamon_metrics(url: &str, timeout: u64, http: &HttpSettings) -> Result<KamonMetrics, FetchError> {
+    get_kamon_metrics_async(url, timeout, http)
+}
+
+/// Appends the timeout parameter, keeping any query the url already has
+fn with_timeout(url: &str, timeout: u64) -> String {
+    let separator = if url.contains('?') { '&' } else { '?' };
+    format!("{}{}timeout={}", url, separator, timeout)
+}
+
+fn client(http: &HttpSettings, timeout: Option<u64>) -> Result<reqwest::Client, FetchError> {
+    let mut builder = reqwest::Client::builder()
+        .danger_accept_invalid_certs(http.skip_tls_verify)
+        .danger_accept_invalid_hostnames(http.skip_tls_verify);
+    if let Some(t) = timeout {
+        builder = builder.timeout(Duration::from_millis(t));
+    }
+    Ok(builder.build()?)
+}
+
+/// GET request with the configured headers and authentication
+fn request(client: &reqwest::Client, url: &str, http: &HttpSettings) -> RequestBuilder {
+    let mut request = client.get(url);
+    for (name, value) in &http.headers {
+        request = request.header(name.as_str(), value.as_str());
+    }
+    match &http.auth {
+        Some(HttpAuth::Basic { username, password }) => request.basic_auth(username, password.as_ref()),
+        Some(HttpAuth::Bearer(token)) => request.bearer_auth(token),
+        None => request,
+    }
+}
+
+/// Fetches and deserializes a JSON response, `what` names the requested data in errors
+async fn get_json<T: DeserializeOwned>(url: &str, client: reqwest::Client, http: &HttpSettings, what: &str) -> Result<T, FetchError> {
+    let response = request(&client, url, http).send().await?;
+    if !response.status().is_success() {
+        return Err(FetchError::of_status(response.status(), format!("Request to get {} failed with status {}", what, response.status())));
+    }
+    Ok(response.json().await?)
+}
+
+/// Adds the `path` and `depth` parameters of a partial tree request
+fn with_tree_scope(url: &str, path: Option<&str>, depth: Option<u32>) -> Result<String, String> {
+    let mut url = reqwest::Url::parse(url).map_err(|e| e.to_string())?;
+    if let Some(p) = path {
+        url.query_pairs_mut().append_pair("path", p);
+    }
+    if let Some(d) = depth {
+        url.query_pairs_mut().append_pair("depth", &d.to_string());
+    }
+    Ok(url.to_string())
+}
+
+fn build_actor_tree(json: &mut HashMap<String, Value>) -> ActorTree {
+    let mut tree = ActorTree::default();
+    // user actors should go first
+    if let Some(v) = json.get("user") {
+        tree.nodes.push(ActorTreeNode { name: "user".to_string(), parent: None, id: 1 });
+        build_actor_tree_iter(v, Some(1), "user", &mut tree)
+    }
+
+    for (k, v) in json {
+        if k != "user" {
+            let id = tree.nodes.len() + 1;
+            tree.nodes.push(ActorTreeNode { name: k.to_owned(), parent: None, id });
+            build_actor_tree_iter(v, Some(id), k, &mut tree)
+        }
+    }
+    tree
+}
+
+/// Adds the children of the actor at `path`. Children that weren't fetched are reported as their number, or null
+fn build_actor_tree_iter(json: &Value, parent_id: Option<usize>, path: &str, tree: &mut ActorTree) {
+    match json {
+        Value::Object(mm) => {
+            for (k, v) in mm {
+                let id = tree.nodes.len() + 1;
+                tree.nodes.push(ActorTreeNode { name: k.to_owned(), parent: parent_id, id });
+                let child_path = if path.is_empty() { k.to_owned() } else { format!("{}/{}", path, k) };
+                build_actor_tree_iter(v, Some(id), &child_path, tree);
+            }
+        }
+        other => {
+            tree.unloaded.insert(path.to_owned(), other.as_u64());
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct CountResult {
+    result: u64
+}
+
+#[tokio::main]
+async fn get_actor_count_async(url: &str, timeout: u64, http: &HttpSettings) -> Result<u64, FetchError> {
+    let url = with_timeout(url, timeout);
+    let body: CountResult = get_json(&url, client(http, None)?, http, "actor count").await?;
+    Ok(body.result)
+}
+
+#[tokio::main]
+async fn get_dead_letters_async(url: &str, timeout: u64, http: &HttpSettings) -> Result<DeadLetterMetrics, FetchError> {
+    let url = with_timeout(url, timeout);
+    let body: Value = get_json(&url, client(http, None)?, http, "dead letters").await?;
+    Ok(parse_dead_letters(&body)?)
+}
+
+#[tokio::main]
+async fn get_actor_stats_async(url: &str, timeout: u64, http: &HttpSettings) -> Result<HashMap<String, ActorStats>, FetchError> {
+    let url = with_timeout(url, timeout);
+    let body: Value = get_json(&url, client(http, None)?, http, "mailbox sizes").await?;
+    Ok(parse_actor_stats(&body)?)
+}
+
+///
+/// Parses mailbox sizes keyed by actor path, optionally along with the number of processed messages, e.g.
+///
+/// {"akka://app/user/parent/child": 12, "user/other": {"mailbox": 0, "messages": 3400}}
+///
+/// Full actor paths are made relative to the actor system, so that they match the actor tree.
+fn parse_actor_stats(json: &Value) -> Result<HashMap<String, ActorStats>, String> {
+    let stats = json.as_object().ok_or("Mailbox sizes should be an object of actor paths to sizes")?;
+    Ok(stats.iter()
+        .filter_map(|(path, value)| {
+            let stats = match value {
+                Value::Object(_) => ActorStats { mailbox: value["mailbox"].as_u64(), messages: value["messages"].as_u64(), processing_time_ms: None },
+                _ => ActorStats { mailbox: Some(value.as_u64()?), messages: None, processing_time_ms: None },
+            };
+            Some((relative_actor_path(path).to_owned(), stats))
+        })
+        .collect())
+}
+
+/// Makes a full actor path (`akka://app/user/a`) relative to the actor system (`user/a`)
+fn relative_actor_path(path: &str) -> &str {
+    let relative = match path.find("://") {
+        Some(i) => path[i + 3..].split_once('/').map_or("", |(_, p)| p),
+        None => path,
+    };
+    relative.trim_matches('/')
+}
+
+#[tokio::main]
+async fn get_kamon_metrics_async(url: &str, timeout: u64, http: &HttpSettings) -> Result<KamonMetrics, FetchError> {
+    let response = request(&client(http, Some(timeout))?, url, http).send().await?;
+    if !response.status().is_success() {
+        return Err(FetchError::of_status(response.status(), format!("Request to get Kamon metrics failed with status {}", response.status())));
+    }
+    let body = response.text().await?;
+    Ok(parse_kamon_metrics(&body))
+}
+
+///
+/// Picks the metrics of Kamon's Akka instrumentation out of a Prometheus scrape, e.g.
+///
+/// akka_actor_processing_time_seconds_sum{path="app/user/a",system="app"} 0.25
+///
+/// Actor paths are tagged with the actor system name as their first segment, which is dropped so that they
+/// match the actor tree. Dead letters and remote messages of all the actor systems are added up.
+fn parse_kamon_metrics(text: &str) -> KamonMetrics {
+    let mut metrics = KamonMetrics {
+        timestamp: Local::now(),
+        processing_time: HashMap::new(),
+        mailbox_size: HashMap::new(),
+        dead_letters: None,
+        remote: None,
+    };
+    for (name, labels, value) in text.lines().filter_map(parse_sample) {
+        if name == "akka_system_dead_letters_total" {
+            *metrics.dead_letters.get_or_insert(0) += value as u64;
+            continue;
+        }
+        if let Some(suffix) = name.strip_prefix("akka_remote_message_size_bytes_") {
+            let remote = metrics.remote.get_or_insert_with(RemoteTotals::default);
+            match (labels.get("direction").map(|d| d.as_str()), suffix) {
+                (Some("in"), "count") => remote.messages_in += value as u64,
+                (Some("in"), "sum") => remote.bytes_in += value,
+                (Some("out"), "count") => remote.messages_out += value as u64,
+                (Some("out"), "sum") => remote.bytes_out += value,
+                _ => {}
+            }
+            continue;
+        }
+        let (histogram, suffix) = match name.rsplit_once('_') {
+            Some(("akka_actor_processing_time_seconds", suffix)) => (&mut metrics.processing_time, suffix),
+            Some(("akka_actor_mailbox_size", suffix)) => (&mut metrics.mailbox_size, suffix),
+            _ => continue,
+        };
+        let path = match labels.get("path") {
+            Some(path) => match labels.get("system").and_then(|s| path.strip_prefix(s.as_str())) {
+                Some(relative) => relative.trim_matches('/'),
+                None => relative_actor_path(path),
+            },
+            None => continue,
+        };
+        let totals = histogram.entry(path.to_owned()).or_insert_with(HistogramTotals::default);
+        match suffix {
+            "sum" => totals.sum = value,
+            "count" => totals.count = value as u64,
+            _ => {}
+        }
+    }
+    metrics
+}
+
+#[tokio::main]
+async fn get_shard_region_stats_async(node: &str, region: &str, timeout: u64, http: &HttpSettings) -> Result<Vec<(String, u64)>, FetchError> {
+    let url = format!("{}/cluster/shards/{}", node.trim_end_matches('/'), region);
+    let body: Value = get_json(&url, client(http, Some(timeout))?, http, "shard region stats").await?;
+    Ok(parse_shard_region_stats(&body)?)
+}
+
+///
+/// Parses shard details of Akka Management, e.g.
+///
+/// {"regions": [{"shardId": "12", "numEntities": 30}, {"shardId": "7", "numEntities": 2}]}
+fn parse_shard_region_stats(json: &Value) -> Result<Vec<(String, u64)>, String> {
+    let regions = json["regions"].as_array().ok_or("Shard details have no regions")?;
+    Ok(regions.iter()
+        .filter_map(|r| {
+            let id = match &r["shardId"] {
+                Value::String(s) => s.to_owned(),
+                Value::Null => return None,
+                other => other.to_string(),
+            };
+            Some((id, r["numEntities"].as_u64().unwrap_or(0)))
+        })
+        .collect())
+}
+
+///
+/// Parses a response of the dead letters endpoint, e.g.
+///
+/// {"count": 42, "samples": [{"timestamp": 1591000000000, "sender": "...", "recipient": "...", "message": "..."}]}
+///
+/// where `count` is the number of dead letters since the start, and `samples` are the most recent ones,
+/// with timestamps in epoch millis. Samples without a timestamp are skipped.
+fn parse_dead_letters(json: &Value) -> Result<DeadLetterMetrics, String> {
+    let count = json["count"].as_u64().ok_or("Dead letters response has no count")?;
+    let text = |v: &Value| match v {
+        Value::String(s) => s.to_owned(),
+        Value::Null => "-".to_owned(),
+        other => other.to_string(),
+    };
+    let samples = json["samples"].as_array().map_or(vec![], |samples| {
+        samples.iter()
+            .filter_map(|s| s["timestamp"].as_i64().map(|t| DeadLetter {
+                timestamp: Local.timestamp_millis(t),
+                sender: text(&s["sender"]),
+                recipient: text(&s["recipient"]),
+                message: text(&s["message"]),
+            }))
+            .collect()
+    });
+    Ok(DeadLetterMetrics { timestamp: Local::now(), count, samples })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::akka::client::{build_actor_tree, parse_actor_stats, parse_dead_letters, parse_kamon_metrics, parse_shard_region_stats, request, with_timeout, with_tree_scope};
+    use crate::akka::model::{ActorStats, HistogramTotals, HttpAuth, HttpSettings, RemoteTotals};
+
+    #[test]
+    fn dead_letters_are_parsed() {
+        let json = json!({"count": 42, "samples": [
+            {"timestamp": 1591000000000i64, "sender": "akka://app/user/a", "recipient": "akka://app/user/b", "message": "Ping"},
+            {"timestamp": 1591000001000i64, "sender": null, "recipient": "akka://app/user/b", "message": {"id": 1}},
+            {"sender": "akka://app/user/a", "recipient": "akka://app/user/b", "message": "Lost"}
+        ]});
+
+        let metrics = parse_dead_letters(&json).unwrap();
+        assert_eq!(metrics.count, 42);
+        assert_eq!(metrics.samples.len(), 2);
+        assert_eq!(metrics.samples[0].timestamp.timestamp_millis(), 1591000000000);
+        assert_eq!(metrics.samples[0].message, "Ping");
+        assert_eq!((metrics.samples[1].sender.as_str(), metrics.samples[1].message.as_str()), ("-", "{\"id\":1}"));
+
+        assert!(parse_dead_letters(&json!({"samples": []})).is_err());
+    }
+
+    #[test]
+    fn shard_region_stats_are_parsed() {
+        let json = json!({"regions": [{"shardId": "12", "numEntities": 30}, {"shardId": 7, "numEntities": 2}, {"numEntities": 1}]});
+        assert_eq!(parse_shard_region_stats(&json), Ok(vec![("12".to_owned(), 30), ("7".to_owned(), 2)]));
+        assert!(parse_shard_region_stats(&json!({})).is_err());
+    }
+
+    #[test]
+    fn actor_stats_are_keyed_by_relative_paths() {
+        let json = json!({
+            "akka://app/user/a/b": 12,
+            "akka.tcp://app@10.0.0.1:2552/system/log": {"mailbox": 1, "messages": 30},
+            "/user/c": {"messages": 5},
+            "user/d": "x"
+        });
+        let stats = parse_actor_stats(&json).unwrap();
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats.get("user/a/b"), Some(&ActorStats { mailbox: Some(12), ..ActorStats::default() }));
+        assert_eq!(stats.get("system/log"), Some(&ActorStats { mailbox: Some(1), messages: Some(30), ..ActorStats::default() }));
+        assert_eq!(stats.get("user/c"), Some(&ActorStats { messages: Some(5), ..ActorStats::default() }));
+    }
+
+    #[test]
+    fn kamon_metrics_are_parsed_from_prometheus_scrape() {
+        let text = r#"
+# TYPE akka_actor_processing_time_seconds histogram
+akka_actor_processing_time_seconds_bucket{path="app/user/a",system="app",le="0.005"} 3.0
+akka_actor_processing_time_seconds_count{path="app/user/a",system="app",class="a.A"} 4.0
+akka_actor_processing_time_seconds_sum{path="app/user/a",system="app",class="a.A"} 0.5
+akka_actor_mailbox_size_count{system="app",path="app/user/b",class="say \"hi\", b"} 2.0
+akka_actor_mailbox_size_sum{system="app",path="app/user/b",class="say \"hi\", b"} 30.0
+akka_system_dead_letters_total{system="app"} 7.0
+akka_system_dead_letters_total{system="other"} 3.0
+akka_remote_message_size_bytes_count{system="app",direction="in"} 20.0
+akka_remote_message_size_bytes_sum{system="app",direction="in"} 2048.0
+akka_remote_message_size_bytes_count{system="app",direction="out"} 15.0
+jvm_threads{state="runnable"} 12.0
+"#;
+        let metrics = parse_kamon_metrics(text);
+        assert_eq!(metrics.processing_time.get("user/a"), Some(&HistogramTotals { sum: 0.5, count: 4 }));
+        assert_eq!(metrics.mailbox_size.get("user/b"), Some(&HistogramTotals { sum: 30.0, count: 2 }));
+        assert_eq!(metrics.dead_letters, Some(10));
+        assert_eq!(metrics.remote, Some(RemoteTotals { messages_in: 20, messages_out: 15, bytes_in: 2048.0, bytes_out: 0.0 }));
+        assert_eq!(parse_kamon_metrics("").dead_letters, None);
+    }
+
+    #[test]
+    fn actors_cut_off_by_depth_are_reported_as_unloaded() {
+        let json = json!({"user": {"workers": 1243, "sessions": {"s1": {}}}, "system": null});
+        let mut json = json.as_object().unwrap().clone().into_iter().collect();
+        let tree = build_actor_tree(&mut json);
+        assert_eq!(tree.nodes.len(), 5);
+        assert_eq!(tree.unloaded.len(), 2);
+        assert_eq!(tree.unloaded.get("user/workers"), Some(&Some(1243)));
+        assert_eq!(tree.unloaded.get("system"), Some(&None));
+        assert_eq!(with_tree_scope("http://app/actor-tree?timeout=1000", Some("user/a$b"), Some(2)).unwrap(),
+                   "http://app/actor-tree?timeout=1000&path=user%2Fa%24b&depth=2");
+    }
+
+    #[test]
+    fn timeout_is_added_to_url_query() {
+        assert_eq!(with_timeout("https://app/actor-tree", 1000), "https://app/actor-tree?timeout=1000");
+        assert_eq!(with_timeout("https://app/actor-tree?system=app", 1000), "https://app/actor-tree?system=app&timeout=1000");
+    }
+
+    #[test]
+    fn requests_carry_configured_headers_and_auth() {
+        let http = HttpSettings {
+            headers: vec![("X-Tenant".to_owned(), "staging".to_owned())],
+            auth: Some(HttpAuth::Bearer("secret".to_owned())),
+            skip_tls_verify: false,
+        };
+        let bearer = request(&reqwest::Client::new(), "http://localhost:8080/actor-tree", &http).build().unwrap();
+        assert_eq!(bearer.headers()["X-Tenant"], "staging");
+        assert_eq!(bearer.headers()["Authorization"], "Bearer secret");
+
+        let http = HttpSettings {
+            auth: Some(HttpAuth::Basic { username: "user".to_owned(), password: Some("pass".to_owned()) }),
+            ..HttpSettings::default()
+        };
+        let basic = request(&reqwest::Client::new(), "http://localhost:8080/actor-tree", &http).build().unwrap();
+        assert_eq!(basic.headers()["Authorization"], "Basic dXNlcjpwYXNz");
+    }
+}
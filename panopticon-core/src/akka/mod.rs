@@ -0,0 +1,6 @@
+//! Akka actor systems, through the endpoints of akka-tools
+
+/// Actor trees, counts, dead letters and cluster sharding stats, with the settings of the source
+pub mod model;
+/// Fetches from the akka-tools endpoints and Kamon's Prometheus reporter
+pub mod client;
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Actor system polled through the endpoints of akka-tools
+#[derive(Clone)]
+pub struct AkkaSettings {
+    /// Endpoint serving the actor tree
+    pub tree_address: String,
+    /// Endpoint serving the actor count
+    pub count_address: String,
+    /// Time in ms the tree has to be fetched
+    pub tree_timeout: u64,
+    /// Time in ms the count, dead letters and mailboxes have to be fetched
+    pub count_timeout: u64,
+    /// Endpoint serving dead letters, if any
+    pub dead_letters_address: Option<String>,
+    /// Endpoint serving actor stats, mailbox sizes included, if any
+    pub mailbox_address: Option<String>,
+    /// Prometheus endpoint of the Kamon reporter, an alternative source of mailbox sizes and dead letters
+    pub kamon_address: Option<String>,
+    /// Headers and authentication sent to all the endpoints
+    pub http: HttpSettings,
+    /// Whether the actor system is an Akka or Pekko one
+    pub flavor: ActorSystemFlavor,
+    /// Path patterns of actors left out of the tree and counts
+    pub excluded_actors: Vec<String>,
+    /// Path patterns of actors to highlight and keep track of across tree reloads
+    pub watched_actors: Vec<String>,
+    /// Number of tree levels to fetch at once, deeper subtrees are fetched when expanded. `None` fetches the whole tree
+    pub tree_depth: Option<u32>,
+    /// When to alert about the actor count
+    pub count_alerts: ActorCountAlertSettings,
+}
+
+/// Thresholds for actor count alerts, a steadily growing count usually means actors are leaking
+#[derive(Clone, Debug)]
+pub struct ActorCountAlertSettings {
+    /// Number of consecutive fetches with a growing actor count
+    pub growth_ticks: u32,
+    /// Maximum number of running actors
+    pub limit: Option<u64>,
+}
+
+/// System actors, and stream actors materialized under `/user` by Akka versions before 2.6
+pub const DEFAULT_EXCLUDED_ACTORS: [&str; 2] = ["/system/*", "/user/StreamSupervisor-*"];
+
+///
+/// Whether an actor path relative to the actor system, e.g. `user/a/b`, matches one of the patterns.
+///
+/// Patterns are matched segment by segment, with `*` standing for any characters within a segment,
+/// and also match all the descendants of the matching actors.
+pub fn is_excluded_actor(path: &str, patterns: &[String]) -> bool {
+    let segments: Vec<&str> = path.split('/').collect();
+    patterns.iter().any(|pattern| {
+        let pattern: Vec<&str> = pattern.trim_start_matches('/').split('/').collect();
+        pattern.len() <= segments.len() && pattern.iter().zip(&segments).all(|(p, s)| matches_segment(p, s))
+    })
+}
+
+/// Whether an actor path relative to the actor system matches the pattern, without its descendants
+pub fn matches_actor_pattern(path: &str, pattern: &str) -> bool {
+    let pattern: Vec<&str> = pattern.trim_start_matches('/').split('/').collect();
+    let segments: Vec<&str> = path.split('/').collect();
+    pattern.len() == segments.len() && pattern.iter().zip(&segments).all(|(p, s)| matches_segment(p, s))
+}
+
+/// Paths of the ancestors of an actor, closest first, e.g. `user/a` and `user` for `user/a/b`
+pub fn ancestor_paths(path: &str) -> impl Iterator<Item = &str> {
+    path.rmatch_indices('/').map(move |(i, _)| &path[..i])
+}
+
+fn matches_segment(pattern: &str, segment: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == segment,
+        Some((prefix, rest)) => segment.strip_prefix(prefix).is_some_and(|tail| {
+            (0..=tail.len()).filter(|i| tail.is_char_boundary(*i)).any(|i| matches_segment(rest, &tail[i..]))
+        }),
+    }
+}
+
+/// Apache Pekko is a fork of Akka 2.6, exposing the same actor tree, with `pekko://` actor paths
+/// Actor systems panopticon tells apart
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ActorSystemFlavor {
+    /// Akka, with `akka://` actor paths
+    Akka,
+    /// Apache Pekko, with `pekko://` actor paths
+    Pekko,
+}
+
+impl ActorSystemFlavor {
+    /// Name of the flavor, as shown
+    pub fn name(&self) -> &'static str {
+        match self {
+            ActorSystemFlavor::Akka => "Akka",
+            ActorSystemFlavor::Pekko => "Pekko",
+        }
+    }
+}
+
+/// Extra headers and authentication sent to akka HTTP endpoints
+#[derive(Clone, Debug, Default)]
+pub struct HttpSettings {
+    /// Headers sent with every request, as names and values
+    pub headers: Vec<(String, String)>,
+    /// Authentication sent with every request, if any
+    pub auth: Option<HttpAuth>,
+    /// Accept invalid (e.g. self-signed) certificates of https endpoints
+    pub skip_tls_verify: bool,
+}
+
+/// Authentication sent to akka HTTP endpoints
+#[derive(Clone, Debug)]
+pub enum HttpAuth {
+    /// Basic authentication
+    Basic {
+        /// User name
+        username: String,
+        /// Password, left empty if not given
+        password: Option<String>,
+    },
+    /// A bearer token
+    Bearer(String),
+}
+
+/// An actor of the tree
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
+pub struct ActorTreeNode {
+    /// Name of the actor, its last path segment
+    pub name: String,
+    /// Id of the parent actor, none for the roots
+    pub parent: Option<usize>,
+    /// Id of the actor, its index in the tree
+    pub id: usize,
+}
+
+/// Actors fetched from the tree endpoint, which may leave out the deeper levels of big trees
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ActorTree {
+    /// All the fetched actors, linked to their parents by id
+    pub nodes: Vec<ActorTreeNode>,
+    /// Paths of actors whose children weren't fetched, with the number of their descendants if reported
+    pub unloaded: HashMap<String, Option<u64>>,
+}
+
+/// Statistics of a single actor, as far as they are reported
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct ActorStats {
+    /// Messages waiting in the mailbox
+    pub mailbox: Option<u64>,
+    /// Messages processed since the actor started
+    pub messages: Option<u64>,
+    /// Average time spent processing a message
+    pub processing_time_ms: Option<f64>,
+}
+
+/// Cumulative sum and count of a histogram reported by Kamon
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct HistogramTotals {
+    /// Sum of the recorded values
+    pub sum: f64,
+    /// Number of recorded values
+    pub count: u64,
+}
+
+impl HistogramTotals {
+    /// Mean of the values recorded since the previous totals, or since the start if there are none or the app restarted
+    pub fn mean_since(&self, previous: Option<&HistogramTotals>) -> Option<f64> {
+        let (sum, count) = match previous {
+            Some(p) if self.count >= p.count => (self.sum - p.sum, self.count - p.count),
+            _ => (self.sum, self.count),
+        };
+        if count == 0 { None } else { Some(sum / count as f64) }
+    }
+}
+
+/// Akka instrumentation metrics scraped from the Prometheus endpoint of Kamon, keyed by relative actor path
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct KamonMetrics {
+    /// When the endpoint was scraped
+    pub timestamp: DateTime<Local>,
+    /// In seconds
+    pub processing_time: HashMap<String, HistogramTotals>,
+    /// Mailbox sizes recorded by actor
+    pub mailbox_size: HashMap<String, HistogramTotals>,
+    /// Dead letters since the app started, `None` if not reported
+    pub dead_letters: Option<u64>,
+    /// `None` if remoting isn't instrumented
+    pub remote: Option<RemoteTotals>,
+}
+
+/// Messages sent to and received from remote actor systems since the app started
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct RemoteTotals {
+    /// Messages received
+    pub messages_in: u64,
+    /// Messages sent
+    pub messages_out: u64,
+    /// Bytes received
+    pub bytes_in: f64,
+    /// Bytes sent
+    pub bytes_out: f64,
+}
+
+/// A message that couldn't be delivered
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct DeadLetter {
+    /// When the message was sent
+    pub timestamp: DateTime<Local>,
+    /// Path of the sender
+    pub sender: String,
+    /// Path of the recipient
+    pub recipient: String,
+    /// The message, as printed
+    pub message: String,
+}
+
+/// Dead letters of the actor system
+#[derive(Deserialize, Serialize)]
+pub struct DeadLetterMetrics {
+    /// When the dead letters were read
+    pub timestamp: DateTime<Local>,
+    /// Dead letters since the start of the actor system
+    pub count: u64,
+    /// Most recent dead letters
+    pub samples: Vec<DeadLetter>,
+}
+
+/// Cluster sharding polled through Akka Management
+#[derive(Clone)]
+pub struct ShardingSettings {
+    /// Akka Management addresses of cluster nodes, e.g. http://10.0.0.1:8558
+    pub nodes: Vec<String>,
+    /// Sharded entity type names
+    pub regions: Vec<String>,
+    /// Time in ms each request has to finish
+    pub timeout: u64,
+    /// Headers and authentication sent to all the nodes
+    pub http: HttpSettings,
+}
+
+/// Shards of a region hosted on a single node
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ShardRegionStats {
+    /// Entity type name of the region
+    pub region: String,
+    /// Node hosting the shards
+    pub node: String,
+    /// Shard ids with their number of entities
+    pub shards: Vec<(String, u64)>,
+}
+
+impl ShardRegionStats {
+    /// Entities of all the shards
+    pub fn entities(&self) -> u64 {
+        self.shards.iter().map(|(_, n)| n).sum()
+    }
+
+    /// The shard with the most entities
+    pub fn hottest_shard(&self) -> Option<&(String, u64)> {
+        self.shards.iter().max_by_key(|(_, n)| *n)
+    }
+}
+
+/// Shards of the regions on all the nodes
+#[derive(Deserialize, Serialize)]
+pub struct ShardingStats {
+    /// When the nodes were read
+    pub timestamp: DateTime<Local>,
+    /// Shards of every region on every node that could be read
+    pub regions: Vec<ShardRegionStats>,
+    /// Regions that couldn't be read on some nodes
+    pub errors: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::akka::model::{ancestor_paths, DEFAULT_EXCLUDED_ACTORS, is_excluded_actor, matches_actor_pattern};
+
+    #[test]
+    fn actor_paths_are_matched_against_exclusion_patterns() {
+        let defaults: Vec<String> = DEFAULT_EXCLUDED_ACTORS.iter().map(|p| p.to_string()).collect();
+        assert!(is_excluded_actor("system/log1-Logging$DefaultLogger", &defaults));
+        assert!(is_excluded_actor("system/Materializers/StreamSupervisor-0/flow-1", &defaults));
+        assert!(is_excluded_actor("user/StreamSupervisor-3", &defaults));
+        assert!(!is_excluded_actor("system", &defaults));
+        assert!(!is_excluded_actor("user/orders", &defaults));
+
+        let patterns = vec!["user/*-worker-*".to_owned()];
+        assert!(is_excluded_actor("user/io-worker-1/child", &patterns));
+        assert!(!is_excluded_actor("user/io-worker", &patterns));
+
+        assert!(matches_actor_pattern("user/singleton", "/user/single*"));
+        assert!(!matches_actor_pattern("user/singleton/child", "/user/single*"));
+    }
+
+    #[test]
+    fn ancestors_of_actors_are_listed_closest_first() {
+        assert_eq!(ancestor_paths("user/a/b").collect::<Vec<_>>(), vec!["user/a", "user"]);
+        assert_eq!(ancestor_paths("user").count(), 0);
+    }
+}
@@ -0,0 +1,74 @@
+use std::process::Command;
+use std::time::Duration;
+
+use crate::cats_effect::dump_parser::{parse_fiber_count, parse_fiber_dump};
+use crate::cats_effect::model::{CatsEffectDumpSource, CatsEffectSettings};
+use crate::command::client::run;
+use crate::error::FetchError;
+use crate::zio::model::{Fiber, FiberCount};
+use crate::zio::zmx::ZMXClient;
+
+/// Takes fiber dumps of a cats-effect 3 app, to be shown like the ones of ZIO apps
+pub struct CatsEffectClient {
+    settings: CatsEffectSettings,
+}
+
+impl CatsEffectClient {
+    /// A client of the app, taking a dump on every poll
+    pub fn new(settings: CatsEffectSettings) -> CatsEffectClient {
+        CatsEffectClient { settings }
+    }
+
+    fn get_dump(&self) -> Result<String, FetchError> {
+        match &self.settings.source {
+            CatsEffectDumpSource::Command(c) => {
+                let mut sh = Command::new("sh");
+                sh.args(["-c", c]);
+                run(sh, Duration::from_millis(self.settings.timeout))
+            }
+            CatsEffectDumpSource::Url(u) => get_dump_async(u, self.settings.timeout),
+        }
+    }
+}
+
+#[tokio::main]
+async fn get_dump_async(url: &str, timeout: u64) -> Result<String, FetchError> {
+    let response = reqwest::Client::builder()
+        .timeout(Duration::from_millis(timeout))
+        .build()?
+        .get(url)
+        .send().await?;
+    if !response.status().is_success() {
+        return Err(FetchError::of_status(response.status(), format!("Request to get fiber dump failed with status {}", response.status())));
+    }
+    Ok(response.text().await?)
+}
+
+impl ZMXClient for CatsEffectClient {
+    fn address(&self) -> String {
+        match &self.settings.source {
+            CatsEffectDumpSource::Command(c) => format!("`{}`", c),
+            CatsEffectDumpSource::Url(u) => u.to_owned(),
+        }
+    }
+
+    fn dump_fibers(&self) -> Result<Vec<Fiber>, FetchError> {
+        let fibers = parse_fiber_dump(&self.get_dump()?);
+        if fibers.is_empty() {
+            return Err(FetchError::from("No cats-effect fiber dump found in the output"));
+        }
+        Ok(fibers)
+    }
+
+    fn count_fibers(&self) -> Result<FiberCount, FetchError> {
+        let count = parse_fiber_count(&self.get_dump()?);
+        if count.total() == 0 {
+            return Err(FetchError::from("No cats-effect fiber dump found in the output"));
+        }
+        Ok(count)
+    }
+
+    fn troubleshooting(&self) -> &'static str {
+        "Make sure it prints a cats-effect fiber dump."
+    }
+}
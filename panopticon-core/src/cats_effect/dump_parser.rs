@@ -0,0 +1,96 @@
+use crate::zio::model::{Fiber, FiberCount, FiberStatus};
+
+///
+/// Parses the fibers of a cats-effect 3 fiber dump, as printed on SIGUSR1 (SIGINFO on macOS), e.g.
+///
+///   ```text
+///   cats.effect.IOFiber@56824a14 WAITING
+///    ├ flatMap @ com.example.Orders$.poll(Orders.scala:42)
+///    ╰ sleep @ com.example.Orders$.poll(Orders.scala:40)
+///
+///   cats.effect.IOFiber@6a2a1d10 RUNNING
+///    ╰ map @ com.example.Server$.handle(Server.scala:18)
+///
+///   Thread[io-compute-0,5,main] (#0): 1 enqueued
+///
+///   Global: enqueued 0, foreign 0, waiting 1
+///   ```
+///
+/// Fibers are flat, cats-effect doesn't report their parents. Their ids are the hashes they're printed with,
+/// which stay the same across dumps. Their statuses are mapped to the ZIO ones, the dump keeps the original.
+/// When given several dumps, e.g. the tail of a log, only the last one is parsed.
+pub fn parse_fiber_dump(output: &str) -> Vec<Fiber> {
+    let mut fibers: Vec<Fiber> = vec![];
+    let mut in_fiber = false;
+    for line in last_dump(output).lines() {
+        if let Some((hash, status)) = parse_header(line) {
+            let id = usize::from_str_radix(hash, 16).unwrap_or(fibers.len() + 1);
+            fibers.push(Fiber { id, parent_id: None, status, dump: line.to_owned() });
+            in_fiber = true;
+        } else if in_fiber && line.starts_with(' ') && !line.trim().is_empty() {
+            // trace lines are indented
+            let fiber = fibers.last_mut().unwrap();
+            fiber.dump.push('\n');
+            fiber.dump.push_str(line);
+        } else {
+            in_fiber = false;
+        }
+    }
+    fibers
+}
+
+/// Counts fibers of the last dump by status, reading only the lines they start with
+pub fn parse_fiber_count(output: &str) -> FiberCount {
+    let mut count = FiberCount::now();
+    for (_, status) in last_dump(output).lines().filter_map(parse_header) {
+        count.add(&status, 1);
+    }
+    count
+}
+
+fn last_dump(output: &str) -> &str {
+    output.rsplit("\nGlobal:").find(|d| d.lines().any(|l| parse_header(l).is_some())).unwrap_or("")
+}
+
+/// Hash and status of the fiber a line like `cats.effect.IOFiber@56824a14 WAITING` starts, if it does
+fn parse_header(line: &str) -> Option<(&str, FiberStatus)> {
+    let (fiber, status) = line.trim().split_once(' ')?;
+    let (_, hash) = fiber.split_once("Fiber@")?;
+    match status.trim() {
+        "RUNNING" | "ACTIVE" | "YIELDING" => Some((hash, FiberStatus::Running)),
+        "WAITING" | "BLOCKED" | "SUSPENDED" => Some((hash, FiberStatus::Suspended)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cats_effect::dump_parser::{parse_fiber_count, parse_fiber_dump};
+    use crate::zio::model::FiberStatus;
+
+    #[test]
+    fn fibers_of_the_last_dump_are_parsed() {
+        let dump = |status: &str| format!("\
+cats.effect.IOFiber@56824a14 WAITING
+ ├ flatMap @ com.example.Orders$.poll(Orders.scala:42)
+ ╰ sleep @ com.example.Orders$.poll(Orders.scala:40)
+
+cats.effect.IOFiber@6a2a1d10 {}
+ ╰ map @ com.example.Server$.handle(Server.scala:18)
+
+Thread[io-compute-0,5,main] (#0): 1 enqueued
+
+Global: enqueued 0, foreign 0, waiting 1
+", status);
+        let output = format!("[INFO] started\n{}\n[INFO] request\n{}", dump("RUNNING"), dump("YIELDING"));
+        let fibers = parse_fiber_dump(&output);
+        assert_eq!(fibers.len(), 2);
+        assert_eq!((fibers[0].id, &fibers[0].status), (0x56824a14, &FiberStatus::Suspended));
+        assert_eq!(fibers[0].dump.lines().count(), 3);
+        assert!(fibers[1].dump.starts_with("cats.effect.IOFiber@6a2a1d10 YIELDING"));
+        assert!(parse_fiber_dump("nothing to see").is_empty());
+
+        let count = parse_fiber_count(&output);
+        assert_eq!((count.running, count.suspended), (1, 1));
+    }
+}
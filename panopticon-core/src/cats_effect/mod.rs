@@ -0,0 +1,8 @@
+//! cats-effect 3 apps, through the fiber dumps they print
+
+/// Settings of the source
+pub mod model;
+/// Takes fiber dumps by running a command or from an HTTP endpoint
+pub mod client;
+/// Parses the fiber dumps into the fibers ZIO apps are shown with
+pub mod dump_parser;
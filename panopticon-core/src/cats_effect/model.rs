@@ -0,0 +1,17 @@
+/// Where fiber dumps of a cats-effect 3 app are taken from
+#[derive(Clone, Debug, PartialEq)]
+pub enum CatsEffectDumpSource {
+    /// A shell command printing a dump, e.g. one signalling the app and reading its stderr
+    Command(String),
+    /// An endpoint of the app serving a dump
+    Url(String),
+}
+
+/// cats-effect 3 app whose fiber dumps are taken on every poll
+#[derive(Clone)]
+pub struct CatsEffectSettings {
+    /// Where the dumps come from
+    pub source: CatsEffectDumpSource,
+    /// Time in ms a dump has to be taken
+    pub timeout: u64,
+}
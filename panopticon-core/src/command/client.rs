@@ -0,0 +1,116 @@
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::command::model::CommandSettings;
+use crate::error::FetchError;
+use crate::json_metrics::client::extract_number;
+
+/// Series name of values extracted without a named group
+const VALUE: &str = "value";
+const DEFAULT_TIMEOUT: u64 = 1000;
+
+/// Runs the command and extracts the values to chart from its output, as series names and values
+pub fn get_values(settings: &CommandSettings) -> Result<Vec<(String, f64)>, FetchError> {
+    let mut command = Command::new("sh");
+    command.args(["-c", &settings.command]);
+    let output = run(command, Duration::from_millis(settings.timeout.unwrap_or(DEFAULT_TIMEOUT)))?;
+    match (&settings.regex, &settings.json_pointer) {
+        (Some(regex), _) => Ok(extract_with_regex(output.trim(), regex)?),
+        (None, Some(pointer)) => {
+            let json: Value = serde_json::from_str(&output).map_err(|e| format!("Output is not JSON: {}", e))?;
+            Ok(extract_number(&json, pointer).map(|v| vec![(VALUE.to_owned(), v)])?)
+        }
+        (None, None) => output.trim().parse()
+            .map(|v| vec![(VALUE.to_owned(), v)])
+            .map_err(|_| FetchError::from(format!("Output is not a number: {}", output.trim()))),
+    }
+}
+
+/// Stdout of the command, killing it if it doesn't finish in time
+pub fn run(mut command: Command, timeout: Duration) -> Result<String, FetchError> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Couldn't run {}: {}", program, e))?;
+    // both pipes are drained while waiting, so that the command doesn't block writing to them
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let started = Instant::now();
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if started.elapsed() > timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(FetchError::timeout(format!("Timed out after {}ms", timeout.as_millis())));
+        }
+        thread::sleep(Duration::from_millis(10));
+    };
+    if !status.success() {
+        let stderr = stderr.join().unwrap_or_default();
+        return Err(FetchError::from(format!("Exited with {}: {}", status, stderr.trim())));
+    }
+    Ok(stdout.join().unwrap_or_default())
+}
+
+fn read_in_background<R: Read + Send + 'static>(pipe: Option<R>) -> JoinHandle<String> {
+    thread::spawn(move || {
+        let mut s = String::new();
+        pipe.map(|mut p| p.read_to_string(&mut s));
+        s
+    })
+}
+
+fn extract_with_regex(output: &str, regex: &Regex) -> Result<Vec<(String, f64)>, String> {
+    let captures = regex.captures(output).ok_or_else(|| format!("{} doesn't match the output", regex))?;
+    let number = |s: &str| s.trim().parse::<f64>().map_err(|_| format!("{} is not a number", s));
+    let names: Vec<&str> = regex.capture_names().flatten().collect();
+    if names.is_empty() {
+        let value = captures.get(1).or_else(|| captures.get(0)).map_or("", |m| m.as_str());
+        return Ok(vec![(VALUE.to_owned(), number(value)?)]);
+    }
+    // groups that didn't participate in the match are left out
+    names.iter()
+        .filter_map(|n| captures.name(n).map(|m| number(m.as_str()).map(|v| (n.to_string(), v))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use std::time::Duration;
+
+    use regex::Regex;
+
+    use crate::command::client::{extract_with_regex, run};
+    use crate::error::FetchError;
+
+    fn sh(command: &str) -> Command {
+        let mut sh = Command::new("sh");
+        sh.args(["-c", command]);
+        sh
+    }
+
+    #[test]
+    fn values_are_extracted_from_command_output() {
+        let output = run(sh("echo 'load average: 0.52, 0.58, 0.59'"), Duration::from_secs(5)).unwrap();
+        let output = output.trim();
+        let load = Regex::new(r"load average: (?P<m1>[\d.]+), (?P<m5>[\d.]+)").unwrap();
+        assert_eq!(extract_with_regex(output, &load), Ok(vec![("m1".to_owned(), 0.52), ("m5".to_owned(), 0.58)]));
+        assert_eq!(extract_with_regex(output, &Regex::new(r", ([\d.]+)$").unwrap()), Ok(vec![("value".to_owned(), 0.59)]));
+        assert!(extract_with_regex(output, &Regex::new("uptime").unwrap()).is_err());
+
+        assert!(run(sh("echo oops >&2; exit 3"), Duration::from_secs(5)).unwrap_err().message.ends_with("oops"));
+        assert_eq!(run(sh("sleep 5"), Duration::from_millis(50)), Err(FetchError::timeout("Timed out after 50ms")));
+    }
+}
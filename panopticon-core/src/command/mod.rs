@@ -0,0 +1,6 @@
+//! Values printed by any command
+
+/// Settings of the source
+pub mod model;
+/// Runs the command and extracts the values from its output
+pub mod client;
@@ -0,0 +1,38 @@
+use regex::Regex;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+
+/// A command run on an interval, whose output is charted
+#[derive(Clone, Debug, Deserialize)]
+pub struct CommandSettings {
+    /// Name the values are charted under
+    pub name: String,
+    /// Run with `sh -c`, so pipes and redirections can be used
+    pub command: String,
+    /// Extracts the values from the output: named groups become series, otherwise the first group or the whole match is charted.
+    /// Compiled as the settings are read
+    #[serde(default, deserialize_with = "compile_regex")]
+    pub regex: Option<Regex>,
+    /// RFC 6901 pointer to the value in the output, for commands printing JSON
+    pub json_pointer: Option<String>,
+    /// Interval in ms, every tick if not set
+    pub interval: Option<u64>,
+    /// Time in ms the command has to finish, a second if not set
+    pub timeout: Option<u64>,
+}
+
+impl CommandSettings {
+    /// Fails if both extractors are given
+    pub fn validate(&self) -> Result<(), String> {
+        if self.regex.is_some() && self.json_pointer.is_some() {
+            return Err(format!("Command {} has both a regex and a json_pointer", self.name));
+        }
+        Ok(())
+    }
+}
+
+fn compile_regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Regex>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|r| Regex::new(&r).map_err(|e| D::Error::custom(format!("Invalid regex {}: {}", r, e))))
+        .transpose()
+}
@@ -0,0 +1,94 @@
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::time::Duration;
+
+use chrono::Local;
+use serde_json::Value;
+
+use crate::docker::model::{DockerSettings, DockerStats};
+use crate::error::FetchError;
+
+/// Resource usage of the container, read from the Docker Engine API over its unix socket
+pub fn get_stats(settings: &DockerSettings) -> Result<DockerStats, FetchError> {
+    // one-shot skips the second sample older daemons wait a second for, it's computed between ticks instead
+    let stats = get(settings, &format!("/containers/{}/stats?stream=false&one-shot=true", settings.container))?;
+    let inspect = get(settings, &format!("/containers/{}/json", settings.container))?;
+    Ok(parse_stats(&stats, &inspect)?)
+}
+
+fn get(settings: &DockerSettings, path: &str) -> Result<Value, FetchError> {
+    let error = |e: std::io::Error| FetchError::from(e).context(&format!("Couldn't reach the Docker daemon at {}", settings.socket));
+    let mut stream = UnixStream::connect(&settings.socket).map_err(error)?;
+    let timeout = Some(Duration::from_millis(settings.timeout));
+    stream.set_read_timeout(timeout).map_err(error)?;
+    stream.set_write_timeout(timeout).map_err(error)?;
+    // HTTP/1.0, so that the response isn't chunked and ends when the connection is closed
+    write!(stream, "GET {} HTTP/1.0\r\nHost: docker\r\n\r\n", path).map_err(error)?;
+    let mut response = vec![];
+    stream.read_to_end(&mut response).map_err(error)?;
+    let response = String::from_utf8_lossy(&response);
+
+    let (head, body) = response.split_once("\r\n\r\n").ok_or("Incomplete response from the Docker daemon")?;
+    let status = head.split(' ').nth(1).unwrap_or_default();
+    let json: Value = serde_json::from_str(body).map_err(|e| format!("Invalid response from the Docker daemon: {}", e))?;
+    if status != "200" {
+        let message = json["message"].as_str().unwrap_or(status);
+        return Err(FetchError::from(format!("Docker request for container {} failed: {}", settings.container, message)));
+    }
+    Ok(json)
+}
+
+fn parse_stats(stats: &Value, inspect: &Value) -> Result<DockerStats, String> {
+    let number = |pointer: &str| stats.pointer(pointer).and_then(|v| v.as_u64());
+    // the page cache can be reclaimed, docker stats leaves it out too (inactive_file on cgroup v2)
+    let cache = number("/memory_stats/stats/inactive_file")
+        .or_else(|| number("/memory_stats/stats/total_inactive_file"))
+        .unwrap_or(0);
+    let networks: Vec<&Value> = stats["networks"].as_object().map_or(vec![], |n| n.values().collect());
+
+    let host_config = &inspect["HostConfig"];
+    let nano_cpus = host_config["NanoCpus"].as_u64().unwrap_or(0);
+    let quota = host_config["CpuQuota"].as_i64().unwrap_or(0);
+    let period = host_config["CpuPeriod"].as_i64().unwrap_or(0);
+    let cpu_limit = if nano_cpus > 0 {
+        Some(nano_cpus as f64 / 1_000_000_000.0)
+    } else if quota > 0 && period > 0 {
+        Some(quota as f64 / period as f64)
+    } else {
+        None
+    };
+
+    Ok(DockerStats {
+        timestamp: Local::now(),
+        cpu_usage: number("/cpu_stats/cpu_usage/total_usage").ok_or("No CPU usage in container stats, is the container running?")?,
+        system_cpu_usage: number("/cpu_stats/system_cpu_usage").unwrap_or(0),
+        online_cpus: number("/cpu_stats/online_cpus").unwrap_or(1) as u32,
+        cpu_limit,
+        memory_usage: number("/memory_stats/usage").unwrap_or(0).saturating_sub(cache),
+        memory_limit: number("/memory_stats/limit").unwrap_or(0),
+        rx_bytes: networks.iter().filter_map(|n| n["rx_bytes"].as_u64()).sum(),
+        tx_bytes: networks.iter().filter_map(|n| n["tx_bytes"].as_u64()).sum(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::docker::client::parse_stats;
+
+    #[test]
+    fn stats_are_parsed() {
+        let stats = json!({
+            "cpu_stats": {"cpu_usage": {"total_usage": 5_000_000_000u64}, "system_cpu_usage": 900_000_000_000u64, "online_cpus": 4},
+            "memory_stats": {"usage": 300_000_000, "limit": 536_870_912, "stats": {"inactive_file": 100_000_000}},
+            "networks": {"eth0": {"rx_bytes": 1000, "tx_bytes": 200}, "eth1": {"rx_bytes": 24, "tx_bytes": 0}}
+        });
+        let inspect = json!({"HostConfig": {"NanoCpus": 0, "CpuQuota": 150_000, "CpuPeriod": 100_000}});
+        let s = parse_stats(&stats, &inspect).unwrap();
+        assert_eq!((s.cpu_usage, s.online_cpus, s.cpu_limit), (5_000_000_000, 4, Some(1.5)));
+        assert_eq!((s.memory_usage, s.memory_limit), (200_000_000, 536_870_912));
+        assert_eq!((s.rx_bytes, s.tx_bytes), (1024, 200));
+        assert!(parse_stats(&json!({"cpu_stats": {}}), &inspect).is_err());
+    }
+}
@@ -0,0 +1,6 @@
+//! Containers, through the Docker Engine API
+
+/// Resource usage of a container, with the settings of the source
+pub mod model;
+/// Reads the stats of the container over the Docker socket
+pub mod client;
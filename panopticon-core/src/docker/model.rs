@@ -0,0 +1,49 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Container polled through the Docker Engine API
+#[derive(Clone)]
+pub struct DockerSettings {
+    /// Path of the Docker daemon socket, e.g. /var/run/docker.sock
+    pub socket: String,
+    /// Name or id of the container
+    pub container: String,
+    /// Time in ms each request has to finish
+    pub timeout: u64,
+}
+
+/// A sample of the container's resource usage, from the stats and inspect endpoints
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct DockerStats {
+    /// When the stats were read
+    pub timestamp: DateTime<Local>,
+    /// CPU time used by the container, in ns, since it started
+    pub cpu_usage: u64,
+    /// CPU time of the whole host, in ns, since it booted
+    pub system_cpu_usage: u64,
+    /// Number of CPUs of the host
+    pub online_cpus: u32,
+    /// Number of CPUs the container is limited to, if it is
+    pub cpu_limit: Option<f64>,
+    /// Memory used, excluding the page cache, like `docker stats` shows it
+    pub memory_usage: u64,
+    /// Memory the container is limited to, or the memory of the host if it isn't
+    pub memory_limit: u64,
+    /// Bytes received, totalled across all the container's networks
+    pub rx_bytes: u64,
+    /// Bytes sent, totalled across all the container's networks
+    pub tx_bytes: u64,
+}
+
+impl DockerStats {
+    /// Share of a single CPU used since the previous sample, in percent, as `docker stats` computes it
+    pub fn cpu_percent_since(&self, previous: &DockerStats) -> Option<f64> {
+        let cpu = self.cpu_usage.checked_sub(previous.cpu_usage)?;
+        let system = self.system_cpu_usage.checked_sub(previous.system_cpu_usage)?;
+        if system == 0 {
+            None
+        } else {
+            Some(cpu as f64 / system as f64 * self.online_cpus as f64 * 100.0)
+        }
+    }
+}
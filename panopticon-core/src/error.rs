@@ -0,0 +1,185 @@
+//! Errors of the clients, telling what kind of failure kept a source from being read
+
+use std::fmt;
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+/// What kind of failure kept a source from being read, for callers to react to each differently
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum FetchErrorKind {
+    /// The source couldn't be reached, or the connection to it was lost
+    Connection,
+    /// The source didn't answer in time
+    Timeout,
+    /// The source turned the credentials down
+    Auth,
+    /// The source answered, but not with what was asked, or failed in another way
+    Other,
+}
+
+///
+/// Why a source couldn't be read, with a message meant for people.
+///
+/// Errors of the HTTP client and of sockets get their kind from the error itself, plain messages are of the `Other` kind.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(from = "RecordedFetchError")]
+pub struct FetchError {
+    /// What kind of failure it is
+    pub kind: FetchErrorKind,
+    /// What went wrong
+    pub message: String,
+}
+
+impl FetchError {
+    /// An error of the given kind
+    pub fn new(kind: FetchErrorKind, message: impl Into<String>) -> FetchError {
+        FetchError { kind, message: message.into() }
+    }
+
+    /// The source couldn't be reached
+    pub fn connection(message: impl Into<String>) -> FetchError {
+        FetchError::new(FetchErrorKind::Connection, message)
+    }
+
+    /// The source didn't answer in time
+    pub fn timeout(message: impl Into<String>) -> FetchError {
+        FetchError::new(FetchErrorKind::Timeout, message)
+    }
+
+    /// The source turned the credentials down
+    pub fn auth(message: impl Into<String>) -> FetchError {
+        FetchError::new(FetchErrorKind::Auth, message)
+    }
+
+    /// A request answered with an unsuccessful status, an authentication failure for 401 and 403
+    pub fn of_status(status: reqwest::StatusCode, message: impl Into<String>) -> FetchError {
+        let kind = match status {
+            reqwest::StatusCode::UNAUTHORIZED | reqwest::StatusCode::FORBIDDEN => FetchErrorKind::Auth,
+            _ => FetchErrorKind::Other,
+        };
+        FetchError::new(kind, message)
+    }
+
+    /// The same error, with the message prefixed by what was being done
+    pub fn context(self, context: &str) -> FetchError {
+        FetchError { kind: self.kind, message: format!("{}: {}", context, self.message) }
+    }
+
+    /// Whether the source may well answer the next time, as it only didn't in time
+    pub fn is_timeout(&self) -> bool {
+        self.kind == FetchErrorKind::Timeout
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for FetchError {}
+
+impl From<String> for FetchError {
+    fn from(message: String) -> FetchError {
+        FetchError::new(FetchErrorKind::Other, message)
+    }
+}
+
+impl From<&str> for FetchError {
+    fn from(message: &str) -> FetchError {
+        FetchError::new(FetchErrorKind::Other, message)
+    }
+}
+
+impl From<reqwest::Error> for FetchError {
+    fn from(e: reqwest::Error) -> FetchError {
+        let kind = if e.is_timeout() {
+            FetchErrorKind::Timeout
+        } else {
+            match e.status() {
+                Some(reqwest::StatusCode::UNAUTHORIZED) | Some(reqwest::StatusCode::FORBIDDEN) => FetchErrorKind::Auth,
+                // connection failures are socket errors wrapped by the HTTP client
+                _ => socket_error_kind(&e).unwrap_or(FetchErrorKind::Other),
+            }
+        };
+        FetchError::new(kind, e.to_string())
+    }
+}
+
+impl From<io::Error> for FetchError {
+    fn from(e: io::Error) -> FetchError {
+        FetchError::new(io_error_kind(e.kind()), e.to_string())
+    }
+}
+
+fn io_error_kind(kind: io::ErrorKind) -> FetchErrorKind {
+    match kind {
+        // reads past a socket timeout fail with WouldBlock on unix
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => FetchErrorKind::Timeout,
+        io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted
+        | io::ErrorKind::NotConnected | io::ErrorKind::AddrNotAvailable | io::ErrorKind::BrokenPipe
+        | io::ErrorKind::UnexpectedEof => FetchErrorKind::Connection,
+        _ => FetchErrorKind::Other,
+    }
+}
+
+/// Kind of the first socket error among the causes of the error, if any
+pub(crate) fn socket_error_kind(e: &dyn std::error::Error) -> Option<FetchErrorKind> {
+    let mut source = e.source();
+    while let Some(cause) = source {
+        if let Some(io) = cause.downcast_ref::<io::Error>() {
+            return Some(io_error_kind(io.kind()));
+        }
+        source = cause.source();
+    }
+    None
+}
+
+/// Recordings made before errors had kinds only have their messages
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RecordedFetchError {
+    Message(String),
+    Typed { kind: FetchErrorKind, message: String },
+}
+
+impl From<RecordedFetchError> for FetchError {
+    fn from(e: RecordedFetchError) -> FetchError {
+        match e {
+            RecordedFetchError::Message(message) => FetchError::from(message),
+            RecordedFetchError::Typed { kind, message } => FetchError::new(kind, message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use crate::error::{FetchError, FetchErrorKind};
+
+    #[test]
+    fn socket_errors_are_told_apart() {
+        let kind = |k: io::ErrorKind| FetchError::from(io::Error::new(k, "boom")).kind;
+        assert_eq!(kind(io::ErrorKind::ConnectionRefused), FetchErrorKind::Connection);
+        assert_eq!(kind(io::ErrorKind::TimedOut), FetchErrorKind::Timeout);
+        assert_eq!(kind(io::ErrorKind::InvalidData), FetchErrorKind::Other);
+        assert_eq!(FetchError::of_status(reqwest::StatusCode::UNAUTHORIZED, "401").kind, FetchErrorKind::Auth);
+        assert_eq!(FetchError::of_status(reqwest::StatusCode::NOT_FOUND, "404").kind, FetchErrorKind::Other);
+    }
+
+    #[test]
+    fn context_keeps_the_kind() {
+        let e = FetchError::timeout("operation timed out").context("Error loading actor count");
+        assert_eq!(e, FetchError::timeout("Error loading actor count: operation timed out"));
+    }
+
+    #[test]
+    fn errors_of_older_recordings_are_read_as_messages() {
+        let e: FetchError = serde_json::from_str("\"Connection refused\"").unwrap();
+        assert_eq!(e, FetchError::from("Connection refused"));
+        let typed = FetchError::auth("wrong password");
+        assert_eq!(serde_json::from_str::<FetchError>(&serde_json::to_string(&typed).unwrap()).unwrap(), typed);
+    }
+}
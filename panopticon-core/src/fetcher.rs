@@ -0,0 +1,1162 @@
+//! Fetching from all the configured sources, on the requests of the TUI
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+
+use crate::actuator;
+use crate::actuator::model::{ActuatorSettings, HealthStatus, JvmThread};
+use crate::akka;
+use crate::akka::model::{ActorStats, ActorTree, AkkaSettings, DeadLetterMetrics, KamonMetrics, ShardRegionStats, ShardingSettings, ShardingStats};
+use crate::docker;
+use crate::error::FetchError;
+use crate::docker::model::{DockerSettings, DockerStats};
+use crate::jfr::client::JfrClient;
+use crate::jfr::model::{JfrMetrics, JfrSettings};
+use crate::jmx::client::JMXClient;
+use crate::jmx::model::{AkkaClusterStatus, AkkaDispatcherMetrics, CassandraMetrics, GcEvent, HikariMetrics, HikariOperation, JMXConnectionSettings, JMXLogger, JvmInfo, JvmOperation, JMXNotification, KafkaMetrics, SlickConfig, SlickMetrics, ThreadPoolsMetrics};
+use crate::cats_effect::client::CatsEffectClient;
+use crate::cats_effect::model::CatsEffectSettings;
+use crate::command;
+use crate::command::model::CommandSettings;
+use crate::json_metrics;
+use crate::json_metrics::model::JsonMetricSettings;
+use crate::k8s::client::K8sClient;
+use crate::k8s::model::{K8sPodSettings, PodMetrics};
+use crate::kafka::client::KafkaLagClient;
+use crate::kafka::model::{KafkaLag, KafkaLagSettings};
+use crate::logs::client::LogTail;
+use crate::logs::model::{LogLines, LogSettings};
+use crate::postgres::client::PostgresClient;
+use crate::postgres::model::{PostgresActivity, PostgresSettings};
+use crate::prometheus;
+use crate::prometheus::model::{MetricSample, PromQLSettings, PrometheusMetrics, PrometheusSettings};
+use crate::proxy;
+use crate::sql::client::SqlClient;
+use crate::sql::model::SqlQuerySettings;
+use crate::proxy::model::{ProxySettings, ProxyStatus};
+use crate::traces;
+use crate::traces::model::{RecentTraces, TracesSettings};
+use crate::rabbitmq;
+use crate::rabbitmq::model::{RabbitMqSettings, RabbitMqStats};
+use crate::redis;
+use crate::redis::model::{RedisInfo, RedisSettings};
+use crate::otlp::model::{OtlpMetrics, OtlpSettings};
+use crate::otlp::receiver::OtlpReceiver;
+use crate::statsd::listener::StatsdListener;
+use crate::statsd::model::{StatsdMetrics, StatsdSettings};
+use crate::zio::model::{Fiber, FiberCount};
+use crate::zio::zmx::{NetworkZMXClient, ZMXClient};
+
+/// What the TUI asks the fetcher for, each request answered with the `FetcherResponse` variant of the same name
+pub enum FetcherRequest {
+    /// All the fibers, with their dumps
+    FiberDump,
+    /// Fibers by status
+    FiberCount,
+    /// Connections of the HikariCP pool
+    HikariMetrics,
+    /// Threads and queue of the Slick executor
+    SlickMetrics,
+    /// Limits of the Slick executor
+    SlickConfig,
+    /// Notifications received since the previous request
+    JMXNotifications,
+    /// Runs an operation of the HikariCP pool
+    HikariOperation(HikariOperation),
+    /// HikariCP pools of the JVM, answered with `DbPools`
+    DbPools,
+    /// Switches to another HikariCP pool, answered with `DbPoolSelected`
+    SelectDbPool(String),
+    /// Fetch metrics of the Kafka consumers of the JVM
+    KafkaMetrics,
+    /// Connections and latency of the Cassandra driver
+    CassandraMetrics,
+    /// Thread pools of the Akka dispatchers
+    DispatcherMetrics,
+    /// Loggers and their levels
+    Loggers,
+    /// Sets the level of a logger
+    SetLoggerLevel(JMXLogger, String),
+    /// Executors and thread pools of the JVM
+    ThreadPoolMetrics,
+    /// Runtime details of the JVM
+    JvmInfo,
+    /// Dumps the heap or runs a garbage collection
+    JvmOperation(JvmOperation),
+    /// Garbage collections since the previous request
+    GcEvents,
+    /// Flight recorder events since the previous request
+    JfrMetrics,
+    /// The actor tree, for the fetch of the given id
+    ActorTree(u64),
+    /// Aborts the fetch of the actor tree of the given id, which isn't answered then
+    CancelActorTree(u64),
+    /// The subtree of the actor at the path, relative to the actor system
+    ActorSubtree(String),
+    /// Number of actors
+    ActorCount,
+    /// Dead letters
+    DeadLetters,
+    /// Shards of the regions on the cluster nodes
+    ShardingStats,
+    /// Stats of the actors, mailbox sizes included
+    ActorStats,
+    /// Akka metrics reported by Kamon
+    KamonMetrics,
+    /// Membership of the Akka Cluster
+    ClusterStatus,
+    /// A scrape of the metrics endpoint
+    PrometheusMetrics,
+    /// Results of the PromQL queries
+    PromQLMetrics,
+    /// Health of the Spring Boot app
+    ActuatorHealth,
+    /// Measurements of the actuator metrics
+    ActuatorMetrics,
+    /// Thread dump of the Spring Boot app
+    ActuatorThreadDump,
+    /// The JSON metric of the given index
+    JsonMetric(usize),
+    /// Values of the command of the given index
+    CommandValues(usize),
+    /// Values of the SQL query of the given index
+    SqlValues(usize),
+    /// Metrics received over statsd since the previous request
+    StatsdMetrics,
+    /// Log lines read since the previous request
+    LogLines,
+    /// Connections and counters of the PostgreSQL server
+    PostgresActivity,
+    /// Lag of the Kafka consumer groups
+    KafkaLag,
+    /// Stats of the Redis server
+    RedisInfo,
+    /// Resource usage of the container
+    DockerStats,
+    /// Resource usage of the pod
+    PodMetrics,
+    /// Stats of the RabbitMQ queues
+    RabbitMqStats,
+    /// Status page of the proxy
+    ProxyStatus,
+    /// Latest traces of the service
+    RecentTraces,
+    /// Metrics received over OTLP since the previous request
+    OtlpMetrics,
+}
+
+/// Answers of the fetcher, reporting failures as `Err` messages
+#[derive(Deserialize, Serialize)]
+pub enum FetcherResponse {
+    /// All the fibers, with their dumps
+    FiberDump(Result<Vec<Fiber>, FetchError>),
+    /// Fibers were counted from whole dumps before, only read from older recordings
+    RegularFiberDump(Result<Vec<Fiber>, FetchError>),
+    /// Fibers by status
+    FiberCount(Result<FiberCount, FetchError>),
+    /// Connections of the HikariCP pool
+    HikariMetrics(Result<HikariMetrics, FetchError>),
+    /// Threads and queue of the Slick executor
+    SlickMetrics(Result<SlickMetrics, FetchError>),
+    /// Limits of the Slick executor
+    SlickConfig(Result<SlickConfig, FetchError>),
+    /// Notifications received since the previous request
+    JMXNotifications(Result<Vec<JMXNotification>, FetchError>),
+    /// Outcome of the HikariCP operation
+    HikariOperation(HikariOperation, Result<(), FetchError>),
+    /// HikariCP pools of the JVM
+    DbPools(Result<Vec<String>, FetchError>),
+    /// Pool whose metrics are read from then on, so that replays switch too
+    DbPoolSelected(String),
+    /// Fetch metrics of the Kafka consumers of the JVM
+    KafkaMetrics(Result<KafkaMetrics, FetchError>),
+    /// Connections and latency of the Cassandra driver
+    CassandraMetrics(Result<CassandraMetrics, FetchError>),
+    /// Thread pools of the Akka dispatchers
+    DispatcherMetrics(Result<AkkaDispatcherMetrics, FetchError>),
+    /// Loggers and their levels
+    Loggers(Result<Vec<JMXLogger>, FetchError>),
+    /// Outcome of setting the logger to the level
+    SetLoggerLevel(JMXLogger, String, Result<(), FetchError>),
+    /// Executors and thread pools of the JVM
+    ThreadPoolMetrics(Result<ThreadPoolsMetrics, FetchError>),
+    /// Runtime details of the JVM
+    JvmInfo(Result<JvmInfo, FetchError>),
+    /// Outcome of the JVM operation
+    JvmOperation(JvmOperation, Result<(), FetchError>),
+    /// Garbage collections since the previous request
+    GcEvents(Result<Vec<GcEvent>, FetchError>),
+    /// Flight recorder events since the previous request
+    JfrMetrics(Result<JfrMetrics, FetchError>),
+    /// The actor tree, with the id of the fetch it answers
+    ActorTree(u64, Result<ActorTree, FetchError>),
+    /// The subtree of the actor at the path
+    ActorSubtree(String, Result<ActorTree, FetchError>),
+    /// Number of actors
+    ActorCount(Result<u64, FetchError>),
+    /// Dead letters
+    DeadLetters(Result<DeadLetterMetrics, FetchError>),
+    /// Shards of the regions on the cluster nodes
+    ShardingStats(Result<ShardingStats, FetchError>),
+    /// Stats by actor path
+    ActorStats(Result<HashMap<String, ActorStats>, FetchError>),
+    /// Akka metrics reported by Kamon
+    KamonMetrics(Result<KamonMetrics, FetchError>),
+    /// Membership of the Akka Cluster
+    ClusterStatus(Result<AkkaClusterStatus, FetchError>),
+    /// Series picked out of a scrape
+    PrometheusMetrics(Result<PrometheusMetrics, FetchError>),
+    /// Results of the PromQL queries, a series per result
+    PromQLMetrics(Result<PrometheusMetrics, FetchError>),
+    /// Health of the Spring Boot app
+    ActuatorHealth(Result<HealthStatus, FetchError>),
+    /// Measurements of the actuator metrics, a series per statistic
+    ActuatorMetrics(Result<PrometheusMetrics, FetchError>),
+    /// Thread dump of the Spring Boot app
+    ActuatorThreadDump(Result<Vec<JvmThread>, FetchError>),
+    /// Value of the JSON metric of the given index
+    JsonMetric(usize, Result<f64, FetchError>),
+    /// Values of the command of the given index
+    CommandValues(usize, Result<Vec<(String, f64)>, FetchError>),
+    /// Values of the SQL query of the given index
+    SqlValues(usize, Result<Vec<(String, f64)>, FetchError>),
+    /// Metrics received over statsd since the previous request
+    StatsdMetrics(StatsdMetrics),
+    /// Log lines read since the previous request
+    LogLines(LogLines),
+    /// Connections and counters of the PostgreSQL server
+    PostgresActivity(Result<PostgresActivity, FetchError>),
+    /// Lag of the Kafka consumer groups
+    KafkaLag(Result<KafkaLag, FetchError>),
+    /// Stats of the Redis server
+    RedisInfo(Result<RedisInfo, FetchError>),
+    /// Resource usage of the container
+    DockerStats(Result<DockerStats, FetchError>),
+    /// Resource usage of the pod
+    PodMetrics(Result<PodMetrics, FetchError>),
+    /// Stats of the RabbitMQ queues
+    RabbitMqStats(Result<RabbitMqStats, FetchError>),
+    /// Status page of the proxy
+    ProxyStatus(Result<ProxyStatus, FetchError>),
+    /// Latest traces of the service
+    RecentTraces(Result<RecentTraces, FetchError>),
+    /// Metrics received over OTLP since the previous request
+    OtlpMetrics(OtlpMetrics),
+    /// Change of the JMX connection
+    JMXConnection(JMXConnectionStatus),
+    /// The fetcher couldn't be set up, nothing will be fetched
+    FatalFailure(FetchError),
+}
+
+impl FetcherResponse {
+    ///
+    /// Source the response came from, with the error if reading from it failed.
+    ///
+    /// Responses to operations picked by the user don't tell whether a source is reachable, so they have no source.
+    pub fn outcome(&self) -> Option<(&'static str, Option<&FetchError>)> {
+        fn error<T>(r: &Result<T, FetchError>) -> Option<&FetchError> {
+            r.as_ref().err()
+        }
+        match self {
+            FetcherResponse::FiberDump(r) => Some(("Fibers", error(r))),
+            FetcherResponse::RegularFiberDump(r) => Some(("Fibers", error(r))),
+            FetcherResponse::FiberCount(r) => Some(("Fibers", error(r))),
+            // fails for pools other than HikariCP
+            FetcherResponse::HikariMetrics(_) => None,
+            FetcherResponse::SlickMetrics(r) => Some(("JMX", error(r))),
+            FetcherResponse::SlickConfig(r) => Some(("JMX", error(r))),
+            FetcherResponse::JMXNotifications(r) => Some(("JMX", error(r))),
+            FetcherResponse::HikariOperation(_, _) => None,
+            FetcherResponse::DbPoolSelected(_) => None,
+            FetcherResponse::DbPools(r) => Some(("JMX", error(r))),
+            FetcherResponse::KafkaMetrics(r) => Some(("JMX", error(r))),
+            FetcherResponse::CassandraMetrics(r) => Some(("JMX", error(r))),
+            FetcherResponse::DispatcherMetrics(r) => Some(("JMX", error(r))),
+            FetcherResponse::Loggers(r) => Some(("JMX", error(r))),
+            FetcherResponse::SetLoggerLevel(_, _, _) => None,
+            FetcherResponse::ThreadPoolMetrics(r) => Some(("JMX", error(r))),
+            FetcherResponse::JvmInfo(r) => Some(("JMX", error(r))),
+            FetcherResponse::JvmOperation(_, _) => None,
+            FetcherResponse::GcEvents(r) => Some(("JMX", error(r))),
+            FetcherResponse::JfrMetrics(r) => Some(("JFR", error(r))),
+            FetcherResponse::ActorTree(_, r) => Some(("Akka", error(r))),
+            FetcherResponse::ActorSubtree(_, r) => Some(("Akka", error(r))),
+            FetcherResponse::ActorCount(r) => Some(("Akka", error(r))),
+            FetcherResponse::DeadLetters(r) => Some(("Akka", error(r))),
+            FetcherResponse::ShardingStats(r) => Some(("Sharding", error(r))),
+            FetcherResponse::ActorStats(r) => Some(("Akka", error(r))),
+            FetcherResponse::KamonMetrics(r) => Some(("Akka", error(r))),
+            FetcherResponse::ClusterStatus(r) => Some(("JMX", error(r))),
+            FetcherResponse::PrometheusMetrics(r) => Some(("Prometheus", error(r))),
+            FetcherResponse::PromQLMetrics(r) => Some(("PromQL", error(r))),
+            FetcherResponse::ActuatorHealth(r) => Some(("Actuator", error(r))),
+            FetcherResponse::ActuatorMetrics(r) => Some(("Actuator", error(r))),
+            FetcherResponse::ActuatorThreadDump(r) => Some(("Actuator", error(r))),
+            FetcherResponse::JsonMetric(_, r) => Some(("JSON", error(r))),
+            FetcherResponse::CommandValues(_, r) => Some(("Commands", error(r))),
+            FetcherResponse::SqlValues(_, r) => Some(("SQL", error(r))),
+            FetcherResponse::StatsdMetrics(m) => Some(("StatsD", m.error.as_ref())),
+            FetcherResponse::LogLines(l) => Some(("Logs", l.error.as_ref())),
+            FetcherResponse::PostgresActivity(r) => Some(("PostgreSQL", error(r))),
+            FetcherResponse::KafkaLag(r) => Some(("Kafka lag", error(r))),
+            FetcherResponse::RedisInfo(r) => Some(("Redis", error(r))),
+            FetcherResponse::DockerStats(r) => Some(("Docker", error(r))),
+            FetcherResponse::PodMetrics(r) => Some(("Kubernetes", error(r))),
+            FetcherResponse::RabbitMqStats(r) => Some(("RabbitMQ", error(r))),
+            FetcherResponse::ProxyStatus(r) => Some(("Proxy", error(r))),
+            FetcherResponse::RecentTraces(r) => Some(("Traces", error(r))),
+            // rejected requests are the sender's problem
+            FetcherResponse::OtlpMetrics(m) => Some(("OTLP", m.error.as_ref())),
+            FetcherResponse::JMXConnection(JMXConnectionStatus::Connected) => Some(("JMX", None)),
+            FetcherResponse::JMXConnection(JMXConnectionStatus::Reconnecting { error, .. }) => Some(("JMX", Some(error))),
+            FetcherResponse::FatalFailure(_) => None,
+        }
+    }
+
+    ///
+    /// The request polled every tick that the response answers, to tell which ones are still in flight.
+    ///
+    /// Responses to requests made once or picked by the user have none.
+    pub fn polled_request(&self) -> Option<FetcherRequest> {
+        let request = match self {
+            FetcherResponse::FiberCount(_) => FetcherRequest::FiberCount,
+            FetcherResponse::HikariMetrics(_) => FetcherRequest::HikariMetrics,
+            FetcherResponse::SlickMetrics(_) => FetcherRequest::SlickMetrics,
+            FetcherResponse::JMXNotifications(_) => FetcherRequest::JMXNotifications,
+            FetcherResponse::KafkaMetrics(_) => FetcherRequest::KafkaMetrics,
+            FetcherResponse::CassandraMetrics(_) => FetcherRequest::CassandraMetrics,
+            FetcherResponse::DispatcherMetrics(_) => FetcherRequest::DispatcherMetrics,
+            FetcherResponse::ThreadPoolMetrics(_) => FetcherRequest::ThreadPoolMetrics,
+            FetcherResponse::GcEvents(_) => FetcherRequest::GcEvents,
+            FetcherResponse::JfrMetrics(_) => FetcherRequest::JfrMetrics,
+            FetcherResponse::ActorCount(_) => FetcherRequest::ActorCount,
+            FetcherResponse::DeadLetters(_) => FetcherRequest::DeadLetters,
+            FetcherResponse::ShardingStats(_) => FetcherRequest::ShardingStats,
+            FetcherResponse::ActorStats(_) => FetcherRequest::ActorStats,
+            FetcherResponse::KamonMetrics(_) => FetcherRequest::KamonMetrics,
+            FetcherResponse::ClusterStatus(_) => FetcherRequest::ClusterStatus,
+            FetcherResponse::PrometheusMetrics(_) => FetcherRequest::PrometheusMetrics,
+            FetcherResponse::PromQLMetrics(_) => FetcherRequest::PromQLMetrics,
+            FetcherResponse::ActuatorHealth(_) => FetcherRequest::ActuatorHealth,
+            FetcherResponse::ActuatorMetrics(_) => FetcherRequest::ActuatorMetrics,
+            FetcherResponse::JsonMetric(i, _) => FetcherRequest::JsonMetric(*i),
+            FetcherResponse::CommandValues(i, _) => FetcherRequest::CommandValues(*i),
+            FetcherResponse::SqlValues(i, _) => FetcherRequest::SqlValues(*i),
+            FetcherResponse::StatsdMetrics(_) => FetcherRequest::StatsdMetrics,
+            FetcherResponse::LogLines(_) => FetcherRequest::LogLines,
+            FetcherResponse::PostgresActivity(_) => FetcherRequest::PostgresActivity,
+            FetcherResponse::KafkaLag(_) => FetcherRequest::KafkaLag,
+            FetcherResponse::RedisInfo(_) => FetcherRequest::RedisInfo,
+            FetcherResponse::DockerStats(_) => FetcherRequest::DockerStats,
+            FetcherResponse::PodMetrics(_) => FetcherRequest::PodMetrics,
+            FetcherResponse::RabbitMqStats(_) => FetcherRequest::RabbitMqStats,
+            FetcherResponse::ProxyStatus(_) => FetcherRequest::ProxyStatus,
+            FetcherResponse::RecentTraces(_) => FetcherRequest::RecentTraces,
+            FetcherResponse::OtlpMetrics(_) => FetcherRequest::OtlpMetrics,
+            _ => return None,
+        };
+        Some(request)
+    }
+}
+
+/// JMX connection changes reported alongside fetcher responses
+#[derive(Deserialize, Serialize)]
+pub enum JMXConnectionStatus {
+    /// The connection was re-established
+    Connected,
+    /// The connection broke, and is being re-established
+    Reconnecting {
+        /// Number of the next attempt
+        attempt: u32,
+        /// Why the connection broke, or why the previous attempt failed
+        error: FetchError,
+    },
+}
+
+///
+/// Settings of the sources to monitor, those left out aren't, e.g. zio-zmx fibers alone with
+///
+/// ```
+/// use panopticon_core::fetcher::Sources;
+///
+/// let sources = Sources { zio_zmx: Some("localhost:6789".to_owned()), ..Sources::default() };
+/// ```
+#[derive(Clone, Default)]
+pub struct Sources {
+    /// Address of a zio-zmx server
+    pub zio_zmx: Option<String>,
+    /// JVM polled over JMX
+    pub jmx: Option<JMXConnectionSettings>,
+    /// Actor system polled through akka-tools
+    pub akka: Option<AkkaSettings>,
+    /// Cluster sharding polled through Akka Management
+    pub sharding: Option<ShardingSettings>,
+    /// Flight recordings
+    pub jfr: Option<JfrSettings>,
+    /// Metrics endpoint scraped
+    pub prometheus: Option<PrometheusSettings>,
+    /// Prometheus server queried
+    pub promql: Option<PromQLSettings>,
+    /// Spring Boot app polled through its actuator
+    pub actuator: Option<ActuatorSettings>,
+    /// Listener of the statsd packets
+    pub statsd: Option<StatsdSettings>,
+    /// Log file or stream tailed
+    pub logs: Option<LogSettings>,
+    /// PostgreSQL server polled
+    pub postgres: Option<PostgresSettings>,
+    /// Kafka consumer groups whose lag is computed
+    pub kafka_lag: Option<KafkaLagSettings>,
+    /// Redis server polled
+    pub redis: Option<RedisSettings>,
+    /// Container polled through the Docker Engine API
+    pub docker: Option<DockerSettings>,
+    /// Pod polled through the Kubernetes metrics API
+    pub k8s_pod: Option<K8sPodSettings>,
+    /// RabbitMQ queues polled through the management API
+    pub rabbitmq: Option<RabbitMqSettings>,
+    /// HAProxy or nginx status page polled
+    pub proxy: Option<ProxySettings>,
+    /// Service whose latest traces are listed
+    pub traces: Option<TracesSettings>,
+    /// Receiver of the metrics pushed over OTLP
+    pub otlp: Option<OtlpSettings>,
+    /// Used unless `zio_zmx` is given, for the same tab
+    pub cats_effect: Option<CatsEffectSettings>,
+    /// Numbers polled from JSON endpoints
+    pub json_metrics: Vec<JsonMetricSettings>,
+    /// Commands whose output is charted
+    pub commands: Vec<CommandSettings>,
+    /// SQL queries whose results are charted
+    pub sql_queries: Vec<SqlQuerySettings>,
+}
+
+impl Sources {
+    /// Whether none of the sources is given, leaving nothing to monitor
+    pub fn is_empty(&self) -> bool {
+        // taken apart, so that new sources can't be left out
+        let Sources {
+            zio_zmx, jmx, akka, sharding, jfr, prometheus, promql, actuator, statsd, logs, postgres, kafka_lag,
+            redis, docker, k8s_pod, rabbitmq, proxy, traces, otlp, cats_effect, json_metrics, commands, sql_queries,
+        } = self;
+        zio_zmx.is_none() && jmx.is_none() && akka.is_none() && sharding.is_none() && jfr.is_none() && prometheus.is_none()
+            && promql.is_none() && actuator.is_none() && statsd.is_none() && logs.is_none() && postgres.is_none()
+            && kafka_lag.is_none() && redis.is_none() && docker.is_none() && k8s_pod.is_none() && rabbitmq.is_none()
+            && proxy.is_none() && traces.is_none() && otlp.is_none() && cats_effect.is_none() && json_metrics.is_empty()
+            && commands.is_empty() && sql_queries.is_empty()
+    }
+}
+
+/// Clients of all the configured sources, answering every `FetcherRequest`
+pub struct Fetcher {
+    // fiber dumps of a zio-zmx server or cats-effect app
+    zmx_client: Option<Box<dyn ZMXClient>>,
+    // JMX client, `None` while the connection is being re-established
+    jmx: Option<JMXClient>,
+    jmx_settings: Option<JMXConnectionSettings>,
+    // number and time of the next reconnection attempt
+    jmx_reconnect: Option<(u32, Instant)>,
+    jmx_status_change: Option<JMXConnectionStatus>,
+    // JMX domain of the Cassandra driver metrics
+    cassandra_domain: Option<String>,
+    // object name pattern of the Akka dispatcher MBeans
+    akka_dispatchers: Option<String>,
+    // object name patterns of the thread pool MBeans
+    thread_pools: Vec<String>,
+    // actor system settings
+    akka_settings: Option<AkkaSettings>,
+    // cluster sharding settings
+    sharding_settings: Option<ShardingSettings>,
+    // flight recording reader, or why it couldn't be set up
+    jfr: Option<Result<JfrClient, FetchError>>,
+    // metrics endpoint settings
+    prometheus_settings: Option<PrometheusSettings>,
+    // Prometheus server settings
+    promql_settings: Option<PromQLSettings>,
+    // Spring Boot actuator settings
+    actuator_settings: Option<ActuatorSettings>,
+    // listener of the statsd packets, or why it couldn't be set up
+    statsd: Option<Result<StatsdListener, FetchError>>,
+    // tail of the log file or stream, or why it couldn't be set up
+    logs: Option<Result<LogTail, FetchError>>,
+    // PostgreSQL client
+    postgres: Option<PostgresClient>,
+    // Kafka lag client
+    kafka_lag: Option<KafkaLagClient>,
+    // Redis server settings
+    redis_settings: Option<RedisSettings>,
+    // container settings
+    docker_settings: Option<DockerSettings>,
+    // Kubernetes client, or why it couldn't be set up
+    k8s: Option<Result<K8sClient, FetchError>>,
+    // RabbitMQ settings
+    rabbitmq_settings: Option<RabbitMqSettings>,
+    // proxy settings
+    proxy_settings: Option<ProxySettings>,
+    // tracing backend settings
+    traces_settings: Option<TracesSettings>,
+    // receiver of the metrics pushed over OTLP, or why it couldn't be set up
+    otlp: Option<Result<OtlpReceiver, FetchError>>,
+    // numbers polled from JSON endpoints
+    json_metrics: Vec<JsonMetricSettings>,
+    // commands whose output is charted
+    commands: Vec<CommandSettings>,
+    // clients of the SQL queries whose results are charted
+    sql_queries: Vec<SqlClient>,
+}
+
+impl Fetcher {
+    const MIN_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+    const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(30);
+    const JSON_METRIC_TIMEOUT: u64 = 1000;
+
+    ///
+    /// Clients of the sources. Those that can't be set up, e.g. a port to listen on that's taken, fail their requests
+    /// instead, and an unreachable JVM is reconnected to like a lost one, the first status change telling why
+    pub fn new(sources: Sources) -> Fetcher {
+        let Sources {
+            zio_zmx: zio_zmx_addr, jmx, akka, sharding, jfr, prometheus, promql, actuator, statsd, logs, postgres, kafka_lag,
+            redis, docker, k8s_pod, rabbitmq, proxy, traces, otlp, cats_effect, json_metrics, commands, sql_queries,
+        } = sources;
+        let cassandra_domain = jmx.as_ref().and_then(|j| j.cassandra_domain.clone());
+        let akka_dispatchers = jmx.as_ref().and_then(|j| j.akka_dispatchers.clone());
+        let thread_pools = jmx.as_ref().map_or(vec![], |j| j.thread_pools.clone());
+        let (jmx_client, jmx_reconnect, jmx_status_change) = match jmx.as_ref().map(JMXClient::connect) {
+            None => (None, None, None),
+            Some(Ok(client)) => (Some(client), None, None),
+            Some(Err(error)) => (
+                None,
+                Some((1, Instant::now() + Fetcher::MIN_RECONNECT_BACKOFF)),
+                Some(JMXConnectionStatus::Reconnecting { attempt: 1, error }),
+            ),
+        };
+
+        Fetcher {
+            zmx_client: match (zio_zmx_addr, cats_effect) {
+                (Some(x), _) => Some(Box::new(NetworkZMXClient::new(x)) as Box<dyn ZMXClient>),
+                (None, Some(settings)) => Some(Box::new(CatsEffectClient::new(settings)) as Box<dyn ZMXClient>),
+                (None, None) => None,
+            },
+            jmx: jmx_client,
+            jmx_settings: jmx,
+            jmx_reconnect,
+            jmx_status_change,
+            cassandra_domain,
+            akka_dispatchers,
+            thread_pools,
+            akka_settings: akka,
+            sharding_settings: sharding,
+            jfr: jfr.map(JfrClient::new),
+            prometheus_settings: prometheus,
+            promql_settings: promql,
+            actuator_settings: actuator,
+            statsd: statsd.map(|s| StatsdListener::bind(&s.address).map_err(FetchError::from)),
+            logs: logs.map(|l| LogTail::start(&l.source)),
+            postgres: postgres.map(PostgresClient::new),
+            kafka_lag: kafka_lag.map(KafkaLagClient::new),
+            redis_settings: redis,
+            docker_settings: docker,
+            k8s: k8s_pod.map(K8sClient::new),
+            rabbitmq_settings: rabbitmq,
+            proxy_settings: proxy,
+            traces_settings: traces,
+            otlp: otlp.map(|o| OtlpReceiver::bind(&o.address).map_err(FetchError::from)),
+            json_metrics,
+            commands,
+            sql_queries: sql_queries.into_iter().map(SqlClient::new).collect(),
+        }
+    }
+
+    /// Reads fibers through the given client instead, e.g. a stub or a client of another fiber dump format
+    pub fn with_zmx_client(mut self, client: Box<dyn ZMXClient>) -> Fetcher {
+        self.zmx_client = Some(client);
+        self
+    }
+
+    /// All the fibers, with their dumps
+    pub fn dump_fibers(&self) -> Result<Vec<Fiber>, FetchError> {
+        let zmx = self.zmx_client.as_ref().ok_or_else(|| not_configured("zio-zmx"))?;
+        zmx.dump_fibers()
+            .map_err(
+                |e| e.context(&format!(
+                    "Couldn't get fiber dump from {}. {} Underlying error",
+                    zmx.address(),
+                    zmx.troubleshooting()
+                ))
+            )
+    }
+
+    /// Fibers by status
+    pub fn count_fibers(&self) -> Result<FiberCount, FetchError> {
+        let zmx = self.zmx_client.as_ref().ok_or_else(|| not_configured("zio-zmx"))?;
+        zmx.count_fibers()
+            .map_err(
+                |e| e.context(&format!(
+                    "Couldn't count fibers from {}. {} Underlying error",
+                    zmx.address(),
+                    zmx.troubleshooting()
+                ))
+            )
+    }
+
+    /// Connections of the HikariCP pool
+    pub fn get_hikari_metrics(&mut self) -> Result<HikariMetrics, FetchError> {
+        self.with_jmx(|jmx| jmx.get_hikari_metrics()).map_err(Fetcher::format_slick_error)
+    }
+
+    /// Threads and queue of the Slick executor
+    pub fn get_slick_metrics(&mut self) -> Result<SlickMetrics, FetchError> {
+        self.with_jmx(|jmx| jmx.get_slick_metrics()).map_err(Fetcher::format_slick_error)
+    }
+
+    /// Limits of the Slick executor
+    pub fn get_slick_config(&mut self) -> Result<SlickConfig, FetchError> {
+        self.with_jmx(|jmx| jmx.get_slick_config()).map_err(Fetcher::format_slick_error)
+    }
+
+    /// JMX notifications received since the previous call
+    pub fn get_jmx_notifications(&mut self) -> Result<Vec<JMXNotification>, FetchError> {
+        self.with_jmx(|jmx| jmx.get_notifications())
+            .map_err(|e| e.context("Error pulling JMX notifications"))
+    }
+
+    /// Runs an operation of the HikariCP pool
+    pub fn invoke_hikari_operation(&mut self, operation: HikariOperation) -> Result<(), FetchError> {
+        self.with_jmx(|jmx| jmx.invoke_hikari_operation(operation))
+    }
+
+    /// HikariCP pools of the JVM
+    pub fn discover_db_pools(&mut self) -> Result<Vec<String>, FetchError> {
+        self.with_jmx(|jmx| jmx.discover_db_pools())
+            .map_err(|e| e.context("Error discovering DB pools"))
+    }
+
+    /// Reads the HikariCP pool of the given name from then on, reconnections included
+    pub fn select_db_pool(&mut self, name: String) {
+        // remembered for reconnections
+        if let Some(settings) = self.jmx_settings.as_mut() {
+            settings.db_pool_name = Some(name.clone());
+        }
+        if let Some(jmx) = self.jmx.as_mut() {
+            jmx.set_db_pool_name(name)
+        }
+    }
+
+    /// Fetch metrics of the Kafka consumers of the JVM
+    pub fn get_kafka_metrics(&mut self) -> Result<KafkaMetrics, FetchError> {
+        self.with_jmx(|jmx| jmx.get_kafka_metrics())
+            .map_err(|e| e.context("Error loading kafka consumer metrics"))
+    }
+
+    /// Connections and latency of the Cassandra driver
+    pub fn get_cassandra_metrics(&mut self) -> Result<CassandraMetrics, FetchError> {
+        let domain = self.cassandra_domain.clone().ok_or_else(|| not_configured("Cassandra"))?;
+        self.with_jmx(|jmx| jmx.get_cassandra_metrics(&domain))
+            .map_err(|e| e.context(&format!(
+                "Error loading DataStax driver metrics from {} domain. Is JMX reporting enabled for the driver? Underlying error",
+                domain
+            )))
+    }
+
+    /// Thread pools of the Akka dispatchers
+    pub fn get_dispatcher_metrics(&mut self) -> Result<AkkaDispatcherMetrics, FetchError> {
+        let pattern = self.akka_dispatchers.clone().ok_or_else(|| not_configured("Akka dispatchers"))?;
+        self.with_jmx(|jmx| jmx.get_dispatcher_metrics(&pattern))
+            .map_err(|e| e.context(&format!("Error loading akka dispatcher metrics from {}", pattern)))
+    }
+
+    /// Loggers and their levels
+    pub fn get_loggers(&mut self) -> Result<Vec<JMXLogger>, FetchError> {
+        self.with_jmx(|jmx| jmx.get_loggers())
+            .map_err(|e| e.context("Error loading loggers"))
+    }
+
+    /// Sets the level of a logger
+    pub fn set_logger_level(&mut self, logger: &JMXLogger, level: &str) -> Result<(), FetchError> {
+        self.with_jmx(|jmx| jmx.set_logger_level(logger, level))
+    }
+
+    /// Executors and thread pools of the JVM
+    pub fn get_thread_pool_metrics(&mut self) -> Result<ThreadPoolsMetrics, FetchError> {
+        let patterns = self.thread_pools.clone();
+        self.with_jmx(|jmx| jmx.get_thread_pool_metrics(&patterns))
+            .map_err(|e| e.context("Error loading thread pool metrics"))
+    }
+
+    /// Runtime details of the JVM
+    pub fn get_jvm_info(&mut self) -> Result<JvmInfo, FetchError> {
+        self.with_jmx(|jmx| jmx.get_jvm_info())
+            .map_err(|e| e.context("Error loading JVM runtime info"))
+    }
+
+    /// Garbage collections since the previous call
+    pub fn get_gc_events(&mut self) -> Result<Vec<GcEvent>, FetchError> {
+        self.with_jmx(|jmx| jmx.get_gc_events())
+            .map_err(|e| e.context("Error loading GC info"))
+    }
+
+    /// Membership of the Akka Cluster
+    pub fn get_cluster_status(&mut self) -> Result<AkkaClusterStatus, FetchError> {
+        self.with_jmx(|jmx| jmx.get_cluster_status())
+    }
+
+    /// Dumps the heap or runs a garbage collection
+    pub fn invoke_jvm_operation(&mut self, operation: &JvmOperation) -> Result<(), FetchError> {
+        self.with_jmx(|jmx| jmx.invoke_jvm_operation(operation))
+    }
+
+    /// Returns the change of JMX connection status caused by the last request, if any
+    pub fn take_jmx_status_change(&mut self) -> Option<JMXConnectionStatus> {
+        self.jmx_status_change.take()
+    }
+
+    ///
+    /// Runs a JMX call, keeping track of the connection.
+    ///
+    /// A failed call is followed by a ping, and if that fails too the connection is considered broken:
+    /// the client is dropped and re-established by subsequent calls, with exponential backoff.
+    fn with_jmx<T, F>(&mut self, f: F) -> Result<T, FetchError>
+        where F: FnOnce(&JMXClient) -> Result<T, FetchError>
+    {
+        if self.jmx.is_none() {
+            self.reconnect_jmx()?;
+        }
+        let jmx = self.jmx.as_ref().unwrap();
+        let result = f(jmx);
+        if let Err(e) = &result {
+            if jmx.ping().is_err() {
+                self.jmx = None;
+                self.jmx_reconnect = Some((1, Instant::now() + Fetcher::MIN_RECONNECT_BACKOFF));
+                self.jmx_status_change = Some(JMXConnectionStatus::Reconnecting { attempt: 1, error: e.clone() });
+            }
+        }
+        result
+    }
+
+    fn reconnect_jmx(&mut self) -> Result<(), FetchError> {
+        let (attempt, next_attempt) = self.jmx_reconnect.unwrap_or((1, Instant::now()));
+        if Instant::now() < next_attempt {
+            return Err(FetchError::connection("JMX connection lost, waiting to reconnect"));
+        }
+        let settings = self.jmx_settings.as_ref().ok_or_else(|| not_configured("JMX"))?;
+        match JMXClient::connect(settings) {
+            Ok(client) => {
+                self.jmx = Some(client);
+                self.jmx_reconnect = None;
+                self.jmx_status_change = Some(JMXConnectionStatus::Connected);
+                Ok(())
+            }
+            Err(e) => {
+                self.jmx_reconnect = Some((attempt + 1, Instant::now() + Fetcher::reconnect_backoff(attempt)));
+                self.jmx_status_change = Some(JMXConnectionStatus::Reconnecting { attempt: attempt + 1, error: e.clone() });
+                Err(e)
+            }
+        }
+    }
+
+    /// Wait after the failed attempt of the given number, doubling from one attempt to the next
+    fn reconnect_backoff(attempt: u32) -> Duration {
+        (Fetcher::MIN_RECONNECT_BACKOFF * 2u32.pow(attempt.min(5))).min(Fetcher::MAX_RECONNECT_BACKOFF)
+    }
+
+    ///
+    /// Fetch of the actor tree, or of the subtree of the actor at `path`.
+    ///
+    /// Assembling the tree of a big actor system takes seconds, so it's fetched apart, on a runtime of the caller's,
+    /// without holding up other metrics and so that it can be aborted
+    pub fn actor_tree_fetch(&self, path: Option<String>) -> impl Future<Output = Result<ActorTree, FetchError>> {
+        let settings = self.akka_settings.clone().ok_or_else(|| not_configured("Akka"));
+        async move {
+            let s = settings?;
+            akka::client::get_actors(&s.tree_address, s.tree_timeout, path.as_deref(), s.tree_depth, &s.http).await
+                .map_err(|e| e.context(&format!("Error loading {} actor tree", s.flavor.name())))
+        }
+    }
+
+    /// Number of actors
+    pub fn get_actor_count(&self) -> Result<u64, FetchError> {
+        let s = self.akka_settings.as_ref().ok_or_else(|| not_configured("Akka"))?;
+        akka::client::get_actor_count(&s.count_address, s.count_timeout, &s.http)
+            .map_err(|e| e.context(&format!("Error loading {} actor count", s.flavor.name())))
+    }
+
+    /// Dead letters
+    pub fn get_dead_letters(&self) -> Result<DeadLetterMetrics, FetchError> {
+        let s = self.akka_settings.as_ref().ok_or_else(|| not_configured("Akka"))?;
+        let address = s.dead_letters_address.as_ref().ok_or_else(|| not_configured("Akka dead letters"))?;
+        akka::client::get_dead_letters(address, s.count_timeout, &s.http)
+            .map_err(|e| e.context(&format!("Error loading {} dead letters", s.flavor.name())))
+    }
+
+    /// Stats of the actors, mailbox sizes included
+    pub fn get_actor_stats(&self) -> Result<HashMap<String, ActorStats>, FetchError> {
+        let s = self.akka_settings.as_ref().ok_or_else(|| not_configured("Akka"))?;
+        let address = s.mailbox_address.as_ref().ok_or_else(|| not_configured("Akka mailboxes"))?;
+        akka::client::get_actor_stats(address, s.count_timeout, &s.http)
+            .map_err(|e| e.context(&format!("Error loading {} actor stats", s.flavor.name())))
+    }
+
+    /// Akka metrics reported by Kamon
+    pub fn get_kamon_metrics(&self) -> Result<KamonMetrics, FetchError> {
+        let s = self.akka_settings.as_ref().ok_or_else(|| not_configured("Akka"))?;
+        let address = s.kamon_address.as_ref().ok_or_else(|| not_configured("Kamon"))?;
+        akka::client::get_kamon_metrics(address, s.count_timeout, &s.http)
+            .map_err(|e| e.context(&format!("Error loading {} metrics from Kamon", s.flavor.name())))
+    }
+
+    /// Queries every region on every node, failing only if none of them could be read
+    pub fn get_sharding_stats(&self) -> Result<ShardingStats, FetchError> {
+        let s = self.sharding_settings.as_ref().ok_or_else(|| not_configured("Cluster sharding"))?;
+        let mut regions = vec![];
+        let mut errors = vec![];
+        let mut error_kind = None;
+        for node in &s.nodes {
+            let label = node.trim_start_matches("http://").trim_start_matches("https://").trim_end_matches('/');
+            for region in &s.regions {
+                match akka::client::get_shard_region_stats(node, region, s.timeout, &s.http) {
+                    Ok(shards) => regions.push(ShardRegionStats { region: region.to_owned(), node: label.to_owned(), shards }),
+                    Err(e) => {
+                        error_kind = Some(e.kind);
+                        errors.push(format!("{} on {}: {}", region, label, e));
+                    }
+                }
+            }
+        }
+        match error_kind {
+            // nodes of a cluster usually fail alike, e.g. all unreachable, so the last failure tells the kind
+            Some(kind) if regions.is_empty() => Err(FetchError::new(kind, format!("Error loading sharding stats: {}", errors.join(", ")))),
+            _ => Ok(ShardingStats { timestamp: Local::now(), regions, errors }),
+        }
+    }
+
+    /// Series picked out of a scrape of the metrics endpoint
+    pub fn get_prometheus_metrics(&self) -> Result<PrometheusMetrics, FetchError> {
+        let s = self.prometheus_settings.as_ref().ok_or_else(|| not_configured("Prometheus"))?;
+        prometheus::client::get_metrics(&s.address, s.timeout, &s.selectors)
+            .map_err(|e| e.context("Error scraping Prometheus metrics"))
+    }
+
+    /// Runs every query, with the series of each sample named by their labels
+    pub fn get_promql_metrics(&self) -> Result<PrometheusMetrics, FetchError> {
+        let s = self.promql_settings.as_ref().ok_or_else(|| not_configured("PromQL"))?;
+        let mut samples = vec![];
+        let mut errors = vec![];
+        let mut last_error = None;
+        for (i, query) in s.queries.iter().enumerate() {
+            // a bad query doesn't keep the others from being charted
+            match prometheus::client::query(&s.address, query, s.timeout) {
+                Ok(result) =>
+                    samples.extend(result.into_iter().map(|(series, value)| MetricSample { selector: i, series, value, counter: false })),
+                Err(e) => {
+                    errors.push(format!("{}: {}", query, e));
+                    last_error = Some(e);
+                }
+            }
+        }
+        match last_error {
+            Some(e) if errors.len() == s.queries.len() => Err(e.context("Error running PromQL queries")),
+            _ => Ok(PrometheusMetrics { timestamp: Local::now(), samples, metrics: vec![], errors }),
+        }
+    }
+
+    /// Health of the Spring Boot app
+    pub fn get_actuator_health(&self) -> Result<HealthStatus, FetchError> {
+        let s = self.actuator_settings.as_ref().ok_or_else(|| not_configured("Actuator"))?;
+        actuator::client::get_health(&s.address, s.timeout)
+            .map_err(|e| e.context("Error loading actuator health"))
+    }
+
+    /// Reads every selected metric, with a series per statistic. Cumulative statistics are flagged as counters
+    pub fn get_actuator_metrics(&self) -> Result<PrometheusMetrics, FetchError> {
+        let s = self.actuator_settings.as_ref().ok_or_else(|| not_configured("Actuator"))?;
+        let mut samples = vec![];
+        let mut errors = vec![];
+        let mut last_error = None;
+        for (i, metric) in s.metrics.iter().enumerate() {
+            // a missing or renamed metric doesn't keep the others from being charted
+            match actuator::client::get_metric(&s.address, metric, s.timeout) {
+                Ok(measurements) => samples.extend(measurements.into_iter().map(|(statistic, value)| MetricSample {
+                    selector: i,
+                    counter: statistic == "COUNT" || statistic.starts_with("TOTAL"),
+                    series: statistic.to_lowercase(),
+                    value,
+                })),
+                Err(e) => {
+                    errors.push(format!("{}: {}", metric, e));
+                    last_error = Some(e);
+                }
+            }
+        }
+        match last_error {
+            Some(e) if errors.len() == s.metrics.len() => Err(e.context("Error loading actuator metrics")),
+            _ => Ok(PrometheusMetrics { timestamp: Local::now(), samples, metrics: vec![], errors }),
+        }
+    }
+
+    /// Thread dump of the Spring Boot app
+    pub fn get_actuator_thread_dump(&self) -> Result<Vec<JvmThread>, FetchError> {
+        let s = self.actuator_settings.as_ref().ok_or_else(|| not_configured("Actuator"))?;
+        actuator::client::get_thread_dump(&s.address, s.timeout)
+            .map_err(|e| e.context("Error loading thread dump"))
+    }
+
+    /// Value of the JSON metric of the given index
+    pub fn get_json_metric(&self, i: usize) -> Result<f64, FetchError> {
+        let m = self.json_metrics.get(i).ok_or_else(|| not_configured(&format!("JSON metric {}", i)))?;
+        json_metrics::client::get_value(&m.url, &m.json_pointer, Fetcher::JSON_METRIC_TIMEOUT)
+    }
+
+    /// Values of the command of the given index
+    pub fn get_command_values(&self, i: usize) -> Result<Vec<(String, f64)>, FetchError> {
+        let c = self.commands.get(i).ok_or_else(|| not_configured(&format!("Command {}", i)))?;
+        command::client::get_values(c)
+    }
+
+    /// Values of the SQL query of the given index
+    pub fn get_sql_values(&mut self, i: usize) -> Result<Vec<(String, f64)>, FetchError> {
+        self.sql_queries.get_mut(i).ok_or_else(|| not_configured(&format!("SQL query {}", i)))?.get_values()
+    }
+
+    /// Metrics received over statsd since the previous call
+    pub fn get_statsd_metrics(&self) -> Result<StatsdMetrics, FetchError> {
+        Ok(set_up(&self.statsd, "StatsD")?.take_metrics())
+    }
+
+    /// Log lines read since the previous call
+    pub fn get_log_lines(&self) -> Result<LogLines, FetchError> {
+        Ok(set_up(&self.logs, "Logs")?.take_lines())
+    }
+
+    /// Connections and counters of the PostgreSQL server
+    pub fn get_postgres_activity(&mut self) -> Result<PostgresActivity, FetchError> {
+        self.postgres.as_mut().ok_or_else(|| not_configured("PostgreSQL"))?.poll()
+    }
+
+    /// Lag of the Kafka consumer groups
+    pub fn get_kafka_lag(&mut self) -> Result<KafkaLag, FetchError> {
+        self.kafka_lag.as_mut().ok_or_else(|| not_configured("Kafka lag"))?.poll()
+    }
+
+    /// Stats of the Redis server
+    pub fn get_redis_info(&self) -> Result<RedisInfo, FetchError> {
+        redis::client::get_info(self.redis_settings.as_ref().ok_or_else(|| not_configured("Redis"))?)
+    }
+
+    /// Resource usage of the container
+    pub fn get_docker_stats(&self) -> Result<DockerStats, FetchError> {
+        docker::client::get_stats(self.docker_settings.as_ref().ok_or_else(|| not_configured("Docker"))?)
+    }
+
+    /// Resource usage of the pod
+    pub fn get_pod_metrics(&self) -> Result<PodMetrics, FetchError> {
+        set_up(&self.k8s, "Kubernetes")?.poll()
+    }
+
+    /// Stats of the RabbitMQ queues
+    pub fn get_rabbitmq_stats(&self) -> Result<RabbitMqStats, FetchError> {
+        rabbitmq::client::get_stats(self.rabbitmq_settings.as_ref().ok_or_else(|| not_configured("RabbitMQ"))?)
+    }
+
+    /// Status page of the proxy
+    pub fn get_proxy_status(&self) -> Result<ProxyStatus, FetchError> {
+        proxy::client::get_status(self.proxy_settings.as_ref().ok_or_else(|| not_configured("Proxy"))?)
+    }
+
+    /// Latest traces of the service
+    pub fn get_recent_traces(&self) -> Result<RecentTraces, FetchError> {
+        traces::client::get_recent_traces(self.traces_settings.as_ref().ok_or_else(|| not_configured("Traces"))?)
+    }
+
+    /// Metrics received over OTLP since the previous call
+    pub fn get_otlp_metrics(&self) -> Result<OtlpMetrics, FetchError> {
+        Ok(set_up(&self.otlp, "OTLP")?.take_metrics())
+    }
+
+    /// Flight recorder events since the previous call
+    pub fn get_jfr_metrics(&mut self) -> Result<JfrMetrics, FetchError> {
+        self.jfr.as_mut().ok_or_else(|| not_configured("JFR"))?.as_mut().map_err(|e| e.clone())?.poll()
+            .map_err(|e| e.context("Error reading JFR events"))
+    }
+
+    fn format_slick_error(e: FetchError) -> FetchError {
+        e.context("No Slick JMX metrics found. Are you sure you have registerMbeans=true in your Slick config?\r\nUnderlying error")
+    }
+}
+
+/// Error of a getter whose source wasn't given to `Fetcher::new`
+fn not_configured(source: &str) -> FetchError {
+    FetchError::from(format!("{} is not configured", source))
+}
+
+/// Client of a source, or why it couldn't be set up
+fn set_up<'a, T>(client: &'a Option<Result<T, FetchError>>, source: &str) -> Result<&'a T, FetchError> {
+    client.as_ref().ok_or_else(|| not_configured(source))?.as_ref().map_err(|e| e.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, UdpSocket};
+    use std::time::{Duration, Instant};
+
+    use serde_json::Value;
+
+    use crate::error::FetchError;
+    use crate::fetcher::{Fetcher, JMXConnectionStatus, Sources};
+    use crate::jmx::client::{JMXClient, MBeanConnection};
+    use crate::jmx::model::{HikariAlertSettings, JMXConnectionSettings};
+    use crate::logs::model::LogSettings;
+    use crate::prometheus::model::PromQLSettings;
+    use crate::statsd::model::StatsdSettings;
+
+    struct BrokenConnection;
+
+    impl MBeanConnection for BrokenConnection {
+        fn get_attribute(&self, _mbean: &str, _attribute: &str) -> Result<Value, FetchError> {
+            Err(FetchError::connection("Connection reset"))
+        }
+
+        fn query_names(&self, _pattern: &str) -> Result<Vec<String>, FetchError> {
+            Err(FetchError::connection("Connection reset"))
+        }
+    }
+
+    fn jolokia_settings(address: String) -> JMXConnectionSettings {
+        JMXConnectionSettings {
+            address,
+            username: None,
+            password: None,
+            db_pool_name: None,
+            kafka: false,
+            cassandra_domain: None,
+            akka_dispatchers: None,
+            loggers: false,
+            thread_pools: vec![],
+            jvm_info: false,
+            akka_cluster: false,
+            heap_dump_dir: "/tmp".to_owned(),
+            gc_pause_threshold: 200,
+            ssl: None,
+            notifications: vec![],
+            allow_operations: false,
+            hikari_alerts: HikariAlertSettings { exhausted_ticks: 2, waiting_threshold: 5 },
+        }
+    }
+
+    /// Address of a Jolokia agent answering a single request
+    fn jolokia_agent() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = format!("http://{}/jolokia", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 4096];
+            let _ = stream.read(&mut request);
+            let body = r#"{"status":200,"value":{"agent":"1.6.2"}}"#;
+            let _ = write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body);
+        });
+        address
+    }
+
+    #[test]
+    fn reconnect_backoff_doubles_up_to_its_cap() {
+        assert_eq!(Fetcher::reconnect_backoff(1), Duration::from_secs(2));
+        assert_eq!(Fetcher::reconnect_backoff(2), Duration::from_secs(4));
+        assert_eq!(Fetcher::reconnect_backoff(4), Duration::from_secs(16));
+        assert_eq!(Fetcher::reconnect_backoff(5), Fetcher::MAX_RECONNECT_BACKOFF);
+        assert_eq!(Fetcher::reconnect_backoff(u32::MAX), Fetcher::MAX_RECONNECT_BACKOFF);
+    }
+
+    #[test]
+    fn broken_jmx_connections_are_dropped_and_reconnected_with_backoff() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let unreachable = format!("http://{}/jolokia", listener.local_addr().unwrap());
+        drop(listener);
+
+        let mut fetcher = Fetcher::new(Sources::default());
+        fetcher.jmx = Some(JMXClient::new(Box::new(BrokenConnection), "db".to_owned()));
+        fetcher.jmx_settings = Some(jolokia_settings(unreachable));
+
+        assert!(fetcher.with_jmx(|j| j.ping()).is_err());
+        assert!(fetcher.jmx.is_none());
+        assert!(matches!(fetcher.take_jmx_status_change(), Some(JMXConnectionStatus::Reconnecting { attempt: 1, .. })));
+        let (attempt, next_attempt) = fetcher.jmx_reconnect.unwrap();
+        assert_eq!(attempt, 1);
+        assert!(next_attempt > Instant::now());
+
+        // no attempt is made before the backoff is over
+        assert_eq!(fetcher.with_jmx(|j| j.ping()).err(), Some(FetchError::connection("JMX connection lost, waiting to reconnect")));
+        assert!(fetcher.take_jmx_status_change().is_none());
+
+        fetcher.jmx_reconnect = Some((3, Instant::now()));
+        let started = Instant::now();
+        assert!(fetcher.with_jmx(|j| j.ping()).is_err());
+        assert!(matches!(fetcher.take_jmx_status_change(), Some(JMXConnectionStatus::Reconnecting { attempt: 4, .. })));
+        let (attempt, next_attempt) = fetcher.jmx_reconnect.unwrap();
+        assert_eq!(attempt, 4);
+        assert!(next_attempt >= started + Fetcher::reconnect_backoff(3));
+
+        fetcher.jmx_settings = Some(jolokia_settings(jolokia_agent()));
+        fetcher.jmx_reconnect = Some((4, Instant::now()));
+        assert_eq!(fetcher.with_jmx(|_| Ok(())), Ok(()));
+        assert!(fetcher.jmx.is_some());
+        assert!(fetcher.jmx_reconnect.is_none());
+        assert!(matches!(fetcher.take_jmx_status_change(), Some(JMXConnectionStatus::Connected)));
+    }
+
+    #[test]
+    fn unreachable_jvms_are_reconnected_to_from_the_start() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let refused = format!("http://{}/jolokia", listener.local_addr().unwrap());
+        drop(listener);
+
+        let mut fetcher = Fetcher::new(Sources { jmx: Some(jolokia_settings(refused)), ..Sources::default() });
+        assert!(fetcher.jmx.is_none());
+        assert!(matches!(fetcher.take_jmx_status_change(), Some(JMXConnectionStatus::Reconnecting { attempt: 1, .. })));
+        assert!(fetcher.jmx_reconnect.is_some_and(|(attempt, next_attempt)| attempt == 1 && next_attempt > Instant::now()));
+        assert_eq!(fetcher.get_loggers().err().map(|e| e.to_string()), Some("Error loading loggers: JMX connection lost, waiting to reconnect".to_owned()));
+
+        fetcher.jmx_settings = Some(jolokia_settings(jolokia_agent()));
+        fetcher.jmx_reconnect = Some((1, Instant::now()));
+        assert_eq!(fetcher.with_jmx(|_| Ok(())), Ok(()));
+        assert!(matches!(fetcher.take_jmx_status_change(), Some(JMXConnectionStatus::Connected)));
+    }
+
+    #[test]
+    fn sources_are_empty_without_any_source() {
+        assert!(Sources::default().is_empty());
+        let promql = PromQLSettings { address: "http://prometheus:9090".to_owned(), queries: vec!["up".to_owned()], timeout: 800 };
+        assert!(!Sources { promql: Some(promql), ..Sources::default() }.is_empty());
+        assert!(!Sources { logs: Some(LogSettings { source: "app.log".to_owned() }), ..Sources::default() }.is_empty());
+    }
+
+    #[test]
+    fn getters_of_sources_that_are_not_configured_fail() {
+        let mut fetcher = Fetcher::new(Sources::default());
+        assert_eq!(fetcher.get_redis_info().err(), Some(FetchError::from("Redis is not configured")));
+        assert_eq!(fetcher.dump_fibers().err(), Some(FetchError::from("zio-zmx is not configured")));
+        assert_eq!(fetcher.get_kafka_metrics().err().map(|e| e.to_string()), Some("Error loading kafka consumer metrics: JMX is not configured".to_owned()));
+        assert!(fetcher.take_jmx_status_change().is_none());
+        assert_eq!(fetcher.get_statsd_metrics().err(), Some(FetchError::from("StatsD is not configured")));
+        assert_eq!(fetcher.get_sql_values(0).err(), Some(FetchError::from("SQL query 0 is not configured")));
+    }
+
+    #[test]
+    fn sources_that_cant_be_set_up_fail_their_requests_alone() {
+        let taken = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let address = taken.local_addr().unwrap().to_string();
+        let fetcher = Fetcher::new(Sources {
+            statsd: Some(StatsdSettings { address: address.clone(), metrics: vec![] }),
+            logs: Some(LogSettings { source: "/nonexistent/app.log".to_owned() }),
+            ..Sources::default()
+        });
+
+        let error = fetcher.get_statsd_metrics().err().unwrap();
+        assert!(error.to_string().starts_with(&format!("Couldn't listen for statsd metrics on {}", address)));
+        assert_eq!(fetcher.get_log_lines().err(), Some(FetchError::from("Log file /nonexistent/app.log doesn't exist")));
+        assert_eq!(fetcher.get_statsd_metrics().err(), Some(error));
+    }
+}
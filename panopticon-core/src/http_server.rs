@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+/// Larger requests are rejected rather than buffered
+const MAX_BODY: usize = 16 * 1024 * 1024;
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+///
+/// Reads the next request of a connection, `None` once it's closed.
+///
+/// It's just enough of HTTP/1.1 for the endpoints Panopticon serves: bodies need a `Content-Length`,
+/// chunked ones aren't supported.
+pub fn read_request<R: BufRead>(reader: &mut R) -> Result<Option<Request>, String> {
+    let mut line = String::new();
+    if reader.read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+        return Ok(None);
+    }
+    let mut parts = line.split_whitespace();
+    let (method, path) = match (parts.next(), parts.next()) {
+        (Some(m), Some(p)) => (m.to_owned(), p.to_owned()),
+        _ => return Err(format!("Invalid request line {}", line.trim())),
+    };
+    let mut headers = HashMap::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).map_err(|e| e.to_string())? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_owned());
+        }
+    }
+    let length: usize = headers.get("content-length").and_then(|l| l.parse().ok()).unwrap_or(0);
+    if length > MAX_BODY {
+        return Err(format!("Request of {} bytes is too large", length));
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+    Ok(Some(Request { method, path, headers, body }))
+}
+
+pub fn write_response<W: Write>(writer: &mut W, status: &str, content_type: &str, body: &str) -> std::io::Result<()> {
+    let response = format!("HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\r\n{}", status, content_type, body.len(), body);
+    writer.write_all(response.as_bytes())
+}
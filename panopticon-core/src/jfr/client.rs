@@ -0,0 +1,240 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use chrono::{DateTime, Local};
+use serde_json::Value;
+
+use crate::error::FetchError;
+use crate::jfr::model::{ContentionEvent, GcPause, JfrMetrics, JfrSettings};
+
+const EVENTS: &str = "jdk.GarbageCollection,jdk.ObjectAllocationSample,jdk.ObjectAllocationInNewTLAB,\
+jdk.ObjectAllocationOutsideTLAB,jdk.JavaMonitorEnter,jdk.ThreadPark";
+
+#[derive(Debug, PartialEq)]
+enum JfrEvent {
+    /// Bytes allocated
+    Allocation(f64),
+    GcPause(GcPause),
+    Contention(ContentionEvent),
+}
+
+///
+/// Reads events from a Java Flight Recorder repository with the JDK `jfr` tool.
+///
+/// A running JVM keeps appending to the newest chunk file of its repository, and starts a new one when
+/// it grows big enough. Every poll prints the chunks modified since the previous one, skipping events
+/// that were already read from them. Recordings from before the first poll are skipped.
+pub struct JfrClient {
+    settings: JfrSettings,
+    // modification time and number of events read, per chunk
+    chunks: HashMap<PathBuf, (SystemTime, usize)>,
+    last_poll: Option<Instant>,
+}
+
+impl JfrClient {
+    /// Fails if the repository doesn't exist or the `jfr` tool can't be run
+    pub fn new(settings: JfrSettings) -> Result<JfrClient, FetchError> {
+        if !Path::new(&settings.repository).exists() {
+            return Err(FetchError::from(format!("JFR repository {} doesn't exist", settings.repository)));
+        }
+        Command::new(&settings.tool)
+            .arg("version")
+            .output()
+            .map_err(|e| format!("Couldn't run {}, JFR support requires the jfr tool of JDK 14+: {}", settings.tool, e))?;
+        Ok(JfrClient { settings, chunks: HashMap::new(), last_poll: None })
+    }
+
+    /// Events recorded since the previous poll
+    pub fn poll(&mut self) -> Result<JfrMetrics, FetchError> {
+        let mut files = chunk_files(Path::new(&self.settings.repository))?;
+        files.sort_by_key(|(_, modified)| *modified);
+        self.chunks.retain(|path, _| files.iter().any(|(p, _)| p == path));
+
+        let first_poll = self.last_poll.is_none();
+        let newest = files.last().map(|(p, _)| p.clone());
+        let mut events = vec![];
+        for (path, modified) in files {
+            let (read_modified, read) = self.chunks.get(&path).cloned().unwrap_or((UNIX_EPOCH, 0));
+            if read_modified == modified {
+                continue;
+            }
+            if first_poll && Some(&path) != newest.as_ref() {
+                self.chunks.insert(path, (modified, usize::MAX));
+                continue;
+            }
+            match self.print_events(&path)? {
+                Some(chunk_events) => {
+                    let total = chunk_events.len();
+                    let skip = if first_poll { total } else { read };
+                    events.extend(chunk_events.into_iter().skip(skip));
+                    self.chunks.insert(path, (modified, total.max(read)));
+                }
+                // the chunk being written may not be readable yet, it's retried on the next poll
+                None if Some(&path) == newest.as_ref() => {}
+                None => {
+                    self.chunks.insert(path, (modified, usize::MAX));
+                }
+            }
+        }
+
+        let elapsed = self.last_poll.map_or(0.0, |t| t.elapsed().as_secs_f64());
+        self.last_poll = Some(Instant::now());
+
+        let mut allocated = 0.0;
+        let mut gc_pauses = vec![];
+        let mut contention = vec![];
+        for event in events {
+            match event {
+                JfrEvent::Allocation(bytes) => allocated += bytes,
+                JfrEvent::GcPause(p) => gc_pauses.push(p),
+                JfrEvent::Contention(c) => contention.push(c),
+            }
+        }
+        Ok(JfrMetrics {
+            timestamp: Local::now(),
+            allocation_rate: if elapsed > 0.0 { allocated / elapsed } else { 0.0 },
+            gc_pauses,
+            contention,
+        })
+    }
+
+    /// Returns `None` if the chunk can't be parsed
+    fn print_events(&self, chunk: &Path) -> Result<Option<Vec<JfrEvent>>, String> {
+        let output = Command::new(&self.settings.tool)
+            .args(["print", "--json", "--events", EVENTS])
+            .arg(chunk)
+            .output()
+            .map_err(|e| format!("Couldn't run {}: {}", self.settings.tool, e))?;
+        if !output.status.success() {
+            return Ok(None);
+        }
+        Ok(serde_json::from_slice(&output.stdout).ok().map(|json: Value| parse_events(&json)))
+    }
+}
+
+/// Recording files under the path, with their modification times
+fn chunk_files(path: &Path) -> Result<Vec<(PathBuf, SystemTime)>, String> {
+    let metadata = fs::metadata(path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    if metadata.is_file() {
+        let modified = metadata.modified().map_err(|e| e.to_string())?;
+        return Ok(vec![(path.to_path_buf(), modified)]);
+    }
+    let mut files = vec![];
+    let entries = fs::read_dir(path).map_err(|e| format!("Couldn't read {}: {}", path.display(), e))?;
+    for entry in entries.filter_map(|e| e.ok()) {
+        let p = entry.path();
+        // repositories keep chunks in a subdirectory per JVM
+        if p.is_dir() || p.extension().is_some_and(|x| x == "jfr") {
+            files.append(&mut chunk_files(&p)?);
+        }
+    }
+    Ok(files)
+}
+
+fn parse_events(json: &Value) -> Vec<JfrEvent> {
+    json["recording"]["events"].as_array()
+        .map_or(vec![], |events| events.iter().filter_map(parse_event).collect())
+}
+
+fn parse_event(event: &Value) -> Option<JfrEvent> {
+    let values = &event["values"];
+    let timestamp = values["startTime"].as_str()
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())?
+        .with_timezone(&Local);
+    let duration = |key: &str| values[key].as_str().and_then(parse_duration).unwrap_or(0.0);
+    let contention = |kind: &str, class: &Value| JfrEvent::Contention(ContentionEvent {
+        timestamp,
+        kind: kind.to_owned(),
+        class: class["name"].as_str().map_or("-".to_owned(), |n| n.replace('/', ".")),
+        thread: values["eventThread"]["javaName"].as_str().unwrap_or("-").to_owned(),
+        duration_ms: duration("duration"),
+    });
+
+    match event["type"].as_str()? {
+        "jdk.ObjectAllocationSample" => values["weight"].as_f64().map(JfrEvent::Allocation),
+        "jdk.ObjectAllocationInNewTLAB" => values["tlabSize"].as_f64().map(JfrEvent::Allocation),
+        "jdk.ObjectAllocationOutsideTLAB" => values["allocationSize"].as_f64().map(JfrEvent::Allocation),
+        "jdk.GarbageCollection" => Some(JfrEvent::GcPause(GcPause {
+            timestamp,
+            name: values["name"].as_str().unwrap_or("-").to_owned(),
+            cause: values["cause"].as_str().unwrap_or("-").to_owned(),
+            duration_ms: duration("sumOfPauses"),
+        })),
+        "jdk.JavaMonitorEnter" => Some(contention("monitor", &values["monitorClass"])),
+        "jdk.ThreadPark" => Some(contention("park", &values["parkedClass"])),
+        _ => None,
+    }
+}
+
+/// Parses ISO-8601 durations printed by `jfr`, e.g. `PT1M2.5S`, into milliseconds
+fn parse_duration(s: &str) -> Option<f64> {
+    let mut rest = s.strip_prefix("PT")?;
+    let mut ms = 0.0;
+    while !rest.is_empty() {
+        let end = rest.find(|c: char| c.is_ascii_alphabetic())?;
+        let n: f64 = rest[..end].parse().ok()?;
+        ms += n * match &rest[end..end + 1] {
+            "H" => 3_600_000.0,
+            "M" => 60_000.0,
+            "S" => 1000.0,
+            _ => return None,
+        };
+        rest = &rest[end + 1..];
+    }
+    Some(ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::jfr::client::{JfrEvent, parse_duration, parse_events};
+
+    #[test]
+    fn durations_are_parsed_to_millis() {
+        assert_eq!(parse_duration("PT0.0125S"), Some(12.5));
+        assert_eq!(parse_duration("PT1M2.5S"), Some(62_500.0));
+        assert_eq!(parse_duration("PT0S"), Some(0.0));
+        assert_eq!(parse_duration("12ms"), None);
+    }
+
+    #[test]
+    fn jfr_events_are_parsed() {
+        let json = json!({"recording": {"events": [
+            {"type": "jdk.ObjectAllocationSample", "values": {"startTime": "2020-06-01T10:00:00.5+02:00", "weight": 1048576}},
+            {"type": "jdk.GarbageCollection", "values": {
+                "startTime": "2020-06-01T10:00:01.123456789+02:00", "duration": "PT0.05S",
+                "name": "G1New", "cause": "G1 Evacuation Pause", "sumOfPauses": "PT0.012S"
+            }},
+            {"type": "jdk.JavaMonitorEnter", "values": {
+                "startTime": "2020-06-01T10:00:02+02:00", "duration": "PT0.3S",
+                "monitorClass": {"name": "com/example/Cache"}, "eventThread": {"javaName": "worker-1"}
+            }},
+            {"type": "jdk.ThreadPark", "values": {"startTime": "2020-06-01T10:00:03+02:00", "duration": "PT1S", "parkedClass": null}},
+            {"type": "jdk.CPULoad", "values": {"startTime": "2020-06-01T10:00:04+02:00"}}
+        ]}});
+
+        let events = parse_events(&json);
+        assert_eq!(events.len(), 4);
+        assert_eq!(events[0], JfrEvent::Allocation(1048576.0));
+        match &events[1] {
+            JfrEvent::GcPause(p) => {
+                assert_eq!((p.name.as_str(), p.cause.as_str(), p.duration_ms), ("G1New", "G1 Evacuation Pause", 12.0));
+            }
+            _ => panic!("Expected a GC pause"),
+        }
+        match &events[2] {
+            JfrEvent::Contention(c) => {
+                assert_eq!((c.kind.as_str(), c.class.as_str(), c.thread.as_str(), c.duration_ms), ("monitor", "com.example.Cache", "worker-1", 300.0));
+            }
+            _ => panic!("Expected a contention event"),
+        }
+        match &events[3] {
+            JfrEvent::Contention(c) => assert_eq!((c.kind.as_str(), c.class.as_str()), ("park", "-")),
+            _ => panic!("Expected a contention event"),
+        }
+    }
+}
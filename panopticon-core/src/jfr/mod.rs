@@ -0,0 +1,6 @@
+//! JVMs recording with Java Flight Recorder
+
+/// GC pauses and lock contention read from the recordings, with the settings of the source
+pub mod model;
+/// Reads the new events of the chunks of a recording repository
+pub mod client;
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Flight recordings read with the `jfr` tool of a JDK
+#[derive(Clone)]
+pub struct JfrSettings {
+    /// JFR repository directory, or a single recording file
+    pub repository: String,
+    /// Path to the `jfr` tool of a JDK 14+
+    pub tool: String,
+}
+
+/// A stop-the-world pause of the garbage collector
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct GcPause {
+    /// When the pause started
+    pub timestamp: DateTime<Local>,
+    /// Name of the collector, e.g. G1New
+    pub name: String,
+    /// Why it collected, e.g. G1 Evacuation Pause
+    pub cause: String,
+    /// Length of the pause
+    pub duration_ms: f64,
+}
+
+/// A thread waiting for a lock held by another
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ContentionEvent {
+    /// When the thread started waiting
+    pub timestamp: DateTime<Local>,
+    /// `monitor` for synchronized blocks, `park` for java.util.concurrent locks
+    pub kind: String,
+    /// Class of the lock
+    pub class: String,
+    /// Name of the waiting thread
+    pub thread: String,
+    /// How long the thread waited
+    pub duration_ms: f64,
+}
+
+/// Events read from the recording since the previous poll
+#[derive(Deserialize, Serialize)]
+pub struct JfrMetrics {
+    /// When the recording was read
+    pub timestamp: DateTime<Local>,
+    /// Bytes allocated per second, estimated from allocation samples
+    pub allocation_rate: f64,
+    /// Pauses that happened since the previous poll
+    pub gc_pauses: Vec<GcPause>,
+    /// Contention that happened since the previous poll
+    pub contention: Vec<ContentionEvent>,
+}
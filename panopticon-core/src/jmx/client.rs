@@ -0,0 +1,676 @@
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use chrono::{Local, TimeZone};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::error::FetchError;
+use crate::jmx::jolokia::JolokiaConnection;
+use crate::jmx::model::*;
+
+///
+/// Minimal set of MBean server operations panopticon relies on.
+///
+/// Implemented over RMI, reading MBeans over JRMP in Rust and running a JVM in-process for the rest,
+/// and over HTTP for targets that expose a Jolokia agent.
+pub trait MBeanConnection {
+    /// Value of an attribute of an MBean, as JSON
+    fn get_attribute(&self, mbean: &str, attribute: &str) -> Result<Value, FetchError>;
+
+    /// Lists names of MBeans matching an object name pattern, e.g. `java.lang:type=GarbageCollector,*`
+    fn query_names(&self, pattern: &str) -> Result<Vec<String>, FetchError>;
+
+    /// Invokes an MBean operation and returns its result.
+    fn invoke(&self, _mbean: &str, _operation: &str, _arguments: &[Value]) -> Result<Value, FetchError> {
+        Err(FetchError::from("This connection can't invoke MBean operations"))
+    }
+
+    /// Sets a writable MBean attribute.
+    fn set_attribute(&self, _mbean: &str, _attribute: &str, _value: Value) -> Result<(), FetchError> {
+        Err(FetchError::from("This connection can't write MBean attributes"))
+    }
+
+    /// Starts collecting notifications emitted by the given MBean.
+    fn subscribe(&self, _mbean: &str) -> Result<(), FetchError> {
+        Err(FetchError::from("This connection can't receive JMX notifications"))
+    }
+
+    /// Returns notifications collected since the previous call.
+    fn pull_notifications(&self) -> Result<Vec<JMXNotification>, FetchError> {
+        Err(FetchError::from("This connection can't receive JMX notifications"))
+    }
+}
+
+// attribute names of a thread pool bean, resolved once as they differ between pool implementations
+#[derive(Clone)]
+struct ThreadPoolAttributes {
+    active_threads: String,
+    queue_size: Option<String>,
+    max_threads: Option<String>,
+}
+
+/// Reads the metrics panopticon charts from the MBeans of a JMX connection
+pub struct JMXClient {
+    connection: Box<dyn MBeanConnection>,
+    db_pool_name: String,
+    // attribute names of thread pool beans by object name
+    thread_pool_attributes: RefCell<HashMap<String, ThreadPoolAttributes>>,
+    // id of the last reported collection by collector name, none before the first poll
+    last_gc_ids: RefCell<Option<HashMap<String, i64>>>,
+    // start of the JVM in epoch millis, which collection times are relative to
+    jvm_start: Cell<Option<i64>>,
+}
+
+impl JMXClient {
+    const SLICK_EXECUTORS: &'static str = "slick:type=AsyncExecutor,*";
+    const HIKARI_POOLS: &'static str = "com.zaxxer.hikari:type=Pool (*)";
+    const LOGBACK_CONFIGURATORS: &'static str = "ch.qos.logback.classic:Type=ch.qos.logback.classic.jmx.JMXConfigurator,*";
+    const LOG4J2_LOGGERS: &'static str = "org.apache.logging.log4j2:component=Loggers,*";
+    const RUNTIME: &'static str = "java.lang:type=Runtime";
+    const MEMORY: &'static str = "java.lang:type=Memory";
+    const HOTSPOT_DIAGNOSTIC: &'static str = "com.sun.management:type=HotSpotDiagnostic";
+    const GARBAGE_COLLECTORS: &'static str = "java.lang:type=GarbageCollector,*";
+
+    // attribute names used by java.util.concurrent executors, ForkJoinPool, Slick, Tomcat and Jetty pools
+    const ACTIVE_THREADS_ATTRIBUTES: [&'static str; 5] = ["ActiveCount", "ActiveThreadCount", "ActiveThreads", "currentThreadsBusy", "busyThreads"];
+    const QUEUE_SIZE_ATTRIBUTES: [&'static str; 4] = ["QueueSize", "QueuedTaskCount", "queueSize", "queuedTasks"];
+    const MAX_THREADS_ATTRIBUTES: [&'static str; 5] = ["MaximumPoolSize", "Parallelism", "MaxThreads", "maxThreads", "maxPoolSize"];
+
+    /// A client of the connection, reading the HikariCP pool of the given name
+    pub fn new(connection: Box<dyn MBeanConnection>, db_pool_name: String) -> JMXClient {
+        JMXClient {
+            connection,
+            db_pool_name,
+            thread_pool_attributes: RefCell::new(HashMap::new()),
+            last_gc_ids: RefCell::new(None),
+            jvm_start: Cell::new(None),
+        }
+    }
+
+    ///
+    /// Connects to a JMX source described by settings.
+    ///
+    /// Addresses starting with `http://` or `https://` are treated as Jolokia endpoints,
+    /// addresses starting with `service:jmx:` as JMX service urls, e.g. of local connectors,
+    /// and everything else as a `host:port` of a remote RMI connector.
+    pub fn connect(settings: &JMXConnectionSettings) -> Result<JMXClient, FetchError> {
+        let connection: Box<dyn MBeanConnection> = if settings.is_jolokia() {
+            Box::new(JolokiaConnection::new(settings)?)
+        } else {
+            JMXClient::connect_rmi(settings)?
+        };
+        let client = JMXClient::new(connection, settings.db_pool_name.clone().unwrap_or_default());
+        client.subscribe_notifications(&settings.notifications)?;
+        Ok(client)
+    }
+
+    #[cfg(feature = "jvm")]
+    fn connect_rmi(settings: &JMXConnectionSettings) -> Result<Box<dyn MBeanConnection>, FetchError> {
+        let c = crate::jmx::rmi::RmiConnection::new(settings)?;
+        Ok(Box::new(c))
+    }
+
+    #[cfg(not(feature = "jvm"))]
+    fn connect_rmi(settings: &JMXConnectionSettings) -> Result<Box<dyn MBeanConnection>, FetchError> {
+        let c = crate::jmx::jrmp::JrmpConnection::new(settings)?;
+        Ok(Box::new(c))
+    }
+
+    /// Checks the connection is alive by reading an attribute every MBean server has
+    pub fn ping(&self) -> Result<(), FetchError> {
+        self.connection.get_attribute("JMImplementation:type=MBeanServerDelegate", "MBeanServerId").map(|_| ())
+    }
+
+    /// Switches to another HikariCP pool, by its name
+    pub fn set_db_pool_name(&mut self, db_pool_name: String) {
+        self.db_pool_name = db_pool_name;
+    }
+
+    /// Lists names of Slick executors and HikariCP pools registered on the server
+    pub fn discover_db_pools(&self) -> Result<Vec<String>, FetchError> {
+        let slick = self.connection.query_names(JMXClient::SLICK_EXECUTORS)?
+            .into_iter()
+            .filter_map(|n| JMXClient::key_property(&n, "name"));
+        let hikari = self.connection.query_names(JMXClient::HIKARI_POOLS)?
+            .into_iter()
+            .filter_map(|n| JMXClient::hikari_pool_name(&n));
+        let mut pools: Vec<String> = slick.chain(hikari).collect();
+        pools.sort();
+        pools.dedup();
+        Ok(pools)
+    }
+
+    /// Pool name of a `com.zaxxer.hikari:type=Pool (<name>)` object name
+    fn hikari_pool_name(object_name: &str) -> Option<String> {
+        JMXClient::key_property(object_name, "type")
+            .filter(|t| t.starts_with("Pool (") && t.ends_with(')'))
+            .map(|t| t["Pool (".len()..t.len() - 1].to_owned())
+    }
+
+    /// Connections of the HikariCP pool
+    pub fn get_hikari_metrics(&self) -> Result<HikariMetrics, FetchError> {
+        let total: i32 = self.get_hikari_attribute("TotalConnections")?;
+        let active: i32 = self.get_hikari_attribute("ActiveConnections")?;
+        let waiting: i32 = self.get_hikari_attribute("ThreadsAwaitingConnection")?;
+        let idle: i32 = self.get_hikari_attribute("IdleConnections")?;
+        let max_pool_size: i32 = self.get_attribute(
+            &format!("com.zaxxer.hikari:type=PoolConfig ({})", self.db_pool_name),
+            "MaximumPoolSize",
+        )?;
+
+        Result::Ok(HikariMetrics {
+            timestamp: Local::now(),
+            total,
+            active,
+            waiting,
+            idle,
+            max_pool_size,
+        })
+    }
+
+    /// Threads and queue of the Slick executor
+    pub fn get_slick_metrics(&self) -> Result<SlickMetrics, FetchError> {
+        let active_threads: i32 = self.get_slick_attribute("ActiveThreads")?;
+        let queue_size: i32 = self.get_slick_attribute("QueueSize")?;
+
+        Result::Ok(SlickMetrics {
+            timestamp: Local::now(),
+            active_threads,
+            queue_size,
+        })
+    }
+
+    /// Limits of the Slick executor
+    pub fn get_slick_config(&self) -> Result<SlickConfig, FetchError> {
+        let max_threads: i32 = self.get_slick_attribute("MaxThreads")?;
+        let max_queue_size: i32 = self.get_slick_attribute("MaxQueueSize")?;
+
+        Result::Ok(SlickConfig {
+            max_threads,
+            max_queue_size,
+        })
+    }
+
+    /// Fetch lag and rates of the Kafka consumers of the JVM
+    pub fn get_kafka_metrics(&self) -> Result<KafkaMetrics, FetchError> {
+        let names = self.connection.query_names("kafka.consumer:type=consumer-fetch-manager-metrics,client-id=*")?;
+        let mut consumers = vec![];
+        for name in names {
+            consumers.push(KafkaConsumerMetrics {
+                client_id: JMXClient::key_property(&name, "client-id").unwrap_or_else(|| name.clone()),
+                records_lag_max: self.get_metric(&name, "records-lag-max")?,
+                fetch_rate: self.get_metric(&name, "fetch-rate")?,
+                records_consumed_rate: self.get_metric(&name, "records-consumed-rate")?,
+            });
+        }
+        consumers.sort_by(|a, b| a.client_id.cmp(&b.client_id));
+        Ok(KafkaMetrics { timestamp: Local::now(), consumers })
+    }
+
+    ///
+    /// Reads DataStax driver metrics, published by its Dropwizard JMX reporter under the given domain
+    /// (`<cluster name>-metrics` by default).
+    pub fn get_cassandra_metrics(&self, domain: &str) -> Result<CassandraMetrics, FetchError> {
+        let bean = |name: &str| format!("{}:name={}", domain, name);
+        let requests = bean("requests");
+        let latency_unit = self.connection.get_attribute(&requests, "DurationUnit")?
+            .as_str()
+            .unwrap_or("")
+            .to_owned();
+        Ok(CassandraMetrics {
+            timestamp: Local::now(),
+            connected_hosts: self.get_metric(&bean("connected-to"), "Value")?,
+            open_connections: self.get_metric(&bean("open-connections"), "Value")?,
+            in_flight_requests: self.get_metric(&bean("inflight-requests"), "Value")?,
+            latency_p50: self.get_metric(&requests, "50thPercentile")?,
+            latency_p95: self.get_metric(&requests, "95thPercentile")?,
+            latency_p99: self.get_metric(&requests, "99thPercentile")?,
+            latency_unit,
+        })
+    }
+
+    ///
+    /// Reads executor metrics of Akka dispatchers, exposed as MBeans matching the given pattern.
+    ///
+    /// Attribute names follow `ForkJoinPool` getters, which is what dispatchers run on by default.
+    /// Dispatchers are named after the `name` key property of their object names.
+    pub fn get_dispatcher_metrics(&self, pattern: &str) -> Result<AkkaDispatcherMetrics, FetchError> {
+        let names = self.connection.query_names(pattern)?;
+        let mut dispatchers = vec![];
+        for name in names {
+            dispatchers.push(DispatcherMetrics {
+                name: JMXClient::key_property(&name, "name").unwrap_or_else(|| name.clone()),
+                active_threads: self.get_metric(&name, "ActiveThreadCount")?,
+                queued_tasks: self.get_metric(&name, "QueuedTaskCount")?,
+                parallelism: self.get_metric(&name, "Parallelism")?,
+            });
+        }
+        dispatchers.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(AkkaDispatcherMetrics { timestamp: Local::now(), dispatchers })
+    }
+
+    ///
+    /// Reads metrics of all executor/thread pool MBeans matching given object name patterns.
+    ///
+    /// Pools are named after the `name` or `id` key property of their object names.
+    pub fn get_thread_pool_metrics(&self, patterns: &[String]) -> Result<ThreadPoolsMetrics, FetchError> {
+        let mut pools = vec![];
+        for pattern in patterns {
+            for mbean in self.connection.query_names(pattern)? {
+                let attributes = self.thread_pool_attributes(&mbean)?;
+                let optional_metric = |attr: &Option<String>| match attr {
+                    Some(a) => self.get_metric(&mbean, a).map(Some),
+                    None => Ok(None),
+                };
+                pools.push(ThreadPoolMetrics {
+                    name: JMXClient::key_property(&mbean, "name")
+                        .or_else(|| JMXClient::key_property(&mbean, "id"))
+                        .map_or(mbean.clone(), |n| n.trim_matches('"').to_owned()),
+                    active_threads: self.get_metric(&mbean, &attributes.active_threads)?,
+                    queue_size: optional_metric(&attributes.queue_size)?,
+                    max_threads: optional_metric(&attributes.max_threads)?,
+                });
+            }
+        }
+        pools.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(ThreadPoolsMetrics { timestamp: Local::now(), pools })
+    }
+
+    fn thread_pool_attributes(&self, mbean: &str) -> Result<ThreadPoolAttributes, FetchError> {
+        if let Some(attributes) = self.thread_pool_attributes.borrow().get(mbean) {
+            return Ok(attributes.clone());
+        }
+        let find = |candidates: &[&str]| candidates.iter()
+            .find(|a| self.connection.get_attribute(mbean, a).is_ok())
+            .map(|a| (*a).to_owned());
+        let attributes = ThreadPoolAttributes {
+            active_threads: find(&JMXClient::ACTIVE_THREADS_ATTRIBUTES)
+                .ok_or(format!("{} has none of {} attributes", mbean, JMXClient::ACTIVE_THREADS_ATTRIBUTES.join(", ")))?,
+            queue_size: find(&JMXClient::QUEUE_SIZE_ATTRIBUTES),
+            max_threads: find(&JMXClient::MAX_THREADS_ATTRIBUTES),
+        };
+        self.thread_pool_attributes.borrow_mut().insert(mbean.to_owned(), attributes.clone());
+        Ok(attributes)
+    }
+
+    ///
+    /// Lists loggers of Logback and Log4j2 along with their effective levels.
+    ///
+    /// Logback only exposes levels through operations, so this needs a Jolokia connection for it.
+    pub fn get_loggers(&self) -> Result<Vec<JMXLogger>, FetchError> {
+        let mut loggers = vec![];
+        for mbean in self.connection.query_names(JMXClient::LOGBACK_CONFIGURATORS)? {
+            let names: Vec<String> = self.get_attribute(&mbean, "LoggerList")?;
+            for name in names {
+                let level = self.connection.invoke(&mbean, "getLoggerEffectiveLevel", &[Value::String(name.clone())])?;
+                loggers.push(JMXLogger {
+                    name,
+                    level: level.as_str().unwrap_or("").to_owned(),
+                    backend: LoggingBackend::Logback,
+                    mbean: mbean.clone(),
+                });
+            }
+        }
+        for mbean in self.connection.query_names(JMXClient::LOG4J2_LOGGERS)? {
+            let name = JMXClient::key_property(&mbean, "name")
+                .map(|n| n.trim_matches('"').to_owned())
+                .filter(|n| !n.is_empty())
+                .unwrap_or_else(|| "ROOT".to_owned());
+            let level: Option<String> = self.get_attribute(&mbean, "Level")?;
+            loggers.push(JMXLogger {
+                name,
+                level: level.unwrap_or_default(),
+                backend: LoggingBackend::Log4j2,
+                mbean,
+            });
+        }
+        if loggers.is_empty() {
+            return Err(FetchError::from("No Logback JMXConfigurator or Log4j2 logger MBeans found"));
+        }
+        loggers.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(loggers)
+    }
+
+    /// Changes the level of a Logback or Log4j2 logger
+    pub fn set_logger_level(&self, logger: &JMXLogger, level: &str) -> Result<(), FetchError> {
+        match logger.backend {
+            LoggingBackend::Logback => self.connection
+                .invoke(&logger.mbean, "setLoggerLevel", &[Value::String(logger.name.clone()), Value::String(level.to_owned())])
+                .map(|_| ()),
+            LoggingBackend::Log4j2 => self.connection.set_attribute(&logger.mbean, "Level", Value::String(level.to_owned())),
+        }
+    }
+
+    /// Version, uptime, arguments and system properties of the JVM, read from its Runtime MBean
+    pub fn get_jvm_info(&self) -> Result<JvmInfo, FetchError> {
+        let string = |attr: &str| self.get_attribute::<Option<String>>(JMXClient::RUNTIME, attr).map(|x| x.unwrap_or_default());
+        let properties = self.connection.get_attribute(JMXClient::RUNTIME, "SystemProperties")?;
+        Ok(JvmInfo {
+            timestamp: Local::now(),
+            uptime: self.get_attribute(JMXClient::RUNTIME, "Uptime")?,
+            vm_name: string("VmName")?,
+            vm_vendor: string("VmVendor")?,
+            vm_version: string("VmVersion")?,
+            spec_version: string("SpecVersion")?,
+            input_arguments: self.get_attribute(JMXClient::RUNTIME, "InputArguments")?,
+            system_properties: JMXClient::tabular_pairs(&properties),
+        })
+    }
+
+    ///
+    /// Returns collections that happened since the previous call, one per collector at most.
+    ///
+    /// Collectors only expose their last collection, so the ones in between polls are missed. The first call only
+    /// takes note of the collections so far, which happened before monitoring started, and returns none.
+    pub fn get_gc_events(&self) -> Result<Vec<GcEvent>, FetchError> {
+        let mut last_gc_ids = self.last_gc_ids.borrow_mut();
+        let baseline = last_gc_ids.is_none();
+        let last_gc_ids = last_gc_ids.get_or_insert_with(HashMap::new);
+        let mut events = vec![];
+        for mbean in self.connection.query_names(JMXClient::GARBAGE_COLLECTORS)? {
+            let info = self.connection.get_attribute(&mbean, "LastGcInfo")?;
+            let id = match info["id"].as_i64() {
+                Some(id) => id,
+                None => continue,
+            };
+            let collector = JMXClient::key_property(&mbean, "name").unwrap_or_else(|| mbean.clone());
+            if last_gc_ids.insert(collector.clone(), id) == Some(id) || baseline {
+                continue;
+            }
+            let used = |key: &str| -> f64 {
+                JMXClient::tabular_rows(&info[key]).iter().filter_map(|(_, usage)| usage["used"].as_f64()).sum()
+            };
+            events.push(GcEvent {
+                timestamp: Local.timestamp_millis(self.jvm_start()? + info["startTime"].as_i64().unwrap_or(0)),
+                collector,
+                id,
+                duration_ms: info["duration"].as_f64().unwrap_or(0.0),
+                reclaimed: (used("memoryUsageBeforeGc") - used("memoryUsageAfterGc")).max(0.0),
+            });
+        }
+        events.sort_by_key(|e| e.timestamp);
+        Ok(events)
+    }
+
+    /// Start of the JVM, read once since it doesn't change for a connection
+    fn jvm_start(&self) -> Result<i64, FetchError> {
+        match self.jvm_start.get() {
+            Some(start) => Ok(start),
+            None => {
+                let start: i64 = self.get_attribute(JMXClient::RUNTIME, "StartTime")?;
+                self.jvm_start.set(Some(start));
+                Ok(start)
+            }
+        }
+    }
+
+    /// Dumps the heap or runs a garbage collection
+    pub fn invoke_jvm_operation(&self, operation: &JvmOperation) -> Result<(), FetchError> {
+        match operation {
+            // only live objects, which triggers a full GC but keeps dumps smaller
+            JvmOperation::DumpHeap(path) => self.connection
+                .invoke(JMXClient::HOTSPOT_DIAGNOSTIC, "dumpHeap", &[Value::String(path.clone()), Value::Bool(true)]),
+            JvmOperation::Gc => self.connection.invoke(JMXClient::MEMORY, "gc", &[]),
+        }.map(|_| ())
+    }
+
+    ///
+    /// Rows of a key-value `TabularData` value, like `SystemProperties`.
+    ///
+    /// Jolokia serializes it as an object indexed by keys, RMI as a list of rows.
+    fn tabular_rows(value: &Value) -> Vec<(String, &Value)> {
+        match value {
+            Value::Object(rows) => rows.iter()
+                .map(|(k, r)| match r {
+                    Value::Object(_) => (k.to_owned(), &r["value"]),
+                    r => (k.to_owned(), r),
+                })
+                .collect(),
+            Value::Array(rows) => rows.iter()
+                .filter_map(|r| r["key"].as_str().map(|k| (k.to_owned(), &r["value"])))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Rows of a `TabularData` with string values, sorted by key
+    fn tabular_pairs(value: &Value) -> Vec<(String, String)> {
+        let mut pairs: Vec<(String, String)> = JMXClient::tabular_rows(value).into_iter()
+            .filter_map(|(k, v)| v.as_str().map(|v| (k, v.to_owned())))
+            .collect();
+        pairs.sort();
+        pairs
+    }
+
+    /// Value of a key property of an object name, e.g. `client-id` of `kafka.consumer:type=x,client-id=y`
+    fn key_property(object_name: &str, key: &str) -> Option<String> {
+        object_name.split_once(':').map(|x| x.1).and_then(|props| {
+            props.split(',')
+                .filter_map(|p| {
+                    let mut kv = p.splitn(2, '=');
+                    match (kv.next(), kv.next()) {
+                        (Some(k), Some(v)) if k == key => Some(v.to_owned()),
+                        _ => None,
+                    }
+                })
+                .next()
+        })
+    }
+
+    /// Numeric metrics, like kafka ones, are doubles, which are NaN (serialized as null or "NaN") until there's data
+    fn get_metric(&self, mbean: &str, attr: &str) -> Result<f64, FetchError> {
+        let value = self.connection.get_attribute(mbean, attr)?;
+        Ok(value.as_f64().filter(|v| v.is_finite()).unwrap_or(0.0))
+    }
+
+    fn subscribe_notifications(&self, patterns: &[String]) -> Result<(), FetchError> {
+        for pattern in patterns {
+            let names = self.connection.query_names(pattern)?;
+            if names.is_empty() {
+                return Err(FetchError::from(format!("No MBeans match {} to subscribe for notifications", pattern)));
+            }
+            for name in names {
+                self.connection.subscribe(&name)
+                    .map_err(|e| format!("Couldn't subscribe for notifications from {}: {}", name, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Notifications received since the previous call
+    pub fn get_notifications(&self) -> Result<Vec<JMXNotification>, FetchError> {
+        self.connection.pull_notifications()
+    }
+
+    /// Soft-evicts the connections of the HikariCP pool, or suspends or resumes it
+    pub fn invoke_hikari_operation(&self, operation: HikariOperation) -> Result<(), FetchError> {
+        self.connection.invoke(&self.hikari_bean(), operation.name(), &[]).map(|_| ())
+    }
+
+    /// Membership of the Akka Cluster the app is a member of, as reported by its `akka:type=Cluster` MBean
+    pub fn get_cluster_status(&self) -> Result<AkkaClusterStatus, FetchError> {
+        let status: String = self.get_attribute("akka:type=Cluster", "ClusterStatus")?;
+        let json: Value = serde_json::from_str(&status).map_err(|e| format!("Unexpected cluster status: {}", e))?;
+        Ok(JMXClient::parse_cluster_status(&json))
+    }
+
+    ///
+    /// Parses the cluster status JSON of Akka, e.g.
+    ///
+    /// {"self-address": "akka://app@10.0.0.1:2552", "members": [{"address": "akka://app@10.0.0.1:2552", "roles": ["dc-default"], "status": "Up"}],
+    ///  "unreachable": [{"node": "akka://app@10.0.0.2:2552", "observed-by": ["akka://app@10.0.0.1:2552"]}]}
+    fn parse_cluster_status(json: &Value) -> AkkaClusterStatus {
+        let text = |v: &Value| v.as_str().unwrap_or("-").to_owned();
+        let texts = |v: &Value| v.as_array().map_or(vec![], |a| a.iter().map(text).collect());
+        let entries = |key: &str| json[key].as_array().cloned().unwrap_or_default();
+        AkkaClusterStatus {
+            timestamp: Local::now(),
+            self_address: text(&json["self-address"]),
+            members: entries("members").iter()
+                .map(|m| ClusterMember { address: text(&m["address"]), status: text(&m["status"]), roles: texts(&m["roles"]) })
+                .collect(),
+            unreachable: entries("unreachable").iter()
+                .map(|u| UnreachableMember { node: text(&u["node"]), observed_by: texts(&u["observed-by"]) })
+                .collect(),
+        }
+    }
+
+    fn get_attribute<T: DeserializeOwned>(&self, mbean: &str, attr: &str) -> Result<T, FetchError> {
+        let value = self.connection.get_attribute(mbean, attr)?;
+        serde_json::from_value(value)
+            .map_err(|e| FetchError::from(format!("Unexpected value of {} attribute of {}: {}", attr, mbean, e)))
+    }
+
+    fn get_slick_attribute<T: DeserializeOwned>(&self, attr: &str) -> Result<T, FetchError> {
+        self.get_attribute(&format!("slick:type=AsyncExecutor,name={}", self.db_pool_name), attr)
+    }
+
+    fn get_hikari_attribute<T: DeserializeOwned>(&self, attr: &str) -> Result<T, FetchError> {
+        self.get_attribute(&self.hikari_bean(), attr)
+    }
+
+    fn hikari_bean(&self) -> String {
+        format!("com.zaxxer.hikari:type=Pool ({})", self.db_pool_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use serde_json::{json, Value};
+
+    use crate::error::FetchError;
+    use crate::jmx::client::{JMXClient, MBeanConnection};
+
+    struct StubConnection {
+        attributes: HashMap<(String, String), Value>,
+    }
+
+    impl MBeanConnection for StubConnection {
+        fn get_attribute(&self, mbean: &str, attribute: &str) -> Result<Value, FetchError> {
+            self.attributes.get(&(mbean.to_owned(), attribute.to_owned()))
+                .cloned()
+                .ok_or_else(|| FetchError::from(format!("No attribute {} in {}", attribute, mbean)))
+        }
+
+        fn query_names(&self, pattern: &str) -> Result<Vec<String>, FetchError> {
+            let prefix = pattern.trim_end_matches('*');
+            let mut names: Vec<String> = self.attributes.keys()
+                .map(|(mbean, _)| mbean.to_owned())
+                .filter(|mbean| mbean.starts_with(prefix))
+                .collect();
+            names.sort();
+            names.dedup();
+            Ok(names)
+        }
+    }
+
+    #[test]
+    fn cluster_status_is_read_from_akka_mbean() {
+        let status = json!({
+            "self-address": "akka://app@10.0.0.1:2552",
+            "members": [
+                {"address": "akka://app@10.0.0.1:2552", "roles": ["dc-default", "backend"], "status": "Up"},
+                {"address": "akka://app@10.0.0.2:2552", "roles": ["dc-default"], "status": "Leaving"}
+            ],
+            "unreachable": [{"node": "akka://app@10.0.0.2:2552", "observed-by": ["akka://app@10.0.0.1:2552"]}]
+        });
+        let attributes = vec![
+            (("akka:type=Cluster".to_owned(), "ClusterStatus".to_owned()), Value::String(status.to_string())),
+        ].into_iter().collect();
+        let client = JMXClient::new(Box::new(StubConnection { attributes }), "".to_owned());
+
+        let status = client.get_cluster_status().unwrap();
+        assert_eq!(status.self_address, "akka://app@10.0.0.1:2552");
+        assert_eq!(status.members.len(), 2);
+        assert_eq!((status.members[1].status.as_str(), status.members[0].roles.len()), ("Leaving", 2));
+        assert_eq!(status.unreachable[0].observed_by, vec!["akka://app@10.0.0.1:2552".to_owned()]);
+    }
+
+    #[test]
+    fn key_property_is_extracted_from_object_name() {
+        let name = "kafka.consumer:type=consumer-fetch-manager-metrics,client-id=consumer-1";
+        assert_eq!(JMXClient::key_property(name, "client-id"), Some("consumer-1".to_owned()));
+        assert_eq!(JMXClient::key_property(name, "topic"), None);
+    }
+
+    #[test]
+    fn hikari_pool_name_is_extracted_from_object_name() {
+        assert_eq!(JMXClient::hikari_pool_name("com.zaxxer.hikari:type=Pool (db)"), Some("db".to_owned()));
+        assert_eq!(JMXClient::hikari_pool_name("com.zaxxer.hikari:type=PoolConfig (db)"), None);
+    }
+
+    #[test]
+    fn system_properties_are_read_from_jolokia_and_rmi_format() {
+        let jolokia = json!({
+            "java.version": {"key": "java.version", "value": "11.0.7"},
+            "file.encoding": {"key": "file.encoding", "value": "UTF-8"}
+        });
+        let rmi = json!([
+            {"key": "java.version", "value": "11.0.7"},
+            {"key": "file.encoding", "value": "UTF-8"}
+        ]);
+        let expected = vec![
+            ("file.encoding".to_owned(), "UTF-8".to_owned()),
+            ("java.version".to_owned(), "11.0.7".to_owned()),
+        ];
+        assert_eq!(JMXClient::tabular_pairs(&jolokia), expected);
+        assert_eq!(JMXClient::tabular_pairs(&rmi), expected);
+    }
+
+    #[test]
+    fn gc_events_are_reported_once_per_collection() {
+        let young = "java.lang:type=GarbageCollector,name=G1 Young Generation";
+        let gc_info = |id: i64| json!({
+            "id": id,
+            "startTime": 5000,
+            "duration": 12,
+            "memoryUsageBeforeGc": {"G1 Eden Space": {"key": "G1 Eden Space", "value": {"used": 3000}}},
+            "memoryUsageAfterGc": {"G1 Eden Space": {"key": "G1 Eden Space", "value": {"used": 1000}}}
+        });
+        let mut attributes: HashMap<(String, String), Value> = vec![
+            (("java.lang:type=Runtime".to_owned(), "StartTime".to_owned()), json!(1590000000000i64)),
+            ((young.to_owned(), "LastGcInfo".to_owned()), gc_info(7)),
+            (("java.lang:type=GarbageCollector,name=G1 Old Generation".to_owned(), "LastGcInfo".to_owned()), Value::Null),
+        ].into_iter().collect();
+        let client = JMXClient::new(Box::new(StubConnection { attributes: attributes.clone() }), "".to_owned());
+
+        assert!(client.get_gc_events().unwrap().is_empty());
+        assert!(client.get_gc_events().unwrap().is_empty());
+
+        attributes.insert((young.to_owned(), "LastGcInfo".to_owned()), gc_info(8));
+        let client = JMXClient { connection: Box::new(StubConnection { attributes: attributes.clone() }), ..client };
+        let events = client.get_gc_events().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!((events[0].collector.as_str(), events[0].id), ("G1 Young Generation", 8));
+        assert_eq!((events[0].duration_ms, events[0].reclaimed), (12.0, 2000.0));
+        assert_eq!(events[0].timestamp.timestamp_millis(), 1590000005000);
+        assert!(client.get_gc_events().unwrap().is_empty());
+
+        // the start time is only read once
+        attributes.remove(&("java.lang:type=Runtime".to_owned(), "StartTime".to_owned()));
+        attributes.insert((young.to_owned(), "LastGcInfo".to_owned()), gc_info(9));
+        let client = JMXClient { connection: Box::new(StubConnection { attributes }), ..client };
+        assert_eq!(client.get_gc_events().unwrap()[0].id, 9);
+    }
+
+    #[test]
+    fn thread_pool_attributes_are_resolved_per_bean() {
+        let tomcat = "Catalina:type=ThreadPool,name=\"http-nio-8080\"";
+        let attributes = vec![
+            ((tomcat.to_owned(), "currentThreadsBusy".to_owned()), json!(3)),
+            ((tomcat.to_owned(), "maxThreads".to_owned()), json!(200)),
+        ];
+        let connection = StubConnection { attributes: attributes.into_iter().collect() };
+        let client = JMXClient::new(Box::new(connection), "".to_owned());
+
+        let metrics = client.get_thread_pool_metrics(&["Catalina:type=ThreadPool,*".to_owned()]).unwrap();
+        assert_eq!(metrics.pools.len(), 1);
+        assert_eq!(metrics.pools[0].name, "http-nio-8080");
+        assert_eq!(metrics.pools[0].active_threads, 3.0);
+        assert_eq!(metrics.pools[0].queue_size, None);
+        assert_eq!(metrics.pools[0].max_threads, Some(200.0));
+    }
+}
@@ -0,0 +1,232 @@
+use std::convert::TryInto;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+const MAGIC: u32 = 0xcafe_c0c0;
+const JAVA_COMMAND: &str = "sun.rt.javaCommand";
+const VM_ARGS: &str = "java.rt.vmArgs";
+const LOCAL_CONNECTOR_ADDRESS: &str = "sun.management.JMXConnectorServer.address";
+const REMOTE_PORT_ARG: &str = "-Dcom.sun.management.jmxremote.port=";
+
+/// A JVM running on this machine, as listed by `jps`
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocalJvm {
+    /// Process id
+    pub pid: u32,
+    /// Main class or jar, with the program arguments
+    pub command: String,
+    /// Service url of the local JMX connector, once the management agent has been started
+    pub local_address: Option<String>,
+    /// Port of the remote JMX connector, if the JVM was started with one
+    pub remote_port: Option<u16>,
+}
+
+impl LocalJvm {
+    /// JMX address to connect to, if the JVM has a connector
+    pub fn jmx_address(&self) -> Option<String> {
+        self.local_address.clone().or_else(|| self.remote_port.map(|p| format!("localhost:{}", p)))
+    }
+}
+
+///
+/// Lists local JVMs from their hsperfdata files, like `jps` does.
+///
+/// JVMs publish their performance counters, including their command line, in a memory mapped file
+/// at `<tmp>/hsperfdata_<user>/<pid>`, unless started with -XX:-UsePerfData.
+pub fn local_jvms() -> Vec<LocalJvm> {
+    let mut jvms: Vec<LocalJvm> = perf_data_dirs().iter()
+        .filter_map(|d| fs::read_dir(d).ok())
+        .flat_map(|d| d.filter_map(|e| e.ok()).map(|e| e.path()))
+        .filter_map(|p| read_jvm(&p))
+        .collect();
+    jvms.sort_by_key(|j| j.pid);
+    jvms
+}
+
+/// Where JVMs keep their perf data, which on Linux is always /tmp, whatever TMPDIR says
+#[cfg(target_os = "linux")]
+fn temp_dir() -> PathBuf {
+    PathBuf::from("/tmp")
+}
+
+#[cfg(not(target_os = "linux"))]
+fn temp_dir() -> PathBuf {
+    std::env::temp_dir()
+}
+
+/// A directory per user running JVMs
+fn perf_data_dirs() -> Vec<PathBuf> {
+    fs::read_dir(temp_dir()).map_or(vec![], |d| d
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_name().to_string_lossy().starts_with("hsperfdata_"))
+        .map(|e| e.path())
+        .collect())
+}
+
+fn read_jvm(path: &Path) -> Option<LocalJvm> {
+    let pid: u32 = path.file_name()?.to_str()?.parse().ok()?;
+    // files of JVMs that were killed are left behind
+    if !is_running(pid) {
+        return None;
+    }
+    let counters = parse_perf_data(&fs::read(path).ok()?)?;
+    let counter = |name: &str| counters.iter().find(|(n, _)| n == name).map(|(_, v)| v.to_owned());
+    Some(LocalJvm {
+        pid,
+        command: counter(JAVA_COMMAND).unwrap_or_default(),
+        local_address: counter(LOCAL_CONNECTOR_ADDRESS),
+        remote_port: counter(VM_ARGS).and_then(|args| args.split_whitespace()
+            .find_map(|a| a.strip_prefix(REMOTE_PORT_ARG).and_then(|p| p.parse().ok()))),
+    })
+}
+
+#[cfg(target_os = "linux")]
+fn is_running(pid: u32) -> bool {
+    Path::new(&format!("/proc/{}", pid)).exists()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn is_running(pid: u32) -> bool {
+    Command::new("kill").args(["-0", &pid.to_string()]).output().is_ok_and(|o| o.status.success())
+}
+
+///
+/// String counters of an hsperfdata file, by name.
+///
+/// The file starts with a prologue giving the byte order and the offset and number of entries,
+/// every entry then gives its own length and the offsets of its name and data.
+fn parse_perf_data(data: &[u8]) -> Option<Vec<(String, String)>> {
+    if u32::from_be_bytes(data.get(0..4)?.try_into().ok()?) != MAGIC {
+        return None;
+    }
+    let big_endian = *data.get(4)? == 0;
+    let int = |offset: usize| -> Option<usize> {
+        let bytes: [u8; 4] = data.get(offset..offset + 4)?.try_into().ok()?;
+        let i = if big_endian { i32::from_be_bytes(bytes) } else { i32::from_le_bytes(bytes) };
+        i.try_into().ok()
+    };
+    let c_string = |bytes: &[u8]| String::from_utf8_lossy(bytes.split(|b| *b == 0).next().unwrap_or_default()).into_owned();
+
+    let mut counters = vec![];
+    let mut entry = int(24)?;
+    for _ in 0..int(28)? {
+        let length = int(entry)?;
+        let vector_length = int(entry + 8)?;
+        let data_type = *data.get(entry + 12)?;
+        // strings are byte vectors, longs aren't needed
+        if vector_length > 0 && data_type == b'B' {
+            let name = c_string(data.get(entry + int(entry + 4)?..entry + length)?);
+            let start = entry + int(entry + 16)?;
+            counters.push((name, c_string(data.get(start..start + vector_length)?)));
+        }
+        if length == 0 {
+            break;
+        }
+        entry += length;
+    }
+    Some(counters)
+}
+
+/// Starts the local JMX connector of a JVM with `jcmd`, returning its address
+fn start_local_agent(jvm: &LocalJvm) -> Result<String, String> {
+    let output = Command::new("jcmd")
+        .args([&jvm.pid.to_string(), "ManagementAgent.start_local"])
+        .output()
+        .map_err(|e| format!("Couldn't run jcmd, it comes with the JDK: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("jcmd failed: {}", String::from_utf8_lossy(&output.stdout).trim()));
+    }
+    // the counter shows up once the connector is listening
+    let path = perf_data_dirs().into_iter()
+        .map(|d| d.join(jvm.pid.to_string()))
+        .find(|p| p.exists())
+        .ok_or("The JVM is gone")?;
+    for _ in 0..20 {
+        if let Some(address) = read_jvm(&path).and_then(|j| j.local_address) {
+            return Ok(address);
+        }
+        thread::sleep(Duration::from_millis(100));
+    }
+    Err(format!("The local JMX connector of {} didn't start", jvm.pid))
+}
+
+///
+/// Lists local JVMs and asks which one to monitor, returning its JMX address.
+///
+/// JVMs without a JMX connector get their local one started, which is only reachable from the same user.
+/// Returns `None` if there are no local JVMs. Without a terminal to ask on, i.e. unless `interactive`, JVMs are
+/// only listed in the error.
+pub fn pick_local_jvm(interactive: bool) -> Result<Option<String>, String> {
+    let jvms: Vec<LocalJvm> = local_jvms().into_iter().filter(|j| j.pid != std::process::id()).collect();
+    if jvms.is_empty() {
+        return Ok(None);
+    }
+    if !interactive {
+        let listed: Vec<String> = jvms.iter()
+            .map(|j| format!("{} {} [{}]", j.pid, j.command, j.jmx_address().unwrap_or_else(|| "no JMX connector yet".to_owned())))
+            .collect();
+        return Err(format!("Found local JVMs, pass the address of one with --jmx: {}", listed.join(", ")));
+    }
+    println!("Local JVMs:\n");
+    for (i, j) in jvms.iter().enumerate() {
+        let address = j.jmx_address().unwrap_or_else(|| "no JMX connector yet".to_owned());
+        println!("{:>3}) {:<8} {} [{}]", i + 1, j.pid, j.command, address);
+    }
+    let stdin = io::stdin();
+    let jvm = loop {
+        print!("\nPick a JVM to monitor over JMX (1-{}): ", jvms.len());
+        io::stdout().flush().map_err(|e| e.to_string())?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).map_err(|e| e.to_string())? == 0 {
+            return Err("No JVM picked".to_owned());
+        }
+        match line.trim().parse::<usize>() {
+            Ok(i) if i >= 1 && i <= jvms.len() => break &jvms[i - 1],
+            _ => println!("Expected a number between 1 and {}", jvms.len()),
+        }
+    };
+    match jvm.jmx_address() {
+        Some(address) => Ok(Some(address)),
+        None => start_local_agent(jvm).map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jmx::discovery::parse_perf_data;
+
+    fn entry(name: &str, value: &str) -> Vec<u8> {
+        let name_offset = 20;
+        let data_offset = name_offset + name.len() + 1;
+        let length = data_offset + value.len() + 1;
+        let mut e = vec![];
+        e.extend_from_slice(&(length as i32).to_le_bytes());
+        e.extend_from_slice(&(name_offset as i32).to_le_bytes());
+        e.extend_from_slice(&((value.len() + 1) as i32).to_le_bytes());
+        e.extend_from_slice(&[b'B', 0, 0, 0]);
+        e.extend_from_slice(&(data_offset as i32).to_le_bytes());
+        e.extend_from_slice(name.as_bytes());
+        e.push(0);
+        e.extend_from_slice(value.as_bytes());
+        e.push(0);
+        e
+    }
+
+    #[test]
+    fn string_counters_are_read_from_perf_data() {
+        let mut data = vec![0xca, 0xfe, 0xc0, 0xc0, 1, 2, 0, 1];
+        data.extend_from_slice(&[0; 16]);
+        data.extend_from_slice(&32i32.to_le_bytes());
+        data.extend_from_slice(&2i32.to_le_bytes());
+        data.extend(entry("sun.rt.javaCommand", "com.example.Main --port 8080"));
+        data.extend(entry("java.rt.vmArgs", "-Xmx1g -Dcom.sun.management.jmxremote.port=9010"));
+        let counters = parse_perf_data(&data).unwrap();
+        assert_eq!(counters[0], ("sun.rt.javaCommand".to_owned(), "com.example.Main --port 8080".to_owned()));
+        assert_eq!(counters[1].1, "-Xmx1g -Dcom.sun.management.jmxremote.port=9010");
+        assert_eq!(parse_perf_data(&[0; 32]), None);
+    }
+}
@@ -0,0 +1,285 @@
+use std::cell::RefCell;
+use std::fs;
+
+use chrono::{Local, TimeZone};
+
+use reqwest::{Certificate, Identity};
+use serde_json::{json, Value};
+use tokio::runtime::Runtime;
+
+use crate::error::{FetchError, FetchErrorKind};
+use crate::jmx::client::MBeanConnection;
+use crate::jmx::model::{JMXConnectionSettings, JMXNotification, JMXSslSettings};
+
+///
+/// MBean server connection over Jolokia's HTTP/JSON protocol.
+///
+/// Requires a Jolokia agent attached to the target JVM, but doesn't need java on panopticon's side.
+/// The HTTP client and its runtime are kept, so that requests reuse the connection and its TLS session.
+pub struct JolokiaConnection {
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+    client: reqwest::Client,
+    runtime: RefCell<Runtime>,
+    // notification client id and handles of pull-mode listeners, registered lazily
+    notification_client: RefCell<Option<String>>,
+    notification_handles: RefCell<Vec<String>>,
+}
+
+impl JolokiaConnection {
+    /// A connection to the Jolokia agent at the address of the settings, with their credentials and TLS settings
+    pub fn new(settings: &JMXConnectionSettings) -> Result<JolokiaConnection, FetchError> {
+        let mut builder = reqwest::Client::builder();
+        if let Some(ssl) = &settings.ssl {
+            if !settings.address.starts_with("https://") {
+                return Err(FetchError::from(format!("Jolokia address {} must be an https:// url to use ssl", settings.address)));
+            }
+            let (root_certificate, identity) = JolokiaConnection::load_tls(ssl)?;
+            if let Some(certificate) = root_certificate {
+                builder = builder.add_root_certificate(certificate);
+            }
+            if let Some(identity) = identity {
+                builder = builder.identity(identity);
+            }
+        }
+        let runtime = tokio::runtime::Builder::new().basic_scheduler().enable_all().build()?;
+        let connection = JolokiaConnection {
+            url: settings.address.clone(),
+            username: settings.username.clone(),
+            password: settings.password.clone(),
+            client: builder.build()?,
+            runtime: RefCell::new(runtime),
+            notification_client: RefCell::new(None),
+            notification_handles: RefCell::new(vec![]),
+        };
+        // fail early, the same way an RMI connection would
+        connection.request(json!({ "type": "version" }))
+            .map_err(|e| e.context(&format!("Couldn't connect to jolokia at {}", connection.url)))?;
+        Ok(connection)
+    }
+
+    fn notification_client(&self) -> Result<String, FetchError> {
+        if let Some(id) = self.notification_client.borrow().as_ref() {
+            return Ok(id.clone());
+        }
+        let registration = self.request(json!({ "type": "notification", "command": "register" }))?;
+        let id = registration.get("id")
+            .and_then(|id| id.as_str())
+            .ok_or("Jolokia didn't return a notification client id")?
+            .to_owned();
+        *self.notification_client.borrow_mut() = Some(id.clone());
+        Ok(id)
+    }
+
+    fn load_tls(ssl: &JMXSslSettings) -> Result<(Option<Certificate>, Option<Identity>), String> {
+        let root_certificate = match &ssl.truststore {
+            Some(path) => {
+                let pem = fs::read(path).map_err(|e| format!("Couldn't read truststore {}: {}", path, e))?;
+                Some(Certificate::from_pem(&pem).map_err(|e| format!("Invalid PEM certificate in {}: {}", path, e))?)
+            }
+            None => None,
+        };
+        let identity = match &ssl.keystore {
+            Some(path) => {
+                let der = fs::read(path).map_err(|e| format!("Couldn't read keystore {}: {}", path, e))?;
+                let password = ssl.keystore_password.clone().unwrap_or_default();
+                Some(Identity::from_pkcs12_der(&der, &password).map_err(|e| format!("Invalid PKCS12 keystore {}: {}", path, e))?)
+            }
+            None => None,
+        };
+        Ok((root_certificate, identity))
+    }
+
+    fn request(&self, body: Value) -> Result<Value, FetchError> {
+        self.runtime.borrow_mut().block_on(self.send(body))
+    }
+
+    async fn send(&self, body: Value) -> Result<Value, FetchError> {
+        let mut request = self.client.post(&self.url).json(&body);
+        if let Some(username) = &self.username {
+            request = request.basic_auth(username, self.password.as_ref());
+        }
+        let response = request.send().await?;
+        if !response.status().is_success() {
+            return Err(FetchError::of_status(response.status(), format!("Jolokia request failed with status: {}", response.status())));
+        }
+        let response_body: Value = response.json().await?;
+        parse_response(response_body)
+    }
+}
+
+impl MBeanConnection for JolokiaConnection {
+    fn get_attribute(&self, mbean: &str, attribute: &str) -> Result<Value, FetchError> {
+        self.request(json!({ "type": "read", "mbean": mbean, "attribute": attribute }))
+    }
+
+    fn query_names(&self, pattern: &str) -> Result<Vec<String>, FetchError> {
+        let names = self.request(json!({ "type": "search", "mbean": pattern }))?;
+        serde_json::from_value(names).map_err(|e| FetchError::from(e.to_string()))
+    }
+
+    fn invoke(&self, mbean: &str, operation: &str, arguments: &[Value]) -> Result<Value, FetchError> {
+        self.request(json!({ "type": "exec", "mbean": mbean, "operation": operation, "arguments": arguments }))
+    }
+
+    fn set_attribute(&self, mbean: &str, attribute: &str, value: Value) -> Result<(), FetchError> {
+        self.request(json!({ "type": "write", "mbean": mbean, "attribute": attribute, "value": value }))
+            .map(|_| ())
+    }
+
+    fn subscribe(&self, mbean: &str) -> Result<(), FetchError> {
+        let client = self.notification_client()?;
+        let handle = self.request(json!({
+            "type": "notification",
+            "command": "add",
+            "client": client,
+            "mode": "pull",
+            "mbean": mbean
+        }))?;
+        let handle = handle.as_str().ok_or("Jolokia didn't return a notification handle")?;
+        self.notification_handles.borrow_mut().push(handle.to_owned());
+        Ok(())
+    }
+
+    fn pull_notifications(&self) -> Result<Vec<JMXNotification>, FetchError> {
+        let client = self.notification_client()?;
+        let mut notifications = vec![];
+        for handle in self.notification_handles.borrow().iter() {
+            let pulled = self.request(json!({
+                "type": "exec",
+                "mbean": "jolokia:type=NotificationStore",
+                "operation": "pull",
+                "arguments": [client, handle]
+            }))?;
+            notifications.append(&mut parse_notifications(&pulled));
+        }
+        notifications.sort_by_key(|n| n.timestamp);
+        Ok(notifications)
+    }
+}
+
+/// Jolokia reports failures with HTTP 200 and an error status inside the body.
+fn parse_response(body: Value) -> Result<Value, FetchError> {
+    match body.get("status").and_then(|s| s.as_u64()) {
+        Some(200) => Ok(body.get("value").cloned().unwrap_or(Value::Null)),
+        status => {
+            let message = format!(
+                "Jolokia responded with status {}: {}",
+                status.map_or("<none>".to_owned(), |s| s.to_string()),
+                body.get("error").and_then(|e| e.as_str()).unwrap_or("unknown error")
+            );
+            // the agent's access policy turns requests down with these
+            let kind = if matches!(status, Some(401) | Some(403)) { FetchErrorKind::Auth } else { FetchErrorKind::Other };
+            Err(FetchError::new(kind, message))
+        }
+    }
+}
+
+fn parse_notifications(pulled: &Value) -> Vec<JMXNotification> {
+    let text = |n: &Value, field: &str| n.get(field).map_or("".to_owned(), |v| match v {
+        Value::String(s) => s.to_owned(),
+        Value::Null => "".to_owned(),
+        other => other.to_string(),
+    });
+    pulled.get("notifications")
+        .and_then(|n| n.as_array())
+        .map_or(vec![], |ns| ns.iter().map(|n| JMXNotification {
+            timestamp: n.get("timeStamp")
+                .and_then(|t| t.as_i64())
+                .map_or(Local::now(), |t| Local.timestamp_millis(t)),
+            source: n.get("source")
+                .and_then(|s| s.get("objectName"))
+                .and_then(|s| s.as_str())
+                .map_or(text(n, "source"), |s| s.to_owned()),
+            notification_type: text(n, "type"),
+            message: text(n, "message"),
+        }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::error::FetchError;
+    use crate::jmx::jolokia::{JolokiaConnection, parse_notifications, parse_response};
+    use crate::jmx::model::{HikariAlertSettings, JMXConnectionSettings, JMXSslSettings};
+
+    fn ssl_settings(truststore: &str) -> JMXSslSettings {
+        JMXSslSettings { truststore: Some(truststore.to_owned()), truststore_password: None, keystore: None, keystore_password: None }
+    }
+
+    #[test]
+    fn jolokia_extracts_value() {
+        let body = json!({ "request": {}, "value": 42, "timestamp": 1590000000, "status": 200 });
+        assert_eq!(parse_response(body), Ok(json!(42)));
+    }
+
+    #[test]
+    fn jolokia_reports_error() {
+        let body = json!({ "error_type": "javax.management.InstanceNotFoundException", "error": "slick:type=AsyncExecutor,name=db", "status": 404 });
+        assert_eq!(
+            parse_response(body),
+            Err(FetchError::from("Jolokia responded with status 404: slick:type=AsyncExecutor,name=db"))
+        );
+    }
+
+    #[test]
+    fn jolokia_parses_pulled_notifications() {
+        let pulled = json!({
+            "dropped": 0,
+            "handle": "1",
+            "notifications": [{
+                "type": "com.sun.management.gc.notification",
+                "message": "end of minor GC",
+                "timeStamp": 1590000000000i64,
+                "source": { "objectName": "java.lang:type=GarbageCollector,name=G1 Young Generation" }
+            }]
+        });
+        let parsed = parse_notifications(&pulled);
+        assert_eq!(parsed.len(), 1);
+        assert_eq!(parsed[0].notification_type, "com.sun.management.gc.notification");
+        assert_eq!(parsed[0].message, "end of minor GC");
+        assert_eq!(parsed[0].source, "java.lang:type=GarbageCollector,name=G1 Young Generation");
+        assert_eq!(parsed[0].timestamp.timestamp_millis(), 1590000000000);
+    }
+
+    #[test]
+    fn jolokia_reports_unreadable_tls_files() {
+        let error = JolokiaConnection::load_tls(&ssl_settings("/nonexistent/truststore.pem")).err().unwrap();
+        assert!(error.starts_with("Couldn't read truststore /nonexistent/truststore.pem: "), "{}", error);
+
+        let path = std::env::temp_dir().join(format!("panopticon-truststore-{}.pem", std::process::id()));
+        std::fs::write(&path, "not a certificate").unwrap();
+        let error = JolokiaConnection::load_tls(&ssl_settings(&path.display().to_string())).err().unwrap();
+        assert!(error.starts_with(&format!("Invalid PEM certificate in {}: ", path.display())), "{}", error);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn jolokia_requires_https_for_ssl() {
+        let settings = JMXConnectionSettings {
+            address: "http://localhost:8778/jolokia".to_owned(),
+            username: None,
+            password: None,
+            db_pool_name: None,
+            kafka: false,
+            cassandra_domain: None,
+            akka_dispatchers: None,
+            loggers: false,
+            thread_pools: vec![],
+            jvm_info: false,
+            akka_cluster: false,
+            heap_dump_dir: "/tmp".to_owned(),
+            gc_pause_threshold: 200,
+            ssl: Some(ssl_settings("/nonexistent/truststore.pem")),
+            notifications: vec![],
+            allow_operations: false,
+            hikari_alerts: HikariAlertSettings { exhausted_ticks: 2, waiting_threshold: 5 },
+        };
+        assert_eq!(
+            JolokiaConnection::new(&settings).err(),
+            Some(FetchError::from("Jolokia address http://localhost:8778/jolokia must be an https:// url to use ssl"))
+        );
+    }
+}
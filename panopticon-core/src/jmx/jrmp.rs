@@ -0,0 +1,336 @@
+use std::cell::RefCell;
+use std::io::{BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+
+use crate::error::FetchError;
+use crate::jmx::client::MBeanConnection;
+use crate::jmx::model::JMXConnectionSettings;
+use crate::jmx::serialization::{exception_message, JavaValue, ObjectReader, ObjectWriter, SC_SERIALIZABLE, SC_WRITE_METHOD, to_json};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_REGISTRY_PORT: u16 = 1099;
+const DEFAULT_NAME: &str = "jmxrmi";
+
+const MAGIC: &[u8] = b"JRMI";
+const VERSION: u16 = 2;
+const STREAM_PROTOCOL: u8 = 0x4b;
+const PROTOCOL_ACK: u8 = 0x4e;
+const CALL: u8 = 0x50;
+const RETURN_DATA: u8 = 0x51;
+const DGC_ACK: u8 = 0x54;
+const NORMAL_RETURN: u8 = 1;
+
+/// Ids of the objects every RMI runtime exports
+const REGISTRY_ID: i64 = 0;
+const DGC_ID: i64 = 2;
+
+/// Stubs of the registry and the DGC call operations by number, the hash being of their interface
+const REGISTRY_INTERFACE_HASH: i64 = 4905912898345647071;
+const REGISTRY_LOOKUP: i32 = 2;
+const DGC_INTERFACE_HASH: i64 = -669196253586618813;
+const DGC_DIRTY: i32 = 1;
+
+/// Other stubs call methods by the hash of their signature, with -1 as the operation
+const METHOD_HASH: i32 = -1;
+/// `RMIServer.newClient(Object)`
+const NEW_CLIENT: i64 = -1089742558549201240;
+/// `RMIConnection.getAttribute(ObjectName, String, Subject)`
+const GET_ATTRIBUTE: i64 = -1089783104982388203;
+/// `RMIConnection.queryNames(ObjectName, MarshalledObject, Subject)`
+const QUERY_NAMES: i64 = 9152567528369059802;
+
+/// Lease asked for the connection object of the server, renewed half way through as Java clients do
+const LEASE: Duration = Duration::from_secs(600);
+
+///
+/// MBean server connection over RMI, speaking JRMP, the RMI wire protocol, without a JVM.
+///
+/// Only reads MBeans: attributes are read with `RMIConnection.getAttribute` and names listed with `queryNames`,
+/// the values coming back as serialized Java objects read as JSON. The connection object the server creates
+/// for this client is kept alive with leases of the distributed garbage collector, like Java clients do.
+///
+/// Connectors exported with socket factories, e.g. over TLS, aren't supported, those need the JVM of the `jvm` feature.
+pub struct JrmpConnection {
+    address: String,
+    channel: RefCell<Channel>,
+    connection: RemoteRef,
+    lease_renewed: RefCell<Instant>,
+}
+
+/// Where a remote object lives and its id there
+#[derive(Clone, Debug, PartialEq)]
+struct RemoteRef {
+    host: String,
+    port: u16,
+    /// Number and address space of the `ObjID`
+    object: i64,
+    space: (i32, i64, i16),
+}
+
+impl JrmpConnection {
+    /// Connects to the RMI connector of the settings, with their credentials if given
+    pub fn new(settings: &JMXConnectionSettings) -> Result<JrmpConnection, FetchError> {
+        let address = settings.address.clone();
+        let connect_error = |e: FetchError| e.context(&format!("Couldn't connect to jmx at {}", address));
+        if settings.ssl.is_some() {
+            return Err(FetchError::from(format!(
+                "Can't connect to {} over RMI with TLS: panopticon was built without the `jvm` feature", address
+            )));
+        }
+        let server = server_ref(&address).map_err(connect_error)?;
+        let mut channel = Channel::open(&server.host, server.port).map_err(connect_error)?;
+        let mut call = channel.call_header(&server, METHOD_HASH, NEW_CLIENT);
+        match (&settings.username, &settings.password) {
+            (Some(username), password) => {
+                call.array("[Ljava.lang.String;", -5921575005990323385, 2)
+                    .string(username)
+                    .string(password.as_deref().unwrap_or_default());
+            }
+            (None, _) => {
+                call.null();
+            }
+        }
+        let connection = channel.call(call).map_err(connect_error).and_then(|c| remote_ref(&c))?;
+        // the connection object is usually exported on the same port as the server
+        if (&connection.host, connection.port) != (&server.host, server.port) {
+            channel = Channel::open(&connection.host, connection.port).map_err(connect_error)?;
+        }
+        channel.dirty(&connection).map_err(connect_error)?;
+        Ok(JrmpConnection { address, channel: RefCell::new(channel), connection, lease_renewed: RefCell::new(Instant::now()) })
+    }
+
+    fn call(&self, method: i64, arguments: impl FnOnce(&mut ObjectWriter)) -> Result<JavaValue, FetchError> {
+        let mut channel = self.channel.borrow_mut();
+        if self.lease_renewed.borrow().elapsed() > LEASE / 2 {
+            channel.dirty(&self.connection)?;
+            *self.lease_renewed.borrow_mut() = Instant::now();
+        }
+        let mut call = channel.call_header(&self.connection, METHOD_HASH, method);
+        arguments(&mut call);
+        channel.call(call)
+    }
+}
+
+impl MBeanConnection for JrmpConnection {
+    fn get_attribute(&self, mbean: &str, attribute: &str) -> Result<Value, FetchError> {
+        let value = self.call(GET_ATTRIBUTE, |call| {
+            object_name(call, mbean);
+            // no delegation subject
+            call.string(attribute).null();
+        })?;
+        Ok(to_json(&value))
+    }
+
+    fn query_names(&self, pattern: &str) -> Result<Vec<String>, FetchError> {
+        let names = self.call(QUERY_NAMES, |call| {
+            object_name(call, pattern);
+            // no query, no delegation subject
+            call.null().null();
+        })?;
+        serde_json::from_value(to_json(&names))
+            .map_err(|e| FetchError::from(format!("Unexpected names from the MBean server at {}: {}", self.address, e)))
+    }
+}
+
+fn object_name(call: &mut ObjectWriter, name: &str) {
+    // written with its writeObject method, as its canonical name
+    call.object("javax.management.ObjectName", 1081892073854801359, SC_SERIALIZABLE | SC_WRITE_METHOD, &[])
+        .string(name)
+        .end_object();
+}
+
+///
+/// The `RMIServer` of a JMX address: `host:port` or a `service:jmx:rmi:///jndi/rmi://host:port/name` url of a connector
+/// bound in an RMI registry, or a `service:jmx:rmi://host/stub/...` url of a local connector, giving the serialized stub.
+fn server_ref(address: &str) -> Result<RemoteRef, FetchError> {
+    if let Some(stub) = address.strip_prefix("service:jmx:rmi:").and_then(|u| u.split_once("/stub/")).map(|(_, s)| s) {
+        let bytes = base64::decode(stub).map_err(|e| format!("Invalid stub in the JMX url {}: {}", address, e))?;
+        let stub = ObjectReader::new(&bytes[..])?.read_value()?;
+        return remote_ref(&stub);
+    }
+    let (registry, name) = registry_address(address)?;
+    let (host, port) = host_port(&registry)?;
+    let mut channel = Channel::open(&host, port)?;
+    let registry_ref = RemoteRef { host, port, object: REGISTRY_ID, space: (0, 0, 0) };
+    let mut call = channel.call_header(&registry_ref, REGISTRY_LOOKUP, REGISTRY_INTERFACE_HASH);
+    call.string(&name);
+    let stub = channel.call(call).map_err(|e| e.context(&format!("Couldn't look {} up in the RMI registry", name)))?;
+    remote_ref(&stub)
+}
+
+/// `host:port` of the registry and the name the connector is bound to
+fn registry_address(address: &str) -> Result<(String, String), String> {
+    if !address.starts_with("service:jmx:") {
+        return Ok((address.to_owned(), DEFAULT_NAME.to_owned()));
+    }
+    let jndi = address.strip_prefix("service:jmx:rmi:")
+        .and_then(|u| u.split_once("/jndi/rmi://"))
+        .map(|(_, jndi)| jndi)
+        .ok_or_else(|| format!("Unsupported JMX url {}, only RMI connectors bound in an RMI registry are", address))?;
+    Ok(match jndi.split_once('/') {
+        Some((registry, name)) => (registry.to_owned(), name.to_owned()),
+        None => (jndi.to_owned(), DEFAULT_NAME.to_owned()),
+    })
+}
+
+fn host_port(address: &str) -> Result<(String, u16), String> {
+    let (host, port) = match address.rsplit_once(':') {
+        // an IPv6 address without a port
+        Some((host, _)) if address.ends_with(']') || host.ends_with(':') => (address, None),
+        Some((host, port)) => (host, Some(port.parse::<u16>().map_err(|_| format!("Invalid port in {}", address))?)),
+        None => (address, None),
+    };
+    Ok((host.trim_start_matches('[').trim_end_matches(']').to_owned(), port.unwrap_or(DEFAULT_REGISTRY_PORT)))
+}
+
+///
+/// Where the stub of a remote object points to, read from what its `RemoteObject` class wrote: the class of its
+/// reference, then the endpoint and the id of the object.
+///
+/// Stubs generated as dynamic proxies hold the reference in their invocation handler, which is looked for in fields.
+fn remote_ref(stub: &JavaValue) -> Result<RemoteRef, FetchError> {
+    fn find(value: &JavaValue) -> Option<&[u8]> {
+        match value {
+            JavaValue::Object(o) => o.class_data("java.rmi.server.RemoteObject")
+                .map(|c| c.data.as_slice())
+                .or_else(|| o.classes.iter().flat_map(|c| c.fields.iter()).find_map(|(_, v)| find(v))),
+            _ => None,
+        }
+    }
+    let data = find(stub).ok_or("The RMI server didn't return a remote object")?;
+    let mut r = ObjectReader::data(data);
+    let reference = r.utf()?;
+    if reference == "UnicastRef2" {
+        // endpoints with a client socket factory, e.g. for TLS, come with their factory
+        if r.u8()? != 0 {
+            return Err(FetchError::from("The JMX connector is exported with a socket factory, e.g. for TLS, which needs the `jvm` feature"));
+        }
+    } else if reference != "UnicastRef" {
+        return Err(FetchError::from(format!("Unsupported RMI reference {}", reference)));
+    }
+    let host = r.utf()?;
+    let port = r.u32()? as u16;
+    let object = r.u64()? as i64;
+    let space = (r.u32()? as i32, r.u64()? as i64, r.u16()? as i16);
+    Ok(RemoteRef { host, port, object, space })
+}
+
+/// A connection to an RMI endpoint, over which calls are made one after the other
+struct Channel {
+    stream: TcpStream,
+    input: BufReader<TcpStream>,
+    /// Sequence number of the next lease asked for
+    sequence: i64,
+}
+
+impl Channel {
+    fn open(host: &str, port: u16) -> Result<Channel, FetchError> {
+        let error = |e: std::io::Error| FetchError::from(e).context(&format!("Couldn't connect to the RMI endpoint {}:{}", host, port));
+        let addr = (host, port).to_socket_addrs().map_err(error)?
+            .next()
+            .ok_or_else(|| FetchError::connection(format!("Couldn't resolve the RMI endpoint {}:{}", host, port)))?;
+        let mut stream = TcpStream::connect_timeout(&addr, TIMEOUT).map_err(error)?;
+        stream.set_read_timeout(Some(TIMEOUT)).map_err(error)?;
+        stream.set_write_timeout(Some(TIMEOUT)).map_err(error)?;
+
+        let mut header = MAGIC.to_vec();
+        header.extend(&VERSION.to_be_bytes());
+        header.push(STREAM_PROTOCOL);
+        stream.write_all(&header).map_err(error)?;
+        let mut input = BufReader::new(stream.try_clone().map_err(error)?);
+        let mut ack = [0; 1];
+        input.read_exact(&mut ack).map_err(error)?;
+        if ack[0] != PROTOCOL_ACK {
+            return Err(FetchError::connection(format!("{}:{} isn't an RMI endpoint", host, port)));
+        }
+        // the endpoint of this client as the server sees it, given back as this client's own
+        let mut len = [0; 2];
+        input.read_exact(&mut len).map_err(error)?;
+        let mut client = vec![0; u16::from_be_bytes(len) as usize + 4];
+        input.read_exact(&mut client).map_err(error)?;
+        let mut endpoint = len.to_vec();
+        endpoint.extend(&client[..client.len() - 4]);
+        endpoint.extend(&0i32.to_be_bytes());
+        stream.write_all(&endpoint).map_err(error)?;
+        Ok(Channel { stream, input, sequence: 0 })
+    }
+
+    /// A call of a method of the remote object, to write the arguments to
+    fn call_header(&self, target: &RemoteRef, operation: i32, hash: i64) -> ObjectWriter {
+        let mut call = ObjectWriter::new();
+        call.data(&target.object.to_be_bytes())
+            .data(&target.space.0.to_be_bytes())
+            .data(&target.space.1.to_be_bytes())
+            .data(&target.space.2.to_be_bytes())
+            .data(&operation.to_be_bytes())
+            .data(&hash.to_be_bytes());
+        call
+    }
+
+    /// Makes the call, returning what it returned or failing with what it threw
+    fn call(&mut self, call: ObjectWriter) -> Result<JavaValue, FetchError> {
+        let mut message = vec![CALL];
+        message.extend(call.finish());
+        self.stream.write_all(&message)?;
+        let mut tag = [0; 1];
+        self.input.read_exact(&mut tag)?;
+        if tag[0] != RETURN_DATA {
+            return Err(FetchError::from(format!("Unexpected RMI message 0x{:02x}", tag[0])));
+        }
+        let mut r = ObjectReader::new(&mut self.input)?;
+        let header = r.read_data(15)?;
+        let value = r.read_value()?;
+        if header[0] != NORMAL_RETURN {
+            let message = exception_message(&value);
+            return Err(if message.starts_with("java.lang.SecurityException") {
+                FetchError::auth(message)
+            } else {
+                FetchError::from(message)
+            });
+        }
+        // remote objects returned are held for this client until it acknowledges them
+        let mut ack = vec![DGC_ACK];
+        ack.extend(&header[1..]);
+        self.stream.write_all(&ack)?;
+        Ok(value)
+    }
+
+    /// Asks the endpoint's distributed garbage collector for a lease on the object, so that it isn't unexported
+    fn dirty(&mut self, object: &RemoteRef) -> Result<(), FetchError> {
+        let dgc = RemoteRef { object: DGC_ID, space: (0, 0, 0), ..object.clone() };
+        let mut call = self.call_header(&dgc, DGC_DIRTY, DGC_INTERFACE_HASH);
+        call.array("[Ljava.rmi.server.ObjID;", -8713620060265225090, 1)
+            .object("java.rmi.server.ObjID", -6386392263968365220, SC_SERIALIZABLE, &[("objNum", "J"), ("space", "Ljava/rmi/server/UID;")])
+            .field(&object.object.to_be_bytes())
+            .object("java.rmi.server.UID", 1086053664494604050, SC_SERIALIZABLE, &[("count", "S"), ("time", "J"), ("unique", "I")])
+            .field(&object.space.2.to_be_bytes())
+            .field(&object.space.1.to_be_bytes())
+            .field(&object.space.0.to_be_bytes());
+        self.sequence += 1;
+        call.data(&self.sequence.to_be_bytes());
+        // without a VMID the server gives one, the leases of earlier ones expire on their own
+        call.object("java.rmi.dgc.Lease", -5713411624328831948, SC_SERIALIZABLE, &[("value", "J"), ("vmid", "Ljava/rmi/dgc/VMID;")])
+            .field(&(LEASE.as_millis() as i64).to_be_bytes())
+            .null();
+        self.call(call).map(|_| ()).map_err(|e| e.context("Couldn't lease the JMX connection"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::jmx::jrmp::{host_port, registry_address};
+
+    #[test]
+    fn registries_are_found_in_addresses_and_urls() {
+        let registry = |a: &str| registry_address(a).and_then(|(r, name)| host_port(&r).map(|(h, p)| (h, p, name)));
+        assert_eq!(registry("localhost:9010"), Ok(("localhost".to_owned(), 9010, "jmxrmi".to_owned())));
+        assert_eq!(registry("service:jmx:rmi:///jndi/rmi://10.0.0.1:9999/jmxrmi"), Ok(("10.0.0.1".to_owned(), 9999, "jmxrmi".to_owned())));
+        assert_eq!(registry("service:jmx:rmi://ignored/jndi/rmi://[::1]/karaf-root"), Ok(("::1".to_owned(), 1099, "karaf-root".to_owned())));
+        assert_eq!(registry("[::1]:9010"), Ok(("::1".to_owned(), 9010, "jmxrmi".to_owned())));
+        assert!(registry("service:jmx:jmxmp://localhost:5555").is_err());
+        assert!(registry("localhost:port").is_err());
+    }
+}
@@ -0,0 +1,17 @@
+//! JVMs, through JMX over RMI or Jolokia
+
+/// Slick, HikariCP, Kafka, thread pool and JVM metrics, with the settings of the connection
+pub mod model;
+/// Reads the metrics from the MBeans of any connection
+pub mod client;
+/// Connection over Jolokia's HTTP/JSON protocol
+pub mod jolokia;
+/// Connection over RMI, reading MBeans without a JVM
+pub mod jrmp;
+/// Java object serialization, as much of it as RMI calls need
+mod serialization;
+/// Finds the JVMs running on this machine
+pub mod discovery;
+/// Connection over RMI, backed by an in-process JVM for operations, notifications and TLS
+#[cfg(feature = "jvm")]
+pub mod rmi;
@@ -0,0 +1,361 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// JVM polled over JMX, through RMI or Jolokia
+#[derive(Clone)]
+pub struct JMXConnectionSettings {
+    /// `host:port` of an RMI connector, a `service:jmx:` url, or the http(s) url of a Jolokia agent
+    pub address: String,
+    /// User of the connector, if it requires authentication
+    pub username: Option<String>,
+    /// Password of the user
+    pub password: Option<String>,
+    /// Name of the HikariCP pool to read, picked among the discovered ones if not set
+    pub db_pool_name: Option<String>,
+    /// Whether to read the metrics of the Kafka consumers
+    pub kafka: bool,
+    /// JMX domain of the DataStax java driver metrics, if any
+    pub cassandra_domain: Option<String>,
+    /// Object name pattern of Akka dispatcher MBeans
+    pub akka_dispatchers: Option<String>,
+    /// Whether to list the Logback and Log4j2 loggers
+    pub loggers: bool,
+    /// Object name patterns of executor/thread pool MBeans
+    pub thread_pools: Vec<String>,
+    /// Whether to read the runtime details of the JVM
+    pub jvm_info: bool,
+    /// Akka Cluster membership, read from the `akka:type=Cluster` MBean
+    pub akka_cluster: bool,
+    /// Directory on the target host to write heap dumps to
+    pub heap_dump_dir: String,
+    /// GC pauses longer than this (in ms) are flagged in the GC log
+    pub gc_pause_threshold: u64,
+    /// TLS options, if the connector requires them
+    pub ssl: Option<JMXSslSettings>,
+    /// Object name patterns of MBeans whose notifications are collected
+    pub notifications: Vec<String>,
+    /// Whether operations changing the JVM, e.g. heap dumps or pool suspensions, can be invoked
+    pub allow_operations: bool,
+    /// When to alert about the HikariCP pool
+    pub hikari_alerts: HikariAlertSettings,
+}
+
+/// Thresholds for HikariCP pool exhaustion alerts
+#[derive(Clone)]
+pub struct HikariAlertSettings {
+    /// Number of consecutive ticks with all connections of the pool in use
+    pub exhausted_ticks: u32,
+    /// Number of threads waiting for a connection
+    pub waiting_threshold: i32,
+}
+
+///
+/// TLS options for JMX connections.
+///
+/// For RMI these are passed to the JVM as standard `javax.net.ssl.*` system properties,
+/// so stores can be in any format java understands (JKS, PKCS12).
+/// For Jolokia the truststore is a PEM certificate and the keystore is a PKCS12 archive.
+#[derive(Clone)]
+pub struct JMXSslSettings {
+    /// Certificates to trust
+    pub truststore: Option<String>,
+    /// Password of the truststore
+    pub truststore_password: Option<String>,
+    /// Client certificate and key, for connectors requiring them
+    pub keystore: Option<String>,
+    /// Password of the keystore
+    pub keystore_password: Option<String>,
+}
+
+impl JMXConnectionSettings {
+    /// Whether the address is one of a Jolokia agent
+    pub fn is_jolokia(&self) -> bool {
+        self.address.starts_with("http://") || self.address.starts_with("https://")
+    }
+
+    /// Slick tab is shown for an explicit pool name, or when nothing else was asked from jmx,
+    /// in which case the pool is picked among discovered ones
+    pub fn has_slick(&self) -> bool {
+        self.db_pool_name.is_some() || !(
+            self.kafka
+                || self.cassandra_domain.is_some()
+                || self.akka_dispatchers.is_some()
+                || self.loggers
+                || !self.thread_pools.is_empty()
+                || self.jvm_info
+                || self.akka_cluster
+        )
+    }
+}
+
+/// Threads and queue of the Slick executor
+#[derive(Clone, Deserialize, Serialize)]
+pub struct SlickMetrics {
+    /// When the metrics were read
+    pub timestamp: DateTime<Local>,
+    /// Threads running database actions
+    pub active_threads: i32,
+    /// Database actions waiting for a thread
+    pub queue_size: i32,
+}
+
+/// Limits of the Slick executor
+#[derive(Deserialize, Serialize)]
+pub struct SlickConfig {
+    /// Threads of the executor
+    pub max_threads: i32,
+    /// Database actions that can wait for a thread
+    pub max_queue_size: i32,
+}
+
+/// Connections of the HikariCP pool
+#[derive(Clone, Deserialize, Serialize)]
+pub struct HikariMetrics {
+    /// When the metrics were read
+    pub timestamp: DateTime<Local>,
+    /// Open connections
+    pub total: i32,
+    /// Connections in use
+    pub active: i32,
+    /// Connections waiting to be used
+    pub idle: i32,
+    /// Threads waiting for a connection
+    pub waiting: i32,
+    /// Largest number of connections the pool opens
+    pub max_pool_size: i32,
+}
+
+/// A notification emitted by an MBean
+#[derive(Clone, Deserialize, Serialize)]
+pub struct JMXNotification {
+    /// When the notification was received
+    pub timestamp: DateTime<Local>,
+    /// Object name of the MBean emitting it
+    pub source: String,
+    /// Type of the notification, e.g. `jmx.attribute.change`
+    pub notification_type: String,
+    /// Message of the notification
+    pub message: String,
+}
+
+/// Whitelisted HikariPoolMXBean operations that can be invoked from the UI.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum HikariOperation {
+    /// Closes the idle connections, and the ones in use once they're returned
+    SoftEvictConnections,
+    /// Stops handing out connections
+    SuspendPool,
+    /// Hands out connections again
+    ResumePool,
+}
+
+impl HikariOperation {
+    /// Name of the MBean operation
+    pub fn name(&self) -> &'static str {
+        match self {
+            HikariOperation::SoftEvictConnections => "softEvictConnections",
+            HikariOperation::SuspendPool => "suspendPool",
+            HikariOperation::ResumePool => "resumePool",
+        }
+    }
+}
+
+/// Fetch metrics of a single kafka consumer (identified by its client id)
+#[derive(Clone, Deserialize, Serialize)]
+pub struct KafkaConsumerMetrics {
+    /// Client id of the consumer
+    pub client_id: String,
+    /// Largest lag of the consumer's partitions, in records
+    pub records_lag_max: f64,
+    /// Fetch requests per second
+    pub fetch_rate: f64,
+    /// Records consumed per second
+    pub records_consumed_rate: f64,
+}
+
+/// Fetch metrics of the Kafka consumers of the JVM
+#[derive(Clone, Deserialize, Serialize)]
+pub struct KafkaMetrics {
+    /// When the metrics were read
+    pub timestamp: DateTime<Local>,
+    /// Metrics of every consumer
+    pub consumers: Vec<KafkaConsumerMetrics>,
+}
+
+/// A member of an Akka Cluster, as seen by the monitored node
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ClusterMember {
+    /// Address of the member, e.g. akka://app@10.0.0.1:2552
+    pub address: String,
+    /// e.g. Joining, Up, Leaving, Exiting, Down
+    pub status: String,
+    /// Roles of the member
+    pub roles: Vec<String>,
+}
+
+/// A member that failure detectors consider unreachable, with the members that detected it
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct UnreachableMember {
+    /// Address of the unreachable member
+    pub node: String,
+    /// Addresses of the members that detected it
+    pub observed_by: Vec<String>,
+}
+
+/// Membership of the Akka Cluster, as seen by the monitored node
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AkkaClusterStatus {
+    /// When the membership was read
+    pub timestamp: DateTime<Local>,
+    /// Address of the monitored node
+    pub self_address: String,
+    /// Members of the cluster
+    pub members: Vec<ClusterMember>,
+    /// Members considered unreachable
+    pub unreachable: Vec<UnreachableMember>,
+}
+
+/// Connection pool and request metrics of the DataStax java driver
+#[derive(Clone, Deserialize, Serialize)]
+pub struct CassandraMetrics {
+    /// When the metrics were read
+    pub timestamp: DateTime<Local>,
+    /// Cassandra hosts the driver is connected to
+    pub connected_hosts: f64,
+    /// Connections open to all the hosts
+    pub open_connections: f64,
+    /// Requests waiting for a response
+    pub in_flight_requests: f64,
+    /// Median request latency
+    pub latency_p50: f64,
+    /// 95th percentile of the request latency
+    pub latency_p95: f64,
+    /// 99th percentile of the request latency
+    pub latency_p99: f64,
+    /// Unit of the latencies, as reported
+    pub latency_unit: String,
+}
+
+/// Thread pool metrics of a single Akka dispatcher
+#[derive(Clone, Deserialize, Serialize)]
+pub struct DispatcherMetrics {
+    /// Name of the dispatcher
+    pub name: String,
+    /// Threads running tasks
+    pub active_threads: f64,
+    /// Tasks waiting for a thread
+    pub queued_tasks: f64,
+    /// Number of threads the dispatcher aims for
+    pub parallelism: f64,
+}
+
+/// Thread pools of the Akka dispatchers
+#[derive(Clone, Deserialize, Serialize)]
+pub struct AkkaDispatcherMetrics {
+    /// When the metrics were read
+    pub timestamp: DateTime<Local>,
+    /// Metrics of every dispatcher
+    pub dispatchers: Vec<DispatcherMetrics>,
+}
+
+/// Logging framework managing a logger, defines how its level is read and changed
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum LoggingBackend {
+    /// Levels are managed through operations of logback's `JMXConfigurator`
+    Logback,
+    /// Every logger config has its own MBean with a writable `Level` attribute
+    Log4j2,
+}
+
+/// Logger exposed over JMX, along with the MBean managing it
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct JMXLogger {
+    /// Name of the logger
+    pub name: String,
+    /// Level of the logger, e.g. INFO
+    pub level: String,
+    /// Framework managing the logger
+    pub backend: LoggingBackend,
+    /// Object name of the MBean managing the logger
+    pub mbean: String,
+}
+
+/// Levels loggers can be set to, least severe first
+pub const LOG_LEVELS: [&str; 5] = ["TRACE", "DEBUG", "INFO", "WARN", "ERROR"];
+
+/// Metrics of a single executor/thread pool MBean
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ThreadPoolMetrics {
+    /// Object name of the MBean
+    pub name: String,
+    /// Threads running tasks
+    pub active_threads: f64,
+    /// Tasks waiting for a thread, if reported
+    pub queue_size: Option<f64>,
+    /// Largest number of threads of the pool, if reported
+    pub max_threads: Option<f64>,
+}
+
+/// Executors and thread pools of the JVM
+#[derive(Clone, Deserialize, Serialize)]
+pub struct ThreadPoolsMetrics {
+    /// When the metrics were read
+    pub timestamp: DateTime<Local>,
+    /// Metrics of every pool
+    pub pools: Vec<ThreadPoolMetrics>,
+}
+
+/// Details of the target JVM, from its Runtime MXBean
+#[derive(Clone, Deserialize, Serialize)]
+pub struct JvmInfo {
+    /// When the details were read
+    pub timestamp: DateTime<Local>,
+    /// Uptime in milliseconds at the time of the request
+    pub uptime: i64,
+    /// Name of the JVM, e.g. OpenJDK 64-Bit Server VM
+    pub vm_name: String,
+    /// Vendor of the JVM
+    pub vm_vendor: String,
+    /// Version of the JVM
+    pub vm_version: String,
+    /// Version of the Java specification
+    pub spec_version: String,
+    /// Arguments the JVM was started with, the main class and its arguments left out
+    pub input_arguments: Vec<String>,
+    /// System properties, sorted by name
+    pub system_properties: Vec<(String, String)>,
+}
+
+/// A garbage collection, from `LastGcInfo` of a GarbageCollector MXBean
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct GcEvent {
+    /// When the collection started
+    pub timestamp: DateTime<Local>,
+    /// Name of the collector
+    pub collector: String,
+    /// Number of the collection, growing with every one
+    pub id: i64,
+    /// Length of the collection
+    pub duration_ms: f64,
+    /// Decrease of used memory over all pools, in bytes
+    pub reclaimed: f64,
+}
+
+/// Diagnostic operations that can be invoked on the target JVM from the JVM tab
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum JvmOperation {
+    /// Dumps live objects to the given file on the target host
+    DumpHeap(String),
+    /// Runs a full garbage collection
+    Gc,
+}
+
+impl JvmOperation {
+    /// What the operation does, for confirmations and notices
+    pub fn describe(&self) -> String {
+        match self {
+            JvmOperation::DumpHeap(path) => format!("Heap dump to {}", path),
+            JvmOperation::Gc => "Garbage collection".to_owned(),
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::cell::{Ref, RefCell};
+
+use chrono::{Local, TimeZone};
+use j4rs::errors::J4RsError;
+use j4rs::{Instance, InvocationArg, JavaOpt, Jvm, JvmBuilder};
+use jmx::{MBeanClient, MBeanClientTrait};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::error::FetchError;
+use crate::jmx::client::MBeanConnection;
+use crate::jmx::jrmp::JrmpConnection;
+use crate::jmx::model::{JMXConnectionSettings, JMXNotification, JMXSslSettings};
+
+/// MBean server connection over RMI, reading MBeans over JRMP and backed by an in-process JVM for the rest.
+pub struct RmiConnection {
+    reads: Reads,
+    url: String,
+    // the JVM only starts for operations and notifications, unless TLS needs it for reads too
+    operations: RefCell<Option<Operations>>,
+}
+
+/// What MBeans are read through: JRMP in Rust, or the jmx crate in the JVM for connectors over TLS
+enum Reads {
+    Jrmp(JrmpConnection),
+    Jvm(MBeanClient),
+}
+
+/// An MBeanServerConnection of our own in the JVM, as the jmx crate only reads
+struct Operations {
+    // JVM started with ssl system properties if given; jmx crate attaches to it instead of creating its own
+    jvm: Jvm,
+    connection: Instance,
+    // notifications of every subscribed MBean, queued by their listener until pulled
+    notifications: Instance,
+}
+
+impl RmiConnection {
+    /// Connects to the RMI connector of the settings, in a JVM started with their TLS settings if given
+    pub fn new(settings: &JMXConnectionSettings) -> Result<RmiConnection, FetchError> {
+        // local connectors of discovered JVMs are given as full service urls
+        let url = if settings.address.starts_with("service:jmx:") {
+            settings.address.clone()
+        } else {
+            format!("service:jmx:rmi://{}/jndi/rmi://{}/jmxrmi", &settings.address, &settings.address)
+        };
+        if settings.ssl.is_none() {
+            return Ok(RmiConnection {
+                reads: Reads::Jrmp(JrmpConnection::new(settings)?),
+                url,
+                operations: RefCell::new(None),
+            });
+        }
+        // the jmx crate attaches to the JVM started with the TLS settings, so it starts first
+        let operations = Operations::start(&url, settings.ssl.as_ref())?;
+        let client = MBeanClient::connect(jmx::MBeanAddress::service_url(url.clone()))
+            .map_err(|e| connect_error(&url, &e))?;
+        Ok(RmiConnection {
+            reads: Reads::Jvm(client),
+            url,
+            operations: RefCell::new(Some(operations)),
+        })
+    }
+
+    /// The connection of operations and notifications, starting the JVM for it on first use, which is without TLS
+    fn operations(&self) -> Result<Ref<'_, Operations>, FetchError> {
+        if self.operations.borrow().is_none() {
+            let operations = Operations::start(&self.url, None)?;
+            self.operations.replace(Some(operations));
+        }
+        Ok(Ref::map(self.operations.borrow(), |o| o.as_ref().expect("the JVM was just started")))
+    }
+}
+
+fn connect_error(url: &str, e: &dyn std::fmt::Display) -> FetchError {
+    FetchError::connection(format!("Couldn't connect to jmx at {}. Error: {}", url, e))
+}
+
+impl Operations {
+    fn start(url: &str, ssl: Option<&JMXSslSettings>) -> Result<Operations, FetchError> {
+        let jvm = Operations::start_jvm(ssl)?;
+        let connection = Operations::connect_operations(&jvm, url).map_err(|e| connect_error(url, &e))?;
+        let notifications = jvm.create_instance("java.util.concurrent.LinkedBlockingQueue", &[])
+            .map_err(|e| connect_error(url, &e))?;
+        Ok(Operations { jvm, connection, notifications })
+    }
+
+    fn start_jvm(ssl: Option<&JMXSslSettings>) -> Result<Jvm, String> {
+        let properties = ssl.map_or(vec![], Operations::ssl_properties);
+        let opts: Vec<JavaOpt> = properties.iter().map(|p| JavaOpt::new(p)).collect();
+        JvmBuilder::new()
+            .java_opts(opts)
+            .build()
+            .map_err(|e| format!("Couldn't start the JVM. Error: {}", e))
+    }
+
+    fn ssl_properties(ssl: &JMXSslSettings) -> Vec<String> {
+        let properties = [
+            ("javax.net.ssl.trustStore", &ssl.truststore),
+            ("javax.net.ssl.trustStorePassword", &ssl.truststore_password),
+            ("javax.net.ssl.keyStore", &ssl.keystore),
+            ("javax.net.ssl.keyStorePassword", &ssl.keystore_password),
+        ];
+        properties.iter()
+            .filter_map(|(k, v)| v.as_ref().map(|v| format!("-D{}={}", k, v)))
+            .collect()
+    }
+
+    fn connect_operations(jvm: &Jvm, url: &str) -> Result<Instance, J4RsError> {
+        let url = jvm.create_instance("javax.management.remote.JMXServiceURL", &[InvocationArg::from(url)])?;
+        let connector = jvm.invoke_static("javax.management.remote.JMXConnectorFactory", "connect", &[InvocationArg::from(url)])?;
+        jvm.invoke(&connector, "getMBeanServerConnection", &[])
+    }
+
+    fn object_name(&self, mbean: &str) -> Result<Instance, J4RsError> {
+        self.jvm.create_instance("javax.management.ObjectName", &[InvocationArg::from(mbean)])
+    }
+
+    // j4rs only finds methods by the exact classes of their arguments, so values passed as objects are cast
+    fn java_object(&self, value: &Value) -> Result<Instance, FetchError> {
+        let (class, text) = match value {
+            Value::String(s) => ("java.lang.String", s.to_owned()),
+            Value::Bool(b) => ("java.lang.Boolean", b.to_string()),
+            Value::Number(n) if n.is_i64() => ("java.lang.Long", n.to_string()),
+            Value::Number(n) => ("java.lang.Double", n.to_string()),
+            other => return Err(FetchError::from(format!("Can't pass {} over RMI, only strings, numbers and booleans", other))),
+        };
+        self.jvm.create_instance(class, &[InvocationArg::from(text.as_str())])
+            .and_then(|i| self.jvm.cast(&i, "java.lang.Object"))
+            .map_err(|e| FetchError::from(e.to_string()))
+    }
+
+    // j4rs can't convert nulls, e.g. results of void operations, but it can convert lists holding them
+    fn to_rust_nullable<T: DeserializeOwned>(&self, instance: Instance) -> Result<Option<T>, J4RsError> {
+        let object = self.jvm.cast(&instance, "java.lang.Object")?;
+        let list = self.jvm.invoke_static("java.util.Collections", "singletonList", &[InvocationArg::from(object)])?;
+        let mut values: Vec<Option<T>> = self.jvm.to_rust(list)?;
+        Ok(values.pop().flatten())
+    }
+
+    fn pull_notification(&self) -> Result<JMXNotification, J4RsError> {
+        let polled = self.jvm.invoke(&self.notifications, "poll", &[])?;
+        let n = self.jvm.cast(&polled, "javax.management.Notification")?;
+        let get = |method: &str| self.jvm.invoke(&n, method, &[]);
+        // the source is a field of the EventObject the notification extends
+        let source = self.jvm.invoke(&self.jvm.cast(&n, "java.util.EventObject")?, "getSource", &[])?;
+        let timestamp: i64 = self.jvm.to_rust(get("getTimeStamp")?)?;
+        Ok(JMXNotification {
+            timestamp: Local.timestamp_millis(timestamp),
+            source: self.jvm.to_rust(self.jvm.invoke(&source, "toString", &[])?)?,
+            notification_type: self.jvm.to_rust(get("getType")?)?,
+            message: self.to_rust_nullable(get("getMessage")?)?.unwrap_or_default(),
+        })
+    }
+}
+
+/// Signature of an operation argument, as MBean servers find operations by the types of their parameters
+fn java_type(value: &Value) -> Result<&'static str, FetchError> {
+    match value {
+        Value::String(_) => Ok("java.lang.String"),
+        Value::Bool(_) => Ok("boolean"),
+        Value::Number(n) if n.is_i64() => Ok("long"),
+        Value::Number(_) => Ok("double"),
+        other => Err(FetchError::from(format!("Can't pass {} over RMI, only strings, numbers and booleans", other))),
+    }
+}
+
+impl MBeanConnection for RmiConnection {
+    fn get_attribute(&self, mbean: &str, attribute: &str) -> Result<Value, FetchError> {
+        match &self.reads {
+            Reads::Jrmp(c) => c.get_attribute(mbean, attribute),
+            Reads::Jvm(c) => c.get_attribute(mbean, attribute).map_err(|e| FetchError::from(e.to_string())),
+        }
+    }
+
+    fn query_names(&self, pattern: &str) -> Result<Vec<String>, FetchError> {
+        match &self.reads {
+            Reads::Jrmp(c) => c.query_names(pattern),
+            Reads::Jvm(c) => c.query_names(pattern, pattern).map_err(|e| FetchError::from(e.to_string())),
+        }
+    }
+
+    fn invoke(&self, mbean: &str, operation: &str, arguments: &[Value]) -> Result<Value, FetchError> {
+        let operations = self.operations()?;
+        let signature = arguments.iter().map(java_type).collect::<Result<Vec<_>, _>>()?;
+        let name = operations.object_name(mbean).map_err(|e| FetchError::from(e.to_string()))?;
+        operations.jvm.invoke(&operations.connection, "invoke", &[
+            InvocationArg::from(name),
+            InvocationArg::from(operation),
+            InvocationArg::new(arguments, "[Ljava.lang.Object;"),
+            InvocationArg::new(&signature, "[Ljava.lang.String;"),
+        ])
+            .and_then(|result| operations.to_rust_nullable(result))
+            .map(|result| result.unwrap_or(Value::Null))
+            .map_err(|e| FetchError::from(format!("Couldn't invoke {} on {}: {}", operation, mbean, e)))
+    }
+
+    fn set_attribute(&self, mbean: &str, attribute: &str, value: Value) -> Result<(), FetchError> {
+        let operations = self.operations()?;
+        let value = operations.java_object(&value)?;
+        operations.object_name(mbean)
+            .and_then(|name| {
+                let attribute = operations.jvm.create_instance("javax.management.Attribute", &[InvocationArg::from(attribute), InvocationArg::from(value)])?;
+                operations.jvm.invoke(&operations.connection, "setAttribute", &[InvocationArg::from(name), InvocationArg::from(attribute)])
+            })
+            .map(|_| ())
+            .map_err(|e| FetchError::from(format!("Couldn't set {} of {}: {}", attribute, mbean, e)))
+    }
+
+    fn subscribe(&self, mbean: &str) -> Result<(), FetchError> {
+        let operations = self.operations()?;
+        let jvm = &operations.jvm;
+        let subscribe = || {
+            // a proxy adding every notification it handles to the queue, as listeners can't be written in Rust
+            let listener_class = jvm.invoke_static("java.lang.Class", "forName", &[InvocationArg::from("javax.management.NotificationListener")])?;
+            let listener = jvm.invoke_static("java.beans.EventHandler", "create", &[
+                InvocationArg::from(listener_class),
+                InvocationArg::from(jvm.cast(&operations.notifications, "java.lang.Object")?),
+                InvocationArg::from("add"),
+                InvocationArg::from(""),
+            ])?;
+            // j4rs can't pass nulls, so an empty prefix lets all the notifications through
+            let filter = jvm.create_instance("javax.management.NotificationFilterSupport", &[])?;
+            jvm.invoke(&filter, "enableType", &[InvocationArg::from("")])?;
+            let handback = jvm.create_instance("java.lang.String", &[InvocationArg::from(mbean)])?;
+            jvm.invoke(&operations.connection, "addNotificationListener", &[
+                InvocationArg::from(operations.object_name(mbean)?),
+                InvocationArg::from(jvm.cast(&listener, "javax.management.NotificationListener")?),
+                InvocationArg::from(jvm.cast(&filter, "javax.management.NotificationFilter")?),
+                InvocationArg::from(jvm.cast(&handback, "java.lang.Object")?),
+            ])
+        };
+        subscribe().map(|_| ()).map_err(|e| FetchError::from(e.to_string()))
+    }
+
+    fn pull_notifications(&self) -> Result<Vec<JMXNotification>, FetchError> {
+        // nothing was subscribed to before the JVM starts
+        let operations = match Ref::filter_map(self.operations.borrow(), Option::as_ref) {
+            Ok(operations) => operations,
+            Err(_) => return Ok(vec![]),
+        };
+        let queued = operations.jvm.invoke(&operations.notifications, "size", &[])
+            .and_then(|size| operations.jvm.to_rust::<usize>(size))
+            .map_err(|e| FetchError::from(e.to_string()))?;
+        // more can come in meanwhile, they're left for the next pull
+        let mut notifications = (0..queued)
+            .map(|_| operations.pull_notification())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| FetchError::from(format!("Couldn't read JMX notifications: {}", e)))?;
+        notifications.sort_by_key(|n| n.timestamp);
+        Ok(notifications)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::jmx::rmi::java_type;
+
+    #[test]
+    fn operations_are_found_by_the_java_types_of_their_arguments() {
+        let signature: Vec<&str> = [json!("/tmp/heap.hprof"), json!(true), json!(1), json!(0.5)].iter()
+            .map(|a| java_type(a).unwrap())
+            .collect();
+        assert_eq!(signature, vec!["java.lang.String", "boolean", "long", "double"]);
+        assert!(java_type(&json!(["a"])).is_err());
+    }
+}
@@ -0,0 +1,719 @@
+use std::io::Read;
+use std::rc::Rc;
+
+use serde_json::{Map, Number, Value};
+
+use crate::error::FetchError;
+
+const STREAM_MAGIC: u16 = 0xaced;
+const STREAM_VERSION: u16 = 5;
+
+const TC_NULL: u8 = 0x70;
+const TC_REFERENCE: u8 = 0x71;
+const TC_CLASSDESC: u8 = 0x72;
+const TC_OBJECT: u8 = 0x73;
+const TC_STRING: u8 = 0x74;
+const TC_ARRAY: u8 = 0x75;
+const TC_CLASS: u8 = 0x76;
+const TC_BLOCKDATA: u8 = 0x77;
+const TC_ENDBLOCKDATA: u8 = 0x78;
+const TC_RESET: u8 = 0x79;
+const TC_BLOCKDATALONG: u8 = 0x7a;
+const TC_EXCEPTION: u8 = 0x7b;
+const TC_LONGSTRING: u8 = 0x7c;
+const TC_PROXYCLASSDESC: u8 = 0x7d;
+const TC_ENUM: u8 = 0x7e;
+const BASE_HANDLE: u32 = 0x7e_0000;
+
+pub const SC_WRITE_METHOD: u8 = 0x01;
+pub const SC_SERIALIZABLE: u8 = 0x02;
+const SC_EXTERNALIZABLE: u8 = 0x04;
+const SC_BLOCK_DATA: u8 = 0x08;
+
+/// Objects nested deeper than this are refused rather than overflowing the stack
+const MAX_DEPTH: usize = 256;
+
+/// A value read from a Java object stream
+#[derive(Clone, Debug, PartialEq)]
+pub enum JavaValue {
+    Null,
+    Boolean(bool),
+    Char(char),
+    /// A byte, short, int or long
+    Integer(i64),
+    /// A float or double
+    Float(f64),
+    String(String),
+    Array(Rc<Vec<JavaValue>>),
+    Object(Rc<JavaObject>),
+    /// Name of an enum constant
+    Enum(String),
+    /// Name of a class
+    Class(String),
+}
+
+/// The fields of an object, and what the `writeObject` methods of its classes wrote, from its class to its superclasses
+#[derive(Debug, PartialEq)]
+pub struct JavaObject {
+    pub classes: Vec<ClassData>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ClassData {
+    pub name: String,
+    pub fields: Vec<(String, JavaValue)>,
+    /// Primitives written by the `writeObject` method of the class, e.g. the sizes of collections
+    pub data: Vec<u8>,
+    /// Objects written by the `writeObject` method of the class, e.g. the elements of collections
+    pub objects: Vec<JavaValue>,
+}
+
+impl JavaObject {
+    /// Name of the class of the object
+    pub fn class(&self) -> &str {
+        self.classes.first().map_or("", |c| c.name.as_str())
+    }
+
+    /// Value of a field of the object's class or of one of its superclasses
+    pub fn field(&self, name: &str) -> Option<&JavaValue> {
+        self.classes.iter().flat_map(|c| c.fields.iter()).find(|(n, _)| n == name).map(|(_, v)| v)
+    }
+
+    /// What the class, the object's own or a superclass, wrote of the object
+    pub fn class_data(&self, name: &str) -> Option<&ClassData> {
+        self.classes.iter().find(|c| c.name == name)
+    }
+}
+
+struct ClassDesc {
+    name: String,
+    flags: u8,
+    /// Type codes and names of the serializable fields
+    fields: Vec<(u8, String)>,
+    super_desc: Option<Rc<ClassDesc>>,
+}
+
+enum Handle {
+    Desc(Rc<ClassDesc>),
+    Value(JavaValue),
+}
+
+///
+/// Reads objects written by a Java `ObjectOutputStream`, as generic values.
+///
+/// Classes aren't known here, the values are read from what the stream tells of them: the serializable fields
+/// of every class, and what their `writeObject` methods wrote. Externalizable classes are only read if written
+/// in block data mode, the default since Java 1.2.
+pub struct ObjectReader<R> {
+    input: R,
+    handles: Vec<Handle>,
+    /// Bytes left in the current block of primitive data
+    block: usize,
+    depth: usize,
+}
+
+impl<R: Read> ObjectReader<R> {
+    /// A reader of the stream, once its header was read
+    pub fn new(mut input: R) -> Result<ObjectReader<R>, FetchError> {
+        let mut header = [0; 4];
+        input.read_exact(&mut header)?;
+        if u16::from_be_bytes([header[0], header[1]]) != STREAM_MAGIC || u16::from_be_bytes([header[2], header[3]]) != STREAM_VERSION {
+            return Err(FetchError::from("Not a Java object stream"));
+        }
+        Ok(ObjectReader { input, handles: vec![], block: 0, depth: 0 })
+    }
+
+    /// A reader of primitive data already taken out of its blocks, e.g. what a `writeObject` method wrote
+    pub fn data(input: R) -> ObjectReader<R> {
+        ObjectReader { input, handles: vec![], block: 0, depth: 0 }
+    }
+
+    /// Primitive data written with `writeInt`, `writeLong` and alike, which may span blocks
+    pub fn read_data(&mut self, n: usize) -> Result<Vec<u8>, FetchError> {
+        let mut data = Vec::with_capacity(n);
+        while data.len() < n {
+            if self.block == 0 {
+                self.block = match self.u8()? {
+                    TC_BLOCKDATA => self.u8()? as usize,
+                    TC_BLOCKDATALONG => self.u32()? as usize,
+                    tag => return Err(FetchError::from(format!("Expected primitive data in the Java object stream, got 0x{:02x}", tag))),
+                };
+            }
+            let take = self.block.min(n - data.len());
+            data.extend(self.bytes(take)?);
+            self.block -= take;
+        }
+        Ok(data)
+    }
+
+    /// The next object of the stream
+    pub fn read_value(&mut self) -> Result<JavaValue, FetchError> {
+        let tag = self.u8()?;
+        self.value_of(tag)
+    }
+
+    fn value_of(&mut self, tag: u8) -> Result<JavaValue, FetchError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(FetchError::from("Java objects are nested too deep"));
+        }
+        let value = match tag {
+            TC_NULL => Ok(JavaValue::Null),
+            TC_REFERENCE => match self.reference()? {
+                Handle::Value(v) => Ok(v.clone()),
+                Handle::Desc(d) => Ok(JavaValue::Class(d.name.clone())),
+            },
+            TC_STRING => {
+                let len = self.u16()? as usize;
+                self.new_string(len)
+            }
+            TC_LONGSTRING => {
+                let len = self.u64()? as usize;
+                self.new_string(len)
+            }
+            TC_OBJECT => self.new_object(),
+            TC_ARRAY => self.new_array(),
+            TC_ENUM => {
+                self.class_desc()?;
+                let handle = self.new_handle(JavaValue::Null);
+                let name = match self.read_value()? {
+                    JavaValue::String(s) => s,
+                    _ => return Err(FetchError::from("Expected the name of an enum constant")),
+                };
+                self.handles[handle] = Handle::Value(JavaValue::Enum(name.clone()));
+                Ok(JavaValue::Enum(name))
+            }
+            TC_CLASS => {
+                let name = self.class_desc()?.map_or(String::new(), |d| d.name.clone());
+                self.new_handle(JavaValue::Class(name.clone()));
+                Ok(JavaValue::Class(name))
+            }
+            TC_CLASSDESC | TC_PROXYCLASSDESC => {
+                let name = self.class_desc_of(tag)?.map_or(String::new(), |d| d.name.clone());
+                Ok(JavaValue::Class(name))
+            }
+            TC_RESET => {
+                self.handles.clear();
+                self.read_value()
+            }
+            TC_EXCEPTION => {
+                self.handles.clear();
+                let exception = self.read_value()?;
+                Err(FetchError::from(format!("The Java object stream was aborted by {}", exception_message(&exception))))
+            }
+            tag => Err(FetchError::from(format!("Unexpected 0x{:02x} in the Java object stream", tag))),
+        };
+        self.depth -= 1;
+        value
+    }
+
+    fn new_handle(&mut self, value: JavaValue) -> usize {
+        self.handles.push(Handle::Value(value));
+        self.handles.len() - 1
+    }
+
+    fn reference(&mut self) -> Result<&Handle, FetchError> {
+        let handle = self.u32()?.wrapping_sub(BASE_HANDLE) as usize;
+        self.handles.get(handle).ok_or_else(|| FetchError::from("Invalid reference in the Java object stream"))
+    }
+
+    fn new_string(&mut self, len: usize) -> Result<JavaValue, FetchError> {
+        let s = modified_utf8(&self.bytes(len)?);
+        self.new_handle(JavaValue::String(s.clone()));
+        Ok(JavaValue::String(s))
+    }
+
+    pub fn utf(&mut self) -> Result<String, FetchError> {
+        let len = self.u16()? as usize;
+        Ok(modified_utf8(&self.bytes(len)?))
+    }
+
+    fn class_desc(&mut self) -> Result<Option<Rc<ClassDesc>>, FetchError> {
+        let tag = self.u8()?;
+        self.class_desc_of(tag)
+    }
+
+    fn class_desc_of(&mut self, tag: u8) -> Result<Option<Rc<ClassDesc>>, FetchError> {
+        let (handle, name, flags, fields) = match tag {
+            TC_NULL => return Ok(None),
+            TC_REFERENCE => return match self.reference()? {
+                Handle::Desc(d) => Ok(Some(d.clone())),
+                Handle::Value(_) => Err(FetchError::from("Expected a class in the Java object stream")),
+            },
+            TC_CLASSDESC => {
+                let name = self.utf()?;
+                let _serial_version_uid = self.u64()?;
+                let handle = self.new_handle(JavaValue::Null);
+                let flags = self.u8()?;
+                let mut fields = vec![];
+                for _ in 0..self.u16()? {
+                    let type_code = self.u8()?;
+                    let name = self.utf()?;
+                    if type_code == b'L' || type_code == b'[' {
+                        // the class of the field, as a string
+                        self.read_value()?;
+                    }
+                    fields.push((type_code, name));
+                }
+                (handle, name, flags, fields)
+            }
+            TC_PROXYCLASSDESC => {
+                let handle = self.new_handle(JavaValue::Null);
+                for _ in 0..self.u32()? {
+                    self.utf()?;
+                }
+                (handle, "$Proxy".to_owned(), SC_SERIALIZABLE, vec![])
+            }
+            tag => return Err(FetchError::from(format!("Expected a class in the Java object stream, got 0x{:02x}", tag))),
+        };
+        // the codebase of the class, if any
+        self.annotations(&mut ClassData::default())?;
+        let super_desc = self.class_desc()?;
+        let desc = Rc::new(ClassDesc { name, flags, fields, super_desc });
+        self.handles[handle] = Handle::Desc(desc.clone());
+        Ok(Some(desc))
+    }
+
+    /// Whatever was written up to the end of a block, by `writeObject` methods or as class annotations
+    fn annotations(&mut self, data: &mut ClassData) -> Result<(), FetchError> {
+        loop {
+            match self.u8()? {
+                TC_ENDBLOCKDATA => return Ok(()),
+                TC_BLOCKDATA => {
+                    let len = self.u8()? as usize;
+                    data.data.extend(self.bytes(len)?);
+                }
+                TC_BLOCKDATALONG => {
+                    let len = self.u32()? as usize;
+                    data.data.extend(self.bytes(len)?);
+                }
+                tag => {
+                    let value = self.value_of(tag)?;
+                    data.objects.push(value);
+                }
+            }
+        }
+    }
+
+    fn new_object(&mut self) -> Result<JavaValue, FetchError> {
+        let desc = self.class_desc()?.ok_or("Object without a class in the Java object stream")?;
+        // references to an object from within itself, e.g. a throwable being its own cause, read as nulls
+        let handle = self.new_handle(JavaValue::Null);
+        let mut hierarchy = vec![desc];
+        while let Some(s) = hierarchy.last().and_then(|d| d.super_desc.clone()) {
+            hierarchy.push(s);
+        }
+        let mut classes = vec![];
+        // superclasses are written first
+        for desc in hierarchy.iter().rev() {
+            let mut data = ClassData { name: desc.name.clone(), ..ClassData::default() };
+            if desc.flags & SC_SERIALIZABLE != 0 {
+                for (type_code, name) in &desc.fields {
+                    let value = self.field(*type_code)?;
+                    data.fields.push((name.clone(), value));
+                }
+                if desc.flags & SC_WRITE_METHOD != 0 {
+                    self.annotations(&mut data)?;
+                }
+            } else if desc.flags & SC_EXTERNALIZABLE != 0 {
+                if desc.flags & SC_BLOCK_DATA == 0 {
+                    return Err(FetchError::from(format!("Can't read {}, written with the Java 1.1 externalizable protocol", desc.name)));
+                }
+                self.annotations(&mut data)?;
+            }
+            classes.push(data);
+        }
+        classes.reverse();
+        let object = JavaValue::Object(Rc::new(JavaObject { classes }));
+        self.handles[handle] = Handle::Value(object.clone());
+        Ok(object)
+    }
+
+    fn new_array(&mut self) -> Result<JavaValue, FetchError> {
+        let desc = self.class_desc()?.ok_or("Array without a class in the Java object stream")?;
+        let handle = self.new_handle(JavaValue::Null);
+        let len = self.u32()? as usize;
+        let type_code = desc.name.as_bytes().get(1).copied().unwrap_or(b'L');
+        // a corrupt length fails once the stream ends, rather than being allocated up front
+        let mut values = Vec::with_capacity(len.min(1024));
+        for _ in 0..len {
+            values.push(self.field(type_code)?);
+        }
+        let array = JavaValue::Array(Rc::new(values));
+        self.handles[handle] = Handle::Value(array.clone());
+        Ok(array)
+    }
+
+    fn field(&mut self, type_code: u8) -> Result<JavaValue, FetchError> {
+        Ok(match type_code {
+            b'Z' => JavaValue::Boolean(self.u8()? != 0),
+            b'B' => JavaValue::Integer(self.u8()? as i8 as i64),
+            b'C' => JavaValue::Char(char::from_u32(self.u16()? as u32).unwrap_or(char::REPLACEMENT_CHARACTER)),
+            b'S' => JavaValue::Integer(self.u16()? as i16 as i64),
+            b'I' => JavaValue::Integer(self.u32()? as i32 as i64),
+            b'J' => JavaValue::Integer(self.u64()? as i64),
+            b'F' => JavaValue::Float(f32::from_bits(self.u32()?) as f64),
+            b'D' => JavaValue::Float(f64::from_bits(self.u64()?)),
+            b'L' | b'[' => self.read_value()?,
+            other => return Err(FetchError::from(format!("Unknown field type {} in the Java object stream", other as char))),
+        })
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<Vec<u8>, FetchError> {
+        let mut bytes = vec![];
+        (&mut self.input).take(n as u64).read_to_end(&mut bytes)?;
+        if bytes.len() < n {
+            return Err(FetchError::connection("The Java object stream ended early"));
+        }
+        Ok(bytes)
+    }
+
+    pub fn u8(&mut self) -> Result<u8, FetchError> {
+        let mut b = [0; 1];
+        self.input.read_exact(&mut b)?;
+        Ok(b[0])
+    }
+
+    pub fn u16(&mut self) -> Result<u16, FetchError> {
+        let mut b = [0; 2];
+        self.input.read_exact(&mut b)?;
+        Ok(u16::from_be_bytes(b))
+    }
+
+    pub fn u32(&mut self) -> Result<u32, FetchError> {
+        let mut b = [0; 4];
+        self.input.read_exact(&mut b)?;
+        Ok(u32::from_be_bytes(b))
+    }
+
+    pub fn u64(&mut self) -> Result<u64, FetchError> {
+        let mut b = [0; 8];
+        self.input.read_exact(&mut b)?;
+        Ok(u64::from_be_bytes(b))
+    }
+}
+
+/// Java's flavour of UTF-8, with nulls and characters beyond the BMP encoded as UTF-16 would
+fn modified_utf8(bytes: &[u8]) -> String {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i] as u16;
+        let continuation = |j: usize| bytes.get(i + j).map_or(0, |c| (*c as u16) & 0x3f);
+        let (unit, len) = match b {
+            b if b < 0x80 => (b, 1),
+            b if b & 0xe0 == 0xc0 => (((b & 0x1f) << 6) | continuation(1), 2),
+            b => (((b & 0x0f) << 12) | (continuation(1) << 6) | continuation(2), 3),
+        };
+        units.push(unit);
+        i += len;
+    }
+    String::from_utf16_lossy(&units)
+}
+
+///
+/// Writes the few objects and primitives RMI calls pass, as a Java `ObjectOutputStream` would.
+///
+/// Classes are annotated with a null codebase, as RMI's own streams do, since the servers read the codebase of
+/// every class from their annotation.
+pub struct ObjectWriter {
+    out: Vec<u8>,
+    block: Vec<u8>,
+}
+
+impl ObjectWriter {
+    pub fn new() -> ObjectWriter {
+        let mut out = vec![];
+        out.extend(&STREAM_MAGIC.to_be_bytes());
+        out.extend(&STREAM_VERSION.to_be_bytes());
+        ObjectWriter { out, block: vec![] }
+    }
+
+    /// Primitive data, as written by `writeInt`, `writeLong` and alike
+    pub fn data(&mut self, bytes: &[u8]) -> &mut ObjectWriter {
+        self.block.extend(bytes);
+        self
+    }
+
+    /// Fields of an object being written, which aren't block data
+    pub fn field(&mut self, bytes: &[u8]) -> &mut ObjectWriter {
+        self.out.extend(bytes);
+        self
+    }
+
+    pub fn null(&mut self) -> &mut ObjectWriter {
+        self.end_block();
+        self.out.push(TC_NULL);
+        self
+    }
+
+    pub fn string(&mut self, s: &str) -> &mut ObjectWriter {
+        self.end_block();
+        self.out.push(TC_STRING);
+        self.utf(s);
+        self
+    }
+
+    ///
+    /// An object of a class without superclasses to write, e.g.
+    ///
+    /// `writer.object("java.rmi.dgc.Lease", -5713411624328831948, SC_SERIALIZABLE, &[("value", "J"), ("vmid", "Ljava/rmi/dgc/VMID;")])`
+    ///
+    /// followed by the values of the fields, in the same order.
+    pub fn object(&mut self, class: &str, serial_version_uid: i64, flags: u8, fields: &[(&str, &str)]) -> &mut ObjectWriter {
+        self.end_block();
+        self.out.push(TC_OBJECT);
+        self.class_desc(class, serial_version_uid, flags, fields);
+        self
+    }
+
+    /// An array of objects or primitives, followed by its elements
+    pub fn array(&mut self, class: &str, serial_version_uid: i64, len: i32) -> &mut ObjectWriter {
+        self.end_block();
+        self.out.push(TC_ARRAY);
+        self.class_desc(class, serial_version_uid, SC_SERIALIZABLE, &[]);
+        self.out.extend(&len.to_be_bytes());
+        self
+    }
+
+    /// Ends what the `writeObject` method of an object's class wrote
+    pub fn end_object(&mut self) -> &mut ObjectWriter {
+        self.end_block();
+        self.out.push(TC_ENDBLOCKDATA);
+        self
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        self.end_block();
+        self.out
+    }
+
+    fn class_desc(&mut self, class: &str, serial_version_uid: i64, flags: u8, fields: &[(&str, &str)]) {
+        self.out.push(TC_CLASSDESC);
+        self.utf(class);
+        self.out.extend(&serial_version_uid.to_be_bytes());
+        self.out.push(flags);
+        self.out.extend(&(fields.len() as u16).to_be_bytes());
+        for (name, signature) in fields {
+            let type_code = signature.as_bytes()[0];
+            self.out.push(type_code);
+            self.utf(name);
+            if type_code == b'L' || type_code == b'[' {
+                self.out.push(TC_STRING);
+                self.utf(signature);
+            }
+        }
+        // no codebase, no superclass
+        self.out.extend(&[TC_NULL, TC_ENDBLOCKDATA, TC_NULL]);
+    }
+
+    fn end_block(&mut self) {
+        for chunk in self.block.chunks(255) {
+            self.out.push(TC_BLOCKDATA);
+            self.out.push(chunk.len() as u8);
+            self.out.extend(chunk);
+        }
+        self.block.clear();
+    }
+
+    fn utf(&mut self, s: &str) {
+        let mut bytes = vec![];
+        for unit in s.encode_utf16() {
+            match unit {
+                0x01..=0x7f => bytes.push(unit as u8),
+                0x00 | 0x80..=0x7ff => bytes.extend(&[0xc0 | (unit >> 6) as u8, 0x80 | (unit & 0x3f) as u8]),
+                _ => bytes.extend(&[0xe0 | (unit >> 12) as u8, 0x80 | ((unit >> 6) & 0x3f) as u8, 0x80 | (unit & 0x3f) as u8]),
+            }
+        }
+        self.out.extend(&(bytes.len() as u16).to_be_bytes());
+        self.out.extend(bytes);
+    }
+}
+
+///
+/// The value as JSON, the way Jolokia gives attributes: boxed primitives as numbers, composite data as objects
+/// of their items, tabular data as arrays of their rows, lists and arrays as arrays, maps as objects.
+///
+/// Objects of other classes are given as objects of their fields. NaN and infinite numbers are nulls.
+pub fn to_json(value: &JavaValue) -> Value {
+    match value {
+        JavaValue::Null => Value::Null,
+        JavaValue::Boolean(b) => Value::Bool(*b),
+        JavaValue::Char(c) => Value::String(c.to_string()),
+        JavaValue::Integer(i) => Value::from(*i),
+        JavaValue::Float(f) => Number::from_f64(*f).map_or(Value::Null, Value::Number),
+        JavaValue::String(s) | JavaValue::Enum(s) | JavaValue::Class(s) => Value::String(s.clone()),
+        JavaValue::Array(values) => Value::Array(values.iter().map(to_json).collect()),
+        JavaValue::Object(object) => object_json(object),
+    }
+}
+
+fn object_json(object: &JavaObject) -> Value {
+    let field = |name: &str| object.field(name).map_or(Value::Null, to_json);
+    let objects = |class: &str| object.class_data(class).map_or(&[][..], |c| c.objects.as_slice());
+    match object.class() {
+        "java.lang.Boolean" | "java.lang.Byte" | "java.lang.Character" | "java.lang.Short" | "java.lang.Integer" | "java.lang.Long"
+        | "java.lang.Float" | "java.lang.Double" | "java.util.concurrent.atomic.AtomicInteger" | "java.util.concurrent.atomic.AtomicLong" =>
+            field("value"),
+        "javax.management.ObjectName" => objects("javax.management.ObjectName").first().map_or(Value::Null, to_json),
+        "javax.management.openmbean.CompositeDataSupport" => field("contents"),
+        "javax.management.openmbean.TabularDataSupport" => match field("dataMap") {
+            Value::Object(rows) => Value::Array(rows.into_iter().map(|(_, row)| row).collect()),
+            _ => Value::Array(vec![]),
+        },
+        "java.util.Date" => object.class_data("java.util.Date")
+            .and_then(|c| c.data.get(0..8))
+            .map_or(Value::Null, |b| Value::from(i64::from_be_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]))),
+        "java.util.ArrayList" | "java.util.LinkedList" | "java.util.ArrayDeque" | "java.util.HashSet" | "java.util.LinkedHashSet"
+        | "java.util.concurrent.CopyOnWriteArrayList" => {
+            let class = if object.class() == "java.util.LinkedHashSet" { "java.util.HashSet" } else { object.class() };
+            Value::Array(objects(class).iter().map(to_json).collect())
+        }
+        // the comparator comes first
+        "java.util.TreeSet" => Value::Array(objects("java.util.TreeSet").iter().skip(1).map(to_json).collect()),
+        "java.util.Arrays$ArrayList" => field("a"),
+        "java.util.Collections$UnmodifiableList" | "java.util.Collections$UnmodifiableRandomAccessList" => field("list"),
+        "java.util.Collections$UnmodifiableSet" | "java.util.Collections$UnmodifiableCollection" => field("c"),
+        "java.util.Collections$UnmodifiableMap" => field("m"),
+        "java.util.Collections$SingletonList" | "java.util.Collections$SingletonSet" => Value::Array(vec![field("element")]),
+        "java.util.Collections$EmptyList" | "java.util.Collections$EmptySet" => Value::Array(vec![]),
+        "java.util.Collections$EmptyMap" => Value::Object(Map::new()),
+        "java.util.HashMap" | "java.util.LinkedHashMap" => map_json(objects("java.util.HashMap")),
+        "java.util.TreeMap" => map_json(objects("java.util.TreeMap")),
+        "java.util.Hashtable" | "java.util.Properties" => map_json(objects("java.util.Hashtable")),
+        // ended by a null key and value
+        "java.util.concurrent.ConcurrentHashMap" => map_json(objects("java.util.concurrent.ConcurrentHashMap")),
+        _ => Value::Object(object.classes.iter().rev()
+            .flat_map(|c| c.fields.iter())
+            .map(|(name, value)| (name.clone(), to_json(value)))
+            .collect()),
+    }
+}
+
+/// Keys and values written one after the other, with keys that aren't strings given as their JSON
+fn map_json(entries: &[JavaValue]) -> Value {
+    Value::Object(entries.chunks(2)
+        .filter_map(|kv| match kv {
+            [JavaValue::Null, JavaValue::Null] => None,
+            [k, v] => Some((match to_json(k) {
+                Value::String(s) => s,
+                k => k.to_string(),
+            }, to_json(v))),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Class and message of a throwable, and of its causes
+pub fn exception_message(value: &JavaValue) -> String {
+    let mut messages: Vec<String> = vec![];
+    let mut current = value;
+    while let JavaValue::Object(object) = current {
+        let message = match object.field("detailMessage") {
+            Some(JavaValue::String(m)) => format!("{}: {}", object.class(), m),
+            _ => object.class().to_owned(),
+        };
+        // remote exceptions repeat the message of their cause
+        if messages.iter().any(|m| m == &message) || messages.len() == 5 {
+            break;
+        }
+        messages.push(message);
+        current = match (object.field("detail"), object.field("cause")) {
+            (Some(detail @ JavaValue::Object(_)), _) => detail,
+            (_, Some(cause)) => cause,
+            _ => &JavaValue::Null,
+        };
+    }
+    messages.join(", caused by ")
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::jmx::serialization::{exception_message, ObjectReader, ObjectWriter, SC_SERIALIZABLE, to_json};
+
+    fn hex(s: &str) -> Vec<u8> {
+        let s: String = s.split_whitespace().collect();
+        (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+    }
+
+    fn read(bytes: &[u8]) -> serde_json::Value {
+        to_json(&ObjectReader::new(bytes).unwrap().read_value().unwrap())
+    }
+
+    #[test]
+    fn boxed_primitives_and_strings_are_read() {
+        // new Long(42), Double.NaN and "héllo", written by ObjectOutputStream
+        assert_eq!(read(&hex("aced0005 7372000e6a6176612e6c616e672e4c6f6e673b8be490cc8f23df0200014a000576616c7565
+            787200106a6176612e6c616e672e4e756d62657286ac951d0b94e08b020000787000000000 0000002a")), json!(42));
+        assert_eq!(read(&hex("aced0005 737200106a6176612e6c616e672e446f75626c6580b3c24a296bfb0402000144000576616c7565
+            787200106a6176612e6c616e672e4e756d62657286ac951d0b94e08b0200007870 7ff8000000000000")), json!(null));
+        assert_eq!(read(&hex("aced0005 740006 68c3a96c6c6f")), json!("héllo"));
+    }
+
+    #[test]
+    fn open_data_and_collections_are_read_as_json() {
+        // CompositeDataSupport of {used: 5, max: 10}, written by ObjectOutputStream
+        assert_eq!(read(&hex("
+            aced00057372002f6a617661782e6d616e6167656d656e742e6f70656e6d6265616e2e436f6d706f7369746544617461537570706f72746f12361ad2
+            285a640200024c000d636f6d706f736974655479706574002a4c6a617661782f6d616e6167656d656e742f6f70656e6d6265616e2f436f6d706f7369
+            7465547970653b4c0008636f6e74656e74737400154c6a6176612f7574696c2f536f727465644d61703b7870737200286a617661782e6d616e616765
+            6d656e742e6f70656e6d6265616e2e436f6d706f7369746554797065b58746eb5a079f420200024c00116e616d65546f4465736372697074696f6e74
+            00134c6a6176612f7574696c2f547265654d61703b4c000a6e616d65546f5479706571007e0005787200236a617661782e6d616e6167656d656e742e
+            6f70656e6d6265616e2e4f70656e5479706580641a91e9eade3c0200034c0009636c6173734e616d657400124c6a6176612f6c616e672f537472696e
+            673b4c000b6465736372697074696f6e71007e00074c0008747970654e616d6571007e000778707400286a617661782e6d616e6167656d656e742e6f
+            70656e6d6265616e2e436f6d706f73697465446174617400017571007e000a737200116a6176612e7574696c2e547265654d61700cc1f63e2d256ae6
+            0300014c000a636f6d70617261746f727400164c6a6176612f7574696c2f436f6d70617261746f723b7870707704000000027400036d617871007e00
+            0e7400047573656471007e000f787371007e000b7077040000000271007e000e737200256a617661782e6d616e6167656d656e742e6f70656e6d6265
+            616e2e53696d706c65547970651ebf4ff8dc6578270200007871007e000674000e6a6176612e6c616e672e4c6f6e6771007e001371007e001371007e
+            000f71007e0012787371007e000b7077040000000271007e000e7372000e6a6176612e6c616e672e4c6f6e673b8be490cc8f23df0200014a00057661
+            6c7565787200106a6176612e6c616e672e4e756d62657286ac951d0b94e08b0200007870000000000000000a71007e000f7371007e00150000000000
+            00000578")), json!({ "max": 10, "used": 5 }));
+        // ArrayList of "-Xmx1g" and "-Dfoo=bar"
+        assert_eq!(read(&hex("
+            aced0005737200136a6176612e7574696c2e41727261794c6973747881d21d99c7619d03000149000473697a65787000000002770400000002740006
+            2d586d7831677400092d44666f6f3d62617278")), json!(["-Xmx1g", "-Dfoo=bar"]));
+        // HashSet of the ObjectName java.lang:type=Runtime, as queryNames returns
+        assert_eq!(read(&hex("
+            aced0005737200116a6176612e7574696c2e48617368536574ba44859596b8b7340300007870770c000000103f400000000000017372001b6a617661
+            782e6d616e6167656d656e742e4f626a6563744e616d650f03a71beb6d15cf03000078707400166a6176612e6c616e673a747970653d52756e74696d
+            657878")), json!(["java.lang:type=Runtime"]));
+    }
+
+    #[test]
+    fn written_objects_are_read_back() {
+        let mut w = ObjectWriter::new();
+        w.data(&7i32.to_be_bytes());
+        w.object("java.rmi.dgc.Lease", -5713411624328831948, SC_SERIALIZABLE, &[("value", "J"), ("vmid", "Ljava/rmi/dgc/VMID;")])
+            .field(&600_000i64.to_be_bytes())
+            .null();
+        w.array("[Ljava.lang.String;", -5921575005990323385, 2).string("user").string("pässword");
+        let bytes = w.finish();
+
+        let mut r = ObjectReader::new(&bytes[..]).unwrap();
+        assert_eq!(r.read_data(4).unwrap(), 7i32.to_be_bytes().to_vec());
+        assert_eq!(to_json(&r.read_value().unwrap()), json!({ "value": 600_000, "vmid": null }));
+        assert_eq!(to_json(&r.read_value().unwrap()), json!(["user", "pässword"]));
+    }
+
+    #[test]
+    fn exceptions_tell_their_class_message_and_cause() {
+        let mut w = ObjectWriter::new();
+        w.object("java.rmi.ServerException", 0, SC_SERIALIZABLE, &[("detail", "Ljava/lang/Throwable;"), ("detailMessage", "Ljava/lang/String;")])
+            .object("javax.management.InstanceNotFoundException", 0, SC_SERIALIZABLE, &[("cause", "Ljava/lang/Throwable;"), ("detailMessage", "Ljava/lang/String;")])
+            .null()
+            .string("java.lang:type=Nope")
+            .string("RemoteException occurred in server thread");
+        let exception = ObjectReader::new(&w.finish()[..]).unwrap().read_value().unwrap();
+        assert_eq!(exception_message(&exception),
+                   "java.rmi.ServerException: RemoteException occurred in server thread, caused by javax.management.InstanceNotFoundException: java.lang:type=Nope");
+    }
+
+    #[test]
+    fn corrupt_streams_fail() {
+        assert!(ObjectReader::new(&b"JRMI"[..]).is_err());
+        assert!(ObjectReader::new(&hex("aced0005 7400ff 41")[..]).unwrap().read_value().is_err());
+        assert!(ObjectReader::new(&hex("aced0005 71007e0009")[..]).unwrap().read_value().is_err());
+    }
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::error::FetchError;
+
+/// Polls the endpoint and extracts the number the pointer refers to
+pub fn get_value(url: &str, json_pointer: &str, timeout: u64) -> Result<f64, FetchError> {
+    get_value_async(url, json_pointer, timeout)
+}
+
+#[tokio::main]
+async fn get_value_async(url: &str, json_pointer: &str, timeout: u64) -> Result<f64, FetchError> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(timeout))
+        .build()?;
+    let response = client.get(url).send().await?;
+    if !response.status().is_success() {
+        return Err(FetchError::of_status(response.status(), format!("Request to {} failed with status {}", url, response.status())));
+    }
+    let body: Value = response.json().await?;
+    Ok(extract_number(&body, json_pointer)?)
+}
+
+/// Numbers can also be given as strings, as some endpoints do for big or precise ones, and booleans count as 0 or 1
+pub fn extract_number(json: &Value, json_pointer: &str) -> Result<f64, String> {
+    match json.pointer(json_pointer) {
+        Some(Value::Number(n)) => n.as_f64().ok_or_else(|| format!("{} is not a valid number", n)),
+        Some(Value::String(s)) => s.trim().parse().map_err(|_| format!("{} at {} is not a number", s, json_pointer)),
+        Some(Value::Bool(b)) => Ok(if *b { 1.0 } else { 0.0 }),
+        Some(other) => Err(format!("{} at {} is not a number", other, json_pointer)),
+        None => Err(format!("Nothing at {}", json_pointer)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::json_metrics::client::extract_number;
+
+    #[test]
+    fn numbers_are_extracted_with_pointers() {
+        let json = json!({"queue": {"depth": 12}, "pools": [{"active": "3"}], "healthy": true, "name": "app"});
+        assert_eq!(extract_number(&json, "/queue/depth"), Ok(12.0));
+        assert_eq!(extract_number(&json, "/pools/0/active"), Ok(3.0));
+        assert_eq!(extract_number(&json, "/healthy"), Ok(1.0));
+        assert_eq!(extract_number(&json, "/name"), Err("app at /name is not a number".to_owned()));
+        assert_eq!(extract_number(&json, "/queue/size"), Err("Nothing at /queue/size".to_owned()));
+    }
+}
@@ -0,0 +1,6 @@
+//! Numbers out of any JSON endpoint
+
+/// Settings of the source
+pub mod model;
+/// Polls the endpoint and extracts the number a JSON pointer refers to
+pub mod client;
@@ -0,0 +1,14 @@
+use serde::Deserialize;
+
+/// A number polled from a JSON endpoint
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct JsonMetricSettings {
+    /// Name the value is charted under
+    pub name: String,
+    /// Endpoint answering with JSON
+    pub url: String,
+    /// RFC 6901 pointer to the value in the response, e.g. `/queue/depth` or `/pools/0/active`
+    pub json_pointer: String,
+    /// Polling interval in ms, every tick if not set
+    pub interval: Option<u64>,
+}
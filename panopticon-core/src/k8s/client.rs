@@ -0,0 +1,124 @@
+use std::process::Command;
+
+use chrono::Local;
+use serde_json::Value;
+
+use crate::error::FetchError;
+use crate::k8s::model::{ContainerUsage, K8sPodSettings, PodMetrics};
+
+///
+/// Reads pod resource usage from the metrics API (served by metrics-server) with the `kubectl` client.
+///
+/// Relying on kubectl keeps the kubeconfig contexts and all of their authentication methods available.
+pub struct K8sClient {
+    settings: K8sPodSettings,
+}
+
+impl K8sClient {
+    /// Fails if `kubectl` can't be run
+    pub fn new(settings: K8sPodSettings) -> Result<K8sClient, FetchError> {
+        Command::new(&settings.tool)
+            .args(["version", "--client"])
+            .output()
+            .map_err(|e| format!("Couldn't run {}, Kubernetes support requires the kubectl client: {}", settings.tool, e))?;
+        Ok(K8sClient { settings })
+    }
+
+    /// Usage and limits of the containers of the pod
+    pub fn poll(&self) -> Result<PodMetrics, FetchError> {
+        let pod = &self.settings.pod;
+        let spec = self.get(&["get", "pod", &pod.name, "--namespace", &pod.namespace, "--output", "json"])?;
+        let metrics_path = format!("/apis/metrics.k8s.io/v1beta1/namespaces/{}/pods/{}", pod.namespace, pod.name);
+        let metrics = self.get(&["get", "--raw", &metrics_path])?;
+        Ok(parse_pod_metrics(&spec, &metrics))
+    }
+
+    fn get(&self, args: &[&str]) -> Result<Value, String> {
+        let output = Command::new(&self.settings.tool)
+            .args(args)
+            .arg(format!("--request-timeout={}ms", self.settings.timeout))
+            .output()
+            .map_err(|e| format!("Couldn't run {}: {}", self.settings.tool, e))?;
+        if !output.status.success() {
+            return Err(format!("kubectl failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        serde_json::from_slice(&output.stdout).map_err(|e| format!("Invalid kubectl output: {}", e))
+    }
+}
+
+fn parse_pod_metrics(spec: &Value, metrics: &Value) -> PodMetrics {
+    let statuses = spec["status"]["containerStatuses"].as_array().cloned().unwrap_or_default();
+    let usages = metrics["containers"].as_array().cloned().unwrap_or_default();
+    let containers = spec["spec"]["containers"].as_array().map_or(vec![], |cs| cs.iter().filter_map(|c| {
+        let name = c["name"].as_str()?;
+        let status = statuses.iter().find(|s| s["name"] == name);
+        let usage = usages.iter().find(|u| u["name"] == name).map(|u| &u["usage"]);
+        let quantity = |v: Option<&Value>| v.and_then(|q| q.as_str()).and_then(parse_quantity);
+        Some(ContainerUsage {
+            name: name.to_owned(),
+            cpu_cores: quantity(usage.map(|u| &u["cpu"])).unwrap_or(0.0),
+            memory_bytes: quantity(usage.map(|u| &u["memory"])).unwrap_or(0.0),
+            cpu_limit: quantity(Some(&c["resources"]["limits"]["cpu"])),
+            memory_limit: quantity(Some(&c["resources"]["limits"]["memory"])),
+            restarts: status.and_then(|s| s["restartCount"].as_u64()).unwrap_or(0) as u32,
+            last_termination: status.and_then(|s| s["lastState"]["terminated"]["reason"].as_str()).map(|r| r.to_owned()),
+        })
+    }).collect());
+    PodMetrics {
+        timestamp: Local::now(),
+        phase: spec["status"]["phase"].as_str().unwrap_or("Unknown").to_owned(),
+        containers,
+    }
+}
+
+/// Parses Kubernetes quantities, e.g. `250m` cores or `512Mi` bytes
+fn parse_quantity(q: &str) -> Option<f64> {
+    const MULTIPLIERS: [(&str, f64); 10] = [
+        ("Ki", 1024.0), ("Mi", 1_048_576.0), ("Gi", 1_073_741_824.0), ("Ti", 1_099_511_627_776.0),
+        ("k", 1e3), ("M", 1e6), ("G", 1e9), ("T", 1e12), ("P", 1e15), ("E", 1e18),
+    ];
+    // dividing keeps e.g. 412m exactly 0.412
+    const DIVISORS: [(&str, f64); 3] = [("n", 1e9), ("u", 1e6), ("m", 1e3)];
+    let with_suffix = |suffixes: &[(&str, f64)], scale: fn(f64, f64) -> f64| suffixes.iter()
+        .find_map(|(suffix, factor)| q.strip_suffix(suffix).and_then(|n| n.parse::<f64>().ok()).map(|n| scale(n, *factor)));
+    with_suffix(&MULTIPLIERS, |n, f| n * f)
+        .or_else(|| with_suffix(&DIVISORS, |n, f| n / f))
+        .or_else(|| q.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::k8s::client::{parse_pod_metrics, parse_quantity};
+
+    #[test]
+    fn pod_metrics_are_parsed() {
+        assert_eq!(parse_quantity("250m"), Some(0.25));
+        assert_eq!(parse_quantity("1500000n"), Some(0.0015));
+        assert_eq!(parse_quantity("512Mi"), Some(536_870_912.0));
+        assert_eq!(parse_quantity("2"), Some(2.0));
+        assert_eq!(parse_quantity("lots"), None);
+
+        let spec = json!({
+            "spec": {"containers": [
+                {"name": "app", "resources": {"limits": {"cpu": "1", "memory": "1Gi"}}},
+                {"name": "sidecar", "resources": {}}
+            ]},
+            "status": {"phase": "Running", "containerStatuses": [
+                {"name": "app", "restartCount": 3, "lastState": {"terminated": {"reason": "OOMKilled", "exitCode": 137}}},
+                {"name": "sidecar", "restartCount": 0, "lastState": {}}
+            ]}
+        });
+        let metrics = json!({"containers": [
+            {"name": "app", "usage": {"cpu": "412m", "memory": "900Mi"}},
+            {"name": "sidecar", "usage": {"cpu": "3m", "memory": "20Mi"}}
+        ]});
+        let pod = parse_pod_metrics(&spec, &metrics);
+        assert_eq!(pod.phase, "Running");
+        assert_eq!((pod.containers[0].cpu_cores, pod.containers[0].cpu_limit, pod.containers[0].restarts), (0.412, Some(1.0), 3));
+        assert!(pod.containers[0].was_oom_killed());
+        assert_eq!((pod.containers[1].memory_bytes, pod.containers[1].memory_limit), (20_971_520.0, None));
+        assert!(!pod.containers[1].was_oom_killed());
+    }
+}
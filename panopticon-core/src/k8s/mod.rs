@@ -0,0 +1,6 @@
+//! Kubernetes pods, through the metrics API
+
+/// Resource usage of the containers of a pod, with the settings of the source
+pub mod model;
+/// Reads the usage with kubectl
+pub mod client;
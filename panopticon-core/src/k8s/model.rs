@@ -0,0 +1,78 @@
+use std::fmt;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// A pod, given as `namespace/pod`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PodRef {
+    /// Namespace of the pod
+    pub namespace: String,
+    /// Name of the pod
+    pub name: String,
+}
+
+impl PodRef {
+    /// Parses a pod given as `namespace/pod`
+    pub fn parse(s: &str) -> Result<PodRef, String> {
+        match s.split_once('/') {
+            Some((namespace, name)) if !namespace.is_empty() && !name.is_empty() && !name.contains('/') =>
+                Ok(PodRef { namespace: namespace.to_owned(), name: name.to_owned() }),
+            _ => Err(format!("Expected a pod as namespace/pod, got {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for PodRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.namespace, self.name)
+    }
+}
+
+/// Pod polled through the Kubernetes metrics API
+#[derive(Clone)]
+pub struct K8sPodSettings {
+    /// The pod to read the usage of
+    pub pod: PodRef,
+    /// Path to the `kubectl` client used to reach the cluster
+    pub tool: String,
+    /// Time in ms each kubectl call has to finish
+    pub timeout: u64,
+}
+
+/// Resource usage and limits of a container of the pod
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ContainerUsage {
+    /// Name of the container
+    pub name: String,
+    /// CPU cores used over the metrics window, from the metrics API
+    pub cpu_cores: f64,
+    /// Memory used over the metrics window, from the metrics API
+    pub memory_bytes: f64,
+    /// CPU limit from the pod spec, if set
+    pub cpu_limit: Option<f64>,
+    /// Memory limit from the pod spec, in bytes, if set
+    pub memory_limit: Option<f64>,
+    /// Times the container was restarted
+    pub restarts: u32,
+    /// Why the previous instance of the container stopped, e.g. `OOMKilled`
+    pub last_termination: Option<String>,
+}
+
+impl ContainerUsage {
+    /// Whether the previous instance of the container was killed for using too much memory
+    pub fn was_oom_killed(&self) -> bool {
+        self.last_termination.as_deref() == Some("OOMKilled")
+    }
+}
+
+/// Usage of the containers of a pod
+#[derive(Deserialize, Serialize)]
+pub struct PodMetrics {
+    /// When the usage was read
+    pub timestamp: DateTime<Local>,
+    /// e.g. `Running`, `Pending`
+    pub phase: String,
+    /// Usage of every container of the pod
+    pub containers: Vec<ContainerUsage>,
+}
@@ -0,0 +1,533 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use chrono::Local;
+
+use crate::error::{FetchError, FetchErrorKind};
+use crate::kafka::model::{KafkaLag, KafkaLagSettings, PartitionLag};
+
+const LIST_OFFSETS: i16 = 2;
+const METADATA: i16 = 3;
+const OFFSET_FETCH: i16 = 9;
+const FIND_COORDINATOR: i16 = 10;
+const CLIENT_ID: &str = "panopticon-tui";
+/// Timestamp asking ListOffsets for the end of a partition
+const LATEST_OFFSET: i64 = -1;
+/// Responses this client asks for are far smaller, bigger sizes are read from brokers speaking another protocol
+const MAX_RESPONSE_SIZE: usize = 64 * 1024 * 1024;
+/// First bytes of TLS records, an alert or a handshake, sent back by TLS listeners to plaintext requests
+const TLS_RECORD_TYPES: [u8; 2] = [0x15, 0x16];
+
+type TopicPartition = (String, i32);
+
+///
+/// Computes consumer group lag from the brokers, speaking just enough of the Kafka protocol:
+/// committed offsets come from the group coordinator, end offsets from the partition leaders.
+///
+/// The protocol is spoken here rather than through a client crate: the `kafka` crate only fetches committed offsets
+/// of topics given up front, where OffsetFetch v2 lists all the topics a group committed to, and `rdkafka` builds
+/// librdkafka from C sources, the kind of native build the JMX clients were freed from.
+///
+/// Connections are kept open between polls, by broker address, and opened again after a failure.
+/// Only request versions available since Kafka 0.11 are used. Only plaintext listeners are supported.
+pub struct KafkaLagClient {
+    settings: KafkaLagSettings,
+    connections: HashMap<String, Connection>,
+}
+
+impl KafkaLagClient {
+    /// A client of the brokers, connecting on the first poll
+    pub fn new(settings: KafkaLagSettings) -> KafkaLagClient {
+        KafkaLagClient { settings, connections: HashMap::new() }
+    }
+
+    /// Lag of the groups on every partition they committed offsets for
+    pub fn poll(&mut self) -> Result<KafkaLag, FetchError> {
+        let mut committed = vec![];
+        let mut errors = vec![];
+        for group in self.settings.groups.clone() {
+            match self.group_offsets(&group) {
+                Ok(offsets) => committed.extend(offsets.into_iter().map(|(t, p, o)| (group.to_owned(), t, p, o))),
+                // brokers that can't be reached fail every group alike
+                Err(e) if e.kind == FetchErrorKind::Connection && self.connections.is_empty() => return Err(e),
+                Err(e) => errors.push(format!("{}: {}", group, e)),
+            }
+        }
+
+        let mut topics: Vec<String> = committed.iter().map(|(_, t, _, _)| t.to_owned()).collect();
+        topics.sort();
+        topics.dedup();
+        let mut end_offsets: HashMap<TopicPartition, i64> = HashMap::new();
+        if !topics.is_empty() {
+            let metadata = parse_metadata(&self.bootstrap_request(METADATA, 1, &metadata_request(&topics)?)?)?;
+            let mut by_leader: HashMap<i32, Vec<TopicPartition>> = HashMap::new();
+            for (tp, leader) in metadata.leaders {
+                by_leader.entry(leader).or_default().push(tp);
+            }
+            for (leader, partitions) in by_leader {
+                let result = metadata.brokers.get(&leader)
+                    .ok_or_else(|| FetchError::from(format!("Broker {} isn't in the cluster metadata", leader)))
+                    .and_then(|address| self.request(address, LIST_OFFSETS, 1, &list_offsets_request(&partitions)?))
+                    .and_then(|r| parse_list_offsets(&r).map_err(FetchError::from));
+                match result {
+                    Ok(offsets) => end_offsets.extend(offsets),
+                    Err(e) => errors.push(format!("broker {}: {}", leader, e)),
+                }
+            }
+        }
+
+        let mut partitions: Vec<PartitionLag> = committed.into_iter()
+            .filter_map(|(group, topic, partition, committed)| {
+                let end = *end_offsets.get(&(topic.to_owned(), partition))?;
+                Some(PartitionLag { group, topic, partition, committed, end })
+            })
+            .collect();
+        partitions.sort_by(|a, b| (&a.group, &a.topic, a.partition).cmp(&(&b.group, &b.topic, b.partition)));
+        Ok(KafkaLag { timestamp: Local::now(), partitions, errors })
+    }
+
+    /// Committed offsets of the group, by topic and partition
+    fn group_offsets(&mut self, group: &str) -> Result<Vec<(String, i32, i64)>, FetchError> {
+        let coordinator = parse_find_coordinator(&self.bootstrap_request(FIND_COORDINATOR, 1, &find_coordinator_request(group)?)?)?;
+        Ok(parse_offset_fetch(&self.request(&coordinator, OFFSET_FETCH, 2, &offset_fetch_request(group)?)?)?)
+    }
+
+    /// Sends the request to the first of the bootstrap brokers that answers it
+    fn bootstrap_request(&mut self, api_key: i16, version: i16, body: &[u8]) -> Result<Vec<u8>, FetchError> {
+        let mut error = FetchError::from("No Kafka brokers given");
+        for broker in self.settings.brokers.clone() {
+            match self.request(&broker, api_key, version, body) {
+                Ok(response) => return Ok(response),
+                Err(e) => error = e,
+            }
+        }
+        Err(error)
+    }
+
+    /// Sends the request over the connection kept to the broker, dropping the connection if it fails
+    fn request(&mut self, address: &str, api_key: i16, version: i16, body: &[u8]) -> Result<Vec<u8>, FetchError> {
+        let mut connection = match self.connections.remove(address) {
+            Some(c) => c,
+            None => Connection::open(address, Duration::from_millis(self.settings.timeout))?,
+        };
+        let response = connection.request(api_key, version, body)?;
+        self.connections.insert(address.to_owned(), connection);
+        Ok(response)
+    }
+}
+
+struct Connection {
+    stream: TcpStream,
+    correlation_id: i32,
+}
+
+impl Connection {
+    fn open(address: &str, timeout: Duration) -> Result<Connection, FetchError> {
+        let error = |e: std::io::Error| FetchError::from(e).context(&format!("Couldn't connect to Kafka broker {}", address));
+        let addr = address.to_socket_addrs().map_err(error)?
+            .next()
+            .ok_or_else(|| FetchError::connection(format!("Couldn't resolve Kafka broker {}", address)))?;
+        let stream = TcpStream::connect_timeout(&addr, timeout).map_err(error)?;
+        stream.set_read_timeout(Some(timeout)).map_err(error)?;
+        stream.set_write_timeout(Some(timeout)).map_err(error)?;
+        Ok(Connection { stream, correlation_id: 0 })
+    }
+
+    /// Sends a request and returns the body of its response
+    fn request(&mut self, api_key: i16, version: i16, body: &[u8]) -> Result<Vec<u8>, FetchError> {
+        self.correlation_id = self.correlation_id.wrapping_add(1);
+        let mut header = Encoder::default();
+        header.i16(api_key).i16(version).i32(self.correlation_id).string(CLIENT_ID)?;
+        let mut message = Encoder::default();
+        message.len(header.0.len() + body.len())?;
+        message.0.extend(header.0);
+        message.0.extend(body);
+        self.stream.write_all(&message.0)?;
+
+        let mut size = [0; 4];
+        self.stream.read_exact(&mut size).map_err(|e| self.unanswered(e))?;
+        if TLS_RECORD_TYPES.contains(&size[0]) {
+            return Err(FetchError::connection("The Kafka broker answered with TLS, only plaintext listeners are supported"));
+        }
+        let size = i32::from_be_bytes(size).max(0) as usize;
+        if size > MAX_RESPONSE_SIZE {
+            return Err(FetchError::from(format!("Kafka response of {} bytes is too big, the broker may not speak the Kafka protocol on this port", size)));
+        }
+        let mut response = vec![0; size];
+        self.stream.read_exact(&mut response)?;
+        let mut d = Decoder::new(&response);
+        if d.i32()? != self.correlation_id {
+            return Err(FetchError::from("Kafka response doesn't match the request"));
+        }
+        Ok(response[d.pos..].to_vec())
+    }
+
+    /// Brokers close the connection without an answer when their listener expects SASL authentication first
+    fn unanswered(&self, e: std::io::Error) -> FetchError {
+        match e.kind() {
+            std::io::ErrorKind::UnexpectedEof | std::io::ErrorKind::ConnectionReset if self.correlation_id == 1 => FetchError::connection(
+                "The Kafka broker closed the connection without answering, its listener may require SASL or TLS, only plaintext listeners are supported"
+            ),
+            _ => FetchError::from(e),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Encoder(Vec<u8>);
+
+impl Encoder {
+    fn i8(&mut self, v: i8) -> &mut Encoder {
+        self.0.extend(&v.to_be_bytes());
+        self
+    }
+
+    fn i16(&mut self, v: i16) -> &mut Encoder {
+        self.0.extend(&v.to_be_bytes());
+        self
+    }
+
+    fn i32(&mut self, v: i32) -> &mut Encoder {
+        self.0.extend(&v.to_be_bytes());
+        self
+    }
+
+    fn i64(&mut self, v: i64) -> &mut Encoder {
+        self.0.extend(&v.to_be_bytes());
+        self
+    }
+
+    /// Fails on strings longer than their i16 length can tell
+    fn string(&mut self, v: &str) -> Result<&mut Encoder, String> {
+        let len = i16::try_from(v.len()).map_err(|_| format!("Kafka strings are {} bytes at most, got one of {}", i16::MAX, v.len()))?;
+        self.i16(len);
+        self.0.extend(v.as_bytes());
+        Ok(self)
+    }
+
+    /// Length of an array or a message, failing on those longer than their i32 length can tell
+    fn len(&mut self, n: usize) -> Result<&mut Encoder, String> {
+        let len = i32::try_from(n).map_err(|_| format!("{} items are more than Kafka allows", n))?;
+        Ok(self.i32(len))
+    }
+}
+
+struct Decoder<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(buf: &'a [u8]) -> Decoder<'a> {
+        Decoder { buf, pos: 0 }
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let bytes = self.bytes(N)?;
+        let mut a = [0; N];
+        a.copy_from_slice(bytes);
+        Ok(a)
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let bytes = self.buf.get(self.pos..self.pos + n).ok_or("Truncated Kafka response")?;
+        self.pos += n;
+        Ok(bytes)
+    }
+
+    fn i8(&mut self) -> Result<i8, String> {
+        self.take().map(i8::from_be_bytes)
+    }
+
+    fn i16(&mut self) -> Result<i16, String> {
+        self.take().map(i16::from_be_bytes)
+    }
+
+    fn i32(&mut self) -> Result<i32, String> {
+        self.take().map(i32::from_be_bytes)
+    }
+
+    fn i64(&mut self) -> Result<i64, String> {
+        self.take().map(i64::from_be_bytes)
+    }
+
+    fn nullable_string(&mut self) -> Result<Option<String>, String> {
+        match self.i16()? {
+            n if n < 0 => Ok(None),
+            n => Ok(Some(String::from_utf8_lossy(self.bytes(n as usize)?).into_owned())),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.nullable_string().map(|s| s.unwrap_or_default())
+    }
+
+    fn array<T, F>(&mut self, mut item: F) -> Result<Vec<T>, String>
+        where F: FnMut(&mut Decoder<'a>) -> Result<T, String> {
+        let n = self.i32()?.max(0) as usize;
+        // every item takes a byte at least, so that a corrupt length fails before being allocated
+        if n > self.buf.len() - self.pos {
+            return Err("Truncated Kafka response".to_owned());
+        }
+        (0..n).map(|_| item(self)).collect()
+    }
+}
+
+fn error_name(code: i16) -> String {
+    match code {
+        3 => "UNKNOWN_TOPIC_OR_PARTITION".to_owned(),
+        6 => "NOT_LEADER_FOR_PARTITION".to_owned(),
+        14 => "COORDINATOR_LOAD_IN_PROGRESS".to_owned(),
+        15 => "COORDINATOR_NOT_AVAILABLE".to_owned(),
+        16 => "NOT_COORDINATOR".to_owned(),
+        29 => "TOPIC_AUTHORIZATION_FAILED".to_owned(),
+        30 => "GROUP_AUTHORIZATION_FAILED".to_owned(),
+        _ => format!("error code {}", code),
+    }
+}
+
+fn find_coordinator_request(group: &str) -> Result<Vec<u8>, String> {
+    let mut e = Encoder::default();
+    // key type 0 is a consumer group
+    e.string(group)?.i8(0);
+    Ok(e.0)
+}
+
+/// Address of the group coordinator
+fn parse_find_coordinator(body: &[u8]) -> Result<String, String> {
+    let mut d = Decoder::new(body);
+    let _throttle_time = d.i32()?;
+    let error = d.i16()?;
+    let message = d.nullable_string()?;
+    if error != 0 {
+        return Err(format!("Couldn't find the group coordinator: {}", message.unwrap_or_else(|| error_name(error))));
+    }
+    let _node_id = d.i32()?;
+    let host = d.string()?;
+    let port = d.i32()?;
+    Ok(format!("{}:{}", host, port))
+}
+
+fn offset_fetch_request(group: &str) -> Result<Vec<u8>, String> {
+    let mut e = Encoder::default();
+    // a null topic list fetches offsets of all the topics the group committed to
+    e.string(group)?.i32(-1);
+    Ok(e.0)
+}
+
+/// Committed offsets by topic and partition, leaving out partitions without one
+fn parse_offset_fetch(body: &[u8]) -> Result<Vec<(String, i32, i64)>, String> {
+    let mut d = Decoder::new(body);
+    let topics = d.array(|d| {
+        let topic = d.string()?;
+        let partitions = d.array(|d| {
+            let partition = d.i32()?;
+            let offset = d.i64()?;
+            let _metadata = d.nullable_string()?;
+            let _error = d.i16()?;
+            Ok((partition, offset))
+        })?;
+        Ok((topic, partitions))
+    })?;
+    let error = d.i16()?;
+    if error != 0 {
+        return Err(format!("Couldn't fetch committed offsets: {}", error_name(error)));
+    }
+    Ok(topics.into_iter()
+        .flat_map(|(topic, partitions)| partitions.into_iter()
+            .filter(|(_, offset)| *offset >= 0)
+            .map(move |(p, offset)| (topic.to_owned(), p, offset)))
+        .collect())
+}
+
+fn metadata_request(topics: &[String]) -> Result<Vec<u8>, String> {
+    let mut e = Encoder::default();
+    e.len(topics.len())?;
+    for t in topics {
+        e.string(t)?;
+    }
+    Ok(e.0)
+}
+
+struct Metadata {
+    /// Broker addresses by node id
+    brokers: HashMap<i32, String>,
+    /// Node id of the leader of every partition
+    leaders: Vec<(TopicPartition, i32)>,
+}
+
+fn parse_metadata(body: &[u8]) -> Result<Metadata, String> {
+    let mut d = Decoder::new(body);
+    let brokers = d.array(|d| {
+        let node_id = d.i32()?;
+        let host = d.string()?;
+        let port = d.i32()?;
+        let _rack = d.nullable_string()?;
+        Ok((node_id, format!("{}:{}", host, port)))
+    })?;
+    let _controller_id = d.i32()?;
+    let topics = d.array(|d| {
+        let _error = d.i16()?;
+        let topic = d.string()?;
+        let _is_internal = d.i8()?;
+        let partitions = d.array(|d| {
+            let _error = d.i16()?;
+            let partition = d.i32()?;
+            let leader = d.i32()?;
+            let _replicas = d.array(|d| d.i32())?;
+            let _isr = d.array(|d| d.i32())?;
+            Ok((partition, leader))
+        })?;
+        Ok((topic, partitions))
+    })?;
+    let leaders = topics.into_iter()
+        .flat_map(|(topic, partitions)| partitions.into_iter()
+            // partitions without a leader have -1
+            .filter(|(_, leader)| *leader >= 0)
+            .map(move |(p, leader)| ((topic.to_owned(), p), leader)))
+        .collect();
+    Ok(Metadata { brokers: brokers.into_iter().collect(), leaders })
+}
+
+fn list_offsets_request(partitions: &[TopicPartition]) -> Result<Vec<u8>, String> {
+    let mut topics: Vec<&str> = partitions.iter().map(|(t, _)| t.as_str()).collect();
+    topics.sort();
+    topics.dedup();
+    let mut e = Encoder::default();
+    // replica id -1 is a regular consumer
+    e.i32(-1).len(topics.len())?;
+    for topic in topics {
+        let ps: Vec<i32> = partitions.iter().filter(|(t, _)| t == topic).map(|(_, p)| *p).collect();
+        e.string(topic)?.len(ps.len())?;
+        for p in ps {
+            e.i32(p).i64(LATEST_OFFSET);
+        }
+    }
+    Ok(e.0)
+}
+
+/// End offsets by topic and partition
+fn parse_list_offsets(body: &[u8]) -> Result<Vec<(TopicPartition, i64)>, String> {
+    let mut d = Decoder::new(body);
+    let topics = d.array(|d| {
+        let topic = d.string()?;
+        let partitions = d.array(|d| {
+            let partition = d.i32()?;
+            let error = d.i16()?;
+            let _timestamp = d.i64()?;
+            let offset = d.i64()?;
+            Ok((partition, error, offset))
+        })?;
+        Ok((topic, partitions))
+    })?;
+    Ok(topics.into_iter()
+        .flat_map(|(topic, partitions)| partitions.into_iter()
+            .filter(|(_, error, _)| *error == 0)
+            .map(move |(p, _, offset)| ((topic.to_owned(), p), offset)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::kafka::client::{Decoder, Encoder, FIND_COORDINATOR, KafkaLagClient, LIST_OFFSETS, METADATA, OFFSET_FETCH, list_offsets_request, offset_fetch_request, parse_list_offsets, parse_offset_fetch};
+    use crate::kafka::model::KafkaLagSettings;
+
+    /// Address of a single broker cluster, counting the connections made to it
+    fn broker(connections: Arc<AtomicUsize>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port() as i32;
+        std::thread::spawn(move || for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            connections.fetch_add(1, Ordering::SeqCst);
+            std::thread::spawn(move || loop {
+                let mut size = [0; 4];
+                if stream.read_exact(&mut size).is_err() {
+                    return;
+                }
+                let mut request = vec![0; i32::from_be_bytes(size) as usize];
+                stream.read_exact(&mut request).unwrap();
+                let mut d = Decoder::new(&request);
+                let api_key = d.i16().unwrap();
+                let _version = d.i16().unwrap();
+                let mut e = Encoder::default();
+                e.i32(d.i32().unwrap());
+                match api_key {
+                    FIND_COORDINATOR => { e.i32(0).i16(0).i16(-1).i32(1).string("127.0.0.1").unwrap().i32(port); }
+                    OFFSET_FETCH => { e.i32(1).string("orders").unwrap().i32(1).i32(0).i64(40).i16(-1).i16(0).i16(0); }
+                    METADATA => {
+                        e.i32(1).i32(1).string("127.0.0.1").unwrap().i32(port).i16(-1).i32(1)
+                            .i32(1).i16(0).string("orders").unwrap().i8(0).i32(1).i16(0).i32(0).i32(1).i32(0).i32(0);
+                    }
+                    LIST_OFFSETS => { e.i32(1).string("orders").unwrap().i32(1).i32(0).i16(0).i64(-1).i64(50); }
+                    _ => return,
+                }
+                let mut response = Encoder::default();
+                response.len(e.0.len()).unwrap();
+                response.0.extend(e.0);
+                stream.write_all(&response.0).unwrap();
+            });
+        });
+        format!("127.0.0.1:{}", port)
+    }
+
+    #[test]
+    fn committed_offsets_are_parsed() {
+        let mut e = Encoder::default();
+        e.i32(1).string("orders").unwrap().i32(2)
+            .i32(0).i64(42).i16(-1).i16(0)
+            // nothing committed to partition 1 yet
+            .i32(1).i64(-1).i16(-1).i16(0)
+            .i16(0);
+        assert_eq!(parse_offset_fetch(&e.0).unwrap(), vec![("orders".to_owned(), 0, 42)]);
+
+        let mut e = Encoder::default();
+        e.i32(0).i16(30);
+        assert_eq!(parse_offset_fetch(&e.0), Err("Couldn't fetch committed offsets: GROUP_AUTHORIZATION_FAILED".to_owned()));
+        assert_eq!(parse_offset_fetch(&[0, 0, 0, 1]), Err("Truncated Kafka response".to_owned()));
+        // a corrupt array length fails without allocating that many items
+        assert_eq!(parse_offset_fetch(&[0x7f, 0xff, 0xff, 0xff]), Err("Truncated Kafka response".to_owned()));
+    }
+
+    #[test]
+    fn end_offsets_are_requested_by_topic() {
+        let partitions = vec![("orders".to_owned(), 0), ("orders".to_owned(), 1), ("payments".to_owned(), 0)];
+        let mut e = Encoder::default();
+        e.i32(-1).i32(2)
+            .string("orders").unwrap().i32(2).i32(0).i64(-1).i32(1).i64(-1)
+            .string("payments").unwrap().i32(1).i32(0).i64(-1);
+        assert_eq!(list_offsets_request(&partitions).unwrap(), e.0);
+
+        let mut e = Encoder::default();
+        e.i32(1).string("orders").unwrap().i32(2)
+            .i32(0).i16(0).i64(-1).i64(50)
+            .i32(1).i16(6).i64(-1).i64(-1);
+        assert_eq!(parse_list_offsets(&e.0).unwrap(), vec![(("orders".to_owned(), 0), 50)]);
+    }
+
+    #[test]
+    fn oversized_strings_are_refused() {
+        let group = "g".repeat(i16::MAX as usize + 1);
+        assert_eq!(offset_fetch_request(&group), Err(format!("Kafka strings are 32767 bytes at most, got one of {}", group.len())));
+    }
+
+    #[test]
+    fn connections_are_kept_between_polls() {
+        let connections = Arc::new(AtomicUsize::new(0));
+        let settings = KafkaLagSettings { brokers: vec![broker(connections.clone())], groups: vec!["billing".to_owned()], timeout: 1000 };
+        let mut client = KafkaLagClient::new(settings);
+        for _ in 0..3 {
+            let lag = client.poll().unwrap();
+            assert_eq!(lag.errors, Vec::<String>::new());
+            assert_eq!((lag.partitions[0].committed, lag.partitions[0].end), (40, 50));
+        }
+        assert_eq!(connections.load(Ordering::SeqCst), 1);
+    }
+}
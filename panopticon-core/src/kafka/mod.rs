@@ -0,0 +1,6 @@
+//! Lag of Kafka consumer groups
+
+/// Offsets and lag by partition, with the settings of the source
+pub mod model;
+/// Speaks the Kafka protocol to the group coordinator and partition leaders
+pub mod client;
@@ -0,0 +1,46 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Consumer groups whose lag is computed from the Kafka protocol
+#[derive(Clone)]
+pub struct KafkaLagSettings {
+    /// Bootstrap brokers, e.g. localhost:9092
+    pub brokers: Vec<String>,
+    /// Consumer groups to compute the lag of
+    pub groups: Vec<String>,
+    /// Time in ms each request has to be answered
+    pub timeout: u64,
+}
+
+/// Offsets of a group on a partition
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PartitionLag {
+    /// The consumer group
+    pub group: String,
+    /// Topic of the partition
+    pub topic: String,
+    /// Partition number
+    pub partition: i32,
+    /// Offset committed by the group
+    pub committed: i64,
+    /// Offset of the next record written to the partition
+    pub end: i64,
+}
+
+impl PartitionLag {
+    /// Records written but not consumed yet
+    pub fn lag(&self) -> i64 {
+        (self.end - self.committed).max(0)
+    }
+}
+
+/// Lag of all the partitions the groups consume
+#[derive(Deserialize, Serialize)]
+pub struct KafkaLag {
+    /// When the offsets were read
+    pub timestamp: DateTime<Local>,
+    /// Lag by group, topic and partition
+    pub partitions: Vec<PartitionLag>,
+    /// Groups whose offsets couldn't be read, with the reason
+    pub errors: Vec<String>,
+}
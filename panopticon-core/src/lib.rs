@@ -0,0 +1,49 @@
+//!
+//! Clients of the sources panopticon-tui monitors, and the models of what they return.
+//!
+//! Each source has a module with its `model`, settings included, and a `client` reading from it, e.g.
+//! [`zio::zmx`] dumps the fibers of a zio-zmx server, [`jmx::client`] reads Slick, HikariCP, Kafka and
+//! JVM metrics over JMX or Jolokia, and [`akka::client`] fetches actor trees and counts from akka-tools.
+//!
+//! [`fetcher::Fetcher`] holds the clients of all the configured sources, answering every
+//! [`fetcher::FetcherRequest`] with a [`fetcher::FetcherResponse`]:
+//!
+//! ```no_run
+//! use panopticon_core::fetcher::{Fetcher, Sources};
+//!
+//! let mut fetcher = Fetcher::new(Sources { zio_zmx: Some("localhost:6789".to_owned()), ..Sources::default() });
+//! let fibers = fetcher.dump_fibers().unwrap();
+//! println!("{} fibers", fibers.len());
+//! ```
+//!
+//! Failures are reported as [`error::FetchError`]s, telling their kind with a message meant for people.
+//! Clients block until the source answered, so the TUI calls them from a thread of their own.
+
+#![warn(missing_docs)]
+
+pub mod actuator;
+pub mod akka;
+pub mod cats_effect;
+pub mod command;
+pub mod docker;
+pub mod error;
+pub mod fetcher;
+/// Shared with the servers of the TUI, not part of the API
+#[doc(hidden)]
+pub mod http_server;
+pub mod jfr;
+pub mod jmx;
+pub mod json_metrics;
+pub mod k8s;
+pub mod kafka;
+pub mod logs;
+pub mod otlp;
+pub mod postgres;
+pub mod prometheus;
+pub mod proxy;
+pub mod rabbitmq;
+pub mod redis;
+pub mod sql;
+pub mod statsd;
+pub mod traces;
+pub mod zio;
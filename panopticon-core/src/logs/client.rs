@@ -0,0 +1,207 @@
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::error::FetchError;
+use crate::logs::model::LogLines;
+
+const FILE_POLL_INTERVAL: Duration = Duration::from_millis(250);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+/// How much of an existing log file is shown when tailing starts
+const INITIAL_BYTES: u64 = 64 * 1024;
+
+#[derive(Default)]
+struct TailState {
+    lines: Vec<String>,
+    error: Option<FetchError>,
+}
+
+///
+/// Follows a log file, or an HTTP log stream, in the background, buffering lines until they're taken.
+///
+/// Files are polled for growth, and read from the start again when truncated by log rotation. Streams
+/// are either Server-Sent Events, whose `data` fields are the lines, or any other chunked text response.
+/// Dropped connections are re-established.
+pub struct LogTail {
+    state: Arc<Mutex<TailState>>,
+}
+
+impl LogTail {
+    /// Follows a file path or http(s) url from a thread of its own
+    pub fn start(source: &str) -> Result<LogTail, FetchError> {
+        let state = Arc::new(Mutex::new(TailState::default()));
+        let source = source.to_owned();
+        let is_http = source.starts_with("http://") || source.starts_with("https://");
+        if !is_http && !Path::new(&source).is_file() {
+            return Err(FetchError::from(format!("Log file {} doesn't exist", source)));
+        }
+        {
+            let state = state.clone();
+            thread::spawn(move || if is_http { follow_stream(&source, &state) } else { follow_file(&source, &state) });
+        }
+        Ok(LogTail { state })
+    }
+
+    /// Lines read since the previous call, with the error keeping the source from being read, if any
+    pub fn take_lines(&self) -> LogLines {
+        let mut state = self.state.lock().unwrap();
+        LogLines { lines: std::mem::take(&mut state.lines), error: state.error.clone() }
+    }
+}
+
+/// Adds the bytes read so far to the text, keeping back a character cut at their end until the rest is read
+fn push_utf8(bytes: &mut Vec<u8>, partial: &mut String) {
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(text) => {
+                partial.push_str(text);
+                bytes.clear();
+                return;
+            }
+            Err(e) => {
+                let valid = e.valid_up_to();
+                partial.push_str(&String::from_utf8_lossy(&bytes[..valid]));
+                match e.error_len() {
+                    None => {
+                        bytes.drain(..valid);
+                        return;
+                    }
+                    Some(invalid) => {
+                        partial.push(char::REPLACEMENT_CHARACTER);
+                        bytes.drain(..valid + invalid);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Splits complete lines off the text read so far, leaving the last, unfinished one
+fn split_lines(partial: &mut String) -> Vec<String> {
+    let mut lines = vec![];
+    while let Some(i) = partial.find('\n') {
+        lines.push(partial[..i].trim_end_matches('\r').to_owned());
+        partial.drain(..=i);
+    }
+    lines
+}
+
+fn follow_file(path: &str, state: &Mutex<TailState>) {
+    let mut file: Option<File> = None;
+    let mut position = 0;
+    let mut bytes = vec![];
+    let mut partial = String::new();
+    // the first line read from the middle of the file is likely cut
+    let mut skip_line = false;
+    loop {
+        let result = fs::metadata(path).and_then(|m| {
+            if file.is_none() || m.len() < position {
+                position = if file.is_none() { m.len().saturating_sub(INITIAL_BYTES) } else { 0 };
+                skip_line = position > 0;
+                bytes.clear();
+                partial.clear();
+                file = Some(File::open(path)?);
+            }
+            let mut buf = vec![];
+            if m.len() > position {
+                let f = file.as_mut().unwrap();
+                f.seek(SeekFrom::Start(position))?;
+                position += f.read_to_end(&mut buf)? as u64;
+            }
+            Ok(buf)
+        });
+        let mut state = state.lock().unwrap();
+        match result {
+            Ok(buf) => {
+                bytes.extend(buf);
+                push_utf8(&mut bytes, &mut partial);
+                let mut lines = split_lines(&mut partial);
+                if skip_line && !lines.is_empty() {
+                    lines.remove(0);
+                    skip_line = false;
+                }
+                state.lines.extend(lines);
+                state.error = None;
+            }
+            Err(e) => {
+                file = None;
+                state.error = Some(FetchError::from(e).context(&format!("Couldn't read {}", path)));
+            }
+        }
+        drop(state);
+        thread::sleep(FILE_POLL_INTERVAL);
+    }
+}
+
+fn follow_stream(url: &str, state: &Mutex<TailState>) {
+    loop {
+        let error = match read_stream(url, state) {
+            Ok(()) => FetchError::connection("Log stream ended"),
+            Err(e) => e.context("Log stream failed"),
+        };
+        let message = format!("{}, reconnecting in {}s", error, RECONNECT_DELAY.as_secs());
+        state.lock().unwrap().error = Some(FetchError::new(error.kind, message));
+        thread::sleep(RECONNECT_DELAY);
+    }
+}
+
+#[tokio::main]
+async fn read_stream(url: &str, state: &Mutex<TailState>) -> Result<(), FetchError> {
+    let mut response = reqwest::Client::new().get(url)
+        .header("Accept", "text/event-stream, text/plain")
+        .send().await?;
+    if !response.status().is_success() {
+        return Err(FetchError::of_status(response.status(), format!("Request failed with status {}", response.status())));
+    }
+    let is_sse = response.headers().get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("text/event-stream"));
+    state.lock().unwrap().error = None;
+    let (mut bytes, mut partial) = (vec![], String::new());
+    while let Some(chunk) = response.chunk().await? {
+        bytes.extend(chunk);
+        push_utf8(&mut bytes, &mut partial);
+        let lines = split_lines(&mut partial);
+        let lines = lines.into_iter().filter_map(|l| if is_sse { sse_data(&l).map(|d| d.to_owned()) } else { Some(l) });
+        state.lock().unwrap().lines.extend(lines);
+    }
+    Ok(())
+}
+
+/// Payload of an SSE `data` field, skipping event names, ids and comments
+fn sse_data(line: &str) -> Option<&str> {
+    line.strip_prefix("data:").map(|d| d.strip_prefix(' ').unwrap_or(d))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logs::client::{push_utf8, split_lines, sse_data};
+
+    #[test]
+    fn complete_lines_are_split_off() {
+        let mut partial = "first\r\nsecond\nthi".to_owned();
+        assert_eq!(split_lines(&mut partial), vec!["first".to_owned(), "second".to_owned()]);
+        assert_eq!(partial, "thi");
+        assert_eq!(sse_data("data: INFO started"), Some("INFO started"));
+        assert_eq!(sse_data("event: log"), None);
+    }
+
+    #[test]
+    fn characters_split_across_reads_are_kept_whole() {
+        let (mut bytes, mut partial) = (vec![], String::new());
+        let text = "zażółć\n".as_bytes();
+        bytes.extend(&text[..3]);
+        push_utf8(&mut bytes, &mut partial);
+        assert_eq!((partial.as_str(), bytes.len()), ("za", 1));
+        bytes.extend(&text[3..]);
+        push_utf8(&mut bytes, &mut partial);
+        assert_eq!(partial, "zażółć\n");
+
+        bytes.extend(b"a\xffb");
+        push_utf8(&mut bytes, &mut partial);
+        assert!(partial.ends_with("a\u{FFFD}b") && bytes.is_empty());
+    }
+}
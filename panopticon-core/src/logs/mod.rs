@@ -0,0 +1,6 @@
+//! Lines of log files and streams
+
+/// Log lines and their levels, with the settings of the source
+pub mod model;
+/// Tails files and HTTP streams
+pub mod client;
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::FetchError;
+/// A log file or stream whose new lines are shown
+#[derive(Clone)]
+pub struct LogSettings {
+    /// Path of a log file, or an http(s) url of a log stream
+    pub source: String,
+}
+
+/// Severity of a log line
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogLevel {
+    /// ERROR or FATAL
+    Error,
+    /// WARN or WARNING
+    Warn,
+    /// INFO
+    Info,
+    /// DEBUG
+    Debug,
+    /// TRACE
+    Trace,
+}
+
+impl LogLevel {
+    /// Level of a line, the first level name in it as a separate word, e.g. `12:00:01 [main] WARN c.e.Cache - miss`
+    pub fn detect(line: &str) -> Option<LogLevel> {
+        line.split(|c: char| !c.is_ascii_alphabetic())
+            .find_map(|word| match word {
+                "ERROR" | "FATAL" => Some(LogLevel::Error),
+                "WARN" | "WARNING" => Some(LogLevel::Warn),
+                "INFO" => Some(LogLevel::Info),
+                "DEBUG" => Some(LogLevel::Debug),
+                "TRACE" => Some(LogLevel::Trace),
+                _ => None,
+            })
+    }
+}
+
+/// A line of the log, as shown
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogLine {
+    /// The line as read
+    pub text: String,
+    /// Lines without a level, like stack traces, get the level of the line before them
+    pub level: Option<LogLevel>,
+    /// The text in lower case, to search it ignoring case
+    pub lowercase: String,
+}
+
+/// Lines read since the previous tick
+#[derive(Deserialize, Serialize)]
+pub struct LogLines {
+    /// New lines, in the order they were read
+    pub lines: Vec<String>,
+    /// Why the source can't be read at the moment, e.g. a missing file or a dropped connection
+    pub error: Option<FetchError>,
+}
+
+impl LogLines {
+    /// No lines, as the source couldn't be read
+    pub fn failed(error: FetchError) -> LogLines {
+        LogLines { lines: vec![], error: Some(error) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::logs::model::LogLevel;
+
+    #[test]
+    fn levels_are_detected_as_words() {
+        assert_eq!(LogLevel::detect("2020-05-04 12:00:01.123 [main] WARN  c.e.Cache - miss"), Some(LogLevel::Warn));
+        assert_eq!(LogLevel::detect("[ERROR] [05/04/2020 12:00:01] [akka://app/user/a] boom"), Some(LogLevel::Error));
+        assert_eq!(LogLevel::detect("INFORMATION about DEBUG"), Some(LogLevel::Debug));
+        assert_eq!(LogLevel::detect("\tat com.example.Cache.get(Cache.java:42)"), None);
+    }
+}
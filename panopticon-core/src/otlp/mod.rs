@@ -0,0 +1,6 @@
+//! Metrics pushed by OpenTelemetry exporters
+
+/// Received metrics, with the settings of the receiver
+pub mod model;
+/// Serves the OTLP/HTTP metrics endpoint
+pub mod receiver;
@@ -0,0 +1,192 @@
+use chrono::{DateTime, Local};
+use serde_json::Value;
+use serde::{Deserialize, Serialize};
+
+use crate::error::FetchError;
+
+/// Receiver of the metrics OpenTelemetry exporters push over OTLP/HTTP
+#[derive(Clone)]
+pub struct OtlpSettings {
+    /// Local address to receive OTLP/HTTP requests on, e.g. 0.0.0.0:4318
+    pub address: String,
+    /// Names of the metrics to chart, all the received ones if empty
+    pub metrics: Vec<String>,
+}
+
+/// AGGREGATION_TEMPORALITY_DELTA, the other one being cumulative
+const DELTA: u64 = 1;
+
+/// A value of a data point, as exported
+#[derive(Clone, Debug, PartialEq)]
+pub enum OtlpValue {
+    /// Current value, non-monotonic sums and summary quantiles included
+    Gauge(f64),
+    /// Monotonic sum, given as the total since the start or as the change since the previous export
+    Sum {
+        /// Total or change, depending on `delta`
+        value: f64,
+        /// Whether the value is the change since the previous export
+        delta: bool,
+    },
+    /// Number and sum of the recorded measurements, buckets left out
+    Histogram {
+        /// Number of measurements
+        count: f64,
+        /// Sum of the measurements
+        sum: f64,
+        /// Whether count and sum are the changes since the previous export
+        delta: bool,
+    },
+}
+
+/// A data point of an exported metric
+#[derive(Clone, Debug, PartialEq)]
+pub struct OtlpPoint {
+    /// Name of the metric
+    pub name: String,
+    /// `gauge`, `sum`, `histogram` or `summary`
+    pub kind: &'static str,
+    /// Name of the series within the metric's chart, e.g. `{method=GET,route=/orders}`, the metric name if it has no attributes
+    pub series: String,
+    /// Value of the point
+    pub value: OtlpValue,
+}
+
+///
+/// Data points of an OTLP ExportMetricsServiceRequest in its JSON encoding, e.g.
+///
+/// {"resourceMetrics": [{"scopeMetrics": [{"metrics": [
+///   {"name": "http.server.active_requests", "gauge": {"dataPoints": [{"asInt": "3", "attributes": [...]}]}},
+///   {"name": "http.server.requests", "sum": {"aggregationTemporality": 2, "isMonotonic": true, "dataPoints": [{"asDouble": 120}]}}
+/// ]}]}]}
+///
+/// Non-monotonic sums are charted like gauges, and summaries as a gauge per quantile.
+pub fn parse_export_request(json: &Value) -> Vec<OtlpPoint> {
+    let mut points = vec![];
+    let empty = vec![];
+    for resource in json["resourceMetrics"].as_array().unwrap_or(&empty) {
+        // older exporters send instrumentation library metrics
+        let scopes = resource["scopeMetrics"].as_array().or_else(|| resource["instrumentationLibraryMetrics"].as_array());
+        for metric in scopes.unwrap_or(&empty).iter().flat_map(|s| s["metrics"].as_array().unwrap_or(&empty)) {
+            let name = metric["name"].as_str().unwrap_or_default();
+            let mut point = |kind: &'static str, data_point: &Value, suffix: &str, value: OtlpValue| points.push(OtlpPoint {
+                name: name.to_owned(),
+                kind,
+                series: series_name(name, &data_point["attributes"], suffix),
+                value,
+            });
+            let data_points = |data: &Value| data["dataPoints"].as_array().cloned().unwrap_or_default();
+            if metric["gauge"].is_object() {
+                for p in data_points(&metric["gauge"]) {
+                    point("gauge", &p, "", OtlpValue::Gauge(number_value(&p)));
+                }
+            } else if metric["sum"].is_object() {
+                let sum = &metric["sum"];
+                let delta = sum["aggregationTemporality"].as_u64() == Some(DELTA);
+                for p in data_points(sum) {
+                    let value = number_value(&p);
+                    point("sum", &p, "", if sum["isMonotonic"] == true { OtlpValue::Sum { value, delta } } else { OtlpValue::Gauge(value) });
+                }
+            } else if let Some(histogram) = [&metric["histogram"], &metric["exponentialHistogram"]].iter().find(|h| h.is_object()) {
+                let delta = histogram["aggregationTemporality"].as_u64() == Some(DELTA);
+                for p in data_points(histogram) {
+                    point("histogram", &p, "", OtlpValue::Histogram { count: number(&p["count"]), sum: number(&p["sum"]), delta });
+                }
+            } else if metric["summary"].is_object() {
+                for p in data_points(&metric["summary"]) {
+                    for q in p["quantileValues"].as_array().unwrap_or(&empty) {
+                        point("summary", &p, &format!("p{}", number(&q["quantile"]) * 100.0), OtlpValue::Gauge(number(&q["value"])));
+                    }
+                }
+            }
+        }
+    }
+    points
+}
+
+/// 64 bit integers are encoded as strings
+fn number(v: &Value) -> f64 {
+    v.as_f64().or_else(|| v.as_str().and_then(|s| s.parse().ok())).unwrap_or(0.0)
+}
+
+fn number_value(data_point: &Value) -> f64 {
+    if data_point["asDouble"].is_null() { number(&data_point["asInt"]) } else { number(&data_point["asDouble"]) }
+}
+
+fn series_name(name: &str, attributes: &Value, suffix: &str) -> String {
+    let mut attributes: Vec<String> = attributes.as_array().map_or(vec![], |a| a.iter().map(|a| {
+        let value = a["value"].as_object().and_then(|v| v.values().next()).map_or("".to_owned(), |v| match v {
+            Value::String(s) => s.to_owned(),
+            other => other.to_string(),
+        });
+        format!("{}={}", a["key"].as_str().unwrap_or_default(), value)
+    }).collect());
+    attributes.sort();
+    match (attributes.is_empty(), suffix.is_empty()) {
+        (true, true) => name.to_owned(),
+        (true, false) => suffix.to_owned(),
+        (false, true) => format!("{{{}}}", attributes.join(",")),
+        (false, false) => format!("{{{}}} {}", attributes.join(","), suffix),
+    }
+}
+
+/// Value of a series at the tick, ready to chart
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct OtlpSample {
+    /// Name of the metric, charted on its own
+    pub name: String,
+    /// Name of the series within the metric's chart
+    pub series: String,
+    /// Latest value, or the total since the receiver started for sums and histogram counts
+    pub value: f64,
+    /// Sums are reported as totals since the receiver started, so their rate is charted
+    pub counter: bool,
+}
+
+/// Metrics aggregated from the requests received since the previous tick
+#[derive(Deserialize, Serialize)]
+pub struct OtlpMetrics {
+    /// When the metrics were taken
+    pub timestamp: DateTime<Local>,
+    /// Latest value of every series of the charted metrics
+    pub samples: Vec<OtlpSample>,
+    /// Names and kinds of all the metrics received so far
+    pub metrics: Vec<(String, String)>,
+    /// Why the last request was rejected, if it was
+    pub last_rejection: Option<String>,
+    /// Why no requests can be received, e.g. a port that's taken
+    #[serde(default)]
+    pub error: Option<FetchError>,
+}
+
+impl OtlpMetrics {
+    /// No metrics, as the receiver couldn't be set up
+    pub fn failed(error: FetchError) -> OtlpMetrics {
+        OtlpMetrics { timestamp: Local::now(), samples: vec![], metrics: vec![], last_rejection: None, error: Some(error) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::otlp::model::{OtlpValue, parse_export_request};
+
+    #[test]
+    fn data_points_are_parsed_from_json_requests() {
+        let attributes = json!([{"key": "route", "value": {"stringValue": "/orders"}}, {"key": "code", "value": {"intValue": "200"}}]);
+        let points = parse_export_request(&json!({"resourceMetrics": [{"scopeMetrics": [{"metrics": [
+            {"name": "active", "gauge": {"dataPoints": [{"asInt": "3"}]}},
+            {"name": "requests", "sum": {"aggregationTemporality": 1, "isMonotonic": true, "dataPoints": [{"asDouble": 12.0, "attributes": attributes}]}},
+            {"name": "latency", "histogram": {"aggregationTemporality": 2, "dataPoints": [{"count": "4", "sum": 100.0}]}},
+            {"name": "gc", "summary": {"dataPoints": [{"quantileValues": [{"quantile": 0.99, "value": 8.5}]}]}}
+        ]}]}]}));
+        let values: Vec<(&str, &str, OtlpValue)> = points.iter().map(|p| (p.kind, p.series.as_str(), p.value.clone())).collect();
+        assert_eq!(values, vec![
+            ("gauge", "active", OtlpValue::Gauge(3.0)),
+            ("sum", "{code=200,route=/orders}", OtlpValue::Sum { value: 12.0, delta: true }),
+            ("histogram", "latency", OtlpValue::Histogram { count: 4.0, sum: 100.0, delta: false }),
+            ("summary", "p99", OtlpValue::Gauge(8.5)),
+        ]);
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use chrono::Local;
+use serde_json::Value;
+
+use crate::http_server::{read_request, write_response, Request};
+use crate::otlp::model::{OtlpMetrics, OtlpPoint, OtlpSample, OtlpValue, parse_export_request};
+
+const METRICS_PATH: &str = "/v1/metrics";
+
+// metric name and series name
+type SeriesKey = (String, String);
+
+///
+/// Receives metrics pushed by OpenTelemetry exporters over OTLP/HTTP with JSON encoding, aggregating
+/// them in the background until they're taken.
+///
+/// Protobuf, gRPC and compressed requests aren't supported, exporters have to be configured with
+/// the `http/json` protocol and no compression.
+pub struct OtlpReceiver {
+    aggregate: Arc<Mutex<Aggregate>>,
+}
+
+impl OtlpReceiver {
+    /// Serves the OTLP/HTTP metrics endpoint on the address, from threads of its own
+    pub fn bind(address: &str) -> Result<OtlpReceiver, String> {
+        let listener = TcpListener::bind(address)
+            .map_err(|e| format!("Couldn't listen for OTLP metrics on {}: {}", address, e))?;
+        let aggregate = Arc::new(Mutex::new(Aggregate::default()));
+        {
+            let aggregate = aggregate.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming().filter_map(|s| s.ok()) {
+                    let aggregate = aggregate.clone();
+                    thread::spawn(move || serve(stream, &aggregate));
+                }
+            });
+        }
+        Ok(OtlpReceiver { aggregate })
+    }
+
+    /// Metrics aggregated from the requests received since the previous call
+    pub fn take_metrics(&self) -> OtlpMetrics {
+        self.aggregate.lock().unwrap().take()
+    }
+}
+
+/// Serves the requests of a connection, which exporters keep alive, until it's closed
+fn serve(stream: TcpStream, aggregate: &Mutex<Aggregate>) {
+    let mut writer = match stream.try_clone() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    let mut reader = BufReader::new(stream);
+    while let Ok(Some(request)) = read_request(&mut reader) {
+        let result = handle(&request).map(|points| aggregate.lock().unwrap().add(points));
+        let (status, body) = match result {
+            Ok(()) => ("200 OK", "{}".to_owned()),
+            Err((status, message)) => {
+                aggregate.lock().unwrap().last_rejection = Some(message.to_owned());
+                (status, serde_json::json!({ "message": message }).to_string())
+            }
+        };
+        if write_response(&mut writer, status, "application/json", &body).is_err() {
+            return;
+        }
+    }
+}
+
+fn handle(request: &Request) -> Result<Vec<OtlpPoint>, (&'static str, String)> {
+    if request.method != "POST" || request.path.split('?').next() != Some(METRICS_PATH) {
+        return Err(("404 Not Found", format!("Only metrics are received, on POST {}", METRICS_PATH)));
+    }
+    let header = |name: &str| request.headers.get(name).map_or("", |h| h.as_str());
+    if !header("content-type").starts_with("application/json") {
+        return Err(("415 Unsupported Media Type", "Only JSON encoded metrics are supported, set the exporter protocol to http/json".to_owned()));
+    }
+    if !header("content-encoding").is_empty() && header("content-encoding") != "identity" {
+        return Err(("415 Unsupported Media Type", "Compressed metrics aren't supported, set the exporter compression to none".to_owned()));
+    }
+    let json: Value = serde_json::from_slice(&request.body).map_err(|e| ("400 Bad Request", format!("Invalid JSON: {}", e)))?;
+    Ok(parse_export_request(&json))
+}
+
+#[derive(Default)]
+struct Aggregate {
+    /// Last value of each series and whether it's a total
+    values: BTreeMap<SeriesKey, (f64, bool)>,
+    /// Last cumulative count and sum of each histogram, to get the mean of the values added since
+    histograms: BTreeMap<SeriesKey, (f64, f64)>,
+    metrics: Vec<(String, String)>,
+    last_rejection: Option<String>,
+}
+
+impl Aggregate {
+    fn add(&mut self, points: Vec<OtlpPoint>) {
+        self.last_rejection = None;
+        for p in points {
+            if !self.metrics.iter().any(|(name, _)| name == &p.name) {
+                self.metrics.push((p.name.to_owned(), p.kind.to_owned()));
+            }
+            // a histogram without attributes gets the name of the statistic alone
+            let stat = |s: &str| if p.series == p.name { s.to_owned() } else { format!("{} {}", p.series, s) };
+            let key = (p.name.to_owned(), p.series.to_owned());
+            match p.value {
+                OtlpValue::Gauge(v) => { self.values.insert(key, (v, false)); }
+                OtlpValue::Sum { value, delta: false } => { self.values.insert(key, (value, true)); }
+                OtlpValue::Sum { value, delta: true } => self.values.entry(key).or_insert((0.0, true)).0 += value,
+                OtlpValue::Histogram { count, sum, delta } => {
+                    let (added_count, added_sum) = match (delta, self.histograms.insert(key, (count, sum))) {
+                        (false, Some((c, s))) if count >= c => (count - c, sum - s),
+                        // restarted or first export, the mean since the start is all there is
+                        _ => (count, sum),
+                    };
+                    let count_key = (p.name.to_owned(), stat("count"));
+                    if delta {
+                        self.values.entry(count_key).or_insert((0.0, true)).0 += count;
+                    } else {
+                        self.values.insert(count_key, (count, true));
+                    }
+                    if added_count > 0.0 {
+                        self.values.insert((p.name.to_owned(), stat("mean")), (added_sum / added_count, false));
+                    }
+                }
+            }
+        }
+    }
+
+    fn take(&mut self) -> OtlpMetrics {
+        OtlpMetrics {
+            timestamp: Local::now(),
+            samples: self.values.iter()
+                .map(|((name, series), (value, counter))| OtlpSample { name: name.to_owned(), series: series.to_owned(), value: *value, counter: *counter })
+                .collect(),
+            metrics: self.metrics.clone(),
+            last_rejection: self.last_rejection.clone(),
+            error: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use crate::otlp::model::{OtlpPoint, OtlpValue};
+    use crate::http_server::read_request;
+    use crate::otlp::receiver::{Aggregate, handle};
+
+    #[test]
+    fn requests_are_read_and_points_aggregated() {
+        let body = r#"{"resourceMetrics": [{"scopeMetrics": [{"metrics": [{"name": "active", "gauge": {"dataPoints": [{"asInt": "3"}]}}]}]}]}"#;
+        let raw = format!("POST /v1/metrics HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}GET /", body.len(), body);
+        let mut reader = BufReader::new(raw.as_bytes());
+        let request = read_request(&mut reader).unwrap().unwrap();
+        assert_eq!(handle(&request).unwrap().len(), 1);
+        assert_eq!(handle(&read_request(&mut reader).unwrap().unwrap()).unwrap_err().0, "404 Not Found");
+
+        let point = |series: &str, value: OtlpValue| OtlpPoint { name: "latency".to_owned(), kind: "histogram", series: series.to_owned(), value };
+        let mut aggregate = Aggregate::default();
+        aggregate.add(vec![point("latency", OtlpValue::Histogram { count: 4.0, sum: 100.0, delta: false })]);
+        aggregate.add(vec![point("latency", OtlpValue::Histogram { count: 6.0, sum: 180.0, delta: false })]);
+        aggregate.add(vec![point("{route=/}", OtlpValue::Histogram { count: 2.0, sum: 10.0, delta: true })]);
+        aggregate.add(vec![point("{route=/}", OtlpValue::Histogram { count: 3.0, sum: 30.0, delta: true })]);
+        let values: Vec<(String, f64, bool)> = aggregate.take().samples.into_iter().map(|s| (s.series, s.value, s.counter)).collect();
+        assert_eq!(values, vec![
+            ("count".to_owned(), 6.0, true),
+            ("mean".to_owned(), 40.0, false),
+            ("{route=/} count".to_owned(), 5.0, true),
+            ("{route=/} mean".to_owned(), 10.0, false),
+        ]);
+    }
+}
@@ -0,0 +1,147 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use chrono::Local;
+use postgres::error::SqlState;
+use postgres::SimpleQueryMessage;
+use postgres_native_tls::MakeTlsConnector;
+
+use crate::error::{FetchError, FetchErrorKind, socket_error_kind};
+use crate::postgres::model::{PostgresActivity, PostgresBackend, PostgresDatabaseStats, PostgresSettings};
+
+// whitespace in queries is collapsed, so that every row fits on a line
+const ACTIVITY_QUERY: &str = "SELECT pid, coalesce(usename, ''), coalesce(datname, ''), coalesce(state, ''), \
+coalesce(extract(epoch FROM now() - query_start), 0), coalesce(wait_event_type || ':' || wait_event, ''), \
+regexp_replace(query, '\\s+', ' ', 'g') \
+FROM pg_stat_activity WHERE backend_type = 'client backend' AND pid <> pg_backend_pid()";
+
+const DATABASE_QUERY: &str = "SELECT datname, xact_commit, xact_rollback, blks_read, blks_hit \
+FROM pg_stat_database WHERE datname IS NOT NULL";
+
+///
+/// Queries PostgreSQL statistics views over a connection kept open between polls.
+///
+/// The connection is made on the first poll, and again on the poll after a failure.
+pub struct PostgresClient {
+    settings: PostgresSettings,
+    client: Option<postgres::Client>,
+}
+
+impl PostgresClient {
+    /// A client of the server, connecting on the first poll
+    pub fn new(settings: PostgresSettings) -> PostgresClient {
+        PostgresClient { settings, client: None }
+    }
+
+    /// Connections and database counters, both read in a single round trip
+    pub fn poll(&mut self) -> Result<PostgresActivity, FetchError> {
+        let mut client = match self.client.take() {
+            Some(c) => c,
+            None => connect(&self.settings.connection, Duration::from_millis(self.settings.timeout))?,
+        };
+        let messages = client.simple_query(&format!("{}; {}", ACTIVITY_QUERY, DATABASE_QUERY)).map_err(fetch_error)?;
+        self.client = Some(client);
+
+        // rows of the two statements are told apart by the completion of the first one
+        let mut results: Vec<Vec<Vec<String>>> = vec![vec![]];
+        for message in messages {
+            match message {
+                SimpleQueryMessage::Row(row) => results.last_mut().unwrap()
+                    .push((0..row.len()).map(|i| row.get(i).unwrap_or_default().to_owned()).collect()),
+                SimpleQueryMessage::CommandComplete(_) => results.push(vec![]),
+                _ => {}
+            }
+        }
+        let backends = results[0].iter().filter_map(|r| parse_backend(r)).collect();
+        let databases = results.get(1).map_or(vec![], |rows| rows.iter().filter_map(|r| parse_database(r)).collect());
+        Ok(PostgresActivity { timestamp: Local::now(), backends, databases })
+    }
+}
+
+///
+/// Connects to the server of a libpq connection string or URL, with queries cancelled if they don't finish in time.
+///
+/// TLS is used as libpq's `prefer` and `require` modes do, without checking the certificate of the server.
+pub fn connect(connection: &str, timeout: Duration) -> Result<postgres::Client, FetchError> {
+    let mut config = postgres::Config::from_str(connection)
+        .map_err(|e| format!("Invalid PostgreSQL connection string: {}", e))?;
+    config.connect_timeout(timeout);
+    if config.get_application_name().is_none() {
+        config.application_name("panopticon-tui");
+    }
+    let tls = native_tls::TlsConnector::builder()
+        .danger_accept_invalid_certs(true)
+        .danger_accept_invalid_hostnames(true)
+        .build()
+        .map_err(|e| format!("Couldn't set up TLS: {}", e))?;
+    let mut client = config.connect(MakeTlsConnector::new(tls)).map_err(fetch_error)?;
+    // set for the session, as options of the connection string are left as they are
+    client.batch_execute(&format!("SET statement_timeout = {}", timeout.as_millis())).map_err(fetch_error)?;
+    Ok(client)
+}
+
+/// Failures of the server are told apart by their SQLSTATE, the others by their socket error
+pub fn fetch_error(e: postgres::Error) -> FetchError {
+    let kind = match e.code() {
+        Some(c) if *c == SqlState::INVALID_PASSWORD || *c == SqlState::INVALID_AUTHORIZATION_SPECIFICATION => FetchErrorKind::Auth,
+        Some(c) if *c == SqlState::QUERY_CANCELED => FetchErrorKind::Timeout,
+        Some(_) => FetchErrorKind::Other,
+        None if e.is_closed() => FetchErrorKind::Connection,
+        None => socket_error_kind(&e).unwrap_or(FetchErrorKind::Other),
+    };
+    // the message of the server is only told by the cause
+    let message = match std::error::Error::source(&e) {
+        Some(cause) => format!("PostgreSQL query failed: {}: {}", e, cause),
+        None => format!("PostgreSQL query failed: {}", e),
+    };
+    FetchError::new(kind, message)
+}
+
+fn parse_backend(row: &[String]) -> Option<PostgresBackend> {
+    match row {
+        [pid, user, database, state, secs, wait, query] => Some(PostgresBackend {
+            pid: pid.parse().ok()?,
+            user: user.to_owned(),
+            database: database.to_owned(),
+            state: state.to_owned(),
+            query_secs: secs.parse().ok()?,
+            wait_event: if wait.is_empty() { None } else { Some(wait.to_owned()) },
+            query: query.to_owned(),
+        }),
+        _ => None,
+    }
+}
+
+fn parse_database(row: &[String]) -> Option<PostgresDatabaseStats> {
+    match row {
+        [name, commits, rollbacks, read, hit] => Some(PostgresDatabaseStats {
+            name: name.to_owned(),
+            commits: commits.parse().ok()?,
+            rollbacks: rollbacks.parse().ok()?,
+            blocks_read: read.parse().ok()?,
+            blocks_hit: hit.parse().ok()?,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::postgres::client::parse_backend;
+
+    #[test]
+    fn activity_rows_are_parsed() {
+        let row = |columns: &[&str]| columns.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        let rows = [
+            row(&["4242", "app", "orders", "active", "12.5", "Lock:transactionid", "UPDATE orders SET status = $1 WHERE id = $2"]),
+            row(&["4243", "app", "orders", "idle", "0.1", "", "COMMIT"]),
+        ];
+        let backends: Vec<_> = rows.iter().filter_map(|r| parse_backend(r)).collect();
+        assert_eq!(backends.len(), 2);
+        assert_eq!(backends[0].pid, 4242);
+        assert_eq!(backends[0].wait_event.as_deref(), Some("Lock:transactionid"));
+        assert_eq!(backends[0].query_secs, 12.5);
+        assert_eq!(backends[1].wait_event, None);
+        assert!(!backends[1].is_active());
+    }
+}
@@ -0,0 +1,6 @@
+//! PostgreSQL servers, through pg_stat_activity
+
+/// Connections and queries, with the settings of the source
+pub mod model;
+/// Queries the server over a connection kept open
+pub mod client;
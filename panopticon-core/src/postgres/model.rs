@@ -0,0 +1,85 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// PostgreSQL server polled over a connection of its own
+#[derive(Clone)]
+pub struct PostgresSettings {
+    /// libpq connection string, e.g. postgresql://monitor@localhost:5432/app
+    pub connection: String,
+    /// Time in ms each query has to finish
+    pub timeout: u64,
+}
+
+/// A client connection, from pg_stat_activity
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PostgresBackend {
+    /// Process id of the backend
+    pub pid: u32,
+    /// User the client connected as
+    pub user: String,
+    /// Database the client connected to
+    pub database: String,
+    /// e.g. `active`, `idle`, `idle in transaction`
+    pub state: String,
+    /// Time since the current (or last, when idle) query started
+    pub query_secs: f64,
+    /// What the backend is waiting for, e.g. `Lock:transactionid`
+    pub wait_event: Option<String>,
+    /// Text of the current query, or of the last one when idle
+    pub query: String,
+}
+
+impl PostgresBackend {
+    /// Whether the backend is running a query
+    pub fn is_active(&self) -> bool {
+        self.state == "active"
+    }
+}
+
+/// Counters of a database since the statistics were last reset, from pg_stat_database
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct PostgresDatabaseStats {
+    /// Name of the database
+    pub name: String,
+    /// Committed transactions
+    pub commits: u64,
+    /// Rolled back transactions
+    pub rollbacks: u64,
+    /// Blocks read from disk
+    pub blocks_read: u64,
+    /// Blocks found in the buffer cache
+    pub blocks_hit: u64,
+}
+
+/// Connections and database counters of the server
+#[derive(Deserialize, Serialize)]
+pub struct PostgresActivity {
+    /// When the server was queried
+    pub timestamp: DateTime<Local>,
+    /// All the client connections
+    pub backends: Vec<PostgresBackend>,
+    /// Counters of every database
+    pub databases: Vec<PostgresDatabaseStats>,
+}
+
+impl PostgresActivity {
+    /// Number of connections in each state, by state name
+    pub fn connections_by_state(&self) -> Vec<(String, usize)> {
+        let mut states: Vec<(String, usize)> = vec![];
+        for b in &self.backends {
+            match states.iter_mut().find(|(s, _)| s == &b.state) {
+                Some((_, n)) => *n += 1,
+                None => states.push((b.state.to_owned(), 1)),
+            }
+        }
+        states.sort();
+        states
+    }
+
+    /// The active backend whose query started the longest time ago
+    pub fn longest_running(&self) -> Option<&PostgresBackend> {
+        self.backends.iter()
+            .filter(|b| b.is_active())
+            .max_by(|a, b| a.query_secs.partial_cmp(&b.query_secs).unwrap_or(std::cmp::Ordering::Equal))
+    }
+}
@@ -0,0 +1,230 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::Local;
+use serde_json::Value;
+
+use crate::error::FetchError;
+use crate::prometheus::model::{MetricSample, MetricSelector, PrometheusMetrics};
+
+/// Samples picked by the selectors out of a scrape of the metrics endpoint
+pub fn get_metrics(url: &str, timeout: u64, selectors: &[MetricSelector]) -> Result<PrometheusMetrics, FetchError> {
+    get_metrics_async(url, timeout, selectors)
+}
+
+#[tokio::main]
+async fn get_metrics_async(url: &str, timeout: u64, selectors: &[MetricSelector]) -> Result<PrometheusMetrics, FetchError> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(timeout))
+        .build()?;
+    let response = client.get(url).send().await?;
+    if !response.status().is_success() {
+        return Err(FetchError::of_status(response.status(), format!("Request to scrape {} failed with status {}", url, response.status())));
+    }
+    let body = response.text().await?;
+    Ok(parse_metrics(&body, selectors))
+}
+
+/// Series returned by an instant query to the HTTP API of a Prometheus server, named by their labels
+pub fn query(server: &str, expression: &str, timeout: u64) -> Result<Vec<(String, f64)>, FetchError> {
+    query_async(server, expression, timeout)
+}
+
+#[tokio::main]
+async fn query_async(server: &str, expression: &str, timeout: u64) -> Result<Vec<(String, f64)>, FetchError> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(timeout))
+        .build()?;
+    let url = format!("{}/api/v1/query", server.trim_end_matches('/'));
+    let response = client.get(&url).query(&[("query", expression)]).send().await?;
+    // errors come with a JSON body too, explaining what's wrong with the query
+    let body: Value = response.json().await?;
+    Ok(parse_query_result(&body)?)
+}
+
+///
+/// Parses an instant query response, e.g.
+///
+/// {"status": "success", "data": {"resultType": "vector", "result": [{"metric": {"job": "api"}, "value": [1591000000.5, "0.25"]}]}}
+///
+/// Scalar results, and aggregations without grouping labels, give a single series named `result`. The metric name is left out of series names, as most queries
+/// return series of a single metric. Series without a finite value are left out.
+fn parse_query_result(json: &Value) -> Result<Vec<(String, f64)>, String> {
+    if json["status"] != "success" {
+        return Err(json["error"].as_str().unwrap_or("Query failed").to_owned());
+    }
+    // NaN and infinite results, e.g. of a division by zero, can't be charted
+    let value = |v: &Value| v[1].as_str().and_then(|x| x.parse::<f64>().ok()).filter(|x| x.is_finite());
+    let data = &json["data"];
+    match data["resultType"].as_str() {
+        Some("vector") => Ok(data["result"].as_array().map_or(vec![], |result| {
+            result.iter()
+                .filter_map(|r| {
+                    let mut labels: Vec<String> = r["metric"].as_object().map_or(vec![], |m| {
+                        m.iter()
+                            .filter(|(k, _)| k.as_str() != "__name__")
+                            .map(|(k, v)| format!("{}=\"{}\"", k, v.as_str().unwrap_or("")))
+                            .collect()
+                    });
+                    labels.sort();
+                    let name = if labels.is_empty() { "result".to_owned() } else { format!("{{{}}}", labels.join(",")) };
+                    value(&r["value"]).map(|v| (name, v))
+                })
+                .collect()
+        })),
+        Some("scalar") => Ok(value(&data["result"]).map_or(vec![], |v| vec![("result".to_owned(), v)])),
+        other => Err(format!("Unsupported query result type {}", other.unwrap_or("-"))),
+    }
+}
+
+///
+/// Picks samples matching the selectors out of the Prometheus text format, e.g.
+///
+/// # TYPE http_requests_total counter
+/// http_requests_total{method="GET",status="200"} 1027
+///
+/// A sample matching several selectors is picked by each of them.
+fn parse_metrics(text: &str, selectors: &[MetricSelector]) -> PrometheusMetrics {
+    let mut metrics: Vec<(String, String)> = vec![];
+    let mut samples = vec![];
+    for line in text.lines() {
+        if let Some(declaration) = line.strip_prefix("# TYPE ") {
+            if let Some((name, kind)) = declaration.trim().split_once(' ') {
+                metrics.push((name.to_owned(), kind.trim().to_owned()));
+            }
+            continue;
+        }
+        let (name, labels, value) = match parse_sample(line) {
+            Some(s) => s,
+            None => continue,
+        };
+        if !metrics.iter().any(|(n, _)| is_sample_of(&name, n)) {
+            metrics.push((name.to_owned(), "untyped".to_owned()));
+        }
+        for (i, selector) in selectors.iter().enumerate() {
+            if selector.matches(&name, &labels) {
+                samples.push(MetricSample { selector: i, series: selector.series_name(&labels), value, counter: is_counter(&name, &metrics) });
+            }
+        }
+    }
+    PrometheusMetrics { timestamp: Local::now(), samples, metrics, errors: vec![] }
+}
+
+/// Whether a sample belongs to the metric, e.g. `rpc_seconds_sum` to the `rpc_seconds` summary
+fn is_sample_of(sample: &str, metric: &str) -> bool {
+    sample.strip_prefix(metric)
+        .is_some_and(|suffix| ["", "_total", "_sum", "_count", "_bucket", "_created"].contains(&suffix))
+}
+
+fn is_counter(sample: &str, metrics: &[(String, String)]) -> bool {
+    match metrics.iter().find(|(n, _)| is_sample_of(sample, n)) {
+        Some((_, kind)) if kind == "counter" => true,
+        Some((_, kind)) if kind == "histogram" || kind == "summary" =>
+            ["_sum", "_count", "_bucket"].iter().any(|s| sample.ends_with(s)),
+        _ => sample.ends_with("_total"),
+    }
+}
+
+/// Parses a sample line of the Prometheus text format into the metric name, labels and value
+pub fn parse_sample(line: &str) -> Option<(String, HashMap<String, String>, f64)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let name_end = line.find(|c: char| c == '{' || c.is_whitespace())?;
+    let name = line[..name_end].to_owned();
+    let mut labels = HashMap::new();
+    let mut rest = &line[name_end..];
+    if let Some(mut l) = rest.strip_prefix('{') {
+        loop {
+            l = l.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+            if let Some(after) = l.strip_prefix('}') {
+                rest = after;
+                break;
+            }
+            let (key, after_key) = l.split_once('=')?;
+            let mut chars = after_key.strip_prefix('"')?.char_indices();
+            let mut value = String::new();
+            let end = loop {
+                match chars.next()? {
+                    (i, '"') => break i,
+                    (_, '\\') => match chars.next()?.1 {
+                        'n' => value.push('\n'),
+                        c => value.push(c),
+                    },
+                    (_, c) => value.push(c),
+                }
+            };
+            labels.insert(key.trim().to_owned(), value);
+            l = &after_key[end + 2..];
+        }
+    }
+    let value = rest.split_whitespace().next()?.parse().ok()?;
+    Some((name, labels, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::prometheus::client::{parse_metrics, parse_query_result, parse_sample};
+use crate::prometheus::model::{MetricSample, MetricSelector};
+
+    #[test]
+    fn samples_are_picked_by_selectors() {
+        let text = r#"
+# HELP http_requests_total Requests served
+# TYPE http_requests_total counter
+http_requests_total{method="GET",status="200"} 1027
+http_requests_total{method="GET",status="500"} 3
+http_requests_total{method="POST",status="200"} 12
+# TYPE queue_size gauge
+queue_size 7
+# TYPE rpc_seconds summary
+rpc_seconds{quantile="0.99"} 0.2
+rpc_seconds_count 10
+"#;
+        let selectors = vec![
+            MetricSelector::parse("http_requests_total{method=\"GET\"}").unwrap(),
+            MetricSelector::parse("queue_size").unwrap(),
+            MetricSelector::parse("rpc_seconds_count").unwrap(),
+        ];
+        let metrics = parse_metrics(text, &selectors);
+        let sample = |selector: usize, series: &str, value: f64, counter: bool| MetricSample { selector, series: series.to_owned(), value, counter };
+        assert_eq!(metrics.samples, vec![
+            sample(0, "{status=\"200\"}", 1027.0, true),
+            sample(0, "{status=\"500\"}", 3.0, true),
+            sample(1, "queue_size", 7.0, false),
+            sample(2, "rpc_seconds_count", 10.0, true),
+        ]);
+        let names: Vec<&str> = metrics.metrics.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["http_requests_total", "queue_size", "rpc_seconds"]);
+    }
+
+    #[test]
+    fn label_values_are_unescaped() {
+        let (name, labels, value) = parse_sample(r#"up{job="a \"b\"",path="c\\d"} 1"#).unwrap();
+        assert_eq!((name.as_str(), value), ("up", 1.0));
+        assert_eq!(labels.get("job").map(|s| s.as_str()), Some("a \"b\""));
+        assert_eq!(labels.get("path").map(|s| s.as_str()), Some("c\\d"));
+        assert!(parse_sample("# HELP up").is_none());
+    }
+
+    #[test]
+    fn query_results_are_parsed() {
+        let json = json!({"status": "success", "data": {"resultType": "vector", "result": [
+            {"metric": {"__name__": "up", "job": "api", "instance": "a:80"}, "value": [1591000000.5, "1"]},
+            {"metric": {}, "value": [1591000000.5, "0.5"]},
+            {"metric": {"job": "idle"}, "value": [1591000000.5, "NaN"]},
+            {"metric": {"job": "empty"}, "value": [1591000000.5, "+Inf"]}
+        ]}});
+        let result = parse_query_result(&json).unwrap();
+        assert_eq!(result, vec![("{instance=\"a:80\",job=\"api\"}".to_owned(), 1.0), ("result".to_owned(), 0.5)]);
+
+        let scalar = json!({"status": "success", "data": {"resultType": "scalar", "result": [1591000000.5, "0.25"]}});
+        assert_eq!(parse_query_result(&scalar), Ok(vec![("result".to_owned(), 0.25)]));
+
+        let error = json!({"status": "error", "errorType": "bad_data", "error": "parse error at char 5"});
+        assert_eq!(parse_query_result(&error), Err("parse error at char 5".to_owned()));
+    }
+}
@@ -0,0 +1,6 @@
+//! Prometheus metrics endpoints and servers
+
+/// Scraped samples and selectors, with the settings of the sources
+pub mod model;
+/// Scrapes endpoints and runs PromQL queries
+pub mod client;
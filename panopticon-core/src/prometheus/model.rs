@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Metrics endpoint scraped on every poll
+#[derive(Clone)]
+pub struct PrometheusSettings {
+    /// Address of the scraped endpoint, e.g. http://localhost:9090/metrics
+    pub address: String,
+    /// Picks the series to chart out of each scrape
+    pub selectors: Vec<MetricSelector>,
+    /// Time in ms each scrape has to finish
+    pub timeout: u64,
+}
+
+/// Prometheus server whose queries are run on every poll
+#[derive(Clone)]
+pub struct PromQLSettings {
+    /// Address of the Prometheus server, e.g. http://prometheus:9090
+    pub address: String,
+    /// PromQL expressions, each one charted with a series per result
+    pub queries: Vec<String>,
+    /// Time in ms each query has to finish
+    pub timeout: u64,
+}
+
+/// Label condition of a selector, `name="value"` or `name!="value"`
+#[derive(Clone, Debug, PartialEq)]
+pub struct LabelMatcher {
+    /// Name of the label
+    pub name: String,
+    /// Value the label is compared with
+    pub value: String,
+    /// Whether the label must differ from the value
+    pub negated: bool,
+}
+
+///
+/// Picks series out of a scrape by metric name and labels, e.g. `http_requests_total{method="GET",status!="200"}`.
+///
+/// A subset of PromQL's instant vector selectors: regex matchers aren't supported.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MetricSelector {
+    /// Name of the metric
+    pub name: String,
+    /// Conditions on the labels, all of which must hold
+    pub labels: Vec<LabelMatcher>,
+}
+
+impl MetricSelector {
+    /// Parses a selector like `name{label="value",label!="value"}`, the labels being optional
+    pub fn parse(s: &str) -> Result<MetricSelector, String> {
+        let invalid = || format!("Expected a metric selector like name{{label=\"value\"}}, got {}", s);
+        let s = s.trim();
+        let (name, rest) = match s.find('{') {
+            Some(i) => (&s[..i], Some(&s[i + 1..])),
+            None => (s, None),
+        };
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == ':') {
+            return Err(invalid());
+        }
+        let mut labels = vec![];
+        if let Some(mut l) = rest {
+            loop {
+                l = l.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+                if l == "}" {
+                    break;
+                }
+                let op = l.find('=').ok_or_else(invalid)?;
+                let (label, negated) = match l[..op].strip_suffix('!') {
+                    Some(label) => (label, true),
+                    None => (&l[..op], false),
+                };
+                let value_start = l[op + 1..].trim_start().strip_prefix('"').ok_or_else(invalid)?;
+                let end = value_start.find('"').ok_or_else(invalid)?;
+                labels.push(LabelMatcher { name: label.trim().to_owned(), value: value_start[..end].to_owned(), negated });
+                l = &value_start[end + 1..];
+            }
+        }
+        Ok(MetricSelector { name: name.to_owned(), labels })
+    }
+
+    /// Whether a series of the metric with these labels is picked
+    pub fn matches(&self, name: &str, labels: &HashMap<String, String>) -> bool {
+        name == self.name && self.labels.iter().all(|m| {
+            // a missing label matches an empty value, as in PromQL
+            let value = labels.get(&m.name).map_or("", |v| v.as_str());
+            (value == m.value) != m.negated
+        })
+    }
+
+    /// Name of a matching series, leaving out the name and labels the selector fixes
+    pub fn series_name(&self, labels: &HashMap<String, String>) -> String {
+        let mut varying: Vec<String> = labels.iter()
+            .filter(|(k, _)| !self.labels.iter().any(|m| &m.name == *k && !m.negated))
+            .map(|(k, v)| format!("{}=\"{}\"", k, v))
+            .collect();
+        varying.sort();
+        if varying.is_empty() {
+            self.name.to_owned()
+        } else {
+            format!("{{{}}}", varying.join(","))
+        }
+    }
+}
+
+impl fmt::Display for MetricSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.labels.is_empty() {
+            let labels: Vec<String> = self.labels.iter()
+                .map(|m| format!("{}{}=\"{}\"", m.name, if m.negated { "!" } else { "" }, m.value))
+                .collect();
+            write!(f, "{{{}}}", labels.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Value of a series picked by a selector
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct MetricSample {
+    /// Index of the selector that picked the sample
+    pub selector: usize,
+    /// Name of the series within the selector's chart
+    pub series: String,
+    /// Value in the scrape
+    pub value: f64,
+    /// Counters (and histogram/summary totals) only ever grow, so their rate is charted
+    pub counter: bool,
+}
+
+/// Series picked out of a scrape
+#[derive(Deserialize, Serialize)]
+pub struct PrometheusMetrics {
+    /// When the endpoint was scraped
+    pub timestamp: DateTime<Local>,
+    /// Values of the series picked by the selectors
+    pub samples: Vec<MetricSample>,
+    /// Names of all the metrics in the scrape, with their types
+    pub metrics: Vec<(String, String)>,
+    /// Queries that failed, with the reason
+    #[serde(default)]
+    pub errors: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::prometheus::model::MetricSelector;
+
+    #[test]
+    fn selectors_are_parsed_and_matched() {
+        let selector = MetricSelector::parse("http_requests_total{method=\"GET\", status!=\"200\"}").unwrap();
+        assert_eq!(selector.to_string(), "http_requests_total{method=\"GET\",status!=\"200\"}");
+        let labels = |status: &str| -> HashMap<String, String> {
+            vec![("method".to_owned(), "GET".to_owned()), ("status".to_owned(), status.to_owned())].into_iter().collect()
+        };
+        assert!(selector.matches("http_requests_total", &labels("500")));
+        assert!(!selector.matches("http_requests_total", &labels("200")));
+        assert!(!selector.matches("http_requests", &labels("500")));
+        assert_eq!(selector.series_name(&labels("500")), "{status=\"500\"}");
+
+        assert_eq!(MetricSelector::parse("up").unwrap().labels, vec![]);
+        assert!(MetricSelector::parse("up{job}").is_err());
+        assert!(MetricSelector::parse("{job=\"a\"}").is_err());
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+use chrono::Local;
+
+use crate::error::FetchError;
+use crate::prometheus::model::MetricSample;
+use crate::proxy::model::{ProxyKind, ProxyServer, ProxySettings, ProxyStatus};
+
+// HAProxy's `type` column
+const FRONTEND: &str = "0";
+const BACKEND: &str = "1";
+const SERVER: &str = "2";
+
+/// Traffic and backend health from HAProxy's CSV stats or nginx's stub_status, whichever the address serves
+pub fn get_status(settings: &ProxySettings) -> Result<ProxyStatus, FetchError> {
+    let body = get_status_async(settings)?;
+    if body.starts_with("# pxname") {
+        Ok(parse_haproxy_csv(&body))
+    } else if body.starts_with("Active connections") {
+        Ok(parse_nginx_status(&body)?)
+    } else {
+        Err(FetchError::from(format!("{} serves neither HAProxy CSV stats nor nginx stub_status", settings.address)))
+    }
+}
+
+#[tokio::main]
+async fn get_status_async(settings: &ProxySettings) -> Result<String, FetchError> {
+    let response = reqwest::Client::builder()
+        .timeout(Duration::from_millis(settings.timeout))
+        .build()?
+        .get(&settings.address)
+        .send().await?;
+    if !response.status().is_success() {
+        return Err(FetchError::of_status(response.status(), format!("Request to get proxy status failed with status {}", response.status())));
+    }
+    Ok(response.text().await?)
+}
+
+///
+/// Parses HAProxy's CSV stats, with a row per frontend, backend and server, e.g.
+///
+/// # pxname,svname,qcur,qmax,scur,smax,slim,stot,...,status,...,type,...,check_status,...,req_tot,...
+/// http-in,FRONTEND,,,3,10,2000,1520,...,OPEN,...,0,...,,...,1880,...
+///
+/// Columns are looked up by name, as new HAProxy versions add some.
+fn parse_haproxy_csv(csv: &str) -> ProxyStatus {
+    let mut lines = csv.lines();
+    let header: Vec<&str> = lines.next().unwrap_or_default().trim_start_matches("# ").split(',').collect();
+    let rows: Vec<HashMap<&str, &str>> = lines
+        .filter(|l| !l.is_empty())
+        .map(|l| header.iter().cloned().zip(l.split(',')).collect())
+        .collect();
+    let number = |row: &HashMap<&str, &str>, column: &str| row.get(column).and_then(|v| v.parse::<f64>().ok());
+
+    let mut samples = vec![];
+    let mut servers = vec![];
+    let mut servers_up: BTreeMap<String, u64> = BTreeMap::new();
+    for row in &rows {
+        let proxy = row.get("pxname").cloned().unwrap_or_default();
+        match row.get("type").cloned().unwrap_or_default() {
+            FRONTEND => {
+                // TCP frontends count sessions only
+                if let Some(requests) = number(row, "req_tot").or_else(|| number(row, "stot")) {
+                    samples.push(MetricSample { selector: 0, series: proxy.to_owned(), value: requests, counter: true });
+                }
+                if let Some(sessions) = number(row, "scur") {
+                    samples.push(MetricSample { selector: 1, series: format!("{} frontend", proxy), value: sessions, counter: false });
+                }
+            }
+            BACKEND => {
+                if let Some(sessions) = number(row, "scur") {
+                    samples.push(MetricSample { selector: 1, series: format!("{} backend", proxy), value: sessions, counter: false });
+                }
+                servers_up.entry(proxy.to_owned()).or_insert(0);
+            }
+            SERVER => {
+                let server = ProxyServer {
+                    backend: proxy.to_owned(),
+                    name: row.get("svname").cloned().unwrap_or_default().to_owned(),
+                    status: row.get("status").cloned().unwrap_or_default().to_owned(),
+                    check: row.get("check_status").cloned().unwrap_or_default().trim_start_matches('*').to_owned(),
+                    sessions: number(row, "scur").unwrap_or(0.0) as u64,
+                };
+                *servers_up.entry(proxy.to_owned()).or_insert(0) += server.is_up() as u64;
+                servers.push(server);
+            }
+            _ => {}
+        }
+    }
+    for (backend, up) in servers_up {
+        samples.push(MetricSample { selector: 2, series: backend, value: up as f64, counter: false });
+    }
+    ProxyStatus { timestamp: Local::now(), kind: ProxyKind::HAProxy, samples, servers }
+}
+
+///
+/// Parses nginx's stub_status, e.g.
+///
+/// Active connections: 291
+/// server accepts handled requests
+///  16630948 16630948 31070465
+/// Reading: 6 Writing: 179 Waiting: 106
+fn parse_nginx_status(status: &str) -> Result<ProxyStatus, String> {
+    let numbers: Vec<f64> = status.split_whitespace().filter_map(|w| w.parse().ok()).collect();
+    let (active, accepted, handled, requests, reading, writing, waiting) = match numbers.as_slice() {
+        [active, accepted, handled, requests, reading, writing, waiting] => (*active, *accepted, *handled, *requests, *reading, *writing, *waiting),
+        _ => return Err("Unexpected nginx stub_status format".to_owned()),
+    };
+    let connections = [("active", active), ("reading", reading), ("writing", writing), ("waiting", waiting)];
+    let mut samples: Vec<MetricSample> = connections.iter()
+        .map(|(series, value)| MetricSample { selector: 0, series: series.to_string(), value: *value, counter: false })
+        .collect();
+    samples.push(MetricSample { selector: 1, series: "requests".to_owned(), value: requests, counter: true });
+    // connections nginx accepted but had to drop, e.g. at worker_connections
+    samples.push(MetricSample { selector: 1, series: "dropped connections".to_owned(), value: accepted - handled, counter: true });
+    Ok(ProxyStatus { timestamp: Local::now(), kind: ProxyKind::Nginx, samples, servers: vec![] })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::proxy::client::{parse_haproxy_csv, parse_nginx_status};
+
+    #[test]
+    fn haproxy_and_nginx_statuses_are_parsed() {
+        let csv = "# pxname,svname,scur,stot,status,type,check_status,req_tot,\n\
+            http-in,FRONTEND,3,1520,OPEN,0,,1880,\n\
+            app,web1,1,700,UP,2,L7OK,,\n\
+            app,web2,0,690,DOWN,2,*L4CON,,\n\
+            app,BACKEND,1,1390,UP,1,,,\n";
+        let haproxy = parse_haproxy_csv(csv);
+        let sample = |series: &str| haproxy.samples.iter().find(|s| s.series == series).map(|s| (s.selector, s.value, s.counter));
+        assert_eq!(sample("http-in"), Some((0, 1880.0, true)));
+        assert_eq!(sample("app backend"), Some((1, 1.0, false)));
+        assert_eq!(sample("app"), Some((2, 1.0, false)));
+        assert_eq!(haproxy.servers.len(), 2);
+        assert_eq!((haproxy.servers[1].status.as_str(), haproxy.servers[1].check.as_str()), ("DOWN", "L4CON"));
+
+        let nginx = parse_nginx_status("Active connections: 291 \nserver accepts handled requests\n 16630948 16630940 31070465 \nReading: 6 Writing: 179 Waiting: 106 \n").unwrap();
+        assert_eq!(nginx.samples.len(), 6);
+        assert_eq!((nginx.samples[0].value, nginx.samples[4].value, nginx.samples[5].value), (291.0, 31070465.0, 8.0));
+        assert!(parse_nginx_status("Active connections: 1").is_err());
+    }
+}
@@ -0,0 +1,6 @@
+//! HAProxy and nginx, through their status pages
+
+/// Traffic and backend health, with the settings of the source
+pub mod model;
+/// Reads HAProxy's CSV stats or nginx's stub_status
+pub mod client;
@@ -0,0 +1,76 @@
+use chrono::{DateTime, Local};
+
+use crate::prometheus::model::MetricSample;
+use serde::{Deserialize, Serialize};
+
+/// HAProxy or nginx status page polled on every tick
+#[derive(Clone)]
+pub struct ProxySettings {
+    /// Url of HAProxy's stats in CSV, e.g. http://localhost:8404/stats;csv, or of nginx's stub_status
+    pub address: String,
+    /// Time in ms each request has to finish
+    pub timeout: u64,
+}
+
+/// Told apart by the status page format
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum ProxyKind {
+    /// HAProxy, serving its stats as CSV
+    HAProxy,
+    /// nginx, serving stub_status
+    Nginx,
+}
+
+impl ProxyKind {
+    /// Name of the proxy, as shown
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProxyKind::HAProxy => "HAProxy",
+            ProxyKind::Nginx => "nginx",
+        }
+    }
+
+    /// Names of the charts, in the order samples refer to them
+    pub fn charts(&self) -> Vec<String> {
+        let charts: &[&str] = match self {
+            ProxyKind::HAProxy => &["Requests", "Current sessions", "Servers up"],
+            ProxyKind::Nginx => &["Connections", "Requests"],
+        };
+        charts.iter().map(|c| c.to_string()).collect()
+    }
+}
+
+/// A backend server, as health checked by HAProxy
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ProxyServer {
+    /// Backend the server belongs to
+    pub backend: String,
+    /// Name of the server
+    pub name: String,
+    /// e.g. `UP`, `DOWN`, `MAINT`, `UP 1/3` while going up
+    pub status: String,
+    /// Result of the last health check, e.g. `L7OK`
+    pub check: String,
+    /// Current sessions of the server
+    pub sessions: u64,
+}
+
+impl ProxyServer {
+    /// Whether HAProxy considers the server up
+    pub fn is_up(&self) -> bool {
+        self.status.starts_with("UP")
+    }
+}
+
+/// Traffic and backend health read from the status page
+#[derive(Deserialize, Serialize)]
+pub struct ProxyStatus {
+    /// When the status page was read
+    pub timestamp: DateTime<Local>,
+    /// Which proxy served the page
+    pub kind: ProxyKind,
+    /// Values of the series, by the index of their chart in `ProxyKind::charts`
+    pub samples: Vec<MetricSample>,
+    /// Only reported by HAProxy
+    pub servers: Vec<ProxyServer>,
+}
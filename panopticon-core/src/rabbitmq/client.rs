@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use chrono::Local;
+use reqwest::{StatusCode, Url};
+use serde_json::Value;
+
+use crate::error::FetchError;
+use crate::rabbitmq::model::{QueueStats, RabbitMqSettings, RabbitMqStats, RabbitQueue};
+
+/// Depth, rates and consumers of the queues, read from the management API
+pub fn get_stats(settings: &RabbitMqSettings) -> Result<RabbitMqStats, FetchError> {
+    get_stats_async(settings)
+}
+
+#[tokio::main]
+async fn get_stats_async(settings: &RabbitMqSettings) -> Result<RabbitMqStats, FetchError> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(settings.timeout))
+        .build()?;
+    let mut queues = vec![];
+    let mut errors = vec![];
+    for q in &settings.queues {
+        let response = client.get(queue_url(&settings.address, q)?)
+            .basic_auth(&settings.username, Some(&settings.password))
+            .send().await?;
+        match response.status() {
+            StatusCode::OK => {
+                let body: Value = response.json().await?;
+                queues.push(parse_queue(q, &body));
+            }
+            // the broker itself is fine
+            StatusCode::NOT_FOUND => errors.push(format!("{} not found", q)),
+            status => return Err(FetchError::of_status(status, format!("Request to get queue {} failed with status {}", q, status))),
+        }
+    }
+    Ok(RabbitMqStats { timestamp: Local::now(), queues, errors })
+}
+
+/// `/api/queues/<vhost>/<name>`, where the default vhost `/` has to be escaped
+fn queue_url(address: &str, queue: &RabbitQueue) -> Result<Url, String> {
+    let mut url = Url::parse(address).map_err(|e| format!("Invalid RabbitMQ address {}: {}", address, e))?;
+    url.path_segments_mut()
+        .map_err(|_| format!("Invalid RabbitMQ address {}", address))?
+        .pop_if_empty()
+        .extend(&["api", "queues", &queue.vhost, &queue.name]);
+    Ok(url)
+}
+
+///
+/// Parses a queue, e.g.
+///
+/// {"name": "orders", "state": "running", "messages_ready": 12, "messages_unacknowledged": 3, "consumers": 2,
+///  "message_stats": {"publish_details": {"rate": 40.2}, "deliver_get_details": {"rate": 38.0}}}
+///
+/// Message stats are left out until messages went through the queue.
+fn parse_queue(queue: &RabbitQueue, json: &Value) -> QueueStats {
+    let rate = |stat: &str| json["message_stats"][stat]["rate"].as_f64().unwrap_or(0.0);
+    QueueStats {
+        queue: queue.clone(),
+        state: json["state"].as_str().unwrap_or("-").to_owned(),
+        ready: json["messages_ready"].as_u64().unwrap_or(0),
+        unacknowledged: json["messages_unacknowledged"].as_u64().unwrap_or(0),
+        consumers: json["consumers"].as_u64().unwrap_or(0),
+        publish_rate: rate("publish_details"),
+        deliver_rate: rate("deliver_get_details"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::rabbitmq::client::{parse_queue, queue_url};
+use crate::rabbitmq::model::RabbitQueue;
+
+    #[test]
+    fn queue_stats_are_parsed() {
+        let orders = RabbitQueue::parse("orders").unwrap();
+        assert_eq!(queue_url("http://localhost:15672/", &orders).unwrap().as_str(), "http://localhost:15672/api/queues/%2F/orders");
+        assert_eq!(queue_url("http://rabbit/mgmt", &RabbitQueue::parse("billing/invoices").unwrap()).unwrap().as_str(), "http://rabbit/mgmt/api/queues/billing/invoices");
+
+        let stats = parse_queue(&orders, &json!({
+            "name": "orders", "state": "running", "messages_ready": 12, "messages_unacknowledged": 3, "consumers": 2,
+            "message_stats": {"publish_details": {"rate": 40.2}, "deliver_get_details": {"rate": 38.0}}
+        }));
+        assert_eq!((stats.ready, stats.unacknowledged, stats.consumers), (12, 3, 2));
+        assert_eq!((stats.publish_rate, stats.deliver_rate), (40.2, 38.0));
+        assert_eq!(parse_queue(&orders, &json!({"messages_ready": 0})).publish_rate, 0.0);
+    }
+}
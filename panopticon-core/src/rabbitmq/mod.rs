@@ -0,0 +1,6 @@
+//! RabbitMQ brokers, through the management API
+
+/// Queue depth, rates and consumers, with the settings of the source
+pub mod model;
+/// Reads the queues from the management API
+pub mod client;
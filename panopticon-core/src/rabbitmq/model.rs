@@ -0,0 +1,79 @@
+use std::fmt;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// A queue, given as `vhost/name`, or just `name` on the default vhost
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RabbitQueue {
+    /// Virtual host of the queue, `/` by default
+    pub vhost: String,
+    /// Name of the queue
+    pub name: String,
+}
+
+impl RabbitQueue {
+    /// Parses a queue given as `vhost/name` or `name`
+    pub fn parse(s: &str) -> Result<RabbitQueue, String> {
+        let (vhost, name) = s.split_once('/').unwrap_or(("/", s));
+        if name.is_empty() {
+            return Err(format!("Expected a queue as vhost/name or name, got {}", s));
+        }
+        Ok(RabbitQueue { vhost: if vhost.is_empty() { "/".to_owned() } else { vhost.to_owned() }, name: name.to_owned() })
+    }
+}
+
+impl fmt::Display for RabbitQueue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.vhost == "/" {
+            write!(f, "{}", self.name)
+        } else {
+            write!(f, "{}/{}", self.vhost, self.name)
+        }
+    }
+}
+
+/// RabbitMQ broker polled through its management API
+#[derive(Clone)]
+pub struct RabbitMqSettings {
+    /// Address of the management plugin, e.g. http://localhost:15672
+    pub address: String,
+    /// Queues to chart
+    pub queues: Vec<RabbitQueue>,
+    /// User of the management API
+    pub username: String,
+    /// Password of the user
+    pub password: String,
+    /// Time in ms each request has to finish
+    pub timeout: u64,
+}
+
+/// Depth, rates and consumers of a queue
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct QueueStats {
+    /// The queue
+    pub queue: RabbitQueue,
+    /// e.g. `running`, `idle`, `flow`
+    pub state: String,
+    /// Messages ready to be delivered
+    pub ready: u64,
+    /// Messages delivered but not acknowledged yet
+    pub unacknowledged: u64,
+    /// Consumers of the queue
+    pub consumers: u64,
+    /// Messages published per second, as computed by the broker
+    pub publish_rate: f64,
+    /// Messages delivered per second, as computed by the broker
+    pub deliver_rate: f64,
+}
+
+/// Stats of the charted queues
+#[derive(Deserialize, Serialize)]
+pub struct RabbitMqStats {
+    /// When the stats were read
+    pub timestamp: DateTime<Local>,
+    /// Stats of the queues that could be read
+    pub queues: Vec<QueueStats>,
+    /// Queues that couldn't be read, with the reason
+    pub errors: Vec<String>,
+}
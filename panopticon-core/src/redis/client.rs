@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use bytes::BytesMut;
+use chrono::{Local, TimeZone};
+use redis_protocol::types::Frame;
+
+use crate::error::FetchError;
+use crate::redis::model::{RedisInfo, RedisSettings, SlowlogEntry};
+
+const SLOWLOG_ENTRIES: &str = "10";
+
+/// Server stats and slow commands, read with INFO and SLOWLOG GET
+pub fn get_info(settings: &RedisSettings) -> Result<RedisInfo, FetchError> {
+    let mut connection = connect(settings)?;
+    if let Some(password) = &settings.password {
+        command(&mut connection, &["AUTH", password]).map_err(|e| FetchError::auth(e.message))?;
+    }
+    let info = match command(&mut connection, &["INFO"])? {
+        Frame::BulkString(b) => String::from_utf8_lossy(&b).into_owned(),
+        f => return Err(FetchError::from(format!("Unexpected INFO response: {:?}", f))),
+    };
+    let slowlog = command(&mut connection, &["SLOWLOG", "GET", SLOWLOG_ENTRIES])?;
+    Ok(parse_info(&info, parse_slowlog(&slowlog)))
+}
+
+fn connect(settings: &RedisSettings) -> Result<TcpStream, FetchError> {
+    let timeout = Duration::from_millis(settings.timeout);
+    let error = |e: std::io::Error| FetchError::from(e).context(&format!("Couldn't connect to Redis at {}", settings.address));
+    let addr = settings.address.to_socket_addrs().map_err(error)?
+        .next()
+        .ok_or_else(|| FetchError::connection(format!("Couldn't resolve {}", settings.address)))?;
+    let stream = TcpStream::connect_timeout(&addr, timeout).map_err(error)?;
+    stream.set_read_timeout(Some(timeout)).map_err(error)?;
+    stream.set_write_timeout(Some(timeout)).map_err(error)?;
+    Ok(stream)
+}
+
+/// Sends a command and reads its reply, turning error replies into errors
+fn command(stream: &mut TcpStream, args: &[&str]) -> Result<Frame, FetchError> {
+    let frame = Frame::Array(args.iter().map(|a| Frame::BulkString(a.as_bytes().to_vec())).collect());
+    let mut buf = BytesMut::new();
+    redis_protocol::prelude::encode_bytes(&mut buf, &frame).map_err(|e| format!("Error encoding frame: {:?}", e))?;
+    stream.write_all(&buf)?;
+
+    let mut response = BytesMut::new();
+    let mut chunk = [0; 16 * 1024];
+    loop {
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            return Err(FetchError::connection("Redis closed the connection"));
+        }
+        response.extend_from_slice(&chunk[..n]);
+        match redis_protocol::prelude::decode_bytes(&response).map_err(|e| format!("Error parsing bytes: {:?}", e))? {
+            // servers requiring a password answer with the NOAUTH error code
+            (Some(Frame::Error(e)), _) if e.starts_with("NOAUTH") => return Err(FetchError::auth(format!("{} failed: {}", args[0], e))),
+            (Some(Frame::Error(e)), _) => return Err(FetchError::from(format!("{} failed: {}", args[0], e))),
+            (Some(f), _) => return Ok(f),
+            // the reply is incomplete
+            (None, _) => {}
+        }
+    }
+}
+
+///
+/// Parses INFO sections, e.g.
+///
+/// # Memory
+/// used_memory:1032456
+/// maxmemory:0
+fn parse_info(info: &str, slowlog: Vec<SlowlogEntry>) -> RedisInfo {
+    let fields: HashMap<&str, &str> = info.lines()
+        .filter_map(|l| l.trim().split_once(':'))
+        .collect();
+    let number = |key: &str| fields.get(key).and_then(|v| v.parse::<u64>().ok()).unwrap_or(0);
+    RedisInfo {
+        timestamp: Local::now(),
+        version: fields.get("redis_version").unwrap_or(&"-").to_string(),
+        used_memory: number("used_memory"),
+        max_memory: number("maxmemory"),
+        connected_clients: number("connected_clients"),
+        blocked_clients: number("blocked_clients"),
+        ops_per_sec: number("instantaneous_ops_per_sec") as f64,
+        keyspace_hits: number("keyspace_hits"),
+        keyspace_misses: number("keyspace_misses"),
+        evicted_keys: number("evicted_keys"),
+        slowlog,
+    }
+}
+
+/// Parses SLOWLOG GET entries, `[id, unix time, microseconds, [command, args...], ...]`
+fn parse_slowlog(frame: &Frame) -> Vec<SlowlogEntry> {
+    let entries = match frame {
+        Frame::Array(entries) => entries,
+        _ => return vec![],
+    };
+    entries.iter()
+        .filter_map(|e| match e {
+            Frame::Array(fields) => match fields.as_slice() {
+                [Frame::Integer(id), Frame::Integer(time), Frame::Integer(duration), Frame::Array(args), ..] => Some(SlowlogEntry {
+                    id: *id,
+                    timestamp: Local.timestamp(*time, 0),
+                    duration_us: *duration,
+                    command: args.iter().filter_map(|a| a.as_str()).collect::<Vec<&str>>().join(" "),
+                }),
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use redis_protocol::types::Frame;
+
+    use crate::redis::client::{parse_info, parse_slowlog};
+
+    #[test]
+    fn info_and_slowlog_are_parsed() {
+        let slowlog = Frame::Array(vec![Frame::Array(vec![
+            Frame::Integer(14),
+            Frame::Integer(1_600_000_000),
+            Frame::Integer(15_000),
+            Frame::Array(vec![Frame::BulkString(b"KEYS".to_vec()), Frame::BulkString(b"session:*".to_vec())]),
+            Frame::BulkString(b"10.0.0.5:53412".to_vec()),
+            Frame::BulkString(b"".to_vec()),
+        ])]);
+        let info = parse_info("# Server\r\nredis_version:6.0.9\r\n\r\n# Clients\r\nconnected_clients:12\r\n\
+            # Memory\r\nused_memory:1048576\r\nmaxmemory:0\r\n# Stats\r\ninstantaneous_ops_per_sec:250\r\nkeyspace_hits:90\r\nkeyspace_misses:10\r\n",
+            parse_slowlog(&slowlog));
+        assert_eq!((info.version.as_str(), info.connected_clients, info.used_memory), ("6.0.9", 12, 1_048_576));
+        assert_eq!((info.ops_per_sec, info.keyspace_hits, info.keyspace_misses, info.evicted_keys), (250.0, 90, 10, 0));
+        assert_eq!(info.slowlog[0].command, "KEYS session:*");
+        assert_eq!(info.slowlog[0].duration_us, 15_000);
+    }
+}
@@ -0,0 +1,6 @@
+//! Redis servers
+
+/// Server stats and slow commands, with the settings of the source
+pub mod model;
+/// Reads INFO and SLOWLOG over the Redis protocol
+pub mod client;
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// Redis server polled over its protocol
+#[derive(Clone)]
+pub struct RedisSettings {
+    /// Address of the Redis server, e.g. localhost:6379
+    pub address: String,
+    /// Sent with AUTH before the other commands, if set
+    pub password: Option<String>,
+    /// Time in ms each command has to be answered
+    pub timeout: u64,
+}
+
+/// A command logged for being slow
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct SlowlogEntry {
+    /// Id of the entry, growing with every logged command
+    pub id: i64,
+    /// When the command was run
+    pub timestamp: DateTime<Local>,
+    /// How long the command ran, in µs
+    pub duration_us: i64,
+    /// The command with its arguments, as logged
+    pub command: String,
+}
+
+/// Stats of the server, from INFO, and its slowlog
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct RedisInfo {
+    /// When the server was read
+    pub timestamp: DateTime<Local>,
+    /// Version of the server
+    pub version: String,
+    /// Memory allocated by the server, in bytes
+    pub used_memory: u64,
+    /// 0 if there's no memory limit
+    pub max_memory: u64,
+    /// Connected clients, replicas left out
+    pub connected_clients: u64,
+    /// Clients waiting in a blocking command
+    pub blocked_clients: u64,
+    /// Commands processed per second
+    pub ops_per_sec: f64,
+    /// Successful key lookups since the server started
+    pub keyspace_hits: u64,
+    /// Failed key lookups since the server started
+    pub keyspace_misses: u64,
+    /// Keys evicted because of the memory limit, since the server started
+    pub evicted_keys: u64,
+    /// Most recent slow commands, newest first
+    pub slowlog: Vec<SlowlogEntry>,
+}
@@ -0,0 +1,220 @@
+use std::time::Duration;
+
+use mysql::prelude::Queryable;
+use rusqlite::{NO_PARAMS, OpenFlags};
+use rusqlite::types::ValueRef;
+
+use crate::error::FetchError;
+use crate::postgres;
+use crate::sql::model::{Database, SqlQuerySettings};
+
+const DEFAULT_TIMEOUT: u64 = 1000;
+// MySQL's "Access denied for user"
+const MYSQL_ACCESS_DENIED: u16 = 1045;
+
+///
+/// Runs the query with the database's driver, returning its numeric columns as series names and values.
+///
+/// The connection is kept open between runs, and opened again on the run after a failure.
+/// Credentials are only read from the url, which never leaves the process.
+///
+/// The drivers are used rather than sqlx, which decodes columns into the Rust types they are declared as,
+/// unknown for the columns of an arbitrary query (`count(*)` is a BIGINT, `avg` a NUMERIC, ...), where these
+/// drivers give any value the way it prints. sqlx releases since 0.5 also run on tokio 1, and this crate on tokio 0.2.
+pub struct SqlClient {
+    settings: SqlQuerySettings,
+    connection: Option<Connection>,
+}
+
+enum Connection {
+    Postgres(::postgres::Client),
+    MySql(mysql::Conn),
+    Sqlite(rusqlite::Connection),
+}
+
+/// Names of the columns and the rows of a result, with NULLs as empty values
+type Rows = (Vec<String>, Vec<Vec<String>>);
+
+impl SqlClient {
+    /// A client of the query's database, connecting on the first run
+    pub fn new(settings: SqlQuerySettings) -> SqlClient {
+        SqlClient { settings, connection: None }
+    }
+
+    /// Numeric columns of the query's result
+    pub fn get_values(&mut self) -> Result<Vec<(String, f64)>, FetchError> {
+        let timeout = Duration::from_millis(self.settings.timeout.unwrap_or(DEFAULT_TIMEOUT));
+        let mut connection = match self.connection.take() {
+            Some(c) => c,
+            None => Connection::open(&self.settings.url, timeout)?,
+        };
+        let (columns, rows) = connection.query(&self.settings.query)?;
+        self.connection = Some(connection);
+        Ok(chart_values(&columns, &rows))
+    }
+}
+
+impl Connection {
+    fn open(url: &str, timeout: Duration) -> Result<Connection, FetchError> {
+        match Database::of_url(url)? {
+            Database::Postgres => postgres::client::connect(url, timeout).map(Connection::Postgres),
+            Database::MySql => {
+                let opts = mysql::Opts::from_url(url).map_err(|e| format!("Invalid MySQL url: {}", e))?;
+                let opts = mysql::OptsBuilder::from_opts(opts)
+                    .tcp_connect_timeout(Some(timeout))
+                    .read_timeout(Some(timeout))
+                    .write_timeout(Some(timeout));
+                mysql::Conn::new(opts).map(Connection::MySql).map_err(mysql_error)
+            }
+            Database::Sqlite => {
+                let path = url.trim_start_matches("sqlite:").trim_start_matches("//");
+                let connection = rusqlite::Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+                    .map_err(|e| format!("Couldn't open {}: {}", path, e))?;
+                connection.busy_timeout(timeout).map_err(|e| e.to_string())?;
+                Ok(Connection::Sqlite(connection))
+            }
+        }
+    }
+
+    fn query(&mut self, query: &str) -> Result<Rows, FetchError> {
+        match self {
+            Connection::Postgres(client) => {
+                // the simple query protocol gives every value as text, as psql would show it
+                let rows: Vec<_> = client.simple_query(query).map_err(postgres::client::fetch_error)?.into_iter()
+                    .filter_map(|m| match m {
+                        ::postgres::SimpleQueryMessage::Row(row) => Some(row),
+                        _ => None,
+                    })
+                    .collect();
+                let columns = rows.first().map_or(vec![], |r| r.columns().iter().map(|c| c.name().to_owned()).collect());
+                let rows = rows.iter()
+                    .map(|r| (0..r.len()).map(|i| r.get(i).unwrap_or_default().to_owned()).collect())
+                    .collect();
+                Ok((columns, rows))
+            }
+            Connection::MySql(conn) => {
+                let rows: Vec<mysql::Row> = conn.query(query).map_err(mysql_error)?;
+                let columns = rows.first().map_or(vec![], |r| r.columns_ref().iter().map(|c| c.name_str().into_owned()).collect());
+                let rows = rows.into_iter()
+                    .map(|r| r.unwrap().into_iter().map(|v| match v {
+                        mysql::Value::NULL => String::new(),
+                        mysql::Value::Bytes(b) => String::from_utf8_lossy(&b).into_owned(),
+                        other => other.as_sql(true),
+                    }).collect())
+                    .collect();
+                Ok((columns, rows))
+            }
+            Connection::Sqlite(connection) => sqlite_rows(connection, query)
+                .map_err(|e| FetchError::from(format!("SQLite query failed: {}", e))),
+        }
+    }
+}
+
+fn sqlite_rows(connection: &rusqlite::Connection, query: &str) -> rusqlite::Result<Rows> {
+    let mut statement = connection.prepare(query)?;
+    let columns: Vec<String> = statement.column_names().iter().map(|c| c.to_string()).collect();
+    let mut rows = vec![];
+    let mut result = statement.query(NO_PARAMS)?;
+    while let Some(row) = result.next()? {
+        rows.push((0..columns.len()).map(|i| match row.get_raw(i) {
+            ValueRef::Null => String::new(),
+            ValueRef::Integer(v) => v.to_string(),
+            ValueRef::Real(v) => v.to_string(),
+            ValueRef::Text(t) | ValueRef::Blob(t) => String::from_utf8_lossy(t).into_owned(),
+        }).collect());
+    }
+    Ok((columns, rows))
+}
+
+/// Failures of the server are told apart by their error code, the others by their socket error
+fn mysql_error(e: mysql::Error) -> FetchError {
+    match e {
+        mysql::Error::IoError(io) => FetchError::from(io).context("MySQL query failed"),
+        mysql::Error::MySqlError(ref server) if server.code == MYSQL_ACCESS_DENIED =>
+            FetchError::auth(format!("MySQL query failed: {}", e)),
+        mysql::Error::DriverError(mysql::DriverError::ConnectTimeout) => FetchError::timeout(format!("MySQL query failed: {}", e)),
+        mysql::Error::DriverError(mysql::DriverError::CouldNotConnect(_)) => FetchError::connection(format!("MySQL query failed: {}", e)),
+        e => FetchError::from(format!("MySQL query failed: {}", e)),
+    }
+}
+
+///
+/// Numeric columns of a result, e.g.
+///
+/// status   count   oldest_secs
+/// pending  12      340
+/// failed   2       7200
+///
+/// gives `pending count`, `pending oldest_secs`, `failed count` and `failed oldest_secs`. Non-numeric columns label the rows,
+/// the values of a single numeric column are labelled by them alone, and a single row isn't labelled.
+fn chart_values(header: &[String], rows: &[Vec<String>]) -> Vec<(String, f64)> {
+    // NULLs are left out rather than making a column non-numeric
+    let is_null = |v: &str| v.is_empty() || v == "NULL";
+    let numeric: Vec<bool> = (0..header.len())
+        .map(|i| rows.iter().all(|r| r.get(i).is_some_and(|v| is_null(v) || v.parse::<f64>().is_ok())))
+        .collect();
+    let numeric_count = numeric.iter().filter(|n| **n).count();
+
+    let mut values = vec![];
+    for row in rows {
+        let label: Vec<&str> = row.iter().zip(&numeric).filter(|(_, n)| !**n).map(|(v, _)| v.as_str()).collect();
+        let label = label.join(" ");
+        for ((column, value), _) in header.iter().zip(row).zip(&numeric).filter(|(_, n)| **n) {
+            if let Ok(v) = value.parse::<f64>() {
+                let series = match (rows.len() == 1 || label.is_empty(), numeric_count == 1) {
+                    (true, _) => column.to_string(),
+                    (false, true) => label.to_owned(),
+                    (false, false) => format!("{} {}", label, column),
+                };
+                values.push((series, v));
+            }
+        }
+    }
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sql::client::chart_values;
+
+    fn result(text: &str) -> (Vec<String>, Vec<Vec<String>>) {
+        let mut lines = text.lines().map(|l| l.split('\t').map(|v| v.to_owned()).collect::<Vec<_>>());
+        (lines.next().unwrap_or_default(), lines.collect())
+    }
+
+    #[test]
+    fn numeric_columns_are_charted() {
+        let values = |text: &str| {
+            let (header, rows) = result(text);
+            chart_values(&header, &rows)
+        };
+        assert_eq!(values("count\n12"), vec![("count".to_owned(), 12.0)]);
+        assert_eq!(values("status\tcount\toldest\npending\t12\t340\nfailed\t2\t"), vec![
+            ("pending count".to_owned(), 12.0),
+            ("pending oldest".to_owned(), 340.0),
+            ("failed count".to_owned(), 2.0),
+        ]);
+        assert_eq!(values("queue\tdepth\norders\t3\nbilling\t0.5"), vec![("orders".to_owned(), 3.0), ("billing".to_owned(), 0.5)]);
+        assert_eq!(chart_values(&[], &[]), vec![]);
+    }
+
+    #[test]
+    fn sqlite_databases_are_queried_read_only() {
+        let path = std::env::temp_dir().join(format!("panopticon-sql-{}.db", std::process::id()));
+        let db = rusqlite::Connection::open(&path).unwrap();
+        db.execute_batch("CREATE TABLE jobs (status TEXT, n INTEGER); INSERT INTO jobs VALUES ('pending', 12), ('failed', NULL);").unwrap();
+
+        let settings = crate::sql::model::SqlQuerySettings {
+            name: "jobs".to_owned(),
+            url: format!("sqlite://{}", path.display()),
+            query: "SELECT status, n FROM jobs".to_owned(),
+            interval: None,
+            timeout: None,
+        };
+        let mut client = crate::sql::client::SqlClient::new(settings.clone());
+        assert_eq!(client.get_values().unwrap(), vec![("pending".to_owned(), 12.0)]);
+        assert!(crate::sql::client::SqlClient::new(crate::sql::model::SqlQuerySettings { query: "DELETE FROM jobs".to_owned(), ..settings })
+            .get_values().is_err());
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,6 @@
+//! Values returned by SQL queries
+
+/// Settings of the source
+pub mod model;
+/// Runs the query with the database's driver
+pub mod client;
@@ -0,0 +1,130 @@
+use std::collections::{BTreeMap, HashSet};
+use std::net::UdpSocket;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use chrono::Local;
+
+use crate::statsd::model::{StatsdKind, StatsdLine, StatsdMetrics, StatsdSample};
+
+// metric name and series name
+type SeriesKey = (String, String);
+
+///
+/// Receives statsd packets on a UDP socket, aggregating them in the background until they're taken.
+///
+/// Counters and gauges keep their values across ticks, as statsd servers do. Timers and sets only
+/// report what was received since the previous tick.
+pub struct StatsdListener {
+    aggregate: Arc<Mutex<Aggregate>>,
+}
+
+impl StatsdListener {
+    /// Receives packets on the address, from a thread of its own
+    pub fn bind(address: &str) -> Result<StatsdListener, String> {
+        let socket = UdpSocket::bind(address)
+            .map_err(|e| format!("Couldn't listen for statsd metrics on {}: {}", address, e))?;
+        let aggregate = Arc::new(Mutex::new(Aggregate::default()));
+        {
+            let aggregate = aggregate.clone();
+            thread::spawn(move || {
+                // the largest possible UDP payload
+                let mut buf = [0; 65535];
+                while let Ok(n) = socket.recv(&mut buf) {
+                    let packet = String::from_utf8_lossy(&buf[..n]);
+                    let mut aggregate = aggregate.lock().unwrap();
+                    for line in packet.lines().filter(|l| !l.trim().is_empty()) {
+                        match StatsdLine::parse(line) {
+                            Ok(l) => aggregate.add(l),
+                            Err(_) => aggregate.invalid_lines += 1,
+                        }
+                    }
+                }
+            });
+        }
+        Ok(StatsdListener { aggregate })
+    }
+
+    /// Metrics aggregated from the packets received since the previous call
+    pub fn take_metrics(&self) -> StatsdMetrics {
+        self.aggregate.lock().unwrap().take()
+    }
+}
+
+#[derive(Default)]
+struct Aggregate {
+    counters: BTreeMap<SeriesKey, f64>,
+    gauges: BTreeMap<SeriesKey, f64>,
+    timers: BTreeMap<SeriesKey, Vec<f64>>,
+    sets: BTreeMap<SeriesKey, HashSet<String>>,
+    metrics: Vec<(String, StatsdKind)>,
+    invalid_lines: u64,
+}
+
+impl Aggregate {
+    fn add(&mut self, line: StatsdLine) {
+        if !self.metrics.iter().any(|(name, _)| name == &line.name) {
+            self.metrics.push((line.name.to_owned(), line.kind));
+        }
+        let key = (line.name.to_owned(), line.series_name());
+        // values of sets aren't numbers, the rest were checked when parsing
+        let value = line.value.parse::<f64>().unwrap_or(0.0);
+        match line.kind {
+            StatsdKind::Counter => *self.counters.entry(key).or_insert(0.0) += value / line.sample_rate,
+            StatsdKind::Gauge if line.value.starts_with('+') || line.value.starts_with('-') =>
+                *self.gauges.entry(key).or_insert(0.0) += value,
+            StatsdKind::Gauge => { self.gauges.insert(key, value); }
+            StatsdKind::Timer => self.timers.entry(key).or_default().push(value),
+            StatsdKind::Set => { self.sets.entry(key).or_default().insert(line.value); }
+        }
+    }
+
+    fn take(&mut self) -> StatsdMetrics {
+        let sample = |(name, series): &SeriesKey, value: f64, counter: bool|
+            StatsdSample { name: name.to_owned(), series: series.to_owned(), value, counter };
+        let mut samples: Vec<StatsdSample> = self.counters.iter().map(|(k, v)| sample(k, *v, true)).collect();
+        samples.extend(self.gauges.iter().map(|(k, v)| sample(k, *v, false)));
+        for ((name, series), values) in std::mem::take(&mut self.timers) {
+            // a timer without tags gets the name of the statistic alone
+            let stat = |s: &str| if series == name { s.to_owned() } else { format!("{} {}", series, s) };
+            let mean = values.iter().sum::<f64>() / values.len() as f64;
+            let max = values.iter().cloned().fold(f64::MIN, f64::max);
+            samples.push(sample(&(name.to_owned(), stat("mean")), mean, false));
+            samples.push(sample(&(name.to_owned(), stat("max")), max, false));
+        }
+        samples.extend(std::mem::take(&mut self.sets).iter().map(|(k, v)| sample(k, v.len() as f64, false)));
+        StatsdMetrics {
+            timestamp: Local::now(),
+            samples,
+            metrics: self.metrics.clone(),
+            invalid_lines: std::mem::take(&mut self.invalid_lines),
+            error: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::statsd::listener::Aggregate;
+    use crate::statsd::model::StatsdLine;
+
+    #[test]
+    fn metrics_are_aggregated_per_tick() {
+        let mut aggregate = Aggregate::default();
+        for line in &["hits:2|c", "hits:1|c|@0.25", "queue:10|g", "queue:-3|g", "db:10|ms", "db:30|ms", "users:a|s", "users:b|s", "users:a|s"] {
+            aggregate.add(StatsdLine::parse(line).unwrap());
+        }
+        let values = |a: &mut Aggregate| -> Vec<(String, f64)> {
+            a.take().samples.into_iter().map(|s| (s.series, s.value)).collect()
+        };
+        assert_eq!(values(&mut aggregate), vec![
+            ("hits".to_owned(), 6.0),
+            ("queue".to_owned(), 7.0),
+            ("mean".to_owned(), 20.0),
+            ("max".to_owned(), 30.0),
+            ("users".to_owned(), 2.0),
+        ]);
+        // timers and sets start over every tick
+        assert_eq!(values(&mut aggregate), vec![("hits".to_owned(), 6.0), ("queue".to_owned(), 7.0)]);
+    }
+}
@@ -0,0 +1,6 @@
+//! Metrics pushed over the statsd protocol
+
+/// Parsed lines and aggregated values, with the settings of the listener
+pub mod model;
+/// Receives the lines over UDP and aggregates them by tick
+pub mod listener;
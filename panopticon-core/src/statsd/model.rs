@@ -0,0 +1,162 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use crate::error::FetchError;
+
+/// Listener of the statsd packets apps push over UDP
+#[derive(Clone)]
+pub struct StatsdSettings {
+    /// Local address to receive statsd packets on, e.g. 0.0.0.0:8125
+    pub address: String,
+    /// Names of the metrics to chart, all the received ones if empty
+    pub metrics: Vec<String>,
+}
+
+/// Type of a statsd metric
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+pub enum StatsdKind {
+    /// `c`, summed up
+    Counter,
+    /// `g`, keeping the latest value
+    Gauge,
+    /// `ms`, `h` or `d`, aggregated per tick
+    Timer,
+    /// `s`, counting unique values per tick
+    Set,
+}
+
+impl StatsdKind {
+    /// Name of the type, as shown
+    pub fn name(&self) -> &'static str {
+        match self {
+            StatsdKind::Counter => "counter",
+            StatsdKind::Gauge => "gauge",
+            StatsdKind::Timer => "timer",
+            StatsdKind::Set => "set",
+        }
+    }
+}
+
+///
+/// A single statsd metric, e.g. `api.requests:1|c|@0.5|#route:/orders`.
+///
+/// Tags are a DogStatsD extension. Gauges with a sign, e.g. `queue:-3|g`, change the previous value.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StatsdLine {
+    /// Name of the metric
+    pub name: String,
+    /// Raw value, as sets count unique values of any kind
+    pub value: String,
+    /// Type of the metric
+    pub kind: StatsdKind,
+    /// Share of the events the line stands for, 1 unless sampled
+    pub sample_rate: f64,
+    /// DogStatsD tags, as names and values
+    pub tags: Vec<(String, String)>,
+}
+
+impl StatsdLine {
+    /// Parses a line like `name:value|type|@rate|#tag:value`, the rate and tags being optional
+    pub fn parse(line: &str) -> Result<StatsdLine, String> {
+        let invalid = || format!("Expected a statsd metric like name:value|type, got {}", line);
+        let (name, rest) = line.trim().split_once(':').ok_or_else(invalid)?;
+        let mut fields = rest.split('|');
+        let value = fields.next().filter(|v| !v.is_empty()).ok_or_else(invalid)?;
+        let kind = match fields.next() {
+            Some("c") => StatsdKind::Counter,
+            Some("g") => StatsdKind::Gauge,
+            Some("ms") | Some("h") | Some("d") => StatsdKind::Timer,
+            Some("s") => StatsdKind::Set,
+            _ => return Err(invalid()),
+        };
+        if kind != StatsdKind::Set && value.parse::<f64>().is_err() {
+            return Err(invalid());
+        }
+        let mut sample_rate = 1.0;
+        let mut tags = vec![];
+        for field in fields {
+            if let Some(rate) = field.strip_prefix('@') {
+                sample_rate = rate.parse().ok().filter(|r| *r > 0.0 && *r <= 1.0).ok_or_else(invalid)?;
+            } else if let Some(t) = field.strip_prefix('#') {
+                tags = t.split(',')
+                    .filter(|t| !t.is_empty())
+                    .map(|t| match t.split_once(':') {
+                        Some((k, v)) => (k.to_owned(), v.to_owned()),
+                        None => (t.to_owned(), "".to_owned()),
+                    })
+                    .collect();
+                tags.sort();
+            }
+        }
+        if name.is_empty() {
+            return Err(invalid());
+        }
+        Ok(StatsdLine { name: name.to_owned(), value: value.to_owned(), kind, sample_rate, tags })
+    }
+
+    /// Name of the series within the metric's chart, the metric name if it has no tags
+    pub fn series_name(&self) -> String {
+        if self.tags.is_empty() {
+            self.name.to_owned()
+        } else {
+            let tags: Vec<String> = self.tags.iter()
+                .map(|(k, v)| if v.is_empty() { k.to_owned() } else { format!("{}:{}", k, v) })
+                .collect();
+            format!("{{{}}}", tags.join(","))
+        }
+    }
+}
+
+/// Value of a series at the tick, ready to chart
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct StatsdSample {
+    /// Name of the metric, charted on its own
+    pub name: String,
+    /// Name of the series within the metric's chart
+    pub series: String,
+    /// Latest value, or the total since the listener started for counters
+    pub value: f64,
+    /// Counters are reported as totals since the listener started, so their rate is charted
+    pub counter: bool,
+}
+
+/// Metrics aggregated from the packets received since the previous tick
+#[derive(Deserialize, Serialize)]
+pub struct StatsdMetrics {
+    /// When the metrics were taken
+    pub timestamp: DateTime<Local>,
+    /// Values of every series of the charted metrics
+    pub samples: Vec<StatsdSample>,
+    /// Names and kinds of all the metrics received so far
+    pub metrics: Vec<(String, StatsdKind)>,
+    /// Lines that couldn't be parsed since the previous tick
+    pub invalid_lines: u64,
+    /// Why no packets can be received, e.g. a port that's taken
+    #[serde(default)]
+    pub error: Option<FetchError>,
+}
+
+impl StatsdMetrics {
+    /// No metrics, as the listener couldn't be set up
+    pub fn failed(error: FetchError) -> StatsdMetrics {
+        StatsdMetrics { timestamp: Local::now(), samples: vec![], metrics: vec![], invalid_lines: 0, error: Some(error) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::statsd::model::{StatsdKind, StatsdLine};
+
+    #[test]
+    fn lines_are_parsed_with_rates_and_tags() {
+        let line = StatsdLine::parse("api.requests:3|c|@0.5|#route:/orders,canary").unwrap();
+        assert_eq!((line.kind, line.sample_rate), (StatsdKind::Counter, 0.5));
+        assert_eq!(line.series_name(), "{canary,route:/orders}");
+        assert_eq!(StatsdLine::parse("queue.size:-2|g").unwrap().value, "-2");
+        assert_eq!(StatsdLine::parse("db.query:12.5|ms").unwrap().series_name(), "db.query");
+        assert_eq!(StatsdLine::parse("users:alice|s").unwrap().kind, StatsdKind::Set);
+        assert!(StatsdLine::parse("api.requests:abc|c").is_err());
+        assert!(StatsdLine::parse("api.requests:1|x").is_err());
+        assert!(StatsdLine::parse("api.requests:1|c|@2").is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use std::cmp::Reverse;
+use std::time::Duration;
+
+use chrono::Local;
+use reqwest::Url;
+use serde_json::Value;
+
+use crate::error::FetchError;
+use crate::traces::model::{RawSpan, RecentTraces, Trace, TraceBackend, TracesSettings};
+
+/// Traces of the last hour, Jaeger takes it as a Go duration
+const LOOKBACK_MS: u64 = 3_600_000;
+
+/// Latest traces of the service, read from the Jaeger or Zipkin query API
+pub fn get_recent_traces(settings: &TracesSettings) -> Result<RecentTraces, FetchError> {
+    let body = get_traces_async(settings)?;
+    let mut traces = match settings.backend {
+        TraceBackend::Jaeger => parse_jaeger_traces(&body),
+        TraceBackend::Zipkin => parse_zipkin_traces(&body),
+    };
+    traces.sort_by_key(|t| Reverse(t.start));
+    Ok(RecentTraces { timestamp: Local::now(), traces })
+}
+
+#[tokio::main]
+async fn get_traces_async(settings: &TracesSettings) -> Result<Value, FetchError> {
+    let response = reqwest::Client::builder()
+        .timeout(Duration::from_millis(settings.timeout))
+        .build()?
+        .get(traces_url(settings)?)
+        .send().await?;
+    if !response.status().is_success() {
+        return Err(FetchError::of_status(response.status(), format!("Request to get traces failed with status {}", response.status())));
+    }
+    Ok(response.json().await?)
+}
+
+fn traces_url(settings: &TracesSettings) -> Result<Url, String> {
+    let (path, service, lookback) = match settings.backend {
+        TraceBackend::Jaeger => ("api/traces", "service", "1h".to_owned()),
+        TraceBackend::Zipkin => ("api/v2/traces", "serviceName", LOOKBACK_MS.to_string()),
+    };
+    let base = format!("{}/{}", settings.address.trim_end_matches('/'), path);
+    let limit = settings.limit.to_string();
+    Url::parse_with_params(&base, &[(service, settings.service.as_str()), ("limit", &limit), ("lookback", &lookback)])
+        .map_err(|e| format!("Invalid traces address {}: {}", settings.address, e))
+}
+
+///
+/// Parses traces from Jaeger's query API, e.g.
+///
+/// {"data": [{"traceID": "a1", "processes": {"p1": {"serviceName": "orders"}}, "spans": [
+///   {"spanID": "b2", "operationName": "GET /orders", "references": [{"refType": "CHILD_OF", "spanID": "c3"}],
+///    "startTime": 1591000000000000, "duration": 1200, "processID": "p1", "tags": [{"key": "error", "value": true}]}
+/// ]}]}
+fn parse_jaeger_traces(json: &Value) -> Vec<Trace> {
+    json["data"].as_array().map_or(vec![], |traces| traces.iter().map(|t| {
+        let spans = t["spans"].as_array().map_or(vec![], |spans| spans.iter().map(|s| RawSpan {
+            id: s["spanID"].as_str().unwrap_or_default().to_owned(),
+            parent_id: s["references"].as_array()
+                .and_then(|refs| refs.iter().find(|r| r["refType"] == "CHILD_OF").or_else(|| refs.first()))
+                .and_then(|r| r["spanID"].as_str())
+                .map(|id| id.to_owned()),
+            service: s["processID"].as_str()
+                .and_then(|p| t["processes"][p]["serviceName"].as_str())
+                .unwrap_or("-").to_owned(),
+            operation: s["operationName"].as_str().unwrap_or("-").to_owned(),
+            start: s["startTime"].as_u64().unwrap_or(0),
+            duration: s["duration"].as_u64().unwrap_or(0),
+            error: s["tags"].as_array().is_some_and(|tags| tags.iter()
+                .any(|tag| tag["key"] == "error" && (tag["value"] == true || tag["value"] == "true"))),
+        }).collect());
+        Trace::new(t["traceID"].as_str().unwrap_or_default().to_owned(), spans)
+    }).collect())
+}
+
+///
+/// Parses traces from Zipkin's v2 API, a list of spans for each trace, e.g.
+///
+/// [[{"traceId": "a1", "id": "b2", "parentId": "c3", "name": "get /orders", "timestamp": 1591000000000000,
+///    "duration": 1200, "localEndpoint": {"serviceName": "orders"}, "tags": {"error": "timeout"}}]]
+fn parse_zipkin_traces(json: &Value) -> Vec<Trace> {
+    json.as_array().map_or(vec![], |traces| traces.iter().map(|t| {
+        let spans: Vec<RawSpan> = t.as_array().map_or(vec![], |spans| spans.iter().map(|s| RawSpan {
+            id: s["id"].as_str().unwrap_or_default().to_owned(),
+            parent_id: s["parentId"].as_str().map(|id| id.to_owned()),
+            service: s["localEndpoint"]["serviceName"].as_str().unwrap_or("-").to_owned(),
+            operation: s["name"].as_str().unwrap_or("-").to_owned(),
+            start: s["timestamp"].as_u64().unwrap_or(0),
+            duration: s["duration"].as_u64().unwrap_or(0),
+            // the value is the error message
+            error: !s["tags"]["error"].is_null(),
+        }).collect());
+        let id = t[0]["traceId"].as_str().unwrap_or_default().to_owned();
+        Trace::new(id, spans)
+    }).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::traces::client::{parse_jaeger_traces, parse_zipkin_traces, traces_url};
+use crate::traces::model::{TraceBackend, TracesSettings};
+
+    #[test]
+    fn jaeger_and_zipkin_traces_are_parsed() {
+        let settings = TracesSettings { address: "http://localhost:16686/".to_owned(), backend: TraceBackend::Jaeger, service: "orders api".to_owned(), limit: 20, timeout: 1000 };
+        assert_eq!(traces_url(&settings).unwrap().as_str(), "http://localhost:16686/api/traces?service=orders+api&limit=20&lookback=1h");
+
+        let jaeger = parse_jaeger_traces(&json!({"data": [{"traceID": "a1", "processes": {"p1": {"serviceName": "orders"}, "p2": {"serviceName": "db"}}, "spans": [
+            {"spanID": "s2", "operationName": "SELECT", "references": [{"refType": "CHILD_OF", "traceID": "a1", "spanID": "s1"}],
+             "startTime": 1591000000000300u64, "duration": 700, "processID": "p2", "tags": [{"key": "error", "value": true}]},
+            {"spanID": "s1", "operationName": "GET /orders", "references": [], "startTime": 1591000000000000u64, "duration": 1200, "processID": "p1", "tags": []}
+        ]}]}));
+        let trace = &jaeger[0];
+        assert_eq!((trace.root().unwrap().operation.as_str(), trace.duration(), trace.has_error()), ("GET /orders", 1200, true));
+        assert_eq!((trace.spans[1].parent, trace.spans[1].offset, trace.spans[1].service.as_str()), (Some(0), 300, "db"));
+
+        let zipkin = parse_zipkin_traces(&json!([[
+            {"traceId": "a1", "id": "s1", "name": "get /orders", "timestamp": 1591000000000000u64, "duration": 1200, "localEndpoint": {"serviceName": "orders"}},
+            {"traceId": "a1", "id": "s2", "parentId": "s1", "name": "select", "timestamp": 1591000000000300u64, "duration": 700, "localEndpoint": {"serviceName": "db"}, "tags": {"error": "timeout"}},
+            {"traceId": "a1", "id": "s2", "parentId": "s1", "name": "select", "timestamp": 1591000000000310u64, "duration": 650, "localEndpoint": {"serviceName": "db"}, "shared": true}
+        ]]));
+        let trace = &zipkin[0];
+        assert_eq!(trace.id, "a1");
+        assert_eq!(trace.spans.iter().map(|s| s.parent).collect::<Vec<_>>(), vec![None, Some(0), Some(0)]);
+        assert!(trace.has_error());
+    }
+}
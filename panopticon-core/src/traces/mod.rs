@@ -0,0 +1,6 @@
+//! Recent traces from Jaeger or Zipkin
+
+/// Traces and spans, with the settings of the source
+pub mod model;
+/// Reads the latest traces of a service from the query API
+pub mod client;
@@ -0,0 +1,145 @@
+use chrono::{DateTime, Local, TimeZone};
+use serde::{Deserialize, Serialize};
+
+/// Tracing systems whose query API is read
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TraceBackend {
+    /// Jaeger, through its HTTP query API
+    Jaeger,
+    /// Zipkin, through its v2 API
+    Zipkin,
+}
+
+impl TraceBackend {
+    /// Name of the backend, as shown
+    pub fn name(&self) -> &'static str {
+        match self {
+            TraceBackend::Jaeger => "Jaeger",
+            TraceBackend::Zipkin => "Zipkin",
+        }
+    }
+}
+
+/// Tracing system the latest traces of a service are read from
+#[derive(Clone)]
+pub struct TracesSettings {
+    /// Address of the query API, e.g. http://localhost:16686 for Jaeger or http://localhost:9411 for Zipkin
+    pub address: String,
+    /// Which API the address serves
+    pub backend: TraceBackend,
+    /// Service whose traces are listed
+    pub service: String,
+    /// Number of recent traces to list
+    pub limit: usize,
+    /// Time in ms each request has to finish
+    pub timeout: u64,
+}
+
+/// A span as reported by the tracing backend, linked to its parent by id
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawSpan {
+    /// Id of the span
+    pub id: String,
+    /// Id of the parent span, none for roots
+    pub parent_id: Option<String>,
+    /// Service that reported the span
+    pub service: String,
+    /// Name of the operation
+    pub operation: String,
+    /// Microseconds since the epoch
+    pub start: u64,
+    /// Microseconds
+    pub duration: u64,
+    /// Whether the span is tagged as failed
+    pub error: bool,
+}
+
+/// A span of a trace, identified by its position in the trace so that it can be shown in a tree
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Span {
+    /// Index of the span in the trace
+    pub id: usize,
+    /// Index of the parent span, none for roots
+    pub parent: Option<usize>,
+    /// Service that reported the span
+    pub service: String,
+    /// Name of the operation
+    pub operation: String,
+    /// Microseconds since the start of the trace
+    pub offset: u64,
+    /// Microseconds
+    pub duration: u64,
+    /// Whether the span is tagged as failed
+    pub error: bool,
+}
+
+/// A trace, as a tree of spans
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Trace {
+    /// Id of the trace
+    pub id: String,
+    /// Microseconds since the epoch
+    pub start: u64,
+    /// Spans by start, a span whose parent wasn't reported is shown as a root
+    pub spans: Vec<Span>,
+}
+
+impl Trace {
+    /// Links the spans of a trace by their position, sorting them by start
+    pub fn new(id: String, mut raw: Vec<RawSpan>) -> Trace {
+        raw.sort_by_key(|s| s.start);
+        let start = raw.first().map_or(0, |s| s.start);
+        let index = |id: &str| raw.iter().position(|s| s.id == id);
+        let spans = raw.iter().enumerate().map(|(i, s)| Span {
+            id: i,
+            // Zipkin reports shared spans twice, under the same id
+            parent: s.parent_id.as_deref().and_then(index).filter(|p| *p != i),
+            service: s.service.to_owned(),
+            operation: s.operation.to_owned(),
+            offset: s.start - start,
+            duration: s.duration,
+            error: s.error,
+        }).collect();
+        Trace { id, start, spans }
+    }
+
+    /// The first root span, which usually is the entry point
+    pub fn root(&self) -> Option<&Span> {
+        self.spans.iter().find(|s| s.parent.is_none())
+    }
+
+    /// Microseconds from the first span start to the last span end
+    pub fn duration(&self) -> u64 {
+        self.spans.iter().map(|s| s.offset + s.duration).max().unwrap_or(0)
+    }
+
+    /// Whether any of the spans failed
+    pub fn has_error(&self) -> bool {
+        self.spans.iter().any(|s| s.error)
+    }
+
+    /// When the trace started
+    pub fn started_at(&self) -> DateTime<Local> {
+        Local.timestamp_millis((self.start / 1000) as i64)
+    }
+}
+
+/// Latest traces of the service
+#[derive(Deserialize, Serialize)]
+pub struct RecentTraces {
+    /// When the traces were read
+    pub timestamp: DateTime<Local>,
+    /// Latest first
+    pub traces: Vec<Trace>,
+}
+
+/// e.g. `850µs`, `12.3ms` or `2.05s`
+pub fn format_micros(us: u64) -> String {
+    if us < 1000 {
+        format!("{}µs", us)
+    } else if us < 1_000_000 {
+        format!("{:.1}ms", us as f64 / 1000.0)
+    } else {
+        format!("{:.2}s", us as f64 / 1_000_000.0)
+    }
+}
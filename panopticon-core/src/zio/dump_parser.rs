@@ -5,7 +5,7 @@ use crate::zio::model::{Fiber, FiberStatus};
 ///
 /// Expects a string where two first lines are of the following format:
 ///
-///   ```
+///   ```text
 ///   #4 (7h432m25965s25965835ms)
 ///   Status: Running()
 ///   ```
@@ -32,23 +32,30 @@ pub fn parse_fiber_dump(dump: String) -> Option<Fiber> {
         })
     });
 
-    let status_line = fib_str[1];
-
-    let status: Option<FiberStatus> =
-        if status_line.contains("Done") {
-            Some(FiberStatus::Done)
-        } else if status_line.contains("Finishing") {
-            Some(FiberStatus::Finishing)
-        } else if status_line.contains("Running") {
-            Some(FiberStatus::Running)
-        } else if status_line.contains("Suspended") {
-            Some(FiberStatus::Suspended)
-        } else {
-            None
-        };
+    let status = parse_status_line(fib_str[1]);
 
     match (id, status) {
         (Some(id), Some(status)) => Some(Fiber { id, parent_id, status, dump }),
         _ => None
     }
 }
+
+///
+/// Takes the status of a fiber dump alone, from its second line, for counting fibers without keeping their dumps.
+pub fn parse_fiber_status(dump: &str) -> Option<FiberStatus> {
+    dump.lines().nth(1).and_then(parse_status_line)
+}
+
+fn parse_status_line(status_line: &str) -> Option<FiberStatus> {
+    if status_line.contains("Done") {
+        Some(FiberStatus::Done)
+    } else if status_line.contains("Finishing") {
+        Some(FiberStatus::Finishing)
+    } else if status_line.contains("Running") {
+        Some(FiberStatus::Running)
+    } else if status_line.contains("Suspended") {
+        Some(FiberStatus::Suspended)
+    } else {
+        None
+    }
+}
@@ -0,0 +1,9 @@
+//! ZIO apps, through zio-zmx
+
+/// Fibers and their counts by status
+pub mod model;
+/// Dumps the fibers of a zio-zmx server
+pub mod zmx;
+/// Parses the fiber dumps zio-zmx answers with
+pub mod dump_parser;
+mod tests;
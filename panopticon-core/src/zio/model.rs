@@ -0,0 +1,79 @@
+use std::fmt::{Display, Formatter, Result};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+/// A fiber of a ZIO app, as dumped
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
+pub struct Fiber {
+    /// Id of the fiber
+    pub id: usize,
+    /// Id of the fiber that forked it, none for the main fiber
+    pub parent_id: Option<usize>,
+    /// Status at the time of the dump
+    pub status: FiberStatus,
+    /// The dump of the fiber, as printed
+    pub dump: String,
+}
+
+impl Display for Fiber {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{} {:?} {}", self.id, self.parent_id, self.status)
+    }
+}
+
+/// Status of a fiber
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Deserialize, Serialize)]
+pub enum FiberStatus {
+    /// Exited
+    Done,
+    /// Running its finalizers
+    Finishing,
+    /// Running
+    Running,
+    /// Waiting, e.g. for a resource or another fiber
+    Suspended,
+}
+
+impl Display for FiberStatus {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Fibers by status at a time
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Deserialize, Serialize)]
+pub struct FiberCount {
+    /// When the fibers were counted
+    pub timestamp: DateTime<Local>,
+    /// Fibers done
+    pub done: i32,
+    /// Fibers finishing
+    pub finishing: i32,
+    /// Fibers running
+    pub running: i32,
+    /// Fibers suspended
+    pub suspended: i32,
+}
+
+impl FiberCount {
+    /// No fibers, counted now
+    pub fn now() -> FiberCount {
+        FiberCount { timestamp: Local::now(), done: 0, finishing: 0, running: 0, suspended: 0 }
+    }
+
+    /// Counts `n` more fibers with the status
+    pub fn add(&mut self, status: &FiberStatus, n: i32) {
+        match status {
+            FiberStatus::Done => self.done += n,
+            FiberStatus::Finishing => self.finishing += n,
+            FiberStatus::Running => self.running += n,
+            FiberStatus::Suspended => self.suspended += n,
+        }
+    }
+
+    /// Fibers of all the statuses
+    pub fn total(&self) -> i32 {
+        self.done + self.finishing + self.running + self.suspended
+    }
+}
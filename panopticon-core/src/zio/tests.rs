@@ -84,3 +84,12 @@ fn dump_parser_not_enough_lines() {
     assert_eq!(parse_fiber_dump("#3 (1m96s96402ms)".to_owned()), None);
     assert_eq!(parse_fiber_dump("".to_owned()), None);
 }
+
+#[test]
+fn fibers_are_counted_from_status_lines() {
+    use crate::zio::dump_parser::parse_fiber_status;
+    use crate::zio::model::FiberStatus;
+
+    assert_eq!(parse_fiber_status("#2 (1m98s98260ms) waiting on #2\n    Status: Suspended(interruptible)\n    <something>"), Some(FiberStatus::Suspended));
+    assert_eq!(parse_fiber_status("#3 (1m96s96402ms)"), None);
+}
@@ -0,0 +1,132 @@
+use bytes::BytesMut;
+use redis_protocol::types::Frame;
+use tokio::io::AsyncWriteExt;
+use tokio::io::AsyncReadExt;
+use tokio::net::TcpStream;
+use crate::error::FetchError;
+use crate::zio::dump_parser;
+use crate::zio::model::{Fiber, FiberCount};
+
+/// A source of fiber dumps, shown in the fibers tab
+pub trait ZMXClient {
+    /// Where the dumps are taken from, as shown
+    fn address(&self) -> String;
+    /// All the fibers of the app
+    fn dump_fibers(&self) -> Result<Vec<Fiber>, FetchError>;
+    /// Fibers by status, for the count chart, taken every tick
+    fn count_fibers(&self) -> Result<FiberCount, FetchError> {
+        let mut count = FiberCount::now();
+        for f in self.dump_fibers()? {
+            count.add(&f.status, 1);
+        }
+        Ok(count)
+    }
+    /// What to check when dumps can't be taken
+    fn troubleshooting(&self) -> &'static str {
+        "Make sure zio-zmx is listening on specified port."
+    }
+}
+
+/// Takes fiber dumps from a zio-zmx server over its RESP protocol
+pub struct NetworkZMXClient {
+    address: String,
+}
+
+impl NetworkZMXClient {
+    /// A client of the zio-zmx server at `host:port`
+    pub fn new(address: String) -> NetworkZMXClient { NetworkZMXClient { address } }
+
+    /// Sends the command, returning the frame of the reply, if the server sent a whole one, and the bytes parsed
+    #[tokio::main]
+    async fn send(&self, command: &str) -> Result<(Option<Frame>, usize), FetchError> {
+        let frame = Frame::Array(vec![Frame::BulkString(command.into())]);
+        let mut buf = BytesMut::new();
+
+        let _ = match redis_protocol::prelude::encode_bytes(&mut buf, &frame) {
+            Ok(l) => l,
+            Err(e) => panic!("Error encoding frame: {:?}", e)
+        };
+
+        let mut stream = TcpStream::connect(&self.address).await?;
+
+        let _ = stream.write(&buf).await;
+
+        let mut buffer = String::new();
+        stream.read_to_string(&mut buffer).await?;
+
+        let buf: BytesMut = buffer.into();
+
+        match redis_protocol::prelude::decode_bytes(&buf) {
+            Ok((f, c)) => Ok((f, c)),
+            Err(e) => Err(FetchError::from(format!("Error parsing bytes: {:?}", e)))
+        }
+    }
+
+    /// Dumps of all the fibers, as strings
+    fn get_dumps(&self) -> Result<Vec<String>, FetchError> {
+        match self.send("dump")? {
+            (Some(Frame::Array(frames)), _) => frames.into_iter().map(|f| match f.as_str() {
+                Some(dump) => Ok(dump.to_owned()),
+                None => Err(FetchError::from(format!("Failed to parse dump - invalid frame: {:?}", f))),
+            }).collect(),
+            (_, consumed) => Err(FetchError::from(format!("Incomplete frame, parsed {} bytes", consumed))),
+        }
+    }
+
+    fn get_dump(&self) -> Result<Vec<Fiber>, FetchError> {
+        self.get_dumps()?.into_iter()
+            .map(|dump| dump_parser::parse_fiber_dump(dump.clone())
+                .ok_or_else(|| FetchError::from(format!("Unknown dump format, failed to parse: {}", dump))))
+            .collect()
+    }
+
+    ///
+    /// Counts fibers from a dump, of which only the status lines are read.
+    ///
+    /// zio-zmx has no command answering with counts alone, so counting costs a dump, taken every tick unless
+    /// counts are sampled less often by the caller.
+    fn get_counts(&self) -> Result<FiberCount, FetchError> {
+        let mut count = FiberCount::now();
+        for dump in self.get_dumps()? {
+            let status = dump_parser::parse_fiber_status(&dump)
+                .ok_or_else(|| format!("Unknown dump format, failed to parse: {}", dump))?;
+            count.add(&status, 1);
+        }
+        Ok(count)
+    }
+}
+
+impl ZMXClient for NetworkZMXClient {
+    fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    fn dump_fibers(&self) -> Result<Vec<Fiber>, FetchError> {
+        self.get_dump()
+    }
+
+    fn count_fibers(&self) -> Result<FiberCount, FetchError> {
+        self.get_counts()
+    }
+}
+
+/// Answers with the same dump every time, for tests
+pub struct StubZMXClient {
+    /// What `dump_fibers` returns
+    pub dump: Result<Vec<Fiber>, FetchError>
+}
+
+impl StubZMXClient {
+    /// A client answering with the dump
+    pub fn new(dump: Result<Vec<Fiber>, FetchError>) -> StubZMXClient { StubZMXClient { dump } }
+}
+
+impl ZMXClient for StubZMXClient {
+    fn address(&self) -> String {
+        "<stub>".to_owned()
+    }
+
+    fn dump_fibers(&self) -> Result<Vec<Fiber>, FetchError> {
+        self.dump.clone()
+    }
+}
@@ -0,0 +1,47 @@
+//! What other tools get from the crate, without the TUI
+
+use panopticon_core::error::{FetchError, FetchErrorKind};
+use panopticon_core::fetcher::{Fetcher, FetcherResponse, Sources};
+use panopticon_core::prometheus::client::parse_sample;
+use panopticon_core::statsd::model::StatsdLine;
+use panopticon_core::zio::model::{Fiber, FiberStatus};
+use panopticon_core::zio::zmx::StubZMXClient;
+use panopticon_core::{cats_effect, zio};
+
+fn fetcher() -> Fetcher {
+    Fetcher::new(Sources::default())
+}
+
+#[test]
+fn fibers_are_dumped_through_the_fetcher() {
+    let fiber = Fiber { id: 1, parent_id: None, status: FiberStatus::Running, dump: "#1".to_owned() };
+    assert_eq!(fetcher().dump_fibers(), Err(FetchError::from("zio-zmx is not configured")));
+
+    let dumped = fetcher().with_zmx_client(Box::new(StubZMXClient::new(Ok(vec![fiber.clone()])))).dump_fibers();
+    assert_eq!(dumped, Ok(vec![fiber]));
+
+    let refused = fetcher().with_zmx_client(Box::new(StubZMXClient::new(Err(FetchError::connection("Connection refused")))));
+    let error = refused.dump_fibers().unwrap_err();
+    assert!(error.message.contains("Connection refused"));
+    assert_eq!(error.kind, FetchErrorKind::Connection);
+}
+
+#[test]
+fn responses_tell_their_source() {
+    let response = FetcherResponse::FiberDump(Err(FetchError::connection("Connection refused")));
+    assert_eq!(response.outcome(), Some(("Fibers", Some(&FetchError::connection("Connection refused")))));
+}
+
+#[test]
+fn dumps_and_samples_are_parsed() {
+    let fiber = zio::dump_parser::parse_fiber_dump("#2 (1m98s98260ms)\nStatus: Suspended(interruptible)".to_owned()).unwrap();
+    assert_eq!((fiber.id, fiber.status), (2, FiberStatus::Suspended));
+
+    let fibers = cats_effect::dump_parser::parse_fiber_dump("cats.effect.IOFiber@56824a14 WAITING\n ╰ sleep @ Orders.poll\n");
+    assert_eq!(fibers.len(), 1);
+
+    let (name, labels, value) = parse_sample(r#"http_requests_total{method="GET"} 1027"#).unwrap();
+    assert_eq!((name.as_str(), labels["method"].as_str(), value), ("http_requests_total", "GET", 1027.0));
+
+    assert_eq!(StatsdLine::parse("api.requests:1|c").unwrap().name, "api.requests");
+}
@@ -0,0 +1,5 @@
+pub mod model;
+pub mod notify;
+pub mod rules;
+pub mod watch;
+pub mod webhook;
@@ -0,0 +1,211 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result};
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+///
+/// A threshold on a collected metric, e.g.
+///
+/// {"name": "Pool exhausted", "metric": "hikari/waiting", "comparison": ">", "threshold": 0, "duration": 30}
+///
+/// The alert fires once the latest value of any series of the metric has been beyond the threshold for `duration` seconds.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct AlertRule {
+    pub name: String,
+    /// Source and chart the values are read from, e.g. `fibers/suspended` or `prometheus/http_requests_total`
+    pub metric: String,
+    /// Only this series of the metric is checked, rather than all of them
+    pub series: Option<String>,
+    pub comparison: Comparison,
+    pub threshold: f64,
+    /// Seconds the threshold has to be breached for before the alert fires
+    #[serde(default)]
+    pub duration: u64,
+    /// Rings the terminal bell when the alert fires
+    #[serde(default)]
+    pub bell: bool,
+    /// Sends a desktop notification when the alert fires
+    #[serde(default)]
+    pub desktop: bool,
+    /// Slack-compatible incoming webhook the alert is posted to when it fires and resolves
+    pub webhook: Option<String>,
+}
+
+impl AlertRule {
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        check_metric(&self.metric).map_err(|e| format!("Metric of alert {}: {}", self.name, e))
+    }
+
+    pub fn is_breached(&self, value: f64) -> bool {
+        self.comparison.holds(value, self.threshold)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+pub enum Comparison {
+    #[serde(rename = ">")]
+    Above,
+    #[serde(rename = ">=")]
+    AtLeast,
+    #[serde(rename = "<")]
+    Below,
+    #[serde(rename = "<=")]
+    AtMost,
+    #[serde(rename = "==")]
+    Equal,
+}
+
+impl Comparison {
+    pub const ALL: [Comparison; 5] = [Comparison::AtLeast, Comparison::AtMost, Comparison::Equal, Comparison::Above, Comparison::Below];
+
+    pub fn holds(&self, left: f64, right: f64) -> bool {
+        match self {
+            Comparison::Above => left > right,
+            Comparison::AtLeast => left >= right,
+            Comparison::Below => left < right,
+            Comparison::AtMost => left <= right,
+            Comparison::Equal => left == right,
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        let symbol = match self {
+            Comparison::Above => ">",
+            Comparison::AtLeast => ">=",
+            Comparison::Below => "<",
+            Comparison::AtMost => "<=",
+            Comparison::Equal => "==",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+///
+/// Sources of the metrics named in alert rules, with the name they go by in the status bar, and their charts.
+///
+/// Sources charting configured or received metrics, e.g. Prometheus selectors or statsd metrics, have their charts
+/// named after them, so any chart of theirs is taken.
+const METRIC_SOURCES: [(&str, &str, Option<&[&str]>); 21] = [
+    ("fibers", "Fibers", Some(&["running", "suspended", "done", "finishing", "total"])),
+    ("slick", "JMX", Some(&["active_threads", "queue_size"])),
+    ("hikari", "JMX", Some(&["total", "active", "idle", "waiting"])),
+    ("actors", "Akka", Some(&["count"])),
+    ("threads", "JMX", Some(&["active", "queue_size"])),
+    ("jfr", "JFR", Some(&["allocation_rate"])),
+    ("redis", "Redis", Some(&["used_memory", "connected_clients", "blocked_clients", "ops_per_sec", "hit_rate"])),
+    ("docker", "Docker", Some(&["cpu", "memory_usage"])),
+    ("postgres", "PostgreSQL", Some(&["connections", "commits", "rollbacks"])),
+    ("k8s", "Kubernetes", Some(&["CPU (cores)", "Memory (MB)"])),
+    ("rabbitmq", "RabbitMQ", Some(&["Messages ready", "Unacknowledged messages", "Published (msg/s)", "Delivered (msg/s)"])),
+    ("proxy", "Proxy", Some(&["Requests", "Current sessions", "Servers up", "Connections"])),
+    ("prometheus", "Prometheus", None),
+    ("promql", "PromQL", None),
+    ("actuator", "Actuator", None),
+    ("json", "JSON", None),
+    ("commands", "Commands", None),
+    ("sql", "SQL", None),
+    ("statsd", "StatsD", None),
+    ("kafka_lag", "Kafka lag", None),
+    ("otlp", "OTLP", None),
+];
+
+/// Checks that the metric is `<source>/<chart>` of a known source, and of a chart it has if they're always the same
+pub fn check_metric(metric: &str) -> std::result::Result<(), String> {
+    let (source, chart) = metric.split_once('/')
+        .filter(|(_, chart)| !chart.is_empty())
+        .ok_or_else(|| format!("{} should be <source>/<chart>, e.g. fibers/suspended", metric))?;
+    match METRIC_SOURCES.iter().find(|(s, _, _)| *s == source) {
+        None => {
+            let sources: Vec<&str> = METRIC_SOURCES.iter().map(|(s, _, _)| *s).collect();
+            Err(format!("{} has no source {}, expected one of {}", metric, source, sources.join(", ")))
+        }
+        Some((_, _, Some(charts))) if !charts.contains(&chart) =>
+            Err(format!("{} has no chart {}, expected one of {}", source, chart, charts.join(", "))),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Name of the source of the metric in the status bar
+pub fn status_source(metric: &str) -> Option<&'static str> {
+    let source = metric.split('/').next()?;
+    METRIC_SOURCES.iter().find(|(s, _, _)| *s == source).map(|(_, name, _)| *name)
+}
+
+/// Latest value of a series, as checked against the rules
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MetricValue {
+    pub metric: String,
+    /// Empty for metrics with a single series
+    pub series: String,
+    pub value: f64,
+}
+
+impl MetricValue {
+    pub fn new(metric: &str, series: &str, value: f64) -> MetricValue {
+        MetricValue { metric: metric.to_owned(), series: series.to_owned(), value }
+    }
+}
+
+/// Value of a series, with the time it was sampled at
+pub type TimedValue = (DateTime<Local>, MetricValue);
+
+///
+/// Values of the series that weren't taken yet, for sinks to get every sample once, with the time it was sampled at.
+///
+/// Series of sources that didn't answer since, or while the display is frozen, have no new values, rather than
+/// the same one again.
+#[derive(Default)]
+pub struct NewSamples {
+    /// Time of the latest value taken, by metric and series
+    taken: HashMap<(String, String), DateTime<Local>>,
+}
+
+impl NewSamples {
+    pub fn take(&mut self, values: &[TimedValue]) -> Vec<TimedValue> {
+        let mut new = vec![];
+        for (timestamp, v) in values {
+            let key = (v.metric.clone(), v.series.clone());
+            if self.taken.get(&key).is_none_or(|t| t < timestamp) {
+                self.taken.insert(key, *timestamp);
+                new.push((*timestamp, v.clone()));
+            }
+        }
+        new
+    }
+}
+
+/// A rule starting or stopping to fire for a series
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AlertEvent {
+    pub timestamp: DateTime<Local>,
+    pub rule: String,
+    pub series: String,
+    pub value: f64,
+    pub firing: bool,
+    /// What was breached, e.g. `fibers/suspended = 120 > 100`
+    pub message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use crate::alerts::model::{MetricValue, NewSamples};
+
+    #[test]
+    fn samples_are_taken_once() {
+        let mut samples = NewSamples::default();
+        let start = Local::now();
+        let tick = |active: (i64, f64), idle: (i64, f64)| vec![
+            (start + Duration::seconds(active.0), MetricValue::new("hikari/active", "", active.1)),
+            (start + Duration::seconds(idle.0), MetricValue::new("hikari/idle", "", idle.1)),
+        ];
+        assert_eq!(samples.take(&tick((0, 1.0), (0, 5.0))).len(), 2);
+        // the source of hikari/idle didn't answer since
+        assert_eq!(samples.take(&tick((2, 3.0), (0, 5.0))), vec![(start + Duration::seconds(2), MetricValue::new("hikari/active", "", 3.0))]);
+        assert!(samples.take(&tick((2, 3.0), (0, 5.0))).is_empty());
+    }
+}
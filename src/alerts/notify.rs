@@ -0,0 +1,33 @@
+use std::io::Write;
+
+use notify_rust::Notification;
+#[cfg(all(unix, not(target_os = "macos")))]
+use notify_rust::Urgency;
+
+/// Rings the terminal bell, which terminals can turn into a sound, an urgency hint or a flashing tab
+pub fn ring_bell(terminal: &mut impl Write) -> Result<(), String> {
+    terminal.write_all(b"\x07").and_then(|_| terminal.flush()).map_err(|e| e.to_string())
+}
+
+/// Shows a desktop notification, through D-Bus on Linux and BSDs, the Notification Center on macOS and toasts on Windows
+pub fn send_desktop_notification(title: &str, body: &str) -> Result<(), String> {
+    let mut notification = Notification::new();
+    notification.appname("panopticon-tui").summary(title).body(body);
+    #[cfg(all(unix, not(target_os = "macos")))]
+    notification.urgency(Urgency::Critical);
+    notification.show()
+        .map(|_| ())
+        .map_err(|e| format!("Couldn't send a desktop notification: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alerts::notify::ring_bell;
+
+    #[test]
+    fn the_bell_is_rung_on_the_given_terminal() {
+        let mut terminal = vec![];
+        ring_bell(&mut terminal).unwrap();
+        assert_eq!(terminal, b"\x07");
+    }
+}
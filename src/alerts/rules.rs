@@ -0,0 +1,143 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::{DateTime, Local};
+
+use crate::alerts::model::{status_source, AlertEvent, AlertRule, MetricValue};
+
+///
+/// Rules from the config file, evaluated every tick against the latest values of the collected series.
+///
+/// A series breaching a rule is pending until it did for the rule's duration, and then fires until it's back within the threshold.
+pub struct AlertRules {
+    pub rules: Vec<AlertRule>,
+    /// Since when each series breaches each rule, by the index of the rule and the series
+    breaches: HashMap<(usize, String), DateTime<Local>>,
+    /// Alerts currently firing, by the index of the rule and the series
+    pub firing: Vec<(usize, AlertEvent)>,
+    /// Alerts that fired and resolved, the latest last
+    pub history: VecDeque<AlertEvent>,
+}
+
+impl AlertRules {
+    pub const MAX_HISTORY: usize = 200;
+
+    pub fn new(rules: Vec<AlertRule>) -> AlertRules {
+        AlertRules { rules, breaches: HashMap::new(), firing: vec![], history: VecDeque::new() }
+    }
+
+    ///
+    /// Checks every rule against the values, returning the alerts that started or stopped firing.
+    ///
+    /// Rules on metrics of the failing sources, named like in the status bar, are left as they are until the sources
+    /// are read again, rather than checked against their last values.
+    pub fn evaluate(&mut self, values: &[MetricValue], failing: &[&str], now: DateTime<Local>) -> Vec<AlertEvent> {
+        let mut events = vec![];
+        for (i, rule) in self.rules.iter().enumerate() {
+            if status_source(&rule.metric).is_some_and(|s| failing.contains(&s)) {
+                continue;
+            }
+            let checked = values.iter()
+                .filter(|v| v.metric == rule.metric && rule.series.as_ref().is_none_or(|s| *s == v.series));
+            let mut breaching = vec![];
+            for v in checked.filter(|v| rule.is_breached(v.value)) {
+                let key = (i, v.series.clone());
+                let since = *self.breaches.entry(key.clone()).or_insert(now);
+                let firing = self.firing.iter().any(|(r, e)| *r == i && e.series == v.series);
+                if !firing && (now - since).num_seconds() >= rule.duration as i64 {
+                    let event = AlertEvent {
+                        timestamp: now,
+                        rule: rule.name.to_owned(),
+                        series: v.series.clone(),
+                        value: v.value,
+                        firing: true,
+                        message: describe(rule, v),
+                    };
+                    self.firing.push((i, event.clone()));
+                    events.push(event);
+                }
+                breaching.push(key);
+            }
+            // series that are back within the threshold, or gone
+            self.breaches.retain(|k, _| k.0 != i || breaching.contains(k));
+            let (resolved, firing): (Vec<_>, Vec<_>) = self.firing.drain(..)
+                .partition(|(r, e)| *r == i && !breaching.contains(&(i, e.series.clone())));
+            self.firing = firing;
+            for (_, fired) in resolved {
+                events.push(AlertEvent { timestamp: now, firing: false, ..fired });
+            }
+        }
+        for e in &events {
+            if self.history.len() == AlertRules::MAX_HISTORY {
+                self.history.pop_front();
+            }
+            self.history.push_back(e.clone());
+        }
+        events
+    }
+}
+
+fn describe(rule: &AlertRule, value: &MetricValue) -> String {
+    let metric = if value.series.is_empty() { rule.metric.to_owned() } else { format!("{}{{{}}}", rule.metric, value.series) };
+    format!("{} = {} {} {}", metric, value.value, rule.comparison, rule.threshold)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use crate::alerts::model::{AlertRule, Comparison, MetricValue};
+    use crate::alerts::rules::AlertRules;
+
+    #[test]
+    fn alerts_fire_after_their_duration_and_resolve() {
+        let rule: AlertRule = serde_json::from_str(r#"{"name": "Stuck fibers", "metric": "fibers/suspended", "comparison": ">", "threshold": 100, "duration": 10}"#).unwrap();
+        assert_eq!(rule.comparison, Comparison::Above);
+        let mut rules = AlertRules::new(vec![rule]);
+        let start = Local::now();
+        let suspended = |value: f64| vec![MetricValue::new("fibers/suspended", "", value), MetricValue::new("fibers/running", "", 500.0)];
+
+        assert!(rules.evaluate(&suspended(120.0), &[], start).is_empty());
+        assert!(rules.evaluate(&suspended(130.0), &[], start + Duration::seconds(5)).is_empty());
+        let fired = rules.evaluate(&suspended(140.0), &[], start + Duration::seconds(10));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].message, "fibers/suspended = 140 > 100");
+        assert!(rules.evaluate(&suspended(150.0), &[], start + Duration::seconds(12)).is_empty());
+        assert_eq!(rules.firing.len(), 1);
+
+        let resolved = rules.evaluate(&suspended(80.0), &[], start + Duration::seconds(14));
+        assert_eq!((resolved.len(), resolved[0].firing), (1, false));
+        assert!(rules.firing.is_empty());
+        assert_eq!(rules.history.len(), 2);
+
+        // the breach has to last again
+        assert!(rules.evaluate(&suspended(120.0), &[], start + Duration::seconds(15)).is_empty());
+    }
+
+    #[test]
+    fn rules_on_failing_sources_are_left_as_they_are() {
+        let rule: AlertRule = serde_json::from_str(r#"{"name": "Pool exhausted", "metric": "hikari/waiting", "comparison": ">", "threshold": 0}"#).unwrap();
+        assert_eq!(rule.validate(), Ok(()));
+        let mut rules = AlertRules::new(vec![rule]);
+        let now = Local::now();
+        let waiting = |value: f64| vec![MetricValue::new("hikari/waiting", "", value)];
+
+        assert!(rules.evaluate(&waiting(3.0), &["JMX"], now).is_empty());
+        assert_eq!(rules.evaluate(&waiting(3.0), &["Fibers"], now).len(), 1);
+        // the last value read before JMX failed doesn't resolve the alert, nor does its absence
+        assert!(rules.evaluate(&waiting(0.0), &["JMX"], now).is_empty());
+        assert!(rules.evaluate(&[], &["JMX"], now).is_empty());
+        assert_eq!(rules.firing.len(), 1);
+    }
+
+    #[test]
+    fn metrics_of_rules_are_checked() {
+        let rule = |metric: &str| serde_json::from_value::<AlertRule>(serde_json::json!(
+            {"name": "Rule", "metric": metric, "comparison": ">", "threshold": 0})).unwrap().validate();
+        assert_eq!(rule("fibers/suspended"), Ok(()));
+        assert_eq!(rule("json/Job queue"), Ok(()));
+        assert_eq!(rule("hikari/max"), Err("Metric of alert Rule: hikari has no chart max, expected one of total, active, idle, waiting".to_owned()));
+        assert!(rule("fiber/suspended").unwrap_err().contains("has no source fiber"));
+        assert!(rule("fibers").is_err());
+        assert!(rule("prometheus/").is_err());
+    }
+}
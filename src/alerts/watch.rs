@@ -0,0 +1,205 @@
+use std::fmt::{Display, Formatter, Result};
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+
+use crate::alerts::model::{Comparison, MetricValue};
+
+/// Side of a watch expression
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operand {
+    Number(f64),
+    /// Named like in alert rules, e.g. `hikari/active`, all the series of the metric unless one is picked
+    Metric { metric: String, series: Option<String> },
+}
+
+impl Operand {
+    fn parse(s: &str) -> std::result::Result<Operand, String> {
+        let s = s.trim();
+        if let Ok(n) = s.parse::<f64>() {
+            return Ok(Operand::Number(n));
+        }
+        let (metric, series) = match (s.find('{'), s.ends_with('}')) {
+            (Some(i), true) => (&s[..i], Some(s[i + 1..s.len() - 1].to_owned())),
+            _ => (s, None),
+        };
+        // hikari.active is hikari/active, while dots in chart titles, like json/Job.queue, are kept
+        let metric = if metric.contains('/') { metric.to_owned() } else { metric.replacen('.', "/", 1) };
+        if metric.split('/').count() < 2 || metric.contains(char::is_whitespace) {
+            return Err(format!("Expected a number or a metric like hikari/active or prometheus/up{{app}}, got {}", s));
+        }
+        let metric = match Operand::ALIASES.iter().find(|(alias, _)| *alias == metric) {
+            Some((_, name)) => name.to_string(),
+            None => metric,
+        };
+        Ok(Operand::Metric { metric, series })
+    }
+
+    /// Other names of metrics, e.g. `hikari/max` for all the connections of a HikariCP pool
+    const ALIASES: [(&'static str, &'static str); 1] = [("hikari/max", "hikari/total")];
+
+    /// Values of the series of the operand, with their names
+    fn values(&self, values: &[MetricValue]) -> Vec<(String, f64)> {
+        match self {
+            Operand::Number(n) => vec![(n.to_string(), *n)],
+            Operand::Metric { metric, series } => values.iter()
+                .filter(|v| v.metric == *metric && series.as_ref().is_none_or(|s| *s == v.series))
+                .map(|v| {
+                    let name = if v.series.is_empty() { v.metric.to_owned() } else { format!("{}{{{}}}", v.metric, v.series) };
+                    (format!("{} = {}", name, v.value), v.value)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        match self {
+            Operand::Number(n) => write!(f, "{}", n),
+            Operand::Metric { metric, series: Some(s) } => write!(f, "{}{{{}}}", metric, s),
+            Operand::Metric { metric, series: None } => write!(f, "{}", metric),
+        }
+    }
+}
+
+///
+/// Comparison of a metric with another one or with a number, e.g. `hikari/active >= hikari/total`.
+///
+/// It holds when it does for any of the series of the metrics.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatchExpr {
+    pub left: Operand,
+    pub comparison: Comparison,
+    pub right: Operand,
+}
+
+impl WatchExpr {
+    pub fn parse(s: &str) -> std::result::Result<WatchExpr, String> {
+        // series names may contain comparison symbols, e.g. prometheus/up{{job="a>b"}}
+        let mut depth = 0;
+        let mut quoted = false;
+        for (i, c) in s.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                '{' if !quoted => depth += 1,
+                '}' if !quoted => depth -= 1,
+                _ if depth == 0 && !quoted => {
+                    if let Some(comparison) = Comparison::ALL.iter().find(|c| s[i..].starts_with(&c.to_string())) {
+                        let symbol_len = comparison.to_string().len();
+                        return Ok(WatchExpr {
+                            left: Operand::parse(&s[..i])?,
+                            comparison: *comparison,
+                            right: Operand::parse(&s[i + symbol_len..])?,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        Err(format!("Expected a comparison like hikari/active >= hikari/total, got {}", s))
+    }
+
+    /// What the expression holds for, e.g. `hikari/active = 10 >= hikari/total = 10`
+    pub fn holds(&self, values: &[MetricValue]) -> Option<String> {
+        let right = self.right.values(values);
+        self.left.values(values).into_iter()
+            .flat_map(|l| right.iter().map(move |r| (l.clone(), r.clone())))
+            .find(|(l, r)| self.comparison.holds(l.1, r.1))
+            .map(|(l, r)| format!("{} {} {}", l.0, self.comparison, r.0))
+    }
+}
+
+impl Display for WatchExpr {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{} {} {}", self.left, self.comparison, self.right)
+    }
+}
+
+/// An expression checked every tick, until it held for long enough
+pub struct Watch {
+    pub expr: WatchExpr,
+    pub duration: Duration,
+    /// Since when the expression holds
+    since: Option<DateTime<Local>>,
+}
+
+impl Watch {
+    pub fn new(expr: WatchExpr, duration: Duration) -> Watch {
+        Watch { expr, duration, since: None }
+    }
+
+    /// What the expression holds for, once it did for the duration of the watch
+    pub fn check(&mut self, values: &[MetricValue], now: DateTime<Local>) -> Option<String> {
+        match self.expr.holds(values) {
+            Some(held) => {
+                let since = *self.since.get_or_insert(now);
+                (now - since).to_std().ok().filter(|d| *d >= self.duration).map(|_| held)
+            }
+            None => {
+                self.since = None;
+                None
+            }
+        }
+    }
+}
+
+/// Parses durations like `90s`, `5m` or `1h`, seconds if there's no unit
+pub fn parse_duration(s: &str) -> std::result::Result<Duration, String> {
+    let invalid = || format!("Expected a duration like 60s, 5m or 1h, got {}", s);
+    let (n, unit) = match s.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(i) => (&s[..i], &s[i..]),
+        None => (s, "s"),
+    };
+    let n: u64 = n.parse().map_err(|_| invalid())?;
+    match unit {
+        "s" => Ok(Duration::from_secs(n)),
+        "m" => Ok(Duration::from_secs(n * 60)),
+        "h" => Ok(Duration::from_secs(n * 3600)),
+        _ => Err(invalid()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::Local;
+
+    use crate::alerts::model::{Comparison, MetricValue};
+    use crate::alerts::watch::{parse_duration, Operand, Watch, WatchExpr};
+
+    #[test]
+    fn expressions_are_parsed() {
+        let expr = WatchExpr::parse("hikari/active >= hikari/total").unwrap();
+        assert_eq!(expr.comparison, Comparison::AtLeast);
+        assert_eq!(expr.right, Operand::Metric { metric: "hikari/total".to_owned(), series: None });
+        assert_eq!(expr.to_string(), "hikari/active >= hikari/total");
+
+        let expr = WatchExpr::parse(r#"prometheus/up{{job="a>b"}}<1"#).unwrap();
+        assert_eq!(expr.left, Operand::Metric { metric: "prometheus/up".to_owned(), series: Some(r#"{job="a>b"}"#.to_owned()) });
+        assert_eq!((expr.comparison, expr.right), (Comparison::Below, Operand::Number(1.0)));
+
+        let expr = WatchExpr::parse("hikari.active >= hikari.max").unwrap();
+        assert_eq!(expr.to_string(), "hikari/active >= hikari/total");
+        assert_eq!(WatchExpr::parse("json/Job.queue > 5").unwrap().left, Operand::Metric { metric: "json/Job.queue".to_owned(), series: None });
+
+        assert!(WatchExpr::parse("hikari/active").is_err());
+        assert!(WatchExpr::parse("hikari > 1").is_err());
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert!(parse_duration("5 days").is_err());
+    }
+
+    #[test]
+    fn watches_end_once_the_expression_held_long_enough() {
+        let mut watch = Watch::new(WatchExpr::parse("hikari/active >= hikari/total").unwrap(), Duration::from_secs(60));
+        let values = |active| vec![MetricValue::new("hikari/active", "", active), MetricValue::new("hikari/total", "", 10.0)];
+        let start = Local::now();
+        assert_eq!(watch.check(&values(10.0), start), None);
+        assert_eq!(watch.check(&values(4.0), start + chrono::Duration::seconds(30)), None);
+        assert_eq!(watch.check(&values(10.0), start + chrono::Duration::seconds(40)), None);
+        assert_eq!(watch.check(&values(10.0), start + chrono::Duration::seconds(100)), Some("hikari/active = 10 >= hikari/total = 10".to_owned()));
+        assert_eq!(watch.check(&[], start + chrono::Duration::seconds(110)), None);
+    }
+}
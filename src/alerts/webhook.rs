@@ -0,0 +1,60 @@
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::alerts::model::AlertEvent;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Posts an alert starting or stopping to fire to a Slack-compatible incoming webhook
+pub fn post(url: &str, event: &AlertEvent) -> Result<(), String> {
+    post_async(url, &payload(event))
+}
+
+#[tokio::main]
+async fn post_async(url: &str, payload: &Value) -> Result<(), String> {
+    let response = reqwest::Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .map_err(|e| e.to_string())?
+        .post(url)
+        .json(payload)
+        .send().await.map_err(|e| format!("Couldn't post alert to webhook: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Posting alert to webhook failed with status {}", response.status()));
+    }
+    Ok(())
+}
+
+/// A message with Slack's markup, which Mattermost, Rocket.Chat and Discord's Slack endpoint take too
+fn payload(event: &AlertEvent) -> Value {
+    let text = if event.firing {
+        format!(":rotating_light: *{}* firing since {}: `{}`", event.rule, event.timestamp.format("%H:%M:%S"), event.message)
+    } else {
+        format!(":white_check_mark: *{}* resolved at {}: `{}`", event.rule, event.timestamp.format("%H:%M:%S"), event.message)
+    };
+    json!({ "username": "panopticon-tui", "text": text })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Local, TimeZone};
+
+    use crate::alerts::model::AlertEvent;
+    use crate::alerts::webhook::payload;
+
+    #[test]
+    fn alerts_are_posted_as_slack_messages() {
+        let mut event = AlertEvent {
+            timestamp: Local.ymd(2020, 6, 1).and_hms(12, 30, 5),
+            rule: "Pool exhausted".to_owned(),
+            series: String::new(),
+            value: 3.0,
+            firing: true,
+            message: "hikari/waiting = 3 > 0".to_owned(),
+        };
+        assert_eq!(payload(&event)["text"], ":rotating_light: *Pool exhausted* firing since 12:30:05: `hikari/waiting = 3 > 0`");
+        event.firing = false;
+        assert_eq!(payload(&event)["text"], ":white_check_mark: *Pool exhausted* resolved at 12:30:05: `hikari/waiting = 3 > 0`");
+    }
+}
@@ -0,0 +1,204 @@
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use chrono::Local;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use panopticon_core::http_server::{read_request, write_response};
+
+use crate::app::App;
+use crate::grafana::{QueryBody, SearchBody};
+
+/// Calls not answered by then, e.g. while the event loop is stuck, fail rather than hang the client
+const ANSWER_TIMEOUT: Duration = Duration::from_secs(5);
+
+const ENDPOINTS: &str = "GET /api/metrics, GET /api/fibers, GET /api/fibers/<id>, GET /api/actors, GET /api/alerts, POST /api/markers, \
+    and GET /grafana, POST /grafana/search, POST /grafana/query for the Grafana JSON datasource";
+
+/// What a call to the API asks for
+#[derive(Clone, Debug, PartialEq)]
+pub enum ApiRequest {
+    Metrics,
+    Fibers,
+    Fiber(usize),
+    Actors,
+    Alerts,
+    /// Drops a marker with the label on all the charts, like `<a>` does
+    Marker(String),
+    /// Grafana testing the datasource, answered by the event loop like other calls, so that a stuck session fails the test
+    GrafanaTest,
+    GrafanaSearch(SearchBody),
+    GrafanaQuery(QueryBody),
+}
+
+#[derive(Deserialize)]
+struct MarkerBody {
+    label: String,
+}
+
+/// A call of a client, answered by the event loop, which holds the app
+pub struct ApiCall {
+    pub request: ApiRequest,
+    reply: mpsc::Sender<(&'static str, Value)>,
+}
+
+impl ApiCall {
+    pub fn answer(self, app: &mut App) {
+        // the client may have given up already
+        let _ = self.reply.send(respond(&self.request, app));
+    }
+}
+
+///
+/// Serves the state of the session as a JSON REST API: the latest values of all the collected series, the fibers,
+/// the actor tree and the alerts, and takes chart markers.
+///
+/// Calls are passed to `send`, to be answered with `ApiCall::answer` where the app is.
+pub fn serve(address: &str, send: impl Fn(ApiCall) -> Result<(), String> + Clone + Send + 'static) -> Result<(), String> {
+    let listener = TcpListener::bind(address)
+        .map_err(|e| format!("Couldn't serve the API on {}: {}", address, e))?;
+    thread::spawn(move || {
+        for stream in listener.incoming().filter_map(|s| s.ok()) {
+            let send = send.clone();
+            thread::spawn(move || serve_connection(stream, send));
+        }
+    });
+    Ok(())
+}
+
+/// Serves the calls of a connection until it's closed
+fn serve_connection(stream: TcpStream, send: impl Fn(ApiCall) -> Result<(), String>) {
+    let mut writer = match stream.try_clone() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    let mut reader = BufReader::new(stream);
+    while let Ok(Some(request)) = read_request(&mut reader) {
+        let (status, body) = match route(&request.method, &request.path, &request.body) {
+            Ok(request) => {
+                let (reply, answer) = mpsc::channel();
+                send(ApiCall { request, reply })
+                    .map_err(|e| ("503 Service Unavailable", json!({"error": e})))
+                    .and_then(|_| answer.recv_timeout(ANSWER_TIMEOUT)
+                        .map_err(|_| ("503 Service Unavailable", json!({"error": "The session didn't answer in time"}))))
+                    .unwrap_or_else(|e| e)
+            }
+            Err(e) => e,
+        };
+        if write_response(&mut writer, status, "application/json", &format!("{}\n", body)).is_err() {
+            return;
+        }
+    }
+}
+
+fn route(method: &str, path: &str, body: &[u8]) -> Result<ApiRequest, (&'static str, Value)> {
+    let path = path.split('?').next().unwrap_or("").trim_end_matches('/');
+    match (method, path) {
+        ("GET", "/api/metrics") => Ok(ApiRequest::Metrics),
+        ("GET", "/api/fibers") => Ok(ApiRequest::Fibers),
+        ("GET", "/api/actors") => Ok(ApiRequest::Actors),
+        ("GET", "/api/alerts") => Ok(ApiRequest::Alerts),
+        ("GET", p) if p.starts_with("/api/fibers/") => p["/api/fibers/".len()..].parse()
+            .map(ApiRequest::Fiber)
+            .map_err(|_| ("400 Bad Request", json!({"error": format!("Expected a fiber id, got {}", &p["/api/fibers/".len()..])}))),
+        ("POST", "/api/markers") => serde_json::from_slice::<MarkerBody>(body)
+            .ok()
+            .filter(|b| !b.label.trim().is_empty())
+            .map(|b| ApiRequest::Marker(b.label.trim().to_owned()))
+            .ok_or(("400 Bad Request", json!({"error": r#"Expected a label like {"label": "deployed v2.3.1"}"#}))),
+        ("GET", "/grafana") => Ok(ApiRequest::GrafanaTest),
+        // Grafana sends no body when nothing was typed yet
+        ("POST", "/grafana/search") if body.is_empty() => Ok(ApiRequest::GrafanaSearch(SearchBody::default())),
+        ("POST", "/grafana/search") => serde_json::from_slice(body)
+            .map(ApiRequest::GrafanaSearch)
+            .map_err(|e| ("400 Bad Request", json!({"error": format!("Expected a search like {{\"target\": \"hikari\"}}: {}", e)}))),
+        ("POST", "/grafana/query") => serde_json::from_slice(body)
+            .map(ApiRequest::GrafanaQuery)
+            .map_err(|e| ("400 Bad Request", json!({"error": format!("Expected a query of the Grafana JSON datasource: {}", e)}))),
+        _ => Err(("404 Not Found", json!({"error": format!("The API serves {}", ENDPOINTS)}))),
+    }
+}
+
+/// Answer to a call, with its status
+pub fn respond(request: &ApiRequest, app: &mut App) -> (&'static str, Value) {
+    let not_shown = |what: &str| ("404 Not Found", json!({"error": format!("The session doesn't show {}", what)}));
+    match request {
+        ApiRequest::Metrics => ("200 OK", json!({"timestamp": Local::now().to_rfc3339(), "metrics": app.metric_values()})),
+        ApiRequest::Fibers => match &app.zmx {
+            Some(zmx) => {
+                let fibers: Vec<Value> = zmx.fiber_ids.iter().zip(zmx.fibers.items.iter())
+                    .map(|(id, label)| json!({"id": id, "label": label.trim_end()}))
+                    .collect();
+                ("200 OK", json!({"fibers": fibers}))
+            }
+            None => not_shown("fibers"),
+        },
+        ApiRequest::Fiber(id) => match app.zmx.as_ref().and_then(|z| z.fiber_ids.iter().position(|i| i == id).map(|i| (z, i))) {
+            Some((zmx, i)) => ("200 OK", json!({"id": id, "label": zmx.fibers.items[i].trim_end(), "dump": &*zmx.fiber_dump_all[i]})),
+            None => ("404 Not Found", json!({"error": format!("No fiber {}", id)})),
+        },
+        ApiRequest::Actors => match &app.actor_tree {
+            Some(tree) => ("200 OK", json!({"actors": tree.actor_nodes, "paths": tree.actor_paths})),
+            None => not_shown("an actor tree"),
+        },
+        ApiRequest::Alerts => {
+            let tabs: Vec<Value> = app.alerts().into_iter().map(|(tab, message)| json!({"tab": tab, "message": message})).collect();
+            let (firing, history) = match &app.alert_rules {
+                Some(rules) => (rules.firing.iter().map(|(_, e)| e).collect(), rules.history.iter().collect()),
+                None => (vec![], vec![]),
+            };
+            ("200 OK", json!({"tabs": tabs, "firing": firing, "history": history}))
+        }
+        ApiRequest::Marker(label) => {
+            let now = Local::now();
+            app.add_marker(now, label);
+            ("201 Created", json!({"timestamp": now.to_rfc3339(), "label": label}))
+        }
+        ApiRequest::GrafanaTest => ("200 OK", json!({"status": "ok"})),
+        ApiRequest::GrafanaSearch(body) => match &app.grafana_series {
+            Some(series) => ("200 OK", series.search(body)),
+            None => not_shown("series for Grafana"),
+        },
+        ApiRequest::GrafanaQuery(body) => match &app.grafana_series {
+            Some(series) => ("200 OK", series.query(body)),
+            None => not_shown("series for Grafana"),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use panopticon_core::fetcher::Sources;
+
+    use crate::api::{respond, route, ApiRequest};
+    use crate::app::App;
+    use crate::config::Config;
+
+    #[test]
+    fn calls_are_routed() {
+        assert_eq!(route("GET", "/api/metrics?pretty", b""), Ok(ApiRequest::Metrics));
+        assert_eq!(route("GET", "/api/fibers/42", b""), Ok(ApiRequest::Fiber(42)));
+        assert_eq!(route("POST", "/api/markers", br#"{"label": " deployed v2.3.1 "}"#), Ok(ApiRequest::Marker("deployed v2.3.1".to_owned())));
+        assert_eq!(route("POST", "/api/markers", b"deployed").unwrap_err().0, "400 Bad Request");
+        assert_eq!(route("GET", "/api/fibers/main", b"").unwrap_err().0, "400 Bad Request");
+        assert_eq!(route("DELETE", "/api/metrics", b"").unwrap_err().0, "404 Not Found");
+        assert_eq!(route("GET", "/grafana/", b""), Ok(ApiRequest::GrafanaTest));
+        assert_eq!(route("POST", "/grafana/search", b""), Ok(ApiRequest::GrafanaSearch(Default::default())));
+        assert_eq!(route("POST", "/grafana/query", br#"{"targets": []}"#).unwrap_err().0, "400 Bad Request");
+    }
+
+    #[test]
+    fn markers_are_dropped_on_the_charts() {
+        let config = Config::default();
+        let mut app = App::new("test", &Sources::default(), &config);
+        let (status, body) = respond(&ApiRequest::Marker("deployed v2.3.1".to_owned()), &mut app);
+        assert_eq!((status, &body["label"]), ("201 Created", &serde_json::json!("deployed v2.3.1")));
+        assert_eq!(app.viewport.markers[0].label, "deployed v2.3.1");
+        assert_eq!(respond(&ApiRequest::Fibers, &mut app).0, "404 Not Found");
+        assert_eq!(respond(&ApiRequest::Alerts, &mut app).1["tabs"], serde_json::json!([]));
+    }
+}
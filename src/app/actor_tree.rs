@@ -0,0 +1,690 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::time::Instant;
+
+use chrono::{DateTime, Local};
+
+use panopticon_core::akka::model::{ActorCountAlertSettings, ancestor_paths, ActorStats, ActorTree, ActorTreeNode, AkkaSettings, DeadLetter, DeadLetterMetrics, is_excluded_actor, KamonMetrics, matches_actor_pattern};
+use panopticon_core::fetcher::FetcherRequest;
+use panopticon_core::jmx::model::AkkaDispatcherMetrics;
+
+use crate::app::{SourceTab, StatefulList};
+use crate::export;
+use crate::widgets::tree;
+use crate::widgets::viewport;
+
+/// Difference of an actor from the previous tree fetch
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ActorChange {
+    Appeared,
+    Disappeared,
+}
+
+/// Actors matching a watched path pattern
+pub struct ActorWatch {
+    pub pattern: String,
+    /// Number of matching actors in the current tree
+    pub matches: usize,
+    /// Whether any actor matched on each of the last tree reloads, oldest first
+    pub presence: VecDeque<bool>,
+}
+
+/// Running actors reported on a fetch
+pub struct ActorCountSample {
+    pub timestamp: DateTime<Local>,
+    pub total: u64,
+    /// How many actors were excluded from the tree at the time
+    pub excluded: u64,
+}
+
+pub struct AkkaActorTreeTab {
+    pub actors: StatefulList<String>,
+    /// Path of the actor shown on each line of `actors`
+    pub actor_paths: Vec<String>,
+    /// Width of the longest line of `actors`, to align the mailbox sizes next to them
+    pub actor_label_width: usize,
+    /// Actors of the last fetched tree, including the ones hidden in collapsed subtrees
+    pub actor_nodes: Vec<ActorTreeNode>,
+    /// Paths of the collapsed actors, kept across refreshes
+    pub collapsed: HashSet<String>,
+    /// Actors that appeared or disappeared on the last fetches by path, with the number of ticks left to highlight them
+    pub actor_changes: HashMap<String, (ActorChange, u32)>,
+    pub actor_counts: VecDeque<ActorCountSample>,
+    pub count_alert_settings: ActorCountAlertSettings,
+    /// Number of consecutive fetches the actor count grew on
+    pub growth_ticks: u32,
+    pub count_alert: Option<String>,
+    /// Path patterns of infrastructure actors left out of the tree and actor counts
+    pub excluded_actors: Vec<String>,
+    pub show_excluded: bool,
+    pub watches: Vec<ActorWatch>,
+    /// Actors of the last fetched tree matching `excluded_actors`
+    pub excluded_count: u64,
+    /// Size of the subtree of every top-level actor, e.g. `user/workers`, with its change since the previous fetch
+    pub subtree_counts: Vec<(String, u64, i64)>,
+    pub has_dispatchers: bool,
+    pub dispatchers: VecDeque<AkkaDispatcherMetrics>,
+    pub has_dead_letters: bool,
+    /// Dead letters since the previous sample
+    pub dead_letter_counts: VecDeque<(DateTime<Local>, u64)>,
+    /// Total reported by the last sample
+    pub dead_letter_total: Option<u64>,
+    pub dead_letters: VecDeque<DeadLetter>,
+    pub dead_letters_scroll: u16,
+    pub has_mailboxes: bool,
+    /// Mailbox sizes and message counts by actor path
+    pub actor_stats: HashMap<String, ActorStats>,
+    /// Whether mailbox sizes and dead letters are scraped from Kamon rather than read from their own endpoints
+    pub has_kamon: bool,
+    /// Totals of the previous Kamon scrape, to average histograms over the last tick
+    pub kamon_totals: Option<KamonMetrics>,
+    /// When actors of the current tree were first fetched, by path
+    pub first_seen: HashMap<String, DateTime<Local>>,
+    /// Numbers of children and descendants of actors of the current tree with any, by path
+    pub descendant_counts: HashMap<String, (usize, usize)>,
+    /// Id of the tree fetch in flight, with when it was requested
+    pub tree_fetch: Option<(u64, Instant)>,
+    /// Number of levels fetched at once, subtrees are fetched on demand when set
+    pub tree_depth: Option<u32>,
+    /// Paths of actors whose children weren't fetched, with the number of their descendants if known
+    pub unloaded_actors: HashMap<String, Option<u64>>,
+    /// Paths of actors whose subtrees are being fetched
+    pub subtree_fetches: HashSet<String>,
+    /// Paths of actors whose subtrees were fetched on demand, fetched again when a reload leaves them out
+    pub expanded: HashSet<String>,
+    last_tree_fetch_id: u64,
+    pub status_message: Option<String>,
+}
+
+/// Details of the actor selected in the tree
+pub struct ActorDetails<'a> {
+    pub path: &'a str,
+    pub parent: Option<&'a str>,
+    pub children: usize,
+    pub descendants: usize,
+    /// `None` if the actor is gone
+    pub first_seen: Option<DateTime<Local>>,
+    pub stats: Option<&'a ActorStats>,
+}
+
+impl AkkaActorTreeTab {
+    pub const MAX_ACTOR_COUNT_MEASURES: usize = viewport::HISTORY_SAMPLES;
+    pub const MAX_DISPATCHER_MEASURES: usize = viewport::HISTORY_SAMPLES;
+    pub const ACTOR_CHANGE_TICKS: u32 = 5;
+    pub const MAX_DEAD_LETTER_MEASURES: usize = viewport::HISTORY_SAMPLES;
+    pub const MAX_DEAD_LETTERS: usize = 500;
+    pub const TOP_MAILBOXES: usize = 10;
+    pub const MAX_WATCH_HISTORY: usize = 30;
+
+    pub fn new(has_dispatchers: bool, settings: &AkkaSettings) -> AkkaActorTreeTab {
+        AkkaActorTreeTab {
+            actors: StatefulList::with_items(vec![]),
+            actor_paths: vec![],
+            actor_label_width: 0,
+            actor_nodes: vec![],
+            collapsed: HashSet::new(),
+            actor_changes: HashMap::new(),
+            actor_counts: VecDeque::new(),
+            count_alert_settings: settings.count_alerts.clone(),
+            growth_ticks: 0,
+            count_alert: None,
+            excluded_actors: settings.excluded_actors.clone(),
+            watches: settings.watched_actors.iter()
+                .map(|p| ActorWatch { pattern: p.to_owned(), matches: 0, presence: VecDeque::new() })
+                .collect(),
+            show_excluded: false,
+            excluded_count: 0,
+            subtree_counts: vec![],
+            has_dispatchers,
+            dispatchers: VecDeque::new(),
+            has_dead_letters: settings.dead_letters_address.is_some() || settings.kamon_address.is_some(),
+            dead_letter_counts: VecDeque::new(),
+            dead_letter_total: None,
+            dead_letters: VecDeque::new(),
+            dead_letters_scroll: 0,
+            has_mailboxes: settings.mailbox_address.is_some() || settings.kamon_address.is_some(),
+            actor_stats: HashMap::new(),
+            has_kamon: settings.kamon_address.is_some(),
+            kamon_totals: None,
+            first_seen: HashMap::new(),
+            descendant_counts: HashMap::new(),
+            tree_fetch: None,
+            tree_depth: settings.tree_depth,
+            unloaded_actors: HashMap::new(),
+            subtree_fetches: HashSet::new(),
+            expanded: HashSet::new(),
+            last_tree_fetch_id: 0,
+            status_message: None,
+        }
+    }
+
+    /// Request to reload the tree, unless it's already being fetched
+    pub fn start_tree_fetch(&mut self) -> Option<FetcherRequest> {
+        if self.tree_fetch.is_some() {
+            return None;
+        }
+        self.last_tree_fetch_id += 1;
+        self.tree_fetch = Some((self.last_tree_fetch_id, Instant::now()));
+        Some(FetcherRequest::ActorTree(self.last_tree_fetch_id))
+    }
+
+    /// Request to abort the tree being fetched, whose response is dropped too if it arrives anyway
+    pub fn cancel_tree_fetch(&mut self) -> Option<FetcherRequest> {
+        let (id, _) = self.tree_fetch.take()?;
+        self.status_message = Some("Actor tree reload cancelled".to_owned());
+        Some(FetcherRequest::CancelActorTree(id))
+    }
+
+    /// Whether a fetched tree is the one waited for, rather than one that was cancelled
+    pub fn finish_tree_fetch(&mut self, id: u64) -> bool {
+        match self.tree_fetch {
+            Some((waited, _)) if waited == id => {
+                self.tree_fetch = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Replaces the tree with a fetched one, which may leave out subtrees, returning requests for the expanded ones
+    pub fn replace_actor_tree(&mut self, tree: ActorTree) -> Vec<FetcherRequest> {
+        self.unloaded_actors = tree.unloaded;
+        self.update_actor_tree(tree.nodes);
+        self.fetch_expanded_subtrees()
+    }
+
+    /// Requests the expanded subtrees that aren't loaded, unless they're being fetched already
+    fn fetch_expanded_subtrees(&mut self) -> Vec<FetcherRequest> {
+        let paths: Vec<String> = self.expanded.iter()
+            .filter(|p| self.unloaded_actors.contains_key(*p) && !self.subtree_fetches.contains(*p))
+            .cloned()
+            .collect();
+        if paths.is_empty() {
+            return vec![];
+        }
+        self.subtree_fetches.extend(paths.iter().cloned());
+        self.refresh_actor_list();
+        paths.into_iter().map(FetcherRequest::ActorSubtree).collect()
+    }
+
+    pub fn update_actor_tree(&mut self, actors: Vec<ActorTreeNode>) {
+        // on the first fetch every actor would be new
+        if !self.actor_nodes.is_empty() {
+            let old: HashSet<String> = tree::node_paths(&self.actor_nodes).into_values().collect();
+            let new: HashSet<String> = tree::node_paths(&actors).into_values().collect();
+            for p in new.difference(&old) {
+                self.actor_changes.insert(p.to_owned(), (ActorChange::Appeared, AkkaActorTreeTab::ACTOR_CHANGE_TICKS));
+            }
+            // subtrees fetched on demand are left out of a reloaded tree, rather than gone
+            let unloaded = &self.unloaded_actors;
+            let is_unloaded = |p: &str| ancestor_paths(p).any(|a| unloaded.contains_key(a));
+            for p in old.difference(&new).filter(|p| !is_unloaded(p)) {
+                self.actor_changes.insert(p.to_owned(), (ActorChange::Disappeared, AkkaActorTreeTab::ACTOR_CHANGE_TICKS));
+            }
+        }
+        self.set_actor_nodes(actors);
+        self.record_watches();
+    }
+
+    /// Counts actors matching every watched pattern, and notes whether they were there on this reload
+    fn record_watches(&mut self) {
+        self.count_watch_matches();
+        for w in self.watches.iter_mut() {
+            let present = w.matches > 0;
+            match w.presence.back() {
+                Some(true) if !present => self.status_message = Some(format!("No actor matches watched {} anymore", w.pattern)),
+                Some(false) if present => self.status_message = Some(format!("Watched {} is back", w.pattern)),
+                _ => {}
+            }
+            if w.presence.len() >= AkkaActorTreeTab::MAX_WATCH_HISTORY {
+                w.presence.pop_front();
+            }
+            w.presence.push_back(present);
+        }
+    }
+
+    fn count_watch_matches(&mut self) {
+        let paths = tree::node_paths(&self.actor_nodes);
+        for w in self.watches.iter_mut() {
+            w.matches = paths.values().filter(|p| matches_actor_pattern(p, &w.pattern)).count();
+        }
+    }
+
+    /// Starts watching the path of the selected actor, or stops if it's already watched
+    pub fn toggle_selected_watch(&mut self) {
+        if let Some(path) = self.selected_actor_path() {
+            match self.watches.iter().position(|w| w.pattern == path) {
+                Some(i) => {
+                    self.watches.remove(i);
+                    self.status_message = Some(format!("Stopped watching {}", path));
+                }
+                None => {
+                    self.status_message = Some(format!("Watching {}", path));
+                    self.watches.push(ActorWatch { pattern: path, matches: 0, presence: VecDeque::new() });
+                    self.count_watch_matches();
+                }
+            }
+        }
+    }
+
+    /// Adds a subtree fetched on demand under its actor, returning requests for expanded subtrees within it
+    pub fn merge_actor_subtree(&mut self, path: &str, subtree: ActorTree) -> Vec<FetcherRequest> {
+        self.subtree_fetches.remove(path);
+        // the tree may have been reloaded meanwhile
+        if !self.unloaded_actors.contains_key(path) {
+            return vec![];
+        }
+        let parent = match tree::node_paths(&self.actor_nodes).into_iter().find(|(_, p)| p == path) {
+            Some((id, _)) => id,
+            None => return vec![],
+        };
+        let offset = self.actor_nodes.iter().map(|n| n.id).max().unwrap_or(0);
+        let mut nodes = self.actor_nodes.clone();
+        nodes.extend(subtree.nodes.into_iter().map(|n| ActorTreeNode {
+            parent: Some(n.parent.map_or(parent, |p| p + offset)),
+            id: n.id + offset,
+            name: n.name,
+        }));
+        self.unloaded_actors.remove(path);
+        self.unloaded_actors.extend(subtree.unloaded.into_iter().map(|(p, c)| (format!("{}/{}", path, p), c)));
+        self.expanded.insert(path.to_owned());
+        self.set_actor_nodes(nodes);
+        self.fetch_expanded_subtrees()
+    }
+
+    fn set_actor_nodes(&mut self, actors: Vec<ActorTreeNode>) {
+        let now = Local::now();
+        let paths: HashSet<String> = tree::node_paths(&actors).into_values().collect();
+        self.descendant_counts.clear();
+        for p in paths.iter() {
+            for (i, ancestor) in ancestor_paths(p).enumerate() {
+                let counts = self.descendant_counts.entry(ancestor.to_owned()).or_insert((0, 0));
+                if i == 0 {
+                    counts.0 += 1;
+                }
+                counts.1 += 1;
+            }
+        }
+        self.first_seen.retain(|p, _| paths.contains(p));
+        for p in paths {
+            self.first_seen.entry(p).or_insert(now);
+        }
+        // the same actors the list leaves out, along with the known descendants of excluded actors that weren't fetched
+        let unloaded = self.unloaded_actors.iter()
+            .filter(|(p, _)| is_excluded_actor(p, &self.excluded_actors))
+            .filter_map(|(_, c)| *c);
+        self.excluded_count = tree::node_paths(&actors).values()
+            .filter(|p| is_excluded_actor(p, &self.excluded_actors))
+            .count() as u64 + unloaded.sum::<u64>();
+        self.update_subtree_counts(&actors);
+        self.actor_nodes = actors;
+        self.refresh_actor_list();
+    }
+
+    fn update_subtree_counts(&mut self, actors: &[ActorTreeNode]) {
+        let previous: HashMap<&str, u64> = self.subtree_counts.iter().map(|(p, c, _)| (p.as_str(), *c)).collect();
+        let mut counts: HashMap<String, u64> = HashMap::new();
+        let loaded = tree::node_paths(actors).into_values().map(|p| (p, 1));
+        let unloaded = self.unloaded_actors.iter().filter_map(|(p, c)| c.map(|c| (p.to_owned(), c)));
+        for (p, c) in loaded.chain(unloaded) {
+            let mut segments = p.splitn(3, '/');
+            if let (Some(root), Some(top)) = (segments.next(), segments.next()) {
+                *counts.entry(format!("{}/{}", root, top)).or_insert(0) += c;
+            }
+        }
+        let mut subtree_counts: Vec<(String, u64, i64)> = counts.into_iter()
+            .map(|(p, c)| {
+                let change = c as i64 - previous.get(p.as_str()).cloned().unwrap_or(c) as i64;
+                (p, c, change)
+            })
+            .collect();
+        subtree_counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        self.subtree_counts = subtree_counts;
+    }
+
+    /// Subtree counts, leaving out excluded subtrees unless they're shown
+    pub fn displayed_subtree_counts(&self) -> Vec<&(String, u64, i64)> {
+        self.subtree_counts.iter()
+            .filter(|(p, _, _)| self.show_excluded || !is_excluded_actor(p, &self.excluded_actors))
+            .collect()
+    }
+
+    /// Shows actors matching the exclusion patterns again, or hides them
+    pub fn toggle_excluded_actors(&mut self) {
+        self.show_excluded = !self.show_excluded;
+        self.status_message = Some(format!(
+            "{} actors matching {}",
+            if self.show_excluded { "Showing" } else { "Hiding" },
+            self.excluded_actors.join(", ")
+        ));
+        self.refresh_actor_list();
+    }
+
+    /// Actor counts to chart, leaving out excluded actors unless they're shown
+    pub fn displayed_actor_counts(&self) -> Vec<u64> {
+        self.actor_counts.iter()
+            .map(|c| if self.show_excluded { c.total } else { c.total.saturating_sub(c.excluded) })
+            .collect()
+    }
+
+    /// Average change of the charted actor count per minute over the retained samples
+    pub fn actor_growth_per_minute(&self) -> Option<f64> {
+        let (first, last) = (self.actor_counts.front()?, self.actor_counts.back()?);
+        let minutes = (last.timestamp - first.timestamp).num_milliseconds() as f64 / 60_000.0;
+        if minutes <= 0.0 {
+            return None;
+        }
+        let counts = self.displayed_actor_counts();
+        Some((counts[counts.len() - 1] as f64 - counts[0] as f64) / minutes)
+    }
+
+    /// Writes the whole last fetched tree, including collapsed subtrees, as DOT and JSON files in the working directory
+    pub fn export_tree(&mut self) -> Result<String, String> {
+        let dot_file = export::timestamped_file_name("actor-tree", "dot");
+        let json_file = export::timestamped_file_name("actor-tree", "json");
+        let json = serde_json::to_string_pretty(&export::tree_to_json(&self.actor_nodes)).unwrap_or_default();
+        let result = fs::write(&dot_file, export::tree_to_dot("actors", &self.actor_nodes))
+            .and_then(|_| fs::write(&json_file, json));
+        let result = match result {
+            Ok(_) => Ok(format!("Exported actor tree to {} and {}", dot_file, json_file)),
+            Err(e) => Err(format!("Export failed: {}", e)),
+        };
+        self.status_message = Some(result.clone().unwrap_or_else(|e| e));
+        result
+    }
+
+    pub fn selected_actor_details(&self) -> Option<ActorDetails<'_>> {
+        let path = self.actors.state.selected().and_then(|i| self.actor_paths.get(i))?;
+        let (children, descendants) = self.descendant_counts.get(path).cloned().unwrap_or((0, 0));
+        Some(ActorDetails {
+            path,
+            parent: path.rsplit_once('/').map(|(parent, _)| parent),
+            children,
+            descendants,
+            first_seen: self.first_seen.get(path).cloned(),
+            stats: self.actor_stats.get(path),
+        })
+    }
+
+    /// Drops highlights of actor changes older than `ACTOR_CHANGE_TICKS`
+    pub fn fade_actor_changes(&mut self) {
+        let had_disappeared = self.actor_changes.values().any(|(c, _)| *c == ActorChange::Disappeared);
+        for (_, ticks) in self.actor_changes.values_mut() {
+            *ticks = ticks.saturating_sub(1);
+        }
+        self.actor_changes.retain(|_, (_, ticks)| *ticks > 0);
+        if had_disappeared {
+            self.refresh_actor_list();
+        }
+    }
+
+    ///
+    /// Collapses the subtree of the selected actor, or expands it if it's already collapsed.
+    ///
+    /// When subtrees are fetched on demand, collapsing drops the subtree, and expanding requests it again.
+    pub fn toggle_selected_actor(&mut self) -> Option<FetcherRequest> {
+        let path = self.selected_actor_path()?;
+        if self.tree_depth.is_some() {
+            if self.unloaded_actors.contains_key(&path) {
+                self.expanded.insert(path.clone());
+                let request = if self.subtree_fetches.insert(path.clone()) { Some(FetcherRequest::ActorSubtree(path)) } else { None };
+                self.refresh_actor_list();
+                return request;
+            }
+            let paths = tree::node_paths(&self.actor_nodes);
+            let prefix = format!("{}/", path);
+            let (dropped, kept): (Vec<ActorTreeNode>, Vec<ActorTreeNode>) = self.actor_nodes.iter().cloned()
+                .partition(|n| paths.get(&n.id).is_some_and(|p| p.starts_with(&prefix)));
+            if !dropped.is_empty() {
+                self.unloaded_actors.retain(|p, _| !p.starts_with(&prefix));
+                self.expanded.retain(|p| !p.starts_with(&prefix));
+                self.expanded.remove(&path);
+                self.unloaded_actors.insert(path, Some(dropped.len() as u64));
+                self.set_actor_nodes(kept);
+                return None;
+            }
+        }
+        if !self.collapsed.remove(&path) {
+            self.collapsed.insert(path);
+        }
+        self.refresh_actor_list();
+        None
+    }
+
+    /// Collapses every actor with children
+    pub fn collapse_all_actors(&mut self) {
+        let mut paths = tree::node_paths(&self.actor_nodes);
+        let parents: HashSet<usize> = self.actor_nodes.iter().filter_map(|n| n.parent).collect();
+        self.collapsed = parents.iter().filter_map(|id| paths.remove(id)).collect();
+        self.refresh_actor_list();
+    }
+
+    pub fn expand_all_actors(&mut self) {
+        self.collapsed.clear();
+        self.refresh_actor_list();
+    }
+
+    pub fn selected_actor_path(&self) -> Option<String> {
+        self.actors.state.selected().and_then(|i| self.actor_paths.get(i)).cloned()
+    }
+
+    /// Rebuilds the displayed tree, leaving out descendants of collapsed actors and keeping the selection
+    fn refresh_actor_list(&mut self) {
+        let selected = self.selected_actor_path();
+        let mut nodes = self.actor_nodes.clone();
+        let mut paths = tree::node_paths(&nodes);
+
+        // actors that are gone stay in the tree until their highlight fades, parents first
+        let mut gone: Vec<&String> = self.actor_changes.iter()
+            .filter(|(_, (c, _))| *c == ActorChange::Disappeared)
+            .map(|(p, _)| p)
+            .collect();
+        gone.sort_by_key(|p| (p.matches('/').count(), p.to_owned()));
+        let mut ids: HashMap<String, usize> = paths.iter().map(|(id, p)| (p.to_owned(), *id)).collect();
+        let mut next_id = nodes.iter().map(|n| n.id).max().unwrap_or(0) + 1;
+        for p in gone {
+            let (parent_path, name) = match p.rfind('/') {
+                Some(i) => (Some(&p[..i]), &p[i + 1..]),
+                None => (None, p.as_str()),
+            };
+            let parent = parent_path.and_then(|pp| ids.get(pp).cloned());
+            if parent_path.is_none() || parent.is_some() {
+                nodes.push(ActorTreeNode { name: name.to_owned(), parent, id: next_id });
+                paths.insert(next_id, p.to_owned());
+                ids.insert(p.to_owned(), next_id);
+                next_id += 1;
+            }
+        }
+
+        if !self.show_excluded {
+            nodes.retain(|n| paths.get(&n.id).is_none_or(|p| !is_excluded_actor(p, &self.excluded_actors)));
+        }
+
+        let path = |n: &ActorTreeNode| paths.get(&n.id).map_or("", |p| p.as_str());
+
+        // actors are hidden under their outermost collapsed ancestor, which shows how many there are
+        let mut hidden: HashMap<&str, usize> = HashMap::new();
+        for n in nodes.iter() {
+            if let Some(ancestor) = ancestor_paths(path(n)).filter(|a| self.collapsed.contains(*a)).last() {
+                *hidden.entry(ancestor).or_insert(0) += 1;
+            }
+        }
+
+        let visible: Vec<ActorTreeNode> = nodes.iter()
+            .filter(|n| !ancestor_paths(path(n)).any(|a| self.collapsed.contains(a)))
+            .map(|n| {
+                let hidden = hidden.get(path(n)).cloned().unwrap_or(0);
+                let note = if self.subtree_fetches.contains(path(n)) {
+                    Some("loading...".to_owned())
+                } else if let Some(count) = self.unloaded_actors.get(path(n)) {
+                    Some(count.map_or("...".to_owned(), |c| format!("+{}", c)))
+                } else if hidden > 0 {
+                    Some(format!("+{}", hidden))
+                } else {
+                    None
+                };
+                match note {
+                    Some(note) => ActorTreeNode { name: format!("{} ({})", n.name, note), ..n.clone() },
+                    None => n.clone(),
+                }
+            })
+            .collect();
+
+        let (items, actor_paths): (Vec<String>, Vec<String>) = tree::tree_list_widget(visible, false)
+            .into_iter()
+            .map(|(label, n)| (label, path(&n).to_owned()))
+            .unzip();
+        self.actor_label_width = items.iter().map(|i| i.chars().count()).max().unwrap_or(0);
+        self.actors.items = items;
+        self.actor_paths = actor_paths;
+        // an actor that's gone hands the selection over to its closest ancestor still in the list
+        let index = selected.and_then(|s| {
+            let mut path = s.as_str();
+            loop {
+                if let Some(i) = self.actor_paths.iter().position(|p| p == path) {
+                    return Some(i);
+                }
+                path = &path[..path.rfind('/')?];
+            }
+        });
+        let last = self.actor_paths.len().checked_sub(1);
+        self.actors.state.select(index.or_else(|| self.actors.state.selected().and_then(|i| last.map(|l| i.min(l)))));
+    }
+
+    pub fn select_prev_actor(&mut self) {
+        self.actors.previous();
+    }
+
+    pub fn select_next_actor(&mut self) {
+        self.actors.next();
+    }
+
+    pub fn append_actor_count(&mut self, c: u64) {
+        if self.actor_counts.len() > AkkaActorTreeTab::MAX_ACTOR_COUNT_MEASURES {
+            self.actor_counts.pop_front();
+        }
+        self.check_count_alert(c);
+        self.actor_counts.push_back(ActorCountSample { timestamp: Local::now(), total: c, excluded: self.excluded_count });
+    }
+
+    fn check_count_alert(&mut self, c: u64) {
+        match self.actor_counts.back() {
+            Some(last) if c > last.total => self.growth_ticks += 1,
+            _ => self.growth_ticks = 0,
+        }
+
+        let settings = &self.count_alert_settings;
+        let alert = match settings.limit {
+            Some(limit) if c > limit => Some(format!("Actor count {} exceeds the limit of {}", c, limit)),
+            _ if settings.growth_ticks > 0 && self.growth_ticks >= settings.growth_ticks =>
+                Some(format!("Actor count grew on {} consecutive fetches, up to {}", self.growth_ticks, c)),
+            _ => None,
+        };
+
+        match (&self.count_alert, &alert) {
+            (None, Some(a)) => self.status_message = Some(a.to_owned()),
+            (Some(_), None) => self.status_message = Some("Actor count back to normal".to_owned()),
+            _ => {}
+        }
+        self.count_alert = alert;
+    }
+
+    pub fn append_dispatcher_metrics(&mut self, m: AkkaDispatcherMetrics) {
+        if self.dispatchers.len() > AkkaActorTreeTab::MAX_DISPATCHER_MEASURES {
+            self.dispatchers.pop_front();
+        }
+        self.dispatchers.push_back(m);
+    }
+
+    pub fn append_dead_letters(&mut self, m: DeadLetterMetrics) {
+        // the total starts over when the app is restarted
+        let count = match self.dead_letter_total {
+            Some(total) if m.count >= total => m.count - total,
+            Some(_) => m.count,
+            None => 0,
+        };
+        self.dead_letter_total = Some(m.count);
+        if self.dead_letter_counts.len() > AkkaActorTreeTab::MAX_DEAD_LETTER_MEASURES {
+            self.dead_letter_counts.pop_front();
+        }
+        self.dead_letter_counts.push_back((m.timestamp, count));
+
+        // samples are reported again until newer ones push them out
+        let last = self.dead_letters.back().map(|d| d.timestamp);
+        for d in m.samples.into_iter().filter(|d| last.is_none_or(|t| d.timestamp > t)) {
+            if self.dead_letters.len() > AkkaActorTreeTab::MAX_DEAD_LETTERS {
+                self.dead_letters.pop_front();
+            }
+            self.dead_letters.push_back(d);
+        }
+    }
+
+    pub fn replace_actor_stats(&mut self, stats: HashMap<String, ActorStats>) {
+        self.actor_stats = stats;
+    }
+
+    /// Turns cumulative Kamon histograms into averages since the previous scrape
+    pub fn append_kamon_metrics(&mut self, m: KamonMetrics) {
+        let previous = self.kamon_totals.take();
+        let mut stats: HashMap<String, ActorStats> = HashMap::new();
+        for (path, totals) in &m.mailbox_size {
+            let prev = previous.as_ref().and_then(|p| p.mailbox_size.get(path));
+            stats.entry(path.to_owned()).or_default().mailbox = totals.mean_since(prev).map(|x| x.round() as u64);
+        }
+        for (path, totals) in &m.processing_time {
+            let prev = previous.as_ref().and_then(|p| p.processing_time.get(path));
+            let s = stats.entry(path.to_owned()).or_default();
+            s.messages = Some(totals.count);
+            s.processing_time_ms = totals.mean_since(prev).map(|x| x * 1000.0);
+        }
+        self.replace_actor_stats(stats);
+        if let Some(count) = m.dead_letters {
+            self.append_dead_letters(DeadLetterMetrics { timestamp: m.timestamp, count, samples: vec![] });
+        }
+        self.kamon_totals = Some(m);
+    }
+
+    /// Mailboxes keep their last sizes, the next scrape is averaged since the last one that worked
+    pub fn on_kamon_error(&mut self, e: String) {
+        self.status_message = Some(format!("Couldn't scrape Kamon metrics: {}", e));
+    }
+
+    /// Actors with the biggest non-empty mailboxes, biggest first
+    pub fn top_mailboxes(&self) -> Vec<(&str, u64)> {
+        let mut top: Vec<(&str, u64)> = self.actor_stats.iter()
+            .filter_map(|(p, s)| s.mailbox.filter(|m| *m > 0).map(|m| (p.as_str(), m)))
+            .collect();
+        top.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        top.truncate(AkkaActorTreeTab::TOP_MAILBOXES);
+        top
+    }
+
+    pub fn scroll_dead_letters_up(&mut self) {
+        self.dead_letters_scroll = self.dead_letters_scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_dead_letters_down(&mut self) {
+        if (self.dead_letters_scroll as usize) + 1 < self.dead_letters.len() {
+            self.dead_letters_scroll += 1;
+        }
+    }
+}
+
+impl SourceTab for AkkaActorTreeTab {
+    fn on_up(&mut self) {
+        self.select_prev_actor()
+    }
+
+    fn on_down(&mut self) {
+        self.select_next_actor()
+    }
+
+    /// Dispatchers are read over JMX, the actor tree itself over HTTP
+    fn uses_jmx(&self) -> bool {
+        self.has_dispatchers
+    }
+
+    fn alert_message(&self) -> Option<String> {
+        self.count_alert.clone()
+    }
+}
@@ -0,0 +1,95 @@
+use panopticon_core::actuator::model::{ActuatorSettings, HealthStatus, JvmThread};
+
+use crate::app::{SourceTab, StatefulList};
+use crate::app::prometheus::PrometheusTab;
+use crate::app::zmx::ZMXTab;
+
+pub struct ActuatorTab {
+    pub health: Option<HealthStatus>,
+    /// Charts of the selected metrics
+    pub metrics: PrometheusTab,
+    pub threads: StatefulList<String>,
+    pub thread_dumps: Vec<String>,
+    pub selected_thread_dump: (String, u16),
+    pub scroll: u16,
+}
+
+impl ActuatorTab {
+    pub fn new(settings: &ActuatorSettings) -> ActuatorTab {
+        ActuatorTab {
+            health: None,
+            metrics: PrometheusTab::new(settings.metrics.iter().map(|m| m.to_string()).collect()),
+            threads: StatefulList::with_items(vec![]),
+            thread_dumps: vec![],
+            selected_thread_dump: ("".to_owned(), 0),
+            scroll: 0,
+        }
+    }
+
+    pub fn replace_health(&mut self, health: HealthStatus) {
+        self.health = Some(health);
+    }
+
+    /// Lists threads by state, so that blocked and waiting ones are next to each other
+    pub fn replace_thread_dump(&mut self, mut threads: Vec<JvmThread>) {
+        threads.sort_by(|a, b| (&a.state, &a.name).cmp(&(&b.state, &b.name)));
+        self.threads = StatefulList::with_items(threads.iter().map(|t| format!("{:13} #{} {}", t.state, t.id, t.name)).collect());
+        self.thread_dumps = threads.into_iter().map(|t| t.dump).collect();
+        if !self.thread_dumps.is_empty() {
+            self.threads.state.select(Some(0));
+        }
+        self.on_thread_change();
+    }
+
+    /// Shows the error in place of the dump, as the endpoint may just be disabled
+    pub fn on_thread_dump_error(&mut self, e: String) {
+        self.replace_thread_dump(vec![]);
+        self.selected_thread_dump = ZMXTab::prepare_dump(e);
+    }
+
+    pub fn select_prev_thread(&mut self) {
+        if !self.threads.items.is_empty() {
+            self.threads.previous();
+            self.on_thread_change();
+        }
+    }
+
+    pub fn select_next_thread(&mut self) {
+        if !self.threads.items.is_empty() {
+            self.threads.next();
+            self.on_thread_change();
+        }
+    }
+
+    pub fn on_thread_change(&mut self) {
+        let dump = self.threads.state.selected().and_then(|n| self.thread_dumps.get(n)).cloned().unwrap_or_default();
+        self.selected_thread_dump = ZMXTab::prepare_dump(dump);
+        self.scroll = 0;
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        if self.scroll < self.selected_thread_dump.1 {
+            self.scroll += 1;
+        }
+    }
+}
+
+impl SourceTab for ActuatorTab {
+    fn on_up(&mut self) {
+        self.select_prev_thread()
+    }
+
+    fn on_down(&mut self) {
+        self.select_next_thread()
+    }
+
+    fn alert_message(&self) -> Option<String> {
+        self.health.as_ref()
+            .filter(|h| !h.is_up())
+            .map(|h| format!("Health is {}", h.status))
+    }
+}
@@ -0,0 +1,31 @@
+use std::collections::VecDeque;
+
+use panopticon_core::jmx::model::CassandraMetrics;
+
+use crate::app::SourceTab;
+use crate::widgets::viewport;
+
+pub struct CassandraTab {
+    pub metrics: VecDeque<CassandraMetrics>,
+}
+
+impl CassandraTab {
+    pub const MAX_CASSANDRA_MEASURES: usize = viewport::HISTORY_SAMPLES;
+
+    pub fn new() -> CassandraTab {
+        CassandraTab { metrics: VecDeque::new() }
+    }
+
+    pub fn append_cassandra_metrics(&mut self, m: CassandraMetrics) {
+        if self.metrics.len() > CassandraTab::MAX_CASSANDRA_MEASURES {
+            self.metrics.pop_front();
+        }
+        self.metrics.push_back(m);
+    }
+}
+
+impl SourceTab for CassandraTab {
+    fn uses_jmx(&self) -> bool {
+        true
+    }
+}
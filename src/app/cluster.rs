@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+
+use panopticon_core::akka::model::RemoteTotals;
+use panopticon_core::jmx::model::AkkaClusterStatus;
+
+use crate::app::SourceTab;
+use crate::widgets::viewport;
+
+/// Remote messages per second
+pub struct RemotingRates {
+    pub timestamp: DateTime<Local>,
+    pub messages_in: f64,
+    pub messages_out: f64,
+    pub bytes_in: f64,
+    pub bytes_out: f64,
+}
+
+pub struct ClusterTab {
+    pub statuses: VecDeque<AkkaClusterStatus>,
+    /// Membership and reachability changes
+    pub events: VecDeque<(DateTime<Local>, String)>,
+    pub remoting: VecDeque<RemotingRates>,
+    last_remote_totals: Option<(DateTime<Local>, RemoteTotals)>,
+}
+
+impl ClusterTab {
+    pub const MAX_CLUSTER_MEASURES: usize = viewport::HISTORY_SAMPLES;
+    pub const MAX_CLUSTER_EVENTS: usize = 200;
+
+    pub fn new() -> ClusterTab {
+        ClusterTab { statuses: VecDeque::new(), events: VecDeque::new(), remoting: VecDeque::new(), last_remote_totals: None }
+    }
+
+    pub fn append_cluster_status(&mut self, m: AkkaClusterStatus) {
+        if let Some(previous) = self.statuses.back() {
+            let mut events = vec![];
+            for member in &m.members {
+                match previous.members.iter().find(|p| p.address == member.address) {
+                    None => events.push(format!("{} joined ({})", member.address, member.status)),
+                    Some(p) if p.status != member.status => events.push(format!("{} is {}", member.address, member.status)),
+                    _ => {}
+                }
+            }
+            // only members removed while unreachable or downed are quarantined, the ones leaving gracefully aren't
+            for p in previous.members.iter().filter(|p| !m.members.iter().any(|x| x.address == p.address)) {
+                let event = if p.status == "Down" || previous.unreachable.iter().any(|u| u.node == p.address) {
+                    "was removed and quarantined"
+                } else if p.status == "Leaving" || p.status == "Exiting" {
+                    "left the cluster"
+                } else {
+                    "was removed"
+                };
+                events.push(format!("{} {}", p.address, event));
+            }
+            for u in m.unreachable.iter().filter(|u| !previous.unreachable.iter().any(|p| p.node == u.node)) {
+                events.push(format!("{} is unreachable, observed by {}", u.node, u.observed_by.join(", ")));
+            }
+            for p in previous.unreachable.iter().filter(|p| !m.unreachable.iter().any(|u| u.node == p.node)) {
+                events.push(format!("{} is reachable again", p.node));
+            }
+            for e in events {
+                if self.events.len() > ClusterTab::MAX_CLUSTER_EVENTS {
+                    self.events.pop_front();
+                }
+                self.events.push_back((m.timestamp, e));
+            }
+        }
+        if self.statuses.len() > ClusterTab::MAX_CLUSTER_MEASURES {
+            self.statuses.pop_front();
+        }
+        self.statuses.push_back(m);
+    }
+
+    /// Turns remote message totals into rates since the previous ones
+    pub fn append_remote_totals(&mut self, timestamp: DateTime<Local>, totals: RemoteTotals) {
+        if let Some((t, previous)) = self.last_remote_totals {
+            let secs = (timestamp - t).num_milliseconds() as f64 / 1000.0;
+            // totals start over when the app is restarted
+            if secs > 0.0 && totals.messages_in >= previous.messages_in && totals.messages_out >= previous.messages_out {
+                if self.remoting.len() > ClusterTab::MAX_CLUSTER_MEASURES {
+                    self.remoting.pop_front();
+                }
+                self.remoting.push_back(RemotingRates {
+                    timestamp,
+                    messages_in: (totals.messages_in - previous.messages_in) as f64 / secs,
+                    messages_out: (totals.messages_out - previous.messages_out) as f64 / secs,
+                    bytes_in: (totals.bytes_in - previous.bytes_in) / secs,
+                    bytes_out: (totals.bytes_out - previous.bytes_out) / secs,
+                });
+            }
+        }
+        self.last_remote_totals = Some((timestamp, totals));
+    }
+}
+
+impl SourceTab for ClusterTab {
+    fn uses_jmx(&self) -> bool {
+        true
+    }
+
+    fn alert_message(&self) -> Option<String> {
+        self.statuses.back()
+            .filter(|s| !s.unreachable.is_empty())
+            .map(|s| format!("{} unreachable members", s.unreachable.len()))
+    }
+}
@@ -0,0 +1,37 @@
+use std::time::{Duration, Instant};
+
+use panopticon_core::command::model::CommandSettings;
+
+use crate::app::{SourceTab, append_polled_values, due_polls};
+use crate::app::prometheus::PrometheusTab;
+
+pub struct CommandsTab {
+    /// A chart for each of the commands, with a series per extracted value
+    pub charts: PrometheusTab,
+    /// Error of the last run of each command, if it failed
+    pub errors: Vec<Option<String>>,
+    intervals: Vec<Option<Duration>>,
+    last_polled: Vec<Option<Instant>>,
+}
+
+impl CommandsTab {
+    pub fn new(commands: &[CommandSettings]) -> CommandsTab {
+        CommandsTab {
+            charts: PrometheusTab::new(commands.iter().map(|c| c.name.to_owned()).collect()),
+            errors: commands.iter().map(|_| None).collect(),
+            intervals: commands.iter().map(|c| c.interval.map(Duration::from_millis)).collect(),
+            last_polled: commands.iter().map(|_| None).collect(),
+        }
+    }
+
+    /// Commands to run on this tick, those without an interval are run every tick
+    pub fn due_commands(&mut self, now: Instant) -> Vec<usize> {
+        due_polls(&mut self.last_polled, &self.intervals, now)
+    }
+
+    pub fn on_values(&mut self, i: usize, values: Result<Vec<(String, f64)>, String>) {
+        append_polled_values(&mut self.charts, &mut self.errors, i, values)
+    }
+}
+
+impl SourceTab for CommandsTab {}
@@ -0,0 +1,52 @@
+use chrono::{DateTime, Local};
+
+use crate::alerts::model::MetricValue;
+use crate::app::SourceTab;
+use crate::app::prometheus::{PrometheusSeries, PrometheusTab};
+use crate::dashboard::{DashboardSettings, WidgetKind};
+
+/// An error returned by a source, along with the identical ones it kept returning right after
+/// A tab composed in the config file, charting metrics sampled every tick
+pub struct DashboardTab {
+    pub settings: DashboardSettings,
+    /// Series of each of the widgets, left empty for those that aren't charts
+    pub series: Vec<Vec<PrometheusSeries>>,
+}
+
+impl DashboardTab {
+    pub fn new(settings: &DashboardSettings) -> DashboardTab {
+        DashboardTab {
+            series: settings.rows.iter().flat_map(|r| r.widgets.iter()).map(|_| vec![]).collect(),
+            settings: settings.clone(),
+        }
+    }
+
+    /// Appends the values of the charted metrics
+    pub fn append_values(&mut self, values: &[MetricValue], timestamp: DateTime<Local>) {
+        let widgets = self.settings.rows.iter().flat_map(|r| r.widgets.iter());
+        for (widget, series) in widgets.zip(self.series.iter_mut()) {
+            let (metric, picked) = match &widget.kind {
+                WidgetKind::Chart { metric, series } => (metric, series),
+                _ => continue,
+            };
+            let matching = values.iter()
+                .filter(|v| &v.metric == metric && picked.as_ref().is_none_or(|p| p == &v.series));
+            for v in matching {
+                let i = match series.iter().position(|s| s.name == v.series) {
+                    Some(i) => i,
+                    None => {
+                        series.push(PrometheusSeries::new(v.series.clone(), false));
+                        series.len() - 1
+                    }
+                };
+                let s = &mut series[i];
+                if s.values.len() >= PrometheusTab::MAX_PROMETHEUS_MEASURES {
+                    s.values.pop_front();
+                }
+                s.values.push_back((timestamp, v.value));
+            }
+        }
+    }
+}
+
+impl SourceTab for DashboardTab {}
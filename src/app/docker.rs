@@ -0,0 +1,57 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+
+use panopticon_core::docker::model::{DockerSettings, DockerStats};
+
+use crate::app::SourceTab;
+use crate::widgets::viewport;
+
+pub struct DockerTab {
+    pub container: String,
+    pub stats: VecDeque<DockerStats>,
+    /// Percent of a single CPU used since the previous sample
+    pub cpu: VecDeque<(DateTime<Local>, f64)>,
+    /// Bytes received and sent per second, across all networks
+    pub network: VecDeque<(DateTime<Local>, f64, f64)>,
+    pub error: Option<String>,
+}
+
+impl DockerTab {
+    pub const MAX_DOCKER_MEASURES: usize = viewport::HISTORY_SAMPLES;
+
+    pub fn new(settings: &DockerSettings) -> DockerTab {
+        DockerTab { container: settings.container.to_owned(), stats: VecDeque::new(), cpu: VecDeque::new(), network: VecDeque::new(), error: None }
+    }
+
+    pub fn append_stats(&mut self, s: DockerStats) {
+        if let Some(last) = self.stats.back() {
+            if let Some(cpu) = s.cpu_percent_since(last) {
+                if self.cpu.len() > DockerTab::MAX_DOCKER_MEASURES {
+                    self.cpu.pop_front();
+                }
+                self.cpu.push_back((s.timestamp, cpu));
+            }
+            let secs = (s.timestamp - last.timestamp).num_milliseconds() as f64 / 1000.0;
+            // counters start over when the container is restarted
+            if secs > 0.0 && s.rx_bytes >= last.rx_bytes && s.tx_bytes >= last.tx_bytes {
+                if self.network.len() > DockerTab::MAX_DOCKER_MEASURES {
+                    self.network.pop_front();
+                }
+                self.network.push_back((s.timestamp, (s.rx_bytes - last.rx_bytes) as f64 / secs, (s.tx_bytes - last.tx_bytes) as f64 / secs));
+            }
+        }
+        if self.stats.len() > DockerTab::MAX_DOCKER_MEASURES {
+            self.stats.pop_front();
+        }
+        self.stats.push_back(s);
+        self.error = None;
+    }
+
+    /// Keeps showing the last stats, as the container may be restarting
+    pub fn on_error(&mut self, e: String) {
+        self.error = Some(e);
+    }
+}
+
+impl SourceTab for DockerTab {}
@@ -0,0 +1,92 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+
+use crate::app::SourceTab;
+use crate::export;
+
+pub struct LoggedError {
+    pub first_seen: DateTime<Local>,
+    pub last_seen: DateTime<Local>,
+    pub source: &'static str,
+    pub message: String,
+    pub count: usize,
+    // until the source responds successfully again
+    ongoing: bool,
+}
+
+pub struct ErrorLogTab {
+    /// Errors of all the sources, the latest last
+    pub errors: VecDeque<LoggedError>,
+    /// Number of errors scrolled back from the latest one
+    pub scroll: usize,
+}
+
+impl ErrorLogTab {
+    pub const MAX_ERRORS: usize = 1000;
+
+    pub fn new() -> ErrorLogTab {
+        ErrorLogTab { errors: VecDeque::new(), scroll: 0 }
+    }
+
+    /// Adds an error, unless the source has been failing with the same message since its last one
+    pub fn append_error(&mut self, source: &'static str, message: &str) {
+        let now = Local::now();
+        if let Some(e) = self.errors.iter_mut().rev().find(|e| e.source == source && e.ongoing) {
+            if e.message == message {
+                e.last_seen = now;
+                e.count += 1;
+                return;
+            }
+            e.ongoing = false;
+        }
+        if self.errors.len() == ErrorLogTab::MAX_ERRORS {
+            self.errors.pop_front();
+        }
+        self.errors.push_back(LoggedError { first_seen: now, last_seen: now, source, message: message.to_owned(), count: 1, ongoing: true });
+        // keep showing the same errors while scrolled back
+        if self.scroll > 0 {
+            self.scroll = (self.scroll + 1).min(self.errors.len() - 1);
+        }
+    }
+
+    pub fn on_success(&mut self, source: &str) {
+        if let Some(e) = self.errors.iter_mut().rev().find(|e| e.source == source && e.ongoing) {
+            e.ongoing = false;
+        }
+    }
+
+    pub fn scroll_up(&mut self, errors: usize) {
+        self.scroll = self.scroll.saturating_sub(errors);
+    }
+
+    pub fn scroll_down(&mut self, errors: usize) {
+        self.scroll = (self.scroll + errors).min(self.errors.len().saturating_sub(1));
+    }
+
+    pub fn export_csv(&self) -> Result<String, String> {
+        let file = export::timestamped_file_name("errors", "csv");
+        let rows: Vec<Vec<String>> = self.errors.iter()
+            .map(|e| vec![
+                e.first_seen.to_rfc3339(),
+                e.last_seen.to_rfc3339(),
+                e.source.to_owned(),
+                e.count.to_string(),
+                e.message.to_owned(),
+            ])
+            .collect();
+        export::write_csv(&file, &["first_seen", "last_seen", "source", "count", "message"], &rows)
+            .map(|_| format!("Exported {} errors to {}", rows.len(), file))
+            .map_err(|e| format!("Export failed: {}", e))
+    }
+}
+
+impl SourceTab for ErrorLogTab {
+    fn on_up(&mut self) {
+        self.scroll_up(1)
+    }
+
+    fn on_down(&mut self) {
+        self.scroll_down(1)
+    }
+}
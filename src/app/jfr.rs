@@ -0,0 +1,40 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+
+use panopticon_core::jfr::model::{ContentionEvent, GcPause, JfrMetrics};
+
+use crate::app::SourceTab;
+use crate::widgets::viewport;
+
+pub struct JfrTab {
+    pub allocation_rates: VecDeque<(DateTime<Local>, f64)>,
+    pub gc_pauses: VecDeque<GcPause>,
+    pub contention: VecDeque<ContentionEvent>,
+}
+
+impl JfrTab {
+    pub const MAX_ALLOCATION_MEASURES: usize = viewport::HISTORY_SAMPLES;
+    pub const MAX_EVENTS: usize = 100;
+
+    pub fn new() -> JfrTab {
+        JfrTab { allocation_rates: VecDeque::new(), gc_pauses: VecDeque::new(), contention: VecDeque::new() }
+    }
+
+    pub fn append_jfr_metrics(&mut self, m: JfrMetrics) {
+        if self.allocation_rates.len() > JfrTab::MAX_ALLOCATION_MEASURES {
+            self.allocation_rates.pop_front();
+        }
+        self.allocation_rates.push_back((m.timestamp, m.allocation_rate));
+        self.gc_pauses.extend(m.gc_pauses);
+        while self.gc_pauses.len() > JfrTab::MAX_EVENTS {
+            self.gc_pauses.pop_front();
+        }
+        self.contention.extend(m.contention);
+        while self.contention.len() > JfrTab::MAX_EVENTS {
+            self.contention.pop_front();
+        }
+    }
+}
+
+impl SourceTab for JfrTab {}
@@ -0,0 +1,49 @@
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+
+use panopticon_core::json_metrics::model::JsonMetricSettings;
+use panopticon_core::prometheus::model::{MetricSample, PrometheusMetrics};
+
+use crate::app::{SourceTab, due_polls};
+use crate::app::prometheus::PrometheusTab;
+
+pub struct JsonMetricsTab {
+    /// A chart for each of the metrics
+    pub charts: PrometheusTab,
+    /// Error of the last poll of each metric, if it failed
+    pub errors: Vec<Option<String>>,
+    pointers: Vec<String>,
+    intervals: Vec<Option<Duration>>,
+    last_polled: Vec<Option<Instant>>,
+}
+
+impl JsonMetricsTab {
+    pub fn new(metrics: &[JsonMetricSettings]) -> JsonMetricsTab {
+        JsonMetricsTab {
+            charts: PrometheusTab::new(metrics.iter().map(|m| m.name.to_owned()).collect()),
+            errors: metrics.iter().map(|_| None).collect(),
+            pointers: metrics.iter().map(|m| m.json_pointer.to_owned()).collect(),
+            intervals: metrics.iter().map(|m| m.interval.map(Duration::from_millis)).collect(),
+            last_polled: metrics.iter().map(|_| None).collect(),
+        }
+    }
+
+    /// Metrics to poll on this tick, those without an interval are polled every tick
+    pub fn due_metrics(&mut self, now: Instant) -> Vec<usize> {
+        due_polls(&mut self.last_polled, &self.intervals, now)
+    }
+
+    pub fn on_value(&mut self, i: usize, value: Result<f64, String>) {
+        match value {
+            Ok(v) => {
+                let sample = MetricSample { selector: i, series: self.pointers[i].to_owned(), value: v, counter: false };
+                self.charts.append_metrics(PrometheusMetrics { timestamp: Local::now(), samples: vec![sample], metrics: vec![], errors: vec![] });
+                self.errors[i] = None;
+            }
+            Err(e) => self.errors[i] = Some(e),
+        }
+    }
+}
+
+impl SourceTab for JsonMetricsTab {}
@@ -0,0 +1,127 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+
+use panopticon_core::fetcher::FetcherRequest;
+use panopticon_core::jmx::model::{GcEvent, JMXConnectionSettings, JvmInfo, JvmOperation};
+
+use crate::app::{Confirmation, SourceTab};
+use crate::export;
+
+pub struct JvmTab {
+    pub info: Option<JvmInfo>,
+    pub scroll: u16,
+    pub allow_operations: bool,
+    pub heap_dump_dir: String,
+    pub status_message: Option<String>,
+    /// Results of invoked operations, most recent last
+    pub operations_log: VecDeque<(DateTime<Local>, String)>,
+    pub gc_events: VecDeque<GcEvent>,
+    /// Pauses longer than this (in ms) are flagged
+    pub gc_pause_threshold: f64,
+}
+
+impl JvmTab {
+    /// Lines of the details panel besides arguments and properties: vm details and section headers
+    pub const HEADER_LINES: usize = 8;
+    pub const MAX_LOGGED_OPERATIONS: usize = 50;
+    pub const MAX_GC_EVENTS: usize = 100;
+
+    pub fn new(settings: &JMXConnectionSettings) -> JvmTab {
+        JvmTab {
+            info: None,
+            scroll: 0,
+            allow_operations: settings.allow_operations,
+            heap_dump_dir: settings.heap_dump_dir.clone(),
+            status_message: None,
+            operations_log: VecDeque::new(),
+            gc_events: VecDeque::new(),
+            gc_pause_threshold: settings.gc_pause_threshold as f64,
+        }
+    }
+
+    pub fn append_gc_events(&mut self, events: Vec<GcEvent>) {
+        self.gc_events.extend(events);
+        while self.gc_events.len() > JvmTab::MAX_GC_EVENTS {
+            self.gc_events.pop_front();
+        }
+    }
+
+    pub fn is_long_pause(&self, event: &GcEvent) -> bool {
+        event.duration_ms > self.gc_pause_threshold
+    }
+
+    pub fn replace_jvm_info(&mut self, info: JvmInfo) {
+        self.info = Some(info);
+        self.scroll = self.scroll.min(self.max_scroll());
+    }
+
+    fn max_scroll(&self) -> u16 {
+        self.info.as_ref().map_or(0, |i| {
+            (JvmTab::HEADER_LINES + i.input_arguments.len() + i.system_properties.len()) as u16 - 1
+        })
+    }
+
+    pub fn scroll_up(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    pub fn scroll_down(&mut self, lines: u16) {
+        self.scroll = (self.scroll + lines).min(self.max_scroll());
+    }
+
+    /// What to confirm before the operation is actually invoked, if it's allowed
+    pub fn request_operation(&mut self, operation: JvmOperation) -> Option<Confirmation> {
+        if !self.allow_operations {
+            self.status_message = Some("Heap dumps and GC require --allow-jmx-operations".to_owned());
+            return None;
+        }
+        let warning = match &operation {
+            JvmOperation::DumpHeap(_) => "The JVM is paused while the heap is written, and the file takes as much space as the live objects",
+            JvmOperation::Gc => "A full collection pauses the JVM, for long on large heaps",
+        };
+        Some(Confirmation {
+            title: "JVM".to_owned(),
+            question: format!("{} on the target JVM?", operation.describe()),
+            warning: Some(warning.to_owned()),
+            request: FetcherRequest::JvmOperation(operation),
+        })
+    }
+
+    /// Heap dump to a new file in the configured directory
+    pub fn request_heap_dump(&mut self) -> Option<Confirmation> {
+        let file = export::timestamped_file_name("heap", "hprof");
+        let path = format!("{}/{}", self.heap_dump_dir.trim_end_matches('/'), file);
+        self.request_operation(JvmOperation::DumpHeap(path))
+    }
+
+    pub fn on_operation_started(&mut self, operation: &JvmOperation) {
+        self.status_message = Some(format!("{}...", operation.describe()));
+    }
+
+    pub fn on_operation_result(&mut self, operation: JvmOperation, result: Result<(), String>) {
+        let message = match result {
+            Ok(_) => format!("{} succeeded", operation.describe()),
+            Err(e) => format!("{} failed: {}", operation.describe(), e),
+        };
+        if self.operations_log.len() >= JvmTab::MAX_LOGGED_OPERATIONS {
+            self.operations_log.pop_front();
+        }
+        self.operations_log.push_back((Local::now(), message));
+        self.status_message = None;
+    }
+}
+
+impl SourceTab for JvmTab {
+    fn on_up(&mut self) {
+        self.scroll_up(1)
+    }
+
+    fn on_down(&mut self) {
+        self.scroll_down(1)
+    }
+
+    fn uses_jmx(&self) -> bool {
+        true
+    }
+}
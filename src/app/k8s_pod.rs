@@ -0,0 +1,43 @@
+use panopticon_core::k8s::model::{K8sPodSettings, PodMetrics};
+use panopticon_core::prometheus::model::{MetricSample, PrometheusMetrics};
+
+use crate::app::SourceTab;
+use crate::app::prometheus::PrometheusTab;
+
+pub struct K8sPodTab {
+    pub pod: String,
+    /// CPU and memory charts, with a series per container
+    pub charts: PrometheusTab,
+    pub metrics: Option<PodMetrics>,
+    pub error: Option<String>,
+}
+
+impl K8sPodTab {
+    pub fn new(settings: &K8sPodSettings) -> K8sPodTab {
+        K8sPodTab {
+            pod: settings.pod.to_string(),
+            charts: PrometheusTab::new(vec!["CPU (cores)".to_owned(), "Memory (MB)".to_owned()]),
+            metrics: None,
+            error: None,
+        }
+    }
+
+    pub fn append_metrics(&mut self, m: PodMetrics) {
+        let samples = m.containers.iter()
+            .flat_map(|c| vec![
+                MetricSample { selector: 0, series: c.name.to_owned(), value: c.cpu_cores, counter: false },
+                MetricSample { selector: 1, series: c.name.to_owned(), value: c.memory_bytes / (1024.0 * 1024.0), counter: false },
+            ])
+            .collect();
+        self.charts.append_metrics(PrometheusMetrics { timestamp: m.timestamp, samples, metrics: vec![], errors: vec![] });
+        self.metrics = Some(m);
+        self.error = None;
+    }
+
+    /// Keeps showing the last metrics, as the pod may be restarting
+    pub fn on_error(&mut self, e: String) {
+        self.error = Some(e);
+    }
+}
+
+impl SourceTab for K8sPodTab {}
@@ -0,0 +1,31 @@
+use std::collections::VecDeque;
+
+use panopticon_core::jmx::model::KafkaMetrics;
+
+use crate::app::SourceTab;
+use crate::widgets::viewport;
+
+pub struct KafkaTab {
+    pub metrics: VecDeque<KafkaMetrics>,
+}
+
+impl KafkaTab {
+    pub const MAX_KAFKA_MEASURES: usize = viewport::HISTORY_SAMPLES;
+
+    pub fn new() -> KafkaTab {
+        KafkaTab { metrics: VecDeque::new() }
+    }
+
+    pub fn append_kafka_metrics(&mut self, m: KafkaMetrics) {
+        if self.metrics.len() > KafkaTab::MAX_KAFKA_MEASURES {
+            self.metrics.pop_front();
+        }
+        self.metrics.push_back(m);
+    }
+}
+
+impl SourceTab for KafkaTab {
+    fn uses_jmx(&self) -> bool {
+        true
+    }
+}
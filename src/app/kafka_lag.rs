@@ -0,0 +1,45 @@
+use panopticon_core::kafka::model::{KafkaLag, KafkaLagSettings};
+use panopticon_core::prometheus::model::{MetricSample, PrometheusMetrics};
+
+use crate::app::SourceTab;
+use crate::app::prometheus::PrometheusTab;
+
+pub struct KafkaLagTab {
+    /// A chart for each of the groups, with a series per topic partition
+    pub charts: PrometheusTab,
+    pub lag: Option<KafkaLag>,
+    pub error: Option<String>,
+}
+
+impl KafkaLagTab {
+    pub fn new(settings: &KafkaLagSettings) -> KafkaLagTab {
+        KafkaLagTab { charts: PrometheusTab::new(settings.groups.clone()), lag: None, error: None }
+    }
+
+    pub fn append_lag(&mut self, l: KafkaLag) {
+        let samples = l.partitions.iter()
+            .filter_map(|p| Some(MetricSample {
+                selector: self.charts.selectors.iter().position(|g| g == &p.group)?,
+                series: format!("{}/{}", p.topic, p.partition),
+                value: p.lag() as f64,
+                counter: false,
+            }))
+            .collect();
+        self.charts.append_metrics(PrometheusMetrics { timestamp: l.timestamp, samples, metrics: vec![], errors: vec![] });
+        self.lag = Some(l);
+        self.error = None;
+    }
+
+    pub fn on_error(&mut self, e: String) {
+        self.error = Some(e);
+    }
+
+    /// Total lag of every group, across all its partitions
+    pub fn group_totals(&self) -> Vec<(String, i64)> {
+        self.charts.selectors.iter()
+            .map(|g| (g.to_owned(), self.lag.as_ref().map_or(0, |l| l.partitions.iter().filter(|p| &p.group == g).map(|p| p.lag()).sum())))
+            .collect()
+    }
+}
+
+impl SourceTab for KafkaLagTab {}
@@ -0,0 +1,64 @@
+use panopticon_core::fetcher::FetcherRequest;
+use panopticon_core::jmx::model::JMXLogger;
+
+use crate::app::{SourceTab, StatefulList};
+
+pub struct LoggersTab {
+    pub loggers: StatefulList<JMXLogger>,
+    pub allow_operations: bool,
+    pub status_message: Option<String>,
+}
+
+impl LoggersTab {
+    pub fn new(allow_operations: bool) -> LoggersTab {
+        LoggersTab { loggers: StatefulList::with_items(vec![]), allow_operations, status_message: None }
+    }
+
+    pub fn replace_loggers(&mut self, loggers: Vec<JMXLogger>) {
+        if self.loggers.state.selected().is_some_and(|i| i >= loggers.len()) {
+            self.loggers.state.select(None);
+        }
+        self.loggers.items = loggers;
+    }
+
+    pub fn select_prev_logger(&mut self) {
+        self.loggers.previous();
+    }
+
+    pub fn select_next_logger(&mut self) {
+        self.loggers.next();
+    }
+
+    /// Builds a request changing level of the selected logger, if operations are allowed
+    pub fn request_level(&mut self, level: &str) -> Option<FetcherRequest> {
+        if !self.allow_operations {
+            self.status_message = Some("Changing logger levels requires --allow-jmx-operations".to_owned());
+            return None;
+        }
+        let items = &self.loggers.items;
+        let logger = self.loggers.state.selected().and_then(|i| items.get(i))?;
+        self.status_message = Some(format!("Setting level of {} to {}...", logger.name, level));
+        Some(FetcherRequest::SetLoggerLevel(logger.clone(), level.to_owned()))
+    }
+
+    pub fn on_level_result(&mut self, logger: JMXLogger, level: String, result: Result<(), String>) {
+        self.status_message = Some(match result {
+            Ok(_) => format!("Level of {} set to {}", logger.name, level),
+            Err(e) => format!("Failed to set level of {} to {}: {}", logger.name, level, e),
+        });
+    }
+}
+
+impl SourceTab for LoggersTab {
+    fn on_up(&mut self) {
+        self.select_prev_logger()
+    }
+
+    fn on_down(&mut self) {
+        self.select_next_logger()
+    }
+
+    fn uses_jmx(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,131 @@
+use std::collections::VecDeque;
+
+use panopticon_core::logs::model::{LogLevel, LogLine, LogLines, LogSettings};
+
+use crate::app::SourceTab;
+
+pub struct LogsTab {
+    pub source: String,
+    pub lines: VecDeque<LogLine>,
+    /// Lines received while paused, added once resumed
+    pending: Vec<LogLine>,
+    pub paused: bool,
+    /// Only lines containing the text are shown, ignoring case
+    pub search: String,
+    /// Set while the search text is being typed
+    pub searching: bool,
+    /// Number of lines scrolled back from the newest one
+    pub scroll: usize,
+    pub error: Option<String>,
+}
+
+impl LogsTab {
+    pub const MAX_LOG_LINES: usize = 5000;
+
+    pub fn new(settings: &LogSettings) -> LogsTab {
+        LogsTab {
+            source: settings.source.to_owned(),
+            lines: VecDeque::new(),
+            pending: vec![],
+            paused: false,
+            search: "".to_owned(),
+            searching: false,
+            scroll: 0,
+            error: None,
+        }
+    }
+
+    pub fn append_lines(&mut self, l: LogLines) {
+        let mut level = self.pending.last().or_else(|| self.lines.back()).and_then(|l| l.level);
+        for text in l.lines {
+            level = LogLevel::detect(&text).or(level);
+            self.pending.push(LogLine { lowercase: text.to_lowercase(), text, level });
+        }
+        if self.pending.len() > LogsTab::MAX_LOG_LINES {
+            self.pending.drain(..self.pending.len() - LogsTab::MAX_LOG_LINES);
+        }
+        if !self.paused {
+            self.flush_pending();
+        }
+        self.error = l.error.map(|e| e.message);
+    }
+
+    fn flush_pending(&mut self) {
+        // lines scrolled back to stay in place as new ones come in
+        if self.scroll > 0 {
+            let search = self.search.to_lowercase();
+            self.scroll += self.pending.iter().filter(|l| LogsTab::matches_search(l, &search)).count();
+        }
+        self.lines.extend(self.pending.drain(..));
+        if self.lines.len() > LogsTab::MAX_LOG_LINES {
+            self.lines.drain(..self.lines.len() - LogsTab::MAX_LOG_LINES);
+        }
+        self.scroll = self.scroll.min(self.shown_lines().len().saturating_sub(1));
+    }
+
+    /// Lines matching the search, oldest first
+    pub fn shown_lines(&self) -> Vec<&LogLine> {
+        let search = self.search.to_lowercase();
+        self.lines.iter().filter(|l| LogsTab::matches_search(l, &search)).collect()
+    }
+
+    fn matches_search(line: &LogLine, lowercase_search: &str) -> bool {
+        line.lowercase.contains(lowercase_search)
+    }
+
+    /// Number of lines received since pausing
+    pub fn pending_lines(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        if !self.paused {
+            self.flush_pending();
+        }
+    }
+
+    pub fn start_search(&mut self) {
+        self.searching = true;
+        self.search.clear();
+        self.scroll = 0;
+    }
+
+    pub fn on_search_char(&mut self, c: char) {
+        self.search.push(c);
+        self.scroll = 0;
+    }
+
+    pub fn on_search_backspace(&mut self) {
+        self.search.pop();
+        self.scroll = 0;
+    }
+
+    pub fn finish_search(&mut self) {
+        self.searching = false;
+    }
+
+    pub fn cancel_search(&mut self) {
+        self.searching = false;
+        self.search.clear();
+        self.scroll = 0;
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll = (self.scroll + lines).min(self.shown_lines().len().saturating_sub(1));
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+}
+
+impl SourceTab for LogsTab {
+    fn on_up(&mut self) {
+        self.scroll_up(1)
+    }
+
+    fn on_down(&mut self) {
+        self.scroll_down(1)
+    }
+}
@@ -0,0 +1,3042 @@
+pub mod actor_tree;
+pub mod actuator;
+pub mod cassandra;
+pub mod cluster;
+pub mod commands;
+pub mod dashboard;
+pub mod docker;
+pub mod errors;
+pub mod jfr;
+pub mod json_metrics;
+pub mod jvm;
+pub mod k8s_pod;
+pub mod kafka;
+pub mod kafka_lag;
+pub mod loggers;
+pub mod logs;
+pub mod otlp;
+pub mod postgres;
+pub mod prometheus;
+pub mod proxy;
+pub mod rabbitmq;
+pub mod redis;
+pub mod sharding;
+pub mod slick;
+pub mod sql_queries;
+pub mod statsd;
+pub mod thread_pools;
+pub mod traces;
+pub mod zmx;
+
+use std::borrow::Cow;
+use std::collections::{HashSet, VecDeque};
+use std::iter::Iterator;
+use std::mem::Discriminant;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+
+use tui::layout::Rect;
+use tui::widgets::ListState;
+
+use panopticon_core::error::FetchError;
+use panopticon_core::fetcher::{FetcherRequest, FetcherResponse, JMXConnectionStatus, Sources};
+use panopticon_core::jmx::model::{HikariMetrics, HikariOperation, JMXLogger, JvmOperation, LOG_LEVELS, SlickMetrics, ThreadPoolMetrics, ThreadPoolsMetrics};
+use panopticon_core::prometheus::model::{MetricSample, PrometheusMetrics};
+use panopticon_core::zio::model::FiberCount;
+
+use crate::alerts::model::{AlertEvent, MetricValue, NewSamples, TimedValue};
+use crate::alerts::notify;
+use crate::alerts::rules::AlertRules;
+use crate::app::actor_tree::{ActorCountSample, AkkaActorTreeTab};
+use crate::app::actuator::ActuatorTab;
+use crate::app::cassandra::CassandraTab;
+use crate::app::cluster::ClusterTab;
+use crate::app::commands::CommandsTab;
+use crate::app::dashboard::DashboardTab;
+use crate::app::docker::DockerTab;
+use crate::app::errors::ErrorLogTab;
+use crate::app::jfr::JfrTab;
+use crate::app::json_metrics::JsonMetricsTab;
+use crate::app::jvm::JvmTab;
+use crate::app::k8s_pod::K8sPodTab;
+use crate::app::kafka::KafkaTab;
+use crate::app::kafka_lag::KafkaLagTab;
+use crate::app::loggers::LoggersTab;
+use crate::app::logs::LogsTab;
+use crate::app::otlp::OtlpTab;
+use crate::app::postgres::PostgresTab;
+use crate::app::prometheus::PrometheusTab;
+use crate::app::proxy::ProxyTab;
+use crate::app::rabbitmq::RabbitMqTab;
+use crate::app::redis::RedisTab;
+use crate::app::sharding::ShardingTab;
+use crate::app::slick::SlickTab;
+use crate::app::sql_queries::SqlQueriesTab;
+use crate::app::statsd::StatsdTab;
+use crate::app::thread_pools::ThreadPoolsTab;
+use crate::app::traces::TracesTab;
+use crate::app::zmx::ZMXTab;
+use crate::config::Config;
+use crate::exporter::MetricsExporter;
+use crate::grafana::SeriesStore;
+use crate::history::{History, SavedTick};
+use crate::influx::InfluxSink;
+use crate::statsd_forwarder::StatsdForwarder;
+use crate::ui::Redraw;
+use crate::widgets::mouse;
+use crate::widgets::split::Splits;
+use crate::widgets::viewport::Viewport;
+
+#[derive(Clone)]
+#[allow(clippy::upper_case_acronyms)]
+pub enum TabKind {
+    Overview,
+    /// A tab composed in the config file, by its index there
+    Dashboard(usize),
+    Alerts,
+    ZMX,
+    Slick,
+    Kafka,
+    Cassandra,
+    Loggers,
+    ThreadPools,
+    Jvm,
+    Jfr,
+    AkkaActorTree,
+    Sharding,
+    Cluster,
+    Prometheus,
+    PromQL,
+    Actuator,
+    JsonMetrics,
+    Commands,
+    SqlQueries,
+    Statsd,
+    Logs,
+    Postgres,
+    KafkaLag,
+    Redis,
+    Docker,
+    K8sPod,
+    RabbitMq,
+    Proxy,
+    Traces,
+    Otlp,
+    Errors,
+}
+
+/// Keys available on every tab
+pub const GLOBAL_KEYBINDINGS: [(&str, &str); 15] = [
+    ("<Left>/<Right>", "switch tabs"),
+    ("<1>-<9>", "jump to a tab, except on the Slick tab"),
+    ("<Shift+Left>/<Shift+Right>", "move the tab left/right"),
+    ("<+>/<->", "zoom charts in/out"),
+    ("<[>/<]>", "pan charts back/forward in time"),
+    ("<0>", "reset the chart window"),
+    ("<<>/<>>", "narrow/widen lists next to dumps"),
+    ("<{>/<}>", "shorten/lengthen charts above tables"),
+    ("<m>", "maximize the dump, logs or a single chart, and restore the layout"),
+    ("<z>", "freeze/unfreeze the display, data is still collected meanwhile"),
+    ("<a>", "mark the current time on all charts, <Enter> to finish typing the label"),
+    ("<X>", "export the current view as an HTML and a text report"),
+    ("<?>", "show/hide this help"),
+    ("<Esc>", "close this help"),
+    ("<q>", "quit"),
+];
+
+impl TabKind {
+    /// Name of the source the tab shows, as listed in the status bar
+    pub fn source(&self) -> Option<&'static str> {
+        match self {
+            TabKind::ZMX => Some("Fibers"),
+            TabKind::Slick | TabKind::Kafka | TabKind::Cassandra | TabKind::Loggers | TabKind::ThreadPools | TabKind::Jvm | TabKind::Cluster => Some("JMX"),
+            TabKind::Jfr => Some("JFR"),
+            TabKind::AkkaActorTree => Some("Akka"),
+            TabKind::Sharding => Some("Sharding"),
+            TabKind::Prometheus => Some("Prometheus"),
+            TabKind::PromQL => Some("PromQL"),
+            TabKind::Actuator => Some("Actuator"),
+            TabKind::JsonMetrics => Some("JSON"),
+            TabKind::Commands => Some("Commands"),
+            TabKind::SqlQueries => Some("SQL"),
+            TabKind::Statsd => Some("StatsD"),
+            TabKind::Logs => Some("Logs"),
+            TabKind::Postgres => Some("PostgreSQL"),
+            TabKind::KafkaLag => Some("Kafka lag"),
+            TabKind::Redis => Some("Redis"),
+            TabKind::Docker => Some("Docker"),
+            TabKind::K8sPod => Some("Kubernetes"),
+            TabKind::RabbitMq => Some("RabbitMQ"),
+            TabKind::Proxy => Some("Proxy"),
+            TabKind::Traces => Some("Traces"),
+            TabKind::Otlp => Some("OTLP"),
+            TabKind::Overview | TabKind::Dashboard(_) | TabKind::Alerts | TabKind::Errors => None,
+        }
+    }
+
+    /// Keys specific to the tab, shown in the help overlay
+    pub fn keybindings(&self) -> Vec<(&'static str, &'static str)> {
+        match self {
+            TabKind::ZMX => vec![
+                ("<Enter>", "take a fiber dump"),
+                ("<Up>/<Down>", "select a fiber"),
+                ("<PageUp>/<PageDown>", "scroll the fiber dump"),
+                ("<n>", "show/hide line numbers in the fiber dump"),
+            ],
+            TabKind::Slick => vec![
+                ("<Enter>", "monitor the DB pool picked from the list"),
+                ("<Up>/<Down>", "pick a DB pool, or scroll JMX notifications"),
+                ("<p>", "pick another DB pool"),
+                ("<1>-<7>", "show/hide series"),
+                ("<x>", "export collected metrics as CSV"),
+                ("<e>/<s>/<r>", "soft evict connections/suspend/resume the pool, if operations are allowed"),
+                ("<y>/<n>", "confirm/cancel an operation"),
+                ("<Esc>", "cancel an operation or close the pool picker"),
+            ],
+            TabKind::Loggers => vec![
+                ("<Enter>", "reload loggers"),
+                ("<Up>/<Down>", "select a logger"),
+                ("<t>/<d>/<i>/<w>/<e>", "set TRACE/DEBUG/INFO/WARN/ERROR level, if operations are allowed"),
+            ],
+            TabKind::Jvm => vec![
+                ("<Enter>", "reload JVM runtime info"),
+                ("<Up>/<Down>", "scroll"),
+                ("<PageUp>/<PageDown>", "scroll by 10 lines"),
+                ("<h>/<g>", "dump heap/run GC, if operations are allowed"),
+                ("<y>/<n>", "confirm/cancel an operation"),
+                ("<Esc>", "cancel an operation"),
+            ],
+            TabKind::AkkaActorTree => vec![
+                ("<Enter>", "reload the actor tree"),
+                ("<Esc>", "cancel reloading the actor tree"),
+                ("<Up>/<Down>", "select an actor"),
+                ("<Space>", "collapse/expand the selected actor"),
+                ("<c>/<e>", "collapse/expand all actors"),
+                ("<f>", "show/hide excluded actors"),
+                ("<w>", "watch the selected actor"),
+                ("<x>", "export the actor tree as DOT and JSON files"),
+                ("<PageUp>/<PageDown>", "scroll dead letters"),
+            ],
+            TabKind::Actuator => vec![
+                ("<Enter>", "take a thread dump"),
+                ("<Up>/<Down>", "select a thread"),
+                ("<PageUp>/<PageDown>", "scroll the thread dump"),
+                ("<n>", "show/hide line numbers in the thread dump"),
+            ],
+            TabKind::Logs => vec![
+                ("</>", "search, <Enter> to finish typing"),
+                ("<Esc>", "clear the search"),
+                ("<p>", "pause/resume tailing"),
+                ("<Up>/<Down>", "scroll"),
+                ("<PageUp>/<PageDown>", "scroll by 10 lines"),
+            ],
+            TabKind::Traces => vec![
+                ("<Up>/<Down>", "select a trace"),
+                ("<PageUp>/<PageDown>", "scroll the spans"),
+                ("<n>", "show/hide line numbers in the spans"),
+            ],
+            TabKind::Kafka => vec![],
+            TabKind::Cassandra => vec![],
+            TabKind::ThreadPools => vec![],
+            TabKind::Jfr => vec![],
+            TabKind::Sharding => vec![],
+            TabKind::Cluster => vec![],
+            TabKind::Prometheus => vec![],
+            TabKind::PromQL => vec![],
+            TabKind::JsonMetrics => vec![],
+            TabKind::Commands => vec![],
+            TabKind::SqlQueries => vec![],
+            TabKind::Statsd => vec![],
+            TabKind::Postgres => vec![],
+            TabKind::KafkaLag => vec![],
+            TabKind::Redis => vec![],
+            TabKind::Docker => vec![],
+            TabKind::K8sPod => vec![],
+            TabKind::RabbitMq => vec![],
+            TabKind::Proxy => vec![],
+            TabKind::Overview => vec![],
+            TabKind::Dashboard(_) => vec![],
+            TabKind::Alerts => vec![],
+            TabKind::Otlp => vec![],
+            TabKind::Errors => vec![
+                ("<Up>/<Down>", "scroll"),
+                ("<PageUp>/<PageDown>", "scroll by 10 errors"),
+                ("<x>", "export the errors as CSV"),
+            ],
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Tab<'a> {
+    pub kind: TabKind,
+    pub title: Cow<'a, str>,
+}
+
+#[derive(Clone)]
+pub struct TabsState<'a> {
+    pub tabs: Vec<Tab<'a>>,
+    pub index: usize,
+    /// Where the titles were last drawn, for mouse clicks
+    pub title_areas: Vec<Rect>,
+}
+
+impl<'a> TabsState<'a> {
+    pub fn new(tabs: Vec<Tab<'a>>) -> TabsState<'a> {
+        TabsState { tabs, index: 0, title_areas: vec![] }
+    }
+    pub fn next(&mut self) {
+        if !self.tabs.is_empty() {
+            self.index = (self.index + 1) % self.tabs.len();
+        }
+    }
+
+    pub fn previous(&mut self) {
+        if self.tabs.is_empty() {
+            return;
+        }
+        if self.index > 0 {
+            self.index -= 1;
+        } else {
+            self.index = self.tabs.len() - 1;
+        }
+    }
+
+    pub fn current(&self) -> &Tab<'a> {
+        &self.tabs[self.index]
+    }
+
+    pub fn select(&mut self, index: usize) {
+        if index < self.tabs.len() {
+            self.index = index;
+        }
+    }
+
+    /// Swaps the current tab with its neighbour, keeping it current
+    pub fn move_current(&mut self, forward: bool) {
+        let other = if forward { self.index + 1 } else { self.index.wrapping_sub(1) };
+        if other < self.tabs.len() {
+            self.tabs.swap(self.index, other);
+            self.index = other;
+        }
+    }
+
+    pub fn tab_at(&self, column: u16, row: u16) -> Option<usize> {
+        self.title_areas.iter().position(|a| mouse::contains(*a, column, row))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToastLevel {
+    Info,
+    Warning,
+}
+
+/// A heavyweight or destructive action waiting to be confirmed, shown in a modal dialog
+pub struct Confirmation {
+    pub title: String,
+    pub question: String,
+    /// What the action costs or risks
+    pub warning: Option<String>,
+    pub request: FetcherRequest,
+}
+
+/// A short lived notification about something that happened, that doesn't need any action
+pub struct Toast {
+    pub message: String,
+    pub level: ToastLevel,
+    expires: Instant,
+}
+
+/// Timings of the event loop and the fetcher, shown in the debug overlay
+pub struct DebugStats {
+    pub shown: bool,
+    /// Durations of the latest frames, the latest last
+    pub frame_times: VecDeque<Duration>,
+    /// Events waiting to be handled once the last one was received
+    pub queued_events: usize,
+    /// Duration of the latest fetch from every source, in the order they were first fetched from
+    pub fetch_times: Vec<(&'static str, Duration)>,
+}
+
+impl DebugStats {
+    pub const MAX_FRAMES: usize = 100;
+
+    pub fn new() -> DebugStats {
+        DebugStats { shown: false, frame_times: VecDeque::new(), queued_events: 0, fetch_times: vec![] }
+    }
+
+    pub fn toggle(&mut self) {
+        self.shown = !self.shown;
+    }
+
+    pub fn on_frame(&mut self, duration: Duration) {
+        if self.frame_times.len() == DebugStats::MAX_FRAMES {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(duration);
+    }
+
+    pub fn on_fetch(&mut self, source: &'static str, duration: Duration) {
+        match self.fetch_times.iter_mut().find(|(s, _)| *s == source) {
+            Some(t) => t.1 = duration,
+            None => self.fetch_times.push((source, duration)),
+        }
+    }
+
+    /// Average and longest of the latest frames
+    pub fn frame_stats(&self) -> Option<(Duration, Duration)> {
+        let max = *self.frame_times.iter().max()?;
+        Some((self.frame_times.iter().sum::<Duration>() / self.frame_times.len() as u32, max))
+    }
+}
+
+/// How reading from a source went lately, shown in the status bar
+pub struct SourceStatus {
+    pub name: &'static str,
+    pub last_success: Option<DateTime<Local>>,
+    /// Error of the latest read, if it failed
+    pub error: Option<FetchError>,
+    /// When the reads started failing, if the latest one did
+    pub failing_since: Option<Instant>,
+}
+
+pub struct StatefulList<T> {
+    pub state: ListState,
+    pub items: Vec<T>,
+    // where the items were last drawn, tui keeps its own offset private
+    area: Option<Rect>,
+    offset: usize,
+}
+
+impl<T> StatefulList<T> {
+    pub fn with_items(items: Vec<T>) -> StatefulList<T> {
+        StatefulList {
+            state: ListState::default(),
+            items,
+            area: None,
+            offset: 0,
+        }
+    }
+
+    /// Remembers the area the items are about to be drawn in, scrolled the way tui does to keep the selection visible
+    pub fn on_draw(&mut self, area: Rect) {
+        let height = area.height as usize;
+        self.offset = match self.state.selected() {
+            Some(s) if s + 1 >= height + self.offset => (s + 1).saturating_sub(height),
+            Some(s) if s < self.offset => s,
+            Some(_) => self.offset,
+            None => 0,
+        };
+        self.area = Some(area);
+    }
+
+    /// Index of the first item drawn
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Indices of the items drawn, so that only those are built however long the list is
+    pub fn visible(&self) -> Range<usize> {
+        let height = self.area.map_or(0, |a| a.height as usize);
+        self.offset.min(self.items.len())..(self.offset + height).min(self.items.len())
+    }
+
+    /// Selection among the items drawn, for tui to draw just those without scrolling them
+    pub fn visible_state(&self) -> ListState {
+        let mut state = ListState::default();
+        state.select(self.state.selected().filter(|s| self.visible().contains(s)).map(|s| s - self.offset));
+        state
+    }
+
+    pub fn contains(&self, column: u16, row: u16) -> bool {
+        self.area.is_some_and(|a| mouse::contains(a, column, row))
+    }
+
+    /// Selects the item drawn at the position, if there's one
+    pub fn select_at(&mut self, column: u16, row: u16) -> bool {
+        let i = match self.area {
+            Some(a) if mouse::contains(a, column, row) => self.offset + (row - a.top()) as usize,
+            _ => return false,
+        };
+        if i < self.items.len() {
+            self.state.select(Some(i));
+        }
+        i < self.items.len()
+    }
+
+    pub fn next(&mut self) {
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i >= self.items.len() - 1 {
+                    0
+                } else {
+                    i + 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        let i = match self.state.selected() {
+            Some(i) => {
+                if i == 0 {
+                    self.items.len() - 1
+                } else {
+                    i - 1
+                }
+            }
+            None => 0,
+        };
+        self.state.select(Some(i));
+    }
+}
+
+/// What the app asks of the tab of every source, so that a source's behaviour lives in its own module
+pub trait SourceTab {
+    /// Selects the previous item, or scrolls up
+    fn on_up(&mut self) {}
+
+    /// Selects the next item, or scrolls down
+    fn on_down(&mut self) {}
+
+    /// Whether the tab shows what's read over JMX, and so is left behind while JMX reconnects
+    fn uses_jmx(&self) -> bool {
+        false
+    }
+
+    /// What the firing alert of the tab is about, if it has one
+    fn alert_message(&self) -> Option<String> {
+        None
+    }
+}
+
+pub struct App<'a> {
+    pub title: &'a str,
+    pub should_quit: bool,
+    pub exit_reason: Option<FetchError>,
+    pub tabs: TabsState<'a>,
+    pub zmx: Option<ZMXTab>,
+    pub slick: Option<SlickTab>,
+    pub kafka: Option<KafkaTab>,
+    pub cassandra: Option<CassandraTab>,
+    pub loggers: Option<LoggersTab>,
+    pub thread_pools: Option<ThreadPoolsTab>,
+    pub jvm: Option<JvmTab>,
+    pub jfr: Option<JfrTab>,
+    pub actor_tree: Option<AkkaActorTreeTab>,
+    pub sharding: Option<ShardingTab>,
+    pub cluster: Option<ClusterTab>,
+    pub prometheus: Option<PrometheusTab>,
+    pub promql: Option<PrometheusTab>,
+    pub actuator: Option<ActuatorTab>,
+    pub json_metrics: Option<JsonMetricsTab>,
+    pub commands: Option<CommandsTab>,
+    pub sql_queries: Option<SqlQueriesTab>,
+    pub statsd: Option<StatsdTab>,
+    pub logs: Option<LogsTab>,
+    pub postgres: Option<PostgresTab>,
+    pub kafka_lag: Option<KafkaLagTab>,
+    pub redis: Option<RedisTab>,
+    pub docker: Option<DockerTab>,
+    pub k8s_pod: Option<K8sPodTab>,
+    pub rabbitmq: Option<RabbitMqTab>,
+    pub proxy: Option<ProxyTab>,
+    pub traces: Option<TracesTab>,
+    pub otlp: Option<OtlpTab>,
+    pub dashboards: Vec<DashboardTab>,
+    /// Values of every metric as of the last tick, shown by value widgets of dashboards
+    pub dashboard_values: Vec<MetricValue>,
+    pub errors: ErrorLogTab,
+    /// Rules of the config file, if it has any
+    pub alert_rules: Option<AlertRules>,
+    /// Requests to the fetcher triggered by user input, sent out by the event loop
+    pub fetcher_requests: Vec<FetcherRequest>,
+    /// Requests polled every tick that weren't answered yet, by kind and index
+    in_flight: HashSet<(Discriminant<FetcherRequest>, usize)>,
+    /// Alerts to post to the webhooks of their rules, with the URL of the hook, sent out by the event loop
+    pub alert_webhooks: Vec<(String, AlertEvent)>,
+    /// Whether an alert rings the terminal bell, rung by the event loop, which owns the terminal
+    pub bell: bool,
+    /// Toggled every tick to make titles of tabs with firing alerts flash
+    pub flash: bool,
+    /// Set while the lost JMX connection is being re-established
+    pub jmx_reconnecting: Option<String>,
+    /// Time window shown by all the metric charts
+    pub viewport: Viewport,
+    /// Sizes of the panes, changed from the layout of the tabs
+    pub splits: Splits,
+    /// Whether the main panel of the tab fills the terminal
+    pub maximized: bool,
+    /// Chart shown when a tab of charts is maximized
+    pub maximized_chart: usize,
+    /// Whether the keybindings of the current tab are shown over it
+    pub show_help: bool,
+    /// Whether fiber dumps, thread dumps and spans are shown with line numbers
+    pub line_numbers: bool,
+    /// Whether box-drawing, block and braille characters are drawn as ASCII, for terminals whose fonts lack them
+    pub ascii: bool,
+    /// Whether colors are drawn with a palette that doesn't rely on telling red from green
+    pub high_contrast: bool,
+    /// Sources of the tabs, in their order, followed by any other ones in the order they first responded
+    pub sources: Vec<SourceStatus>,
+    /// Notifications shown until they expire, the latest last
+    pub toasts: VecDeque<Toast>,
+    /// Action waiting for <y> or <n>, the dialog takes all the keys until then
+    pub confirmation: Option<Confirmation>,
+    pub debug: DebugStats,
+    /// How often sources are polled, set once the event loop starts
+    pub tick_rate: Option<Duration>,
+    /// Whether the screen keeps showing what it showed when frozen, while fetched data is still taken in
+    pub frozen: bool,
+    /// Label of the chart marker being typed
+    pub marker_label: Option<String>,
+    /// Whether the view is to be exported as a report, which the event loop draws
+    pub report_requested: bool,
+    /// Serves the latest values on a Prometheus endpoint, if asked to
+    pub metrics_exporter: Option<MetricsExporter>,
+    /// Saves the values of every tick to a file, if asked to
+    pub history: Option<History>,
+    /// Writes the values of every tick to InfluxDB, if asked to
+    pub influx: Option<InfluxSink>,
+    /// Sends the values of every tick to a statsd server, if asked to
+    pub statsd_forwarder: Option<StatsdForwarder>,
+    /// Keeps the values of every tick for Grafana when serving the API
+    pub grafana_series: Option<SeriesStore>,
+    /// Samples already sent to InfluxDB, statsd and the Grafana datasource
+    sent_samples: NewSamples,
+    /// Responses of sources taken in since frozen, which aren't shown yet
+    frozen_updates: usize,
+}
+
+impl<'a> App<'a> {
+    const TOAST_DURATION: Duration = Duration::from_secs(5);
+    const MAX_TOASTS: usize = 4;
+    /// How long a source keeps failing before running without the TUI gives up on it
+    pub const SOURCE_FAILURE_GRACE: Duration = Duration::from_secs(30);
+
+    pub fn new(title: &'a str, sources: &Sources, config: &Config) -> App<'a> {
+        let Sources {
+            zio_zmx: zio_zmx_addr, jmx, akka, sharding, jfr, prometheus, promql, actuator, statsd, logs, postgres, kafka_lag,
+            redis, docker, k8s_pod, rabbitmq, proxy, traces, otlp, cats_effect, json_metrics, commands, sql_queries,
+        } = sources.clone();
+        let mut tabs: Vec<Tab> = vec![];
+
+        if zio_zmx_addr.is_some() {
+            tabs.push(Tab { kind: TabKind::ZMX, title: "ZIO".into() })
+        } else if cats_effect.is_some() {
+            tabs.push(Tab { kind: TabKind::ZMX, title: "Cats Effect".into() })
+        }
+
+        let has_slick = jmx.as_ref().is_some_and(|j| j.has_slick());
+        let has_kafka = jmx.as_ref().is_some_and(|j| j.kafka);
+        let has_cassandra = jmx.as_ref().is_some_and(|j| j.cassandra_domain.is_some());
+        let has_dispatchers = jmx.as_ref().is_some_and(|j| j.akka_dispatchers.is_some());
+        let has_loggers = jmx.as_ref().is_some_and(|j| j.loggers);
+        let has_thread_pools = jmx.as_ref().is_some_and(|j| !j.thread_pools.is_empty());
+        let has_jvm_info = jmx.as_ref().is_some_and(|j| j.jvm_info);
+        let has_cluster = jmx.as_ref().is_some_and(|j| j.akka_cluster);
+
+        if has_slick {
+            tabs.push(Tab { kind: TabKind::Slick, title: "Slick".into() })
+        }
+
+        if has_kafka {
+            tabs.push(Tab { kind: TabKind::Kafka, title: "Kafka".into() })
+        }
+
+        if has_cassandra {
+            tabs.push(Tab { kind: TabKind::Cassandra, title: "Cassandra".into() })
+        }
+
+        if has_loggers {
+            tabs.push(Tab { kind: TabKind::Loggers, title: "Loggers".into() })
+        }
+
+        if has_thread_pools {
+            tabs.push(Tab { kind: TabKind::ThreadPools, title: "Thread pools".into() })
+        }
+
+        if has_jvm_info {
+            tabs.push(Tab { kind: TabKind::Jvm, title: "JVM".into() })
+        }
+
+        if jfr.is_some() {
+            tabs.push(Tab { kind: TabKind::Jfr, title: "JFR".into() })
+        }
+
+        if let Some(a) = &akka {
+            tabs.push(Tab { kind: TabKind::AkkaActorTree, title: a.flavor.name().into() })
+        }
+
+        if sharding.is_some() {
+            tabs.push(Tab { kind: TabKind::Sharding, title: "Sharding".into() })
+        }
+
+        if has_cluster {
+            tabs.push(Tab { kind: TabKind::Cluster, title: "Cluster".into() })
+        }
+
+        if prometheus.is_some() {
+            tabs.push(Tab { kind: TabKind::Prometheus, title: "Prometheus".into() })
+        }
+
+        if promql.is_some() {
+            tabs.push(Tab { kind: TabKind::PromQL, title: "PromQL".into() })
+        }
+
+        if actuator.is_some() {
+            tabs.push(Tab { kind: TabKind::Actuator, title: "Actuator".into() })
+        }
+
+        if !json_metrics.is_empty() {
+            tabs.push(Tab { kind: TabKind::JsonMetrics, title: "JSON".into() })
+        }
+
+        if !commands.is_empty() {
+            tabs.push(Tab { kind: TabKind::Commands, title: "Commands".into() })
+        }
+
+        if !sql_queries.is_empty() {
+            tabs.push(Tab { kind: TabKind::SqlQueries, title: "SQL".into() })
+        }
+
+        if statsd.is_some() {
+            tabs.push(Tab { kind: TabKind::Statsd, title: "StatsD".into() })
+        }
+
+        if logs.is_some() {
+            tabs.push(Tab { kind: TabKind::Logs, title: "Logs".into() })
+        }
+
+        if postgres.is_some() {
+            tabs.push(Tab { kind: TabKind::Postgres, title: "PostgreSQL".into() })
+        }
+
+        if kafka_lag.is_some() {
+            tabs.push(Tab { kind: TabKind::KafkaLag, title: "Kafka lag".into() })
+        }
+
+        if redis.is_some() {
+            tabs.push(Tab { kind: TabKind::Redis, title: "Redis".into() })
+        }
+
+        if docker.is_some() {
+            tabs.push(Tab { kind: TabKind::Docker, title: "Docker".into() })
+        }
+
+        if k8s_pod.is_some() {
+            tabs.push(Tab { kind: TabKind::K8sPod, title: "Pod".into() })
+        }
+
+        if rabbitmq.is_some() {
+            tabs.push(Tab { kind: TabKind::RabbitMq, title: "RabbitMQ".into() })
+        }
+
+        if proxy.is_some() {
+            tabs.push(Tab { kind: TabKind::Proxy, title: "Proxy".into() })
+        }
+
+        if traces.is_some() {
+            tabs.push(Tab { kind: TabKind::Traces, title: "Traces".into() })
+        }
+
+        if otlp.is_some() {
+            tabs.push(Tab { kind: TabKind::Otlp, title: "OTLP".into() })
+        }
+
+        // a single source is an overview of itself
+        if tabs.len() > 1 {
+            tabs.insert(0, Tab { kind: TabKind::Overview, title: "Overview".into() });
+        }
+
+        for (i, d) in config.dashboards.iter().enumerate() {
+            tabs.push(Tab { kind: TabKind::Dashboard(i), title: d.title.clone().into() })
+        }
+
+        if !config.alerts.is_empty() {
+            tabs.push(Tab { kind: TabKind::Alerts, title: "Alerts".into() })
+        }
+
+        // any source can fail
+        tabs.push(Tab { kind: TabKind::Errors, title: "Errors".into() });
+
+        // sources are listed before they first respond, so the ones that never do stand out
+        let mut sources: Vec<SourceStatus> = vec![];
+        let names = tabs.iter().filter_map(|t| t.kind.source()).chain(jmx.as_ref().map(|_| "JMX"));
+        for name in names {
+            if !sources.iter().any(|s| s.name == name) {
+                sources.push(SourceStatus { name, last_success: None, error: None, failing_since: None });
+            }
+        }
+
+        App {
+            title,
+            should_quit: false,
+            exit_reason: None,
+            tabs: TabsState::new(tabs),
+            zmx: if zio_zmx_addr.is_some() || cats_effect.is_some() { Some(ZMXTab::new()) } else { None },
+            slick: jmx.as_ref().filter(|_| has_slick).map(SlickTab::new),
+            kafka: if has_kafka { Some(KafkaTab::new()) } else { None },
+            cassandra: if has_cassandra { Some(CassandraTab::new()) } else { None },
+            loggers: jmx.as_ref().filter(|_| has_loggers).map(|j| LoggersTab::new(j.allow_operations)),
+            thread_pools: if has_thread_pools { Some(ThreadPoolsTab::new()) } else { None },
+            jvm: jmx.as_ref().filter(|_| has_jvm_info).map(JvmTab::new),
+            jfr: jfr.map(|_| JfrTab::new()),
+            actor_tree: akka.map(|a| AkkaActorTreeTab::new(has_dispatchers, &a)),
+            sharding: sharding.map(|_| ShardingTab::new()),
+            cluster: if has_cluster { Some(ClusterTab::new()) } else { None },
+            prometheus: prometheus.map(|p| PrometheusTab::new(p.selectors.iter().map(|s| s.to_string()).collect())),
+            promql: promql.map(|p| PrometheusTab::new(p.queries)),
+            actuator: actuator.as_ref().map(ActuatorTab::new),
+            json_metrics: if json_metrics.is_empty() { None } else { Some(JsonMetricsTab::new(&json_metrics)) },
+            commands: if commands.is_empty() { None } else { Some(CommandsTab::new(&commands)) },
+            sql_queries: if sql_queries.is_empty() { None } else { Some(SqlQueriesTab::new(&sql_queries)) },
+            statsd: statsd.as_ref().map(StatsdTab::new),
+            logs: logs.as_ref().map(LogsTab::new),
+            postgres: postgres.map(|_| PostgresTab::new()),
+            kafka_lag: kafka_lag.as_ref().map(KafkaLagTab::new),
+            redis: redis.map(|_| RedisTab::new()),
+            docker: docker.as_ref().map(DockerTab::new),
+            k8s_pod: k8s_pod.as_ref().map(K8sPodTab::new),
+            rabbitmq: rabbitmq.map(|_| RabbitMqTab::new()),
+            proxy: proxy.as_ref().map(ProxyTab::new),
+            traces: traces.as_ref().map(TracesTab::new),
+            otlp: otlp.as_ref().map(OtlpTab::new),
+            dashboards: config.dashboards.iter().map(DashboardTab::new).collect(),
+            dashboard_values: vec![],
+            errors: ErrorLogTab::new(),
+            alert_rules: if config.alerts.is_empty() { None } else { Some(AlertRules::new(config.alerts.clone())) },
+            fetcher_requests: vec![],
+            in_flight: HashSet::new(),
+            alert_webhooks: vec![],
+            bell: false,
+            flash: false,
+            jmx_reconnecting: None,
+            viewport: Viewport::default(),
+            splits: config.layout.clone(),
+            maximized: false,
+            maximized_chart: 0,
+            show_help: false,
+            line_numbers: false,
+            ascii: false,
+            high_contrast: false,
+            sources,
+            toasts: VecDeque::new(),
+            confirmation: None,
+            debug: DebugStats::new(),
+            tick_rate: None,
+            frozen: false,
+            marker_label: None,
+            report_requested: false,
+            metrics_exporter: None,
+            influx: None,
+            statsd_forwarder: None,
+            grafana_series: None,
+            sent_samples: NewSamples::default(),
+            history: None,
+            frozen_updates: 0,
+        }
+    }
+
+    pub fn on_up(&mut self) {
+        if self.cycle_maximized_chart(false) {
+            return;
+        }
+        let kind = self.tabs.current().kind.clone();
+        if let Some(tab) = self.source_tab_mut(&kind) {
+            tab.on_up();
+        }
+    }
+
+    pub fn on_down(&mut self) {
+        if self.cycle_maximized_chart(true) {
+            return;
+        }
+        let kind = self.tabs.current().kind.clone();
+        if let Some(tab) = self.source_tab_mut(&kind) {
+            tab.on_down();
+        }
+    }
+
+    ///
+    /// Records how the source of the response is doing, in its status and on the errors tab, returning what of the screen that changed.
+    ///
+    /// The data of the response is taken in by `on_fetcher_response` of the main loop, frozen or not.
+    pub fn on_fetcher_response(&mut self, response: &FetcherResponse) -> Redraw {
+        if let Some(request) = response.polled_request() {
+            self.in_flight.remove(&in_flight_key(&request));
+        }
+        let toasts = self.toasts_state();
+        self.record_outcome(response);
+        if self.frozen && response.outcome().is_some() {
+            self.frozen_updates += 1;
+        }
+        if self.toasts_state() != toasts || matches!(self.tabs.current().kind, TabKind::Errors) {
+            Redraw::All
+        } else if response.outcome().is_some() {
+            Redraw::StatusBar
+        } else {
+            Redraw::Nothing
+        }
+    }
+
+    fn record_outcome(&mut self, response: &FetcherResponse) {
+        match response {
+            FetcherResponse::HikariOperation(_, Err(e)) => self.errors.append_error("Slick", &e.message),
+            FetcherResponse::SetLoggerLevel(_, _, Err(e)) | FetcherResponse::JvmOperation(_, Err(e)) => self.errors.append_error("JMX", &e.message),
+            _ => {}
+        }
+        let (name, error) = match response.outcome() {
+            Some(o) => o,
+            None => return,
+        };
+        match error {
+            Some(e) => self.errors.append_error(name, &e.message),
+            None => self.errors.on_success(name),
+        }
+        let i = match self.sources.iter().position(|s| s.name == name) {
+            Some(i) => i,
+            None => {
+                self.sources.push(SourceStatus { name, last_success: None, error: None, failing_since: None });
+                self.sources.len() - 1
+            }
+        };
+        let source = &mut self.sources[i];
+        source.error = error.cloned();
+        match error {
+            // failed reads are retried, the first one of a streak is all that pops up
+            Some(e) if source.failing_since.is_none() => {
+                source.failing_since = Some(Instant::now());
+                let message = format!("{} failed: {}", name, e.message.lines().next().unwrap_or_default());
+                self.notify(ToastLevel::Warning, message);
+            }
+            Some(_) => {}
+            None => {
+                source.failing_since = None;
+                source.last_success = Some(Local::now());
+            }
+        }
+    }
+
+    /// Error of a source that has been failing for longer than the grace period, if any
+    pub fn source_given_up(&self, now: Instant) -> Option<&FetchError> {
+        self.sources.iter()
+            .find(|s| s.failing_since.is_some_and(|t| now.duration_since(t) >= App::SOURCE_FAILURE_GRACE))
+            .and_then(|s| s.error.as_ref())
+    }
+
+    /// Freezes the screen or shows what was taken in meanwhile
+    pub fn toggle_freeze(&mut self) {
+        self.frozen = !self.frozen;
+        self.frozen_updates = 0;
+    }
+
+    pub fn frozen_updates(&self) -> usize {
+        self.frozen_updates
+    }
+
+    /// Time of the most recent successful read from any of the sources
+    pub fn last_refresh(&self) -> Option<DateTime<Local>> {
+        self.sources.iter().filter_map(|s| s.last_success).max()
+    }
+
+    /// Titles of the tabs that currently have firing alerts
+    pub fn alerting_tabs(&self) -> Vec<&str> {
+        self.tabs.tabs.iter().filter(|t| self.has_alert(&t.kind)).map(|t| t.title.as_ref()).collect()
+    }
+
+    pub fn notify(&mut self, level: ToastLevel, message: String) {
+        if self.toasts.len() == App::MAX_TOASTS {
+            self.toasts.pop_front();
+        }
+        self.toasts.push_back(Toast { message, level, expires: Instant::now() + App::TOAST_DURATION });
+    }
+
+    ///
+    /// Samples the values charted every tick, returning whether that changed what's shown: toasts coming and going,
+    /// alerts firing or blinking, dashboards and actor changes fading on the current tab.
+    pub fn on_tick(&mut self) -> bool {
+        let toasts = self.toasts_state();
+        let alerts = self.owned_alerts();
+        let shown = match self.tabs.current().kind {
+            TabKind::Dashboard(_) => true,
+            TabKind::AkkaActorTree => self.actor_tree.as_ref().is_some_and(|t| !t.actor_changes.is_empty()),
+            _ => false,
+        };
+        let now = Instant::now();
+        self.toasts.retain(|t| t.expires > now);
+        self.flash = !self.flash;
+        if let Some(t) = self.actor_tree.as_mut() {
+            t.fade_actor_changes();
+        }
+        let timed_values = self.timed_metric_values();
+        let values: Vec<MetricValue> = timed_values.iter().map(|(_, v)| v.clone()).collect();
+        let now = Local::now();
+        for d in self.dashboards.iter_mut() {
+            d.append_values(&values, now);
+        }
+        if !self.dashboards.is_empty() {
+            self.dashboard_values = values.clone();
+        }
+        self.evaluate_alert_rules(&values);
+        // a history that can't be written to is given up on, rather than warned about every tick
+        if let Some(Err(e)) = self.history.as_mut().map(|h| h.append(now, &values)) {
+            self.history = None;
+            self.notify(ToastLevel::Warning, e);
+        }
+        // sinks keeping series of their own get gaps while sources fail, rather than the last value over and over
+        let samples = self.sent_samples.take(&timed_values);
+        if let Some(i) = &self.influx {
+            i.publish(&samples);
+        }
+        if let Some(e) = self.influx.as_ref().and_then(|i| i.take_error()) {
+            self.notify(ToastLevel::Warning, e);
+        }
+        if let Some(Err(e)) = self.statsd_forwarder.as_mut().map(|f| f.publish(&samples)) {
+            self.notify(ToastLevel::Warning, e);
+        }
+        if let Some(s) = self.grafana_series.as_mut() {
+            s.append(&samples);
+        }
+        if let Some(e) = &self.metrics_exporter {
+            e.publish(values);
+        }
+        shown || self.toasts_state() != toasts || !alerts.is_empty() || self.owned_alerts() != alerts
+    }
+
+    /// Changes whenever a toast pops up or goes away
+    fn toasts_state(&self) -> (usize, Option<Instant>) {
+        (self.toasts.len(), self.toasts.back().map(|t| t.expires))
+    }
+
+    fn owned_alerts(&self) -> Vec<(String, String)> {
+        self.alerts().into_iter().map(|(tab, message)| (tab.to_owned(), message)).collect()
+    }
+
+    /// Charts the values saved before a restart again, on dashboards and tabs charting any metric of their source
+    pub fn restore_history(&mut self, history: Vec<SavedTick>) {
+        for (timestamp, values) in history {
+            if let Some(s) = self.grafana_series.as_mut() {
+                s.append(&values.iter().map(|v| (timestamp, v.clone())).collect::<Vec<_>>());
+            }
+            for d in self.dashboards.iter_mut() {
+                d.append_values(&values, timestamp);
+            }
+            self.restore_tab_values(timestamp, &values);
+            for (source, tab) in self.chart_tabs_mut() {
+                for v in &values {
+                    let chart = v.metric.strip_prefix(source).and_then(|m| m.strip_prefix('/')).and_then(|m| tab.chart_of(m, false));
+                    if let Some(i) = chart {
+                        tab.restore_value(i, &v.series, timestamp, v.value);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Puts back the values of the tabs charting fixed metrics, without raising their alerts again
+    fn restore_tab_values(&mut self, timestamp: DateTime<Local>, values: &[MetricValue]) {
+        let value = |metric: &str| values.iter().find(|v| v.metric == metric).map(|v| v.value);
+        if let Some(zmx) = self.zmx.as_mut() {
+            if let (Some(running), Some(suspended), Some(done), Some(finishing)) =
+                (value("fibers/running"), value("fibers/suspended"), value("fibers/done"), value("fibers/finishing")) {
+                let c = FiberCount { timestamp, running: running as i32, suspended: suspended as i32, done: done as i32, finishing: finishing as i32 };
+                restore_sample(&mut zmx.fiber_counts, ZMXTab::MAX_FIBER_COUNT_MEASURES, c);
+            }
+        }
+        if let Some(slick) = self.slick.as_mut() {
+            if let (Some(active_threads), Some(queue_size)) = (value("slick/active_threads"), value("slick/queue_size")) {
+                let m = SlickMetrics { timestamp, active_threads: active_threads as i32, queue_size: queue_size as i32 };
+                restore_sample(&mut slick.slick_metrics, SlickTab::MAX_SLICK_MEASURES, m);
+            }
+            if let (Some(total), Some(active), Some(idle), Some(waiting)) =
+                (value("hikari/total"), value("hikari/active"), value("hikari/idle"), value("hikari/waiting")) {
+                // the maximum pool size isn't saved, the next fetch brings it back
+                let m = HikariMetrics { timestamp, total: total as i32, active: active as i32, idle: idle as i32, waiting: waiting as i32, max_pool_size: 0 };
+                restore_sample(&mut slick.hikari_metrics, SlickTab::MAX_HIKARI_MEASURES, m);
+            }
+        }
+        if let Some(tab) = self.actor_tree.as_mut() {
+            if let Some(total) = value("actors/count") {
+                let c = ActorCountSample { timestamp, total: total as u64, excluded: 0 };
+                restore_sample(&mut tab.actor_counts, AkkaActorTreeTab::MAX_ACTOR_COUNT_MEASURES, c);
+            }
+        }
+        if let Some(tab) = self.thread_pools.as_mut() {
+            let pools: Vec<ThreadPoolMetrics> = values.iter().filter(|v| v.metric == "threads/active").map(|v| ThreadPoolMetrics {
+                name: v.series.clone(),
+                active_threads: v.value,
+                queue_size: values.iter().find(|q| q.metric == "threads/queue_size" && q.series == v.series).map(|q| q.value),
+                max_threads: None,
+            }).collect();
+            if !pools.is_empty() {
+                restore_sample(&mut tab.metrics, ThreadPoolsTab::MAX_THREAD_POOL_MEASURES, ThreadPoolsMetrics { timestamp, pools });
+            }
+        }
+    }
+
+    fn evaluate_alert_rules(&mut self, values: &[MetricValue]) {
+        let events = match self.alert_rules.as_mut() {
+            Some(rules) => {
+                let failing: Vec<&str> = self.sources.iter().filter(|s| s.error.is_some()).map(|s| s.name).collect();
+                rules.evaluate(values, &failing, Local::now())
+            }
+            None => return,
+        };
+        for e in events {
+            let webhook = self.alert_rules.as_ref()
+                .and_then(|r| r.rules.iter().find(|r| r.name == e.rule))
+                .and_then(|r| r.webhook.clone());
+            if let Some(url) = webhook {
+                self.alert_webhooks.push((url, e.clone()));
+            }
+            if e.firing {
+                self.notify(ToastLevel::Warning, format!("{} firing: {}", e.rule, e.message));
+                let rule = self.alert_rules.as_ref().and_then(|r| r.rules.iter().find(|r| r.name == e.rule));
+                let (bell, desktop) = rule.map_or((false, false), |r| (r.bell, r.desktop));
+                self.bell |= bell;
+                if desktop {
+                    if let Err(error) = notify::send_desktop_notification(&format!("{} firing", e.rule), &e.message) {
+                        self.errors.append_error("Alerts", &error);
+                    }
+                }
+            } else {
+                self.notify(ToastLevel::Info, format!("{} resolved", e.rule));
+            }
+        }
+    }
+
+    ///
+    /// Latest values of the collected series, named after their source and chart for alert rules.
+    ///
+    /// Charts of metrics from Prometheus, JSON endpoints, commands and the like are named after their title,
+    /// e.g. `prometheus/http_requests_total` or `json/Job queue`, with a series for each of their lines.
+    pub fn metric_values(&self) -> Vec<MetricValue> {
+        self.timed_metric_values().into_iter().map(|(_, v)| v).collect()
+    }
+
+    /// Latest values of the collected series, with the time they were sampled at
+    pub fn timed_metric_values(&self) -> Vec<TimedValue> {
+        let mut values = vec![];
+        if let Some(c) = self.zmx.as_ref().and_then(|t| t.fiber_counts.back()) {
+            for (name, value) in [("running", c.running), ("suspended", c.suspended), ("done", c.done), ("finishing", c.finishing), ("total", c.total())] {
+                values.push((c.timestamp, MetricValue::new(&format!("fibers/{}", name), "", value as f64)));
+            }
+        }
+        if let Some(slick) = &self.slick {
+            if let Some(m) = slick.slick_metrics.back() {
+                values.push((m.timestamp, MetricValue::new("slick/active_threads", "", m.active_threads as f64)));
+                values.push((m.timestamp, MetricValue::new("slick/queue_size", "", m.queue_size as f64)));
+            }
+            if let Some(m) = slick.hikari_metrics.back() {
+                for (name, value) in [("total", m.total), ("active", m.active), ("idle", m.idle), ("waiting", m.waiting)] {
+                    values.push((m.timestamp, MetricValue::new(&format!("hikari/{}", name), "", value as f64)));
+                }
+            }
+        }
+        if let Some(c) = self.actor_tree.as_ref().and_then(|t| t.actor_counts.back()) {
+            values.push((c.timestamp, MetricValue::new("actors/count", "", c.total as f64)));
+        }
+        if let Some(m) = self.thread_pools.as_ref().and_then(|t| t.metrics.back()) {
+            for p in &m.pools {
+                values.push((m.timestamp, MetricValue::new("threads/active", &p.name, p.active_threads)));
+                if let Some(queue_size) = p.queue_size {
+                    values.push((m.timestamp, MetricValue::new("threads/queue_size", &p.name, queue_size)));
+                }
+            }
+        }
+        if let Some((t, rate)) = self.jfr.as_ref().and_then(|t| t.allocation_rates.back()) {
+            values.push((*t, MetricValue::new("jfr/allocation_rate", "", *rate)));
+        }
+        if let Some(redis) = &self.redis {
+            if let Some(i) = redis.info.back() {
+                values.push((i.timestamp, MetricValue::new("redis/used_memory", "", i.used_memory as f64)));
+                values.push((i.timestamp, MetricValue::new("redis/connected_clients", "", i.connected_clients as f64)));
+                values.push((i.timestamp, MetricValue::new("redis/blocked_clients", "", i.blocked_clients as f64)));
+                values.push((i.timestamp, MetricValue::new("redis/ops_per_sec", "", i.ops_per_sec)));
+            }
+            if let Some((t, rate)) = redis.hit_rates.back() {
+                values.push((*t, MetricValue::new("redis/hit_rate", "", *rate)));
+            }
+        }
+        if let Some(docker) = &self.docker {
+            if let Some((t, cpu)) = docker.cpu.back() {
+                values.push((*t, MetricValue::new("docker/cpu", "", *cpu)));
+            }
+            if let Some(s) = docker.stats.back() {
+                values.push((s.timestamp, MetricValue::new("docker/memory_usage", "", s.memory_usage as f64)));
+            }
+        }
+        if let Some(postgres) = &self.postgres {
+            if let Some((t, counts)) = postgres.connections.back() {
+                for (state, count) in counts {
+                    values.push((*t, MetricValue::new("postgres/connections", state, *count as f64)));
+                }
+            }
+            if let Some((t, commits, rollbacks)) = postgres.transactions.back() {
+                values.push((*t, MetricValue::new("postgres/commits", "", *commits)));
+                values.push((*t, MetricValue::new("postgres/rollbacks", "", *rollbacks)));
+            }
+        }
+        let charts = [
+            ("prometheus", self.prometheus.as_ref()),
+            ("promql", self.promql.as_ref()),
+            ("actuator", self.actuator.as_ref().map(|t| &t.metrics)),
+            ("json", self.json_metrics.as_ref().map(|t| &t.charts)),
+            ("commands", self.commands.as_ref().map(|t| &t.charts)),
+            ("sql", self.sql_queries.as_ref().map(|t| &t.charts)),
+            ("statsd", self.statsd.as_ref().map(|t| &t.charts)),
+            ("kafka_lag", self.kafka_lag.as_ref().map(|t| &t.charts)),
+            ("k8s", self.k8s_pod.as_ref().map(|t| &t.charts)),
+            ("rabbitmq", self.rabbitmq.as_ref().map(|t| &t.charts)),
+            ("proxy", self.proxy.as_ref().map(|t| &t.charts)),
+            ("otlp", self.otlp.as_ref().map(|t| &t.charts)),
+        ];
+        for (source, tab) in charts.iter().filter_map(|(s, t)| t.map(|t| (s, t))) {
+            for (selector, series) in tab.selectors.iter().zip(tab.series.iter()) {
+                let metric = format!("{}/{}", source, selector);
+                for s in series {
+                    if let Some((t, value)) = s.values.back() {
+                        values.push((*t, MetricValue::new(&metric, &s.name, *value)));
+                    }
+                }
+            }
+        }
+        values
+    }
+
+    /// Tabs charting any metric of their source, with the source their metrics are named after in `metric_values`
+    fn chart_tabs_mut(&mut self) -> Vec<(&'static str, &mut PrometheusTab)> {
+        let charts = [
+            ("prometheus", self.prometheus.as_mut()),
+            ("promql", self.promql.as_mut()),
+            ("actuator", self.actuator.as_mut().map(|t| &mut t.metrics)),
+            ("json", self.json_metrics.as_mut().map(|t| &mut t.charts)),
+            ("commands", self.commands.as_mut().map(|t| &mut t.charts)),
+            ("sql", self.sql_queries.as_mut().map(|t| &mut t.charts)),
+            ("statsd", self.statsd.as_mut().map(|t| &mut t.charts)),
+            ("kafka_lag", self.kafka_lag.as_mut().map(|t| &mut t.charts)),
+            ("k8s", self.k8s_pod.as_mut().map(|t| &mut t.charts)),
+            ("rabbitmq", self.rabbitmq.as_mut().map(|t| &mut t.charts)),
+            ("proxy", self.proxy.as_mut().map(|t| &mut t.charts)),
+            ("otlp", self.otlp.as_mut().map(|t| &mut t.charts)),
+        ];
+        IntoIterator::into_iter(charts).filter_map(|(s, t)| t.map(|t| (s, t))).collect()
+    }
+
+    fn has_alert(&self, kind: &TabKind) -> bool {
+        self.alert_message(kind).is_some()
+    }
+
+    /// What the firing alert of a tab is about, if it has one
+    pub fn alert_message(&self, kind: &TabKind) -> Option<String> {
+        match kind {
+            TabKind::Alerts => self.alert_rules.as_ref()
+                .filter(|r| !r.firing.is_empty())
+                .map(|r| r.firing.iter().map(|(_, e)| e.message.as_str()).collect::<Vec<_>>().join(", ")),
+            _ => self.source_tab(kind).and_then(|t| t.alert_message()),
+        }
+    }
+
+    /// Firing alerts by the title of their tab, in the order of the tabs
+    pub fn alerts(&self) -> Vec<(&str, String)> {
+        self.tabs.tabs.iter().filter_map(|t| self.alert_message(&t.kind).map(|m| (t.title.as_ref(), m))).collect()
+    }
+
+    pub fn uses_jmx(&self, kind: &TabKind) -> bool {
+        self.source_tab(kind).is_some_and(|t| t.uses_jmx())
+    }
+
+    /// Tab of the source a tab kind shows, if it has one
+    fn source_tab(&self, kind: &TabKind) -> Option<&dyn SourceTab> {
+        match kind {
+            TabKind::ZMX => self.zmx.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Slick => self.slick.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Kafka => self.kafka.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Cassandra => self.cassandra.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Loggers => self.loggers.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::ThreadPools => self.thread_pools.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Jvm => self.jvm.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Jfr => self.jfr.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::AkkaActorTree => self.actor_tree.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Sharding => self.sharding.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Cluster => self.cluster.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Prometheus => self.prometheus.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::PromQL => self.promql.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Actuator => self.actuator.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::JsonMetrics => self.json_metrics.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Commands => self.commands.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::SqlQueries => self.sql_queries.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Statsd => self.statsd.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Logs => self.logs.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Postgres => self.postgres.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::KafkaLag => self.kafka_lag.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Redis => self.redis.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Docker => self.docker.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::K8sPod => self.k8s_pod.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::RabbitMq => self.rabbitmq.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Proxy => self.proxy.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Traces => self.traces.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Otlp => self.otlp.as_ref().map(|t| t as &dyn SourceTab),
+            TabKind::Dashboard(i) => self.dashboards.get(*i).map(|t| t as &dyn SourceTab),
+            TabKind::Errors => Some(&self.errors),
+            TabKind::Overview | TabKind::Alerts => None,
+        }
+    }
+
+    fn source_tab_mut(&mut self, kind: &TabKind) -> Option<&mut dyn SourceTab> {
+        match kind {
+            TabKind::ZMX => self.zmx.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Slick => self.slick.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Kafka => self.kafka.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Cassandra => self.cassandra.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Loggers => self.loggers.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::ThreadPools => self.thread_pools.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Jvm => self.jvm.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Jfr => self.jfr.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::AkkaActorTree => self.actor_tree.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Sharding => self.sharding.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Cluster => self.cluster.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Prometheus => self.prometheus.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::PromQL => self.promql.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Actuator => self.actuator.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::JsonMetrics => self.json_metrics.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Commands => self.commands.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::SqlQueries => self.sql_queries.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Statsd => self.statsd.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Logs => self.logs.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Postgres => self.postgres.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::KafkaLag => self.kafka_lag.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Redis => self.redis.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Docker => self.docker.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::K8sPod => self.k8s_pod.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::RabbitMq => self.rabbitmq.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Proxy => self.proxy.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Traces => self.traces.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Otlp => self.otlp.as_mut().map(|t| t as &mut dyn SourceTab),
+            TabKind::Dashboard(i) => self.dashboards.get_mut(*i).map(|t| t as &mut dyn SourceTab),
+            TabKind::Errors => Some(&mut self.errors),
+            TabKind::Overview | TabKind::Alerts => None,
+        }
+    }
+
+    pub fn tab_titles(&self) -> Vec<String> {
+        self.tabs.tabs.iter().map(|t| {
+            if self.flash && self.has_alert(&t.kind) {
+                format!("! {} !", t.title)
+            } else {
+                t.title.to_string()
+            }
+        }).collect()
+    }
+
+    pub fn on_right(&mut self) {
+        self.maximized = false;
+        self.tabs.next();
+    }
+
+    pub fn on_left(&mut self) {
+        self.maximized = false;
+        self.tabs.previous();
+    }
+
+    /// Charts of the current tab, if they're its main panel
+    pub fn metric_charts(&self) -> Option<&PrometheusTab> {
+        match self.tabs.current().kind {
+            TabKind::Prometheus => self.prometheus.as_ref(),
+            TabKind::PromQL => self.promql.as_ref(),
+            TabKind::JsonMetrics => self.json_metrics.as_ref().map(|t| &t.charts),
+            TabKind::Commands => self.commands.as_ref().map(|t| &t.charts),
+            TabKind::SqlQueries => self.sql_queries.as_ref().map(|t| &t.charts),
+            TabKind::Statsd => self.statsd.as_ref().map(|t| &t.charts),
+            TabKind::KafkaLag => self.kafka_lag.as_ref().map(|t| &t.charts),
+            TabKind::K8sPod => self.k8s_pod.as_ref().map(|t| &t.charts),
+            TabKind::RabbitMq => self.rabbitmq.as_ref().map(|t| &t.charts),
+            TabKind::Proxy => self.proxy.as_ref().map(|t| &t.charts),
+            TabKind::Otlp => self.otlp.as_ref().map(|t| &t.charts),
+            _ => None,
+        }
+    }
+
+    /// Lets the main panel of the tab fill the terminal, or restores the layout.
+    ///
+    /// Dumps and logs are maximized where the tab has them, charts one at a time otherwise.
+    pub fn toggle_maximized(&mut self) {
+        if self.maximized {
+            self.maximized = false;
+            return;
+        }
+        self.maximized = match self.tabs.current().kind {
+            TabKind::ZMX | TabKind::Actuator | TabKind::Traces | TabKind::Logs => true,
+            _ => self.metric_charts().is_some_and(|c| !c.selectors.is_empty()),
+        };
+        self.maximized_chart = 0;
+    }
+
+    /// Picks the chart shown while a tab of charts is maximized, returning `false` if it isn't
+    fn cycle_maximized_chart(&mut self, forward: bool) -> bool {
+        let count = match self.metric_charts().map(|c| c.selectors.len()) {
+            Some(n) if self.maximized && n > 0 => n,
+            _ => return false,
+        };
+        let current = self.maximized_chart.min(count - 1);
+        self.maximized_chart = if forward { (current + 1) % count } else { (current + count - 1) % count };
+        true
+    }
+
+    /// Whether keys are taken as text, e.g. while searching logs
+    pub fn is_typing(&self) -> bool {
+        self.marker_label.is_some() || self.is_searching_logs()
+    }
+
+    fn is_searching_logs(&self) -> bool {
+        matches!(self.tabs.current().kind, TabKind::Logs) && self.logs.as_ref().is_some_and(|l| l.searching)
+    }
+
+    pub fn on_key(&mut self, c: char) {
+        if let Some(label) = self.marker_label.as_mut() {
+            label.push(c);
+            return;
+        }
+        if self.is_typing() {
+            self.logs.as_mut().unwrap().on_search_char(c);
+            return;
+        }
+        // help on what to answer is still there
+        if self.confirmation.is_some() && c != '?' {
+            self.on_confirmation_key(c);
+            return;
+        }
+        match c {
+            'q' => self.quit(None),
+            '+' => self.viewport.zoom_in(),
+            '-' => self.viewport.zoom_out(),
+            '[' => self.viewport.pan_back(),
+            ']' => self.viewport.pan_forward(),
+            '0' => self.viewport.reset(),
+            '?' => self.show_help = !self.show_help,
+            '<' => self.splits.resize_list(false),
+            '>' => self.splits.resize_list(true),
+            '{' => self.splits.resize_charts(false),
+            '}' => self.splits.resize_charts(true),
+            'm' => self.toggle_maximized(),
+            'z' => self.toggle_freeze(),
+            'a' => self.marker_label = Some(String::new()),
+            'X' => self.report_requested = true,
+            // digits toggle series on the Slick tab
+            '1'..='9' if !matches!(self.tabs.current().kind, TabKind::Slick) => {
+                self.maximized = false;
+                self.tabs.select(c as usize - '1' as usize);
+            }
+            _ => match self.tabs.current().kind {
+                TabKind::Slick => self.on_slick_key(c),
+                TabKind::Loggers => self.on_loggers_key(c),
+                TabKind::Jvm => self.on_jvm_key(c),
+                TabKind::AkkaActorTree => self.on_actor_tree_key(c),
+                TabKind::Logs => self.on_logs_key(c),
+                TabKind::Errors => self.on_errors_key(c),
+                TabKind::ZMX | TabKind::Actuator | TabKind::Traces if c == 'n' => self.line_numbers = !self.line_numbers,
+                _ => {}
+            }
+        }
+    }
+
+    fn on_slick_key(&mut self, c: char) {
+        let slick = self.slick.as_mut().unwrap();
+        match c {
+            'e' => self.confirmation = slick.request_operation(HikariOperation::SoftEvictConnections),
+            's' => self.confirmation = slick.request_operation(HikariOperation::SuspendPool),
+            'r' => self.confirmation = slick.request_operation(HikariOperation::ResumePool),
+            'x' => {
+                let result = slick.export_csv();
+                self.notify_export(result);
+            }
+            'p' => self.fetcher_requests.push(FetcherRequest::DbPools),
+            '1'..='9' => slick.toggle_series(c),
+            _ => {}
+        }
+    }
+
+    /// Sends the request waiting for confirmation with <y>, or drops it with <n>
+    fn on_confirmation_key(&mut self, c: char) {
+        let confirmation = match c {
+            'y' => self.confirmation.take(),
+            'n' => {
+                self.confirmation = None;
+                return;
+            }
+            _ => return,
+        };
+        if let Some(c) = confirmation {
+            if let FetcherRequest::JvmOperation(op) = &c.request {
+                self.jvm.as_mut().unwrap().on_operation_started(op);
+            }
+            self.fetcher_requests.push(c.request);
+        }
+    }
+
+    ///
+    /// Takes a full fiber dump, with the stack traces of all the fibers.
+    ///
+    /// Apps with lots of fibers take a while to dump them, so it needs confirming once the last count got large.
+    pub fn request_fiber_dump(&mut self) {
+        let fibers = self.zmx.as_ref().and_then(|t| t.fiber_counts.back()).map_or(0, |c| c.total());
+        if fibers > ZMXTab::LARGE_DUMP_FIBERS {
+            self.confirmation = Some(Confirmation {
+                title: "Fiber dump".to_owned(),
+                question: "Take a full fiber dump?".to_owned(),
+                warning: Some(format!("The app had {} fibers at the last count, dumping their stack traces may take a while and slow it down", fibers)),
+                request: FetcherRequest::FiberDump,
+            });
+        } else {
+            self.fetcher_requests.push(FetcherRequest::FiberDump);
+        }
+    }
+
+    fn on_actor_tree_key(&mut self, c: char) {
+        let tab = self.actor_tree.as_mut().unwrap();
+        match c {
+            ' ' => {
+                if let Some(r) = tab.toggle_selected_actor() {
+                    self.fetcher_requests.push(r);
+                }
+            }
+            'c' => tab.collapse_all_actors(),
+            'e' => tab.expand_all_actors(),
+            'x' => {
+                let result = tab.export_tree();
+                self.notify_export(result);
+            }
+            'f' => tab.toggle_excluded_actors(),
+            'w' => tab.toggle_selected_watch(),
+            _ => {}
+        }
+    }
+
+    fn on_errors_key(&mut self, c: char) {
+        if c == 'x' {
+            let result = self.errors.export_csv();
+            self.notify_export(result);
+        }
+    }
+
+    pub fn notify_export(&mut self, result: Result<String, String>) {
+        match result {
+            Ok(message) => self.notify(ToastLevel::Info, message),
+            Err(message) => self.notify(ToastLevel::Warning, message),
+        }
+    }
+
+    fn on_logs_key(&mut self, c: char) {
+        let tab = self.logs.as_mut().unwrap();
+        match c {
+            'p' => tab.toggle_pause(),
+            '/' => tab.start_search(),
+            _ => {}
+        }
+    }
+
+    /// Drops a marker with the typed label on all the charts, at the current time
+    pub fn finish_marker(&mut self) {
+        if let Some(label) = self.marker_label.take().filter(|l| !l.trim().is_empty()) {
+            self.add_marker(Local::now(), label.trim());
+        }
+    }
+
+    pub fn add_marker(&mut self, timestamp: DateTime<Local>, label: &str) {
+        self.viewport.add_marker(timestamp.timestamp_millis() as f64 / 1000.0, label);
+        self.notify(ToastLevel::Info, format!("Marked {} at {}", label, timestamp.format("%H:%M:%S")));
+    }
+
+    /// What's shown stays as the recording left it
+    pub fn on_replay_finished(&mut self, result: Result<usize, String>) {
+        match result {
+            Ok(0) => self.notify(ToastLevel::Info, "Replay finished".to_owned()),
+            Ok(skipped) => self.notify(ToastLevel::Warning, format!("Replay finished, {} unreadable responses were skipped", skipped)),
+            Err(e) => self.notify(ToastLevel::Warning, e),
+        }
+    }
+
+    pub fn on_backspace(&mut self) {
+        if let Some(label) = self.marker_label.as_mut() {
+            label.pop();
+        } else if self.is_typing() {
+            self.logs.as_mut().unwrap().on_search_backspace();
+        }
+    }
+
+    /// Starts monitoring the DB pool picked on the Slick tab
+    pub fn select_db_pool(&mut self) {
+        if let Some(name) = self.slick.as_mut().unwrap().select_pool() {
+            self.fetcher_requests.push(FetcherRequest::SelectDbPool(name));
+            self.fetcher_requests.push(FetcherRequest::SlickConfig);
+            self.fetcher_requests.push(FetcherRequest::HikariMetrics);
+            self.fetcher_requests.push(FetcherRequest::SlickMetrics);
+        }
+    }
+
+    fn on_jvm_key(&mut self, c: char) {
+        let jvm = self.jvm.as_mut().unwrap();
+        match c {
+            'h' => self.confirmation = jvm.request_heap_dump(),
+            'g' => self.confirmation = jvm.request_operation(JvmOperation::Gc),
+            _ => {}
+        }
+    }
+
+    fn on_loggers_key(&mut self, c: char) {
+        let level = match c {
+            't' => LOG_LEVELS[0],
+            'd' => LOG_LEVELS[1],
+            'i' => LOG_LEVELS[2],
+            'w' => LOG_LEVELS[3],
+            'e' => LOG_LEVELS[4],
+            _ => return,
+        };
+        if let Some(request) = self.loggers.as_mut().unwrap().request_level(level) {
+            self.fetcher_requests.push(request)
+        }
+    }
+
+    /// Reloads loggers after a level change, since it also affects effective levels of descendants
+    pub fn on_logger_level_result(&mut self, logger: JMXLogger, level: String, result: Result<(), String>) {
+        let reload = result.is_ok();
+        self.loggers.as_mut().unwrap().on_level_result(logger, level, result);
+        if reload {
+            self.fetcher_requests.push(FetcherRequest::Loggers)
+        }
+    }
+
+    pub fn on_click(&mut self, column: u16, row: u16) {
+        if self.show_help {
+            self.show_help = false;
+            return;
+        }
+        // lists aren't shown
+        if self.maximized {
+            return;
+        }
+        if let Some(i) = self.tabs.tab_at(column, row) {
+            self.tabs.index = i;
+            return;
+        }
+        match self.tabs.current().kind {
+            TabKind::ZMX => {
+                let zmx = self.zmx.as_mut().unwrap();
+                if zmx.fibers.select_at(column, row) {
+                    zmx.on_fiber_change();
+                }
+            }
+            TabKind::Slick => {
+                if let Some(p) = self.slick.as_mut().unwrap().pool_picker.as_mut() {
+                    p.select_at(column, row);
+                }
+            }
+            TabKind::Loggers => {
+                self.loggers.as_mut().unwrap().loggers.select_at(column, row);
+            }
+            TabKind::AkkaActorTree => {
+                self.actor_tree.as_mut().unwrap().actors.select_at(column, row);
+            }
+            TabKind::Actuator => {
+                let actuator = self.actuator.as_mut().unwrap();
+                if actuator.threads.select_at(column, row) {
+                    actuator.on_thread_change();
+                }
+            }
+            TabKind::Traces => {
+                let traces = self.traces.as_mut().unwrap();
+                if traces.list.select_at(column, row) {
+                    traces.on_trace_change(true);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The wheel moves the selection over lists, like the arrows, and scrolls anything else
+    pub fn on_scroll(&mut self, column: u16, row: u16, up: bool) {
+        if !self.maximized && self.is_over_list(column, row) {
+            if up { self.on_up() } else { self.on_down() }
+            return;
+        }
+        // a notch scrolls a few lines
+        match self.tabs.current().kind {
+            TabKind::Jvm if up => self.jvm.as_mut().unwrap().scroll_up(3),
+            TabKind::Jvm => self.jvm.as_mut().unwrap().scroll_down(3),
+            TabKind::Logs if up => self.logs.as_mut().unwrap().scroll_up(3),
+            TabKind::Logs => self.logs.as_mut().unwrap().scroll_down(3),
+            TabKind::Slick if up => self.slick.as_mut().unwrap().scroll_notifications_up(),
+            TabKind::Slick => self.slick.as_mut().unwrap().scroll_notifications_down(),
+            _ if up => self.on_page_up(),
+            _ => self.on_page_down(),
+        }
+    }
+
+    fn is_over_list(&self, column: u16, row: u16) -> bool {
+        match self.tabs.current().kind {
+            TabKind::ZMX => self.zmx.as_ref().unwrap().fibers.contains(column, row),
+            TabKind::Slick => self.slick.as_ref().unwrap().pool_picker.as_ref().is_some_and(|p| p.contains(column, row)),
+            TabKind::Loggers => self.loggers.as_ref().unwrap().loggers.contains(column, row),
+            TabKind::AkkaActorTree => self.actor_tree.as_ref().unwrap().actors.contains(column, row),
+            TabKind::Actuator => self.actuator.as_ref().unwrap().threads.contains(column, row),
+            TabKind::Traces => self.traces.as_ref().unwrap().list.contains(column, row),
+            _ => false,
+        }
+    }
+
+    pub fn on_escape(&mut self) {
+        if self.marker_label.is_some() {
+            self.marker_label = None;
+            return;
+        }
+        if self.show_help {
+            self.show_help = false;
+            return;
+        }
+        if self.confirmation.is_some() {
+            self.confirmation = None;
+            return;
+        }
+        if self.maximized {
+            self.maximized = false;
+            return;
+        }
+        match self.tabs.current().kind {
+            TabKind::Slick => {
+                let slick = self.slick.as_mut().unwrap();
+                // picker can only be dismissed if there's a pool to get back to
+                if slick.db_pool_name.is_some() {
+                    slick.pool_picker = None;
+                }
+            }
+            TabKind::AkkaActorTree => {
+                if let Some(r) = self.actor_tree.as_mut().unwrap().cancel_tree_fetch() {
+                    self.fetcher_requests.push(r);
+                }
+            }
+            TabKind::Logs => self.logs.as_mut().unwrap().cancel_search(),
+            _ => {}
+        }
+    }
+
+    pub fn reload_actor_tree(&mut self) {
+        if let Some(r) = self.actor_tree.as_mut().unwrap().start_tree_fetch() {
+            self.fetcher_requests.push(r);
+        }
+    }
+
+    pub fn take_fetcher_requests(&mut self) -> Vec<FetcherRequest> {
+        std::mem::take(&mut self.fetcher_requests)
+    }
+
+    /// Whether the request polled every tick can be sent, as the previous one was answered, marking it in flight then
+    pub fn start_polling(&mut self, request: &FetcherRequest) -> bool {
+        self.in_flight.insert(in_flight_key(request))
+    }
+
+    pub fn take_alert_webhooks(&mut self) -> Vec<(String, AlertEvent)> {
+        std::mem::take(&mut self.alert_webhooks)
+    }
+
+    /// Failures are in the error log
+    pub fn on_alert_webhook(&mut self, rule: &str, result: Result<(), String>) {
+        if let Err(e) = result {
+            self.errors.append_error("Alerts", &format!("{}: {}", rule, e));
+        }
+    }
+
+    ///
+    /// Shows the JMX connection being re-established, also when the JVM was unreachable from the start.
+    ///
+    /// Once connected, what's only fetched at startup is fetched again, as it may have failed meanwhile
+    pub fn on_jmx_status(&mut self, status: JMXConnectionStatus) {
+        if let (JMXConnectionStatus::Connected, Some(_)) = (&status, &self.jmx_reconnecting) {
+            self.notify(ToastLevel::Info, "JMX connection re-established".to_owned());
+            if let Some(slick) = &self.slick {
+                self.fetcher_requests.push(if slick.db_pool_name.is_some() { FetcherRequest::SlickConfig } else { FetcherRequest::DbPools });
+            }
+            if self.loggers.is_some() {
+                self.fetcher_requests.push(FetcherRequest::Loggers);
+            }
+            if self.jvm.is_some() {
+                self.fetcher_requests.push(FetcherRequest::JvmInfo);
+            }
+        }
+        self.jmx_reconnecting = match status {
+            JMXConnectionStatus::Connected => None,
+            JMXConnectionStatus::Reconnecting { attempt, error } =>
+                Some(format!("JMX unreachable, reconnecting (attempt {})... Last error: {}", attempt, error)),
+        };
+    }
+
+    pub fn quit(&mut self, error: Option<FetchError>) {
+        self.should_quit = true;
+        self.exit_reason = error;
+    }
+
+    pub fn on_page_up(&mut self) {
+        match self.tabs.current().kind {
+            TabKind::ZMX => self.zmx.as_mut().unwrap().scroll_up(),
+            TabKind::Slick => {}
+            TabKind::Kafka => {}
+            TabKind::Cassandra => {}
+            TabKind::Loggers => {}
+            TabKind::ThreadPools => {}
+            TabKind::Jvm => self.jvm.as_mut().unwrap().scroll_up(10),
+            TabKind::Jfr => {}
+            TabKind::AkkaActorTree => self.actor_tree.as_mut().unwrap().scroll_dead_letters_up(),
+            TabKind::Sharding => {}
+            TabKind::Cluster => {}
+            TabKind::Prometheus => {}
+            TabKind::PromQL => {}
+            TabKind::Actuator => self.actuator.as_mut().unwrap().scroll_up(),
+            TabKind::JsonMetrics => {}
+            TabKind::Commands => {}
+            TabKind::SqlQueries => {}
+            TabKind::Statsd => {}
+            TabKind::Logs => self.logs.as_mut().unwrap().scroll_up(10),
+            TabKind::Postgres => {}
+            TabKind::KafkaLag => {}
+            TabKind::Redis => {}
+            TabKind::Docker => {}
+            TabKind::K8sPod => {}
+            TabKind::RabbitMq => {}
+            TabKind::Proxy => {}
+            TabKind::Traces => self.traces.as_mut().unwrap().scroll_up(),
+            TabKind::Otlp => {}
+            TabKind::Alerts => {}
+            TabKind::Overview => {}
+            TabKind::Dashboard(_) => {}
+            TabKind::Errors => self.errors.scroll_up(10),
+        }
+    }
+
+    pub fn on_page_down(&mut self) {
+        match self.tabs.current().kind {
+            TabKind::ZMX => self.zmx.as_mut().unwrap().scroll_down(),
+            TabKind::Slick => {}
+            TabKind::Kafka => {}
+            TabKind::Cassandra => {}
+            TabKind::Loggers => {}
+            TabKind::ThreadPools => {}
+            TabKind::Jvm => self.jvm.as_mut().unwrap().scroll_down(10),
+            TabKind::Jfr => {}
+            TabKind::AkkaActorTree => self.actor_tree.as_mut().unwrap().scroll_dead_letters_down(),
+            TabKind::Sharding => {}
+            TabKind::Cluster => {}
+            TabKind::Prometheus => {}
+            TabKind::PromQL => {}
+            TabKind::Actuator => self.actuator.as_mut().unwrap().scroll_down(),
+            TabKind::JsonMetrics => {}
+            TabKind::Commands => {}
+            TabKind::SqlQueries => {}
+            TabKind::Statsd => {}
+            TabKind::Logs => self.logs.as_mut().unwrap().scroll_down(10),
+            TabKind::Postgres => {}
+            TabKind::KafkaLag => {}
+            TabKind::Redis => {}
+            TabKind::Docker => {}
+            TabKind::K8sPod => {}
+            TabKind::RabbitMq => {}
+            TabKind::Proxy => {}
+            TabKind::Traces => self.traces.as_mut().unwrap().scroll_down(),
+            TabKind::Otlp => {}
+            TabKind::Alerts => {}
+            TabKind::Overview => {}
+            TabKind::Dashboard(_) => {}
+            TabKind::Errors => self.errors.scroll_down(10),
+        }
+    }
+}
+
+/// Appends a sample charted before a restart, keeping at most `max` of them
+fn restore_sample<T>(samples: &mut VecDeque<T>, max: usize, sample: T) {
+    if samples.len() >= max {
+        samples.pop_front();
+    }
+    samples.push_back(sample);
+}
+
+/// Values of the i-th polled source as a chart of their own, or the error they failed with
+fn append_polled_values(charts: &mut PrometheusTab, errors: &mut [Option<String>], i: usize, values: Result<Vec<(String, f64)>, String>) {
+    match values {
+        Ok(vs) => {
+            let samples = vs.into_iter()
+                .map(|(series, value)| MetricSample { selector: i, series, value, counter: false })
+                .collect();
+            charts.append_metrics(PrometheusMetrics { timestamp: Local::now(), samples, metrics: vec![], errors: vec![] });
+            errors[i] = None;
+        }
+        Err(e) => errors[i] = Some(e),
+    }
+}
+
+/// Sources to poll on this tick, marking them as polled. Those without an interval are polled every tick
+fn due_polls(last_polled: &mut [Option<Instant>], intervals: &[Option<Duration>], now: Instant) -> Vec<usize> {
+    let mut due = vec![];
+    for (i, last) in last_polled.iter_mut().enumerate() {
+        let is_due = match (*last, intervals[i]) {
+            (Some(t), Some(interval)) => now.duration_since(t) >= interval,
+            _ => true,
+        };
+        if is_due {
+            *last = Some(now);
+            due.push(i);
+        }
+    }
+    due
+}
+
+/// Requests of the same kind are in flight together, apart from those of configured metrics, commands and queries
+fn in_flight_key(request: &FetcherRequest) -> (Discriminant<FetcherRequest>, usize) {
+    let index = match request {
+        FetcherRequest::JsonMetric(i) | FetcherRequest::CommandValues(i) | FetcherRequest::SqlValues(i) => *i,
+        _ => 0,
+    };
+    (std::mem::discriminant(request), index)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::time::{Duration, Instant};
+
+    use chrono::{Local, TimeZone};
+    use tui::layout::Rect;
+
+    use panopticon_core::actuator::model::{ActuatorSettings, JvmThread};
+    use panopticon_core::akka::model::{ActorCountAlertSettings, ActorStats, ActorSystemFlavor, ActorTree, ActorTreeNode, AkkaSettings, DeadLetter, DeadLetterMetrics, HistogramTotals, HttpSettings, KamonMetrics, RemoteTotals};
+    use panopticon_core::docker::model::{DockerSettings, DockerStats};
+    use panopticon_core::error::FetchError;
+    use panopticon_core::fetcher::{FetcherRequest, FetcherResponse, JMXConnectionStatus, Sources};
+    use panopticon_core::jmx::model::{AkkaClusterStatus, ClusterMember, HikariAlertSettings, HikariMetrics, HikariOperation, JMXConnectionSettings, JMXLogger, JvmOperation, LoggingBackend, UnreachableMember};
+    use panopticon_core::json_metrics::model::JsonMetricSettings;
+    use panopticon_core::logs::model::{LogLevel, LogLines, LogSettings};
+    use panopticon_core::postgres::model::{PostgresActivity, PostgresBackend, PostgresDatabaseStats, PostgresSettings};
+    use panopticon_core::prometheus::model::{MetricSample, PrometheusMetrics};
+    use panopticon_core::redis::model::{RedisInfo, RedisSettings};
+    use panopticon_core::statsd::model::{StatsdKind, StatsdMetrics, StatsdSample, StatsdSettings};
+    use panopticon_core::traces::model::{RawSpan, RecentTraces, Trace, TraceBackend, TracesSettings};
+    use panopticon_core::zio::model::{Fiber, FiberCount, FiberStatus};
+
+    use crate::alerts::model::{AlertRule, MetricValue};
+    use crate::app::{App, DebugStats, StatefulList, TabKind, ToastLevel};
+    use crate::app::actor_tree::{ActorChange, AkkaActorTreeTab};
+    use crate::app::prometheus::PrometheusTab;
+    use crate::app::slick::SlickSeries;
+    use crate::config::Config;
+    use crate::dashboard::DashboardSettings;
+    use crate::ui::Redraw;
+
+    fn postgres_settings() -> PostgresSettings {
+        PostgresSettings { connection: "postgresql://localhost/app".to_owned(), timeout: 1000 }
+    }
+
+    fn jmx_settings() -> JMXConnectionSettings {
+        JMXConnectionSettings {
+            address: "http://localhost:8778/jolokia".to_owned(),
+            username: None,
+            password: None,
+            db_pool_name: Some("db".to_owned()),
+            kafka: false,
+            cassandra_domain: None,
+            akka_dispatchers: None,
+            loggers: false,
+            thread_pools: vec![],
+            jvm_info: false,
+            heap_dump_dir: "/tmp".to_owned(),
+            gc_pause_threshold: 200,
+            ssl: None,
+            notifications: vec![],
+            allow_operations: true,
+            hikari_alerts: HikariAlertSettings { exhausted_ticks: 2, waiting_threshold: 5 },
+            akka_cluster: false,
+        }
+    }
+
+    fn akka_settings() -> AkkaSettings {
+        AkkaSettings {
+            tree_address: "http://localhost:8080/actor-tree".to_owned(),
+            count_address: "http://localhost:8080/actor-count".to_owned(),
+            tree_timeout: 1000,
+            count_timeout: 1000,
+            dead_letters_address: None,
+            mailbox_address: None,
+            kamon_address: None,
+            http: HttpSettings::default(),
+            flavor: ActorSystemFlavor::Akka,
+            excluded_actors: vec![],
+            watched_actors: vec![],
+            tree_depth: None,
+            count_alerts: ActorCountAlertSettings { growth_ticks: 3, limit: Some(100) },
+        }
+    }
+
+    fn hikari_metrics(active: i32, waiting: i32) -> HikariMetrics {
+        HikariMetrics {
+            timestamp: Local::now(),
+            total: 10,
+            active,
+            idle: 10 - active,
+            waiting,
+            max_pool_size: 10,
+        }
+    }
+
+    #[test]
+    fn slick_tab_alerts_on_pool_exhaustion() {
+        let mut app = App::new("test", &Sources { jmx: Some(jmx_settings()), ..Sources::default() }, &Config::default());
+        let slick = app.slick.as_mut().unwrap();
+
+        slick.append_hikari_metrics(hikari_metrics(10, 0));
+        assert!(slick.hikari_alert.is_none());
+        slick.append_hikari_metrics(hikari_metrics(10, 0));
+        assert!(slick.hikari_alert.is_some());
+        slick.append_hikari_metrics(hikari_metrics(5, 0));
+        assert!(slick.hikari_alert.is_none());
+        slick.append_hikari_metrics(hikari_metrics(5, 6));
+        assert!(slick.hikari_alert.is_some());
+        assert_eq!(slick.alert_events.len(), 3);
+
+        app.on_tick();
+        assert_eq!(app.tab_titles(), vec!["! Slick !", "Errors"]);
+        assert_eq!(app.alerts().iter().map(|(tab, _)| *tab).collect::<Vec<_>>(), vec!["Slick"]);
+    }
+
+    #[test]
+    fn slick_tab_invokes_operations_after_confirmation() {
+        let mut app = App::new("test", &Sources { jmx: Some(jmx_settings()), ..Sources::default() }, &Config::default());
+        app.slick.as_mut().unwrap().has_hikari = true;
+
+        app.on_key('s');
+        assert!(matches!(app.confirmation.as_ref().map(|c| &c.request), Some(FetcherRequest::HikariOperation(HikariOperation::SuspendPool))));
+        // the dialog takes all the keys
+        app.on_key('r');
+        assert!(app.take_fetcher_requests().is_empty());
+
+        app.on_key('y');
+        assert!(app.confirmation.is_none());
+        match app.take_fetcher_requests().as_slice() {
+            [FetcherRequest::HikariOperation(HikariOperation::SuspendPool)] => {}
+            _ => panic!("expected a single suspendPool request"),
+        }
+
+        app.on_key('e');
+        app.on_escape();
+        assert!(app.confirmation.is_none());
+        assert!(app.take_fetcher_requests().is_empty());
+    }
+
+    #[test]
+    fn slick_tab_toggles_series_with_number_keys() {
+        let mut app = App::new("test", &Sources { jmx: Some(jmx_settings()), ..Sources::default() }, &Config::default());
+
+        app.on_key('2');
+        app.on_key('5');
+        app.on_key('9');
+        let slick = app.slick.as_ref().unwrap();
+        assert_eq!(slick.hidden_series, vec![SlickSeries::QueueSize, SlickSeries::HikariActive]);
+        assert!(slick.is_shown(SlickSeries::ActiveThreads));
+
+        app.on_key('2');
+        let slick = app.slick.as_ref().unwrap();
+        assert!(slick.is_shown(SlickSeries::QueueSize));
+        assert_eq!(slick.status_message.as_deref(), Some("Slick queue size shown"));
+    }
+
+    #[test]
+    fn jvm_tab_invokes_operations_after_confirmation() {
+        let mut app = App::new("test", &Sources { jmx: Some(JMXConnectionSettings { jvm_info: true, ..jmx_settings() }), ..Sources::default() }, &Config::default());
+        // after the Overview and Slick tabs
+        app.on_key('3');
+
+        app.on_key('h');
+        let path = match app.confirmation.as_ref().map(|c| &c.request) {
+            Some(FetcherRequest::JvmOperation(JvmOperation::DumpHeap(path))) => path.clone(),
+            _ => panic!("expected a pending heap dump"),
+        };
+        assert!(path.starts_with("/tmp/heap-") && path.ends_with(".hprof"));
+        app.on_escape();
+        assert!(app.take_fetcher_requests().is_empty());
+
+        app.on_key('g');
+        app.on_key('y');
+        match app.take_fetcher_requests().as_slice() {
+            [FetcherRequest::JvmOperation(JvmOperation::Gc)] => {}
+            _ => panic!("expected a single gc request"),
+        }
+        assert_eq!(app.jvm.as_ref().unwrap().status_message.as_deref(), Some("Garbage collection..."));
+
+        let jvm = app.jvm.as_mut().unwrap();
+        jvm.on_operation_result(JvmOperation::Gc, Err("Connection refused".to_owned()));
+        assert_eq!(jvm.operations_log.back().unwrap().1, "Garbage collection failed: Connection refused");
+    }
+
+    #[test]
+    fn debug_stats_keep_the_latest_timings() {
+        let mut debug = DebugStats::new();
+        assert_eq!(debug.frame_stats(), None);
+        for ms in 1..=DebugStats::MAX_FRAMES as u64 + 2 {
+            debug.on_frame(Duration::from_millis(ms));
+        }
+        assert_eq!(debug.frame_stats(), Some((Duration::from_micros(52500), Duration::from_millis(102))));
+
+        debug.on_fetch("JMX", Duration::from_millis(40));
+        debug.on_fetch("Akka", Duration::from_millis(900));
+        debug.on_fetch("JMX", Duration::from_millis(60));
+        assert_eq!(debug.fetch_times, vec![("JMX", Duration::from_millis(60)), ("Akka", Duration::from_millis(900))]);
+    }
+
+    #[test]
+    fn large_fiber_dumps_are_confirmed() {
+        let mut app = App::new("test", &Sources { zio_zmx: Some("localhost:6789".to_owned()), ..Sources::default() }, &Config::default());
+        app.request_fiber_dump();
+        assert!(matches!(app.take_fetcher_requests().as_slice(), [FetcherRequest::FiberDump]));
+
+        let count = FiberCount { timestamp: Local::now(), done: 0, finishing: 0, running: 2000, suspended: 9000 };
+        app.zmx.as_mut().unwrap().fiber_counts.push_back(count);
+        app.request_fiber_dump();
+        assert!(app.take_fetcher_requests().is_empty());
+        assert!(app.confirmation.as_ref().and_then(|c| c.warning.as_deref()).is_some_and(|w| w.contains("11000 fibers")));
+        app.on_key('n');
+        assert!(app.confirmation.is_none() && app.take_fetcher_requests().is_empty());
+    }
+
+    #[test]
+    fn actor_tree_keeps_collapsed_subtrees_and_highlights_changes() {
+        let mut app = App::new("test", &Sources { akka: Some(akka_settings()), ..Sources::default() }, &Config::default());
+        let node = |id: usize, name: &str, parent: Option<usize>| ActorTreeNode { name: name.to_owned(), parent, id };
+        let tab = app.actor_tree.as_mut().unwrap();
+        tab.update_actor_tree(vec![
+            node(1, "user", None), node(2, "a", Some(1)),
+            node(3, "system", None), node(4, "log", Some(3)), node(5, "sharding", Some(3)), node(6, "region", Some(5)),
+        ]);
+        assert_eq!(tab.actor_paths, vec!["user", "user/a", "system", "system/log", "system/sharding", "system/sharding/region"]);
+
+        app.on_down();
+        app.on_down();
+        app.on_down();
+        app.on_key(' ');
+        let tab = app.actor_tree.as_mut().unwrap();
+        assert_eq!(tab.actor_paths, vec!["user", "user/a", "system"]);
+        assert!(tab.actors.items[2].ends_with("system (+3)"));
+
+        // ids are assigned anew on every fetch
+        tab.update_actor_tree(vec![
+            node(1, "system", None), node(2, "sharding", Some(1)), node(3, "user", None), node(4, "a", Some(3)), node(5, "b", Some(3)),
+        ]);
+        assert_eq!(tab.actor_paths, vec!["system", "user", "user/a", "user/b"]);
+        assert_eq!(tab.actors.state.selected(), Some(0));
+
+        assert_eq!(tab.actor_changes.get("user/b"), Some(&(ActorChange::Appeared, AkkaActorTreeTab::ACTOR_CHANGE_TICKS)));
+
+        // actors that are gone are shown until their highlight fades
+        app.on_key('e');
+        let tab = app.actor_tree.as_ref().unwrap();
+        assert_eq!(tab.actor_paths, vec!["system", "system/sharding", "system/sharding/region", "system/log", "user", "user/a", "user/b"]);
+        assert_eq!(tab.actor_changes.get("system/log"), Some(&(ActorChange::Disappeared, AkkaActorTreeTab::ACTOR_CHANGE_TICKS)));
+
+        // the selection moves up to the parent of an actor that's gone
+        app.actor_tree.as_mut().unwrap().actors.state.select(Some(2));
+        for _ in 0..AkkaActorTreeTab::ACTOR_CHANGE_TICKS {
+            app.on_tick();
+        }
+        let tab = app.actor_tree.as_ref().unwrap();
+        assert_eq!(tab.actor_paths.len(), 5);
+        assert_eq!(tab.selected_actor_path().as_deref(), Some("system/sharding"));
+        assert!(tab.actor_changes.is_empty());
+    }
+
+    #[test]
+    fn cancelled_actor_tree_fetches_are_dropped() {
+        let mut app = App::new("test", &Sources { akka: Some(akka_settings()), ..Sources::default() }, &Config::default());
+        app.reload_actor_tree();
+        app.reload_actor_tree();
+        let requests = app.take_fetcher_requests();
+        assert!(matches!(requests.as_slice(), [FetcherRequest::ActorTree(1)]));
+
+        app.on_escape();
+        assert!(matches!(app.take_fetcher_requests().as_slice(), [FetcherRequest::CancelActorTree(1)]));
+        app.reload_actor_tree();
+        let tab = app.actor_tree.as_mut().unwrap();
+        assert!(!tab.finish_tree_fetch(1));
+        assert!(tab.tree_fetch.is_some());
+        assert!(tab.finish_tree_fetch(2));
+        assert!(tab.tree_fetch.is_none());
+    }
+
+    #[test]
+    fn subtrees_are_fetched_when_expanded() {
+        let mut app = App::new("test", &Sources { akka: Some(AkkaSettings { tree_depth: Some(2), ..akka_settings() }), ..Sources::default() }, &Config::default());
+        let node = |id: usize, name: &str, parent: Option<usize>| ActorTreeNode { name: name.to_owned(), parent, id };
+        let tab = app.actor_tree.as_mut().unwrap();
+        tab.replace_actor_tree(ActorTree {
+            nodes: vec![node(1, "user", None), node(2, "workers", Some(1))],
+            unloaded: vec![("user/workers".to_owned(), Some(2))].into_iter().collect(),
+        });
+        assert!(tab.actors.items[1].ends_with("workers (+2)"));
+        assert_eq!(tab.subtree_counts, vec![("user/workers".to_owned(), 3, 0)]);
+
+        app.on_down();
+        app.on_down();
+        app.on_key(' ');
+        app.on_key(' ');
+        assert!(matches!(app.take_fetcher_requests().as_slice(), [FetcherRequest::ActorSubtree(p)] if p == "user/workers"));
+
+        let subtree = || ActorTree {
+            nodes: vec![node(1, "w1", None), node(2, "w2", None), node(3, "child", Some(2))],
+            unloaded: HashMap::new(),
+        };
+        let tab = app.actor_tree.as_mut().unwrap();
+        assert!(tab.merge_actor_subtree("user/workers", subtree()).is_empty());
+        assert_eq!(tab.actor_paths, vec!["user", "user/workers", "user/workers/w1", "user/workers/w2", "user/workers/w2/child"]);
+        assert!(tab.unloaded_actors.is_empty());
+        assert_eq!(tab.subtree_counts, vec![("user/workers".to_owned(), 4, 1)]);
+
+        // reloads leave the subtree out, so it's fetched again
+        let requests = tab.replace_actor_tree(ActorTree {
+            nodes: vec![node(1, "user", None), node(2, "workers", Some(1))],
+            unloaded: vec![("user/workers".to_owned(), Some(3))].into_iter().collect(),
+        });
+        assert!(matches!(requests.as_slice(), [FetcherRequest::ActorSubtree(p)] if p == "user/workers"));
+        assert!(tab.actor_changes.is_empty());
+        tab.merge_actor_subtree("user/workers", subtree());
+        assert_eq!(tab.actor_paths.len(), 5);
+
+        // collapsing drops the subtree
+        app.on_key(' ');
+        let tab = app.actor_tree.as_ref().unwrap();
+        assert_eq!(tab.actor_nodes.len(), 2);
+        assert_eq!(tab.unloaded_actors.get("user/workers"), Some(&Some(3)));
+        assert!(tab.actors.items[1].ends_with("workers (+3)"));
+    }
+
+    #[test]
+    fn watched_actors_are_tracked_across_reloads() {
+        let settings = AkkaSettings { watched_actors: vec!["/user/singleton*".to_owned()], ..akka_settings() };
+        let mut app = App::new("test", &Sources { akka: Some(settings), ..Sources::default() }, &Config::default());
+        let node = |id: usize, name: &str, parent: Option<usize>| ActorTreeNode { name: name.to_owned(), parent, id };
+        let tab = app.actor_tree.as_mut().unwrap();
+        tab.update_actor_tree(vec![node(1, "user", None), node(2, "singletonProxy", Some(1)), node(3, "singleton", Some(1))]);
+        tab.update_actor_tree(vec![node(1, "user", None), node(2, "singletonProxy", Some(1))]);
+        tab.update_actor_tree(vec![node(1, "user", None)]);
+        assert_eq!(tab.watches[0].presence, vec![true, true, false]);
+        assert_eq!(tab.status_message.as_deref(), Some("No actor matches watched /user/singleton* anymore"));
+
+        app.on_down();
+        app.on_key('w');
+        let tab = app.actor_tree.as_ref().unwrap();
+        assert_eq!(tab.watches.len(), 2);
+        assert_eq!(tab.watches[1].matches, 1);
+        app.on_key('w');
+        assert_eq!(app.actor_tree.as_ref().unwrap().watches.len(), 1);
+    }
+
+    #[test]
+    fn excluded_actors_are_hidden_from_tree_and_counts_until_toggled() {
+        let settings = AkkaSettings { excluded_actors: vec!["/system/*".to_owned()], ..akka_settings() };
+        let mut app = App::new("test", &Sources { akka: Some(settings), ..Sources::default() }, &Config::default());
+        let node = |id: usize, name: &str, parent: Option<usize>| ActorTreeNode { name: name.to_owned(), parent, id };
+        let tab = app.actor_tree.as_mut().unwrap();
+        tab.update_actor_tree(vec![
+            node(1, "user", None), node(2, "a", Some(1)),
+            node(3, "system", None), node(4, "log", Some(3)), node(5, "sharding", Some(3)), node(6, "region", Some(5)),
+        ]);
+        tab.append_actor_count(6);
+        assert_eq!(tab.actor_paths, vec!["user", "user/a", "system"]);
+        assert_eq!(tab.displayed_actor_counts(), vec![3]);
+
+        app.on_key('f');
+        let tab = app.actor_tree.as_ref().unwrap();
+        assert_eq!(tab.actor_paths.len(), 6);
+        assert_eq!(tab.displayed_actor_counts(), vec![6]);
+    }
+
+    #[test]
+    fn descendants_of_excluded_actors_are_excluded_from_counts_before_they_are_fetched() {
+        let settings = AkkaSettings { excluded_actors: vec!["/system/*".to_owned()], tree_depth: Some(2), ..akka_settings() };
+        let mut tab = AkkaActorTreeTab::new(false, &settings);
+        let node = |id: usize, name: &str, parent: Option<usize>| ActorTreeNode { name: name.to_owned(), parent, id };
+        tab.replace_actor_tree(ActorTree {
+            nodes: vec![node(1, "user", None), node(2, "a", Some(1)), node(3, "system", None), node(4, "sharding", Some(3))],
+            unloaded: vec![("system/sharding".to_owned(), Some(40))].into_iter().collect(),
+        });
+        tab.append_actor_count(44);
+        assert_eq!(tab.actor_paths, vec!["user", "user/a", "system"]);
+        assert_eq!(tab.displayed_actor_counts(), vec![3]);
+    }
+
+    #[test]
+    fn actors_are_counted_by_top_level_subtree() {
+        let settings = AkkaSettings { excluded_actors: vec!["/system/*".to_owned()], ..akka_settings() };
+        let mut tab = AkkaActorTreeTab::new(false, &settings);
+        let node = |id: usize, name: &str, parent: Option<usize>| ActorTreeNode { name: name.to_owned(), parent, id };
+        tab.update_actor_tree(vec![
+            node(1, "user", None), node(2, "sessions", Some(1)), node(3, "s1", Some(2)),
+            node(4, "workers", Some(1)), node(5, "w1", Some(4)), node(6, "w2", Some(4)),
+            node(7, "system", None), node(8, "log", Some(7)),
+        ]);
+        assert_eq!(tab.subtree_counts, vec![
+            ("user/workers".to_owned(), 3, 0), ("user/sessions".to_owned(), 2, 0), ("system/log".to_owned(), 1, 0),
+        ]);
+
+        tab.update_actor_tree(vec![
+            node(1, "user", None), node(2, "sessions", Some(1)),
+            node(3, "workers", Some(1)), node(4, "w1", Some(3)), node(5, "w2", Some(3)), node(6, "w3", Some(3)),
+        ]);
+        let counts: Vec<(&str, u64, i64)> = tab.displayed_subtree_counts().iter().map(|(p, c, d)| (p.as_str(), *c, *d)).collect();
+        assert_eq!(counts, vec![("user/workers", 4, 1), ("user/sessions", 1, -1)]);
+    }
+
+    #[test]
+    fn actor_tab_alerts_on_growing_actor_count() {
+        let mut app = App::new("test", &Sources { akka: Some(akka_settings()), ..Sources::default() }, &Config::default());
+        let tab = app.actor_tree.as_mut().unwrap();
+        for c in &[10, 20, 30] {
+            tab.append_actor_count(*c);
+        }
+        assert!(tab.count_alert.is_none());
+        tab.append_actor_count(40);
+        assert!(tab.count_alert.is_some());
+        assert!(app.has_alert(&TabKind::AkkaActorTree));
+
+        let tab = app.actor_tree.as_mut().unwrap();
+        tab.append_actor_count(40);
+        assert!(tab.count_alert.is_none());
+        tab.append_actor_count(150);
+        assert_eq!(tab.count_alert.as_deref(), Some("Actor count 150 exceeds the limit of 100"));
+
+        let start = tab.actor_counts[0].timestamp;
+        for (i, c) in tab.actor_counts.iter_mut().enumerate() {
+            c.timestamp = start + chrono::Duration::seconds(30 * i as i64);
+        }
+        // from 10 to 150 in 2.5 minutes
+        assert_eq!(tab.actor_growth_per_minute(), Some(56.0));
+    }
+
+    #[test]
+    fn dead_letters_are_counted_per_tick_and_logged_once() {
+        let mut tab = AkkaActorTreeTab::new(false, &AkkaSettings { dead_letters_address: Some("".to_owned()), ..akka_settings() });
+        let letter = |t: i64, message: &str| DeadLetter {
+            timestamp: Local.timestamp_millis(t),
+            sender: "a".to_owned(),
+            recipient: "b".to_owned(),
+            message: message.to_owned(),
+        };
+        let metrics = |count: u64, samples: Vec<DeadLetter>| DeadLetterMetrics { timestamp: Local::now(), count, samples };
+
+        tab.append_dead_letters(metrics(10, vec![letter(1000, "m1")]));
+        tab.append_dead_letters(metrics(13, vec![letter(1000, "m1"), letter(2000, "m2"), letter(3000, "m3")]));
+        // the app was restarted
+        tab.append_dead_letters(metrics(2, vec![letter(3000, "m3")]));
+
+        let counts: Vec<u64> = tab.dead_letter_counts.iter().map(|(_, c)| *c).collect();
+        assert_eq!(counts, vec![0, 3, 2]);
+        let messages: Vec<&str> = tab.dead_letters.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(messages, vec!["m1", "m2", "m3"]);
+    }
+
+    #[test]
+    fn pekko_tab_is_named_after_its_flavor() {
+        let app = App::new("test", &Sources { akka: Some(AkkaSettings { flavor: ActorSystemFlavor::Pekko, ..akka_settings() }), ..Sources::default() }, &Config::default());
+        assert_eq!(app.tab_titles(), vec!["Pekko", "Errors"]);
+    }
+
+    #[test]
+    fn details_of_selected_actor_are_shown() {
+        let mut tab = AkkaActorTreeTab::new(false, &akka_settings());
+        let node = |id: usize, name: &str, parent: Option<usize>| ActorTreeNode { name: name.to_owned(), parent, id };
+        tab.update_actor_tree(vec![node(1, "user", None), node(2, "a", Some(1)), node(3, "b", Some(2)), node(4, "c", Some(3)), node(5, "d", Some(2))]);
+        tab.replace_actor_stats(vec![("user/a".to_owned(), ActorStats { mailbox: Some(3), messages: Some(40), ..ActorStats::default() })].into_iter().collect());
+        assert!(tab.selected_actor_details().is_none());
+
+        tab.select_next_actor();
+        tab.select_next_actor();
+        let details = tab.selected_actor_details().unwrap();
+        assert_eq!((details.path, details.parent), ("user/a", Some("user")));
+        assert_eq!((details.children, details.descendants), (2, 3));
+        assert!(details.first_seen.is_some());
+        assert_eq!(details.stats.and_then(|s| s.messages), Some(40));
+    }
+
+    #[test]
+    fn biggest_mailboxes_go_first() {
+        let mut tab = AkkaActorTreeTab::new(false, &AkkaSettings { mailbox_address: Some("".to_owned()), ..akka_settings() });
+        let stats = |mailbox: u64| ActorStats { mailbox: Some(mailbox), ..ActorStats::default() };
+        let mut sizes: HashMap<String, ActorStats> = (0..20).map(|i| (format!("user/a{}", i), stats(i))).collect();
+        sizes.insert("user/b".to_owned(), stats(19));
+        sizes.insert("user/c".to_owned(), ActorStats { messages: Some(100), ..ActorStats::default() });
+        tab.replace_actor_stats(sizes);
+
+        let top = tab.top_mailboxes();
+        assert_eq!(top.len(), AkkaActorTreeTab::TOP_MAILBOXES);
+        assert_eq!(&top[..3], &[("user/a19", 19), ("user/b", 19), ("user/a18", 18)]);
+    }
+
+    #[test]
+    fn kamon_histograms_are_averaged_since_previous_scrape() {
+        let mut tab = AkkaActorTreeTab::new(false, &AkkaSettings { kamon_address: Some("".to_owned()), ..akka_settings() });
+        assert!(tab.has_mailboxes && tab.has_dead_letters);
+        let metrics = |processing: (f64, u64), mailbox: (f64, u64), dead_letters: u64| KamonMetrics {
+            timestamp: Local::now(),
+            processing_time: vec![("user/a".to_owned(), HistogramTotals { sum: processing.0, count: processing.1 })].into_iter().collect(),
+            mailbox_size: vec![("user/a".to_owned(), HistogramTotals { sum: mailbox.0, count: mailbox.1 })].into_iter().collect(),
+            dead_letters: Some(dead_letters),
+            remote: None,
+        };
+        tab.append_kamon_metrics(metrics((1.0, 100), (50.0, 10), 5));
+        tab.on_kamon_error("Connection refused".to_owned());
+        assert_eq!(tab.status_message.as_deref(), Some("Couldn't scrape Kamon metrics: Connection refused"));
+        tab.append_kamon_metrics(metrics((1.5, 110), (90.0, 20), 8));
+
+        let stats = tab.actor_stats.get("user/a").unwrap();
+        assert_eq!(stats.mailbox, Some(4));
+        assert_eq!(stats.messages, Some(110));
+        assert_eq!(stats.processing_time_ms, Some(50.0));
+        assert_eq!(tab.dead_letter_counts.back().map(|(_, c)| *c), Some(3));
+    }
+
+    #[test]
+    fn cluster_tab_records_membership_events_and_remote_rates() {
+        let mut app = App::new("test", &Sources { jmx: Some(JMXConnectionSettings { akka_cluster: true, ..jmx_settings() }), ..Sources::default() }, &Config::default());
+        assert!(app.tabs.tabs.iter().any(|t| matches!(t.kind, TabKind::Cluster)));
+        let member = |address: &str, status: &str| ClusterMember { address: address.to_owned(), status: status.to_owned(), roles: vec![] };
+        let status = |members: Vec<ClusterMember>, unreachable: Vec<&str>| AkkaClusterStatus {
+            timestamp: Local::now(),
+            self_address: "akka://app@a:25520".to_owned(),
+            members,
+            unreachable: unreachable.into_iter()
+                .map(|n| UnreachableMember { node: n.to_owned(), observed_by: vec!["akka://app@a:25520".to_owned()] })
+                .collect(),
+        };
+        let tab = app.cluster.as_mut().unwrap();
+        tab.append_cluster_status(status(vec![member("akka://app@a:25520", "Up"), member("akka://app@b:25520", "Up")], vec![]));
+        tab.append_cluster_status(status(vec![member("akka://app@a:25520", "Up"), member("akka://app@b:25520", "Up")], vec!["akka://app@b:25520"]));
+        tab.append_cluster_status(status(vec![member("akka://app@a:25520", "Up"), member("akka://app@c:25520", "Joining")], vec![]));
+        tab.append_cluster_status(status(vec![member("akka://app@a:25520", "Up"), member("akka://app@c:25520", "Exiting")], vec![]));
+        tab.append_cluster_status(status(vec![member("akka://app@a:25520", "Up")], vec![]));
+        let events: Vec<&str> = tab.events.iter().map(|(_, e)| e.as_str()).collect();
+        assert_eq!(events, vec![
+            "akka://app@b:25520 is unreachable, observed by akka://app@a:25520",
+            "akka://app@c:25520 joined (Joining)",
+            "akka://app@b:25520 was removed and quarantined",
+            "akka://app@b:25520 is reachable again",
+            "akka://app@c:25520 is Exiting",
+            "akka://app@c:25520 left the cluster",
+        ]);
+
+        let t = Local.timestamp(1_600_000_000, 0);
+        let totals = |messages: u64| RemoteTotals { messages_in: messages, messages_out: messages / 2, ..RemoteTotals::default() };
+        tab.append_remote_totals(t, totals(100));
+        tab.append_remote_totals(t + chrono::Duration::seconds(2), totals(300));
+        // restarted node
+        tab.append_remote_totals(t + chrono::Duration::seconds(4), totals(10));
+        let rates: Vec<(f64, f64)> = tab.remoting.iter().map(|r| (r.messages_in, r.messages_out)).collect();
+        assert_eq!(rates, vec![(100.0, 50.0)]);
+        assert!(!app.has_alert(&TabKind::Cluster));
+    }
+
+    #[test]
+    fn prometheus_counters_are_charted_as_rates() {
+        let mut tab = PrometheusTab::new(vec!["requests_total".to_owned(), "queue_size".to_owned()]);
+        let t = Local.timestamp(1_600_000_000, 0);
+        let scrape = |secs: i64, requests: f64, queue: f64| PrometheusMetrics {
+            timestamp: t + chrono::Duration::seconds(secs),
+            samples: vec![
+                MetricSample { selector: 0, series: "requests_total".to_owned(), value: requests, counter: true },
+                MetricSample { selector: 1, series: "queue_size".to_owned(), value: queue, counter: false },
+            ],
+            metrics: vec![],
+            errors: vec![],
+        };
+        tab.append_metrics(scrape(0, 100.0, 3.0));
+        tab.append_metrics(scrape(2, 120.0, 5.0));
+        tab.append_metrics(scrape(4, 10.0, 4.0));
+        let values = |i: usize| -> Vec<f64> { tab.series[i][0].values.iter().map(|v| v.1).collect() };
+        assert_eq!(values(0), vec![10.0]);
+        assert_eq!(values(1), vec![3.0, 5.0, 4.0]);
+    }
+
+    #[test]
+    fn failed_promql_queries_are_shown_until_they_answer() {
+        let mut tab = PrometheusTab::new(vec!["up".to_owned(), "rate(".to_owned()]);
+        let results = |errors: Vec<String>| PrometheusMetrics {
+            timestamp: Local::now(),
+            samples: vec![MetricSample { selector: 0, series: "result".to_owned(), value: 1.0, counter: false }],
+            metrics: vec![],
+            errors,
+        };
+        tab.append_metrics(results(vec!["rate(: parse error".to_owned()]));
+        assert_eq!(tab.series[0][0].values.len(), 1);
+        assert_eq!(tab.errors, vec!["rate(: parse error"]);
+        tab.on_error("Connection refused".to_owned());
+        assert_eq!(tab.errors, vec!["Connection refused"]);
+        tab.append_metrics(results(vec![]));
+        assert!(tab.errors.is_empty());
+    }
+
+    #[test]
+    fn actuator_tab_lists_threads_by_state() {
+        let settings = ActuatorSettings { address: "http://localhost:8080/actuator".to_owned(), metrics: vec![], timeout: 1000 };
+        let mut app = App::new("test", &Sources { actuator: Some(settings), ..Sources::default() }, &Config::default());
+        let thread = |id: u64, name: &str, state: &str| JvmThread { id, name: name.to_owned(), state: state.to_owned(), dump: format!("dump of {}", name) };
+        let tab = app.actuator.as_mut().unwrap();
+        tab.replace_thread_dump(vec![thread(1, "main", "WAITING"), thread(12, "worker-2", "BLOCKED"), thread(11, "worker-1", "BLOCKED")]);
+        assert_eq!(tab.threads.items[0], "BLOCKED       #11 worker-1");
+        assert_eq!(tab.selected_thread_dump.0, "dump of worker-1");
+
+        app.on_up();
+        assert_eq!(app.actuator.as_ref().unwrap().selected_thread_dump.0, "dump of main");
+
+        let tab = app.actuator.as_mut().unwrap();
+        tab.on_thread_dump_error("Request to get thread dump failed with status 404 Not Found".to_owned());
+        assert!(tab.threads.items.is_empty());
+        assert!(tab.selected_thread_dump.0.ends_with("404 Not Found"));
+    }
+
+    #[test]
+    fn json_metrics_are_polled_at_their_intervals() {
+        let metric = |name: &str, interval: Option<u64>| JsonMetricSettings {
+            name: name.to_owned(),
+            url: "http://localhost:8080/stats".to_owned(),
+            json_pointer: format!("/{}", name),
+            interval,
+        };
+        let sources = Sources { json_metrics: vec![metric("a", None), metric("b", Some(5000))], ..Sources::default() };
+        let mut app = App::new("test", &sources, &Config::default());
+        let tab = app.json_metrics.as_mut().unwrap();
+        let start = Instant::now();
+        assert_eq!(tab.due_metrics(start), vec![0, 1]);
+        assert_eq!(tab.due_metrics(start + Duration::from_secs(1)), vec![0]);
+        assert_eq!(tab.due_metrics(start + Duration::from_secs(5)), vec![0, 1]);
+
+        tab.on_value(1, Ok(3.0));
+        tab.on_value(0, Err("Nothing at /a".to_owned()));
+        assert_eq!(tab.charts.series[1][0].name, "/b");
+        assert_eq!(tab.errors, vec![Some("Nothing at /a".to_owned()), None]);
+    }
+
+    #[test]
+    fn statsd_charts_are_added_as_metrics_arrive() {
+        let metrics = |samples: Vec<(&str, &str, f64)>| StatsdMetrics {
+            timestamp: Local::now(),
+            samples: samples.into_iter()
+                .map(|(name, series, value)| StatsdSample { name: name.to_owned(), series: series.to_owned(), value, counter: false })
+                .collect(),
+            metrics: vec![("queue".to_owned(), StatsdKind::Gauge), ("db".to_owned(), StatsdKind::Timer)],
+            invalid_lines: 2,
+            error: None,
+        };
+        let mut app = App::new("test", &Sources { statsd: Some(StatsdSettings { address: "0.0.0.0:8125".to_owned(), metrics: vec![] }), ..Sources::default() }, &Config::default());
+        let tab = app.statsd.as_mut().unwrap();
+        tab.append_metrics(metrics(vec![("queue", "queue", 7.0)]));
+        tab.append_metrics(metrics(vec![("queue", "queue", 5.0), ("db", "mean", 20.0), ("db", "max", 30.0)]));
+        assert_eq!(tab.charts.selectors, vec!["queue".to_owned(), "db".to_owned()]);
+        assert_eq!(tab.charts.series[0][0].values.len(), 2);
+        assert_eq!(tab.charts.series[1].len(), 2);
+        assert_eq!(tab.charts.metrics[1], ("db".to_owned(), "timer".to_owned()));
+        assert_eq!(tab.invalid_lines, 2);
+
+        let mut app = App::new("test", &Sources { statsd: Some(StatsdSettings { address: "0.0.0.0:8125".to_owned(), metrics: vec!["db".to_owned()] }), ..Sources::default() }, &Config::default());
+        let tab = app.statsd.as_mut().unwrap();
+        tab.append_metrics(metrics(vec![("queue", "queue", 7.0), ("db", "mean", 20.0)]));
+        assert_eq!(tab.charts.selectors, vec!["db".to_owned()]);
+        assert_eq!(tab.charts.series[0][0].name, "mean");
+    }
+
+    #[test]
+    fn logs_are_paused_and_searched() {
+        let lines = |lines: &[&str]| LogLines { lines: lines.iter().map(|l| l.to_string()).collect(), error: None };
+        let mut app = App::new("test", &Sources { logs: Some(LogSettings { source: "app.log".to_owned() }), ..Sources::default() }, &Config::default());
+        app.logs.as_mut().unwrap().append_lines(lines(&["12:00:01 ERROR Request failed", "java.io.IOException: Broken pipe", "12:00:02 INFO Request served"]));
+        let levels: Vec<Option<LogLevel>> = app.logs.as_ref().unwrap().lines.iter().map(|l| l.level).collect();
+        assert_eq!(levels, vec![Some(LogLevel::Error), Some(LogLevel::Error), Some(LogLevel::Info)]);
+
+        app.on_key('p');
+        app.logs.as_mut().unwrap().append_lines(lines(&["12:00:03 WARN Slow request"]));
+        assert_eq!(app.logs.as_ref().unwrap().lines.len(), 3);
+        app.on_key('p');
+        assert_eq!(app.logs.as_ref().unwrap().lines.len(), 4);
+
+        // q is typed into the search rather than quitting
+        app.on_key('/');
+        for c in "reQuest f".chars() {
+            app.on_key(c);
+        }
+        app.on_backspace();
+        app.on_key('f');
+        assert!(app.is_typing() && !app.should_quit);
+        app.logs.as_mut().unwrap().finish_search();
+        let shown: Vec<&str> = app.logs.as_ref().unwrap().shown_lines().iter().map(|l| l.text.as_str()).collect();
+        assert_eq!(shown, vec!["12:00:01 ERROR Request failed"]);
+        app.on_escape();
+        assert_eq!(app.logs.as_ref().unwrap().shown_lines().len(), 4);
+    }
+
+    #[test]
+    fn postgres_transactions_are_charted_as_rates() {
+        let activity = |secs: i64, commits: u64, states: &[&str]| PostgresActivity {
+            timestamp: Local.timestamp(1_600_000_000 + secs, 0),
+            backends: states.iter().enumerate().map(|(i, s)| PostgresBackend {
+                pid: i as u32,
+                user: "app".to_owned(),
+                database: "orders".to_owned(),
+                state: s.to_string(),
+                query_secs: i as f64,
+                wait_event: None,
+                query: "SELECT 1".to_owned(),
+            }).collect(),
+            databases: vec![PostgresDatabaseStats { name: "orders".to_owned(), commits, rollbacks: 0, blocks_read: 0, blocks_hit: 0 }],
+        };
+        let mut app = App::new("test", &Sources { postgres: Some(postgres_settings()), ..Sources::default() }, &Config::default());
+        let tab = app.postgres.as_mut().unwrap();
+        tab.append_activity(activity(0, 100, &["idle"]));
+        tab.append_activity(activity(2, 150, &["active", "idle", "active"]));
+        // statistics reset
+        tab.append_activity(activity(4, 10, &["idle"]));
+        assert_eq!(tab.transactions.iter().map(|t| t.1).collect::<Vec<_>>(), vec![25.0]);
+        assert_eq!(tab.connections[1].1, vec![("active".to_owned(), 2), ("idle".to_owned(), 1)]);
+        assert_eq!(activity(0, 0, &["active", "idle", "active"]).longest_running().map(|b| b.pid), Some(2));
+    }
+
+    #[test]
+    fn redis_hit_rate_is_computed_between_polls() {
+        let info = |secs: i64, hits: u64, misses: u64| RedisInfo {
+            timestamp: Local.timestamp(1_600_000_000 + secs, 0),
+            version: "6.0.9".to_owned(),
+            used_memory: 1024,
+            max_memory: 0,
+            connected_clients: 1,
+            blocked_clients: 0,
+            ops_per_sec: 0.0,
+            keyspace_hits: hits,
+            keyspace_misses: misses,
+            evicted_keys: 0,
+            slowlog: vec![],
+        };
+        let redis = RedisSettings { address: "localhost:6379".to_owned(), password: None, timeout: 1000 };
+        let mut app = App::new("test", &Sources { redis: Some(redis), ..Sources::default() }, &Config::default());
+        let tab = app.redis.as_mut().unwrap();
+        tab.append_info(info(0, 100, 100));
+        tab.append_info(info(1, 190, 110));
+        // no lookups
+        tab.append_info(info(2, 190, 110));
+        // server restarted
+        tab.append_info(info(3, 5, 0));
+        assert_eq!(tab.hit_rates.iter().map(|h| h.1).collect::<Vec<_>>(), vec![90.0]);
+        assert_eq!(tab.info.len(), 4);
+    }
+
+    #[test]
+    fn docker_cpu_and_network_are_charted_as_rates() {
+        let stats = |secs: i64, cpu_usage: u64, rx_bytes: u64| DockerStats {
+            timestamp: Local.timestamp(1_600_000_000 + secs, 0),
+            cpu_usage,
+            system_cpu_usage: secs as u64 * 4_000_000_000,
+            online_cpus: 4,
+            cpu_limit: None,
+            memory_usage: 0,
+            memory_limit: 0,
+            rx_bytes,
+            tx_bytes: 0,
+        };
+        let docker = DockerSettings { socket: "/var/run/docker.sock".to_owned(), container: "orders".to_owned(), timeout: 1000 };
+        let mut app = App::new("test", &Sources { docker: Some(docker), ..Sources::default() }, &Config::default());
+        let tab = app.docker.as_mut().unwrap();
+        tab.append_stats(stats(1, 1_000_000_000, 1000));
+        tab.append_stats(stats(3, 2_500_000_000, 5000));
+        // container restarted
+        tab.append_stats(stats(5, 100_000_000, 10));
+        assert_eq!(tab.cpu.iter().map(|c| c.1).collect::<Vec<_>>(), vec![75.0]);
+        assert_eq!(tab.network.iter().map(|n| n.1).collect::<Vec<_>>(), vec![2000.0]);
+    }
+
+    #[test]
+    fn traces_tab_keeps_selected_trace_across_polls() {
+        let span = |id: &str, parent: Option<&str>, start: u64| RawSpan {
+            id: id.to_owned(),
+            parent_id: parent.map(|p| p.to_owned()),
+            service: "orders".to_owned(),
+            operation: format!("op {}", id),
+            start,
+            duration: 1500,
+            error: false,
+        };
+        let trace = |id: &str, start: u64| Trace::new(id.to_owned(), vec![span("a", None, start), span("b", Some("a"), start + 200)]);
+        let settings = TracesSettings { address: "http://localhost:16686".to_owned(), backend: TraceBackend::Jaeger, service: "orders".to_owned(), limit: 20, timeout: 1000 };
+        let mut app = App::new("test", &Sources { traces: Some(settings), ..Sources::default() }, &Config::default());
+        let tab = app.traces.as_mut().unwrap();
+        tab.replace_traces(RecentTraces { timestamp: Local::now(), traces: vec![trace("t2", 2_000_000), trace("t1", 1_000_000)] });
+        tab.select_next_trace();
+        assert_eq!(tab.selected().unwrap().id, "t1");
+        assert_eq!(tab.selected_trace.0, "└─#   op a [orders] 1.5ms at +0µs\n  └─# op b [orders] 1.5ms at +200µs");
+
+        tab.replace_traces(RecentTraces { timestamp: Local::now(), traces: vec![trace("t3", 3_000_000), trace("t2", 2_000_000), trace("t1", 1_000_000)] });
+        assert_eq!(tab.list.state.selected(), Some(2));
+    }
+
+    #[test]
+    fn loggers_tab_changes_level_of_selected_logger() {
+        let mut app = App::new("test", &Sources { jmx: Some(JMXConnectionSettings { loggers: true, ..jmx_settings() }), ..Sources::default() }, &Config::default());
+        app.on_key('3');
+        let logger = JMXLogger {
+            name: "com.example".to_owned(),
+            level: "INFO".to_owned(),
+            backend: LoggingBackend::Logback,
+            mbean: "ch.qos.logback.classic:Name=default,Type=ch.qos.logback.classic.jmx.JMXConfigurator".to_owned(),
+        };
+        app.loggers.as_mut().unwrap().replace_loggers(vec![logger]);
+
+        app.on_key('d');
+        assert!(app.take_fetcher_requests().is_empty());
+
+        app.on_down();
+        app.on_key('d');
+        let requests = app.take_fetcher_requests();
+        let (logger, level) = match requests.as_slice() {
+            [FetcherRequest::SetLoggerLevel(logger, level)] => (logger.clone(), level.clone()),
+            _ => panic!("expected a single level change request"),
+        };
+        assert_eq!(logger.name, "com.example");
+        assert_eq!(level, "DEBUG");
+
+        app.on_logger_level_result(logger, level, Ok(()));
+        match app.take_fetcher_requests().as_slice() {
+            [FetcherRequest::Loggers] => {}
+            _ => panic!("expected loggers to be reloaded"),
+        }
+    }
+
+    #[test]
+    fn slick_tab_picks_discovered_db_pool() {
+        let mut app = App::new("test", &Sources { jmx: Some(JMXConnectionSettings { db_pool_name: None, ..jmx_settings() }), ..Sources::default() }, &Config::default());
+        app.slick.as_mut().unwrap().replace_discovered_pools(vec!["db1".to_owned(), "db2".to_owned()]);
+
+        app.on_down();
+        app.select_db_pool();
+        let slick = app.slick.as_ref().unwrap();
+        assert_eq!(slick.db_pool_name, Some("db2".to_owned()));
+        assert!(slick.pool_picker.is_none());
+        match app.take_fetcher_requests().as_slice() {
+            [FetcherRequest::SelectDbPool(name), FetcherRequest::SlickConfig, FetcherRequest::HikariMetrics, FetcherRequest::SlickMetrics] =>
+                assert_eq!(name, "db2"),
+            _ => panic!("expected the pool to be selected and its metrics requested"),
+        }
+
+        // replays only have the answer
+        let slick = app.slick.as_mut().unwrap();
+        slick.on_pool_selected("db2".to_owned());
+        assert_eq!(slick.status_message.as_deref(), Some("Showing metrics of pool db2"));
+        slick.replace_discovered_pools(vec!["db1".to_owned(), "db2".to_owned()]);
+        slick.on_pool_selected("db1".to_owned());
+        assert_eq!(slick.db_pool_name, Some("db1".to_owned()));
+        assert!(slick.pool_picker.is_none());
+    }
+
+    #[test]
+    fn failing_sources_are_shown_and_given_up_on_after_a_while() {
+        let mut app = App::new("test", &Sources { jmx: Some(jmx_settings()), ..Sources::default() }, &Config::default());
+
+        app.on_fetcher_response(&FetcherResponse::Loggers(Err(FetchError::from("No such MBean"))));
+        app.on_fetcher_response(&FetcherResponse::Loggers(Err(FetchError::from("No such MBean"))));
+        assert!(!app.should_quit);
+        assert_eq!(app.toasts.iter().map(|t| t.message.as_str()).collect::<Vec<_>>(), vec!["JMX failed: No such MBean"]);
+        let since = app.sources[0].failing_since.unwrap();
+        assert!(app.source_given_up(since).is_none());
+        assert_eq!(app.source_given_up(since + App::SOURCE_FAILURE_GRACE), Some(&FetchError::from("No such MBean")));
+
+        app.on_fetcher_response(&FetcherResponse::Loggers(Ok(vec![])));
+        assert!(app.sources[0].failing_since.is_none());
+        assert!(app.source_given_up(since + App::SOURCE_FAILURE_GRACE).is_none());
+    }
+
+    #[test]
+    fn help_is_toggled_and_closed_before_anything_else() {
+        let mut app = App::new("test", &Sources { jmx: Some(jmx_settings()), ..Sources::default() }, &Config::default());
+        app.on_key('?');
+        assert!(app.show_help);
+        app.on_key('?');
+        assert!(!app.show_help);
+
+        app.slick.as_mut().unwrap().has_hikari = true;
+        app.on_key('s');
+        app.on_key('?');
+        app.on_escape();
+        assert!(!app.show_help);
+        assert!(app.confirmation.is_some());
+        assert!(!TabKind::ZMX.keybindings().is_empty());
+    }
+
+    #[test]
+    fn only_the_items_drawn_are_visible() {
+        let mut fibers = StatefulList::with_items((0..50_000).map(|i| format!("#{}", i)).collect());
+        fibers.state.select(Some(41_234));
+        fibers.on_draw(Rect::new(1, 1, 30, 20));
+        assert_eq!(fibers.visible(), 41_215..41_235);
+        assert_eq!(fibers.visible_state().selected(), Some(19));
+
+        fibers.state.select(Some(2));
+        fibers.on_draw(Rect::new(1, 1, 30, 20));
+        assert_eq!((fibers.visible(), fibers.visible_state().selected()), (2..22, Some(0)));
+        let mut short = StatefulList::with_items(vec!["#1", "#2"]);
+        short.on_draw(Rect::new(1, 1, 30, 20));
+        assert_eq!((short.visible(), short.visible_state().selected()), (0..2, None));
+    }
+
+    #[test]
+    fn mouse_selects_tabs_and_list_items() {
+        let mut app = App::new("test", &Sources { jmx: Some(JMXConnectionSettings { db_pool_name: None, ..jmx_settings() }), ..Sources::default() }, &Config::default());
+        app.slick.as_mut().unwrap().replace_discovered_pools((0..10).map(|i| format!("db{}", i)).collect());
+        for _ in 0..6 {
+            app.on_down();
+        }
+        // scrolled by tui to keep the selection in the last line
+        let picker = app.slick.as_mut().unwrap().pool_picker.as_mut().unwrap();
+        picker.on_draw(Rect::new(1, 4, 20, 3));
+        app.on_click(2, 4);
+        assert_eq!(app.slick.as_ref().unwrap().pool_picker.as_ref().unwrap().state.selected(), Some(4));
+        app.on_scroll(2, 5, false);
+        assert_eq!(app.slick.as_ref().unwrap().pool_picker.as_ref().unwrap().state.selected(), Some(5));
+        app.on_click(2, 8);
+        assert_eq!(app.slick.as_ref().unwrap().pool_picker.as_ref().unwrap().state.selected(), Some(5));
+
+        app.tabs.title_areas = vec![Rect::new(2, 1, 5, 1), Rect::new(10, 1, 4, 1)];
+        app.on_click(11, 1);
+        assert_eq!(app.tabs.index, 1);
+    }
+
+    #[test]
+    fn source_statuses_follow_fetcher_responses() {
+        let mut app = App::new("test", &Sources { jmx: Some(jmx_settings()), ..Sources::default() }, &Config::default());
+        assert_eq!(app.sources.iter().map(|s| (s.name, s.last_success, s.error.is_some())).collect::<Vec<_>>(), vec![("JMX", None, false)]);
+        app.on_fetcher_response(&FetcherResponse::JMXConnection(JMXConnectionStatus::Reconnecting { attempt: 1, error: FetchError::connection("Connection refused") }));
+        app.on_fetcher_response(&FetcherResponse::JvmOperation(JvmOperation::Gc, Err(FetchError::from("Denied"))));
+        assert_eq!(app.sources.len(), 1);
+        assert_eq!(app.sources[0].error.as_ref().map(|e| e.message.as_str()), Some("Connection refused"));
+        assert!(app.last_refresh().is_none());
+
+        app.on_fetcher_response(&FetcherResponse::JMXConnection(JMXConnectionStatus::Connected));
+        app.on_fetcher_response(&FetcherResponse::JsonMetric(0, Err(FetchError::timeout("Timed out"))));
+        assert_eq!(app.sources.iter().map(|s| (s.name, s.error.is_some())).collect::<Vec<_>>(), vec![("JMX", false), ("JSON", true)]);
+        assert_eq!(app.last_refresh(), app.sources[0].last_success);
+    }
+
+    #[test]
+    fn maximized_charts_are_cycled_until_restored() {
+        let metric = |name: &str| JsonMetricSettings { name: name.to_owned(), url: "http://localhost:8080/stats".to_owned(), json_pointer: format!("/{}", name), interval: None };
+        let sources = Sources { json_metrics: vec![metric("a"), metric("b"), metric("c")], ..Sources::default() };
+        let mut app = App::new("test", &sources, &Config::default());
+        app.on_key('m');
+        assert!(app.maximized);
+        app.on_up();
+        assert_eq!(app.maximized_chart, 2);
+        app.on_down();
+        app.on_down();
+        assert_eq!(app.maximized_chart, 1);
+
+        app.on_escape();
+        assert!(!app.maximized);
+        app.on_key('m');
+        assert_eq!(app.maximized_chart, 0);
+        app.on_key('m');
+        assert!(!app.maximized);
+    }
+
+    #[test]
+    fn tabs_are_picked_by_number_and_moved() {
+        let mut app = App::new("test", &Sources { zio_zmx: Some("localhost:6789".to_owned()), jmx: Some(jmx_settings()), ..Sources::default() }, &Config::default());
+        let titles = |app: &App| app.tabs.tabs.iter().map(|t| t.title.to_string()).collect::<Vec<_>>();
+        assert_eq!(titles(&app), ["Overview", "ZIO", "Slick", "Errors"]);
+        app.on_key('3');
+        assert_eq!(app.tabs.index, 2);
+        // toggles a series instead
+        app.on_key('1');
+        assert_eq!(app.tabs.index, 2);
+        app.tabs.move_current(false);
+        app.tabs.move_current(false);
+        app.tabs.move_current(false);
+        assert_eq!(app.tabs.index, 0);
+        assert_eq!(titles(&app)[..3], ["Slick", "Overview", "ZIO"]);
+        app.on_right();
+        app.on_key('9');
+        assert_eq!(app.tabs.index, 1);
+    }
+
+    #[test]
+    fn toasts_are_shown_for_non_fatal_events_until_they_expire() {
+        let mut app = App::new("test", &Sources { jmx: Some(jmx_settings()), ..Sources::default() }, &Config::default());
+        app.on_fetcher_response(&FetcherResponse::Loggers(Err(FetchError::timeout("operation timed out"))));
+        assert!(!app.should_quit);
+        app.on_jmx_status(JMXConnectionStatus::Reconnecting { attempt: 1, error: FetchError::connection("Connection refused") });
+        app.on_jmx_status(JMXConnectionStatus::Connected);
+        assert_eq!(app.toasts.iter().map(|t| t.level).collect::<Vec<_>>(), vec![ToastLevel::Warning, ToastLevel::Info]);
+
+        for i in 0..5 {
+            app.notify(ToastLevel::Info, i.to_string());
+        }
+        assert_eq!(app.toasts.iter().map(|t| t.message.as_str()).collect::<Vec<_>>(), vec!["1", "2", "3", "4"]);
+        app.toasts[0].expires = Instant::now();
+        app.on_tick();
+        assert_eq!(app.toasts.len(), 3);
+    }
+
+    #[test]
+    fn what_is_fetched_at_startup_is_fetched_again_once_jmx_connects() {
+        let settings = JMXConnectionSettings { loggers: true, ..jmx_settings() };
+        let mut app = App::new("test", &Sources { jmx: Some(settings), ..Sources::default() }, &Config::default());
+        app.on_jmx_status(JMXConnectionStatus::Reconnecting { attempt: 1, error: FetchError::connection("Connection refused") });
+        assert!(app.jmx_reconnecting.as_ref().is_some_and(|m| m.starts_with("JMX unreachable, reconnecting (attempt 1)")));
+        assert!(app.fetcher_requests.is_empty());
+
+        app.on_jmx_status(JMXConnectionStatus::Connected);
+        assert!(app.jmx_reconnecting.is_none());
+        assert!(matches!(app.fetcher_requests[..], [FetcherRequest::SlickConfig, FetcherRequest::Loggers]));
+    }
+
+    #[test]
+    fn polled_requests_are_only_sent_again_once_answered() {
+        let mut app = App::new("test", &Sources::default(), &Config::default());
+        assert!(app.start_polling(&FetcherRequest::PrometheusMetrics));
+        assert!(!app.start_polling(&FetcherRequest::PrometheusMetrics));
+        assert!(app.start_polling(&FetcherRequest::JsonMetric(0)));
+        assert!(app.start_polling(&FetcherRequest::JsonMetric(1)));
+
+        app.on_fetcher_response(&FetcherResponse::PrometheusMetrics(Err(FetchError::timeout("timed out"))));
+        app.on_fetcher_response(&FetcherResponse::JsonMetric(1, Ok(1.0)));
+        assert!(app.start_polling(&FetcherRequest::PrometheusMetrics));
+        assert!(!app.start_polling(&FetcherRequest::JsonMetric(0)));
+        assert!(app.start_polling(&FetcherRequest::JsonMetric(1)));
+    }
+
+    #[test]
+    fn only_what_changed_is_drawn_again() {
+        let mut app = App::new("test", &Sources { jmx: Some(jmx_settings()), ..Sources::default() }, &Config::default());
+        assert_eq!(app.on_fetcher_response(&FetcherResponse::Loggers(Err(FetchError::timeout("timed out")))), Redraw::All);
+        assert_eq!(app.on_fetcher_response(&FetcherResponse::Loggers(Err(FetchError::timeout("timed out")))), Redraw::StatusBar);
+        assert_eq!(app.on_fetcher_response(&FetcherResponse::Loggers(Ok(vec![]))), Redraw::StatusBar);
+        assert_eq!(app.on_fetcher_response(&FetcherResponse::DbPoolSelected("main".to_owned())), Redraw::Nothing);
+        // the toast is still up
+        assert!(!app.on_tick());
+        app.toasts[0].expires = Instant::now();
+        assert!(app.on_tick());
+        assert!(!app.on_tick());
+    }
+    #[test]
+    fn errors_of_all_sources_are_logged_once_per_failure() {
+        let mut app = App::new("test", &Sources { jmx: Some(jmx_settings()), ..Sources::default() }, &Config::default());
+        app.on_fetcher_response(&FetcherResponse::Loggers(Err(FetchError::connection("Connection refused"))));
+        app.on_fetcher_response(&FetcherResponse::PrometheusMetrics(Err(FetchError::timeout("timed out"))));
+        app.on_fetcher_response(&FetcherResponse::Loggers(Err(FetchError::connection("Connection refused"))));
+        app.on_fetcher_response(&FetcherResponse::JvmOperation(JvmOperation::Gc, Err(FetchError::from("Not allowed"))));
+        let logged: Vec<(&str, &str, usize)> = app.errors.errors.iter().map(|e| (e.source, e.message.as_str(), e.count)).collect();
+        assert_eq!(logged, vec![("JMX", "Connection refused", 2), ("Prometheus", "timed out", 1), ("JMX", "Not allowed", 1)]);
+
+        app.on_fetcher_response(&FetcherResponse::Loggers(Ok(vec![])));
+        app.on_fetcher_response(&FetcherResponse::Loggers(Err(FetchError::connection("Connection refused"))));
+        assert_eq!(app.errors.errors.len(), 4);
+        app.errors.scroll_down(10);
+        assert_eq!(app.errors.scroll, 3);
+    }
+
+    #[test]
+    fn data_keeps_being_taken_in_while_frozen() {
+        let rule: AlertRule = serde_json::from_str(r#"{"name": "busy", "metric": "fibers/total", "comparison": ">", "threshold": 1}"#).unwrap();
+        let config = Config { alerts: vec![rule], ..Config::default() };
+        let mut app = App::new("test", &Sources { zio_zmx: Some("localhost:6789".to_owned()), ..Sources::default() }, &config);
+        app.on_key('z');
+        assert!(app.frozen);
+
+        let fiber = |id: usize| Fiber { id, parent_id: None, status: FiberStatus::Running, dump: id.to_string() };
+        app.on_fetcher_response(&FetcherResponse::FiberDump(Ok(vec![fiber(1), fiber(2)])));
+        app.zmx.as_mut().unwrap().append_fiber_dump_for_counts(vec![fiber(1), fiber(2)]);
+        app.on_tick();
+        assert_eq!(app.frozen_updates(), 1);
+        assert!(app.metric_values().iter().any(|v| v.metric == "fibers/total" && v.value == 2.0));
+        assert!(app.alert_message(&TabKind::Alerts).is_some());
+
+        app.on_key('z');
+        assert!(!app.frozen);
+        assert_eq!(app.frozen_updates(), 0);
+    }
+
+    #[test]
+    fn dashboards_chart_the_bound_metrics() {
+        let dashboard: DashboardSettings = serde_json::from_str(r#"{"title": "Fibers", "rows": [
+            {"widgets": [{"widget": "chart", "metric": "fibers/total"}, {"widget": "alerts"}, {"widget": "chart", "metric": "fibers/running"}]}]}"#).unwrap();
+        let config = Config { dashboards: vec![dashboard], ..Config::default() };
+        let mut app = App::new("test", &Sources { zio_zmx: Some("localhost:6789".to_owned()), ..Sources::default() }, &config);
+        assert!(matches!(app.tabs.tabs[1].kind, TabKind::Dashboard(0)));
+        assert_eq!(app.tabs.tabs[1].title, "Fibers");
+
+        let fiber = |id: usize, status: FiberStatus| Fiber { id, parent_id: None, status, dump: id.to_string() };
+        app.zmx.as_mut().unwrap().append_fiber_dump_for_counts(vec![fiber(1, FiberStatus::Running), fiber(2, FiberStatus::Suspended)]);
+        app.on_tick();
+        app.on_tick();
+        let charted = |i: usize| app.dashboards[0].series[i].iter().map(|s| s.values.iter().map(|v| v.1).collect::<Vec<_>>()).collect::<Vec<_>>();
+        assert_eq!(charted(0), vec![vec![2.0, 2.0]]);
+        assert!(charted(1).is_empty());
+        assert_eq!(charted(2), vec![vec![1.0, 1.0]]);
+    }
+
+    #[test]
+    fn markers_are_typed_and_dropped_on_charts() {
+        let mut app = App::new("test", &Sources { zio_zmx: Some("localhost:6789".to_owned()), ..Sources::default() }, &Config::default());
+        app.on_key('a');
+        for c in "deployed v2.3.1q".chars() {
+            app.on_key(c);
+        }
+        app.on_backspace();
+        assert!(!app.should_quit);
+        app.finish_marker();
+        assert_eq!(app.viewport.markers.iter().map(|m| m.label.as_str()).collect::<Vec<_>>(), ["deployed v2.3.1"]);
+        assert!(!app.is_typing());
+
+        app.on_key('a');
+        app.on_key('x');
+        app.on_escape();
+        app.on_key('a');
+        app.finish_marker();
+        assert_eq!(app.viewport.markers.len(), 1);
+    }
+
+    #[test]
+    fn saved_values_are_charted_again() {
+        let metric = JsonMetricSettings { name: "queue".to_owned(), url: "http://localhost:8080/stats".to_owned(), json_pointer: "/queue".to_owned(), interval: None };
+        let dashboard: DashboardSettings = serde_json::from_str(r#"{"title": "Queue", "rows": [{"widgets": [{"widget": "chart", "metric": "json/queue"}]}]}"#).unwrap();
+        let config = Config { dashboards: vec![dashboard], ..Config::default() };
+        let mut app = App::new("test", &Sources { json_metrics: vec![metric], ..Sources::default() }, &config);
+        let earlier = Local::now() - chrono::Duration::minutes(5);
+        app.restore_history(vec![
+            (earlier, vec![MetricValue::new("json/queue", "queue", 7.0), MetricValue::new("json/gone", "gone", 1.0), MetricValue::new("hikari/active", "", 2.0)]),
+            (earlier + chrono::Duration::seconds(2), vec![MetricValue::new("json/queue", "queue", 5.0)]),
+        ]);
+        let charts = &app.json_metrics.as_ref().unwrap().charts;
+        assert_eq!(charts.selectors, ["queue"]);
+        assert_eq!(charts.series[0][0].values.iter().map(|v| v.1).collect::<Vec<_>>(), [7.0, 5.0]);
+        assert_eq!(app.dashboards[0].series[0][0].values.len(), 2);
+        assert_eq!(app.metric_values(), vec![MetricValue::new("json/queue", "queue", 5.0)]);
+    }
+
+    #[test]
+    fn saved_values_of_fixed_charts_are_restored_without_alerts() {
+        let mut app = App::new("test", &Sources { zio_zmx: Some("localhost:6789".to_owned()), jmx: Some(jmx_settings()), ..Sources::default() }, &Config::default());
+        let earlier = Local::now() - chrono::Duration::minutes(5);
+        let fibers = [("running", 3.0), ("suspended", 120.0), ("done", 0.0), ("finishing", 1.0)];
+        let mut values: Vec<MetricValue> = fibers.iter().map(|(n, v)| MetricValue::new(&format!("fibers/{}", n), "", *v)).collect();
+        values.extend([("total", 10.0), ("active", 10.0), ("idle", 0.0), ("waiting", 4.0)].iter().map(|(n, v)| MetricValue::new(&format!("hikari/{}", n), "", *v)));
+        app.restore_history(vec![(earlier, values)]);
+
+        let zmx = app.zmx.as_ref().unwrap();
+        assert_eq!((zmx.fiber_counts[0].suspended, zmx.fiber_counts[0].timestamp), (120, earlier));
+        let slick = app.slick.as_ref().unwrap();
+        assert_eq!(slick.hikari_metrics[0].waiting, 4);
+        assert!(slick.slick_metrics.is_empty());
+        assert_eq!(slick.hikari_alert, None);
+    }
+}
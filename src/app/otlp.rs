@@ -0,0 +1,38 @@
+use panopticon_core::otlp::model::{OtlpMetrics, OtlpSettings};
+use panopticon_core::prometheus::model::{MetricSample, PrometheusMetrics};
+
+use crate::app::SourceTab;
+use crate::app::prometheus::PrometheusTab;
+
+pub struct OtlpTab {
+    pub address: String,
+    /// A chart for each of the metrics, in the order they were first received unless picked up front
+    pub charts: PrometheusTab,
+    pub last_rejection: Option<String>,
+    // charts of other metrics are only added if none were picked
+    fixed: bool,
+}
+
+impl OtlpTab {
+    pub fn new(settings: &OtlpSettings) -> OtlpTab {
+        OtlpTab {
+            address: settings.address.to_owned(),
+            charts: PrometheusTab::new(settings.metrics.clone()),
+            last_rejection: None,
+            fixed: !settings.metrics.is_empty(),
+        }
+    }
+
+    pub fn append_metrics(&mut self, m: OtlpMetrics) {
+        let mut samples = vec![];
+        for s in m.samples {
+            if let Some(selector) = self.charts.chart_of(&s.name, !self.fixed) {
+                samples.push(MetricSample { selector, series: s.series, value: s.value, counter: s.counter });
+            }
+        }
+        self.charts.append_metrics(PrometheusMetrics { timestamp: m.timestamp, samples, metrics: m.metrics, errors: vec![] });
+        self.last_rejection = m.last_rejection;
+    }
+}
+
+impl SourceTab for OtlpTab {}
@@ -0,0 +1,64 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+
+use panopticon_core::postgres::model::PostgresActivity;
+
+use crate::app::SourceTab;
+use crate::widgets::viewport;
+
+/// Number of connections by state
+type ConnectionCounts = Vec<(String, usize)>;
+
+pub struct PostgresTab {
+    pub activity: Option<PostgresActivity>,
+    pub connections: VecDeque<(DateTime<Local>, ConnectionCounts)>,
+    /// Commits and rollbacks per second, across all databases
+    pub transactions: VecDeque<(DateTime<Local>, f64, f64)>,
+    pub error: Option<String>,
+    last_totals: Option<(DateTime<Local>, u64, u64)>,
+}
+
+impl PostgresTab {
+    pub const MAX_POSTGRES_MEASURES: usize = viewport::HISTORY_SAMPLES;
+
+    pub fn new() -> PostgresTab {
+        PostgresTab {
+            activity: None,
+            connections: VecDeque::new(),
+            transactions: VecDeque::new(),
+            error: None,
+            last_totals: None,
+        }
+    }
+
+    pub fn append_activity(&mut self, a: PostgresActivity) {
+        if self.connections.len() > PostgresTab::MAX_POSTGRES_MEASURES {
+            self.connections.pop_front();
+        }
+        self.connections.push_back((a.timestamp, a.connections_by_state()));
+
+        let commits = a.databases.iter().map(|d| d.commits).sum();
+        let rollbacks = a.databases.iter().map(|d| d.rollbacks).sum();
+        if let Some((t, last_commits, last_rollbacks)) = self.last_totals {
+            let secs = (a.timestamp - t).num_milliseconds() as f64 / 1000.0;
+            // totals drop when statistics are reset
+            if secs > 0.0 && commits >= last_commits && rollbacks >= last_rollbacks {
+                if self.transactions.len() > PostgresTab::MAX_POSTGRES_MEASURES {
+                    self.transactions.pop_front();
+                }
+                self.transactions.push_back((a.timestamp, (commits - last_commits) as f64 / secs, (rollbacks - last_rollbacks) as f64 / secs));
+            }
+        }
+        self.last_totals = Some((a.timestamp, commits, rollbacks));
+        self.activity = Some(a);
+        self.error = None;
+    }
+
+    /// Keeps showing the last activity, as the database may be restarting
+    pub fn on_error(&mut self, e: String) {
+        self.error = Some(e);
+    }
+}
+
+impl SourceTab for PostgresTab {}
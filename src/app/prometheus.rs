@@ -0,0 +1,119 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+
+use panopticon_core::prometheus::model::PrometheusMetrics;
+
+use crate::app::{SourceTab, restore_sample};
+use crate::widgets::viewport;
+
+pub struct PrometheusSeries {
+    pub name: String,
+    /// Counters are charted as rates per second
+    pub counter: bool,
+    pub values: VecDeque<(DateTime<Local>, f64)>,
+    last_total: Option<(DateTime<Local>, f64)>,
+}
+
+impl PrometheusSeries {
+    pub fn new(name: String, counter: bool) -> PrometheusSeries {
+        PrometheusSeries { name, counter, values: VecDeque::new(), last_total: None }
+    }
+}
+
+pub struct PrometheusTab {
+    pub selectors: Vec<String>,
+    /// Series picked by each of the selectors
+    pub series: Vec<Vec<PrometheusSeries>>,
+    /// Names and types of all the scraped metrics
+    pub metrics: Vec<(String, String)>,
+    pub last_scrape: Option<DateTime<Local>>,
+    /// Why the latest read, or some of its queries, failed
+    pub errors: Vec<String>,
+}
+
+impl PrometheusTab {
+    pub const MAX_PROMETHEUS_MEASURES: usize = viewport::HISTORY_SAMPLES;
+
+    /// A tab charting the series picked by each of the selectors, or returned by each of the queries
+    pub fn new(selectors: Vec<String>) -> PrometheusTab {
+        PrometheusTab {
+            series: selectors.iter().map(|_| vec![]).collect(),
+            selectors,
+            metrics: vec![],
+            last_scrape: None,
+            errors: vec![],
+        }
+    }
+
+    /// Index of the chart of a metric, adding one for new metrics if `add` is set
+    pub fn chart_of(&mut self, name: &str, add: bool) -> Option<usize> {
+        match self.selectors.iter().position(|s| s == name) {
+            Some(i) => Some(i),
+            None if add => {
+                self.selectors.push(name.to_owned());
+                self.series.push(vec![]);
+                Some(self.selectors.len() - 1)
+            }
+            None => None,
+        }
+    }
+
+    pub fn append_metrics(&mut self, m: PrometheusMetrics) {
+        let timestamp = m.timestamp;
+        for sample in m.samples {
+            let series = &mut self.series[sample.selector];
+            let i = match series.iter().position(|s| s.name == sample.series) {
+                Some(i) => i,
+                None => {
+                    series.push(PrometheusSeries::new(sample.series.clone(), sample.counter));
+                    series.len() - 1
+                }
+            };
+            let s = &mut series[i];
+            // restored series don't know whether they're counters
+            s.counter = sample.counter;
+            let value = if s.counter {
+                let rate = s.last_total.and_then(|(t, total)| {
+                    let secs = (timestamp - t).num_milliseconds() as f64 / 1000.0;
+                    // counters start over when the app is restarted
+                    if secs > 0.0 && sample.value >= total { Some((sample.value - total) / secs) } else { None }
+                });
+                s.last_total = Some((timestamp, sample.value));
+                match rate {
+                    Some(r) => r,
+                    None => continue,
+                }
+            } else {
+                sample.value
+            };
+            if s.values.len() > PrometheusTab::MAX_PROMETHEUS_MEASURES {
+                s.values.pop_front();
+            }
+            s.values.push_back((timestamp, value));
+        }
+        self.metrics = m.metrics;
+        self.errors = m.errors;
+        self.last_scrape = Some(timestamp);
+    }
+
+    pub fn on_error(&mut self, e: String) {
+        self.errors = vec![e];
+    }
+
+    /// Puts back a value charted before a restart
+    pub fn restore_value(&mut self, chart: usize, series: &str, timestamp: DateTime<Local>, value: f64) {
+        let series_list = &mut self.series[chart];
+        let i = match series_list.iter().position(|s| s.name == series) {
+            Some(i) => i,
+            None => {
+                series_list.push(PrometheusSeries::new(series.to_owned(), false));
+                series_list.len() - 1
+            }
+        };
+        let s = &mut series_list[i];
+        restore_sample(&mut s.values, PrometheusTab::MAX_PROMETHEUS_MEASURES, (timestamp, value));
+    }
+}
+
+impl SourceTab for PrometheusTab {}
@@ -0,0 +1,36 @@
+use panopticon_core::prometheus::model::PrometheusMetrics;
+use panopticon_core::proxy::model::{ProxyKind, ProxyServer, ProxySettings, ProxyStatus};
+
+use crate::app::SourceTab;
+use crate::app::prometheus::PrometheusTab;
+
+pub struct ProxyTab {
+    pub address: String,
+    /// Known once the status page was read
+    pub kind: Option<ProxyKind>,
+    pub charts: PrometheusTab,
+    pub servers: Vec<ProxyServer>,
+    pub error: Option<String>,
+}
+
+impl ProxyTab {
+    pub fn new(settings: &ProxySettings) -> ProxyTab {
+        ProxyTab { address: settings.address.to_owned(), kind: None, charts: PrometheusTab::new(vec![]), servers: vec![], error: None }
+    }
+
+    pub fn append_status(&mut self, s: ProxyStatus) {
+        if self.kind != Some(s.kind) {
+            self.kind = Some(s.kind);
+            self.charts = PrometheusTab::new(s.kind.charts());
+        }
+        self.charts.append_metrics(PrometheusMetrics { timestamp: s.timestamp, samples: s.samples, metrics: vec![], errors: vec![] });
+        self.servers = s.servers;
+        self.error = None;
+    }
+
+    pub fn on_error(&mut self, e: String) {
+        self.error = Some(e);
+    }
+}
+
+impl SourceTab for ProxyTab {}
@@ -0,0 +1,39 @@
+use panopticon_core::prometheus::model::{MetricSample, PrometheusMetrics};
+use panopticon_core::rabbitmq::model::RabbitMqStats;
+
+use crate::app::SourceTab;
+use crate::app::prometheus::PrometheusTab;
+
+pub struct RabbitMqTab {
+    /// Depth and rate charts, with a series per queue
+    pub charts: PrometheusTab,
+    pub stats: Option<RabbitMqStats>,
+    pub error: Option<String>,
+}
+
+impl RabbitMqTab {
+    pub fn new() -> RabbitMqTab {
+        let charts = ["Messages ready", "Unacknowledged messages", "Published (msg/s)", "Delivered (msg/s)"];
+        RabbitMqTab { charts: PrometheusTab::new(charts.iter().map(|c| c.to_string()).collect()), stats: None, error: None }
+    }
+
+    pub fn append_stats(&mut self, s: RabbitMqStats) {
+        let samples = s.queues.iter()
+            .flat_map(|q| {
+                let values = [q.ready as f64, q.unacknowledged as f64, q.publish_rate, q.deliver_rate];
+                values.iter().enumerate()
+                    .map(|(selector, value)| MetricSample { selector, series: q.queue.to_string(), value: *value, counter: false })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        self.charts.append_metrics(PrometheusMetrics { timestamp: s.timestamp, samples, metrics: vec![], errors: vec![] });
+        self.stats = Some(s);
+        self.error = None;
+    }
+
+    pub fn on_error(&mut self, e: String) {
+        self.error = Some(e);
+    }
+}
+
+impl SourceTab for RabbitMqTab {}
@@ -0,0 +1,51 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+
+use panopticon_core::redis::model::RedisInfo;
+
+use crate::app::SourceTab;
+use crate::widgets::viewport;
+
+pub struct RedisTab {
+    pub info: VecDeque<RedisInfo>,
+    /// Share of key lookups that found the key, in percent, since the previous poll
+    pub hit_rates: VecDeque<(DateTime<Local>, f64)>,
+    pub error: Option<String>,
+}
+
+impl RedisTab {
+    pub const MAX_REDIS_MEASURES: usize = viewport::HISTORY_SAMPLES;
+
+    pub fn new() -> RedisTab {
+        RedisTab { info: VecDeque::new(), hit_rates: VecDeque::new(), error: None }
+    }
+
+    pub fn append_info(&mut self, i: RedisInfo) {
+        if let Some(last) = self.info.back() {
+            // totals drop when the server restarts or CONFIG RESETSTAT is run
+            if i.keyspace_hits >= last.keyspace_hits && i.keyspace_misses >= last.keyspace_misses {
+                let hits = i.keyspace_hits - last.keyspace_hits;
+                let lookups = hits + i.keyspace_misses - last.keyspace_misses;
+                if lookups > 0 {
+                    if self.hit_rates.len() > RedisTab::MAX_REDIS_MEASURES {
+                        self.hit_rates.pop_front();
+                    }
+                    self.hit_rates.push_back((i.timestamp, hits as f64 * 100.0 / lookups as f64));
+                }
+            }
+        }
+        if self.info.len() > RedisTab::MAX_REDIS_MEASURES {
+            self.info.pop_front();
+        }
+        self.info.push_back(i);
+        self.error = None;
+    }
+
+    /// Keeps showing the last stats, as the server may be restarting
+    pub fn on_error(&mut self, e: String) {
+        self.error = Some(e);
+    }
+}
+
+impl SourceTab for RedisTab {}
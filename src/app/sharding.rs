@@ -0,0 +1,27 @@
+use std::collections::VecDeque;
+
+use panopticon_core::akka::model::ShardingStats;
+
+use crate::app::SourceTab;
+use crate::widgets::viewport;
+
+pub struct ShardingTab {
+    pub stats: VecDeque<ShardingStats>,
+}
+
+impl ShardingTab {
+    pub const MAX_SHARDING_MEASURES: usize = viewport::HISTORY_SAMPLES;
+
+    pub fn new() -> ShardingTab {
+        ShardingTab { stats: VecDeque::new() }
+    }
+
+    pub fn append_sharding_stats(&mut self, m: ShardingStats) {
+        if self.stats.len() > ShardingTab::MAX_SHARDING_MEASURES {
+            self.stats.pop_front();
+        }
+        self.stats.push_back(m);
+    }
+}
+
+impl SourceTab for ShardingTab {}
@@ -0,0 +1,308 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Local};
+
+use panopticon_core::fetcher::FetcherRequest;
+use panopticon_core::jmx::model::{HikariAlertSettings, HikariMetrics, HikariOperation, JMXConnectionSettings, JMXNotification, SlickConfig, SlickMetrics};
+
+use crate::app::{Confirmation, SourceTab, StatefulList};
+use crate::export;
+use crate::widgets::viewport;
+
+/// Series of the Slick tab graphs, which can be hidden with number keys
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SlickSeries {
+    ActiveThreads,
+    QueueSize,
+    MaxThreads,
+    HikariTotal,
+    HikariActive,
+    HikariIdle,
+    HikariWaiting,
+}
+
+impl SlickSeries {
+    /// In the order of their keys, starting with `1`
+    pub const ALL: [SlickSeries; 7] = [
+        SlickSeries::ActiveThreads,
+        SlickSeries::QueueSize,
+        SlickSeries::MaxThreads,
+        SlickSeries::HikariTotal,
+        SlickSeries::HikariActive,
+        SlickSeries::HikariIdle,
+        SlickSeries::HikariWaiting,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SlickSeries::ActiveThreads => "Slick active threads",
+            SlickSeries::QueueSize => "Slick queue size",
+            SlickSeries::MaxThreads => "Slick max threads",
+            SlickSeries::HikariTotal => "HikariCP total",
+            SlickSeries::HikariActive => "HikariCP active",
+            SlickSeries::HikariIdle => "HikariCP idle",
+            SlickSeries::HikariWaiting => "HikariCP waiting",
+        }
+    }
+}
+
+pub struct SlickTab {
+    /// Selected DB pool, `None` until picked among discovered ones
+    pub db_pool_name: Option<String>,
+    /// Discovered DB pools, shown instead of graphs while a pool is being picked
+    pub pool_picker: Option<StatefulList<String>>,
+    pub has_hikari: bool,
+    pub slick_metrics: VecDeque<SlickMetrics>,
+    pub slick_config: SlickConfig,
+    pub hikari_metrics: VecDeque<HikariMetrics>,
+    pub has_notifications: bool,
+    pub notifications: VecDeque<JMXNotification>,
+    pub notifications_scroll: u16,
+    pub allow_operations: bool,
+    pub status_message: Option<String>,
+    pub alert_settings: HikariAlertSettings,
+    pub exhausted_ticks: u32,
+    pub hikari_alert: Option<String>,
+    pub alert_events: VecDeque<(DateTime<Local>, String)>,
+    pub hidden_series: Vec<SlickSeries>,
+}
+
+impl SlickTab {
+    pub const MAX_SLICK_MEASURES: usize = viewport::HISTORY_SAMPLES;
+    pub const MAX_HIKARI_MEASURES: usize = viewport::HISTORY_SAMPLES;
+    pub const MAX_NOTIFICATIONS: usize = 500;
+    pub const MAX_ALERT_EVENTS: usize = 100;
+
+    pub fn new(settings: &JMXConnectionSettings) -> SlickTab {
+        SlickTab {
+            db_pool_name: settings.db_pool_name.clone(),
+            pool_picker: None,
+            has_hikari: false,
+            slick_metrics: VecDeque::new(),
+            slick_config: SlickConfig { max_threads: 0, max_queue_size: 0 },
+            hikari_metrics: VecDeque::new(),
+            has_notifications: !settings.notifications.is_empty(),
+            notifications: VecDeque::new(),
+            notifications_scroll: 0,
+            allow_operations: settings.allow_operations,
+            status_message: None,
+            alert_settings: settings.hikari_alerts.clone(),
+            exhausted_ticks: 0,
+            hikari_alert: None,
+            alert_events: VecDeque::new(),
+            hidden_series: vec![],
+        }
+    }
+
+    /// Hides or shows the series bound to a number key
+    pub fn toggle_series(&mut self, key: char) {
+        let series = key.to_digit(10)
+            .and_then(|d| (d as usize).checked_sub(1))
+            .and_then(|i| SlickSeries::ALL.get(i));
+        if let Some(series) = series {
+            let shown = self.is_shown(*series);
+            self.hidden_series.retain(|s| s != series);
+            if shown {
+                self.hidden_series.push(*series);
+            }
+            self.status_message = Some(format!("{} {}", series.label(), if shown { "hidden" } else { "shown" }));
+        }
+    }
+
+    pub fn is_shown(&self, series: SlickSeries) -> bool {
+        !self.hidden_series.contains(&series)
+    }
+
+    pub fn replace_slick_config(&mut self, m: SlickConfig) {
+        self.slick_config = m
+    }
+
+    pub fn append_slick_metrics(&mut self, m: SlickMetrics) {
+        if self.slick_metrics.len() > SlickTab::MAX_SLICK_MEASURES {
+            self.slick_metrics.pop_front();
+        }
+        self.slick_metrics.push_back(m);
+    }
+
+    pub fn append_hikari_metrics(&mut self, m: HikariMetrics) {
+        if self.hikari_metrics.len() > SlickTab::MAX_HIKARI_MEASURES {
+            self.hikari_metrics.pop_front();
+        }
+        self.check_hikari_alert(&m);
+        self.hikari_metrics.push_back(m);
+    }
+
+    fn check_hikari_alert(&mut self, m: &HikariMetrics) {
+        if m.max_pool_size > 0 && m.active >= m.max_pool_size {
+            self.exhausted_ticks += 1;
+        } else {
+            self.exhausted_ticks = 0;
+        }
+
+        let alert = if self.exhausted_ticks >= self.alert_settings.exhausted_ticks {
+            Some(format!(
+                "HikariCP pool exhausted: {} of {} connections active for {} ticks",
+                m.active, m.max_pool_size, self.exhausted_ticks
+            ))
+        } else if m.waiting > self.alert_settings.waiting_threshold {
+            Some(format!("HikariCP pool starving: {} threads awaiting connection", m.waiting))
+        } else {
+            None
+        };
+
+        match (&self.hikari_alert, &alert) {
+            (None, Some(a)) => self.push_alert_event(a.to_owned()),
+            (Some(_), None) => self.push_alert_event("HikariCP pool recovered".to_owned()),
+            _ => {}
+        }
+        self.hikari_alert = alert;
+    }
+
+    fn push_alert_event(&mut self, message: String) {
+        if self.alert_events.len() > SlickTab::MAX_ALERT_EVENTS {
+            self.alert_events.pop_front();
+        }
+        self.status_message = Some(message.clone());
+        self.alert_events.push_back((Local::now(), message));
+    }
+
+    pub fn append_notifications(&mut self, ns: Vec<JMXNotification>) {
+        for n in ns {
+            if self.notifications.len() > SlickTab::MAX_NOTIFICATIONS {
+                self.notifications.pop_front();
+            }
+            self.notifications.push_back(n);
+        }
+    }
+
+    /// What to confirm before the operation is actually invoked, if it's allowed
+    pub fn request_operation(&self, operation: HikariOperation) -> Option<Confirmation> {
+        if !self.allow_operations || !self.has_hikari {
+            return None;
+        }
+        let warning = match operation {
+            HikariOperation::SoftEvictConnections => Some("Idle connections are closed right away, and the ones in use once returned"),
+            HikariOperation::SuspendPool => Some("Requests for connections block until the pool is resumed, or time out"),
+            HikariOperation::ResumePool => None,
+        };
+        Some(Confirmation {
+            title: "HikariCP".to_owned(),
+            question: format!("Invoke {} on HikariCP pool?", operation.name()),
+            warning: warning.map(|w| w.to_owned()),
+            request: FetcherRequest::HikariOperation(operation),
+        })
+    }
+
+    pub fn on_operation_result(&mut self, operation: HikariOperation, result: Result<(), String>) {
+        self.status_message = Some(match result {
+            Ok(_) => format!("{} succeeded", operation.name()),
+            Err(e) => format!("{} failed: {}", operation.name(), e),
+        });
+    }
+
+    /// Writes collected slick and hikari metrics to CSV files in the working directory
+    pub fn export_csv(&mut self) -> Result<String, String> {
+        let slick_file = export::timestamped_file_name("slick-metrics", "csv");
+        let slick_rows: Vec<Vec<String>> = self.slick_metrics.iter()
+            .map(|m| vec![m.timestamp.to_rfc3339(), m.active_threads.to_string(), m.queue_size.to_string()])
+            .collect();
+        let hikari_file = export::timestamped_file_name("hikari-metrics", "csv");
+        let hikari_rows: Vec<Vec<String>> = self.hikari_metrics.iter()
+            .map(|m| vec![
+                m.timestamp.to_rfc3339(),
+                m.total.to_string(),
+                m.active.to_string(),
+                m.idle.to_string(),
+                m.waiting.to_string(),
+            ])
+            .collect();
+
+        let result = export::write_csv(&slick_file, &["timestamp", "active_threads", "queue_size"], &slick_rows)
+            .and_then(|_| export::write_csv(&hikari_file, &["timestamp", "total", "active", "idle", "waiting"], &hikari_rows));
+        let result = match result {
+            Ok(_) => Ok(format!("Exported metrics to {} and {}", slick_file, hikari_file)),
+            Err(e) => Err(format!("Export failed: {}", e)),
+        };
+        self.status_message = Some(result.clone().unwrap_or_else(|e| e));
+        result
+    }
+
+    pub fn replace_discovered_pools(&mut self, pools: Vec<String>) {
+        if pools.is_empty() {
+            self.status_message = Some(
+                "No Slick or HikariCP pools found. Are you sure you have registerMbeans=true in your Slick config?".to_owned()
+            );
+        }
+        let mut picker = StatefulList::with_items(pools);
+        picker.next();
+        self.pool_picker = Some(picker);
+    }
+
+    /// Switches to the pool highlighted in the picker, dropping metrics of the previous one
+    pub fn select_pool(&mut self) -> Option<String> {
+        let picker = self.pool_picker.take()?;
+        let name = picker.state.selected().and_then(|i| picker.items.get(i)).cloned();
+        match &name {
+            Some(n) => self.switch_pool(n),
+            None => self.pool_picker = Some(picker),
+        }
+        name
+    }
+
+    /// Confirms the pool is read from now, switching to it when replaying a session where it was picked
+    pub fn on_pool_selected(&mut self, name: String) {
+        if self.db_pool_name.as_ref() != Some(&name) {
+            self.pool_picker = None;
+            self.switch_pool(&name);
+        }
+        self.status_message = Some(format!("Showing metrics of pool {}", name));
+    }
+
+    fn switch_pool(&mut self, name: &str) {
+        self.db_pool_name = Some(name.to_owned());
+        self.has_hikari = false;
+        self.slick_metrics.clear();
+        self.hikari_metrics.clear();
+        self.exhausted_ticks = 0;
+        self.hikari_alert = None;
+        self.status_message = None;
+    }
+
+    pub fn scroll_notifications_up(&mut self) {
+        if self.notifications_scroll > 0 {
+            self.notifications_scroll -= 1;
+        }
+    }
+
+    pub fn scroll_notifications_down(&mut self) {
+        if (self.notifications_scroll as usize) + 1 < self.notifications.len() {
+            self.notifications_scroll += 1;
+        }
+    }
+}
+
+impl SourceTab for SlickTab {
+    fn on_up(&mut self) {
+        match &mut self.pool_picker {
+            Some(p) if !p.items.is_empty() => p.previous(),
+            Some(_) => {}
+            None => self.scroll_notifications_up(),
+        }
+    }
+
+    fn on_down(&mut self) {
+        match &mut self.pool_picker {
+            Some(p) if !p.items.is_empty() => p.next(),
+            Some(_) => {}
+            None => self.scroll_notifications_down(),
+        }
+    }
+
+    fn uses_jmx(&self) -> bool {
+        true
+    }
+
+    fn alert_message(&self) -> Option<String> {
+        self.hikari_alert.clone()
+    }
+}
@@ -0,0 +1,37 @@
+use std::time::{Duration, Instant};
+
+use panopticon_core::sql::model::SqlQuerySettings;
+
+use crate::app::{SourceTab, append_polled_values, due_polls};
+use crate::app::prometheus::PrometheusTab;
+
+pub struct SqlQueriesTab {
+    /// A chart for each of the queries, with a series per numeric column
+    pub charts: PrometheusTab,
+    /// Error of the last run of each query, if it failed
+    pub errors: Vec<Option<String>>,
+    intervals: Vec<Option<Duration>>,
+    last_polled: Vec<Option<Instant>>,
+}
+
+impl SqlQueriesTab {
+    pub fn new(queries: &[SqlQuerySettings]) -> SqlQueriesTab {
+        SqlQueriesTab {
+            charts: PrometheusTab::new(queries.iter().map(|q| q.name.to_owned()).collect()),
+            errors: queries.iter().map(|_| None).collect(),
+            intervals: queries.iter().map(|q| q.interval.map(Duration::from_millis)).collect(),
+            last_polled: queries.iter().map(|_| None).collect(),
+        }
+    }
+
+    /// Queries to run on this tick, those without an interval are run every tick
+    pub fn due_queries(&mut self, now: Instant) -> Vec<usize> {
+        due_polls(&mut self.last_polled, &self.intervals, now)
+    }
+
+    pub fn on_values(&mut self, i: usize, values: Result<Vec<(String, f64)>, String>) {
+        append_polled_values(&mut self.charts, &mut self.errors, i, values)
+    }
+}
+
+impl SourceTab for SqlQueriesTab {}
@@ -0,0 +1,38 @@
+use panopticon_core::prometheus::model::{MetricSample, PrometheusMetrics};
+use panopticon_core::statsd::model::{StatsdMetrics, StatsdSettings};
+
+use crate::app::SourceTab;
+use crate::app::prometheus::PrometheusTab;
+
+pub struct StatsdTab {
+    /// A chart for each of the metrics, in the order they were first received unless picked up front
+    pub charts: PrometheusTab,
+    /// Lines that couldn't be parsed on the last tick
+    pub invalid_lines: u64,
+    // charts of other metrics are only added if none were picked
+    fixed: bool,
+}
+
+impl StatsdTab {
+    pub fn new(settings: &StatsdSettings) -> StatsdTab {
+        StatsdTab {
+            charts: PrometheusTab::new(settings.metrics.clone()),
+            invalid_lines: 0,
+            fixed: !settings.metrics.is_empty(),
+        }
+    }
+
+    pub fn append_metrics(&mut self, m: StatsdMetrics) {
+        let mut samples = vec![];
+        for s in m.samples {
+            if let Some(selector) = self.charts.chart_of(&s.name, !self.fixed) {
+                samples.push(MetricSample { selector, series: s.series, value: s.value, counter: s.counter });
+            }
+        }
+        let metrics = m.metrics.iter().map(|(name, kind)| (name.to_owned(), kind.name().to_owned())).collect();
+        self.charts.append_metrics(PrometheusMetrics { timestamp: m.timestamp, samples, metrics, errors: vec![] });
+        self.invalid_lines = m.invalid_lines;
+    }
+}
+
+impl SourceTab for StatsdTab {}
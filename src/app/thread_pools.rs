@@ -0,0 +1,31 @@
+use std::collections::VecDeque;
+
+use panopticon_core::jmx::model::ThreadPoolsMetrics;
+
+use crate::app::SourceTab;
+use crate::widgets::viewport;
+
+pub struct ThreadPoolsTab {
+    pub metrics: VecDeque<ThreadPoolsMetrics>,
+}
+
+impl ThreadPoolsTab {
+    pub const MAX_THREAD_POOL_MEASURES: usize = viewport::HISTORY_SAMPLES;
+
+    pub fn new() -> ThreadPoolsTab {
+        ThreadPoolsTab { metrics: VecDeque::new() }
+    }
+
+    pub fn append_thread_pool_metrics(&mut self, m: ThreadPoolsMetrics) {
+        if self.metrics.len() > ThreadPoolsTab::MAX_THREAD_POOL_MEASURES {
+            self.metrics.pop_front();
+        }
+        self.metrics.push_back(m);
+    }
+}
+
+impl SourceTab for ThreadPoolsTab {
+    fn uses_jmx(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,104 @@
+use chrono::{DateTime, Local};
+
+use panopticon_core::traces::model::{format_micros, RecentTraces, Trace, TraceBackend, TracesSettings};
+
+use crate::app::{SourceTab, StatefulList};
+use crate::app::zmx::ZMXTab;
+use crate::widgets::tree;
+
+pub struct TracesTab {
+    pub service: String,
+    pub backend: TraceBackend,
+    pub traces: Vec<Trace>,
+    pub list: StatefulList<String>,
+    /// Span tree of the selected trace, and its number of lines
+    pub selected_trace: (String, u16),
+    pub scroll: u16,
+    pub last_update: Option<DateTime<Local>>,
+    pub error: Option<String>,
+}
+
+impl TracesTab {
+    pub fn new(settings: &TracesSettings) -> TracesTab {
+        TracesTab {
+            service: settings.service.to_owned(),
+            backend: settings.backend,
+            traces: vec![],
+            list: StatefulList::with_items(vec![]),
+            selected_trace: ("".to_owned(), 0),
+            scroll: 0,
+            last_update: None,
+            error: None,
+        }
+    }
+
+    /// Keeps the selected trace selected as newer ones come in
+    pub fn replace_traces(&mut self, recent: RecentTraces) {
+        let selected_id = self.selected().map(|t| t.id.to_owned());
+        self.traces = recent.traces;
+        self.list = StatefulList::with_items(self.traces.iter().map(|t| {
+            let root = t.root().map_or("-", |s| &s.operation);
+            let error = if t.has_error() { " ERROR" } else { "" };
+            format!("{} {:>9} {:>4} spans  {}{}", t.started_at().format("%H:%M:%S"), format_micros(t.duration()), t.spans.len(), root, error)
+        }).collect());
+        if !self.traces.is_empty() {
+            let index = selected_id.as_ref().and_then(|id| self.traces.iter().position(|t| &t.id == id)).unwrap_or(0);
+            self.list.state.select(Some(index));
+        }
+        let changed = self.selected().map(|t| &t.id) != selected_id.as_ref();
+        self.on_trace_change(changed);
+        self.last_update = Some(recent.timestamp);
+        self.error = None;
+    }
+
+    pub fn on_error(&mut self, e: String) {
+        self.error = Some(e);
+    }
+
+    pub fn selected(&self) -> Option<&Trace> {
+        self.list.state.selected().and_then(|i| self.traces.get(i))
+    }
+
+    pub fn select_prev_trace(&mut self) {
+        if !self.traces.is_empty() {
+            self.list.previous();
+            self.on_trace_change(true);
+        }
+    }
+
+    pub fn select_next_trace(&mut self) {
+        if !self.traces.is_empty() {
+            self.list.next();
+            self.on_trace_change(true);
+        }
+    }
+
+    pub fn on_trace_change(&mut self, reset_scroll: bool) {
+        let spans = self.selected().map_or(vec![], |t| t.spans.clone());
+        let tree: Vec<String> = tree::tree_list_widget(spans, false).into_iter().map(|(label, _)| label).collect();
+        self.selected_trace = ZMXTab::prepare_dump(tree.join("\n"));
+        if reset_scroll {
+            self.scroll = 0;
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    pub fn scroll_down(&mut self) {
+        if self.scroll < self.selected_trace.1 {
+            self.scroll += 1;
+        }
+    }
+}
+
+impl SourceTab for TracesTab {
+    fn on_up(&mut self) {
+        self.select_prev_trace()
+    }
+
+    fn on_down(&mut self) {
+        self.select_next_trace()
+    }
+}
@@ -0,0 +1,226 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use panopticon_core::zio::model::{Fiber, FiberCount};
+
+use crate::app::{SourceTab, StatefulList};
+use crate::widgets::tree;
+use crate::widgets::viewport;
+
+pub struct ZMXTab {
+    pub fibers: StatefulList<String>,
+    /// Id of the fiber of every list item
+    pub fiber_ids: Vec<usize>,
+    /// Dump of the selected fiber, with its number of lines
+    pub selected_fiber_dump: (Arc<str>, u16),
+    /// Dump of the fiber of every list item, only the selected one is prepared to be drawn
+    pub fiber_dump_all: Vec<Arc<str>>,
+    pub scroll: u16,
+    pub fiber_counts: VecDeque<FiberCount>,
+    /// Fibers are counted every this many ticks
+    pub count_ticks: u32,
+    /// Ticks since fibers were last counted
+    ticks_since_count: u32,
+}
+
+impl ZMXTab {
+    pub const MAX_FIBER_COUNT_MEASURES: usize = viewport::HISTORY_SAMPLES;
+    /// Full dumps of more fibers than this are only taken once confirmed
+    pub const LARGE_DUMP_FIBERS: i32 = 10_000;
+
+    pub fn new() -> ZMXTab {
+        ZMXTab {
+            fibers: StatefulList::with_items(vec![]),
+            fiber_ids: vec![],
+            selected_fiber_dump: ("".into(), 1),
+            fiber_dump_all: vec![],
+            scroll: 0,
+            fiber_counts: VecDeque::new(),
+            count_ticks: 1,
+            ticks_since_count: 0,
+        }
+    }
+
+    /// Whether fibers are to be counted on this tick, the first one and then every `count_ticks`
+    pub fn count_due(&mut self) -> bool {
+        let due = self.ticks_since_count == 0;
+        self.ticks_since_count = (self.ticks_since_count + 1) % self.count_ticks.max(1);
+        due
+    }
+
+    pub fn append_fiber_count(&mut self, c: FiberCount) {
+        if self.fiber_counts.len() > ZMXTab::MAX_FIBER_COUNT_MEASURES {
+            self.fiber_counts.pop_front();
+        }
+        self.fiber_counts.push_back(c);
+    }
+
+    pub fn select_prev_fiber(&mut self) {
+        if !self.fibers.items.is_empty() {
+            self.fibers.previous();
+            self.on_fiber_change()
+        }
+    }
+
+    pub fn select_next_fiber(&mut self) {
+        if !self.fibers.items.is_empty() {
+            self.fibers.next();
+            self.on_fiber_change()
+        }
+    }
+
+    pub fn on_fiber_change(&mut self) {
+        let n = self.fibers.state.selected().unwrap_or(0);
+        self.selected_fiber_dump = ZMXTab::prepare_dump(self.fiber_dump_all[n].clone());
+        self.scroll = 0;
+    }
+
+    /// Replaces the fibers with a new dump, keeping the selected fiber if it's still there
+    pub fn replace_fiber_dump(&mut self, dump: Vec<Fiber>) {
+        // the tree is built without the dumps, as it clones its items over and over
+        let mut dumps: HashMap<usize, Arc<str>> = HashMap::with_capacity(dump.len());
+        let fibers = dump.into_iter()
+            .map(|f| {
+                dumps.insert(f.id, f.dump.into());
+                Fiber { dump: String::new(), ..f }
+            })
+            .collect();
+        let list = tree::tree_list_widget(fibers, true);
+        // no fibers are left to show
+        if list.is_empty() {
+            self.fiber_ids.clear();
+            self.fiber_dump_all.clear();
+            self.fibers.items.clear();
+            self.fibers.state.select(None);
+            self.selected_fiber_dump = ZMXTab::prepare_dump("".into());
+            self.scroll = 0;
+            return;
+        }
+        let selected = self.fibers.state.selected();
+        let selected_id = selected.and_then(|i| self.fiber_ids.get(i)).cloned();
+
+        self.fiber_ids = list.iter().map(|(_, f)| f.id).collect();
+        self.fiber_dump_all = self.fiber_ids.iter().map(|id| dumps.remove(id).unwrap_or_else(|| "".into())).collect();
+        self.fibers.items = list.into_iter().map(|(label, _)| label).collect();
+        // a fiber that's done is replaced by the one taking its place in the list
+        let kept = selected_id.and_then(|id| self.fiber_ids.iter().position(|i| *i == id));
+        let index = kept.unwrap_or_else(|| selected.unwrap_or(0).min(self.fiber_ids.len() - 1));
+        self.fibers.state.select(Some(index));
+        let scroll = self.scroll;
+        self.on_fiber_change();
+        if kept.is_some() {
+            self.scroll = scroll.min(self.selected_fiber_dump.1);
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        if self.scroll > 0 {
+            self.scroll -= 1;
+        }
+    }
+
+    pub fn scroll_down(&mut self) {
+        if self.scroll < self.selected_fiber_dump.1 {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn append_fiber_dump_for_counts(&mut self, dump: Vec<Fiber>) {
+        let mut count = FiberCount::now();
+        for f in dump.iter() {
+            count.add(&f.status, 1);
+        }
+        self.append_fiber_count(count);
+    }
+
+    pub fn prepare_dump<S: AsRef<str>>(s: S) -> (S, u16) {
+        let lines = s.as_ref().lines().count() as u16;
+        (s, lines)
+    }
+}
+
+impl SourceTab for ZMXTab {
+    fn on_up(&mut self) {
+        self.select_prev_fiber()
+    }
+
+    fn on_down(&mut self) {
+        self.select_next_fiber()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use panopticon_core::zio::model::{Fiber, FiberStatus};
+
+    use crate::app::StatefulList;
+    use crate::app::zmx::ZMXTab;
+
+    #[test]
+    fn fibers_are_counted_every_few_ticks() {
+        let mut tab = ZMXTab::new();
+        tab.count_ticks = 3;
+        let due: Vec<bool> = (0..7).map(|_| tab.count_due()).collect();
+        assert_eq!(due, vec![true, false, false, true, false, false, true]);
+    }
+
+    #[test]
+    fn zmx_tab_dumps_fibers() {
+        let fiber1 = Fiber {
+            id: 1,
+            parent_id: None,
+            status: FiberStatus::Running,
+            dump: "1".to_owned(),
+        };
+        let fiber2 = Fiber {
+            id: 2,
+            parent_id: Some(1),
+            status: FiberStatus::Suspended,
+            dump: "2".to_owned(),
+        };
+        let fiber4 = Fiber {
+            id: 4,
+            parent_id: None,
+            status: FiberStatus::Done,
+            dump: "4".to_owned(),
+        };
+
+        let fibers = vec![fiber1, fiber2, fiber4];
+
+        let mut tab = ZMXTab {
+            fibers: StatefulList::with_items(vec!["Fiber #1".to_owned()]),
+            fiber_ids: vec![],
+            selected_fiber_dump: ("".into(), 0),
+            fiber_dump_all: vec![],
+            scroll: 0,
+            fiber_counts: VecDeque::new(),
+            count_ticks: 1,
+            ticks_since_count: 0,
+        };
+
+        tab.replace_fiber_dump(fibers);
+
+        assert_eq!(tab.fiber_dump_all.iter().map(|d| &**d).collect::<Vec<_>>(), vec!["1", "2", "4"]);
+        assert_eq!(tab.fibers.items, vec![
+            "├─#1   Running",
+            "│ └─#2 Suspended",
+            "└─#4   Done"
+        ]);
+        assert_eq!(tab.fibers.state.selected(), Some(0));
+
+        // the selection follows the fiber rather than its position
+        tab.select_next_fiber();
+        let fiber = |id: usize, parent_id: Option<usize>| Fiber { id, parent_id, status: FiberStatus::Running, dump: id.to_string() };
+        tab.replace_fiber_dump(vec![fiber(0, None), fiber(1, None), fiber(2, Some(1))]);
+        assert_eq!((tab.fibers.state.selected(), &*tab.selected_fiber_dump.0), (Some(2), "2"));
+        tab.replace_fiber_dump(vec![fiber(1, None)]);
+        assert_eq!((tab.fibers.state.selected(), &*tab.selected_fiber_dump.0), (Some(0), "1"));
+
+        tab.replace_fiber_dump(vec![]);
+        assert!(tab.fibers.items.is_empty() && tab.fiber_ids.is_empty());
+        assert_eq!((tab.fibers.state.selected(), &*tab.selected_fiber_dump.0), (None, ""));
+        tab.select_next_fiber();
+    }
+}
@@ -0,0 +1,154 @@
+use std::fs;
+use std::ops::Range;
+
+use serde::Deserialize;
+
+use panopticon_core::command::model::CommandSettings;
+use panopticon_core::json_metrics::model::JsonMetricSettings;
+use panopticon_core::sql::model::SqlQuerySettings;
+
+use crate::alerts::model::AlertRule;
+use crate::dashboard::DashboardSettings;
+use crate::widgets::split::Splits;
+
+///
+/// Settings read from the file passed with `--config`, for sources that don't fit on the command line.
+///
+/// The file is JSON, and all of its sections are optional:
+///
+/// {"json_metrics": [{"name": "Queue depth", "url": "http://localhost:8080/admin/stats", "json_pointer": "/queue/depth", "interval": 5000}],
+///  "commands": [{"name": "Open files", "command": "ls /proc/1234/fd | wc -l", "interval": 5000}],
+///  "sql_queries": [{"name": "Pending jobs", "url": "postgresql://localhost/app", "query": "select count(*) from job_queue"}],
+///  "alerts": [{"name": "Pool exhausted", "metric": "hikari/waiting", "comparison": ">", "threshold": 0, "duration": 30}],
+///  "dashboards": [{"title": "Checkout", "rows": [{"widgets": [{"widget": "chart", "metric": "hikari/active"}, {"widget": "alerts"}]}]}],
+///  "layout": {"list": 10, "charts": -5}}
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub json_metrics: Vec<JsonMetricSettings>,
+    pub commands: Vec<CommandSettings>,
+    pub sql_queries: Vec<SqlQuerySettings>,
+    pub alerts: Vec<AlertRule>,
+    pub dashboards: Vec<DashboardSettings>,
+    pub layout: Splits,
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Config, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Couldn't read config file {}: {}", path, e))?;
+        Config::parse(&text, path)
+    }
+
+    /// Reads and checks the text of a config file, `path` telling where it comes from in errors
+    pub fn parse(text: &str, path: &str) -> Result<Config, String> {
+        let config: Config = serde_json::from_str(text).map_err(|e| format!("Invalid config file {}: {}", path, e))?;
+        for c in &config.commands {
+            c.validate()?;
+        }
+        for q in &config.sql_queries {
+            q.validate()?;
+        }
+        for a in &config.alerts {
+            a.validate()?;
+        }
+        for d in &config.dashboards {
+            d.validate()?;
+        }
+        Ok(config)
+    }
+
+    /// Replaces the layout section of the file, leaving the rest as it is
+    pub fn save_layout(path: &str, layout: &Splits) -> Result<(), String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Couldn't read config file {}: {}", path, e))?;
+        let text = Config::with_layout(&text, layout).map_err(|e| format!("Invalid config file {}: {}", path, e))?;
+        fs::write(path, text).map_err(|e| format!("Couldn't save the layout to config file {}: {}", path, e))
+    }
+
+    /// Text of a config file with the layout section replaced, or added last if there's none
+    fn with_layout(text: &str, layout: &Splits) -> Result<String, String> {
+        serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(text).map_err(|e| e.to_string())?;
+        let layout = serde_json::to_string(layout).map_err(|e| e.to_string())?;
+        match top_level_value(text, "layout") {
+            Some(value) => Ok(format!("{}{}{}", &text[..value.start], layout, &text[value.end..])),
+            None => {
+                let end = text.rfind('}').ok_or("No object")?;
+                let before = text[..end].trim_end();
+                let separator = if before.ends_with('{') { "" } else { "," };
+                Ok(format!("{}{}\n  \"layout\": {}\n{}", before, separator, layout, &text[end..]))
+            }
+        }
+    }
+}
+
+/// Byte range of the value of a key of the object in a JSON text, if it has the key
+fn top_level_value(text: &str, key: &str) -> Option<Range<usize>> {
+    let bytes = text.as_bytes();
+    let key = format!("\"{}\"", key);
+    let (mut depth, mut i) = (0, 0);
+    let mut last_string = 0..0;
+    let mut value_start = None;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'"' => {
+                let start = i;
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                last_string = start..i + 1;
+            }
+            b'{' | b'[' => depth += 1,
+            b':' if depth == 1 && text.get(last_string.clone()) == Some(key.as_str()) => value_start = Some(i + 1),
+            b',' | b'}' | b']' => {
+                if let Some(start) = value_start.filter(|_| depth == 1) {
+                    let value = &text[start..i];
+                    let start = start + value.len() - value.trim_start().len();
+                    return Some(start..start + value.trim().len());
+                }
+                if bytes[i] != b',' {
+                    depth -= 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::Config;
+    use crate::widgets::split::Splits;
+
+    #[test]
+    fn config_sections_are_optional() {
+        let config: Config = serde_json::from_str(r#"{"json_metrics": [{"name": "Queue depth", "url": "http://localhost:8080/stats", "json_pointer": "/queue/depth"}]}"#).unwrap();
+        assert_eq!(config.json_metrics[0].interval, None);
+        let empty: Config = serde_json::from_str("{}").unwrap();
+        assert!(empty.json_metrics.is_empty() && empty.commands.is_empty() && empty.sql_queries.is_empty());
+    }
+
+    #[test]
+    fn command_regexes_are_compiled_as_the_config_is_read() {
+        let config = Config::parse(r#"{"commands": [{"name": "Load", "command": "uptime", "regex": "load average: ([\\d.]+)"}]}"#, "config.json").unwrap();
+        assert_eq!(config.commands[0].regex.as_ref().map(|r| r.as_str()), Some(r"load average: ([\d.]+)"));
+        let invalid = Config::parse(r#"{"commands": [{"name": "Load", "command": "uptime", "regex": "(["}]}"#, "config.json");
+        assert!(invalid.unwrap_err().starts_with("Invalid config file config.json: Invalid regex (["));
+    }
+
+    #[test]
+    fn only_the_layout_section_is_saved() {
+        let layout = Splits { list: 10, charts: -5 };
+        let text = "{\n  \"json_metrics\": [{\"name\": \"a, \\\"b\\\"\", \"layout\": 1}],\n  \"layout\": {\"list\": 5},\n  \"alerts\": []\n}\n";
+        assert_eq!(
+            Config::with_layout(text, &layout).unwrap(),
+            "{\n  \"json_metrics\": [{\"name\": \"a, \\\"b\\\"\", \"layout\": 1}],\n  \"layout\": {\"list\":10,\"charts\":-5},\n  \"alerts\": []\n}\n"
+        );
+        assert_eq!(
+            Config::with_layout("{\"alerts\": []}", &layout).unwrap(),
+            "{\"alerts\": [],\n  \"layout\": {\"list\":10,\"charts\":-5}\n}"
+        );
+        assert!(Config::with_layout("{\"alerts\": [", &layout).is_err());
+    }
+}
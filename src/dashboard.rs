@@ -0,0 +1,129 @@
+use serde::Deserialize;
+
+///
+/// A tab composed from the config file, e.g.
+///
+/// {"title": "Checkout", "rows": [
+///   {"height": 60, "widgets": [{"widget": "chart", "metric": "hikari/active", "width": 40}, {"widget": "chart", "metric": "prometheus/http_requests_total"}]},
+///   {"widgets": [{"widget": "values", "metric": "redis/used_memory"}, {"widget": "alerts"}, {"widget": "sources"}]}]}
+///
+/// Rows are stacked top to bottom and their widgets laid out left to right. Rows and widgets without a size
+/// share what the others leave.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct DashboardSettings {
+    pub title: String,
+    pub rows: Vec<DashboardRow>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct DashboardRow {
+    /// Percentage of the tab's height
+    pub height: Option<u16>,
+    pub widgets: Vec<DashboardWidget>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct DashboardWidget {
+    #[serde(flatten)]
+    pub kind: WidgetKind,
+    /// Percentage of the row's width
+    pub width: Option<u16>,
+    /// Shown instead of the metric name
+    pub title: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(tag = "widget", rename_all = "snake_case")]
+pub enum WidgetKind {
+    /// Line chart of the series of a metric, named like in alert rules, e.g. `hikari/active`
+    Chart { metric: String, series: Option<String> },
+    /// Latest values of the series of a metric
+    Values { metric: String },
+    /// Firing alerts of all the tabs
+    Alerts,
+    /// Sources and whether their last read failed
+    Sources,
+    /// Latest errors of all the sources
+    Errors,
+}
+
+impl DashboardWidget {
+    pub fn metric(&self) -> Option<&str> {
+        match &self.kind {
+            WidgetKind::Chart { metric, .. } | WidgetKind::Values { metric } => Some(metric),
+            _ => None,
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match (&self.title, &self.kind) {
+            (Some(t), _) => t.to_owned(),
+            (None, WidgetKind::Chart { metric, series: Some(s) }) => format!("{}{{{}}}", metric, s),
+            (None, WidgetKind::Chart { metric, .. }) | (None, WidgetKind::Values { metric }) => metric.to_owned(),
+            (None, WidgetKind::Alerts) => "Alerts".to_owned(),
+            (None, WidgetKind::Sources) => "Sources".to_owned(),
+            (None, WidgetKind::Errors) => "Errors".to_owned(),
+        }
+    }
+}
+
+impl DashboardSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rows.is_empty() || self.rows.iter().any(|r| r.widgets.is_empty()) {
+            return Err(format!("Every row of dashboard {} needs widgets", self.title));
+        }
+        if total_percent(self.rows.iter().map(|r| r.height)) > 100 {
+            return Err(format!("Rows of dashboard {} are higher than 100%", self.title));
+        }
+        for row in &self.rows {
+            if total_percent(row.widgets.iter().map(|w| w.width)) > 100 {
+                return Err(format!("Widgets of dashboard {} are wider than 100%", self.title));
+            }
+            if let Some(m) = row.widgets.iter().filter_map(|w| w.metric()).find(|m| m.split('/').count() < 2) {
+                return Err(format!("Metric {} of dashboard {} should be <source>/<chart>, e.g. fibers/suspended", m, self.title));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn total_percent(sizes: impl Iterator<Item=Option<u16>>) -> u16 {
+    sizes.flatten().sum()
+}
+
+/// Percentages of the given sizes, splitting what's left evenly between the unsized ones
+pub fn percentages(sizes: &[Option<u16>]) -> Vec<u16> {
+    let unsized_count = sizes.iter().filter(|s| s.is_none()).count() as u16;
+    let left = 100u16.saturating_sub(total_percent(sizes.iter().cloned()));
+    sizes.iter().map(|s| s.unwrap_or(left / unsized_count.max(1))).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dashboard::{percentages, DashboardSettings, WidgetKind};
+
+    #[test]
+    fn dashboards_are_read_and_checked() {
+        let dashboard: DashboardSettings = serde_json::from_str(r#"{"title": "Checkout", "rows": [
+            {"height": 60, "widgets": [{"widget": "chart", "metric": "hikari/active", "width": 40}, {"widget": "chart", "metric": "prometheus/up", "series": "app"}]},
+            {"widgets": [{"widget": "values", "metric": "redis/used_memory", "title": "Redis"}, {"widget": "alerts"}]}]}"#).unwrap();
+        assert_eq!(dashboard.rows[0].widgets[1].kind, WidgetKind::Chart { metric: "prometheus/up".to_owned(), series: Some("app".to_owned()) });
+        assert_eq!(dashboard.rows[0].widgets[1].title(), "prometheus/up{app}");
+        assert_eq!(dashboard.rows[1].widgets[0].title(), "Redis");
+        assert_eq!(dashboard.validate(), Ok(()));
+
+        let too_wide: DashboardSettings = serde_json::from_str(r#"{"title": "Wide", "rows": [
+            {"widgets": [{"widget": "sources", "width": 60}, {"widget": "errors", "width": 50}]}]}"#).unwrap();
+        assert!(too_wide.validate().is_err());
+        let bad_metric: DashboardSettings = serde_json::from_str(r#"{"title": "Bad", "rows": [
+            {"widgets": [{"widget": "values", "metric": "hikari"}]}]}"#).unwrap();
+        assert!(bad_metric.validate().is_err());
+    }
+
+    #[test]
+    fn unsized_panes_share_what_is_left() {
+        assert_eq!(percentages(&[Some(40), None, None]), vec![40, 30, 30]);
+        assert_eq!(percentages(&[None]), vec![100]);
+        assert_eq!(percentages(&[Some(100), None]), vec![100, 0]);
+    }
+}
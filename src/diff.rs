@@ -0,0 +1,209 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use panopticon_core::zio::model::{Fiber, FiberCount};
+
+use crate::alerts::model::MetricValue;
+use crate::app::App;
+use crate::widgets::tree;
+
+/// Fibers and actors listed by name in a report, those beyond are only counted
+const MAX_LISTED: usize = 10;
+
+/// What a recorded session showed at its end
+pub struct Snapshot {
+    pub name: String,
+    /// Fibers of the last dump
+    pub fibers: Option<Vec<Fiber>>,
+    /// Fibers by status at the last count, all there is of sessions where no dump was taken
+    pub fiber_count: Option<FiberCount>,
+    /// Paths of the actors of the last fetched tree
+    pub actors: Option<BTreeSet<String>>,
+    pub metrics: Vec<MetricValue>,
+}
+
+impl Snapshot {
+    pub fn of(name: &str, app: &App, fibers: Option<Vec<Fiber>>) -> Snapshot {
+        Snapshot {
+            name: name.to_owned(),
+            fibers,
+            fiber_count: app.zmx.as_ref().and_then(|z| z.fiber_counts.back().cloned()),
+            actors: app.actor_tree.as_ref()
+                .filter(|t| !t.actor_nodes.is_empty())
+                .map(|t| tree::node_paths(&t.actor_nodes).into_values().collect()),
+            metrics: app.metric_values(),
+        }
+    }
+
+    /// Fibers by status at the last count, or of the last dump
+    fn by_status(&self) -> Option<BTreeMap<String, i64>> {
+        match (&self.fiber_count, &self.fibers) {
+            (Some(c), _) => Some(
+                vec![("Done", c.done), ("Finishing", c.finishing), ("Running", c.running), ("Suspended", c.suspended)]
+                    .into_iter()
+                    .filter(|(_, n)| *n > 0)
+                    .map(|(status, n)| (status.to_owned(), n as i64))
+                    .collect()
+            ),
+            (None, Some(fibers)) => Some(counted(fibers)),
+            (None, None) => None,
+        }
+    }
+}
+
+///
+/// What changed from one snapshot to the other: fibers by status and by id, actors that appeared or disappeared,
+/// and metrics whose values changed.
+///
+/// Fiber ids only tell the same fibers apart within a process, between instances only their statuses compare.
+/// Without dumps on both sides, only the last counts of fibers by status are compared.
+pub fn report(a: &Snapshot, b: &Snapshot) -> String {
+    let mut lines = vec![format!("--- {}", a.name), format!("+++ {}", b.name)];
+    match (&a.fibers, &b.fibers) {
+        (Some(fa), Some(fb)) => fiber_changes(fa, fb, &mut lines),
+        _ => match (a.by_status(), b.by_status()) {
+            (Some(ca), Some(cb)) => status_changes(ca, cb, &mut lines),
+            (None, None) => {}
+            (ca, _) => lines.push(format!("Fibers: only counted in {}", if ca.is_some() { &a.name } else { &b.name })),
+        },
+    }
+    match (&a.actors, &b.actors) {
+        (Some(pa), Some(pb)) => {
+            lines.push(format!("Actors: {} -> {} ({:+})", pa.len(), pb.len(), pb.len() as i64 - pa.len() as i64));
+            list(pa.difference(pb).map(|p| format!("- {}", p)).collect(), &mut lines);
+            list(pb.difference(pa).map(|p| format!("+ {}", p)).collect(), &mut lines);
+        }
+        (None, None) => {}
+        _ => lines.push(format!("Actors: only fetched in {}", if a.actors.is_some() { &a.name } else { &b.name })),
+    }
+    metric_changes(&a.metrics, &b.metrics, &mut lines);
+    lines.join("\n") + "\n"
+}
+
+fn fiber_changes(a: &[Fiber], b: &[Fiber], lines: &mut Vec<String>) {
+    status_changes(counted(a), counted(b), lines);
+    let by_id = |fibers: &[Fiber]| fibers.iter().map(|f| (f.id, f.status.to_string())).collect::<BTreeMap<_, _>>();
+    let (ia, ib) = (by_id(a), by_id(b));
+    let mut changes: Vec<String> = ia.iter().filter(|(id, _)| !ib.contains_key(id)).map(|(id, s)| format!("- #{} {}", id, s)).collect();
+    changes.extend(ib.iter().filter(|(id, _)| !ia.contains_key(id)).map(|(id, s)| format!("+ #{} {}", id, s)));
+    changes.extend(ia.iter()
+        .filter_map(|(id, sa)| ib.get(id).filter(|sb| *sb != sa).map(|sb| format!("~ #{} {} -> {}", id, sa, sb))));
+    list(changes, lines);
+}
+
+/// Fibers by status of a dump
+fn counted(fibers: &[Fiber]) -> BTreeMap<String, i64> {
+    let mut counts: BTreeMap<String, i64> = BTreeMap::new();
+    for f in fibers {
+        *counts.entry(f.status.to_string()).or_default() += 1;
+    }
+    counts
+}
+
+fn status_changes(a: BTreeMap<String, i64>, b: BTreeMap<String, i64>, lines: &mut Vec<String>) {
+    let (ta, tb): (i64, i64) = (a.values().sum(), b.values().sum());
+    lines.push(format!("Fibers: {} -> {} ({:+})", ta, tb, tb - ta));
+    let statuses: BTreeSet<&String> = a.keys().chain(b.keys()).collect();
+    for status in statuses {
+        let (ca, cb) = (a.get(status).copied().unwrap_or(0), b.get(status).copied().unwrap_or(0));
+        lines.push(format!("  {:<10} {} -> {} ({:+})", status, ca, cb, cb - ca));
+    }
+}
+
+fn metric_changes(a: &[MetricValue], b: &[MetricValue], lines: &mut Vec<String>) {
+    let name = |v: &MetricValue| if v.series.is_empty() { v.metric.to_owned() } else { format!("{}{{{}}}", v.metric, v.series) };
+    let va: BTreeMap<String, f64> = a.iter().map(|v| (name(v), v.value)).collect();
+    let vb: BTreeMap<String, f64> = b.iter().map(|v| (name(v), v.value)).collect();
+    let mut changes = vec![];
+    for (metric, value) in &va {
+        match vb.get(metric) {
+            None => changes.push(format!("- {} = {}", metric, value)),
+            // NaN is what unset gauges read, it's the same on both sides
+            Some(other) if other != value && !(other.is_nan() && value.is_nan()) => changes.push(format!("~ {} {} -> {} ({:+})", metric, value, other, other - value)),
+            Some(_) => {}
+        }
+    }
+    changes.extend(vb.iter().filter(|(m, _)| !va.contains_key(*m)).map(|(m, v)| format!("+ {} = {}", m, v)));
+    if !changes.is_empty() {
+        lines.push(format!("Metrics: {} changed", changes.len()));
+        lines.extend(changes.into_iter().map(|c| format!("  {}", c)));
+    }
+}
+
+/// Changes in a section, the first ones of long lists
+fn list(changes: Vec<String>, lines: &mut Vec<String>) {
+    let more = changes.len().saturating_sub(MAX_LISTED);
+    lines.extend(changes.into_iter().take(MAX_LISTED).map(|c| format!("  {}", c)));
+    if more > 0 {
+        lines.push(format!("  ... and {} more", more));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use chrono::Local;
+
+    use panopticon_core::zio::model::{Fiber, FiberCount, FiberStatus};
+
+    use crate::alerts::model::MetricValue;
+    use crate::diff::{report, Snapshot};
+
+    fn fiber(id: usize, status: FiberStatus) -> Fiber {
+        Fiber { id, parent_id: None, status, dump: String::new() }
+    }
+
+    #[test]
+    fn changes_between_snapshots_are_reported() {
+        let actors = |paths: &[&str]| Some(paths.iter().map(|p| p.to_string()).collect::<BTreeSet<_>>());
+        let healthy = Snapshot {
+            name: "healthy.jsonl".to_owned(),
+            fibers: Some(vec![fiber(1, FiberStatus::Running), fiber(2, FiberStatus::Suspended)]),
+            fiber_count: None,
+            actors: actors(&["user", "user/workers", "user/workers/w1"]),
+            metrics: vec![MetricValue::new("hikari/active", "", 4.0), MetricValue::new("postgres/connections", "idle", 7.0), MetricValue::new("json/queue", "queue", 1.0), MetricValue::new("kafka_lag/orders", "", f64::NAN)],
+        };
+        let sick = Snapshot {
+            name: "sick.jsonl".to_owned(),
+            fibers: Some(vec![fiber(1, FiberStatus::Suspended), fiber(3, FiberStatus::Suspended)]),
+            fiber_count: None,
+            actors: actors(&["user", "user/workers", "user/workers/w2"]),
+            metrics: vec![MetricValue::new("hikari/active", "", 10.0), MetricValue::new("postgres/connections", "idle", 7.0), MetricValue::new("hikari/waiting", "", 3.0), MetricValue::new("kafka_lag/orders", "", f64::NAN)],
+        };
+        assert_eq!(report(&healthy, &sick), "\
+--- healthy.jsonl
++++ sick.jsonl
+Fibers: 2 -> 2 (+0)
+  Running    1 -> 0 (-1)
+  Suspended  1 -> 2 (+1)
+  - #2 Suspended
+  + #3 Suspended
+  ~ #1 Running -> Suspended
+Actors: 3 -> 3 (+0)
+  - user/workers/w1
+  + user/workers/w2
+Metrics: 3 changed
+  ~ hikari/active 4 -> 10 (+6)
+  - json/queue{queue} = 1
+  + hikari/waiting = 3
+");
+    }
+
+    #[test]
+    fn fiber_counts_are_compared_without_dumps() {
+        let counted = |name: &str, running: i32, suspended: i32| Snapshot {
+            name: name.to_owned(),
+            fibers: None,
+            fiber_count: Some(FiberCount { timestamp: Local::now(), done: 0, finishing: 0, running, suspended }),
+            actors: None,
+            metrics: vec![],
+        };
+        assert_eq!(report(&counted("a.jsonl", 3, 0), &counted("b.jsonl", 2, 40)), "\
+--- a.jsonl
++++ b.jsonl
+Fibers: 3 -> 42 (+39)
+  Running    3 -> 2 (-1)
+  Suspended  0 -> 40 (+40)
+");
+    }
+}
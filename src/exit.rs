@@ -0,0 +1,134 @@
+use std::fmt;
+
+use panopticon_core::error::{FetchError, FetchErrorKind};
+use serde_json::json;
+
+/// How the error Panopticon exits with is printed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ErrorFormat {
+    Text,
+    /// A line of JSON with the message, the kind of failure and the exit code
+    Json,
+}
+
+impl ErrorFormat {
+    pub fn parse(s: &str) -> Result<ErrorFormat, String> {
+        match s {
+            "text" => Ok(ErrorFormat::Text),
+            "json" => Ok(ErrorFormat::Json),
+            _ => Err(format!("Expected text or json, got {}", s)),
+        }
+    }
+}
+
+/// Why Panopticon failed, each with an exit code of its own so that scripts can tell them apart
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExitKind {
+    /// Bad options, config file or recording, or any failure not below
+    Error,
+    /// The expression of `watch` held
+    WatchHeld,
+    /// A source couldn't be reached, or the connection to it was lost
+    Connection,
+    /// A source turned the credentials down
+    Auth,
+    /// A source failed in another way
+    Fetch,
+}
+
+impl ExitKind {
+    pub fn code(self) -> i32 {
+        match self {
+            ExitKind::Error => 1,
+            ExitKind::WatchHeld => 2,
+            ExitKind::Connection => 3,
+            ExitKind::Auth => 4,
+            ExitKind::Fetch => 5,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ExitKind::Error => "error",
+            ExitKind::WatchHeld => "watch_held",
+            ExitKind::Connection => "connection",
+            ExitKind::Auth => "auth",
+            ExitKind::Fetch => "fetch",
+        }
+    }
+
+    /// Sources that don't answer in time count as unreachable
+    fn of_fetch_error(kind: FetchErrorKind) -> ExitKind {
+        match kind {
+            FetchErrorKind::Connection | FetchErrorKind::Timeout => ExitKind::Connection,
+            FetchErrorKind::Auth => ExitKind::Auth,
+            FetchErrorKind::Other => ExitKind::Fetch,
+        }
+    }
+}
+
+/// Error of a known kind to exit with
+#[derive(Debug)]
+pub struct Exit {
+    pub kind: ExitKind,
+    pub message: String,
+}
+
+impl Exit {
+    pub fn new(kind: ExitKind, message: String) -> Exit {
+        Exit { kind, message }
+    }
+
+    /// A source failing for good
+    pub fn source_failure(error: FetchError) -> Exit {
+        Exit { kind: ExitKind::of_fetch_error(error.kind), message: error.message }
+    }
+}
+
+impl fmt::Display for Exit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Exit {}
+
+/// Prints the error to stderr in the given format, returning the code to exit with
+pub fn report(error: &failure::Error, format: ErrorFormat) -> i32 {
+    let (kind, message) = match (error.downcast_ref::<Exit>(), error.downcast_ref::<FetchError>()) {
+        (Some(e), _) => (e.kind, e.message.clone()),
+        (None, Some(e)) => (ExitKind::of_fetch_error(e.kind), e.message.clone()),
+        (None, None) => (ExitKind::Error, error.to_string()),
+    };
+    match format {
+        ErrorFormat::Text => eprintln!("Error: {}", message),
+        ErrorFormat::Json => eprintln!("{}", json!({"error": message, "kind": kind.name(), "code": kind.code()})),
+    }
+    kind.code()
+}
+
+#[cfg(test)]
+mod tests {
+    use panopticon_core::error::FetchError;
+
+    use crate::exit::{Exit, ExitKind};
+
+    #[test]
+    fn failures_of_sources_are_told_apart() {
+        let kind = |e: FetchError| Exit::source_failure(e).kind;
+        assert_eq!(kind(FetchError::connection("Couldn't connect to jmx at localhost:9010")), ExitKind::Connection);
+        assert_eq!(kind(FetchError::timeout("operation timed out")), ExitKind::Connection);
+        assert_eq!(kind(FetchError::auth("Request failed with status 401 Unauthorized")), ExitKind::Auth);
+        // only the kind counts, not what the message says
+        assert_eq!(kind(FetchError::from("password authentication failed")), ExitKind::Fetch);
+        assert_eq!((ExitKind::Error.code(), ExitKind::WatchHeld.code(), ExitKind::Fetch.code()), (1, 2, 5));
+    }
+
+    #[test]
+    fn other_errors_exit_with_the_generic_code() {
+        let error = failure::err_msg("Couldn't connect to the config server");
+        assert_eq!(crate::exit::report(&error, crate::exit::ErrorFormat::Text), 1);
+        let error: failure::Error = FetchError::auth("wrong password").into();
+        assert_eq!(crate::exit::report(&error, crate::exit::ErrorFormat::Text), 4);
+    }
+}
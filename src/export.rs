@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Write};
+
+use chrono::Local;
+use serde_json::{json, Value};
+
+use crate::widgets::tree::{TreeWidgetNode, node_paths};
+
+/// File name with a timestamp suffix, so that subsequent exports don't overwrite each other
+pub fn timestamped_file_name(prefix: &str, extension: &str) -> String {
+    format!("{}-{}.{}", prefix, Local::now().format("%Y%m%d-%H%M%S"), extension)
+}
+
+/// Writes rows of already formatted values as a CSV file
+pub fn write_csv(path: &str, header: &[&str], rows: &[Vec<String>]) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    writeln!(file, "{}", header.join(","))?;
+    for row in rows {
+        let escaped: Vec<String> = row.iter().map(|v| escape_csv(v)).collect();
+        writeln!(file, "{}", escaped.join(","))?;
+    }
+    Ok(())
+}
+
+/// Graphviz digraph of a tree, with an edge from every item to each of its children
+pub fn tree_to_dot<T: TreeWidgetNode>(name: &str, items: &[T]) -> String {
+    let mut dot = format!("digraph \"{}\" {{\n  rankdir=LR;\n  node [shape=box];\n", escape_dot(name));
+    for i in items {
+        dot.push_str(&format!("  n{} [label=\"{}\"];\n", i.id(), escape_dot(&i.label())));
+    }
+    for i in items {
+        if let Some(parent) = i.parent_id() {
+            dot.push_str(&format!("  n{} -> n{};\n", parent, i.id()));
+        }
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Nested JSON objects with the name, path and children of every root item and its descendants
+pub fn tree_to_json<T: TreeWidgetNode>(items: &[T]) -> Value {
+    let paths = node_paths(items);
+    let mut children: HashMap<Option<usize>, Vec<&T>> = HashMap::new();
+    for i in items {
+        children.entry(i.parent_id()).or_default().push(i);
+    }
+    fn nest<T: TreeWidgetNode>(parent: Option<usize>, children: &HashMap<Option<usize>, Vec<&T>>, paths: &HashMap<usize, String>) -> Value {
+        Value::Array(children.get(&parent).map_or(vec![], |items| {
+            items.iter().map(|i| json!({
+                "name": i.label(),
+                "path": paths.get(&i.id()),
+                "children": nest(Some(i.id()), children, paths),
+            })).collect()
+        }))
+    }
+    nest(None, &children, &paths)
+}
+
+fn escape_dot(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use panopticon_core::akka::model::ActorTreeNode;
+
+    use crate::export::{escape_csv, tree_to_dot, tree_to_json};
+
+    #[test]
+    fn csv_values_are_escaped() {
+        assert_eq!(escape_csv("42"), "42");
+        assert_eq!(escape_csv("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn trees_are_exported_as_dot_and_json() {
+        let nodes = vec![
+            ActorTreeNode { name: "user".to_owned(), parent: None, id: 1 },
+            ActorTreeNode { name: "a\"b".to_owned(), parent: Some(1), id: 2 },
+        ];
+        assert_eq!(
+            tree_to_dot("actors", &nodes),
+            "digraph \"actors\" {\n  rankdir=LR;\n  node [shape=box];\n  n1 [label=\"user\"];\n  n2 [label=\"a\\\"b\"];\n  n1 -> n2;\n}\n"
+        );
+        assert_eq!(tree_to_json(&nodes), json!([
+            {"name": "user", "path": "user", "children": [{"name": "a\"b", "path": "user/a\"b", "children": []}]}
+        ]));
+    }
+}
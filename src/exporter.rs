@@ -0,0 +1,112 @@
+use std::io::BufReader;
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use panopticon_core::http_server::{read_request, write_response};
+
+use crate::alerts::model::MetricValue;
+
+const METRICS_PATH: &str = "/metrics";
+
+///
+/// Serves the latest values of all the collected series on a Prometheus `/metrics` endpoint.
+///
+/// Values are published every tick, scrapes get the last ones. Metrics are named after the source and chart,
+/// e.g. `hikari/active` is exported as `panopticon_hikari_active`, with a `series` label for charts with several lines.
+pub struct MetricsExporter {
+    values: Arc<Mutex<Vec<MetricValue>>>,
+}
+
+impl MetricsExporter {
+    pub fn bind(address: &str) -> Result<MetricsExporter, String> {
+        let listener = TcpListener::bind(address)
+            .map_err(|e| format!("Couldn't serve metrics on {}: {}", address, e))?;
+        let values = Arc::new(Mutex::new(vec![]));
+        {
+            let values = values.clone();
+            thread::spawn(move || {
+                for stream in listener.incoming().filter_map(|s| s.ok()) {
+                    let values = values.clone();
+                    thread::spawn(move || serve(stream, &values));
+                }
+            });
+        }
+        Ok(MetricsExporter { values })
+    }
+
+    pub fn publish(&self, values: Vec<MetricValue>) {
+        *self.values.lock().unwrap() = values;
+    }
+}
+
+/// Serves the scrapes of a connection until it's closed
+fn serve(stream: TcpStream, values: &Mutex<Vec<MetricValue>>) {
+    let mut writer = match stream.try_clone() {
+        Ok(s) => s,
+        Err(_) => return,
+    };
+    let mut reader = BufReader::new(stream);
+    while let Ok(Some(request)) = read_request(&mut reader) {
+        let result = if request.method == "GET" && request.path.split('?').next() == Some(METRICS_PATH) {
+            write_response(&mut writer, "200 OK", "text/plain; version=0.0.4", &render(&values.lock().unwrap()))
+        } else {
+            write_response(&mut writer, "404 Not Found", "text/plain", &format!("Metrics are served on GET {}\n", METRICS_PATH))
+        };
+        if result.is_err() {
+            return;
+        }
+    }
+}
+
+/// Values in the Prometheus text format, all of them gauges
+pub fn render(values: &[MetricValue]) -> String {
+    let mut text = String::new();
+    let mut last_metric: Option<&str> = None;
+    for v in values {
+        let name = metric_name(&v.metric);
+        if last_metric != Some(v.metric.as_str()) {
+            text.push_str(&format!("# TYPE {} gauge\n", name));
+            last_metric = Some(&v.metric);
+        }
+        if v.series.is_empty() {
+            text.push_str(&format!("{} {}\n", name, v.value));
+        } else {
+            text.push_str(&format!("{}{{series=\"{}\"}} {}\n", name, escape_label(&v.series), v.value));
+        }
+    }
+    text
+}
+
+/// Characters that can't be in Prometheus metric names are replaced with underscores
+fn metric_name(metric: &str) -> String {
+    let sanitized: String = metric.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    format!("panopticon_{}", sanitized)
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::alerts::model::MetricValue;
+    use crate::exporter::render;
+
+    #[test]
+    fn values_are_rendered_in_the_prometheus_format() {
+        let values = vec![
+            MetricValue::new("hikari/active", "", 3.0),
+            MetricValue::new("postgres/connections", "idle", 2.0),
+            MetricValue::new("postgres/connections", "active \"rw\"", 1.5),
+            MetricValue::new("json/Job queue", "", 7.0),
+        ];
+        assert_eq!(render(&values), "# TYPE panopticon_hikari_active gauge\n\
+            panopticon_hikari_active 3\n\
+            # TYPE panopticon_postgres_connections gauge\n\
+            panopticon_postgres_connections{series=\"idle\"} 2\n\
+            panopticon_postgres_connections{series=\"active \\\"rw\\\"\"} 1.5\n\
+            # TYPE panopticon_json_Job_queue gauge\n\
+            panopticon_json_Job_queue 7\n");
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::{BTreeMap, VecDeque};
+
+use chrono::{DateTime, Duration, FixedOffset};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::alerts::model::{MetricValue, TimedValue};
+
+/// Points returned for a target when Grafana doesn't say how many it can show
+const DEFAULT_MAX_POINTS: usize = 1000;
+
+///
+/// Values of all the collected series over the last window, for the Grafana JSON datasource to chart.
+///
+/// Targets are named like in alert rules, with the series in braces for charts with several lines,
+/// e.g. `hikari/active` or `postgres/connections{idle}`.
+pub struct SeriesStore {
+    window: Duration,
+    /// Times in ms since the epoch and values, the oldest first
+    series: BTreeMap<String, VecDeque<(i64, f64)>>,
+}
+
+/// Body of `/search`, the text typed in the metric field of a query
+#[derive(Deserialize, Default, Clone, Debug, PartialEq)]
+pub struct SearchBody {
+    #[serde(default)]
+    target: String,
+}
+
+/// Body of `/query`
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryBody {
+    range: QueryRange,
+    targets: Vec<QueryTarget>,
+    max_data_points: Option<usize>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct QueryRange {
+    from: DateTime<FixedOffset>,
+    to: DateTime<FixedOffset>,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct QueryTarget {
+    target: Option<String>,
+}
+
+impl SeriesStore {
+    pub fn new(window: Duration) -> SeriesStore {
+        SeriesStore { window, series: BTreeMap::new() }
+    }
+
+    /// Adds the samples, each at the time it was taken, dropping those that fell out of the window
+    pub fn append(&mut self, samples: &[TimedValue]) {
+        for (timestamp, v) in samples.iter().filter(|(_, v)| v.value.is_finite()) {
+            self.series.entry(target_name(v)).or_default().push_back((timestamp.timestamp_millis(), v.value));
+        }
+        let latest = match samples.iter().map(|(t, _)| t.timestamp_millis()).max() {
+            Some(t) => t,
+            None => return,
+        };
+        let oldest = latest - self.window.num_milliseconds();
+        for points in self.series.values_mut() {
+            while points.front().is_some_and(|(t, _)| *t < oldest) {
+                points.pop_front();
+            }
+        }
+        self.series.retain(|_, points| !points.is_empty());
+    }
+
+    /// Names of the targets containing the text, all of them if it's empty
+    pub fn search(&self, body: &SearchBody) -> Value {
+        json!(self.series.keys().filter(|t| t.contains(body.target.trim())).collect::<Vec<_>>())
+    }
+
+    /// Points of the targets within the range, as `[value, ms]` pairs, no more than Grafana asked for
+    pub fn query(&self, body: &QueryBody) -> Value {
+        let (from, to) = (body.range.from.timestamp_millis(), body.range.to.timestamp_millis());
+        let max_points = body.max_data_points.filter(|m| *m > 0).unwrap_or(DEFAULT_MAX_POINTS);
+        let results: Vec<Value> = body.targets.iter()
+            .filter_map(|t| t.target.as_ref())
+            .map(|target| {
+                let points: Vec<&(i64, f64)> = self.series.get(target)
+                    .map(|p| p.iter().filter(|(t, _)| *t >= from && *t <= to).collect())
+                    .unwrap_or_default();
+                // evenly thinned out, keeping the latest point
+                let step = points.len().div_ceil(max_points).max(1);
+                let datapoints: Vec<Value> = points.iter().rev().step_by(step).rev().map(|(t, v)| json!([v, t])).collect();
+                json!({"target": target, "datapoints": datapoints})
+            })
+            .collect();
+        json!(results)
+    }
+}
+
+fn target_name(v: &MetricValue) -> String {
+    if v.series.is_empty() { v.metric.to_owned() } else { format!("{}{{{}}}", v.metric, v.series) }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local, TimeZone};
+    use serde_json::json;
+
+    use crate::alerts::model::MetricValue;
+    use crate::grafana::{QueryBody, SearchBody, SeriesStore};
+
+    #[test]
+    fn series_are_searched_and_queried_within_the_window() {
+        let mut store = SeriesStore::new(Duration::minutes(1));
+        let start = Local.timestamp_millis(1_590_000_000_000);
+        for i in 0..40 {
+            let timestamp = start + Duration::seconds(2 * i);
+            store.append(&[
+                (timestamp, MetricValue::new("hikari/active", "", i as f64)),
+                (timestamp, MetricValue::new("postgres/connections", "idle", 2.0)),
+            ]);
+        }
+        assert_eq!(store.search(&SearchBody::default()), json!(["hikari/active", "postgres/connections{idle}"]));
+        assert_eq!(store.search(&serde_json::from_str(r#"{"target": "post"}"#).unwrap()), json!(["postgres/connections{idle}"]));
+
+        let body: QueryBody = serde_json::from_value(json!({
+            "range": {"from": "2020-05-20T18:39:00.000Z", "to": "2020-05-20T18:41:00.000Z"},
+            "targets": [{"target": "hikari/active", "refId": "A"}, {"target": "redis/hit_rate", "refId": "B"}],
+            "maxDataPoints": 4
+        })).unwrap();
+        let result = store.query(&body);
+        // only the last minute is kept, from 18:40:18, and 22 points are in the range, every 6th is returned
+        assert_eq!(result[0]["datapoints"], json!([[12.0, 1_590_000_024_000_i64], [18.0, 1_590_000_036_000_i64], [24.0, 1_590_000_048_000_i64], [30.0, 1_590_000_060_000_i64]]));
+        assert_eq!(result[1], json!({"target": "redis/hit_rate", "datapoints": []}));
+    }
+}
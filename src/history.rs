@@ -0,0 +1,106 @@
+use chrono::{DateTime, Local, TimeZone};
+use rusqlite::{params, Connection};
+
+use crate::alerts::model::MetricValue;
+
+/// Values of a tick, with its time
+pub type SavedTick = (DateTime<Local>, Vec<MetricValue>);
+
+///
+/// Values of all the collected series, saved every tick to a SQLite file, to chart them again after a restart.
+///
+/// Samples are in a `samples` table, with their time in ms since the epoch, e.g. for
+/// `sqlite3 history.db "select * from samples where metric = 'hikari/active'"`.
+pub struct History {
+    connection: Connection,
+}
+
+impl History {
+    pub fn open(path: &str) -> Result<History, String> {
+        let error = |e: rusqlite::Error| format!("Couldn't open history file {}: {}", path, e);
+        let connection = Connection::open(path).map_err(error)?;
+        connection.execute_batch("
+            PRAGMA journal_mode = WAL;
+            PRAGMA synchronous = NORMAL;
+            CREATE TABLE IF NOT EXISTS samples (timestamp INTEGER NOT NULL, metric TEXT NOT NULL, series TEXT NOT NULL, value REAL NOT NULL);
+            CREATE INDEX IF NOT EXISTS samples_timestamp ON samples (timestamp);
+        ").map_err(error)?;
+        Ok(History { connection })
+    }
+
+    pub fn append(&mut self, timestamp: DateTime<Local>, values: &[MetricValue]) -> Result<(), String> {
+        let error = |e: rusqlite::Error| format!("Couldn't save the history: {}", e);
+        let transaction = self.connection.transaction().map_err(error)?;
+        {
+            let mut insert = transaction.prepare_cached("INSERT INTO samples VALUES (?, ?, ?, ?)").map_err(error)?;
+            // NaN from failed divisions and the like can't be saved, and wouldn't be charted anyway
+            for v in values.iter().filter(|v| v.value.is_finite()) {
+                insert.execute(params![timestamp.timestamp_millis(), v.metric, v.series, v.value]).map_err(error)?;
+            }
+        }
+        transaction.commit().map_err(error)
+    }
+
+    /// Values saved since the given time, by tick
+    pub fn load(&self, since: DateTime<Local>) -> Result<Vec<SavedTick>, String> {
+        let error = |e: rusqlite::Error| format!("Couldn't load the history: {}", e);
+        let mut select = self.connection
+            .prepare("SELECT timestamp, metric, series, value FROM samples WHERE timestamp >= ? ORDER BY timestamp, rowid")
+            .map_err(error)?;
+        let rows = select
+            .query_map(params![since.timestamp_millis()], |r| Ok((r.get::<_, i64>(0)?, MetricValue { metric: r.get(1)?, series: r.get(2)?, value: r.get(3)? })))
+            .map_err(error)?;
+        let mut ticks: Vec<(i64, Vec<MetricValue>)> = vec![];
+        for row in rows {
+            let (timestamp, value) = row.map_err(error)?;
+            match ticks.last_mut() {
+                Some((t, values)) if *t == timestamp => values.push(value),
+                _ => ticks.push((timestamp, vec![value])),
+            }
+        }
+        Ok(ticks.into_iter().map(|(t, values)| (Local.timestamp_millis(t), values)).collect())
+    }
+
+    /// Drops the values saved before the given time, so that the file doesn't keep growing
+    pub fn prune(&self, before: DateTime<Local>) -> Result<(), String> {
+        self.connection.execute("DELETE FROM samples WHERE timestamp < ?", params![before.timestamp_millis()])
+            .map(|_| ())
+            .map_err(|e| format!("Couldn't prune the history: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local};
+
+    use crate::alerts::model::MetricValue;
+    use crate::history::History;
+
+    #[test]
+    fn values_are_loaded_back_by_tick() {
+        let mut history = History::open(":memory:").unwrap();
+        let now = Local::now();
+        let tick = |active: f64| vec![MetricValue::new("hikari/active", "", active), MetricValue::new("postgres/connections", "idle", 2.0)];
+        history.append(now - Duration::hours(2), &tick(1.0)).unwrap();
+        history.append(now - Duration::seconds(4), &tick(2.0)).unwrap();
+        history.append(now - Duration::seconds(2), &tick(3.0)).unwrap();
+
+        let loaded = history.load(now - Duration::hours(1)).unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert_eq!(loaded[0].0.timestamp_millis(), (now - Duration::seconds(4)).timestamp_millis());
+        assert_eq!(loaded[1].1, tick(3.0));
+
+        history.prune(now - Duration::hours(1)).unwrap();
+        assert_eq!(history.load(now - Duration::days(1)).unwrap().len(), 2);
+    }
+    #[test]
+    fn non_finite_values_are_left_out() {
+        let mut history = History::open(":memory:").unwrap();
+        let now = Local::now();
+        let values = vec![MetricValue::new("json/ratio", "ratio", f64::NAN), MetricValue::new("hikari/active", "", 2.0)];
+        history.append(now, &values).unwrap();
+
+        let loaded = history.load(now - Duration::hours(1)).unwrap();
+        assert_eq!(loaded[0].1, vec![MetricValue::new("hikari/active", "", 2.0)]);
+    }
+}
@@ -0,0 +1,124 @@
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::alerts::model::TimedValue;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+/// Ticks waiting to be written while the endpoint is slow, later ones are dropped
+const MAX_PENDING: usize = 60;
+
+///
+/// Writes the samples of all the collected series to InfluxDB, or any endpoint taking the line protocol, as they're taken.
+///
+/// Metrics are written as measurements named like in alert rules, e.g. `hikari/active`, with a `series` tag for charts
+/// with several lines and a `value` field. Timestamps are those of the samples, in nanoseconds, the default precision of writes.
+pub struct InfluxSink {
+    pending: mpsc::SyncSender<String>,
+    /// Why writes started failing, until it's reported
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl InfluxSink {
+    /// Writes to the URL of the write endpoint, e.g. `http://localhost:8086/write?db=panopticon`, with the token of InfluxDB 2 if given
+    pub fn new(url: String, token: Option<String>) -> InfluxSink {
+        let (pending, rx) = mpsc::sync_channel::<String>(MAX_PENDING);
+        let error = Arc::new(Mutex::new(None));
+        {
+            let error = error.clone();
+            thread::spawn(move || {
+                // one client and runtime for all the writes, keeping connections open between them
+                let setup = reqwest::Client::builder().timeout(TIMEOUT).build().map_err(|e| e.to_string())
+                    .and_then(|c| tokio::runtime::Builder::new().basic_scheduler().enable_all().build().map(|r| (c, r)).map_err(|e| e.to_string()));
+                let (client, mut runtime) = match setup {
+                    Ok(setup) => setup,
+                    Err(e) => {
+                        *error.lock().unwrap() = Some(format!("Couldn't write to InfluxDB: {}", e));
+                        return;
+                    }
+                };
+                let mut failing = false;
+                for body in rx {
+                    match runtime.block_on(write(&client, &url, token.as_deref(), body)) {
+                        Ok(()) => failing = false,
+                        // reported once until writes succeed again, rather than every tick
+                        Err(e) if !failing => {
+                            failing = true;
+                            *error.lock().unwrap() = Some(e);
+                        }
+                        Err(_) => {}
+                    }
+                }
+            });
+        }
+        InfluxSink { pending, error }
+    }
+
+    pub fn publish(&self, samples: &[TimedValue]) {
+        let body = render(samples);
+        if !body.is_empty() {
+            let _ = self.pending.try_send(body);
+        }
+    }
+
+    /// Why writes started failing, once
+    pub fn take_error(&self) -> Option<String> {
+        self.error.lock().unwrap().take()
+    }
+}
+
+async fn write(client: &reqwest::Client, url: &str, token: Option<&str>, body: String) -> Result<(), String> {
+    let mut request = client.post(url).body(body);
+    if let Some(token) = token {
+        request = request.header("Authorization", format!("Token {}", token));
+    }
+    let response = request.send().await.map_err(|e| format!("Couldn't write to InfluxDB: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("Writing to InfluxDB failed with status {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Samples in the line protocol, leaving out those that aren't finite, which InfluxDB rejects
+fn render(samples: &[TimedValue]) -> String {
+    let mut text = String::new();
+    for (timestamp, v) in samples.iter().filter(|(_, v)| v.value.is_finite()) {
+        text.push_str(&escape(&v.metric, &[',', ' ']));
+        if !v.series.is_empty() {
+            text.push_str(&format!(",series={}", escape(&v.series, &[',', '=', ' '])));
+        }
+        text.push_str(&format!(" value={} {}\n", v.value, timestamp.timestamp_nanos()));
+    }
+    text
+}
+
+fn escape(s: &str, special: &[char]) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if c == '\\' || special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local, TimeZone};
+
+    use crate::alerts::model::MetricValue;
+    use crate::influx::render;
+
+    #[test]
+    fn values_are_rendered_in_the_line_protocol() {
+        let timestamp = Local.timestamp_millis(1_590_000_000_123);
+        let samples = vec![
+            (timestamp, MetricValue::new("hikari/active", "", 3.0)),
+            (timestamp + Duration::seconds(1), MetricValue::new("postgres/connections", "idle, rw=1", 2.5)),
+            (timestamp, MetricValue::new("json/Job queue", "Job queue", f64::NAN)),
+        ];
+        assert_eq!(render(&samples), "hikari/active value=3 1590000000123000000\n\
+            postgres/connections,series=idle\\,\\ rw\\=1 value=2.5 1590000001123000000\n");
+    }
+}
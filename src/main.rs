@@ -1,68 +1,278 @@
 mod ui;
-mod zio;
-mod jmx;
-mod akka;
 mod app;
-mod fetcher;
 mod widgets;
+mod tunnel;
+mod port_forward;
+mod export;
+mod exporter;
+mod config;
+mod alerts;
+mod dashboard;
+mod recording;
+mod history;
+mod api;
+mod influx;
+mod statsd_forwarder;
+mod diff;
+mod grafana;
+mod exit;
+mod report;
 
 use std::{
-    env,
-    io::{stdout, Write},
-    sync::mpsc,
+    env, fs, iter, process,
+    io::{self, stdout, Write},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
     thread,
     time::{Duration, Instant},
 };
 
 use crossterm::{
-    event::{self, Event as CEvent, KeyCode},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event as CEvent, KeyCode, KeyModifiers, MouseButton, MouseEvent},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+    tty::IsTty,
 };
+use chrono::Local;
+use futures::future::{abortable, AbortHandle};
 use structopt::StructOpt;
 use tui::{
     backend::CrosstermBackend,
     Terminal,
 };
 
-use crate::app::{App, TabKind};
-use crate::fetcher::{Fetcher, FetcherRequest, FetcherResponse};
+use panopticon_core::actuator::model::{ActuatorMetric, ActuatorSettings};
+use panopticon_core::akka::model::{ActorCountAlertSettings, ActorSystemFlavor, AkkaSettings, DEFAULT_EXCLUDED_ACTORS, HttpAuth, HttpSettings, ShardingSettings};
+use panopticon_core::cats_effect::model::{CatsEffectDumpSource, CatsEffectSettings};
+use panopticon_core::docker::model::DockerSettings;
+use panopticon_core::error::FetchError;
+use panopticon_core::fetcher::{Fetcher, FetcherRequest, FetcherResponse, Sources};
+use panopticon_core::jfr::model::JfrSettings;
+use panopticon_core::jmx::discovery;
+use panopticon_core::jmx::model::{HikariAlertSettings, JMXConnectionSettings, JMXSslSettings};
+use panopticon_core::k8s::model::{K8sPodSettings, PodRef};
+use panopticon_core::kafka::model::KafkaLagSettings;
+use panopticon_core::logs::model::{LogLines, LogSettings};
+use panopticon_core::otlp::model::{OtlpMetrics, OtlpSettings};
+use panopticon_core::postgres::model::PostgresSettings;
+use panopticon_core::prometheus::model::{MetricSelector, PromQLSettings, PrometheusSettings};
+use panopticon_core::proxy::model::ProxySettings;
+use panopticon_core::rabbitmq::model::{RabbitMqSettings, RabbitQueue};
+use panopticon_core::redis::model::RedisSettings;
+use panopticon_core::statsd::model::{StatsdMetrics, StatsdSettings};
+use panopticon_core::traces::model::{TraceBackend, TracesSettings};
 
-use crate::akka::model::AkkaSettings;
-use crate::jmx::model::JMXConnectionSettings;
+use crate::alerts::notify;
+use crate::alerts::watch::{parse_duration, Watch, WatchExpr};
+use crate::api::ApiCall;
+use crate::app::{App, TabKind};
+use crate::config::Config;
+use crate::diff::Snapshot;
+use crate::exit::{ErrorFormat, Exit, ExitKind};
+use crate::exporter::MetricsExporter;
+use crate::grafana::SeriesStore;
+use crate::history::History;
+use crate::influx::InfluxSink;
+use crate::port_forward::PortForwardManager;
+use crate::recording::{Recorder, Recording};
+use crate::statsd_forwarder::StatsdForwarder;
+use crate::tunnel::TunnelManager;
+use crate::ui::{LastFrame, Redraw};
 
 enum Event<I> {
     Input(I),
+    Mouse(MouseEvent),
     Tick,
     FetcherResponse(FetcherResponse),
+    /// How long reading from a source took, for the debug overlay
+    FetchTime(&'static str, Duration),
+    Resize,
+    /// All the responses of a recording were replayed, but those that couldn't be read
+    ReplayFinished(Result<usize, String>),
+    /// A call to the API served with --serve-api
+    Api(ApiCall),
+    /// An alert was posted to the webhook of its rule, by the name of the rule
+    AlertWebhook(String, Result<(), String>),
 }
 
-/// At least one of the following option sets has to be specified for panopticon-tui to launch:
+/// Frames are put off while events are waiting, but no longer than this
+const MAX_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+///
+/// What of the screen handling the event may change, so that the next frame has to draw it again.
+///
+/// Ticks change what `App` tells once they're handled, and responses only change the status bar while frozen.
+fn redraw_of<I>(event: &Event<I>, app: &App) -> Redraw {
+    match event {
+        Event::FetchTime(_, _) if app.debug.shown => Redraw::All,
+        Event::FetcherResponse(_) if !app.frozen => Redraw::All,
+        Event::FetchTime(_, _) | Event::FetcherResponse(_) | Event::Tick => Redraw::Nothing,
+        Event::Mouse(MouseEvent::Up(_, _, _, _)) | Event::Mouse(MouseEvent::Drag(_, _, _, _)) => Redraw::Nothing,
+        _ => Redraw::All,
+    }
+}
+
+/// Sends events to the main loop, counting those that weren't handled yet, and recording responses if asked to
+struct EventSender<I> {
+    tx: mpsc::Sender<Event<I>>,
+    queued: Arc<AtomicUsize>,
+    recorder: Option<Arc<Recorder>>,
+}
+
+impl<I> EventSender<I> {
+    fn send(&self, event: Event<I>) -> Result<(), String> {
+        if let (Some(recorder), Event::FetcherResponse(r)) = (&self.recorder, &event) {
+            recorder.record(r);
+        }
+        self.queued.fetch_add(1, Ordering::Relaxed);
+        self.tx.send(event).map_err(|_| "The event loop is gone".to_owned())
+    }
+}
+
+impl<I> Clone for EventSender<I> {
+    fn clone(&self) -> Self {
+        EventSender { tx: self.tx.clone(), queued: self.queued.clone(), recorder: self.recorder.clone() }
+    }
+}
+
+/// Without any of the following option sets, panopticon-tui offers to monitor a local JVM over JMX:
 ///
 /// - zio-zmx
 ///
-/// - jmx + db-pool-name
+/// - cats-effect-dump-command or cats-effect-dump-url
+///
+/// - jmx, optionally with db-pool-name and/or kafka and/or cassandra-metrics-domain and/or loggers and/or thread-pool
+///   and/or jvm-info and/or akka-cluster
+///
+/// - actor-tree + actor-count (+ jmx + akka-dispatchers)
+///
+/// - akka-management + sharding-region
+///
+/// - jfr
 ///
-/// - actor-tree + actor-count
+/// - prometheus, optionally with prometheus-metric
+///
+/// - prometheus-server + promql
+///
+/// - actuator, optionally with actuator-metric
+///
+/// - config with json_metrics, commands or sql_queries
+///
+/// - statsd, optionally with statsd-metric
+///
+/// - log
+///
+/// - postgres
+///
+/// - kafka-brokers + kafka-group
+///
+/// - redis, optionally with redis-password
+///
+/// - docker-container, optionally with docker-socket
+///
+/// - k8s-pod
+///
+/// - rabbitmq + rabbitmq-queue
+///
+/// - proxy-status
+///
+/// - jaeger or zipkin + trace-service
+///
+/// - otlp, optionally with otlp-metric
 #[derive(Debug, StructOpt)]
 struct Cli {
     /// Frequency (in ms) to use for fetching metrics.
-    /// Don't set this too low, because currently zmx tab does a full fiber dump every tick
+    /// Fibers are counted every tick from a full fiber dump, so don't set this too low with zio-zmx or cats-effect,
+    /// or count them less often with --fiber-count-ticks
     #[structopt(long = "tick-rate", default_value = "2000")]
     tick_rate: u64,
     /// Address of zio-zmx server, e.g. localhost:6789
     #[structopt(long = "zio-zmx")]
     zio_zmx: Option<String>,
-    /// Address of remote jmx source, e.g. localhost:9010
+    /// Count fibers every this many ticks instead of every tick,
+    /// as zio-zmx servers and cats-effect apps take a full fiber dump to be counted
+    #[structopt(long = "fiber-count-ticks", default_value = "1")]
+    fiber_count_ticks: u32,
+    /// Shell command printing a fiber dump of a cats-effect 3 app, e.g. one sending it SIGUSR1 and reading its stderr.
+    /// Shows the fibers like the ones of ZIO apps
+    #[structopt(long = "cats-effect-dump-command", conflicts_with_all = &["zio-zmx", "cats-effect-dump-url"])]
+    cats_effect_dump_command: Option<String>,
+    /// Url of an endpoint serving a fiber dump of a cats-effect 3 app. Shows the fibers like the ones of ZIO apps
+    #[structopt(long = "cats-effect-dump-url", conflicts_with = "zio-zmx")]
+    cats_effect_dump_url: Option<String>,
+    /// Address of remote jmx source, e.g. localhost:9010.
+    /// Use an http(s) url to connect through a Jolokia agent instead, e.g. http://localhost:8778/jolokia,
+    /// or a service:jmx: url, e.g. of a local connector
     #[structopt(long = "jmx")]
     jmx: Option<String>,
-    /// Optional username for authorized jmx access
+    /// Optional username for authorized jmx (or Jolokia) access
     #[structopt(long = "jmx-username")]
     jmx_username: Option<String>,
-    /// Optional password for authorized jmx access
+    /// Optional password for authorized jmx (or Jolokia) access
     #[structopt(long = "jmx-password")]
     jmx_password: Option<String>,
-    /// Connection pool name, used to qualify JMX beans for Slick and/or HikariCP
+    /// Connect to jmx over SSL/TLS
+    #[structopt(long = "jmx-ssl")]
+    jmx_ssl: bool,
+    /// Truststore used to verify the jmx server certificate.
+    /// For RMI: any java keystore (passed as javax.net.ssl.trustStore). For Jolokia: a PEM certificate
+    #[structopt(long = "jmx-truststore")]
+    jmx_truststore: Option<String>,
+    /// Optional password for the jmx truststore
+    #[structopt(long = "jmx-truststore-password")]
+    jmx_truststore_password: Option<String>,
+    /// Keystore with a client certificate, for servers requiring client authentication.
+    /// For RMI: any java keystore (passed as javax.net.ssl.keyStore). For Jolokia: a PKCS12 archive
+    #[structopt(long = "jmx-keystore")]
+    jmx_keystore: Option<String>,
+    /// Optional password for the jmx keystore
+    #[structopt(long = "jmx-keystore-password")]
+    jmx_keystore_password: Option<String>,
+    /// Object name pattern of MBeans to receive notifications from, e.g. java.lang:type=GarbageCollector,*.
+    /// Can be repeated
+    #[structopt(long = "jmx-notifications")]
+    jmx_notifications: Vec<String>,
+    /// Allow invoking HikariCP pool operations (soft evict connections, suspend and resume pool) from the Slick tab,
+    /// changing logger levels from the Loggers tab, and dumping heap or running GC from the JVM tab
+    #[structopt(long = "allow-jmx-operations")]
+    allow_jmx_operations: bool,
+    /// Show kafka consumer metrics (lag, fetch rate) of the app connected over jmx
+    #[structopt(long = "kafka")]
+    kafka: bool,
+    /// Show a Loggers tab listing Logback or Log4j2 loggers of the app connected over jmx
+    #[structopt(long = "loggers")]
+    loggers: bool,
+    /// Object name pattern of executor/thread pool MBeans to chart in a Thread pools tab,
+    /// e.g. Catalina:type=ThreadPool,* or org.eclipse.jetty.util.thread:type=queuedthreadpool,*. Can be repeated
+    #[structopt(long = "thread-pool")]
+    thread_pools: Vec<String>,
+    /// Show a JVM tab with uptime, version, input arguments and system properties of the app connected over jmx
+    #[structopt(long = "jvm-info")]
+    jvm_info: bool,
+    /// Show a Cluster tab with Akka cluster membership and reachability of the app connected over jmx.
+    /// Together with --kamon-prometheus, also charts remote messages sent and received by the node
+    #[structopt(long = "akka-cluster")]
+    akka_cluster: bool,
+    /// GC pauses longer than this (in ms) are flagged in the GC log of the JVM tab
+    #[structopt(long = "gc-pause-threshold", default_value = "200")]
+    gc_pause_threshold: u64,
+    /// Directory on the monitored host to write heap dumps requested from the JVM tab to
+    #[structopt(long = "heap-dump-dir", default_value = "/tmp")]
+    heap_dump_dir: String,
+    /// JMX domain of DataStax java driver metrics, i.e. `<cluster name>-metrics`, e.g. cluster1-metrics.
+    /// Shows a Cassandra tab with driver connection pool and request latency metrics
+    #[structopt(long = "cassandra-metrics-domain")]
+    cassandra_metrics_domain: Option<String>,
+    /// Alert when all HikariCP connections are in use for this many consecutive ticks
+    #[structopt(long = "hikari-alert-ticks", default_value = "3")]
+    hikari_alert_ticks: u32,
+    /// Alert when more than this many threads are awaiting a HikariCP connection
+    #[structopt(long = "hikari-alert-waiting", default_value = "5")]
+    hikari_alert_waiting: i32,
+    /// Connection pool name, used to qualify JMX beans for Slick and/or HikariCP.
+    /// If omitted, and no other jmx metrics are requested, pools are discovered and picked on the Slick tab
     #[structopt(long = "db-pool-name")]
     db_pool_name: Option<String>,
     /// Address of http endpoint to get akka actor tree
@@ -71,133 +281,1014 @@ struct Cli {
     /// Address of http endpoint to get current actor count
     #[structopt(long = "actor-count")]
     actor_count: Option<String>,
+    /// Address of http endpoint to get the dead letter count and recent dead letters.
+    /// Shows dead letters per tick and a log of recent ones in the Akka tab
+    #[structopt(long = "dead-letters")]
+    dead_letters: Option<String>,
+    /// Object name pattern of Akka dispatcher MBeans, e.g. akka:type=Dispatcher,*.
+    /// Charts active threads, queued tasks and parallelism of every dispatcher in the Akka tab. Requires --jmx
+    #[structopt(long = "akka-dispatchers")]
+    akka_dispatchers: Option<String>,
+    /// Address of http endpoint to get mailbox sizes by actor path.
+    /// Adds a mailbox column to the actor tree and lists actors with the biggest mailboxes in the Akka tab
+    #[structopt(long = "mailbox-sizes")]
+    mailbox_sizes: Option<String>,
+    /// Address of the Prometheus endpoint of the Kamon reporter, e.g. http://localhost:9095/metrics.
+    /// Mailbox sizes, processing times and dead letter counts are scraped from Kamon's Akka instrumentation instead
+    #[structopt(long = "kamon-prometheus", conflicts_with_all = &["mailbox-sizes", "dead-letters"])]
+    kamon_prometheus: Option<String>,
+    /// Path pattern of actors to leave out of the actor tree and count, e.g. /user/workers/*.
+    /// `*` matches any characters within a path segment. Can be repeated. Defaults to /system/* and /user/StreamSupervisor-*
+    #[structopt(long = "exclude-actors")]
+    exclude_actors: Vec<String>,
+    /// Path pattern of actors to watch, e.g. /user/singleton. Matching actors are highlighted in the actor tree,
+    /// and whether they're there is tracked across tree reloads. Can be repeated
+    #[structopt(long = "watch-actor")]
+    watch_actors: Vec<String>,
+    /// Number of actor tree levels to fetch at once, for very large actor systems.
+    /// Deeper subtrees are fetched when expanded, and dropped when collapsed. Requires a tree endpoint supporting `depth` and `path` parameters
+    #[structopt(long = "actor-tree-depth")]
+    actor_tree_depth: Option<u32>,
+    /// Alert when the actor count grows on this many consecutive fetches, 0 to disable
+    #[structopt(long = "actor-count-alert-ticks", default_value = "10")]
+    actor_count_alert_ticks: u32,
+    /// Alert when more than this many actors are running
+    #[structopt(long = "actor-count-limit")]
+    actor_count_limit: Option<u64>,
+    /// Monitor an Apache Pekko actor system instead of an Akka one
+    #[structopt(long = "pekko")]
+    pekko: bool,
+    /// Extra header sent to akka HTTP endpoints, as `Name: value`. Can be repeated
+    #[structopt(long = "actor-http-header", parse(try_from_str = parse_http_header))]
+    actor_http_headers: Vec<(String, String)>,
+    /// Username for basic authentication on akka HTTP endpoints
+    #[structopt(long = "actor-http-username")]
+    actor_http_username: Option<String>,
+    /// Password for basic authentication on akka HTTP endpoints
+    #[structopt(long = "actor-http-password")]
+    actor_http_password: Option<String>,
+    /// Bearer token sent to akka HTTP endpoints
+    #[structopt(long = "actor-http-token", conflicts_with = "actor-http-username")]
+    actor_http_token: Option<String>,
+    /// Don't verify certificates of https akka endpoints, e.g. self-signed ones on staging. Insecure
+    #[structopt(long = "insecure-skip-tls-verify")]
+    insecure_skip_tls_verify: bool,
+    /// Akka Management address of a cluster node, e.g. http://10.0.0.1:8558. Can be repeated, one per node.
+    /// Together with --sharding-region, shows entity counts of shard regions on every node in a Sharding tab
+    #[structopt(long = "akka-management")]
+    akka_management: Vec<String>,
+    /// Sharded entity type name to show in the Sharding tab. Can be repeated
+    #[structopt(long = "sharding-region")]
+    sharding_regions: Vec<String>,
     /// Time period (in ms) to assemble akka actor tree
     #[structopt(long = "actor-tree-timeout", default_value = "1000")]
     actor_tree_timeout: u64,
+    /// Java Flight Recorder repository of a local JDK 14+ app (or a single .jfr file), e.g. /tmp/jfr.
+    /// Shows a JFR tab with allocation rate, GC pauses and thread contention events
+    #[structopt(long = "jfr")]
+    jfr: Option<String>,
+    /// The jfr tool used to read recordings, from JDK 14 or newer
+    #[structopt(long = "jfr-tool", default_value = "jfr")]
+    jfr_tool: String,
+    /// Prometheus metrics endpoint of the app, e.g. http://localhost:9100/metrics. Shows a Prometheus tab
+    /// charting the metrics picked with --prometheus-metric, or listing all the scraped ones if none are
+    #[structopt(long = "prometheus")]
+    prometheus: Option<String>,
+    /// Selector of metrics to chart in the Prometheus tab, e.g. http_requests_total{method="GET"}.
+    /// Counters are charted as rates per second. Can be repeated
+    #[structopt(long = "prometheus-metric", parse(try_from_str = MetricSelector::parse))]
+    prometheus_metrics: Vec<MetricSelector>,
+    /// Address of a Prometheus server, e.g. http://prometheus:9090. Shows a PromQL tab charting the results
+    /// of the --promql queries
+    #[structopt(long = "prometheus-server", requires = "promql")]
+    prometheus_server: Option<String>,
+    /// PromQL query to run against the Prometheus server every tick and chart in the PromQL tab,
+    /// e.g. 'histogram_quantile(0.99, sum(rate(http_request_duration_seconds_bucket[5m])) by (le))'. Can be repeated
+    #[structopt(long = "promql", requires = "prometheus-server")]
+    promql: Vec<String>,
+    /// Spring Boot Actuator base address, e.g. http://localhost:8080/actuator. Shows an Actuator tab with
+    /// health status, charts of the --actuator-metric metrics and a thread dump viewer
+    #[structopt(long = "actuator")]
+    actuator: Option<String>,
+    /// Micrometer metric to chart in the Actuator tab, optionally with tags, e.g. http.server.requests{status:500}.
+    /// Counts and totals are charted as rates per second. Can be repeated
+    #[structopt(long = "actuator-metric", parse(try_from_str = ActuatorMetric::parse))]
+    actuator_metrics: Vec<ActuatorMetric>,
+    /// Local address to receive statsd metrics on over UDP, e.g. 0.0.0.0:8125. Shows a StatsD tab charting
+    /// the metrics the app sends, aggregated every tick. Counters are charted as rates per second,
+    /// timers as the mean and max of the values received during the tick
+    #[structopt(long = "statsd")]
+    statsd: Option<String>,
+    /// Name of a statsd metric to chart in the StatsD tab, e.g. api.requests. Can be repeated.
+    /// If omitted, every received metric gets a chart
+    #[structopt(long = "statsd-metric", requires = "statsd")]
+    statsd_metrics: Vec<String>,
+    /// Log file to tail in a Logs tab, e.g. /var/log/app.log, or the http(s) url of a log stream,
+    /// either Server-Sent Events or plain chunked text
+    #[structopt(long = "log")]
+    log: Option<String>,
+    /// PostgreSQL connection string, e.g. postgresql://monitor@localhost:5432/app. Shows a PostgreSQL tab with
+    /// connections by state, transaction rates and running queries, from pg_stat_activity and pg_stat_database
+    #[structopt(long = "postgres")]
+    postgres: Option<String>,
+    /// Kafka bootstrap broker, e.g. localhost:9092. Can be repeated. Together with --kafka-group, shows a Kafka lag tab
+    /// with consumer group lag computed from the brokers' offsets. Only plaintext listeners are supported
+    #[structopt(long = "kafka-brokers", requires = "kafka-group", use_delimiter = true)]
+    kafka_brokers: Vec<String>,
+    /// Consumer group to chart the lag of in the Kafka lag tab. Can be repeated
+    #[structopt(long = "kafka-group", requires = "kafka-brokers")]
+    kafka_groups: Vec<String>,
+    /// Address of a Redis server, e.g. localhost:6379. Shows a Redis tab charting memory usage, clients, ops/sec
+    /// and keyspace hit rate from INFO, along with the slowest commands from SLOWLOG
+    #[structopt(long = "redis")]
+    redis: Option<String>,
+    /// Password sent with AUTH before polling Redis
+    #[structopt(long = "redis-password", requires = "redis")]
+    redis_password: Option<String>,
+    /// Name or id of a local Docker container. Shows a Docker tab charting its CPU, memory and network usage
+    /// against its limits, read from the Docker daemon
+    #[structopt(long = "docker-container")]
+    docker_container: Option<String>,
+    /// Unix socket of the Docker daemon
+    #[structopt(long = "docker-socket", default_value = "/var/run/docker.sock")]
+    docker_socket: String,
+    /// Kubernetes pod, as namespace/pod. Shows a Pod tab charting the CPU and memory of its containers against their
+    /// limits, from the metrics API (metrics-server must be installed), along with restarts and OOM kills
+    #[structopt(long = "k8s-pod", parse(try_from_str = PodRef::parse))]
+    k8s_pod: Option<PodRef>,
+    /// The kubectl client used to reach the cluster, with its current context
+    #[structopt(long = "kubectl-tool", default_value = "kubectl")]
+    kubectl_tool: String,
+    /// Address of the RabbitMQ management API, e.g. http://localhost:15672. Together with --rabbitmq-queue,
+    /// shows a RabbitMQ tab charting queue depths and rates
+    #[structopt(long = "rabbitmq", requires = "rabbitmq-queue")]
+    rabbitmq: Option<String>,
+    /// Queue to chart in the RabbitMQ tab, as vhost/name or just name on the default vhost. Can be repeated
+    #[structopt(long = "rabbitmq-queue", requires = "rabbitmq", parse(try_from_str = RabbitQueue::parse))]
+    rabbitmq_queues: Vec<RabbitQueue>,
+    /// User of the RabbitMQ management API, which needs the monitoring tag
+    #[structopt(long = "rabbitmq-username", default_value = "guest")]
+    rabbitmq_username: String,
+    /// Password of the RabbitMQ management API user
+    #[structopt(long = "rabbitmq-password", default_value = "guest")]
+    rabbitmq_password: String,
+    /// Url of HAProxy's CSV stats, e.g. http://localhost:8404/stats;csv, or of nginx's stub_status, e.g.
+    /// http://localhost/nginx_status. Shows a Proxy tab charting requests, connections and backend health
+    #[structopt(long = "proxy-status")]
+    proxy_status: Option<String>,
+    /// Address of the Jaeger query service, e.g. http://localhost:16686. Together with --trace-service,
+    /// shows a Traces tab listing the service's recent traces
+    #[structopt(long = "jaeger", requires = "trace-service", conflicts_with = "zipkin")]
+    jaeger: Option<String>,
+    /// Address of the Zipkin API, e.g. http://localhost:9411. Together with --trace-service,
+    /// shows a Traces tab listing the service's recent traces
+    #[structopt(long = "zipkin", requires = "trace-service")]
+    zipkin: Option<String>,
+    /// Service whose traces are listed, as it reports itself to Jaeger or Zipkin
+    #[structopt(long = "trace-service")]
+    trace_service: Option<String>,
+    /// Number of recent traces to list
+    #[structopt(long = "trace-limit", default_value = "20")]
+    trace_limit: usize,
+    /// Local address to receive OpenTelemetry metrics on over OTLP/HTTP with JSON encoding, e.g. 0.0.0.0:4318.
+    /// Shows an OTLP tab charting the received metrics
+    #[structopt(long = "otlp")]
+    otlp: Option<String>,
+    /// Name of an OpenTelemetry metric to chart in the OTLP tab, e.g. http.server.requests. Can be repeated.
+    /// All the received metrics are charted if not set
+    #[structopt(long = "otlp-metric", requires = "otlp")]
+    otlp_metrics: Vec<String>,
+    /// JSON config file with sources that don't fit on the command line, e.g. JSON endpoints to poll, commands to run or SQL queries.
+    /// See the README for its format
+    #[structopt(long = "config")]
+    config: Option<String>,
+    /// SSH jump host, e.g. user@bastion.example.com.
+    /// All the addresses above are then reached through ssh tunnels, established with the system `ssh` binary.
+    /// Key-based authentication is required. For RMI, the app must use the same jmxremote port and rmi port
+    /// and set -Djava.rmi.server.hostname=localhost, and that port must be free locally. Kafka brokers can't be reached this way
+    #[structopt(long = "ssh")]
+    ssh: Option<String>,
+    /// Draws borders, trees and charts with ASCII characters only, for terminals and fonts that lack box-drawing
+    /// and braille characters
+    #[structopt(long = "ascii")]
+    ascii: bool,
+    /// Draws with high-contrast colors that don't rely on telling red from green, for color blindness
+    #[structopt(long = "high-contrast")]
+    high_contrast: bool,
+    /// Doesn't draw the TUI, but prints the latest values of all the collected series to stdout every tick,
+    /// as a line of JSON. Series are named like in alert rules
+    #[structopt(long = "headless", conflicts_with_all = &["ascii", "high-contrast"])]
+    headless: bool,
+    /// Local address to serve the latest values of all the collected series on, as a Prometheus /metrics endpoint,
+    /// e.g. 0.0.0.0:9464
+    #[structopt(long = "serve-metrics")]
+    serve_metrics: Option<String>,
+    /// Local address to serve the state of the session on, as a JSON API, e.g. 127.0.0.1:9465. See the README
+    /// for the endpoints
+    #[structopt(long = "serve-api")]
+    serve_api: Option<String>,
+    /// Write endpoint of InfluxDB to write the values of all the collected series to every tick, e.g.
+    /// http://localhost:8086/write?db=panopticon, or any endpoint taking the line protocol
+    #[structopt(long = "influx-url")]
+    influx_url: Option<String>,
+    /// API token of InfluxDB 2, e.g. with --influx-url http://localhost:8086/api/v2/write?org=acme&bucket=panopticon
+    #[structopt(long = "influx-token", requires = "influx-url")]
+    influx_token: Option<String>,
+    /// Address of a statsd server to send the values of all the collected series to every tick, as gauges,
+    /// e.g. localhost:8125
+    #[structopt(long = "forward-statsd")]
+    forward_statsd: Option<String>,
+    /// Prefix of the names of the gauges sent with --forward-statsd
+    #[structopt(long = "forward-statsd-prefix", default_value = "panopticon")]
+    forward_statsd_prefix: String,
+    /// Sends the series of charts with several lines as DogStatsD tags, rather than in the names of the gauges
+    #[structopt(long = "forward-statsd-tags", requires = "forward-statsd")]
+    forward_statsd_tags: bool,
+    /// File to write everything read from the sources to, along with the options and config, to replay the session
+    /// offline with --replay
+    #[structopt(long = "record", conflicts_with = "replay")]
+    record: Option<String>,
+    /// Replays a session recorded with --record instead of reading from the sources, with the tabs it had.
+    /// Source options are taken from the recording, other ones like --headless from the command line
+    #[structopt(long = "replay", conflicts_with_all = &["influx-url", "forward-statsd", "history"])]
+    replay: Option<String>,
+    /// How much faster than recorded to replay, e.g. 10
+    #[structopt(long = "replay-speed", default_value = "1", parse(try_from_str = parse_replay_speed))]
+    replay_speed: f64,
+    /// SQLite file to save the values of all the collected series to every tick.
+    /// Those of the last --history-window are charted again on startup
+    #[structopt(long = "history")]
+    history: Option<String>,
+    /// How far back values saved with --history are kept and charted again on startup, e.g. 1h or 30m
+    #[structopt(long = "history-window", default_value = "1h", parse(try_from_str = parse_duration))]
+    history_window: Duration,
+    /// How the error Panopticon exits with is printed to stderr: text, or json for scripts, with the kind of failure
+    /// and the exit code. See the README for the exit codes
+    #[structopt(long = "error-format", default_value = "text", parse(try_from_str = ErrorFormat::parse))]
+    error_format: ErrorFormat,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Doesn't draw the TUI, but watches an expression, exiting with an error once it held for the given duration,
+    /// e.g. for canary checks. Sources are set with the options before `watch`
+    #[structopt(name = "watch")]
+    Watch {
+        /// Comparison of a metric with another one or with a number, with metrics named like in alert rules,
+        /// e.g. "hikari/active >= hikari/total". Holds when it does for any of their series
+        #[structopt(long = "expr", parse(try_from_str = WatchExpr::parse))]
+        expr: WatchExpr,
+        /// How long the expression has to hold for, e.g. 60s or 5m
+        #[structopt(long = "for", default_value = "0s", parse(try_from_str = parse_duration))]
+        duration: Duration,
+        /// Exits successfully after watching for this long without the expression holding, e.g. 10m. Watches until it holds if not set
+        #[structopt(long = "timeout", parse(try_from_str = parse_duration))]
+        timeout: Option<Duration>,
+    },
+    /// Compares what two sessions recorded with --record showed at their end, e.g. of a healthy and a sick instance,
+    /// printing the fibers, actors and metrics that changed
+    #[structopt(name = "diff")]
+    Diff {
+        /// Recording to compare from
+        a: String,
+        /// Recording to compare to
+        b: String,
+    },
 }
 
 impl Cli {
+    /// Rewrites all the addresses to point to local ends of ssh tunnels.
+    fn tunnel_addresses(&mut self, tunnels: &mut TunnelManager) -> Result<(), String> {
+        // brokers advertise addresses of their own, that clients connect to once they got the cluster metadata
+        if !self.kafka_brokers.is_empty() {
+            return Err("Kafka brokers can't be reached through ssh, as clients connect to the addresses the brokers advertise".to_owned());
+        }
+        let addresses = [&mut self.zio_zmx, &mut self.actor_tree, &mut self.actor_count, &mut self.dead_letters, &mut self.mailbox_sizes, &mut self.kamon_prometheus, &mut self.prometheus, &mut self.prometheus_server, &mut self.actuator, &mut self.redis, &mut self.rabbitmq, &mut self.proxy_status, &mut self.jaeger, &mut self.zipkin, &mut self.cats_effect_dump_url];
+        for a in IntoIterator::into_iter(addresses).flatten().chain(self.akka_management.iter_mut()) {
+            *a = tunnels.tunnel_address(a)?;
+        }
+        if let Some(j) = self.jmx.as_mut() {
+            let rmi = !j.starts_with("http://") && !j.starts_with("https://") && !j.starts_with(port_forward::K8S_SCHEME);
+            *j = if rmi { tunnels.tunnel_rmi_address(j)? } else { tunnels.tunnel_address(j)? };
+        }
+        if let Some(p) = self.postgres.as_mut() {
+            if !p.starts_with("postgres://") && !p.starts_with("postgresql://") {
+                return Err("Only postgresql:// connection urls can be reached through ssh".to_owned());
+            }
+            *p = tunnels.tunnel_address(p)?;
+        }
+        // log files are read locally
+        if let Some(l) = self.log.as_mut().filter(|l| l.starts_with("http://") || l.starts_with("https://")) {
+            *l = tunnels.tunnel_address(l)?;
+        }
+        Ok(())
+    }
+
+    /// Rewrites k8s:// addresses to point to local ends of kubectl port-forwards.
+    fn forward_k8s_addresses(&mut self, forwards: &mut PortForwardManager) -> Result<(), String> {
+        for a in IntoIterator::into_iter([&mut self.zio_zmx, &mut self.redis]).flatten() {
+            *a = forwards.forward_address(a, false)?;
+        }
+        let urls = [&mut self.actor_tree, &mut self.actor_count, &mut self.dead_letters, &mut self.mailbox_sizes, &mut self.kamon_prometheus, &mut self.prometheus, &mut self.prometheus_server, &mut self.actuator, &mut self.rabbitmq, &mut self.proxy_status, &mut self.jaeger, &mut self.zipkin, &mut self.cats_effect_dump_url, &mut self.log];
+        for a in IntoIterator::into_iter(urls).flatten().chain(self.akka_management.iter_mut()) {
+            *a = forwards.forward_address(a, true)?;
+        }
+        // jolokia endpoints have a path, RMI ones don't
+        if let Some(j) = self.jmx.as_mut() {
+            *j = forwards.forward_address(j, port_forward::has_path(j))?;
+        }
+        Ok(())
+    }
+
     fn jmx_settings(&self) -> Option<JMXConnectionSettings> {
-        match (&self.jmx, &self.db_pool_name) {
-            (Some(addr), Some(db_pool)) => Some(JMXConnectionSettings {
-                address: addr.clone(),
-                username: self.jmx_username.clone(),
-                password: self.jmx_password.clone(),
-                db_pool_name: db_pool.clone(),
-            }),
-            _ => None
+        self.jmx.as_ref().map(|addr| JMXConnectionSettings {
+            address: addr.clone(),
+            username: self.jmx_username.clone(),
+            password: self.jmx_password.clone(),
+            db_pool_name: self.db_pool_name.clone(),
+            kafka: self.kafka,
+            cassandra_domain: self.cassandra_metrics_domain.clone(),
+            akka_dispatchers: self.akka_dispatchers.clone(),
+            loggers: self.loggers,
+            thread_pools: self.thread_pools.clone(),
+            jvm_info: self.jvm_info,
+            akka_cluster: self.akka_cluster,
+            heap_dump_dir: self.heap_dump_dir.clone(),
+            gc_pause_threshold: self.gc_pause_threshold,
+            ssl: self.jmx_ssl_settings(),
+            notifications: self.jmx_notifications.clone(),
+            allow_operations: self.allow_jmx_operations,
+            hikari_alerts: HikariAlertSettings {
+                exhausted_ticks: self.hikari_alert_ticks,
+                waiting_threshold: self.hikari_alert_waiting,
+            },
+        })
+    }
+
+    fn jmx_ssl_settings(&self) -> Option<JMXSslSettings> {
+        if self.jmx_ssl {
+            Some(JMXSslSettings {
+                truststore: self.jmx_truststore.clone(),
+                truststore_password: self.jmx_truststore_password.clone(),
+                keystore: self.jmx_keystore.clone(),
+                keystore_password: self.jmx_keystore_password.clone(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Time polled sources have to answer in, so that a slow one doesn't answer after the next tick
+    fn request_timeout(&self) -> Duration {
+        Duration::from_millis(self.tick_rate).mul_f64(0.8)
+    }
+
+    fn actor_http_settings(&self) -> HttpSettings {
+        let auth = match (&self.actor_http_username, &self.actor_http_token) {
+            (Some(username), _) => Some(HttpAuth::Basic { username: username.clone(), password: self.actor_http_password.clone() }),
+            (None, Some(token)) => Some(HttpAuth::Bearer(token.clone())),
+            (None, None) => None,
+        };
+        HttpSettings {
+            headers: self.actor_http_headers.clone(),
+            auth,
+            skip_tls_verify: self.insecure_skip_tls_verify,
+        }
+    }
+
+    fn sharding_settings(&self) -> Option<ShardingSettings> {
+        if self.akka_management.is_empty() || self.sharding_regions.is_empty() {
+            None
+        } else {
+            Some(ShardingSettings {
+                nodes: self.akka_management.clone(),
+                regions: self.sharding_regions.clone(),
+                timeout: self.request_timeout().as_millis() as u64,
+                http: self.actor_http_settings(),
+            })
+        }
+    }
+
+    fn jfr_settings(&self) -> Option<JfrSettings> {
+        self.jfr.as_ref().map(|repository| JfrSettings {
+            repository: repository.to_owned(),
+            tool: self.jfr_tool.clone(),
+        })
+    }
+
+    fn prometheus_settings(&self) -> Option<PrometheusSettings> {
+        self.prometheus.as_ref().map(|address| PrometheusSettings {
+            address: address.to_owned(),
+            selectors: self.prometheus_metrics.clone(),
+            timeout: self.request_timeout().as_millis() as u64,
+        })
+    }
+
+    fn promql_settings(&self) -> Option<PromQLSettings> {
+        self.prometheus_server.as_ref().map(|address| PromQLSettings {
+            address: address.to_owned(),
+            queries: self.promql.clone(),
+            timeout: self.request_timeout().as_millis() as u64,
+        })
+    }
+
+    fn actuator_settings(&self) -> Option<ActuatorSettings> {
+        self.actuator.as_ref().map(|address| ActuatorSettings {
+            address: address.to_owned(),
+            metrics: self.actuator_metrics.clone(),
+            timeout: self.request_timeout().as_millis() as u64,
+        })
+    }
+
+    fn statsd_settings(&self) -> Option<StatsdSettings> {
+        self.statsd.as_ref().map(|address| StatsdSettings {
+            address: address.to_owned(),
+            metrics: self.statsd_metrics.clone(),
+        })
+    }
+
+    fn log_settings(&self) -> Option<LogSettings> {
+        self.log.as_ref().map(|source| LogSettings { source: source.to_owned() })
+    }
+
+    fn postgres_settings(&self) -> Option<PostgresSettings> {
+        self.postgres.as_ref().map(|connection| PostgresSettings {
+            connection: connection.to_owned(),
+            timeout: self.request_timeout().as_millis() as u64,
+        })
+    }
+
+    fn kafka_lag_settings(&self) -> Option<KafkaLagSettings> {
+        if self.kafka_brokers.is_empty() || self.kafka_groups.is_empty() {
+            None
+        } else {
+            Some(KafkaLagSettings {
+                brokers: self.kafka_brokers.clone(),
+                groups: self.kafka_groups.clone(),
+                timeout: self.request_timeout().as_millis() as u64,
+            })
+        }
+    }
+
+    fn redis_settings(&self) -> Option<RedisSettings> {
+        self.redis.as_ref().map(|address| RedisSettings {
+            address: address.to_owned(),
+            password: self.redis_password.clone(),
+            timeout: self.request_timeout().as_millis() as u64,
+        })
+    }
+
+    fn docker_settings(&self) -> Option<DockerSettings> {
+        self.docker_container.as_ref().map(|container| DockerSettings {
+            socket: self.docker_socket.clone(),
+            container: container.to_owned(),
+            timeout: self.request_timeout().as_millis() as u64,
+        })
+    }
+
+    fn k8s_pod_settings(&self) -> Option<K8sPodSettings> {
+        self.k8s_pod.as_ref().map(|pod| K8sPodSettings {
+            pod: pod.clone(),
+            tool: self.kubectl_tool.clone(),
+            timeout: self.request_timeout().as_millis() as u64,
+        })
+    }
+
+    fn rabbitmq_settings(&self) -> Option<RabbitMqSettings> {
+        self.rabbitmq.as_ref().map(|address| RabbitMqSettings {
+            address: address.to_owned(),
+            queues: self.rabbitmq_queues.clone(),
+            username: self.rabbitmq_username.clone(),
+            password: self.rabbitmq_password.clone(),
+            timeout: self.request_timeout().as_millis() as u64,
+        })
+    }
+
+    fn proxy_settings(&self) -> Option<ProxySettings> {
+        self.proxy_status.as_ref().map(|address| ProxySettings {
+            address: address.to_owned(),
+            timeout: self.request_timeout().as_millis() as u64,
+        })
+    }
+
+    fn traces_settings(&self) -> Option<TracesSettings> {
+        let (address, backend) = match (&self.jaeger, &self.zipkin) {
+            (Some(a), _) => (a, TraceBackend::Jaeger),
+            (None, Some(a)) => (a, TraceBackend::Zipkin),
+            (None, None) => return None,
+        };
+        self.trace_service.as_ref().map(|service| TracesSettings {
+            address: address.to_owned(),
+            backend,
+            service: service.to_owned(),
+            limit: self.trace_limit,
+            timeout: self.request_timeout().as_millis() as u64,
+        })
+    }
+
+    fn otlp_settings(&self) -> Option<OtlpSettings> {
+        self.otlp.as_ref().map(|address| OtlpSettings {
+            address: address.to_owned(),
+            metrics: self.otlp_metrics.clone(),
+        })
+    }
+
+    /// Settings of all the sources the options and config ask for
+    fn sources(&self, config: &Config) -> Sources {
+        Sources {
+            zio_zmx: self.zio_zmx.clone(),
+            jmx: self.jmx_settings(),
+            akka: self.akka_settings(),
+            sharding: self.sharding_settings(),
+            jfr: self.jfr_settings(),
+            prometheus: self.prometheus_settings(),
+            promql: self.promql_settings(),
+            actuator: self.actuator_settings(),
+            statsd: self.statsd_settings(),
+            logs: self.log_settings(),
+            postgres: self.postgres_settings(),
+            kafka_lag: self.kafka_lag_settings(),
+            redis: self.redis_settings(),
+            docker: self.docker_settings(),
+            k8s_pod: self.k8s_pod_settings(),
+            rabbitmq: self.rabbitmq_settings(),
+            proxy: self.proxy_settings(),
+            traces: self.traces_settings(),
+            otlp: self.otlp_settings(),
+            cats_effect: self.cats_effect_settings(),
+            json_metrics: config.json_metrics.clone(),
+            commands: config.commands.clone(),
+            sql_queries: config.sql_queries.clone(),
         }
     }
 
+    fn cats_effect_settings(&self) -> Option<CatsEffectSettings> {
+        let source = match (&self.cats_effect_dump_command, &self.cats_effect_dump_url) {
+            (Some(c), _) => CatsEffectDumpSource::Command(c.to_owned()),
+            (None, Some(u)) => CatsEffectDumpSource::Url(u.to_owned()),
+            (None, None) => return None,
+        };
+        Some(CatsEffectSettings { source, timeout: self.request_timeout().as_millis() as u64 })
+    }
+
     fn akka_settings(&self) -> Option<AkkaSettings> {
         match (&self.actor_tree, &self.actor_count) {
             (Some(tree_addr), Some(count_addr)) => Some(AkkaSettings {
                 tree_address: tree_addr.to_owned(),
                 tree_timeout: self.actor_tree_timeout,
                 count_address: count_addr.to_owned(),
-                count_timeout: (self.tick_rate as f64 * 0.8) as u64,
+                count_timeout: self.request_timeout().as_millis() as u64,
+                dead_letters_address: self.dead_letters.clone(),
+                mailbox_address: self.mailbox_sizes.clone(),
+                kamon_address: self.kamon_prometheus.clone(),
+                http: self.actor_http_settings(),
+                flavor: if self.pekko { ActorSystemFlavor::Pekko } else { ActorSystemFlavor::Akka },
+                excluded_actors: if self.exclude_actors.is_empty() {
+                    DEFAULT_EXCLUDED_ACTORS.iter().map(|p| p.to_string()).collect()
+                } else {
+                    self.exclude_actors.clone()
+                },
+                watched_actors: self.watch_actors.clone(),
+                tree_depth: self.actor_tree_depth,
+                count_alerts: ActorCountAlertSettings {
+                    growth_ticks: self.actor_count_alert_ticks,
+                    limit: self.actor_count_limit,
+                },
             }),
             _ => None
         }
     }
 }
 
-fn main() -> Result<(), failure::Error> {
+fn parse_http_header(s: &str) -> Result<(String, String), String> {
+    match s.split_once(':') {
+        Some((name, value)) if !name.trim().is_empty() => Ok((name.trim().to_owned(), value.trim().to_owned())),
+        _ => Err(format!("Expected a header as `Name: value`, got {}", s)),
+    }
+}
+
+fn parse_replay_speed(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(speed) if speed > 0.0 => Ok(speed),
+        _ => Err(format!("Expected a speed above 0, got {}", s)),
+    }
+}
+
+/// App of the sources the options and config ask for, as long as one of them has a tab to show
+fn new_app(cli: &Cli, config: &Config) -> Result<App<'static>, failure::Error> {
+    let app = App::new("PANOPTICON-TUI", &cli.sources(config), config);
+    if app.tabs.tabs.iter().all(|t| matches!(t.kind, TabKind::Errors)) {
+        return Err(failure::err_msg("None of the given options has a tab to show, e.g. --akka-dispatchers charts in the Akka tab, which needs --actor-tree"));
+    }
+    Ok(app)
+}
+
+/// What a recorded session showed at its end, with its responses handled as they would have been
+fn load_snapshot(path: &str) -> Result<Snapshot, failure::Error> {
+    let recording = Recording::open(path).map_err(failure::err_msg)?;
+    let cli = Cli::from_iter_safe(iter::once("panopticon-tui".to_owned()).chain(recording.args.clone()))?;
+    let config = match &recording.config {
+        Some(text) => Config::parse(text, path).map_err(failure::err_msg)?,
+        None => Config::default(),
+    };
+    let mut app = new_app(&cli, &config)?;
+    let mut fibers = None;
+    recording.play(f64::INFINITY, |r| {
+        if let FetcherResponse::FiberDump(Ok(f)) | FetcherResponse::RegularFiberDump(Ok(f)) = &r {
+            fibers = Some(f.clone());
+        }
+        app.on_fetcher_response(&r);
+        on_fetcher_response(&mut app, r);
+        Ok(())
+    }).map_err(failure::err_msg)?;
+    Ok(Snapshot::of(path, &app, fibers))
+}
+
+fn main() {
     let cli = Cli::from_args();
+    let error_format = cli.error_format;
+    if let Err(e) = run(cli) {
+        process::exit(exit::report(&e, error_format));
+    }
+}
 
+fn run(mut cli: Cli) -> Result<(), failure::Error> {
     // disable jmx crate logging
     env::set_var("J4RS_CONSOLE_LOG_LEVEL", "disabled");
 
-    if cli.zio_zmx.is_none() && cli.jmx_settings().is_none() && cli.akka_settings().is_none() {
-        let mut clap = Cli::clap();
-        println!("Nothing to monitor. Please check the following help message.\n");
-        clap.print_long_help().expect("Failed printing help message");
+    if let Some(Command::Diff { a, b }) = &cli.command {
+        print!("{}", diff::report(&load_snapshot(a)?, &load_snapshot(b)?));
         return Ok(());
     }
 
-    let tick_rate = Duration::from_millis(cli.tick_rate);
-    let has_jmx = cli.jmx_settings().is_some();
+    // replays show the sources of the recorded session, the way the current command line asks for
+    let replay = match cli.replay.clone() {
+        Some(path) => {
+            let recording = Recording::open(&path).map_err(failure::err_msg)?;
+            let recorded = Cli::from_iter_safe(iter::once("panopticon-tui".to_owned()).chain(recording.args.clone()))?;
+            cli = Cli {
+                headless: cli.headless,
+                ascii: cli.ascii,
+                high_contrast: cli.high_contrast,
+                serve_metrics: cli.serve_metrics.take(),
+                serve_api: cli.serve_api.take(),
+                // replayed values aren't written anywhere, where they'd pass for live ones
+                influx_url: None,
+                influx_token: None,
+                forward_statsd: None,
+                history: None,
+                replay: cli.replay.take(),
+                replay_speed: cli.replay_speed,
+                command: cli.command.take(),
+                // the layout isn't saved to the recorded config file
+                config: None,
+                ..recorded
+            };
+            Some(recording)
+        }
+        None => None,
+    };
 
-    enable_raw_mode()?;
+    let mut config = match (&replay, &cli.config) {
+        (Some(Recording { config: Some(text), .. }), _) => Config::parse(text, "of the recording").map_err(failure::err_msg)?,
+        (None, Some(path)) => Config::load(path).map_err(failure::err_msg)?,
+        _ => Config::default(),
+    };
+    let mut recorded_args = recording::recorded_args(env::args().skip(1));
 
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    if replay.is_none() && cli.sources(&config).is_empty() {
+        // without options, a local JVM is monitored over JMX
+        match discovery::pick_local_jvm(io::stdin().is_tty()).map_err(failure::err_msg)? {
+            Some(address) => {
+                recorded_args.extend(vec!["--jmx".to_owned(), address.clone()]);
+                cli.jmx = Some(address)
+            }
+            None => {
+                let mut clap = Cli::clap();
+                println!("Nothing to monitor and no local JVMs found. Please check the following help message.\n");
+                clap.print_long_help().expect("Failed printing help message");
+                return Ok(());
+            }
+        }
+    }
 
-    let backend = CrosstermBackend::new(stdout);
+    // tunnels live until the end of main, dropping the manager closes them
+    let _tunnels = match cli.ssh.clone().filter(|_| replay.is_none()) {
+        Some(destination) => {
+            let mut tunnels = TunnelManager::new(destination);
+            cli.tunnel_addresses(&mut tunnels).map_err(failure::err_msg)?;
+            for m in config.json_metrics.iter_mut() {
+                m.url = tunnels.tunnel_address(&m.url).map_err(failure::err_msg)?;
+            }
+            Some(tunnels)
+        }
+        None => None,
+    };
 
-    let mut terminal = Terminal::new(backend)?;
-    terminal.hide_cursor()?;
+    // port-forwards are established after ssh tunnels, which leave k8s:// addresses alone
+    let mut forwards = PortForwardManager::new(cli.kubectl_tool.clone());
+    if replay.is_none() {
+        cli.forward_k8s_addresses(&mut forwards).map_err(failure::err_msg)?;
+        for m in config.json_metrics.iter_mut() {
+            m.url = forwards.forward_address(&m.url, true).map_err(failure::err_msg)?;
+        }
+    }
 
-    let mut app = App::new(
-        "PANOPTICON-TUI",
-        cli.zio_zmx.clone(),
-        cli.jmx_settings(),
-        cli.akka_settings(),
-    );
+    let recorder = match &cli.record {
+        Some(path) => {
+            let config_text = cli.config.as_ref().map(fs::read_to_string).transpose()?.and_then(|t| recording::recorded_config(&t));
+            Some(Arc::new(Recorder::create(path, recorded_args, config_text).map_err(failure::err_msg)?))
+        }
+        None => None,
+    };
 
-    terminal.clear()?;
+    let tick_rate = Duration::from_millis(cli.tick_rate).div_f64(cli.replay_speed);
+    let has_slick = cli.jmx_settings().is_some_and(|j| j.has_slick());
+    let has_db_pool_name = cli.db_pool_name.is_some();
+    let has_loggers = cli.jmx_settings().is_some_and(|j| j.loggers);
+    let has_jvm_info = cli.jmx_settings().is_some_and(|j| j.jvm_info);
+
+    let mut app = new_app(&cli, &config)?;
+    app.ascii = cli.ascii;
+    if let Some(z) = app.zmx.as_mut() {
+        z.count_ticks = cli.fiber_count_ticks;
+    }
+    app.high_contrast = cli.high_contrast;
+    app.metrics_exporter = cli.serve_metrics.as_deref().map(MetricsExporter::bind).transpose().map_err(failure::err_msg)?;
+    app.influx = cli.influx_url.clone().map(|url| InfluxSink::new(url, cli.influx_token.clone()));
+    app.statsd_forwarder = cli.forward_statsd.as_deref()
+        .map(|address| StatsdForwarder::new(address, &cli.forward_statsd_prefix, cli.forward_statsd_tags))
+        .transpose()
+        .map_err(failure::err_msg)?;
+    if cli.serve_api.is_some() {
+        app.grafana_series = Some(SeriesStore::new(chrono::Duration::from_std(cli.history_window)?));
+    }
+    if let Some(path) = &cli.history {
+        let history = History::open(path).map_err(failure::err_msg)?;
+        let since = Local::now() - chrono::Duration::from_std(cli.history_window)?;
+        history.prune(since).map_err(failure::err_msg)?;
+        app.restore_history(history.load(since).map_err(failure::err_msg)?);
+        app.history = Some(history);
+    }
+    // saved on exit if changed
+    let (config_path, layout) = (cli.config.clone(), app.splits.clone());
+    let headless = cli.headless;
+    let command = cli.command.take();
 
     // channel for main app event loop
     let (tx, rx) = mpsc::channel();
+    let queued = Arc::new(AtomicUsize::new(0));
+    let tx = EventSender { tx, queued: queued.clone(), recorder };
+
+    if let Some(address) = &cli.serve_api {
+        let tx = tx.clone();
+        api::serve(address, move |call| tx.send(Event::Api(call))).map_err(failure::err_msg)?;
+    }
 
     // Setup fetcher interaction
     let (txf, rxf) = mpsc::channel();
-    {
+    if let Some(recording) = replay {
+        let tx = tx.clone();
+        let speed = cli.replay_speed;
+        // nothing is read from the sources, what's asked for is dropped
+        thread::spawn(move || while rxf.recv().is_ok() {});
+        thread::spawn(move || {
+            let finished = recording.play(speed, |r| tx.send(Event::FetcherResponse(r)));
+            let _ = tx.send(Event::ReplayFinished(finished));
+        });
+    } else {
         let tx = tx.clone();
         thread::spawn(move || {
             let respond = |r| tx.send(Event::FetcherResponse(r)).unwrap();
 
-            match Fetcher::new(cli.zio_zmx.clone(),
-                               cli.jmx_settings(),
-                               cli.akka_settings()) {
+            let fetcher = Fetcher::new(cli.sources(&config));
+            // the actor tree is fetched apart, on a runtime that lets fetches no longer wanted be aborted
+            let setup = tokio::runtime::Builder::new().threaded_scheduler().core_threads(1).enable_all().build()
+                .map(|runtime| (fetcher, runtime))
+                .map_err(|e| FetchError::from(format!("Couldn't start the actor tree fetches: {}", e)));
+            match setup {
                 Err(e) => {
-                    eprintln!("Responding with failure {}", e);
-                    loop {
-                        rxf.recv().unwrap();
+                    // until the main loop is gone, which reports it once the terminal is restored
+                    while rxf.recv().is_ok() {
                         respond(FetcherResponse::FatalFailure(e.to_owned()))
                     }
                 }
-                Ok(fetcher) =>
-                    loop {
-                        match rxf.recv().unwrap() {
+                Ok((mut fetcher, runtime)) => {
+                    let mut tree_fetch: Option<(u64, AbortHandle)> = None;
+                    while let Ok(request) = rxf.recv() {
+                        let started = Instant::now();
+                        let response = match request {
                             FetcherRequest::FiberDump =>
-                                respond(FetcherResponse::FiberDump(fetcher.dump_fibers())),
-                            FetcherRequest::RegularFiberDump =>
-                                respond(FetcherResponse::RegularFiberDump(fetcher.dump_fibers())),
+                                FetcherResponse::FiberDump(fetcher.dump_fibers()),
+                            FetcherRequest::FiberCount =>
+                                FetcherResponse::FiberCount(fetcher.count_fibers()),
                             FetcherRequest::HikariMetrics =>
-                                respond(FetcherResponse::HikariMetrics(fetcher.get_hikari_metrics())),
+                                FetcherResponse::HikariMetrics(fetcher.get_hikari_metrics()),
                             FetcherRequest::SlickMetrics =>
-                                respond(FetcherResponse::SlickMetrics(fetcher.get_slick_metrics())),
+                                FetcherResponse::SlickMetrics(fetcher.get_slick_metrics()),
                             FetcherRequest::SlickConfig =>
-                                respond(FetcherResponse::SlickConfig(fetcher.get_slick_config())),
-                            FetcherRequest::ActorTree =>
-                                respond(FetcherResponse::ActorTree(fetcher.get_actor_tree())),
+                                FetcherResponse::SlickConfig(fetcher.get_slick_config()),
+                            FetcherRequest::JMXNotifications =>
+                                FetcherResponse::JMXNotifications(fetcher.get_jmx_notifications()),
+                            FetcherRequest::HikariOperation(op) =>
+                                FetcherResponse::HikariOperation(op, fetcher.invoke_hikari_operation(op)),
+                            FetcherRequest::DbPools =>
+                                FetcherResponse::DbPools(fetcher.discover_db_pools()),
+                            FetcherRequest::SelectDbPool(name) => {
+                                fetcher.select_db_pool(name.clone());
+                                FetcherResponse::DbPoolSelected(name)
+                            }
+                            FetcherRequest::KafkaMetrics =>
+                                FetcherResponse::KafkaMetrics(fetcher.get_kafka_metrics()),
+                            FetcherRequest::CassandraMetrics =>
+                                FetcherResponse::CassandraMetrics(fetcher.get_cassandra_metrics()),
+                            FetcherRequest::DispatcherMetrics =>
+                                FetcherResponse::DispatcherMetrics(fetcher.get_dispatcher_metrics()),
+                            FetcherRequest::Loggers =>
+                                FetcherResponse::Loggers(fetcher.get_loggers()),
+                            FetcherRequest::SetLoggerLevel(logger, level) => {
+                                let result = fetcher.set_logger_level(&logger, &level);
+                                FetcherResponse::SetLoggerLevel(logger, level, result)
+                            }
+                            FetcherRequest::ThreadPoolMetrics =>
+                                FetcherResponse::ThreadPoolMetrics(fetcher.get_thread_pool_metrics()),
+                            FetcherRequest::JvmInfo =>
+                                FetcherResponse::JvmInfo(fetcher.get_jvm_info()),
+                            FetcherRequest::JvmOperation(op) => {
+                                let result = fetcher.invoke_jvm_operation(&op);
+                                FetcherResponse::JvmOperation(op, result)
+                            }
+                            FetcherRequest::GcEvents =>
+                                FetcherResponse::GcEvents(fetcher.get_gc_events()),
+                            FetcherRequest::JfrMetrics =>
+                                FetcherResponse::JfrMetrics(fetcher.get_jfr_metrics()),
+                            FetcherRequest::ActorTree(id) => {
+                                let (fetch, handle) = abortable(fetcher.actor_tree_fetch(None));
+                                // a newer fetch supersedes the one still running
+                                if let Some((_, superseded)) = tree_fetch.replace((id, handle)) {
+                                    superseded.abort();
+                                }
+                                let tx = tx.clone();
+                                runtime.spawn(async move {
+                                    if let Ok(tree) = fetch.await {
+                                        let _ = tx.send(Event::FetcherResponse(FetcherResponse::ActorTree(id, tree)));
+                                    }
+                                });
+                                continue;
+                            }
+                            FetcherRequest::CancelActorTree(id) => {
+                                if let Some((fetching, handle)) = &tree_fetch {
+                                    if *fetching == id {
+                                        handle.abort();
+                                    }
+                                }
+                                continue;
+                            }
+                            FetcherRequest::ActorSubtree(path) => {
+                                let fetch = fetcher.actor_tree_fetch(Some(path.clone()));
+                                let tx = tx.clone();
+                                runtime.spawn(async move {
+                                    let _ = tx.send(Event::FetcherResponse(FetcherResponse::ActorSubtree(path, fetch.await)));
+                                });
+                                continue;
+                            }
                             FetcherRequest::ActorCount =>
-                                respond(FetcherResponse::ActorCount(fetcher.get_actor_count())),
+                                FetcherResponse::ActorCount(fetcher.get_actor_count()),
+                            FetcherRequest::DeadLetters =>
+                                FetcherResponse::DeadLetters(fetcher.get_dead_letters()),
+                            FetcherRequest::ActorStats =>
+                                FetcherResponse::ActorStats(fetcher.get_actor_stats()),
+                            FetcherRequest::KamonMetrics =>
+                                FetcherResponse::KamonMetrics(fetcher.get_kamon_metrics()),
+                            FetcherRequest::ShardingStats =>
+                                FetcherResponse::ShardingStats(fetcher.get_sharding_stats()),
+                            FetcherRequest::ClusterStatus =>
+                                FetcherResponse::ClusterStatus(fetcher.get_cluster_status()),
+                            FetcherRequest::PrometheusMetrics =>
+                                FetcherResponse::PrometheusMetrics(fetcher.get_prometheus_metrics()),
+                            FetcherRequest::PromQLMetrics =>
+                                FetcherResponse::PromQLMetrics(fetcher.get_promql_metrics()),
+                            FetcherRequest::ActuatorHealth =>
+                                FetcherResponse::ActuatorHealth(fetcher.get_actuator_health()),
+                            FetcherRequest::ActuatorMetrics =>
+                                FetcherResponse::ActuatorMetrics(fetcher.get_actuator_metrics()),
+                            FetcherRequest::ActuatorThreadDump =>
+                                FetcherResponse::ActuatorThreadDump(fetcher.get_actuator_thread_dump()),
+                            FetcherRequest::JsonMetric(i) =>
+                                FetcherResponse::JsonMetric(i, fetcher.get_json_metric(i)),
+                            FetcherRequest::CommandValues(i) =>
+                                FetcherResponse::CommandValues(i, fetcher.get_command_values(i)),
+                            FetcherRequest::SqlValues(i) =>
+                                FetcherResponse::SqlValues(i, fetcher.get_sql_values(i)),
+                            FetcherRequest::StatsdMetrics =>
+                                FetcherResponse::StatsdMetrics(fetcher.get_statsd_metrics().unwrap_or_else(StatsdMetrics::failed)),
+                            FetcherRequest::LogLines =>
+                                FetcherResponse::LogLines(fetcher.get_log_lines().unwrap_or_else(LogLines::failed)),
+                            FetcherRequest::PostgresActivity =>
+                                FetcherResponse::PostgresActivity(fetcher.get_postgres_activity()),
+                            FetcherRequest::KafkaLag =>
+                                FetcherResponse::KafkaLag(fetcher.get_kafka_lag()),
+                            FetcherRequest::RedisInfo =>
+                                FetcherResponse::RedisInfo(fetcher.get_redis_info()),
+                            FetcherRequest::DockerStats =>
+                                FetcherResponse::DockerStats(fetcher.get_docker_stats()),
+                            FetcherRequest::PodMetrics =>
+                                FetcherResponse::PodMetrics(fetcher.get_pod_metrics()),
+                            FetcherRequest::RabbitMqStats =>
+                                FetcherResponse::RabbitMqStats(fetcher.get_rabbitmq_stats()),
+                            FetcherRequest::ProxyStatus =>
+                                FetcherResponse::ProxyStatus(fetcher.get_proxy_status()),
+                            FetcherRequest::RecentTraces =>
+                                FetcherResponse::RecentTraces(fetcher.get_recent_traces()),
+                            FetcherRequest::OtlpMetrics =>
+                                FetcherResponse::OtlpMetrics(fetcher.get_otlp_metrics().unwrap_or_else(OtlpMetrics::failed)),
+                        };
+                        // connection status goes first, so that errors caused by a lost connection aren't fatal
+                        if let Some(status) = fetcher.take_jmx_status_change() {
+                            respond(FetcherResponse::JMXConnection(status))
+                        }
+                        if let Some((source, _)) = response.outcome() {
+                            tx.send(Event::FetchTime(source, started.elapsed())).unwrap();
                         }
+                        respond(response)
                     }
+                }
             }
         });
     }
 
+    if has_slick && has_db_pool_name {
+        txf.send(FetcherRequest::SlickConfig)?;
+        txf.send(FetcherRequest::HikariMetrics)?;
+        txf.send(FetcherRequest::SlickMetrics)?;
+    } else if has_slick {
+        txf.send(FetcherRequest::DbPools)?;
+    }
+
+    if has_loggers {
+        txf.send(FetcherRequest::Loggers)?;
+    }
+
+    if has_jvm_info {
+        txf.send(FetcherRequest::JvmInfo)?;
+    }
+
+    if headless || command.is_some() {
+        {
+            let tx = tx.clone();
+            thread::spawn(move || loop {
+                thread::sleep(tick_rate);
+                tx.send(Event::Tick).unwrap();
+            });
+        }
+        return match command {
+            Some(Command::Watch { expr, duration, timeout }) => {
+                let held_for = if duration.as_secs() > 0 { format!(" for {}s", duration.as_secs()) } else { String::new() };
+                let mut watch = Watch::new(expr, duration);
+                let started = Instant::now();
+                run_headless(app, &tx, rx, &txf, |app| match watch.check(&app.metric_values(), Local::now()) {
+                    Some(held) => Some(Err(Exit::new(ExitKind::WatchHeld, format!("{} held{}: {}", watch.expr, held_for, held)).into())),
+                    None if timeout.is_some_and(|t| started.elapsed() >= t) => {
+                        println!("{} didn't hold{} in {}s", watch.expr, held_for, started.elapsed().as_secs());
+                        Some(Ok(()))
+                    }
+                    None => None,
+                })
+            }
+            Some(Command::Diff { .. }) => unreachable!("diffs are reported before sources are read"),
+            None => run_headless(app, &tx, rx, &txf, |app| {
+                print_metric_values(app);
+                None
+            }),
+        };
+    }
+
+    enable_raw_mode()?;
+
+    let mut stdout = stdout();
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+
+    let backend = CrosstermBackend::new(stdout);
+
+    let mut terminal = Terminal::new(backend)?;
+    terminal.hide_cursor()?;
+    terminal.clear()?;
+
     // Setup input handling
     {
         let tx = tx.clone();
-        let txf = txf.clone();
         thread::spawn(move || {
             let mut last_tick = Instant::now();
 
-            if has_jmx {
-                txf.send(FetcherRequest::SlickConfig).unwrap();
-                txf.send(FetcherRequest::HikariMetrics).unwrap();
-                txf.send(FetcherRequest::SlickMetrics).unwrap();
-            }
-
             loop {
                 // poll for tick rate duration, if no events, sent tick event.
-                if event::poll(tick_rate - last_tick.elapsed()).unwrap() {
-                    if let CEvent::Key(key) = event::read().unwrap() {
-                        tx.send(Event::Input(key)).unwrap();
+                if event::poll(tick_rate.saturating_sub(last_tick.elapsed())).unwrap() {
+                    match event::read().unwrap() {
+                        CEvent::Key(key) => tx.send(Event::Input(key)).unwrap(),
+                        CEvent::Mouse(mouse) => tx.send(Event::Mouse(mouse)).unwrap(),
+                        CEvent::Resize(_, _) => tx.send(Event::Resize).unwrap(),
                     }
                 }
                 if last_tick.elapsed() >= tick_rate {
@@ -208,101 +1299,607 @@ fn main() -> Result<(), failure::Error> {
         });
     }
 
+    app.tick_rate = Some(tick_rate);
+    // frames are only drawn once something changed, and once for a burst of events
+    let mut redraw = Redraw::All;
+    let mut last = LastFrame::default();
+    let mut last_frame = Instant::now();
     loop {
-        ui::draw(&mut terminal, &mut app)?;
-        match rx.recv()? {
+        if redraw > Redraw::Nothing && (queued.load(Ordering::Relaxed) == 0 || last_frame.elapsed() >= MAX_FRAME_DELAY) {
+            let started = Instant::now();
+            ui::redraw(&mut terminal, &mut app, &mut last, redraw)?;
+            app.debug.on_frame(started.elapsed());
+            redraw = Redraw::Nothing;
+            last_frame = Instant::now();
+        }
+        let event = rx.recv()?;
+        app.debug.queued_events = queued.fetch_sub(1, Ordering::Relaxed) - 1;
+        redraw = redraw.max(redraw_of(&event, &app));
+        if let Event::FetcherResponse(r) = &event {
+            redraw = redraw.max(app.on_fetcher_response(r));
+        }
+        match event {
+            // the dialog takes all the keys until answered
+            Event::Input(event) if app.confirmation.is_some() => match event.code {
+                KeyCode::Char(c) => app.on_key(c),
+                KeyCode::Esc => app.on_escape(),
+                _ => {}
+            },
             Event::Input(event) => match event.code {
-                KeyCode::Char('q') => {
+                KeyCode::Char('q') if !app.is_typing() => {
                     disable_raw_mode()?;
-                    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+                    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
                     terminal.show_cursor()?;
                     break;
                 }
                 KeyCode::Char(c) => app.on_key(c),
+                KeyCode::Left if event.modifiers.contains(KeyModifiers::SHIFT) => app.tabs.move_current(false),
+                KeyCode::Right if event.modifiers.contains(KeyModifiers::SHIFT) => app.tabs.move_current(true),
                 KeyCode::Left => app.on_left(),
                 KeyCode::Up => app.on_up(),
                 KeyCode::Right => app.on_right(),
                 KeyCode::Down => app.on_down(),
                 KeyCode::PageUp => app.on_page_up(),
                 KeyCode::PageDown => app.on_page_down(),
+                KeyCode::Esc => app.on_escape(),
+                KeyCode::F(12) => app.debug.toggle(),
+                KeyCode::Backspace => app.on_backspace(),
+                KeyCode::Enter if app.marker_label.is_some() => app.finish_marker(),
                 KeyCode::Enter => {
                     match app.tabs.current().kind {
-                        TabKind::ZMX => txf.send(FetcherRequest::FiberDump)?,
-                        TabKind::Slick => {}
-                        TabKind::AkkaActorTree => txf.send(FetcherRequest::ActorTree)?,
+                        TabKind::ZMX => app.request_fiber_dump(),
+                        TabKind::Slick => app.select_db_pool(),
+                        TabKind::Kafka => {}
+                        TabKind::Cassandra => {}
+                        TabKind::Loggers => txf.send(FetcherRequest::Loggers)?,
+                        TabKind::ThreadPools => {}
+                        TabKind::Jvm => txf.send(FetcherRequest::JvmInfo)?,
+                        TabKind::Jfr => {}
+                        TabKind::Sharding => {}
+                        TabKind::AkkaActorTree => app.reload_actor_tree(),
+                        TabKind::Cluster => {}
+                        TabKind::Prometheus => {}
+                        TabKind::PromQL => {}
+                        TabKind::Actuator => txf.send(FetcherRequest::ActuatorThreadDump)?,
+                        TabKind::JsonMetrics => {}
+                        TabKind::Commands => {}
+                        TabKind::SqlQueries => {}
+                        TabKind::Statsd => {}
+                        TabKind::Logs => app.logs.as_mut().unwrap().finish_search(),
+                        TabKind::Postgres => {}
+                        TabKind::KafkaLag => {}
+                        TabKind::Redis => {}
+                        TabKind::Docker => {}
+                        TabKind::K8sPod => {}
+                        TabKind::RabbitMq => {}
+                        TabKind::Proxy => {}
+                        TabKind::Traces => {}
+                        TabKind::Otlp => {}
+                        TabKind::Overview => {}
+                        TabKind::Dashboard(_) => {}
+                        TabKind::Alerts => {}
+                        TabKind::Errors => {}
                     }
                 }
                 _ => {}
             },
-            Event::FetcherResponse(r) => match r {
-                FetcherResponse::FatalFailure(e) =>
-                    app.quit(Some(e)),
-
-                FetcherResponse::FiberDump(d) =>
-                    match d {
-                        Err(e) => app.quit(Some(e)),
-                        Ok(x) => app.zmx.as_mut().unwrap().replace_fiber_dump(x),
-                    },
-                FetcherResponse::RegularFiberDump(d) =>
-                    match d {
-                        Err(e) => app.quit(Some(e)),
-                        Ok(x) => app.zmx.as_mut().unwrap().append_fiber_dump_for_counts(x),
-                    },
-                FetcherResponse::HikariMetrics(d) =>
-                    match d {
-                        Err(_) => app.slick.as_mut().unwrap().has_hikari = false,
-                        Ok(x) => {
-                            app.slick.as_mut().unwrap().has_hikari = true;
-                            app.slick.as_mut().unwrap().append_hikari_metrics(x)
-                        }
-                    },
-                FetcherResponse::SlickMetrics(d) =>
-                    match d {
-                        Err(e) => app.quit(Some(e)),
-                        Ok(x) => app.slick.as_mut().unwrap().append_slick_metrics(x)
-                    },
-                FetcherResponse::SlickConfig(d) =>
-                    match d {
-                        Err(e) => app.quit(Some(e)),
-                        Ok(x) => app.slick.as_mut().unwrap().replace_slick_config(x)
-                    },
-                FetcherResponse::ActorTree(d) =>
-                    match d {
-                        Err(e) => app.quit(Some(e)),
-                        Ok(x) => app.actor_tree.as_mut().unwrap().update_actor_tree(x)
-                    },
-                FetcherResponse::ActorCount(d) =>
-                    match d {
-                        Err(e) => app.quit(Some(e)),
-                        Ok(x) => app.actor_tree.as_mut().unwrap().append_actor_count(x)
-                    },
+            Event::Mouse(event) => match event {
+                MouseEvent::Down(MouseButton::Left, column, row, _) => app.on_click(column, row),
+                MouseEvent::ScrollUp(column, row, _) => app.on_scroll(column, row, true),
+                MouseEvent::ScrollDown(column, row, _) => app.on_scroll(column, row, false),
+                _ => {}
+            },
+            Event::FetcherResponse(r) => on_fetcher_response(&mut app, r),
+
+            Event::FetchTime(source, duration) => app.debug.on_fetch(source, duration),
+            // the next frame fits the new size
+            Event::Resize => {}
+            Event::ReplayFinished(result) => app.on_replay_finished(result),
+            Event::Api(call) => call.answer(&mut app),
+            Event::AlertWebhook(rule, result) => app.on_alert_webhook(&rule, result),
+            Event::Tick => {
+                if app.on_tick() {
+                    redraw = Redraw::All;
+                }
+                request_metrics(&mut app, &txf)?;
+            }
+        }
+        for request in app.take_fetcher_requests() {
+            txf.send(request)?;
+        }
+        post_alert_webhooks(&mut app, &tx);
+        if std::mem::take(&mut app.bell) {
+            if let Err(e) = notify::ring_bell(terminal.backend_mut()) {
+                app.errors.append_error("Alerts", &e);
             }
+        }
+        if app.report_requested {
+            app.report_requested = false;
+            let result = terminal.size()
+                .and_then(|size| ui::draw_report(&mut app, size))
+                .map_err(|e| format!("Export failed: {}", e))
+                .and_then(|r| r.write());
+            app.notify_export(result);
+        }
+        if app.should_quit {
+            break;
+        }
+    }
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
+    // reported like any other error once the terminal is restored, after a failing source
+    let saved = match config_path.filter(|_| app.splits != layout) {
+        Some(path) => Config::save_layout(&path, &app.splits),
+        None => Ok(()),
+    };
+    match app.exit_reason {
+        Some(e) => Err(Exit::source_failure(e).into()),
+        None => saved.map_err(failure::err_msg),
+    }
+}
 
+///
+/// Handles the events without drawing anything, calling `on_tick` with the updated app every tick until it returns a result.
+///
+/// Runs until then, until a source fails for good, which is returned as an error, or until the end of a replay.
+fn run_headless<I: Send + 'static>(mut app: App, tx: &EventSender<I>, rx: mpsc::Receiver<Event<I>>, txf: &mpsc::Sender<FetcherRequest>,
+                                   mut on_tick: impl FnMut(&App) -> Option<Result<(), failure::Error>>) -> Result<(), failure::Error> {
+    loop {
+        match rx.recv()? {
+            Event::FetcherResponse(r) => {
+                app.on_fetcher_response(&r);
+                on_fetcher_response(&mut app, r);
+            }
             Event::Tick => {
-                if app.zmx.is_some() {
-                    txf.send(FetcherRequest::RegularFiberDump)?;
+                app.on_tick();
+                if let Some(result) = on_tick(&app) {
+                    return result;
                 }
+                request_metrics(&mut app, txf)?;
+            }
+            // the last responses are handled too
+            Event::ReplayFinished(result) => {
+                app.on_tick();
+                return on_tick(&app).unwrap_or_else(|| result.map(|_| ()).map_err(failure::err_msg));
+            }
+            Event::Api(call) => call.answer(&mut app),
+            Event::AlertWebhook(rule, result) => app.on_alert_webhook(&rule, result),
+            _ => {}
+        }
+        for request in app.take_fetcher_requests() {
+            txf.send(request)?;
+        }
+        post_alert_webhooks(&mut app, tx);
+        // there's no terminal to ring without the TUI, only output
+        app.bell = false;
+        // failing sources are retried, a while before giving up on them
+        if let Some(e) = app.source_given_up(Instant::now()) {
+            return Err(Exit::source_failure(e.clone()).into());
+        }
+        if app.should_quit {
+            return match app.exit_reason {
+                Some(e) => Err(Exit::source_failure(e).into()),
+                None => Ok(()),
+            };
+        }
+    }
+}
 
-                match &app.slick {
-                    Some(s) => {
-                        txf.send(FetcherRequest::SlickMetrics)?;
-                        if s.has_hikari {
-                            txf.send(FetcherRequest::HikariMetrics)?;
-                        }
+/// Slow hooks don't hold back the event loop
+fn post_alert_webhooks<I: Send + 'static>(app: &mut App, tx: &EventSender<I>) {
+    for (url, event) in app.take_alert_webhooks() {
+        let tx = tx.clone();
+        thread::spawn(move || {
+            let result = alerts::webhook::post(&url, &event);
+            let _ = tx.send(Event::AlertWebhook(event.rule, result));
+        });
+    }
+}
+
+fn print_metric_values(app: &App) {
+    let line = serde_json::json!({"timestamp": Local::now().to_rfc3339(), "metrics": app.metric_values()});
+    println!("{}", line);
+}
+
+/// Updates the tabs with what a source returned
+fn on_fetcher_response(app: &mut App, r: FetcherResponse) {
+    match r {
+        FetcherResponse::FatalFailure(e) =>
+            app.quit(Some(e)),
+        FetcherResponse::JMXConnection(status) =>
+            app.on_jmx_status(status),
+
+        FetcherResponse::FiberDump(d) =>
+            match d {
+                // failures are kept with the status of their source
+                Err(_) => {}
+                Ok(x) => app.zmx.as_mut().unwrap().replace_fiber_dump(x),
+            },
+        FetcherResponse::RegularFiberDump(d) =>
+            match d {
+                // failures are kept with the status of their source
+                Err(_) => {}
+                Ok(x) => app.zmx.as_mut().unwrap().append_fiber_dump_for_counts(x),
+            },
+        FetcherResponse::FiberCount(c) =>
+            match c {
+                // failures are kept with the status of their source
+                Err(_) => {}
+                Ok(x) => app.zmx.as_mut().unwrap().append_fiber_count(x),
+            },
+        FetcherResponse::HikariMetrics(d) =>
+            match d {
+                // keep polling hikari after reconnection
+                Err(_) if app.jmx_reconnecting.is_some() => {}
+                Err(_) => app.slick.as_mut().unwrap().has_hikari = false,
+                Ok(x) => {
+                    app.slick.as_mut().unwrap().has_hikari = true;
+                    app.slick.as_mut().unwrap().append_hikari_metrics(x)
+                }
+            },
+        FetcherResponse::SlickMetrics(d) =>
+            match d {
+                Err(_) => {}
+                Ok(x) => app.slick.as_mut().unwrap().append_slick_metrics(x)
+            },
+        FetcherResponse::SlickConfig(d) =>
+            match d {
+                Err(_) => {}
+                Ok(x) => app.slick.as_mut().unwrap().replace_slick_config(x)
+            },
+        FetcherResponse::JMXNotifications(d) =>
+            match d {
+                Err(_) => {}
+                Ok(x) => app.slick.as_mut().unwrap().append_notifications(x)
+            },
+        FetcherResponse::HikariOperation(op, r) =>
+            app.slick.as_mut().unwrap().on_operation_result(op, r.map_err(|e| e.message)),
+        FetcherResponse::DbPools(d) =>
+            match d {
+                Err(_) => {}
+                Ok(x) => app.slick.as_mut().unwrap().replace_discovered_pools(x)
+            },
+        FetcherResponse::DbPoolSelected(name) =>
+            app.slick.as_mut().unwrap().on_pool_selected(name),
+        FetcherResponse::KafkaMetrics(d) =>
+            match d {
+                Err(_) => {}
+                Ok(x) => app.kafka.as_mut().unwrap().append_kafka_metrics(x)
+            },
+        FetcherResponse::CassandraMetrics(d) =>
+            match d {
+                Err(_) => {}
+                Ok(x) => app.cassandra.as_mut().unwrap().append_cassandra_metrics(x)
+            },
+        FetcherResponse::DispatcherMetrics(d) =>
+            match d {
+                Err(_) => {}
+                Ok(x) => app.actor_tree.as_mut().unwrap().append_dispatcher_metrics(x)
+            },
+        FetcherResponse::Loggers(d) =>
+            match d {
+                Err(_) => {}
+                Ok(x) => app.loggers.as_mut().unwrap().replace_loggers(x)
+            },
+        FetcherResponse::SetLoggerLevel(logger, level, r) =>
+            app.on_logger_level_result(logger, level, r.map_err(|e| e.message)),
+        FetcherResponse::ThreadPoolMetrics(d) =>
+            match d {
+                Err(_) => {}
+                Ok(x) => app.thread_pools.as_mut().unwrap().append_thread_pool_metrics(x)
+            },
+        FetcherResponse::JvmInfo(d) =>
+            match d {
+                Err(_) => {}
+                Ok(x) => app.jvm.as_mut().unwrap().replace_jvm_info(x)
+            },
+        FetcherResponse::JvmOperation(op, r) =>
+            app.jvm.as_mut().unwrap().on_operation_result(op, r.map_err(|e| e.message)),
+        FetcherResponse::GcEvents(d) =>
+            match d {
+                Err(_) => {}
+                Ok(x) => app.jvm.as_mut().unwrap().append_gc_events(x)
+            },
+        FetcherResponse::JfrMetrics(d) =>
+            match d {
+                // failures are kept with the status of their source
+                Err(_) => {}
+                Ok(x) => app.jfr.as_mut().unwrap().append_jfr_metrics(x)
+            },
+        FetcherResponse::ActorTree(id, d) => {
+            let tab = app.actor_tree.as_mut().unwrap();
+            if tab.finish_tree_fetch(id) {
+                match d {
+                    // failures are kept with the status of their source
+                    Err(_) => {}
+                    Ok(x) => {
+                        let requests = tab.replace_actor_tree(x);
+                        app.fetcher_requests.extend(requests);
                     }
-                    None => {}
                 }
-
-                if app.actor_tree.is_some() {
-                    txf.send(FetcherRequest::ActorCount)?;
+            }
+        }
+        FetcherResponse::ActorSubtree(path, d) => {
+            let tab = app.actor_tree.as_mut().unwrap();
+            match d {
+                Err(e) => {
+                    tab.subtree_fetches.remove(&path);
+                    tab.expanded.remove(&path);
+                    tab.status_message = Some(e.message);
+                }
+                Ok(x) => {
+                    let requests = tab.merge_actor_subtree(&path, x);
+                    app.fetcher_requests.extend(requests);
                 }
             }
         }
-        if app.should_quit {
-            break;
+        FetcherResponse::ActorCount(d) =>
+            match d {
+                // failures are kept with the status of their source
+                Err(_) => {}
+                Ok(x) => app.actor_tree.as_mut().unwrap().append_actor_count(x)
+            },
+        FetcherResponse::DeadLetters(d) =>
+            match d {
+                // failures are kept with the status of their source
+                Err(_) => {}
+                Ok(x) => app.actor_tree.as_mut().unwrap().append_dead_letters(x)
+            },
+        FetcherResponse::ActorStats(d) =>
+            match d {
+                // failures are kept with the status of their source
+                Err(_) => {}
+                Ok(x) => app.actor_tree.as_mut().unwrap().replace_actor_stats(x)
+            },
+        FetcherResponse::KamonMetrics(d) =>
+            match d {
+                Err(e) => app.actor_tree.as_mut().unwrap().on_kamon_error(e.message),
+                Ok(x) => {
+                    if let (Some(c), Some(remote)) = (app.cluster.as_mut(), x.remote) {
+                        c.append_remote_totals(x.timestamp, remote);
+                    }
+                    app.actor_tree.as_mut().unwrap().append_kamon_metrics(x)
+                }
+            },
+        FetcherResponse::ShardingStats(d) =>
+            match d {
+                // failures are kept with the status of their source
+                Err(_) => {}
+                Ok(x) => app.sharding.as_mut().unwrap().append_sharding_stats(x)
+            },
+        FetcherResponse::ClusterStatus(d) =>
+            match d {
+                Err(_) => {}
+                Ok(x) => app.cluster.as_mut().unwrap().append_cluster_status(x)
+            },
+        FetcherResponse::PrometheusMetrics(d) =>
+            match d {
+                Err(e) => app.prometheus.as_mut().unwrap().on_error(e.message),
+                Ok(x) => app.prometheus.as_mut().unwrap().append_metrics(x)
+            },
+        FetcherResponse::PromQLMetrics(d) =>
+            match d {
+                Err(e) => app.promql.as_mut().unwrap().on_error(e.message),
+                Ok(x) => app.promql.as_mut().unwrap().append_metrics(x)
+            },
+        FetcherResponse::ActuatorHealth(d) =>
+            match d {
+                // failures are kept with the status of their source
+                Err(_) => {}
+                Ok(x) => app.actuator.as_mut().unwrap().replace_health(x)
+            },
+        FetcherResponse::ActuatorMetrics(d) =>
+            match d {
+                // failures are kept with the status of their source
+                Err(_) => {}
+                Ok(x) => app.actuator.as_mut().unwrap().metrics.append_metrics(x)
+            },
+        FetcherResponse::ActuatorThreadDump(d) =>
+            match d {
+                Err(e) => app.actuator.as_mut().unwrap().on_thread_dump_error(e.message),
+                Ok(x) => app.actuator.as_mut().unwrap().replace_thread_dump(x)
+            },
+        FetcherResponse::JsonMetric(i, d) =>
+            app.json_metrics.as_mut().unwrap().on_value(i, d.map_err(|e| e.message)),
+        FetcherResponse::CommandValues(i, d) =>
+            app.commands.as_mut().unwrap().on_values(i, d.map_err(|e| e.message)),
+        FetcherResponse::SqlValues(i, d) =>
+            app.sql_queries.as_mut().unwrap().on_values(i, d.map_err(|e| e.message)),
+        FetcherResponse::StatsdMetrics(x) =>
+            app.statsd.as_mut().unwrap().append_metrics(x),
+        FetcherResponse::LogLines(x) =>
+            app.logs.as_mut().unwrap().append_lines(x),
+        FetcherResponse::PostgresActivity(d) =>
+            match d {
+                Err(e) => app.postgres.as_mut().unwrap().on_error(e.message),
+                Ok(x) => app.postgres.as_mut().unwrap().append_activity(x)
+            },
+        FetcherResponse::KafkaLag(d) =>
+            match d {
+                Err(e) => app.kafka_lag.as_mut().unwrap().on_error(e.message),
+                Ok(x) => app.kafka_lag.as_mut().unwrap().append_lag(x)
+            },
+        FetcherResponse::RedisInfo(d) =>
+            match d {
+                Err(e) => app.redis.as_mut().unwrap().on_error(e.message),
+                Ok(x) => app.redis.as_mut().unwrap().append_info(x)
+            },
+        FetcherResponse::DockerStats(d) =>
+            match d {
+                Err(e) => app.docker.as_mut().unwrap().on_error(e.message),
+                Ok(x) => app.docker.as_mut().unwrap().append_stats(x)
+            },
+        FetcherResponse::PodMetrics(d) =>
+            match d {
+                Err(e) => app.k8s_pod.as_mut().unwrap().on_error(e.message),
+                Ok(x) => app.k8s_pod.as_mut().unwrap().append_metrics(x)
+            },
+        FetcherResponse::RabbitMqStats(d) =>
+            match d {
+                Err(e) => app.rabbitmq.as_mut().unwrap().on_error(e.message),
+                Ok(x) => app.rabbitmq.as_mut().unwrap().append_stats(x)
+            },
+        FetcherResponse::ProxyStatus(d) =>
+            match d {
+                Err(e) => app.proxy.as_mut().unwrap().on_error(e.message),
+                Ok(x) => app.proxy.as_mut().unwrap().append_status(x)
+            },
+        FetcherResponse::RecentTraces(d) =>
+            match d {
+                Err(e) => app.traces.as_mut().unwrap().on_error(e.message),
+                Ok(x) => app.traces.as_mut().unwrap().replace_traces(x)
+            },
+        FetcherResponse::OtlpMetrics(x) =>
+            app.otlp.as_mut().unwrap().append_metrics(x),
+    }
+}
+
+///
+/// Polls the sources of all the tabs, done every tick.
+///
+/// Requests whose previous one wasn't answered yet are skipped, so that slow sources are polled as often as they answer
+/// rather than getting requests queued up.
+fn request_metrics(app: &mut App, txf: &mpsc::Sender<FetcherRequest>) -> Result<(), failure::Error> {
+    let mut requests = vec![];
+    if app.zmx.as_mut().is_some_and(|z| z.count_due()) {
+        requests.push(FetcherRequest::FiberCount);
+    }
+
+    if let Some(s) = &app.slick {
+        if s.db_pool_name.is_some() {
+            requests.push(FetcherRequest::SlickMetrics);
+        }
+        if s.has_hikari {
+            requests.push(FetcherRequest::HikariMetrics);
         }
+        if s.has_notifications {
+            requests.push(FetcherRequest::JMXNotifications);
+        }
+    }
+
+    if app.kafka.is_some() {
+        requests.push(FetcherRequest::KafkaMetrics);
+    }
+
+    if app.cassandra.is_some() {
+        requests.push(FetcherRequest::CassandraMetrics);
+    }
+
+    if app.thread_pools.is_some() {
+        requests.push(FetcherRequest::ThreadPoolMetrics);
+    }
+
+    if app.jvm.is_some() {
+        requests.push(FetcherRequest::GcEvents);
+    }
+
+    if app.jfr.is_some() {
+        requests.push(FetcherRequest::JfrMetrics);
+    }
+
+    if let Some(a) = &app.actor_tree {
+        requests.push(FetcherRequest::ActorCount);
+        if a.has_dispatchers {
+            requests.push(FetcherRequest::DispatcherMetrics);
+        }
+        if a.has_kamon {
+            requests.push(FetcherRequest::KamonMetrics);
+        } else {
+            if a.has_dead_letters {
+                requests.push(FetcherRequest::DeadLetters);
+            }
+            if a.has_mailboxes {
+                requests.push(FetcherRequest::ActorStats);
+            }
+        }
+    }
+
+    if app.sharding.is_some() {
+        requests.push(FetcherRequest::ShardingStats);
+    }
+
+    if app.cluster.is_some() {
+        requests.push(FetcherRequest::ClusterStatus);
+    }
+
+    if app.prometheus.is_some() {
+        requests.push(FetcherRequest::PrometheusMetrics);
+    }
+
+    if app.promql.is_some() {
+        requests.push(FetcherRequest::PromQLMetrics);
+    }
+
+    if let Some(j) = app.json_metrics.as_mut() {
+        for i in j.due_metrics(Instant::now()) {
+            requests.push(FetcherRequest::JsonMetric(i));
+        }
+    }
+
+    if let Some(c) = app.commands.as_mut() {
+        for i in c.due_commands(Instant::now()) {
+            requests.push(FetcherRequest::CommandValues(i));
+        }
+    }
+
+    if let Some(q) = app.sql_queries.as_mut() {
+        for i in q.due_queries(Instant::now()) {
+            requests.push(FetcherRequest::SqlValues(i));
+        }
+    }
+
+    if app.statsd.is_some() {
+        requests.push(FetcherRequest::StatsdMetrics);
+    }
+
+    if app.logs.is_some() {
+        requests.push(FetcherRequest::LogLines);
+    }
+
+    if app.postgres.is_some() {
+        requests.push(FetcherRequest::PostgresActivity);
+    }
+
+    if app.kafka_lag.is_some() {
+        requests.push(FetcherRequest::KafkaLag);
+    }
+
+    if app.redis.is_some() {
+        requests.push(FetcherRequest::RedisInfo);
+    }
+
+    if app.docker.is_some() {
+        requests.push(FetcherRequest::DockerStats);
+    }
+
+    if app.k8s_pod.is_some() {
+        requests.push(FetcherRequest::PodMetrics);
+    }
+
+    if app.rabbitmq.is_some() {
+        requests.push(FetcherRequest::RabbitMqStats);
+    }
+
+    if app.proxy.is_some() {
+        requests.push(FetcherRequest::ProxyStatus);
+    }
+
+    if app.traces.is_some() {
+        requests.push(FetcherRequest::RecentTraces);
+    }
+
+    if app.otlp.is_some() {
+        requests.push(FetcherRequest::OtlpMetrics);
+    }
+
+    if let Some(a) = &app.actuator {
+        requests.push(FetcherRequest::ActuatorHealth);
+        if !a.metrics.selectors.is_empty() {
+            requests.push(FetcherRequest::ActuatorMetrics);
+        }
+    }
+    for request in requests.into_iter().filter(|r| app.start_polling(r)) {
+        txf.send(request)?;
     }
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    app.exit_reason.map(|e| println!("{}", e));
     Ok(())
 }
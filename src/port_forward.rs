@@ -0,0 +1,240 @@
+use std::io::{self, Read};
+use std::net::{TcpListener, TcpStream};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use panopticon_core::k8s::model::PodRef;
+
+pub const K8S_SCHEME: &str = "k8s://";
+
+struct PortForward {
+    pod: PodRef,
+    remote_port: u16,
+    local_port: u16,
+    child: Child,
+}
+
+///
+/// Forwards ports of Kubernetes pods, given as `k8s://<namespace>/<pod>:<port>` addresses.
+///
+/// Every distinct pod port gets its own `kubectl port-forward` process, bound to a free local port.
+/// Processes that exit, e.g. when the connection to the API server drops, are restarted on the same local port.
+/// Processes are killed when the manager is dropped.
+pub struct PortForwardManager {
+    tool: String,
+    forwards: Arc<Mutex<Vec<PortForward>>>,
+    watching: bool,
+}
+
+impl PortForwardManager {
+    const STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+    const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+    pub fn new(tool: String) -> PortForwardManager {
+        PortForwardManager { tool, forwards: Arc::new(Mutex::new(vec![])), watching: false }
+    }
+
+    ///
+    /// Returns an address equivalent to the given one, but pointing to the local end of a port-forward.
+    ///
+    /// `k8s://orders/orders-0:9010/jolokia` becomes `127.0.0.1:<local port>/jolokia`, prefixed with `http://`
+    /// when `http` is set. Other addresses are returned as they are.
+    pub fn forward_address(&mut self, address: &str, http: bool) -> Result<String, String> {
+        let (pod, port, path) = match parse_address(address)? {
+            Some(target) => target,
+            None => return Ok(address.to_owned()),
+        };
+        let local_port = self.forward(pod, port)?;
+        let scheme = if http { "http://" } else { "" };
+        Ok(format!("{}127.0.0.1:{}{}", scheme, local_port, path))
+    }
+
+    fn forward(&mut self, pod: PodRef, remote_port: u16) -> Result<u16, String> {
+        let existing = self.forwards.lock().unwrap().iter()
+            .find(|f| f.pod == pod && f.remote_port == remote_port)
+            .map(|f| f.local_port);
+        if let Some(port) = existing {
+            return Ok(port);
+        }
+
+        let local_port = TcpListener::bind("127.0.0.1:0")
+            .and_then(|l| l.local_addr())
+            .map(|a| a.port())
+            .map_err(|e| format!("Couldn't find a free local port: {}", e))?;
+        let mut child = spawn(&self.tool, &pod, remote_port, local_port, Stdio::piped())?;
+
+        let target = format!("{}:{}", pod, remote_port);
+        let started = Instant::now();
+        loop {
+            if let Ok(Some(status)) = child.try_wait() {
+                let mut stderr = String::new();
+                child.stderr.take().map(|mut s| s.read_to_string(&mut stderr));
+                return Err(format!("Port-forward to {} failed ({}): {}", target, status, stderr.trim()));
+            }
+            if TcpStream::connect(("127.0.0.1", local_port)).is_ok() {
+                break;
+            }
+            if started.elapsed() > PortForwardManager::STARTUP_TIMEOUT {
+                let _ = child.kill();
+                return Err(format!("Timed out establishing port-forward to {}", target));
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        // kubectl keeps reporting errors of forwarded connections, the pipe mustn't fill up
+        if let Some(mut stderr) = child.stderr.take() {
+            thread::spawn(move || io::copy(&mut stderr, &mut io::sink()));
+        }
+
+        self.forwards.lock().unwrap().push(PortForward { pod, remote_port, local_port, child });
+        if !self.watching {
+            self.watch();
+            self.watching = true;
+        }
+        Ok(local_port)
+    }
+
+    /// Restarts port-forwards whose process exited, until the manager is dropped
+    fn watch(&self) {
+        let forwards = Arc::downgrade(&self.forwards);
+        let tool = self.tool.clone();
+        thread::spawn(move || {
+            while let Some(forwards) = forwards.upgrade() {
+                restart_exited(&tool, &mut forwards.lock().unwrap());
+                drop(forwards);
+                thread::sleep(PortForwardManager::WATCH_INTERVAL);
+            }
+        });
+    }
+}
+
+/// Pod, port and path of a `k8s://` address, `None` for other addresses
+fn parse_address(address: &str) -> Result<Option<(PodRef, u16, &str)>, String> {
+    let target = match address.strip_prefix(K8S_SCHEME) {
+        Some(t) => t,
+        None => return Ok(None),
+    };
+    let invalid = || format!("Expected an address like k8s://<namespace>/<pod>:<port>, got {}", address);
+    let (pod, rest) = target.split_once(':').ok_or_else(invalid)?;
+    let pod = PodRef::parse(pod).map_err(|_| invalid())?;
+    let (port, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, ""),
+    };
+    let port: u16 = port.parse().map_err(|_| invalid())?;
+    Ok(Some((pod, port, path)))
+}
+
+/// Whether the address is a `k8s://` one with a path after its port, like those of Jolokia endpoints
+pub fn has_path(address: &str) -> bool {
+    address.strip_prefix(K8S_SCHEME).and_then(|t| t.split_once(':')).is_some_and(|(_, port)| port.contains('/'))
+}
+
+fn restart_exited(tool: &str, forwards: &mut [PortForward]) {
+    for f in forwards.iter_mut() {
+        if let Ok(Some(_)) = f.child.try_wait() {
+            // retried on the next round if kubectl can't even be started
+            if let Ok(child) = spawn(tool, &f.pod, f.remote_port, f.local_port, Stdio::null()) {
+                f.child = child;
+            }
+        }
+    }
+}
+
+fn spawn(tool: &str, pod: &PodRef, remote_port: u16, local_port: u16, stderr: Stdio) -> Result<Child, String> {
+    Command::new(tool)
+        .args(["port-forward", "--namespace", &pod.namespace, "--address", "127.0.0.1"])
+        .arg(format!("pod/{}", pod.name))
+        .arg(format!("{}:{}", local_port, remote_port))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(stderr)
+        .spawn()
+        .map_err(|e| format!("Couldn't start {}: {}", tool, e))
+}
+
+impl Drop for PortForwardManager {
+    fn drop(&mut self) {
+        // removed, so that they aren't restarted
+        for mut f in self.forwards.lock().unwrap().drain(..) {
+            let _ = f.child.kill();
+            let _ = f.child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::{Command, Stdio};
+    use std::time::{Duration, Instant};
+
+    use panopticon_core::k8s::model::PodRef;
+
+    use crate::port_forward::{PortForward, PortForwardManager, has_path, parse_address, restart_exited};
+
+    fn pod(namespace: &str, name: &str) -> PodRef {
+        PodRef { namespace: namespace.to_owned(), name: name.to_owned() }
+    }
+
+    #[test]
+    fn k8s_addresses_are_parsed() {
+        assert_eq!(parse_address("k8s://orders/orders-0:9010"), Ok(Some((pod("orders", "orders-0"), 9010, ""))));
+        assert_eq!(parse_address("k8s://orders/orders-0:8778/jolokia"), Ok(Some((pod("orders", "orders-0"), 8778, "/jolokia"))));
+        assert_eq!(parse_address("localhost:9010"), Ok(None));
+        assert_eq!(PortForwardManager::new("kubectl".to_owned()).forward_address("http://localhost:8080/actor-tree", true), Ok("http://localhost:8080/actor-tree".to_owned()));
+    }
+
+    #[test]
+    fn malformed_k8s_addresses_are_rejected() {
+        for address in ["k8s://orders-0:9010", "k8s://orders/orders-0", "k8s://orders/orders-0:jmx", "k8s://orders/orders-0:70000", "k8s:///orders-0:9010", "k8s://a/b/c:9010"] {
+            assert_eq!(
+                PortForwardManager::new("kubectl".to_owned()).forward_address(address, false),
+                Err(format!("Expected an address like k8s://<namespace>/<pod>:<port>, got {}", address))
+            );
+        }
+    }
+
+    #[test]
+    fn jolokia_endpoints_are_told_by_their_path() {
+        assert!(has_path("k8s://orders/orders-0:8778/jolokia"));
+        assert!(!has_path("k8s://orders/orders-0:9010"));
+        assert!(!has_path("http://localhost:8778/jolokia"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exited_port_forwards_are_restarted_on_the_same_port() {
+        let dir = std::env::temp_dir().join(format!("panopticon-port-forward-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let log = dir.join("args");
+        let tool = dir.join("kubectl");
+        std::fs::write(&tool, format!("#!/bin/sh\necho \"$@\" >> {}\nsleep 5\n", log.display())).unwrap();
+        Command::new("chmod").arg("+x").arg(&tool).status().unwrap();
+
+        let exited = Command::new("true").stdout(Stdio::null()).spawn().unwrap();
+        let running = Command::new("sleep").arg("5").spawn().unwrap();
+        let mut forwards = vec![
+            PortForward { pod: pod("orders", "orders-0"), remote_port: 9010, local_port: 40000, child: exited },
+            PortForward { pod: pod("orders", "orders-1"), remote_port: 9010, local_port: 40001, child: running },
+        ];
+        let started = Instant::now();
+        while forwards[0].child.try_wait().unwrap().is_none() && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        restart_exited(&tool.display().to_string(), &mut forwards);
+
+        assert!(forwards[0].child.try_wait().unwrap().is_none());
+        let started = Instant::now();
+        while !log.exists() && started.elapsed() < Duration::from_secs(5) {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "port-forward --namespace orders --address 127.0.0.1 pod/orders-0 40000:9010\n");
+        for f in forwards.iter_mut() {
+            let _ = f.child.kill();
+            let _ = f.child.wait();
+        }
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,349 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Lines, Write};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::de::{self, DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use panopticon_core::fetcher::FetcherResponse;
+
+/// First line of a recording, with what's needed to set up the same tabs
+#[derive(Deserialize, Serialize)]
+struct Header {
+    /// Command line options, without the program name
+    args: Vec<String>,
+    /// Text of the config file
+    config: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RecordedResponse<R> {
+    /// Milliseconds since the recording started
+    at: u64,
+    response: R,
+}
+
+///
+/// Writes all the responses of the sources to a file, as lines of JSON, to replay the session offline later.
+///
+/// The first line holds the command line and config the session was started with.
+pub struct Recorder {
+    file: Mutex<BufWriter<File>>,
+    started: Instant,
+}
+
+impl Recorder {
+    pub fn create(path: &str, args: Vec<String>, config: Option<String>) -> Result<Recorder, String> {
+        let error = |e: std::io::Error| format!("Couldn't record to {}: {}", path, e);
+        let mut file = BufWriter::new(File::create(path).map_err(error)?);
+        let header = serde_json::to_string(&Header { args, config }).map_err(|e| e.to_string())?;
+        writeln!(file, "{}", header).and_then(|_| file.flush()).map_err(error)?;
+        Ok(Recorder { file: Mutex::new(file), started: Instant::now() })
+    }
+
+    pub fn record(&self, response: &FetcherResponse) {
+        let at = self.started.elapsed().as_millis() as u64;
+        if let Ok(line) = serde_json::to_string(&RecordedResponse { at, response }) {
+            let mut file = self.file.lock().unwrap();
+            // a full disk shouldn't take the session down, the recording just ends early
+            let _ = writeln!(file, "{}", line).and_then(|_| file.flush());
+        }
+    }
+}
+
+///
+/// Options of the command line to record, leaving out the recording itself and secrets, which replays don't need:
+/// passwords, tokens, HTTP headers and passwords in URLs.
+pub fn recorded_args(mut args: impl Iterator<Item=String>) -> Vec<String> {
+    let mut recorded = vec![];
+    while let Some(arg) = args.next() {
+        let option = arg.split('=').next().unwrap_or("");
+        if option == "--record" || option.ends_with("-password") || option.ends_with("-token") || option.ends_with("-header") {
+            if !arg.contains('=') {
+                args.next();
+            }
+        } else {
+            recorded.push(without_password(&arg));
+        }
+    }
+    recorded
+}
+
+/// Text of the config file to record, without alert webhooks, which are secrets of their own, and passwords in URLs
+pub fn recorded_config(text: &str) -> Option<String> {
+    fn redact(value: &mut Value) {
+        match value {
+            Value::String(s) => *s = without_password(s),
+            Value::Array(values) => values.iter_mut().for_each(redact),
+            Value::Object(map) => {
+                map.remove("webhook");
+                map.values_mut().for_each(redact);
+            }
+            _ => {}
+        }
+    }
+    let mut config: Value = serde_json::from_str(text).ok()?;
+    redact(&mut config);
+    serde_json::to_string_pretty(&config).ok()
+}
+
+/// The text with the passwords of the URLs in it left out, e.g. `postgresql://app@db/orders` for `postgresql://app:secret@db/orders`
+fn without_password(text: &str) -> String {
+    let mut redacted = String::new();
+    let mut rest = text;
+    while let Some(i) = rest.find("://") {
+        let (scheme, after) = rest.split_at(i + 3);
+        redacted.push_str(scheme);
+        let authority = &after[..after.find(['/', '?', '#']).unwrap_or(after.len())];
+        match (authority.rfind('@'), authority.find(':')) {
+            (Some(at), Some(colon)) if colon < at => {
+                redacted.push_str(&after[..colon]);
+                rest = &after[at..];
+            }
+            _ => rest = after,
+        }
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+/// A recording being replayed
+pub struct Recording {
+    pub args: Vec<String>,
+    pub config: Option<String>,
+    lines: Lines<BufReader<File>>,
+}
+
+impl Recording {
+    pub fn open(path: &str) -> Result<Recording, String> {
+        let file = File::open(path).map_err(|e| format!("Couldn't open recording {}: {}", path, e))?;
+        let mut lines = BufReader::new(file).lines();
+        let header: Header = lines.next()
+            .and_then(|l| l.ok())
+            .and_then(|l| serde_json::from_str(&l).ok())
+            .ok_or_else(|| format!("{} isn't a recording made with --record", path))?;
+        Ok(Recording { args: header.args, config: header.config, lines })
+    }
+
+    ///
+    /// Sends the recorded responses with the delays they were received with, divided by `speed`, all at once
+    /// with `f64::INFINITY`.
+    ///
+    /// Returns how many responses couldn't be read, e.g. of another version of Panopticon, which are skipped.
+    pub fn play(self, speed: f64, mut respond: impl FnMut(FetcherResponse) -> Result<(), String>) -> Result<usize, String> {
+        let started = Instant::now();
+        let mut skipped = 0;
+        for line in self.lines {
+            let line = line.map_err(|e| format!("Couldn't read the recording: {}", e))?;
+            let recorded = serde_json::from_str::<Value>(&line)
+                .and_then(|v| RecordedResponse::<FetcherResponse>::deserialize(Recorded(v)));
+            match recorded {
+                Ok(r) => {
+                    let at = Duration::from_secs_f64(r.at as f64 / 1000.0 / speed);
+                    thread::sleep(at.saturating_sub(started.elapsed()));
+                    respond(r.response)?;
+                }
+                Err(_) => skipped += 1,
+            }
+        }
+        Ok(skipped)
+    }
+}
+
+///
+/// Recorded JSON, read with the nulls that values that aren't numbers were written as, since JSON has no NaN,
+/// turned back into NaN.
+///
+/// Numbers are never null otherwise, so that `null` is only `None` where an `Option` is expected.
+struct Recorded(Value);
+
+impl<'de> Deserializer<'de> for Recorded {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Array(values) => visitor.visit_seq(RecordedValues(values.into_iter())),
+            Value::Object(map) => visitor.visit_map(RecordedEntries { entries: map.into_iter(), value: None }),
+            other => other.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_f32(f32::NAN),
+            other => other.deserialize_f32(visitor),
+        }
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_f64(f64::NAN),
+            other => other.deserialize_f64(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::Null => visitor.visit_none(),
+            other => visitor.visit_some(Recorded(other)),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self, name: &'static str, variants: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Value::String(variant) => visitor.visit_enum(RecordedVariant { variant, value: None }),
+            Value::Object(map) if map.len() == 1 => {
+                let (variant, value) = map.into_iter().next().unwrap();
+                visitor.visit_enum(RecordedVariant { variant, value: Some(value) })
+            }
+            other => other.deserialize_enum(name, variants, visitor),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 char str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct RecordedValues(std::vec::IntoIter<Value>);
+
+impl<'de> SeqAccess<'de> for RecordedValues {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error> {
+        self.0.next().map(|v| seed.deserialize(Recorded(v))).transpose()
+    }
+}
+
+struct RecordedEntries {
+    entries: serde_json::map::IntoIter,
+    value: Option<Value>,
+}
+
+impl<'de> MapAccess<'de> for RecordedEntries {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Self::Error> {
+        seed.deserialize(Recorded(self.value.take().unwrap_or(Value::Null)))
+    }
+}
+
+struct RecordedVariant {
+    variant: String,
+    value: Option<Value>,
+}
+
+impl<'de> EnumAccess<'de> for RecordedVariant {
+    type Error = serde_json::Error;
+    type Variant = RecordedFields;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, RecordedFields(self.value)))
+    }
+}
+
+/// Fields of an enum variant, none for unit variants
+struct RecordedFields(Option<Value>);
+
+impl<'de> VariantAccess<'de> for RecordedFields {
+    type Error = serde_json::Error;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, Self::Error> {
+        seed.deserialize(Recorded(self.0.unwrap_or(Value::Null)))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Some(Value::Array(values)) => visitor.visit_seq(RecordedValues(values.into_iter())),
+            _ => Err(de::Error::custom("expected the fields of a tuple variant")),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _fields: &'static [&'static str], visitor: V) -> Result<V::Value, Self::Error> {
+        match self.0 {
+            Some(v) => Recorded(v).deserialize_any(visitor),
+            None => Err(de::Error::custom("expected the fields of a struct variant")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::sync::Mutex;
+
+    use panopticon_core::error::FetchError;
+    use panopticon_core::fetcher::FetcherResponse;
+
+    use crate::recording::{recorded_args, recorded_config, Recorder, Recording};
+
+    #[test]
+    fn sessions_are_replayed_as_recorded() {
+        let path = env::temp_dir().join(format!("panopticon-recording-{}.jsonl", std::process::id()));
+        let path = path.to_str().unwrap();
+        let recorder = Recorder::create(path, vec!["--jmx".to_owned(), "localhost:9010".to_owned()], Some("{}".to_owned())).unwrap();
+        recorder.record(&FetcherResponse::ActorCount(Ok(42)));
+        recorder.record(&FetcherResponse::JsonMetric(1, Err(FetchError::connection("Connection refused"))));
+        recorder.record(&FetcherResponse::JsonMetric(0, Ok(f64::NAN)));
+        drop(recorder);
+
+        let recording = Recording::open(path).unwrap();
+        assert_eq!(recording.args, vec!["--jmx", "localhost:9010"]);
+        assert_eq!(recording.config.as_deref(), Some("{}"));
+        let responses = Mutex::new(vec![]);
+        let skipped = recording.play(100.0, |r| {
+            responses.lock().unwrap().push(r);
+            Ok(())
+        });
+        assert_eq!(skipped, Ok(0));
+        let responses = responses.into_inner().unwrap();
+        assert!(matches!(responses[0], FetcherResponse::ActorCount(Ok(42))));
+        assert!(matches!(&responses[1], FetcherResponse::JsonMetric(1, Err(e)) if *e == FetchError::connection("Connection refused")));
+        assert!(matches!(responses[2], FetcherResponse::JsonMetric(0, Ok(v)) if v.is_nan()));
+        std::fs::remove_file(path).unwrap();
+        assert!(Recording::open(path).is_err());
+    }
+
+    #[test]
+    fn recording_options_are_left_out() {
+        let args = ["--jmx", "localhost:9010", "--record", "session.jsonl", "--jmx-password=secret", "--redis-password", "secret", "--influx-token", "secret", "--kafka"];
+        assert_eq!(recorded_args(args.iter().map(|a| a.to_string())), vec!["--jmx", "localhost:9010", "--kafka"]);
+
+        let args = ["--postgres", "postgresql://app:s3cr:et@db:5432/orders", "--actor-http-header", "Authorization: Bearer secret", "--redis=redis://:secret@cache"];
+        assert_eq!(recorded_args(args.iter().map(|a| a.to_string())), vec!["--postgres", "postgresql://app@db:5432/orders", "--redis=redis://@cache"]);
+    }
+
+    #[test]
+    fn secrets_of_the_config_file_are_left_out() {
+        let config = r#"{
+            "sql_queries": [{"name": "jobs", "url": "mysql://app:secret@db/jobs", "query": "select 1"}],
+            "alerts": [{"name": "Backlog", "metric": "sql/jobs", "comparison": ">", "threshold": 1, "webhook": "https://hooks.slack.com/services/T000/B000/XXXX"}]
+        }"#;
+        let recorded = recorded_config(config).unwrap();
+        assert!(!recorded.contains("secret") && !recorded.contains("hooks.slack.com"));
+        assert!(recorded.contains("mysql://app@db/jobs"));
+    }
+}
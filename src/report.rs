@@ -0,0 +1,313 @@
+use std::cell::RefCell;
+use std::fs;
+
+use chrono::{DateTime, Local, TimeZone};
+use tui::buffer::Buffer;
+use tui::style::{Color, Modifier};
+
+use crate::export;
+use crate::ui::{format_value, nice_ceiling, Series};
+use crate::widgets::viewport;
+
+const SPARK: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Points of a series drawn as a sparkline in the text report
+const SPARK_WIDTH: usize = 40;
+const SVG_WIDTH: f64 = 760.0;
+const SVG_HEIGHT: f64 = 220.0;
+
+/// Name, color and points of a line
+type ReportSeries = (String, Color, Vec<(f64, f64)>);
+
+/// A chart as drawn, with the points within its window, x in seconds since the epoch
+pub struct ReportChart {
+    pub title: String,
+    pub series: Vec<ReportSeries>,
+    pub window: (f64, f64),
+    pub markers: Vec<(f64, String)>,
+}
+
+pub struct ReportTable {
+    pub title: String,
+    pub header: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Charts and tables of the view, drawn while taking a report
+#[derive(Default)]
+pub struct Captured {
+    pub charts: Vec<ReportChart>,
+    pub tables: Vec<ReportTable>,
+}
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Captured>> = const { RefCell::new(None) };
+}
+
+/// Runs `draw`, keeping what it draws with `capture_chart` and `capture_table`
+pub fn capture<T>(draw: impl FnOnce() -> T) -> (T, Captured) {
+    CAPTURED.with(|c| *c.borrow_mut() = Some(Captured::default()));
+    let result = draw();
+    (result, CAPTURED.with(|c| c.borrow_mut().take()).unwrap_or_default())
+}
+
+/// Keeps a chart being drawn while taking a report, does nothing otherwise
+pub fn capture_chart(title: &str, series: &[Series], window: (f64, f64), markers: Vec<(f64, String)>) {
+    CAPTURED.with(|c| {
+        if let Some(captured) = c.borrow_mut().as_mut() {
+            let series = series.iter()
+                .map(|(name, color, data)| {
+                    let visible = data.iter().cloned().filter(|(x, _)| *x >= window.0 && *x <= window.1).collect();
+                    ((*name).to_owned(), *color, visible)
+                })
+                .collect();
+            captured.charts.push(ReportChart { title: title.to_owned(), series, window, markers });
+        }
+    });
+}
+
+/// Keeps a table being drawn while taking a report, does nothing otherwise
+pub fn capture_table<'r>(title: &str, header: &[&str], rows: impl IntoIterator<Item=&'r Vec<String>>) {
+    CAPTURED.with(|c| {
+        if let Some(captured) = c.borrow_mut().as_mut() {
+            captured.tables.push(ReportTable {
+                title: title.to_owned(),
+                header: header.iter().map(|h| (*h).to_owned()).collect(),
+                rows: rows.into_iter().cloned().collect(),
+            });
+        }
+    });
+}
+
+///
+/// The current view at a point in time, to attach to a postmortem: the screen as it was, its charts and its tables.
+///
+/// Written as a self-contained HTML file, with charts as SVG and tables as HTML tables, and as a text file,
+/// with charts as sparklines.
+pub struct Report {
+    pub tab: String,
+    pub taken: DateTime<Local>,
+    pub screen: Buffer,
+    pub captured: Captured,
+}
+
+impl Report {
+    /// Writes the report as HTML and text files in the working directory
+    pub fn write(&self) -> Result<String, String> {
+        let html_file = export::timestamped_file_name("report", "html");
+        let text_file = export::timestamped_file_name("report", "txt");
+        fs::write(&html_file, self.to_html())
+            .and_then(|_| fs::write(&text_file, self.to_text()))
+            .map(|_| format!("Exported the report to {} and {}", html_file, text_file))
+            .map_err(|e| format!("Export failed: {}", e))
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{} at {}\n\n", self.tab, self.taken.format("%Y-%m-%d %H:%M:%S"));
+        for line in screen_lines(&self.screen) {
+            text.push_str(line.trim_end());
+            text.push('\n');
+        }
+        for chart in &self.captured.charts {
+            text.push_str(&format!("\n{}\n", chart.title));
+            let width = chart.series.iter().map(|s| s.0.chars().count()).max().unwrap_or(0);
+            for (name, _, data) in &chart.series {
+                let latest = data.last().map_or("-".to_owned(), |(_, v)| format_value(*v));
+                text.push_str(&format!("  {:<width$}  {}  {}\n", name, sparkline(data, chart.window), latest, width = width));
+            }
+        }
+        text
+    }
+
+    pub fn to_html(&self) -> String {
+        let mut html = format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{} at {}</title>\n\
+            <style>\n\
+            body {{ font-family: sans-serif; margin: 2em; }}\n\
+            pre {{ background: #1e1e1e; color: #d4d4d4; padding: 1em; line-height: 1.1; overflow-x: auto; }}\n\
+            table {{ border-collapse: collapse; margin-bottom: 1em; }}\n\
+            th, td {{ border: 1px solid #ccc; padding: 2px 8px; text-align: left; }}\n\
+            svg {{ background: #fafafa; border: 1px solid #ccc; }}\n\
+            .legend span {{ margin-right: 1.5em; }}\n\
+            </style>\n</head>\n<body>\n",
+            escape(&self.tab), self.taken.format("%Y-%m-%d %H:%M:%S"));
+        html.push_str(&format!("<h1>{}</h1>\n<p>Taken at {}</p>\n", escape(&self.tab), self.taken.to_rfc3339()));
+        html.push_str(&format!("<h2>Screen</h2>\n<pre>{}</pre>\n", screen_html(&self.screen)));
+        if !self.captured.charts.is_empty() {
+            html.push_str("<h2>Charts</h2>\n");
+            for chart in &self.captured.charts {
+                html.push_str(&format!("<h3>{}</h3>\n{}\n<p class=\"legend\">", escape(&chart.title), svg(chart)));
+                for (name, color, data) in &chart.series {
+                    let latest = data.last().map_or("-".to_owned(), |(_, v)| format_value(*v));
+                    html.push_str(&format!("<span style=\"color: {}\">&#9632;</span>{}: {} ", css_color(*color, "#000"), escape(name), latest));
+                }
+                html.push_str("</p>\n");
+            }
+        }
+        if !self.captured.tables.is_empty() {
+            html.push_str("<h2>Tables</h2>\n");
+            for table in &self.captured.tables {
+                html.push_str(&format!("<h3>{}</h3>\n<table>\n<tr>", escape(&table.title)));
+                for h in &table.header {
+                    html.push_str(&format!("<th>{}</th>", escape(h)));
+                }
+                html.push_str("</tr>\n");
+                for row in &table.rows {
+                    html.push_str("<tr>");
+                    for v in row {
+                        html.push_str(&format!("<td>{}</td>", escape(v)));
+                    }
+                    html.push_str("</tr>\n");
+                }
+                html.push_str("</table>\n");
+            }
+        }
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+}
+
+fn screen_lines(screen: &Buffer) -> Vec<String> {
+    let width = screen.area().width as usize;
+    screen.content().chunks(width.max(1))
+        .map(|row| row.iter().map(|c| c.symbol.as_str()).collect())
+        .collect()
+}
+
+/// The screen with its colors, as runs of cells of the same style
+fn screen_html(screen: &Buffer) -> String {
+    let width = screen.area().width as usize;
+    let mut html = String::new();
+    for row in screen.content().chunks(width.max(1)) {
+        let mut cells = row.iter().peekable();
+        while let Some(first) = cells.next() {
+            let mut text = first.symbol.clone();
+            while let Some(c) = cells.next_if(|c| c.style == first.style) {
+                text.push_str(&c.symbol);
+            }
+            let (fg, bg, modifier) = (first.style.fg, first.style.bg, first.style.modifier);
+            let mut style = String::new();
+            if fg != Color::Reset {
+                style.push_str(&format!("color: {};", css_color(fg, "#d4d4d4")));
+            }
+            if bg != Color::Reset {
+                style.push_str(&format!("background: {};", css_color(bg, "#1e1e1e")));
+            }
+            if modifier.contains(Modifier::BOLD) {
+                style.push_str("font-weight: bold;");
+            }
+            if modifier.contains(Modifier::ITALIC) {
+                style.push_str("font-style: italic;");
+            }
+            if style.is_empty() {
+                html.push_str(&escape(&text));
+            } else {
+                html.push_str(&format!("<span style=\"{}\">{}</span>", style, escape(&text)));
+            }
+        }
+        html.push('\n');
+    }
+    html
+}
+
+fn sparkline(data: &[(f64, f64)], window: (f64, f64)) -> String {
+    let points = viewport::downsample(data, window.0, window.1, SPARK_WIDTH / 2);
+    let max = points.iter().map(|p| p.1).fold(0.0, f64::max);
+    points.iter()
+        .map(|(_, v)| if max > 0.0 { SPARK[((v / max).clamp(0.0, 1.0) * (SPARK.len() - 1) as f64).round() as usize] } else { SPARK[0] })
+        .collect()
+}
+
+/// Line chart of the series, scaled like in the TUI, with the times of the window and markers as dashed lines
+fn svg(chart: &ReportChart) -> String {
+    let (left, right, top, bottom) = (50.0, 10.0, 10.0, 25.0);
+    let (min_x, max_x) = chart.window;
+    let max_y = nice_ceiling(chart.series.iter().flat_map(|s| s.2.iter()).map(|p| p.1).fold(0.0, f64::max));
+    let x = |v: f64| left + (v - min_x) / (max_x - min_x).max(f64::EPSILON) * (SVG_WIDTH - left - right);
+    let y = |v: f64| top + (1.0 - v / max_y) * (SVG_HEIGHT - top - bottom);
+    let time = |v: f64| Local.timestamp_millis((v * 1000.0) as i64).format("%H:%M:%S").to_string();
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" font-size=\"11\" font-family=\"sans-serif\">\n",
+                          w = SVG_WIDTH, h = SVG_HEIGHT);
+    svg.push_str(&format!("<line x1=\"{l}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" stroke=\"#999\"/><line x1=\"{l}\" y1=\"{t}\" x2=\"{l}\" y2=\"{b}\" stroke=\"#999\"/>\n",
+                          l = left, r = SVG_WIDTH - right, t = top, b = SVG_HEIGHT - bottom));
+    for v in [0.0, max_y / 2.0, max_y] {
+        svg.push_str(&format!("<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\" fill=\"#666\">{}</text>\n", left - 4.0, y(v) + 4.0, format_value(v)));
+    }
+    for (v, anchor) in [(min_x, "start"), ((min_x + max_x) / 2.0, "middle"), (max_x, "end")] {
+        svg.push_str(&format!("<text x=\"{:.1}\" y=\"{}\" text-anchor=\"{}\" fill=\"#666\">{}</text>\n", x(v), SVG_HEIGHT - 8.0, anchor, time(v)));
+    }
+    for (at, label) in &chart.markers {
+        svg.push_str(&format!("<line x1=\"{x:.1}\" y1=\"{t}\" x2=\"{x:.1}\" y2=\"{b}\" stroke=\"#888\" stroke-dasharray=\"4 3\"/><text x=\"{x:.1}\" y=\"{ty}\" fill=\"#444\">&#160;{l}</text>\n",
+                              x = x(*at), t = top, b = SVG_HEIGHT - bottom, ty = top + 10.0, l = escape(label)));
+    }
+    for (_, color, data) in &chart.series {
+        let points: Vec<String> = data.iter().map(|(px, py)| format!("{:.1},{:.1}", x(*px), y(*py))).collect();
+        svg.push_str(&format!("<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\" points=\"{}\"/>\n", css_color(*color, "#000"), points.join(" ")));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+fn css_color(color: Color, reset: &str) -> String {
+    let named = match color {
+        Color::Reset => reset,
+        Color::Black => "#000000",
+        Color::Red => "#cd3131",
+        Color::Green => "#0dbc79",
+        Color::Yellow => "#b5a300",
+        Color::Blue => "#2472c8",
+        Color::Magenta => "#bc3fbc",
+        Color::Cyan => "#11a8cd",
+        Color::Gray => "#a0a0a0",
+        Color::DarkGray => "#666666",
+        Color::LightRed => "#f14c4c",
+        Color::LightGreen => "#23d18b",
+        Color::LightYellow => "#e5e510",
+        Color::LightBlue => "#3b8eea",
+        Color::LightMagenta => "#d670d6",
+        Color::LightCyan => "#29b8db",
+        Color::White => "#e5e5e5",
+        Color::Rgb(r, g, b) => return format!("#{:02x}{:02x}{:02x}", r, g, b),
+        // the 16 colors above, then a 6x6x6 cube and a gray ramp
+        Color::Indexed(i) if i >= 232 => return format!("#{0:02x}{0:02x}{0:02x}", 8 + (i - 232) * 10),
+        Color::Indexed(i) if i >= 16 => {
+            let level = |c: u8| if c == 0 { 0 } else { 55 + c * 40 };
+            let i = i - 16;
+            return format!("#{:02x}{:02x}{:02x}", level(i / 36), level(i / 6 % 6), level(i % 6));
+        }
+        Color::Indexed(_) => "#a0a0a0",
+    };
+    named.to_owned()
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Local;
+    use tui::buffer::Buffer;
+    use tui::style::Color;
+
+    use crate::report::{capture, capture_chart, capture_table, Report};
+
+    #[test]
+    fn charts_and_tables_drawn_are_in_the_report() {
+        let ((), captured) = capture(|| {
+            capture_chart("Connections", &[("active", Color::Green, vec![(100.0, 1.0), (110.0, 8.0), (120.0, 4.0), (200.0, 9.0)])], (100.0, 120.0), vec![(110.0, "deploy <v2>".to_owned())]);
+            capture_table("Queues", &["queue", "ready"], &vec![vec!["jobs".to_owned(), "12".to_owned()]]);
+        });
+        // nothing is kept outside of reports
+        capture_table("Queues", &["queue"], &vec![]);
+        let report = Report { tab: "Redis".to_owned(), taken: Local::now(), screen: Buffer::with_lines(vec!["Keys <3>  "]), captured };
+
+        assert_eq!(report.captured.charts[0].series[0].2.len(), 3);
+        let text = report.to_text();
+        assert!(text.contains("\nKeys <3>\n"));
+        assert!(text.contains("\nConnections\n  active  ▂█▅  4\n"), "{}", text);
+        let html = report.to_html();
+        assert!(html.contains("<pre>Keys &lt;3&gt;  \n</pre>"));
+        assert!(html.contains("<polyline fill=\"none\" stroke=\"#0dbc79\""));
+        assert!(html.contains("deploy &lt;v2&gt;"));
+        assert!(html.contains("<tr><th>queue</th><th>ready</th></tr>\n<tr><td>jobs</td><td>12</td></tr>"));
+    }
+}
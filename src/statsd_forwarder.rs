@@ -0,0 +1,122 @@
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+
+use crate::alerts::model::{MetricValue, TimedValue};
+
+/// Packets are kept under the usual MTU, so that they aren't fragmented
+const MAX_PACKET: usize = 1432;
+
+///
+/// Sends the samples of all the collected series to a statsd server as they're taken, as gauges.
+///
+/// Metrics named like in alert rules, e.g. `hikari/active`, are sent as `panopticon.hikari.active`. Series of charts
+/// with several lines are appended to the name, or sent as a `series` tag with DogStatsD tags.
+pub struct StatsdForwarder {
+    socket: UdpSocket,
+    address: SocketAddr,
+    prefix: String,
+    tags: bool,
+    failing: bool,
+}
+
+impl StatsdForwarder {
+    pub fn new(address: &str, prefix: &str, tags: bool) -> Result<StatsdForwarder, String> {
+        let error = |e: std::io::Error| format!("Couldn't forward metrics to {}: {}", address, e);
+        let address = address.to_socket_addrs().map_err(error)?.next()
+            .ok_or_else(|| format!("Couldn't resolve {}", address))?;
+        let local = if address.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(local).map_err(error)?;
+        Ok(StatsdForwarder { socket, address, prefix: prefix.trim_end_matches('.').to_owned(), tags, failing: false })
+    }
+
+    /// Fails only when sends start failing, rather than every tick
+    pub fn publish(&mut self, samples: &[TimedValue]) -> Result<(), String> {
+        // gauges are set when they're received, there's no time to send along
+        let values: Vec<MetricValue> = samples.iter().map(|(_, v)| v.clone()).collect();
+        let result = packets(&self.render(&values)).iter()
+            .try_for_each(|p| self.socket.send_to(p.as_bytes(), self.address).map(|_| ()))
+            .map_err(|e| format!("Couldn't forward metrics to {}: {}", self.address, e));
+        let newly_failing = result.is_err() && !self.failing;
+        self.failing = result.is_err();
+        if newly_failing { result } else { Ok(()) }
+    }
+
+    fn render(&self, values: &[MetricValue]) -> Vec<String> {
+        let mut lines = vec![];
+        for v in values.iter().filter(|v| v.value.is_finite()) {
+            let mut name = format!("{}.{}", self.prefix, sanitize(&v.metric)).trim_start_matches('.').to_owned();
+            let mut tags = String::new();
+            if !v.series.is_empty() {
+                if self.tags {
+                    tags = format!("|#series:{}", v.series.replace([',', '|', '\n'], "_"));
+                } else {
+                    name = format!("{}.{}", name, sanitize(&v.series));
+                }
+            }
+            // a signed value changes a gauge by that much instead of setting it
+            if v.value < 0.0 {
+                lines.push(format!("{}:0|g{}", name, tags));
+            }
+            lines.push(format!("{}:{}|g{}", name, v.value, tags));
+        }
+        lines
+    }
+}
+
+/// Characters that statsd servers don't take in names are replaced with underscores, slashes separate levels
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            '/' => '.',
+            c if c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+/// Lines joined into as few packets as they fit in
+fn packets(lines: &[String]) -> Vec<String> {
+    let mut packets: Vec<String> = vec![];
+    for line in lines {
+        match packets.last_mut() {
+            Some(p) if p.len() + 1 + line.len() <= MAX_PACKET => {
+                p.push('\n');
+                p.push_str(line);
+            }
+            _ => packets.push(line.to_owned()),
+        }
+    }
+    packets
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::UdpSocket;
+
+    use chrono::Local;
+
+    use crate::alerts::model::MetricValue;
+    use crate::statsd_forwarder::StatsdForwarder;
+
+    #[test]
+    fn values_are_forwarded_as_gauges() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut forwarder = StatsdForwarder::new(&server.local_addr().unwrap().to_string(), "panopticon", false).unwrap();
+        let values = [
+            MetricValue::new("hikari/active", "", 3.0),
+            MetricValue::new("postgres/connections", "idle in transaction", 2.5),
+            MetricValue::new("json/Job queue", "Job queue", f64::NAN),
+            MetricValue::new("jvm/lag", "", -1.0),
+        ];
+        let now = Local::now();
+        forwarder.publish(&values.iter().map(|v| (now, v.clone())).collect::<Vec<_>>()).unwrap();
+        let mut buf = [0; 2048];
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(String::from_utf8_lossy(&buf[..n]), "panopticon.hikari.active:3|g\n\
+            panopticon.postgres.connections.idle_in_transaction:2.5|g\n\
+            panopticon.jvm.lag:0|g\n\
+            panopticon.jvm.lag:-1|g");
+
+        forwarder.tags = true;
+        assert_eq!(forwarder.render(&values[1..2]), ["panopticon.postgres.connections:2.5|g|#series:idle in transaction"]);
+    }
+}
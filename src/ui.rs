@@ -1,357 +1,2421 @@
 use std::io;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, TimeZone};
 
 use tui::{
-    backend::Backend,
+    backend::{Backend, TestBackend},
+    buffer::Buffer,
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     symbols::Marker,
     Terminal,
-    widgets::{Axis, BarChart, Block, Borders, Chart, Dataset, List, Paragraph, Tabs, Text},
+    widgets::{Axis, BarChart, Block, Borders, Chart, Clear, Dataset, GraphType, List, Paragraph, Row, Sparkline, Table, Tabs, Text, Widget},
 };
 
-use crate::app::{AkkaActorTreeTab, App, SlickTab, TabKind, ZMXTab};
-use crate::jmx::model::HikariMetrics;
-use crate::zio::model::FiberCount;
+use panopticon_core::akka::model::{matches_actor_pattern, ShardRegionStats};
+use panopticon_core::jmx::model::{CassandraMetrics, DispatcherMetrics, HikariMetrics, ThreadPoolMetrics};
+use panopticon_core::logs::model::LogLevel;
+use panopticon_core::zio::model::FiberCount;
 
-pub fn draw<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<(), io::Error> {
+use crate::app::{App, Confirmation, DebugStats, StatefulList, Tab, TabKind, ToastLevel, GLOBAL_KEYBINDINGS};
+use crate::app::actor_tree::{ActorChange, AkkaActorTreeTab};
+use crate::app::actuator::ActuatorTab;
+use crate::app::cassandra::CassandraTab;
+use crate::app::cluster::{ClusterTab, RemotingRates};
+use crate::app::commands::CommandsTab;
+use crate::app::dashboard::DashboardTab;
+use crate::app::docker::DockerTab;
+use crate::app::errors::ErrorLogTab;
+use crate::app::jfr::JfrTab;
+use crate::app::json_metrics::JsonMetricsTab;
+use crate::app::jvm::JvmTab;
+use crate::app::k8s_pod::K8sPodTab;
+use crate::app::kafka::KafkaTab;
+use crate::app::kafka_lag::KafkaLagTab;
+use crate::app::loggers::LoggersTab;
+use crate::app::logs::LogsTab;
+use crate::app::otlp::OtlpTab;
+use crate::app::postgres::PostgresTab;
+use crate::app::prometheus::{PrometheusSeries, PrometheusTab};
+use crate::app::proxy::ProxyTab;
+use crate::app::rabbitmq::RabbitMqTab;
+use crate::app::redis::RedisTab;
+use crate::app::sharding::ShardingTab;
+use crate::app::slick::{SlickSeries, SlickTab};
+use crate::app::sql_queries::SqlQueriesTab;
+use crate::app::statsd::StatsdTab;
+use crate::app::thread_pools::ThreadPoolsTab;
+use crate::app::traces::TracesTab;
+use crate::app::zmx::ZMXTab;
+use crate::dashboard::{self, WidgetKind};
+use crate::report::{self, Report};
+use crate::widgets::histogram::Histogram;
+use crate::widgets::ascii::Ascii;
+use crate::widgets::glyphs::SeriesGlyphs;
+use crate::widgets::dialog::ConfirmationDialog;
+use crate::widgets::mouse;
+use crate::widgets::palette::HighContrast;
+use crate::widgets::scrollbar::Scrollbar;
+use crate::widgets::split::Splits;
+use crate::widgets::viewport::{self, Viewport};
+
+/// What of the screen the next frame draws again, from the least to the most
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Redraw {
+    Nothing,
+    /// Source statuses and the count of the responses taken in while frozen
+    StatusBar,
+    All,
+}
+
+/// Frames kept to be put back rather than drawn again
+#[derive(Default)]
+pub struct LastFrame {
+    /// The last frame, put back by the frames only drawing the status bar again
+    drawn: Option<Buffer>,
+    /// The frame shown when frozen, put back by every frame until unfrozen
+    frozen: Option<Buffer>,
+}
+
+///
+/// Draws what changed since the last frame. The terminal is only written the cells that differ either way,
+/// but the panes of the current tab aren't laid out and rendered again when only the status bar changed.
+///
+/// While frozen, the frame shown when frozen is put back instead, whatever the tabs took in meanwhile, with the
+/// status bar and dialogs drawn over it. A resized terminal gets a new frame to keep.
+pub fn redraw<B: Backend>(terminal: &mut Terminal<B>, app: &mut App, last: &mut LastFrame, redraw: Redraw) -> Result<(), io::Error> {
+    if redraw == Redraw::Nothing {
+        return Ok(());
+    }
+    let size = terminal.size()?;
+    if !app.frozen || last.frozen.as_ref().is_some_and(|b| b.area != size) {
+        last.frozen = None;
+    } else if last.frozen.is_none() {
+        last.frozen = last.drawn.clone().filter(|b| b.area == size);
+    }
     terminal.draw(|mut f| {
-        let chunks = Layout::default()
-            .constraints([Constraint::Length(3), Constraint::Min(0)].as_ref())
-            .split(f.size());
-        let tabs = app.tabs.to_owned();
-        let titles = tabs.titles();
-        let tabs_widget = Tabs::default()
-            .block(Block::default()
-                .borders(Borders::ALL)
-                .title_style(Style::default().fg(Color::Blue).modifier(Modifier::BOLD))
-                .title(app.title))
-            .titles(&titles)
-            .style(Style::default().fg(Color::Green))
-            .highlight_style(Style::default().fg(Color::Yellow))
-            .select(tabs.index);
-        f.render_widget(tabs_widget, chunks[0]);
-        match tabs.current().kind {
-            TabKind::ZMX => &app.zmx.as_mut().map(|mut t| draw_zio_tab(&mut f, &mut t, chunks[1])),
-            TabKind::Slick => &app.slick.as_ref().map(|t| draw_slick_tab(&mut f, t, chunks[1])),
-            TabKind::AkkaActorTree => &app.actor_tree.as_mut().map(|t| draw_actor_tree_tab(&mut f, t, chunks[1])),
+        let area = f.size();
+        let kept = match (&last.frozen, &last.drawn) {
+            (Some(frozen), _) => Some(frozen),
+            (None, Some(drawn)) if !app.frozen && redraw == Redraw::StatusBar && !app.maximized && drawn.area == area => Some(drawn),
+            _ => None,
         };
+        match kept {
+            Some(buffer) => {
+                f.render_widget(Restore(buffer), area);
+                // maximized charts are drawn without a status bar
+                let body = if app.maximized {
+                    area
+                } else {
+                    let chunks = frame_chunks(area);
+                    draw_status_bar(&mut f, app, chunks[2]);
+                    draw_accessibility_filters(&mut f, app, chunks[2]);
+                    chunks[1]
+                };
+                if app.frozen {
+                    draw_dialogs(&mut f, app, body);
+                }
+            }
+            None => draw_frame(&mut f, app),
+        }
+        f.render_widget(Capture(&mut last.drawn), area);
+    })?;
+    if app.frozen && last.frozen.is_none() {
+        last.frozen = last.drawn.clone();
+    }
+    Ok(())
+}
+
+/// Copies the cells of a frame into the one being drawn
+struct Restore<'a>(&'a Buffer);
+
+impl<'a> Widget for Restore<'a> {
+    fn render(self, _area: Rect, buf: &mut Buffer) {
+        buf.content.clone_from(&self.0.content);
+    }
+}
+
+/// Keeps the cells of the frame being drawn, once everything else was rendered
+struct Capture<'a>(&'a mut Option<Buffer>);
+
+impl<'a> Widget for Capture<'a> {
+    fn render(self, _area: Rect, buf: &mut Buffer) {
+        *self.0 = Some(buf.clone());
+    }
+}
+
+/// Confirmations and the label of a chart marker being typed
+fn draw_dialogs<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect) {
+    if let Some(c) = &app.confirmation {
+        draw_confirmation(f, c, area);
+    }
+    if let Some(label) = &app.marker_label {
+        draw_popup(f, "Chart marker", &format!("Label: {}_", label), area);
+    }
+}
+
+/// Tabs, body and status bar
+fn frame_chunks(area: Rect) -> Vec<Rect> {
+    Layout::default()
+        .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(1)].as_ref())
+        .split(area)
+}
+
+pub fn draw<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> Result<(), io::Error> {
+    terminal.draw(|mut f| draw_frame(&mut f, app))
+}
+
+fn draw_frame<B: Backend>(f: &mut Frame<B>, app: &mut App) {
+    if app.maximized {
+        // tabs aren't shown
+        app.tabs.title_areas = vec![];
+        let area = f.size();
+        draw_maximized(f, app, area);
+        draw_dialogs(f, app, area);
+        draw_toasts(f, app, area);
+        draw_accessibility_filters(f, app, area);
+        return;
+    }
+    let chunks = frame_chunks(f.size());
+    let titles = app.tab_titles();
+    app.tabs.title_areas = mouse::tab_title_areas(chunks[0], &titles);
+    let tabs = app.tabs.to_owned();
+    let tabs_widget = Tabs::default()
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Blue).modifier(Modifier::BOLD))
+            .title(app.title))
+        .titles(&titles)
+        .style(Style::default().fg(Color::Green))
+        .highlight_style(Style::default().fg(Color::Yellow))
+        .select(tabs.index);
+    f.render_widget(tabs_widget, chunks[0]);
+    let body = match app.alert_message(&TabKind::Alerts) {
+        Some(message) => draw_alert_banner(f, message.as_str(), chunks[1]),
+        None => chunks[1],
+    };
+    let viewport = app.viewport.clone();
+    let splits = app.splits.clone();
+    let line_numbers = app.line_numbers;
+    match tabs.current().kind {
+        TabKind::ZMX => &app.zmx.as_mut().map(|t| draw_zio_tab(f, &viewport, &splits, line_numbers, t, body)),
+        TabKind::Slick => &app.slick.as_mut().map(|t| draw_slick_tab(f, &viewport, t, body)),
+        TabKind::Kafka => &app.kafka.as_ref().map(|t| draw_kafka_tab(f, &viewport, t, body)),
+        TabKind::Cassandra => &app.cassandra.as_ref().map(|t| draw_cassandra_tab(f, &viewport, t, body)),
+        TabKind::Loggers => &app.loggers.as_mut().map(|t| draw_loggers_tab(f, t, body)),
+        TabKind::ThreadPools => &app.thread_pools.as_ref().map(|t| draw_thread_pools_tab(f, t, body)),
+        TabKind::Jvm => &app.jvm.as_ref().map(|t| draw_jvm_tab(f, t, body)),
+        TabKind::Jfr => &app.jfr.as_ref().map(|t| draw_jfr_tab(f, &viewport, &splits, t, body)),
+        TabKind::AkkaActorTree => &app.actor_tree.as_mut().map(|t| draw_actor_tree_tab(f, &viewport, t, body)),
+        TabKind::Sharding => &app.sharding.as_ref().map(|t| draw_sharding_tab(f, &viewport, &splits, t, body)),
+        TabKind::Cluster => &app.cluster.as_ref().map(|t| draw_cluster_tab(f, &viewport, &splits, t, body)),
+        TabKind::Prometheus => &app.prometheus.as_ref().map(|t| draw_prometheus_tab(f, &viewport, t, body)),
+        TabKind::PromQL => &app.promql.as_ref().map(|t| draw_prometheus_tab(f, &viewport, t, body)),
+        TabKind::Actuator => &app.actuator.as_mut().map(|t| draw_actuator_tab(f, &viewport, &splits, line_numbers, t, body)),
+        TabKind::JsonMetrics => &app.json_metrics.as_ref().map(|t| draw_json_metrics_tab(f, &viewport, t, body)),
+        TabKind::Commands => &app.commands.as_ref().map(|t| draw_commands_tab(f, &viewport, t, body)),
+        TabKind::SqlQueries => &app.sql_queries.as_ref().map(|t| draw_sql_queries_tab(f, &viewport, t, body)),
+        TabKind::Statsd => &app.statsd.as_ref().map(|t| draw_statsd_tab(f, &viewport, t, body)),
+        TabKind::Logs => &app.logs.as_ref().map(|t| draw_logs_tab(f, t, body)),
+        TabKind::KafkaLag => &app.kafka_lag.as_ref().map(|t| draw_kafka_lag_tab(f, &viewport, &splits, t, body)),
+        TabKind::Redis => &app.redis.as_ref().map(|t| draw_redis_tab(f, &viewport, &splits, t, body)),
+        TabKind::Docker => &app.docker.as_ref().map(|t| draw_docker_tab(f, &viewport, t, body)),
+        TabKind::K8sPod => &app.k8s_pod.as_ref().map(|t| draw_k8s_pod_tab(f, &viewport, &splits, t, body)),
+        TabKind::RabbitMq => &app.rabbitmq.as_ref().map(|t| draw_rabbitmq_tab(f, &viewport, &splits, t, body)),
+        TabKind::Proxy => &app.proxy.as_ref().map(|t| draw_proxy_tab(f, &viewport, &splits, t, body)),
+        TabKind::Traces => &app.traces.as_mut().map(|t| draw_traces_tab(f, &splits, line_numbers, t, body)),
+        TabKind::Otlp => &app.otlp.as_ref().map(|t| draw_otlp_tab(f, &viewport, t, body)),
+        TabKind::Errors => {
+            draw_errors_tab(f, &app.errors, body);
+            &None
+        }
+        TabKind::Alerts => {
+            draw_alerts_tab(f, app, body);
+            &None
+        }
+        TabKind::Overview => {
+            draw_overview_tab(f, app, body);
+            &None
+        }
+        TabKind::Dashboard(i) => {
+            draw_dashboard_tab(f, app, &viewport, &app.dashboards[i], body);
+            &None
+        }
+        TabKind::Postgres => &app.postgres.as_ref().map(|t| draw_postgres_tab(f, &viewport, &splits, t, body)),
+    };
+    if let Some(message) = &app.jmx_reconnecting {
+        if app.uses_jmx(&tabs.current().kind) {
+            draw_popup(f, "JMX", message, body);
+        }
+    }
+    draw_dialogs(f, app, body);
+    if app.debug.shown {
+        draw_debug_overlay(f, &app.debug, body);
+    }
+    if app.show_help {
+        draw_help(f, tabs.current(), body);
+    }
+    draw_status_bar(f, app, chunks[2]);
+    draw_toasts(f, app, body);
+    let area = f.size();
+    draw_accessibility_filters(f, app, area);
+}
+
+/// Draws the current view off screen, at the given size, keeping its charts and tables for a report
+pub fn draw_report(app: &mut App, size: Rect) -> Result<Report, io::Error> {
+    let mut terminal = Terminal::new(TestBackend::new(size.width, size.height))?;
+    let (drawn, captured) = report::capture(|| draw(&mut terminal, app));
+    drawn?;
+    Ok(Report {
+        tab: app.tabs.current().title.to_string(),
+        taken: Local::now(),
+        screen: terminal.backend().buffer().clone(),
+        captured,
     })
 }
 
-fn draw_text<B>(f: &mut Frame<B>, area: Rect)
+/// Redraws the whole frame with ASCII characters or high-contrast colors, if asked to
+fn draw_accessibility_filters<B>(f: &mut Frame<B>, app: &App, area: Rect)
+    where B: Backend,
+{
+    if app.ascii {
+        f.render_widget(Ascii, area);
+    }
+    if app.high_contrast {
+        f.render_widget(HighContrast, area);
+    }
+}
+
+/// A line on top of the area for the alerts firing, returning the rest of the area
+fn draw_alert_banner<B>(f: &mut Frame<B>, message: &str, area: Rect) -> Rect
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Length(1), Constraint::Min(0)].as_ref())
+        .split(area);
+    let text = [Text::styled(format!(" ALERT {}", message), Style::default().fg(Color::White).bg(Color::Red).modifier(Modifier::BOLD))];
+    f.render_widget(Paragraph::new(text.iter()).style(Style::default().bg(Color::Red)), chunks[0]);
+    chunks[1]
+}
+
+/// Frame, event queue and fetch timings in the top right corner
+fn draw_debug_overlay<B>(f: &mut Frame<B>, debug: &DebugStats, area: Rect)
+    where B: Backend,
+{
+    let ms = |d: &Duration| format!("{:.1}ms", d.as_secs_f64() * 1000.0);
+    let mut lines = vec![];
+    if let (Some(last), Some((average, max))) = (debug.frame_times.back(), debug.frame_stats()) {
+        lines.push(format!("frame {}, avg {}, max {} over {}", ms(last), ms(&average), ms(&max), debug.frame_times.len()));
+    }
+    lines.push(format!("{} events queued", debug.queued_events));
+    for (source, duration) in &debug.fetch_times {
+        lines.push(format!("{:<12} {:>9}", source, ms(duration)));
+    }
+    let width = (lines.iter().map(|l| l.chars().count()).max().unwrap_or(0) as u16 + 2).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let overlay = Rect::new(area.right() - width, area.top(), width, height);
+    let text: Vec<Text> = lines.iter().map(|l| Text::raw(format!("{}\n", l))).collect();
+    let p = Paragraph::new(text.iter())
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Magenta))
+            .title_style(Style::default().fg(Color::Magenta))
+            .title("Debug <F12>"));
+    f.render_widget(Clear, overlay);
+    f.render_widget(p, overlay);
+}
+
+/// Notifications stacked in the bottom right corner of the area, the latest at the bottom
+fn draw_toasts<B>(f: &mut Frame<B>, app: &App, area: Rect)
+    where B: Backend,
+{
+    if app.toasts.is_empty() {
+        return;
+    }
+    // long messages are cut off
+    let width = app.toasts.iter().map(|t| t.message.chars().count() as u16 + 2).max().unwrap_or(0).min(area.width);
+    let height = (app.toasts.len() as u16 + 2).min(area.height);
+    let toasts_area = Rect::new(area.right() - width, area.bottom() - height, width, height);
+    let text: Vec<Text> = app.toasts.iter()
+        .map(|t| {
+            let color = match t.level {
+                ToastLevel::Info => Color::Green,
+                ToastLevel::Warning => Color::Yellow,
+            };
+            Text::styled(format!("{}\n", t.message), Style::default().fg(color))
+        })
+        .collect();
+    let p = Paragraph::new(text.iter())
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::DarkGray)));
+    f.render_widget(Clear, toasts_area);
+    f.render_widget(p, toasts_area);
+}
+
+/// The main panel of the tab over the whole terminal
+fn draw_maximized<B>(f: &mut Frame<B>, app: &App, area: Rect)
+    where B: Backend,
+{
+    match app.tabs.current().kind {
+        TabKind::ZMX => {
+            let zmx = app.zmx.as_ref().unwrap();
+            draw_dump(f, "Fiber dump (press <PageUp>/<PageDown> to scroll, <m> to restore)", &zmx.selected_fiber_dump.0, zmx.scroll, app.line_numbers, area);
+        }
+        TabKind::Actuator => {
+            let actuator = app.actuator.as_ref().unwrap();
+            draw_dump(f, "Thread dump (press <PageUp>/<PageDown> to scroll, <m> to restore)", &actuator.selected_thread_dump.0, actuator.scroll, app.line_numbers, area);
+        }
+        TabKind::Traces => {
+            let traces = app.traces.as_ref().unwrap();
+            draw_dump(f, "Spans (press <PageUp>/<PageDown> to scroll, <m> to restore)", &traces.selected_trace.0, traces.scroll, app.line_numbers, area);
+        }
+        TabKind::Logs => draw_logs_tab(f, app.logs.as_ref().unwrap(), area),
+        _ => if let Some(charts) = app.metric_charts() {
+            let count = charts.selectors.len();
+            let i = app.maximized_chart.min(count.saturating_sub(1));
+            if let (Some(selector), Some(series)) = (charts.selectors.get(i), charts.series.get(i)) {
+                let hint = format!(" ({}/{}, press <Up>/<Down> for other charts, <m> to restore)", i + 1, count);
+                draw_metric_chart(f, &app.viewport, selector, series, &hint, area);
+            }
+        }
+    }
+}
+
+/// Whether data is flowing at all: how reading each source went, when anything was last read and what's paused or alerting
+fn draw_status_bar<B>(f: &mut Frame<B>, app: &App, area: Rect)
+    where B: Backend,
+{
+    let dim = Style::default().fg(Color::DarkGray);
+    let separator = || Text::styled(" │ ", dim);
+    let mut text = vec![Text::styled(" <?> help", Style::default().fg(Color::Cyan))];
+    if let Some(rate) = app.tick_rate {
+        text.push(separator());
+        text.push(Text::raw(format!("polling every {}ms", rate.as_millis())));
+    }
+    text.push(separator());
+    text.push(Text::raw(app.last_refresh().map_or("no data yet".to_owned(), |t| format!("updated {}", t.format("%H:%M:%S")))));
+    if app.frozen {
+        text.push(separator());
+        text.push(Text::styled(format!("FROZEN, {} updates since <z>", app.frozen_updates()), Style::default().fg(Color::Yellow).modifier(Modifier::BOLD)));
+    }
+    if app.logs.as_ref().is_some_and(|l| l.paused) {
+        text.push(separator());
+        text.push(Text::styled("logs paused", Style::default().fg(Color::Yellow)));
+    }
+    let alerting = app.alerting_tabs();
+    if !alerting.is_empty() {
+        text.push(separator());
+        text.push(Text::styled(format!("ALERT {}", alerting.join(", ")), Style::default().fg(Color::Red).modifier(Modifier::BOLD)));
+    }
+    for source in &app.sources {
+        text.push(separator());
+        text.push(match (&source.error, source.last_success) {
+            (Some(e), _) => Text::styled(format!("{}: {}", source.name, e.message.lines().next().unwrap_or_default()), Style::default().fg(Color::Red)),
+            (None, Some(_)) => Text::styled(format!("{} ok", source.name), Style::default().fg(Color::Green)),
+            (None, None) => Text::styled(format!("{} waiting", source.name), Style::default().fg(Color::Yellow)),
+        });
+    }
+    f.render_widget(Paragraph::new(text.iter()), area);
+}
+
+fn draw_text<B>(f: &mut Frame<B>, area: Rect, status: Option<&str>)
+    where B: Backend,
+{
+    let text: Vec<Text> = status.map_or(vec![], |s| vec![Text::raw(s)]);
+    let p = Paragraph::new(text.iter())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("by Scalac")
+                .title_style(Style::default().fg(Color::Magenta).modifier(Modifier::BOLD)),
+        )
+        .wrap(true);
+    f.render_widget(p, area);
+}
+
+fn draw_slick_tab<B>(f: &mut Frame<B>, viewport: &Viewport, slick: &mut SlickTab, area: Rect)
+    where B: Backend,
+{
+    let constraints = if slick.allow_operations {
+        vec![Constraint::Min(7), Constraint::Length(3), Constraint::Length(3)]
+    } else {
+        vec![Constraint::Min(7), Constraint::Length(3)]
+    };
+    let chunks = Layout::default()
+        .constraints(constraints)
+        .split(area);
+
+    if let Some(picker) = slick.pool_picker.as_mut() {
+        draw_pool_picker(f, picker, chunks[0]);
+    } else if slick.has_notifications {
+        let db_chunks = Layout::default()
+            .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
+            .split(chunks[0]);
+        draw_database_graphs(f, viewport, slick, db_chunks[0]);
+        draw_jmx_notifications(f, slick, db_chunks[1]);
+    } else {
+        draw_database_graphs(f, viewport, slick, chunks[0]);
+    }
+    if slick.allow_operations {
+        draw_hikari_operations(f, chunks[1]);
+    }
+    let status = slick.status_message.as_deref().unwrap_or("Press <x> to export collected metrics as CSV, <p> to pick another DB pool, <1>-<7> to show/hide series");
+    draw_text(f, chunks[chunks.len() - 1], Some(status));
+}
+
+fn draw_pool_picker<B>(f: &mut Frame<B>, picker: &mut StatefulList<String>, area: Rect)
+    where B: Backend,
+{
+    picker.on_draw(list_area(area));
+    let items = picker.items.iter().map(Text::raw);
+    let list = List::new(items)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("Discovered DB pools (press <Enter> to select)"))
+        .highlight_style(Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))
+        .highlight_symbol(">");
+    f.render_stateful_widget(list, area, &mut picker.state);
+}
+
+fn draw_hikari_operations<B>(f: &mut Frame<B>, area: Rect)
+    where B: Backend,
+{
+    let text = [Text::raw("<e> soft evict connections  <s> suspend pool  <r> resume pool")];
+    let p = Paragraph::new(text.iter())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("HikariCP operations")
+                .title_style(Style::default().fg(Color::Cyan)),
+        );
+    f.render_widget(p, area);
+}
+
+fn draw_confirmation<B>(f: &mut Frame<B>, confirmation: &Confirmation, area: Rect)
+    where B: Backend,
+{
+    let dialog = ConfirmationDialog::new(&confirmation.title, &confirmation.question)
+        .warning(confirmation.warning.as_deref());
+    f.render_widget(dialog, area);
+}
+
+/// Draws a message over the area, wrapped if it doesn't fit in a single line
+fn draw_popup<B>(f: &mut Frame<B>, title: &str, message: &str, area: Rect)
+    where B: Backend,
+{
+    let len = message.chars().count() as u16;
+    let width = (len + 4).min(area.width.saturating_sub(4)).max(5);
+    let lines = (len + width - 3) / (width - 2);
+    let popup = centered_rect(width, lines + 2, area);
+    let text = [Text::raw(message)];
+    let p = Paragraph::new(text.iter())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_style(Style::default().fg(Color::Red).modifier(Modifier::BOLD)),
+        )
+        .wrap(true);
+    f.render_widget(Clear, popup);
+    f.render_widget(p, popup);
+}
+
+/// Lists the keys of the tab, followed by those available everywhere
+fn draw_help<B>(f: &mut Frame<B>, tab: &Tab, area: Rect)
+    where B: Backend,
+{
+    let tab_keys = tab.kind.keybindings();
+    let key_width = tab_keys.iter().chain(GLOBAL_KEYBINDINGS.iter()).map(|(k, _)| k.len()).max().unwrap_or(0);
+    let mut text = vec![];
+    let mut add_section = |title: String, keys: &[(&str, &str)]| {
+        text.push(Text::styled(format!("{}\n", title), Style::default().fg(Color::Cyan).modifier(Modifier::BOLD)));
+        for (key, action) in keys {
+            text.push(Text::styled(format!("  {:<width$}", key, width = key_width), Style::default().fg(Color::Yellow)));
+            text.push(Text::raw(format!("  {}\n", action)));
+        }
+    };
+    if tab_keys.is_empty() {
+        add_section(format!("{}: charts only", tab.title), &[]);
+    } else {
+        add_section(tab.title.to_string(), &tab_keys);
+    }
+    add_section("\nAll tabs".to_owned(), &GLOBAL_KEYBINDINGS);
+
+    let lines = text.iter().map(|t| match t {
+        Text::Raw(s) | Text::Styled(s, _) => s.matches('\n').count(),
+    }).sum::<usize>() as u16;
+    let popup = centered_rect(area.width.saturating_sub(4).min(100), lines + 2, area);
+    let p = Paragraph::new(text.iter())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Keys (press <?> or <Esc> to close)")
+                .title_style(Style::default().fg(Color::Cyan).modifier(Modifier::BOLD)),
+        )
+        .wrap(true);
+    f.render_widget(Clear, popup);
+    f.render_widget(p, popup);
+}
+
+/// A rect of given size, centered within the area (and clipped by it)
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+fn draw_jmx_notifications<B>(f: &mut Frame<B>, slick: &SlickTab, area: Rect)
+    where B: Backend,
+{
+    // most recent notifications go first
+    let text: Vec<Text> = slick.notifications.iter().rev()
+        .map(|n| Text::raw(format!(
+            "{} {} [{}] {}\n",
+            n.timestamp.format("%H:%M:%S%.3f"),
+            n.source,
+            n.notification_type,
+            n.message
+        )))
+        .collect();
+
+    let title = format!("JMX notifications: {} (press <Up>/<Down> to scroll)", slick.notifications.len());
+    let p = Paragraph::new(text.iter())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(&title)
+                .title_style(Style::default().fg(Color::Cyan)),
+        )
+        .scroll(slick.notifications_scroll);
+    f.render_widget(p, area);
+}
+
+fn draw_database_graphs<B>(f: &mut Frame<B>, viewport: &Viewport, db: &SlickTab, area: Rect)
+    where B: Backend,
+{
+    let has_slick = db.is_shown(SlickSeries::ActiveThreads) || db.is_shown(SlickSeries::QueueSize);
+    let constraints: Vec<Constraint> = if db.has_hikari && has_slick {
+        vec![Constraint::Percentage(50), Constraint::Percentage(50)]
+    } else {
+        vec![Constraint::Percentage(100)]
+    };
+
+    let chunks = Layout::default()
+        .constraints(constraints)
+        .direction(Direction::Horizontal)
+        .split(area);
+    {
+        if has_slick {
+            draw_slick_graphs(f, db, chunks[0]);
+        }
+        if db.has_hikari {
+            draw_hikari_graphs(f, viewport, db, chunks[chunks.len() - 1]);
+        }
+    }
+}
+
+fn draw_slick_graphs<B>(f: &mut Frame<B>, db: &SlickTab, area: Rect)
+    where B: Backend,
+{
+    let show_threads = db.is_shown(SlickSeries::ActiveThreads);
+    let show_queue = db.is_shown(SlickSeries::QueueSize);
+    let max_threads = if db.is_shown(SlickSeries::MaxThreads) { db.slick_config.max_threads } else { 0 };
+
+    let mut constraints = vec![];
+    if show_threads {
+        constraints.push(Constraint::Percentage(35));
+    }
+    if show_queue {
+        constraints.push(Constraint::Percentage(35));
+    }
+    constraints.push(Constraint::Min(5));
+    let chunks = Layout::default()
+        .constraints(constraints)
+        .split(area);
+    let mut chunk = chunks.iter();
+
+    if show_threads {
+        let slick_threads_barchart: Vec<(&str, u64)> = db.slick_metrics.iter()
+            .map(|x| ("", x.active_threads as u64))
+            .collect();
+        let active_threads = db.slick_metrics.back().map_or(0, |x| x.active_threads);
+        let active_threads_title = if max_threads > 0 {
+            format!("Slick active threads: {} (max: {})", active_threads, max_threads)
+        } else {
+            format!("Slick active threads: {}", active_threads)
+        };
+        let max = Some(max_threads as u64).filter(|m| *m > 0);
+        draw_bar_chart(f, *chunk.next().unwrap(), &active_threads_title, &slick_threads_barchart, max, Color::Green);
+    }
+
+    if show_queue {
+        let slick_queue_data: Vec<(&str, u64)> = db.slick_metrics.iter()
+            .map(|x| ("", x.queue_size as u64))
+            .collect();
+        let queue_size = db.slick_metrics.back().map_or(0, |x| x.queue_size);
+        let queue_size_title = format!("Slick queue size: {} (max: {})", queue_size, db.slick_config.max_queue_size);
+        draw_bar_chart(f, *chunk.next().unwrap(), &queue_size_title, &slick_queue_data, Some(db.slick_config.max_queue_size as u64), Color::Blue);
+    }
+
+    let histogram_count = show_threads as u32 + show_queue as u32;
+    let histograms = Layout::default()
+        .constraints(vec![Constraint::Ratio(1, histogram_count); histogram_count as usize])
+        .direction(Direction::Horizontal)
+        .split(*chunk.next().unwrap());
+    let mut histogram = histograms.iter();
+    if show_threads {
+        let active_threads: Vec<u64> = db.slick_metrics.iter().map(|x| x.active_threads.max(0) as u64).collect();
+        draw_histogram(f, *histogram.next().unwrap(), "Active threads distribution", &active_threads, max_threads as u64, Color::Green);
+    }
+    if show_queue {
+        let queue_sizes: Vec<u64> = db.slick_metrics.iter().map(|x| x.queue_size.max(0) as u64).collect();
+        draw_histogram(f, *histogram.next().unwrap(), "Queue size distribution", &queue_sizes, db.slick_config.max_queue_size as u64, Color::Blue);
+    }
+}
+
+/// Bar chart of how samples are distributed between 0 and `max`, with the share of samples near `max` in the title
+fn draw_histogram<B>(f: &mut Frame<B>, area: Rect, title: &str, samples: &[u64], max: u64, color: Color)
+    where B: Backend,
+{
+    let max_buckets = (area.width.saturating_sub(2) / 6).max(1) as usize;
+    let histogram = Histogram::new(samples, max, max_buckets.min(10));
+    let bar_width = (area.width.saturating_sub(2) / histogram.buckets.len() as u16).saturating_sub(1).max(1);
+    let title = format!("{} ({} samples, {:.0}% near max)", title, histogram.samples, histogram.top_percentage());
+    let data = histogram.data();
+    let bc = BarChart::default()
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title(&title))
+        .data(&data)
+        .bar_width(bar_width)
+        .bar_gap(1)
+        .value_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(color)
+        )
+        .style(Style::default().fg(color));
+    f.render_widget(bc, area);
+}
+
+/// The latest bars that fit in the area, as bar charts draw the first ones
+fn latest_bars<T>(data: &[T], area: Rect) -> &[T] {
+    let bars = (area.width.saturating_sub(2) / 4) as usize;
+    &data[data.len().saturating_sub(bars)..]
+}
+
+fn draw_bar_chart<B>(f: &mut Frame<B>, area: Rect, title: &str, data: &[(&str, u64)], max: Option<u64>, color: Color)
+    where B: Backend,
+{
+    let mut bc = BarChart::default()
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title(title))
+        .data(latest_bars(data, area))
+        .bar_width(3)
+        .bar_gap(1)
+        .value_style(
+            Style::default()
+                .fg(Color::Black)
+                .bg(color)
+        )
+        .style(Style::default().fg(color));
+    if let Some(m) = max {
+        bc = bc.max(m);
+    }
+    f.render_widget(bc, area);
+}
+
+fn draw_thread_pools_tab<B>(f: &mut Frame<B>, tab: &ThreadPoolsTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+
+    let names: Vec<String> = tab.metrics.back()
+        .map_or(vec![], |m| m.pools.iter().map(|p| p.name.to_owned()).collect());
+    if !names.is_empty() {
+        let rows = Layout::default()
+            .constraints(vec![Constraint::Ratio(1, names.len() as u32); names.len()])
+            .split(chunks[0]);
+        for (name, row) in names.iter().zip(rows) {
+            draw_thread_pool_graphs(f, tab, name, row);
+        }
+    }
+
+    let updated = tab.metrics.back().map(|m| format!("Updated at {}", m.timestamp.format("%H:%M:%S")));
+    draw_text(f, chunks[1], updated.as_deref());
+}
+
+fn draw_thread_pool_graphs<B>(f: &mut Frame<B>, tab: &ThreadPoolsTab, name: &str, area: Rect)
+    where B: Backend,
+{
+    let samples: Vec<&ThreadPoolMetrics> = tab.metrics.iter()
+        .filter_map(|m| m.pools.iter().find(|p| p.name == name))
+        .collect();
+    let last = samples.last();
+    let max_threads = last.and_then(|p| p.max_threads);
+    let has_queue = last.is_some_and(|p| p.queue_size.is_some());
+
+    let constraints = if has_queue {
+        vec![Constraint::Percentage(50), Constraint::Percentage(50)]
+    } else {
+        vec![Constraint::Percentage(100)]
+    };
+    let chunks = Layout::default()
+        .constraints(constraints)
+        .direction(Direction::Horizontal)
+        .split(area);
+
+    let threads_data: Vec<(&str, u64)> = samples.iter().map(|p| ("", p.active_threads as u64)).collect();
+    let threads_title = format!(
+        "{} active threads: {} (max: {})",
+        name,
+        last.map_or(0.0, |p| p.active_threads),
+        max_threads.map_or("?".to_owned(), |m| m.to_string())
+    );
+    draw_bar_chart(f, chunks[0], &threads_title, &threads_data, max_threads.map(|m| m as u64), Color::Green);
+
+    if has_queue {
+        let queue_data: Vec<(&str, u64)> = samples.iter().map(|p| ("", p.queue_size.unwrap_or(0.0) as u64)).collect();
+        let queue_title = format!("{} queue size: {}", name, last.and_then(|p| p.queue_size).unwrap_or(0.0));
+        draw_bar_chart(f, chunks[1], &queue_title, &queue_data, None, Color::Blue);
+    }
+}
+
+fn hikari_chart<F>(db: &SlickTab, f: F) -> Vec<(f64, f64)>
+    where F: Fn(&HikariMetrics) -> i32, {
+    db.hikari_metrics.iter()
+        .map(|x| (time_x(&x.timestamp), f(x) as f64))
+        .collect()
+}
+
+fn draw_hikari_graphs<B>(f: &mut Frame<B>, viewport: &Viewport, db: &SlickTab, area: Rect)
+    where B: Backend,
+{
+    let series: Vec<Series> = IntoIterator::into_iter([
+        (SlickSeries::HikariTotal, "total", Color::Blue, hikari_chart(db, |x| x.total)),
+        (SlickSeries::HikariActive, "active", Color::Red, hikari_chart(db, |x| x.active)),
+        (SlickSeries::HikariWaiting, "waiting", Color::Yellow, hikari_chart(db, |x| x.waiting)),
+        (SlickSeries::HikariIdle, "idle", Color::Green, hikari_chart(db, |x| x.idle)),
+    ])
+        .filter(|(s, _, _, _)| db.is_shown(*s))
+        .map(|(_, name, color, data)| (name, color, data))
+        .collect();
+
+    let title = format!(
+        "HikariCP (max pool size={})",
+        db.hikari_metrics.back().map_or(0, |x| x.max_pool_size)
+    );
+    let border_style = if db.hikari_alert.is_some() {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default()
+    };
+    draw_time_chart(f, viewport, area, &title, border_style, &series);
+}
+
+fn draw_kafka_tab<B>(f: &mut Frame<B>, viewport: &Viewport, kafka: &KafkaTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+    {
+        let chunks = Layout::default()
+            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+            .split(chunks[0]);
+        draw_kafka_consumers(f, kafka, chunks[0]);
+        draw_kafka_lag_chart(f, viewport, kafka, chunks[1]);
+    }
+    draw_text(f, chunks[1], None);
+}
+
+fn draw_kafka_consumers<B>(f: &mut Frame<B>, kafka: &KafkaTab, area: Rect)
+    where B: Backend,
+{
+    let rows: Vec<Vec<String>> = kafka.metrics.back().map_or(vec![], |m| {
+        m.consumers.iter().map(|c| vec![
+            c.client_id.to_owned(),
+            format!("{:.0}", c.records_lag_max),
+            format!("{:.2}", c.fetch_rate),
+            format!("{:.2}", c.records_consumed_rate),
+        ]).collect()
+    });
+    let title = kafka.metrics.back().map_or("Kafka consumers".to_owned(), |m| {
+        format!("Kafka consumers (updated at {})", m.timestamp.format("%H:%M:%S"))
+    });
+    let header = ["client id", "records lag max", "fetch rate", "records consumed rate"];
+    report::capture_table(&title, &header, &rows);
+    let table = Table::new(header.iter(), rows.iter().map(|r| Row::Data(r.iter())))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title(&title))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Percentage(40), Constraint::Percentage(20), Constraint::Percentage(20), Constraint::Percentage(20)]);
+    f.render_widget(table, area);
+}
+
+/// Named line chart series of (timestamp in seconds, value) points
+pub type Series<'a> = (&'a str, Color, Vec<(f64, f64)>);
+
+/// Position of a sample on time charts
+fn time_x(timestamp: &DateTime<Local>) -> f64 {
+    timestamp.timestamp_millis() as f64 / 1000.0
+}
+
+const SERIES_COLORS: [Color; 6] = [Color::Green, Color::Yellow, Color::LightBlue, Color::Magenta, Color::Red, Color::White];
+
+fn draw_kafka_lag_chart<B>(f: &mut Frame<B>, viewport: &Viewport, kafka: &KafkaTab, area: Rect)
+    where B: Backend,
+{
+    let client_ids: Vec<String> = kafka.metrics.back()
+        .map_or(vec![], |m| m.consumers.iter().map(|c| c.client_id.to_owned()).collect());
+    let series: Vec<Series> = client_ids.iter().enumerate().map(|(i, id)| {
+        let data = kafka.metrics.iter()
+            .filter_map(|m| m.consumers.iter()
+                .find(|c| &c.client_id == id)
+                .map(|c| (time_x(&m.timestamp), c.records_lag_max)))
+            .collect();
+        (id.as_str(), SERIES_COLORS[i % SERIES_COLORS.len()], data)
+    }).collect();
+
+    draw_time_chart(f, viewport, area, "Records lag max", Style::default(), &series);
+}
+
+fn draw_cassandra_tab<B>(f: &mut Frame<B>, viewport: &Viewport, cassandra: &CassandraTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+    {
+        let chunks = Layout::default()
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .direction(Direction::Horizontal)
+            .split(chunks[0]);
+
+        let metrics = &cassandra.metrics;
+        let chart = |f: fn(&CassandraMetrics) -> f64| -> Vec<(f64, f64)> {
+            metrics.iter().map(|x| (time_x(&x.timestamp), f(x))).collect()
+        };
+
+        let last = metrics.back();
+        let connections_title = format!(
+            "Driver connections (hosts={}, open={}, in-flight={})",
+            last.map_or(0.0, |x| x.connected_hosts),
+            last.map_or(0.0, |x| x.open_connections),
+            last.map_or(0.0, |x| x.in_flight_requests),
+        );
+        let connections = [
+            ("connected hosts", Color::Blue, chart(|x| x.connected_hosts)),
+            ("open connections", Color::Green, chart(|x| x.open_connections)),
+            ("in-flight requests", Color::Yellow, chart(|x| x.in_flight_requests)),
+        ];
+        draw_time_chart(f, viewport, chunks[0], &connections_title, Style::default(), &connections);
+
+        let latency_title = format!(
+            "Request latency, {} (p50={:.2}, p95={:.2}, p99={:.2})",
+            last.map_or("", |x| x.latency_unit.as_str()),
+            last.map_or(0.0, |x| x.latency_p50),
+            last.map_or(0.0, |x| x.latency_p95),
+            last.map_or(0.0, |x| x.latency_p99),
+        );
+        let latencies = [
+            ("p50", Color::Green, chart(|x| x.latency_p50)),
+            ("p95", Color::Yellow, chart(|x| x.latency_p95)),
+            ("p99", Color::Red, chart(|x| x.latency_p99)),
+        ];
+        draw_time_chart(f, viewport, chunks[1], &latency_title, Style::default(), &latencies);
+    }
+    let updated = cassandra.metrics.back().map(|m| format!("Updated at {}", m.timestamp.format("%H:%M:%S")));
+    draw_text(f, chunks[1], updated.as_deref());
+}
+
+fn draw_sharding_tab<B>(f: &mut Frame<B>, viewport: &Viewport, splits: &Splits, tab: &ShardingTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(splits.charts_percent(40)), Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+
+    let last = tab.stats.back();
+    let rows: Vec<Vec<String>> = last.map_or(vec![], |m| {
+        m.regions.iter().map(|r| vec![
+            r.region.to_owned(),
+            r.node.to_owned(),
+            r.shards.len().to_string(),
+            r.entities().to_string(),
+            r.hottest_shard().map_or("-".to_owned(), |(id, n)| format!("{} ({})", id, n)),
+        ]).collect()
+    });
+    let header = ["region", "node", "shards", "entities", "hottest shard (entities)"];
+    report::capture_table("Shard regions", &header, &rows);
+    let table = Table::new(header.iter(), rows.iter().map(|r| Row::Data(r.iter())))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("Shard regions"))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Percentage(25), Constraint::Percentage(25), Constraint::Percentage(10), Constraint::Percentage(15), Constraint::Percentage(25)]);
+    f.render_widget(table, chunks[0]);
+
+    // a series per region and node, as of the latest sample
+    let names: Vec<(String, String, String)> = last.map_or(vec![], |m| {
+        m.regions.iter().map(|r| (r.region.to_owned(), r.node.to_owned(), format!("{}@{}", r.region, r.node))).collect()
+    });
+    let series = |f: fn(&ShardRegionStats) -> f64| -> Vec<Series> {
+        names.iter().enumerate().map(|(i, (region, node, name))| {
+            let data = tab.stats.iter()
+                .filter_map(|m| m.regions.iter()
+                    .find(|r| &r.region == region && &r.node == node)
+                    .map(|r| (time_x(&m.timestamp), f(r))))
+                .collect();
+            (name.as_str(), SERIES_COLORS[i % SERIES_COLORS.len()], data)
+        }).collect()
+    };
+    let charts = Layout::default()
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .direction(Direction::Horizontal)
+        .split(chunks[1]);
+    draw_time_chart(f, viewport, charts[0], "Entities", Style::default(), &series(|r| r.entities() as f64));
+    draw_time_chart(f, viewport, charts[1], "Shards", Style::default(), &series(|r| r.shards.len() as f64));
+
+    let status = last.map(|m| if m.errors.is_empty() {
+        format!("Updated at {}", m.timestamp.format("%H:%M:%S"))
+    } else {
+        format!("Updated at {}, failed to read {}", m.timestamp.format("%H:%M:%S"), m.errors.join(", "))
+    });
+    draw_text(f, chunks[2], status.as_deref());
+}
+
+fn draw_cluster_tab<B>(f: &mut Frame<B>, viewport: &Viewport, splits: &Splits, tab: &ClusterTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(splits.charts_percent(50)), Constraint::Min(7)].as_ref())
+        .split(area);
+    let top = Layout::default()
+        .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+        .direction(Direction::Horizontal)
+        .split(chunks[0]);
+
+    let last = tab.statuses.back();
+    let rows: Vec<(Vec<String>, bool)> = last.map_or(vec![], |m| {
+        m.members.iter().map(|member| {
+            let unreachable = m.unreachable.iter().find(|u| u.node == member.address);
+            let reachability = unreachable.map_or("reachable".to_owned(), |u| format!("unreachable ({})", u.observed_by.len()));
+            let address = if member.address == m.self_address { format!("{} (self)", member.address) } else { member.address.to_owned() };
+            (vec![address, member.status.to_owned(), member.roles.join(", "), reachability], unreachable.is_some())
+        }).collect()
+    });
+    let header = ["address", "status", "roles", "reachability"];
+    let title = last.map_or("Members".to_owned(), |m| format!("Members ({})", m.members.len()));
+    report::capture_table(&title, &header, rows.iter().map(|(r, _)| r));
+    let table = Table::new(header.iter(), rows.iter().map(|(r, unreachable)| {
+        if *unreachable {
+            Row::StyledData(r.iter(), Style::default().fg(Color::Red))
+        } else {
+            Row::Data(r.iter())
+        }
+    }))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title(&title))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Percentage(40), Constraint::Percentage(15), Constraint::Percentage(25), Constraint::Percentage(20)]);
+    f.render_widget(table, top[0]);
+
+    // newest first
+    let events: Vec<String> = tab.events.iter().rev()
+        .map(|(t, e)| format!("{} {}", t.format("%H:%M:%S"), e))
+        .collect();
+    let list = List::new(events.iter().map(Text::raw))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("Membership events"));
+    f.render_widget(list, top[1]);
+
+    let charts = Layout::default()
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .direction(Direction::Horizontal)
+        .split(chunks[1]);
+    let rates = |f: fn(&RemotingRates) -> f64| -> Vec<(f64, f64)> {
+        tab.remoting.iter().map(|r| (time_x(&r.timestamp), f(r))).collect()
+    };
+    let remote_title = tab.remoting.back().map_or("Remote messages/s".to_owned(), |r| {
+        format!("Remote messages/s ({:.0} B/s in, {:.0} B/s out)", r.bytes_in, r.bytes_out)
+    });
+    draw_time_chart(f, viewport, charts[0], &remote_title, Style::default(), &[
+        ("in", Color::Green, rates(|r| r.messages_in)),
+        ("out", Color::Blue, rates(|r| r.messages_out)),
+    ]);
+    let unreachable: Vec<(f64, f64)> = tab.statuses.iter()
+        .map(|m| (time_x(&m.timestamp), m.unreachable.len() as f64))
+        .collect();
+    let border_style = if last.is_some_and(|m| !m.unreachable.is_empty()) {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default()
+    };
+    draw_time_chart(f, viewport, charts[1], "Unreachable members", border_style, &[("unreachable", Color::Red, unreachable)]);
+}
+
+fn draw_prometheus_tab<B>(f: &mut Frame<B>, viewport: &Viewport, tab: &PrometheusTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+
+    if tab.selectors.is_empty() {
+        let items: Vec<String> = tab.metrics.iter().map(|(name, kind)| format!("{} ({})", name, kind)).collect();
+        let list = List::new(items.iter().map(Text::raw))
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title_style(Style::default().fg(Color::Cyan))
+                .title("Scraped metrics (pick some to chart with --prometheus-metric)"));
+        f.render_widget(list, chunks[0]);
+    } else {
+        draw_metric_charts(f, viewport, tab, chunks[0]);
+    }
+
+    let status = if !tab.errors.is_empty() {
+        Some(tab.errors.join(", "))
+    } else {
+        tab.last_scrape.map(|t| if tab.metrics.is_empty() {
+            format!("Updated at {}", t.format("%H:%M:%S"))
+        } else {
+            format!("Scraped {} metrics at {}", tab.metrics.len(), t.format("%H:%M:%S"))
+        })
+    };
+    draw_text(f, chunks[1], status.as_deref());
+}
+
+fn draw_actuator_tab<B>(f: &mut Frame<B>, viewport: &Viewport, splits: &Splits, line_numbers: bool, tab: &mut ActuatorTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(splits.charts_percent(45)), Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+    let top = Layout::default()
+        .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+        .direction(Direction::Horizontal)
+        .split(chunks[0]);
+
+    let status_style = |status: &str| match status {
+        "UP" => Style::default().fg(Color::Green),
+        "UNKNOWN" => Style::default(),
+        _ => Style::default().fg(Color::Red),
+    };
+    let (title, rows): (String, Vec<(Vec<String>, Style)>) = match &tab.health {
+        Some(h) => (
+            format!("Health: {}", h.status),
+            h.components.iter().map(|(name, status)| (vec![name.to_owned(), status.to_owned()], status_style(status))).collect(),
+        ),
+        None => ("Health".to_owned(), vec![]),
+    };
+    let header = ["component", "status"];
+    report::capture_table(&title, &header, rows.iter().map(|(r, _)| r));
+    let table = Table::new(header.iter(), rows.iter().map(|(r, style)| Row::StyledData(r.iter(), *style)))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .border_style(tab.health.as_ref().map_or(Style::default(), |h| if h.is_up() { Style::default() } else { status_style(&h.status) }))
+            .title_style(Style::default().fg(Color::Cyan))
+            .title(&title))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Percentage(65), Constraint::Percentage(35)]);
+    f.render_widget(table, top[0]);
+
+    if tab.metrics.selectors.is_empty() {
+        let text = [Text::raw("Pick metrics to chart with --actuator-metric")];
+        let p = Paragraph::new(text.iter())
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title_style(Style::default().fg(Color::Cyan))
+                .title("Metrics"));
+        f.render_widget(p, top[1]);
+    } else {
+        draw_metric_charts(f, viewport, &tab.metrics, top[1]);
+    }
+
+    let bottom = Layout::default()
+        .constraints([Constraint::Percentage(splits.list_percent(35)), Constraint::Min(0)].as_ref())
+        .direction(Direction::Horizontal)
+        .split(chunks[1]);
+    draw_dump_list(f, "Threads (press <Enter> to take a thread dump)", &mut tab.threads, bottom[0]);
+    draw_dump(f, "Thread dump (press <PageUp>/<PageDown> to scroll, <n> to number lines)", &tab.selected_thread_dump.0, tab.scroll, line_numbers, bottom[1]);
+
+    let status = if !tab.metrics.errors.is_empty() {
+        Some(format!("Failed to read {}", tab.metrics.errors.join(", ")))
+    } else {
+        tab.health.as_ref().map(|h| format!("Health updated at {}", h.timestamp.format("%H:%M:%S")))
+    };
+    draw_text(f, chunks[2], status.as_deref());
+}
+
+fn draw_json_metrics_tab<B>(f: &mut Frame<B>, viewport: &Viewport, tab: &JsonMetricsTab, area: Rect)
+    where B: Backend,
+{
+    draw_polled_charts(f, viewport, &tab.charts, &tab.errors, "Failed to poll", area);
+}
+
+fn draw_commands_tab<B>(f: &mut Frame<B>, viewport: &Viewport, tab: &CommandsTab, area: Rect)
+    where B: Backend,
+{
+    draw_polled_charts(f, viewport, &tab.charts, &tab.errors, "Failed to run", area);
+}
+
+fn draw_sql_queries_tab<B>(f: &mut Frame<B>, viewport: &Viewport, tab: &SqlQueriesTab, area: Rect)
+    where B: Backend,
+{
+    draw_polled_charts(f, viewport, &tab.charts, &tab.errors, "Failed to query", area);
+}
+
+/// A chart per polled source, with the errors of the last polls in the status line
+fn draw_polled_charts<B>(f: &mut Frame<B>, viewport: &Viewport, charts: &PrometheusTab, errors: &[Option<String>], failure: &str, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+    draw_metric_charts(f, viewport, charts, chunks[0]);
+
+    let errors: Vec<String> = charts.selectors.iter().zip(errors.iter())
+        .filter_map(|(name, e)| e.as_ref().map(|e| format!("{}: {}", name, e)))
+        .collect();
+    let status = if errors.is_empty() {
+        charts.last_scrape.map(|t| format!("Updated at {}", t.format("%H:%M:%S")))
+    } else {
+        Some(format!("{} {}", failure, errors.join(", ")))
+    };
+    draw_text(f, chunks[1], status.as_deref());
+}
+
+fn draw_statsd_tab<B>(f: &mut Frame<B>, viewport: &Viewport, tab: &StatsdTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+    if tab.charts.selectors.is_empty() {
+        draw_text(f, chunks[0], Some("Waiting for statsd metrics..."));
+    } else {
+        draw_metric_charts(f, viewport, &tab.charts, chunks[0]);
+    }
+
+    let status = tab.charts.last_scrape.map(|t| {
+        let mut s = format!("Received {} metrics, updated at {}", tab.charts.metrics.len(), t.format("%H:%M:%S"));
+        if tab.invalid_lines > 0 {
+            s.push_str(&format!(". Skipped {} invalid lines", tab.invalid_lines));
+        }
+        s
+    });
+    draw_text(f, chunks[1], status.as_deref());
+}
+
+/// A chart for each of the selectors or queries, with a series per set of labels
+fn draw_metric_charts<B>(f: &mut Frame<B>, viewport: &Viewport, tab: &PrometheusTab, area: Rect)
+    where B: Backend,
+{
+    // more than three charts are laid out in two columns
+    let columns = if tab.selectors.len() > 3 { 2 } else { 1 };
+    let rows = tab.selectors.len().div_ceil(columns);
+    let row_areas = Layout::default()
+        .constraints(vec![Constraint::Ratio(1, rows as u32); rows])
+        .split(area);
+    let areas: Vec<Rect> = row_areas.iter()
+        .flat_map(|r| Layout::default()
+            .constraints(vec![Constraint::Ratio(1, columns as u32); columns])
+            .direction(Direction::Horizontal)
+            .split(*r))
+        .collect();
+    for ((selector, series), area) in tab.selectors.iter().zip(tab.series.iter()).zip(areas) {
+        draw_metric_chart(f, viewport, selector, series, "", area);
+    }
+}
+
+fn draw_metric_chart<B>(f: &mut Frame<B>, viewport: &Viewport, selector: &str, series: &[PrometheusSeries], hint: &str, area: Rect)
+    where B: Backend,
+{
+    // only the series with the largest latest values fit in the legend
+    let mut shown: Vec<_> = series.iter().collect();
+    shown.sort_by(|a, b| {
+        let last = |s: &&PrometheusSeries| s.values.back().map_or(0.0, |v| v.1);
+        last(b).partial_cmp(&last(a)).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    shown.truncate(SERIES_COLORS.len());
+    // rates are flagged in the title, or in the legend when mixed with other values
+    let all_rates = series.iter().all(|s| s.counter);
+    let names: Vec<String> = shown.iter()
+        .map(|s| if s.counter && !all_rates { format!("{}/s", s.name) } else { s.name.to_owned() })
+        .collect();
+    let data: Vec<Series> = shown.iter().zip(names.iter()).enumerate()
+        .map(|(i, (s, name))| (name.as_str(), SERIES_COLORS[i % SERIES_COLORS.len()], s.values.iter().map(|(t, v)| (time_x(t), *v)).collect()))
+        .collect();
+    let mut title = selector.to_owned();
+    if all_rates && !series.is_empty() {
+        title.push_str(" per second");
+    }
+    if series.len() > shown.len() {
+        title.push_str(&format!(" (top {} of {})", shown.len(), series.len()));
+    }
+    title.push_str(hint);
+    draw_time_chart(f, viewport, area, &title, Style::default(), &data);
+}
+
+fn draw_dispatcher_charts<B>(f: &mut Frame<B>, viewport: &Viewport, tab: &AkkaActorTreeTab, threads_area: Rect, queue_area: Rect)
+    where B: Backend,
+{
+    let names: Vec<String> = tab.dispatchers.back()
+        .map_or(vec![], |m| m.dispatchers.iter().map(|d| d.name.to_owned()).collect());
+    let series = |f: fn(&DispatcherMetrics) -> f64| -> Vec<Series> {
+        names.iter().enumerate().map(|(i, name)| {
+            let data = tab.dispatchers.iter()
+                .filter_map(|m| m.dispatchers.iter()
+                    .find(|d| &d.name == name)
+                    .map(|d| (time_x(&m.timestamp), f(d))))
+                .collect();
+            (name.as_str(), SERIES_COLORS[i % SERIES_COLORS.len()], data)
+        }).collect()
+    };
+
+    let parallelism: Vec<String> = tab.dispatchers.back()
+        .map_or(vec![], |m| m.dispatchers.iter().map(|d| format!("{}={}", d.name, d.parallelism)).collect());
+    let threads_title = format!("Dispatcher active threads (parallelism: {})", parallelism.join(", "));
+    draw_time_chart(f, viewport, threads_area, &threads_title, Style::default(), &series(|d| d.active_threads));
+    draw_time_chart(f, viewport, queue_area, "Dispatcher queued tasks", Style::default(), &series(|d| d.queued_tasks));
+}
+
+///
+/// Draws time series as a line chart.
+///
+/// X axis spans the time range of samples and is labeled with times, y axis starts at zero and is scaled
+/// to the largest value. Legend shows the latest value of every series.
+fn draw_time_chart<B>(f: &mut Frame<B>, viewport: &Viewport, area: Rect, title: &str, border_style: Style, series: &[Series])
+    where B: Backend,
+{
+    let xs: Vec<f64> = series.iter().flat_map(|s| s.2.iter()).map(|x| x.0).collect();
+    let time_label = |x: f64| Local.timestamp_millis((x * 1000.0) as i64).format("%H:%M:%S").to_string();
+    let (min_x, max_x, x_labels) = if xs.is_empty() {
+        (0.0, 1.0, vec![])
+    } else {
+        let max_x = xs.iter().cloned().fold(f64::MIN, f64::max);
+        // a single sample still needs a non-empty range
+        let min_x = xs.iter().cloned().fold(f64::MAX, f64::min).min(max_x - 1.0);
+        let (min_x, max_x) = viewport.window(min_x, max_x);
+        (min_x, max_x, vec![time_label(min_x), time_label((min_x + max_x) / 2.0), time_label(max_x)])
+    };
+    // braille markers fit two points per column
+    let columns = area.width.saturating_sub(2) as usize * 2;
+    let visible: Vec<Vec<(f64, f64)>> = series.iter()
+        .map(|s| {
+            let points: Vec<(f64, f64)> = s.2.iter().cloned().filter(|(x, _)| *x >= min_x && *x <= max_x).collect();
+            viewport::downsample(&points, min_x, max_x, columns)
+        })
+        .collect();
+
+    let names: Vec<String> = series.iter()
+        .map(|(name, _, data)| match data.last() {
+            Some((_, v)) => format!("{}: {}", name, format_value(*v)),
+            None => (*name).to_owned(),
+        })
+        .collect();
+    let mut datasets: Vec<Dataset> = series.iter().zip(names.iter()).zip(visible.iter())
+        .map(|(((_, color, _), name), data)| Dataset::default()
+            .name(name.as_str())
+            .marker(Marker::Braille)
+            .style(Style::default().fg(*color))
+            .data(data))
+        .collect();
+
+    let max_y = nice_ceiling(visible.iter().flat_map(|s| s.iter()).map(|x| x.1).fold(0.0, f64::max));
+    // markers cross the whole chart, labeled in the legend
+    let markers: Vec<(String, [(f64, f64); 2])> = if xs.is_empty() {
+        vec![]
+    } else {
+        viewport.markers_within(min_x, max_x)
+            .map(|m| (format!("| {}", m.label), [(m.x, 0.0), (m.x, max_y)]))
+            .collect()
+    };
+    datasets.extend(markers.iter().map(|(label, line)| Dataset::default()
+        .name(label.as_str())
+        .marker(Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(Color::Gray))
+        .data(line)));
+    let title = match viewport.describe() {
+        Some(window) => format!("{} [{}]", title, window),
+        None => title.to_owned(),
+    };
+    let shown_markers = viewport.markers_within(min_x, max_x).map(|m| (m.x, m.label.to_owned())).collect();
+    report::capture_chart(&title, series, (min_x, max_x), shown_markers);
+    let y_labels = [format_value(0.0), format_value(max_y / 2.0), format_value(max_y)];
+    let c = Chart::default()
+        .block(
+            Block::default()
+                .title(&title)
+                .title_style(Style::default().fg(Color::Cyan))
+                .borders(Borders::ALL)
+                .border_style(border_style)
+        )
+        .x_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .labels_style(Style::default().modifier(Modifier::ITALIC))
+                .bounds([min_x, max_x])
+                .labels(&x_labels)
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .labels_style(Style::default().modifier(Modifier::ITALIC))
+                .bounds([0.0, max_y])
+                .labels(&y_labels)
+        )
+        .datasets(&datasets);
+    f.render_widget(c, area);
+}
+
+/// Smallest of 1, 2, 5 times a power of ten, that's not less than the value
+pub fn nice_ceiling(v: f64) -> f64 {
+    if v <= 0.0 || !v.is_finite() {
+        return 1.0;
+    }
+    let magnitude = 10f64.powf(v.log10().floor());
+    [1.0, 2.0, 5.0, 10.0].iter()
+        .map(|m| m * magnitude)
+        .find(|x| *x >= v)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// Formats whole values without fractions, and others with up to two decimals
+pub fn format_value(v: f64) -> String {
+    if v.fract() == 0.0 {
+        format!("{:.0}", v)
+    } else {
+        format!("{:.2}", v).trim_end_matches('0').to_owned()
+    }
+}
+
+fn draw_zio_tab<B>(f: &mut Frame<B>, viewport: &Viewport, splits: &Splits, line_numbers: bool, zmx: &mut ZMXTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+    draw_fiber_list(f, viewport, splits, line_numbers, zmx, chunks[0]);
+    draw_text(f, chunks[1], None);
+}
+
+fn fiber_count_chart<F>(db: &ZMXTab, f: F) -> Vec<(f64, f64)>
+    where F: Fn(&FiberCount) -> i32, {
+    db.fiber_counts.iter()
+        .map(|x| (time_x(&x.timestamp), f(x) as f64))
+        .collect()
+}
+
+fn draw_fiber_list<B>(f: &mut Frame<B>, viewport: &Viewport, splits: &Splits, line_numbers: bool, zmx: &mut ZMXTab, area: Rect)
+    where B: Backend,
+{
+    let constraints = vec![Constraint::Percentage(100)];
+    let chunks = Layout::default()
+        .constraints(constraints)
+        .direction(Direction::Horizontal)
+        .split(area);
+    {
+        let chunks = Layout::default()
+            .constraints([Constraint::Percentage(100)].as_ref())
+            .split(chunks[0]);
+        {
+            let chunks = Layout::default()
+                .constraints([Constraint::Percentage(splits.list_percent(50)), Constraint::Min(0)].as_ref())
+                .direction(Direction::Horizontal)
+                .split(chunks[0]);
+            {
+                let chunks = Layout::default()
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+                    .split(chunks[0]);
+
+                draw_dump_list(f, "Fibers (press <Enter> to take a snapshot)", &mut zmx.fibers, chunks[0]);
+
+                let series = [
+                    ("* running", Color::Green, fiber_count_chart(zmx, |x| x.running)),
+                    ("+ done", Color::LightBlue, fiber_count_chart(zmx, |x| x.done)),
+                    ("x finishing", Color::White, fiber_count_chart(zmx, |x| x.finishing)),
+                    ("o suspended", Color::Yellow, fiber_count_chart(zmx, |x| x.suspended)),
+                ];
+                let title = format!("Fibers (total={})", zmx.fiber_counts.back().map_or(0, |x| x.total()));
+                draw_time_chart(f, viewport, chunks[1], &title, Style::default(), &series);
+                // the glyphs of the legend, for the series to be told apart without colors
+                let glyphs: Vec<(Color, char)> = series.iter().filter_map(|(name, color, _)| name.chars().next().map(|g| (*color, g))).collect();
+                f.render_widget(SeriesGlyphs(&glyphs), chunks[1]);
+            }
+
+            draw_dump(f, "Fiber dump (press <PageUp>/<PageDown> to scroll, <n> to number lines)", &zmx.selected_fiber_dump.0, zmx.scroll, line_numbers, chunks[1]);
+        }
+    }
+}
+
+/// Where a bordered list draws its items
+fn list_area(area: Rect) -> Rect {
+    Block::default().borders(Borders::ALL).inner(area)
+}
+
+/// List of fibers or threads to pick a dump from
+fn draw_dump_list<B>(f: &mut Frame<B>, title: &str, items: &mut StatefulList<String>, area: Rect)
+    where B: Backend,
+{
+    items.on_draw(list_area(area));
+    let list = List::new(items.items[items.visible()].iter().map(Text::raw))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title(title))
+        .highlight_style(Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))
+        .highlight_symbol(">");
+    f.render_stateful_widget(list, area, &mut items.visible_state());
+    draw_list_scrollbar(f, items, area);
+}
+
+/// Lines prefixed with their number, right-aligned to the widest one
+fn numbered_lines(dump: &str) -> String {
+    let width = dump.lines().count().to_string().len();
+    dump.lines()
+        .enumerate()
+        .map(|(i, l)| format!("{:>width$} │ {}", i + 1, l, width = width))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// Scrollbar over the right border of a list, labelled with the position of the selected item
+fn draw_list_scrollbar<B, T>(f: &mut Frame<B>, items: &StatefulList<T>, area: Rect)
+    where B: Backend,
+{
+    let label = items.state.selected().map(|i| format!("{} of {}", i + 1, items.items.len()));
+    let mut scrollbar = Scrollbar::new(items.offset(), items.items.len()).style(Style::default().fg(Color::Cyan));
+    if let Some(label) = &label {
+        scrollbar = scrollbar.label(label);
+    }
+    f.render_widget(scrollbar, area);
+}
+
+fn draw_dump<B>(f: &mut Frame<B>, title: &str, dump: &str, scroll: u16, line_numbers: bool, area: Rect)
+    where B: Backend,
+{
+    let text = if line_numbers { [Text::raw(numbered_lines(dump))] } else { [Text::raw(dump)] };
+    let p = Paragraph::new(text.iter())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .title_style(Style::default().fg(Color::Cyan)),
+        )
+        .wrap(true)
+        .scroll(scroll);
+    f.render_widget(p, area);
+
+    // wrapped lines are counted once
+    let lines = dump.lines().count();
+    if lines > 0 {
+        let label = format!("line {} of {}", (scroll as usize + 1).min(lines), lines);
+        f.render_widget(Scrollbar::new(scroll as usize, lines).label(&label).style(Style::default().fg(Color::Cyan)), area);
+    }
+}
+
+fn draw_jvm_tab<B>(f: &mut Frame<B>, tab: &JvmTab, area: Rect)
+    where B: Backend,
+{
+    let constraints = if tab.allow_operations {
+        vec![Constraint::Min(7), Constraint::Length(8), Constraint::Length(3)]
+    } else {
+        vec![Constraint::Min(7), Constraint::Length(3)]
+    };
+    let chunks = Layout::default()
+        .constraints(constraints)
+        .split(area);
+
+    // has to be in line with JvmTab::HEADER_LINES
+    let lines: Vec<String> = tab.info.as_ref().map_or(vec![], |i| {
+        let uptime = i.uptime + Local::now().signed_duration_since(i.timestamp).num_milliseconds();
+        let mut lines = vec![
+            format!("VM: {} {} ({})", i.vm_name, i.vm_version, i.vm_vendor),
+            format!("Spec version: {}", i.spec_version),
+            format!("Uptime: {}", format_uptime(uptime)),
+            format!("Updated at: {}", i.timestamp.format("%H:%M:%S")),
+            "".to_owned(),
+            "Input arguments:".to_owned(),
+        ];
+        lines.extend(i.input_arguments.iter().map(|a| format!("  {}", a)));
+        lines.push("".to_owned());
+        lines.push("System properties:".to_owned());
+        lines.extend(i.system_properties.iter().map(|(k, v)| format!("  {} = {}", k, v)));
+        lines
+    });
+    let text: Vec<Text> = lines.iter().map(|l| Text::raw(format!("{}\n", l))).collect();
+    let p = Paragraph::new(text.iter())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("JVM runtime (press <Up>/<Down>/<PageUp>/<PageDown> to scroll, <Enter> to reload)")
+                .title_style(Style::default().fg(Color::Cyan)),
+        )
+        .scroll(tab.scroll);
+    {
+        let chunks = Layout::default()
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .direction(Direction::Horizontal)
+            .split(chunks[0]);
+        f.render_widget(p, chunks[0]);
+        draw_gc_log(f, tab, chunks[1]);
+    }
+
+    if tab.allow_operations {
+        draw_jvm_operations_log(f, tab, chunks[1]);
+    }
+    let hint = if tab.allow_operations { Some("Press <h> to dump heap, <g> to run GC") } else { None };
+    let status = tab.status_message.as_deref().or(hint);
+    draw_text(f, chunks[chunks.len() - 1], status);
+}
+
+fn draw_gc_log<B>(f: &mut Frame<B>, tab: &JvmTab, area: Rect)
+    where B: Backend,
+{
+    // most recent collections go first
+    let rows: Vec<(Vec<String>, bool)> = tab.gc_events.iter().rev()
+        .map(|e| (vec![
+            e.timestamp.format("%H:%M:%S%.3f").to_string(),
+            e.collector.to_owned(),
+            if tab.is_long_pause(e) { format!("{} (long)", format_value(e.duration_ms)) } else { format_value(e.duration_ms) },
+            format!("{} MB", format_value(e.reclaimed / 1024.0 / 1024.0)),
+        ], tab.is_long_pause(e)))
+        .collect();
+    let long_pauses = rows.iter().filter(|(_, long)| *long).count();
+    let title = format!("GC log ({} pauses over {}ms)", long_pauses, tab.gc_pause_threshold);
+    report::capture_table(&title, &["time", "collector", "pause, ms", "reclaimed"], rows.iter().map(|(r, _)| r));
+    let table = Table::new(
+        ["time", "collector", "pause, ms", "reclaimed"].iter(),
+        rows.iter().map(|(r, long)| if *long {
+            Row::StyledData(r.iter(), Style::default().fg(Color::Red).modifier(Modifier::BOLD))
+        } else {
+            Row::Data(r.iter())
+        }),
+    )
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title(&title))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Length(12), Constraint::Percentage(40), Constraint::Length(9), Constraint::Length(12)]);
+    f.render_widget(table, area);
+}
+
+fn draw_jvm_operations_log<B>(f: &mut Frame<B>, tab: &JvmTab, area: Rect)
+    where B: Backend,
+{
+    // most recent operations go first
+    let text: Vec<Text> = tab.operations_log.iter().rev()
+        .map(|(t, message)| Text::raw(format!("{} {}\n", t.format("%H:%M:%S"), message)))
+        .collect();
+    let p = Paragraph::new(text.iter())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("Performed operations")
+                .title_style(Style::default().fg(Color::Cyan)),
+        );
+    f.render_widget(p, area);
+}
+
+fn format_uptime(ms: i64) -> String {
+    let s = ms / 1000;
+    match (s / 86400, s % 86400 / 3600, s % 3600 / 60, s % 60) {
+        (0, 0, m, s) => format!("{}m {}s", m, s),
+        (0, h, m, s) => format!("{}h {}m {}s", h, m, s),
+        (d, h, m, s) => format!("{}d {}h {}m {}s", d, h, m, s),
+    }
+}
+
+fn draw_jfr_tab<B>(f: &mut Frame<B>, viewport: &Viewport, splits: &Splits, tab: &JfrTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(splits.charts_percent(50)), Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+    {
+        let chunks = Layout::default()
+            .constraints([Constraint::Percentage(34), Constraint::Percentage(33), Constraint::Percentage(33)].as_ref())
+            .direction(Direction::Horizontal)
+            .split(chunks[0]);
+
+        let allocation = [("MB/s", Color::Green, tab.allocation_rates.iter()
+            .map(|(t, x)| (time_x(t), x / 1024.0 / 1024.0))
+            .collect())];
+        draw_time_chart(f, viewport, chunks[0], "Allocation rate", Style::default(), &allocation);
+        let gc_pauses = [("ms", Color::Yellow, tab.gc_pauses.iter()
+            .map(|p| (time_x(&p.timestamp), p.duration_ms))
+            .collect())];
+        draw_time_chart(f, viewport, chunks[1], "GC pauses", Style::default(), &gc_pauses);
+        let contention = [("ms", Color::Red, tab.contention.iter()
+            .map(|c| (time_x(&c.timestamp), c.duration_ms))
+            .collect())];
+        draw_time_chart(f, viewport, chunks[2], "Thread contention", Style::default(), &contention);
+    }
+    {
+        let chunks = Layout::default()
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)].as_ref())
+            .direction(Direction::Horizontal)
+            .split(chunks[1]);
+
+        let gc_rows: Vec<Vec<String>> = tab.gc_pauses.iter().rev().map(|p| vec![
+            p.timestamp.format("%H:%M:%S").to_string(),
+            p.name.to_owned(),
+            p.cause.to_owned(),
+            format_value(p.duration_ms),
+        ]).collect();
+        report::capture_table("Recent GC pauses", &["time", "collector", "cause", "pause, ms"], &gc_rows);
+        let gc_table = Table::new(["time", "collector", "cause", "pause, ms"].iter(), gc_rows.iter().map(|r| Row::Data(r.iter())))
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title_style(Style::default().fg(Color::Cyan))
+                .title("Recent GC pauses"))
+            .header_style(Style::default().fg(Color::Yellow))
+            .widths(&[Constraint::Length(8), Constraint::Percentage(30), Constraint::Percentage(40), Constraint::Length(9)]);
+        f.render_widget(gc_table, chunks[0]);
+
+        let contention_rows: Vec<Vec<String>> = tab.contention.iter().rev().map(|c| vec![
+            c.timestamp.format("%H:%M:%S").to_string(),
+            c.kind.to_owned(),
+            c.class.to_owned(),
+            c.thread.to_owned(),
+            format_value(c.duration_ms),
+        ]).collect();
+        report::capture_table("Recent thread contention", &["time", "kind", "class", "thread", "wait, ms"], &contention_rows);
+        let contention_table = Table::new(
+            ["time", "kind", "class", "thread", "wait, ms"].iter(),
+            contention_rows.iter().map(|r| Row::Data(r.iter())),
+        )
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title_style(Style::default().fg(Color::Cyan))
+                .title("Recent thread contention"))
+            .header_style(Style::default().fg(Color::Yellow))
+            .widths(&[Constraint::Length(8), Constraint::Length(7), Constraint::Percentage(45), Constraint::Percentage(25), Constraint::Length(8)]);
+        f.render_widget(contention_table, chunks[1]);
+    }
+    let updated = tab.allocation_rates.back().map(|(t, _)| format!("Updated at {}", t.format("%H:%M:%S")));
+    draw_text(f, chunks[2], updated.as_deref());
+}
+
+fn draw_loggers_tab<B>(f: &mut Frame<B>, tab: &mut LoggersTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+
+    tab.loggers.on_draw(list_area(chunks[0]));
+    let width = tab.loggers.items.iter().map(|l| l.name.chars().count()).max().unwrap_or(0);
+    let labels: Vec<String> = tab.loggers.items.iter()
+        .map(|l| format!("{:width$}  {}", l.name, l.level, width = width))
+        .collect();
+    let items = labels.iter().map(Text::raw);
+    let title = if tab.allow_operations {
+        "Loggers (<t>/<d>/<i>/<w>/<e> set TRACE/DEBUG/INFO/WARN/ERROR level, <Enter> reload)"
+    } else {
+        "Loggers (press <Enter> to reload)"
+    };
+    let list = List::new(items)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title(title))
+        .highlight_style(Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))
+        .highlight_symbol(">");
+    f.render_stateful_widget(list, chunks[0], &mut tab.loggers.state);
+
+    draw_text(f, chunks[1], tab.status_message.as_deref());
+}
+
+fn draw_kafka_lag_tab<B>(f: &mut Frame<B>, viewport: &Viewport, splits: &Splits, tab: &KafkaLagTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(splits.charts_percent(50)), Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+    draw_metric_charts(f, viewport, &tab.charts, chunks[0]);
+
+    let mut partitions: Vec<_> = tab.lag.as_ref().map_or(vec![], |l| l.partitions.iter().collect());
+    partitions.sort_by_key(|p| -p.lag());
+    let rows: Vec<Vec<String>> = partitions.iter().map(|p| vec![
+        p.group.to_owned(),
+        p.topic.to_owned(),
+        p.partition.to_string(),
+        p.committed.to_string(),
+        p.end.to_string(),
+        p.lag().to_string(),
+    ]).collect();
+    report::capture_table("Partitions by lag", &["group", "topic", "partition", "committed", "end", "lag"], &rows);
+    let table = Table::new(
+        ["group", "topic", "partition", "committed", "end", "lag"].iter(),
+        rows.iter().map(|r| Row::Data(r.iter())),
+    )
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("Partitions by lag"))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Percentage(25), Constraint::Percentage(25), Constraint::Length(10), Constraint::Length(14), Constraint::Length(14), Constraint::Length(12)]);
+    f.render_widget(table, chunks[1]);
+
+    let errors = tab.lag.as_ref().map_or(vec![], |l| l.errors.clone());
+    let status = match &tab.error {
+        Some(e) => Some(e.to_owned()),
+        None if !errors.is_empty() => Some(format!("Failed to read offsets of {}", errors.join(", "))),
+        None => tab.lag.as_ref().map(|l| {
+            let totals: Vec<String> = tab.group_totals().iter().map(|(g, lag)| format!("{}: {}", g, lag)).collect();
+            format!("Total lag {}, updated at {}", totals.join(", "), l.timestamp.format("%H:%M:%S"))
+        }),
+    };
+    draw_text(f, chunks[2], status.as_deref());
+}
+
+fn draw_postgres_tab<B>(f: &mut Frame<B>, viewport: &Viewport, splits: &Splits, tab: &PostgresTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(splits.charts_percent(40)), Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+    {
+        let charts = Layout::default()
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .direction(Direction::Horizontal)
+            .split(chunks[0]);
+        // a series per state seen, as of the latest sample
+        let states: Vec<String> = tab.connections.back().map_or(vec![], |(_, c)| c.iter().map(|(s, _)| s.to_owned()).collect());
+        let connections: Vec<Series> = states.iter().enumerate().map(|(i, state)| {
+            let data = tab.connections.iter()
+                .map(|(t, c)| (time_x(t), c.iter().find(|(s, _)| s == state).map_or(0.0, |(_, n)| *n as f64)))
+                .collect();
+            (state.as_str(), SERIES_COLORS[i % SERIES_COLORS.len()], data)
+        }).collect();
+        draw_time_chart(f, viewport, charts[0], "Connections by state", Style::default(), &connections);
+        let transactions = [
+            ("commits", Color::Green, tab.transactions.iter().map(|(t, c, _)| (time_x(t), *c)).collect()),
+            ("rollbacks", Color::Red, tab.transactions.iter().map(|(t, _, r)| (time_x(t), *r)).collect()),
+        ];
+        draw_time_chart(f, viewport, charts[1], "Transactions per second", Style::default(), &transactions);
+    }
+
+    // running queries first, longest first, then the ones holding transactions open
+    let mut backends: Vec<_> = tab.activity.as_ref().map_or(vec![], |a| a.backends.iter().filter(|b| b.state != "idle").collect());
+    backends.sort_by(|a, b| (!a.is_active(), -a.query_secs).partial_cmp(&(!b.is_active(), -b.query_secs)).unwrap_or(std::cmp::Ordering::Equal));
+    let rows: Vec<(Vec<String>, Style)> = backends.iter().map(|b| {
+        let style = if b.state.starts_with("idle in transaction") { Style::default().fg(Color::Yellow) } else { Style::default() };
+        (vec![
+            b.pid.to_string(),
+            format!("{:.1}", b.query_secs),
+            b.state.to_owned(),
+            b.wait_event.to_owned().unwrap_or_default(),
+            format!("{}@{}", b.user, b.database),
+            b.query.to_owned(),
+        ], style)
+    }).collect();
+    report::capture_table("Queries", &["pid", "secs", "state", "waiting on", "user@db", "query"], rows.iter().map(|(r, _)| r));
+    let table = Table::new(
+        ["pid", "secs", "state", "waiting on", "user@db", "query"].iter(),
+        rows.iter().map(|(r, style)| Row::StyledData(r.iter(), *style)),
+    )
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("Queries (idle in transaction in yellow)"))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Length(7), Constraint::Length(8), Constraint::Length(20), Constraint::Length(22), Constraint::Length(20), Constraint::Percentage(60)]);
+    f.render_widget(table, chunks[1]);
+
+    let status = match (&tab.error, &tab.activity) {
+        (Some(e), _) => Some(e.to_owned()),
+        (None, Some(a)) => Some(match a.longest_running() {
+            Some(b) => format!("Longest running query: {:.1}s, pid {}: {}", b.query_secs, b.pid, b.query),
+            None => format!("No running queries, updated at {}", a.timestamp.format("%H:%M:%S")),
+        }),
+        (None, None) => None,
+    };
+    draw_text(f, chunks[2], status.as_deref());
+}
+
+fn draw_redis_tab<B>(f: &mut Frame<B>, viewport: &Viewport, splits: &Splits, tab: &RedisTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(splits.charts_percent(60)), Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+    {
+        let rows = Layout::default()
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(chunks[0]);
+        let top = Layout::default()
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .direction(Direction::Horizontal)
+            .split(rows[0]);
+        let bottom = Layout::default()
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .direction(Direction::Horizontal)
+            .split(rows[1]);
+        let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+
+        let memory_title = match tab.info.back() {
+            Some(i) if i.max_memory > 0 => format!("Used memory (MB), maxmemory {}", format_value(mb(i.max_memory))),
+            _ => "Used memory (MB), no maxmemory".to_owned(),
+        };
+        let memory = [("used", Color::Yellow, tab.info.iter().map(|i| (time_x(&i.timestamp), mb(i.used_memory))).collect())];
+        draw_time_chart(f, viewport, top[0], &memory_title, Style::default(), &memory);
+
+        let clients = [
+            ("connected", Color::Green, tab.info.iter().map(|i| (time_x(&i.timestamp), i.connected_clients as f64)).collect()),
+            ("blocked", Color::Red, tab.info.iter().map(|i| (time_x(&i.timestamp), i.blocked_clients as f64)).collect()),
+        ];
+        draw_time_chart(f, viewport, top[1], "Clients", Style::default(), &clients);
+
+        let ops = [("ops/s", Color::LightBlue, tab.info.iter().map(|i| (time_x(&i.timestamp), i.ops_per_sec)).collect())];
+        draw_time_chart(f, viewport, bottom[0], "Operations per second", Style::default(), &ops);
+
+        let hit_rates = [("hits %", Color::Magenta, tab.hit_rates.iter().map(|(t, r)| (time_x(t), *r)).collect())];
+        draw_time_chart(f, viewport, bottom[1], "Keyspace hit rate (%)", Style::default(), &hit_rates);
+    }
+
+    let rows: Vec<Vec<String>> = tab.info.back().map_or(vec![], |i| i.slowlog.iter().map(|e| vec![
+        e.id.to_string(),
+        e.timestamp.format("%H:%M:%S").to_string(),
+        format!("{:.1}", e.duration_us as f64 / 1000.0),
+        e.command.to_owned(),
+    ]).collect());
+    report::capture_table("Slow log", &["id", "time", "ms", "command"], &rows);
+    let table = Table::new(
+        ["id", "time", "ms", "command"].iter(),
+        rows.iter().map(|r| Row::Data(r.iter())),
+    )
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("Slow log"))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Length(8), Constraint::Length(10), Constraint::Length(10), Constraint::Percentage(80)]);
+    f.render_widget(table, chunks[1]);
+
+    let status = match (&tab.error, tab.info.back()) {
+        (Some(e), _) => Some(e.to_owned()),
+        (None, Some(i)) => Some(format!("Redis {}, {} evicted keys, updated at {}", i.version, i.evicted_keys, i.timestamp.format("%H:%M:%S"))),
+        (None, None) => None,
+    };
+    draw_text(f, chunks[2], status.as_deref());
+}
+
+fn draw_docker_tab<B>(f: &mut Frame<B>, viewport: &Viewport, tab: &DockerTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+    {
+        let rows = Layout::default()
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(chunks[0]);
+        let top = Layout::default()
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .direction(Direction::Horizontal)
+            .split(rows[0]);
+        let mb = |bytes: u64| bytes as f64 / (1024.0 * 1024.0);
+        let last = tab.stats.back();
+
+        let cpu_title = match last {
+            Some(s) => match s.cpu_limit {
+                Some(limit) => format!("CPU (% of a core), limited to {} CPUs", format_value(limit)),
+                None => format!("CPU (% of a core), {} CPUs online", s.online_cpus),
+            },
+            None => "CPU (% of a core)".to_owned(),
+        };
+        let cpu = [("cpu", Color::Green, tab.cpu.iter().map(|(t, c)| (time_x(t), *c)).collect())];
+        draw_time_chart(f, viewport, top[0], &cpu_title, Style::default(), &cpu);
+
+        // close to the limit, the container is about to be OOM killed
+        let near_limit = last.is_some_and(|s| s.memory_limit > 0 && s.memory_usage * 10 >= s.memory_limit * 9);
+        let memory_title = match last {
+            Some(s) if near_limit => format!("Memory (MB), limit {}, close to it!", format_value(mb(s.memory_limit))),
+            Some(s) => format!("Memory (MB), limit {}", format_value(mb(s.memory_limit))),
+            None => "Memory (MB)".to_owned(),
+        };
+        let memory_style = if near_limit { Style::default().fg(Color::Red) } else { Style::default() };
+        let memory = [("used", Color::Yellow, tab.stats.iter().map(|s| (time_x(&s.timestamp), mb(s.memory_usage))).collect())];
+        draw_time_chart(f, viewport, top[1], &memory_title, memory_style, &memory);
+
+        let network = [
+            ("rx", Color::LightBlue, tab.network.iter().map(|(t, rx, _)| (time_x(t), rx / 1024.0)).collect()),
+            ("tx", Color::Magenta, tab.network.iter().map(|(t, _, tx)| (time_x(t), tx / 1024.0)).collect()),
+        ];
+        draw_time_chart(f, viewport, rows[1], "Network (KB/s)", Style::default(), &network);
+    }
+
+    let status = match (&tab.error, tab.stats.back()) {
+        (Some(e), _) => Some(e.to_owned()),
+        (None, Some(s)) => Some(format!("Container {}, updated at {}", tab.container, s.timestamp.format("%H:%M:%S"))),
+        (None, None) => None,
+    };
+    draw_text(f, chunks[1], status.as_deref());
+}
+
+fn draw_k8s_pod_tab<B>(f: &mut Frame<B>, viewport: &Viewport, splits: &Splits, tab: &K8sPodTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(splits.charts_percent(60)), Constraint::Min(5), Constraint::Length(3)].as_ref())
+        .split(area);
+    draw_metric_charts(f, viewport, &tab.charts, chunks[0]);
+
+    let mb = |bytes: f64| format_value((bytes / (1024.0 * 1024.0)).round());
+    let containers = tab.metrics.as_ref().map_or(vec![], |m| m.containers.iter().collect());
+    let rows: Vec<(Vec<String>, Style)> = containers.iter().map(|c| {
+        let style = if c.was_oom_killed() { Style::default().fg(Color::Red) } else { Style::default() };
+        (vec![
+            c.name.to_owned(),
+            format!("{:.3}", c.cpu_cores),
+            c.cpu_limit.map_or("-".to_owned(), format_value),
+            mb(c.memory_bytes),
+            c.memory_limit.map_or("-".to_owned(), mb),
+            c.restarts.to_string(),
+            c.last_termination.to_owned().unwrap_or_default(),
+        ], style)
+    }).collect();
+    report::capture_table("Containers", &["container", "cpu", "cpu limit", "memory MB", "memory limit", "restarts", "last terminated"], rows.iter().map(|(r, _)| r));
+    let table = Table::new(
+        ["container", "cpu", "cpu limit", "memory MB", "memory limit", "restarts", "last terminated"].iter(),
+        rows.iter().map(|(r, style)| Row::StyledData(r.iter(), *style)),
+    )
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("Containers (OOM killed in red)"))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Percentage(25), Constraint::Length(8), Constraint::Length(10), Constraint::Length(10), Constraint::Length(13), Constraint::Length(9), Constraint::Percentage(20)]);
+    f.render_widget(table, chunks[1]);
+
+    let status = match (&tab.error, &tab.metrics) {
+        (Some(e), _) => Some(e.to_owned()),
+        (None, Some(m)) => Some(format!("Pod {} {}, updated at {}", tab.pod, m.phase, m.timestamp.format("%H:%M:%S"))),
+        (None, None) => None,
+    };
+    draw_text(f, chunks[2], status.as_deref());
+}
+
+fn draw_rabbitmq_tab<B>(f: &mut Frame<B>, viewport: &Viewport, splits: &Splits, tab: &RabbitMqTab, area: Rect)
+    where B: Backend,
+{
+    let chunks = Layout::default()
+        .constraints([Constraint::Percentage(splits.charts_percent(60)), Constraint::Min(5), Constraint::Length(3)].as_ref())
+        .split(area);
+    draw_metric_charts(f, viewport, &tab.charts, chunks[0]);
+
+    let queues = tab.stats.as_ref().map_or(vec![], |s| s.queues.iter().collect());
+    let rows: Vec<(Vec<String>, Style)> = queues.iter().map(|q| {
+        // nobody is consuming the messages piling up
+        let starving = q.consumers == 0 && q.ready > 0;
+        let style = if starving { Style::default().fg(Color::Red) } else { Style::default() };
+        (vec![
+            if starving { format!("{} !", q.queue) } else { q.queue.to_string() },
+            q.state.to_owned(),
+            q.ready.to_string(),
+            q.unacknowledged.to_string(),
+            format_value(q.publish_rate),
+            format_value(q.deliver_rate),
+            q.consumers.to_string(),
+        ], style)
+    }).collect();
+    report::capture_table("Queues", &["queue", "state", "ready", "unacked", "publish/s", "deliver/s", "consumers"], rows.iter().map(|(r, _)| r));
+    let table = Table::new(
+        ["queue", "state", "ready", "unacked", "publish/s", "deliver/s", "consumers"].iter(),
+        rows.iter().map(|(r, style)| Row::StyledData(r.iter(), *style)),
+    )
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("Queues (without consumers in red)"))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Percentage(30), Constraint::Length(10), Constraint::Length(10), Constraint::Length(10), Constraint::Length(10), Constraint::Length(10), Constraint::Length(10)]);
+    f.render_widget(table, chunks[1]);
+
+    let errors = tab.stats.as_ref().map_or(vec![], |s| s.errors.clone());
+    let status = match &tab.error {
+        Some(e) => Some(e.to_owned()),
+        None if !errors.is_empty() => Some(errors.join(", ")),
+        None => tab.stats.as_ref().map(|s| format!("Updated at {}", s.timestamp.format("%H:%M:%S"))),
+    };
+    draw_text(f, chunks[2], status.as_deref());
+}
+
+fn draw_proxy_tab<B>(f: &mut Frame<B>, viewport: &Viewport, splits: &Splits, tab: &ProxyTab, area: Rect)
+    where B: Backend,
+{
+    // nginx doesn't report backend servers
+    let constraints = if tab.servers.is_empty() {
+        [Constraint::Min(5), Constraint::Length(0), Constraint::Length(3)]
+    } else {
+        [Constraint::Percentage(splits.charts_percent(60)), Constraint::Min(5), Constraint::Length(3)]
+    };
+    let chunks = Layout::default()
+        .constraints(constraints.as_ref())
+        .split(area);
+    if tab.kind.is_some() {
+        draw_metric_charts(f, viewport, &tab.charts, chunks[0]);
+    }
+
+    if !tab.servers.is_empty() {
+        let rows: Vec<(Vec<String>, Style)> = tab.servers.iter().map(|s| {
+            let style = if s.is_up() { Style::default() } else { Style::default().fg(Color::Red) };
+            (vec![s.backend.to_owned(), s.name.to_owned(), s.status.to_owned(), s.check.to_owned(), s.sessions.to_string()], style)
+        }).collect();
+        report::capture_table("Servers", &["backend", "server", "status", "last check", "sessions"], rows.iter().map(|(r, _)| r));
+        let table = Table::new(
+            ["backend", "server", "status", "last check", "sessions"].iter(),
+            rows.iter().map(|(r, style)| Row::StyledData(r.iter(), *style)),
+        )
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title_style(Style::default().fg(Color::Cyan))
+                .title("Servers (not up in red)"))
+            .header_style(Style::default().fg(Color::Yellow))
+            .widths(&[Constraint::Percentage(30), Constraint::Percentage(30), Constraint::Length(12), Constraint::Length(12), Constraint::Length(10)]);
+        f.render_widget(table, chunks[1]);
+    }
+
+    let status = match (&tab.error, tab.kind) {
+        (Some(e), _) => Some(e.to_owned()),
+        (None, Some(kind)) => tab.charts.last_scrape.map(|t| format!("{} at {}, updated at {}", kind.name(), tab.address, t.format("%H:%M:%S"))),
+        (None, None) => None,
+    };
+    draw_text(f, chunks[2], status.as_deref());
+}
+
+fn draw_traces_tab<B>(f: &mut Frame<B>, splits: &Splits, line_numbers: bool, tab: &mut TracesTab, area: Rect)
     where B: Backend,
 {
-    let text = [];
-    let p = Paragraph::new(text.iter())
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("by Scalac")
-                .title_style(Style::default().fg(Color::Magenta).modifier(Modifier::BOLD)),
-        )
-        .wrap(true);
-    f.render_widget(p, area);
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+    let panes = Layout::default()
+        .constraints([Constraint::Percentage(splits.list_percent(45)), Constraint::Min(0)].as_ref())
+        .direction(Direction::Horizontal)
+        .split(chunks[0]);
+    let title = format!("Recent traces of {}", tab.service);
+    draw_dump_list(f, &title, &mut tab.list, panes[0]);
+    draw_dump(f, "Spans (press <PageUp>/<PageDown> to scroll, <n> to number lines)", &tab.selected_trace.0, tab.scroll, line_numbers, panes[1]);
+
+    let status = match &tab.error {
+        Some(e) => Some(e.to_owned()),
+        None => tab.last_update.map(|t| format!("{} traces from {}, updated at {}", tab.traces.len(), tab.backend.name(), t.format("%H:%M:%S"))),
+    };
+    draw_text(f, chunks[1], status.as_deref());
 }
 
-fn draw_slick_tab<B>(f: &mut Frame<B>, slick: &SlickTab, area: Rect)
+fn draw_otlp_tab<B>(f: &mut Frame<B>, viewport: &Viewport, tab: &OtlpTab, area: Rect)
     where B: Backend,
 {
     let chunks = Layout::default()
         .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
         .split(area);
+    if tab.charts.selectors.is_empty() {
+        let waiting = format!("Waiting for OTLP metrics on http://{}/v1/metrics...", tab.address);
+        draw_text(f, chunks[0], Some(&waiting));
+    } else {
+        draw_metric_charts(f, viewport, &tab.charts, chunks[0]);
+    }
 
-    draw_database_graphs(f, slick, chunks[0]);
-    draw_text(f, chunks[1]);
+    let status = match &tab.last_rejection {
+        Some(e) => Some(format!("Rejected metrics: {}", e)),
+        None => tab.charts.last_scrape.map(|t| format!("Received {} metrics, updated at {}", tab.charts.metrics.len(), t.format("%H:%M:%S"))),
+    };
+    draw_text(f, chunks[1], status.as_deref());
 }
 
-fn draw_database_graphs<B>(f: &mut Frame<B>, db: &SlickTab, area: Rect)
+fn draw_logs_tab<B>(f: &mut Frame<B>, tab: &LogsTab, area: Rect)
     where B: Backend,
 {
-    let constraints: Vec<Constraint> = if db.has_hikari {
-        vec![Constraint::Percentage(50), Constraint::Percentage(50)]
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
+        .split(area);
+
+    let lines = tab.shown_lines();
+    let height = chunks[0].height.saturating_sub(2) as usize;
+    let end = lines.len() - tab.scroll.min(lines.len());
+    let items = lines[end.saturating_sub(height)..end].iter().map(|l| {
+        let style = match l.level {
+            Some(LogLevel::Error) => Style::default().fg(Color::Red),
+            Some(LogLevel::Warn) => Style::default().fg(Color::Yellow),
+            Some(LogLevel::Info) => Style::default(),
+            Some(LogLevel::Debug) | Some(LogLevel::Trace) => Style::default().fg(Color::DarkGray),
+            None => Style::default(),
+        };
+        Text::styled(l.text.as_str(), style)
+    });
+    let mut title = format!("{} (<p> pause, </> search, arrows and <PageUp>/<PageDown> scroll)", tab.source);
+    if tab.paused {
+        title = format!("{} - PAUSED, {} new lines", tab.source, tab.pending_lines());
+    } else if tab.scroll > 0 {
+        title = format!("{} - {} lines back", tab.source, tab.scroll);
+    }
+    let list = List::new(items)
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title(&title));
+    f.render_widget(list, chunks[0]);
+
+    let status = if tab.searching {
+        Some(format!("Search: {}_", tab.search))
+    } else if !tab.search.is_empty() {
+        Some(format!("{} lines matching \"{}\" (<Esc> to clear)", lines.len(), tab.search))
     } else {
-        vec![Constraint::Percentage(100)]
+        tab.error.clone()
     };
+    draw_text(f, chunks[1], status.as_deref());
+}
+
+/// The key metric of the sources that have one, next to firing alerts and the state of every source
+fn draw_overview_tab<B>(f: &mut Frame<B>, app: &App, area: Rect)
+    where B: Backend,
+{
+    let mut sparklines: Vec<(String, Color, Vec<u64>)> = vec![];
+    if let Some(zmx) = &app.zmx {
+        let total = zmx.fiber_counts.back().map_or(0, |c| c.total());
+        sparklines.push((format!("Fibers: {}", total), Color::Green, zmx.fiber_counts.iter().map(|c| c.total().max(0) as u64).collect()));
+    }
+    if let Some(slick) = &app.slick {
+        let title = match slick.hikari_metrics.back() {
+            Some(m) => format!("HikariCP active connections: {} of {}", m.active, m.max_pool_size),
+            None => "HikariCP active connections".to_owned(),
+        };
+        sparklines.push((title, Color::Red, slick.hikari_metrics.iter().map(|m| m.active.max(0) as u64).collect()));
+    }
+    if let Some(tab) = &app.actor_tree {
+        let counts = tab.displayed_actor_counts();
+        let title = counts.last().map_or("Actors".to_owned(), |c| format!("Actors: {}", c));
+        sparklines.push((title, Color::LightBlue, counts));
+    }
 
+    let columns = if sparklines.is_empty() { vec![Constraint::Percentage(100)] } else { vec![Constraint::Percentage(60), Constraint::Percentage(40)] };
     let chunks = Layout::default()
-        .constraints(constraints.as_ref())
+        .constraints(columns)
         .direction(Direction::Horizontal)
         .split(area);
-    {
-        draw_slick_graphs(f, db, chunks[0]);
-        if db.has_hikari {
-            draw_hikari_graphs(f, db, chunks[1]);
+    if !sparklines.is_empty() {
+        let rows = Layout::default()
+            .constraints(vec![Constraint::Ratio(1, sparklines.len() as u32); sparklines.len()])
+            .split(chunks[0]);
+        for ((title, color, data), row) in sparklines.iter().zip(rows) {
+            // the latest samples that fit
+            let width = row.width.saturating_sub(2) as usize;
+            let sparkline = Sparkline::default()
+                .block(Block::default()
+                    .borders(Borders::ALL)
+                    .title_style(Style::default().fg(Color::Cyan))
+                    .title(title))
+                .data(&data[data.len().saturating_sub(width)..])
+                .style(Style::default().fg(*color));
+            f.render_widget(sparkline, row);
         }
     }
+
+    let side = Layout::default()
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(*chunks.last().unwrap());
+    draw_alert_list(f, app, "Alerts", side[0]);
+    draw_source_list(f, app, "Sources", side[1]);
 }
 
-fn draw_slick_graphs<B>(f: &mut Frame<B>, db: &SlickTab, area: Rect)
+fn draw_alert_list<B>(f: &mut Frame<B>, app: &App, title: &str, area: Rect)
     where B: Backend,
 {
-    let chunks = Layout::default()
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(area);
-
-    let slick_threads_barchart: Vec<(&str, u64)> = db.slick_metrics.iter()
-        .map(|x| ("", x.active_threads as u64))
-        .collect();
-    let active_threads = db.slick_metrics.back().map_or(0, |x| x.active_threads);
-    let active_threads_title = format!("Slick active threads: {} (max: {})", active_threads, db.slick_config.max_threads);
-    let active_threads_bc = BarChart::default()
+    let alerts = app.alerts();
+    let alert_items: Vec<Text> = if alerts.is_empty() {
+        vec![Text::styled("No alerts firing", Style::default().fg(Color::Green))]
+    } else {
+        alerts.iter().map(|(tab, message)| Text::styled(format!("{}: {}", tab, message), Style::default().fg(Color::Red))).collect()
+    };
+    let alert_list = List::new(alert_items.into_iter())
         .block(Block::default()
             .borders(Borders::ALL)
             .title_style(Style::default().fg(Color::Cyan))
-            .title(&active_threads_title))
-        .data(&slick_threads_barchart)
-        .max(db.slick_config.max_threads as u64)
-        .bar_width(3)
-        .bar_gap(1)
-        .value_style(
-            Style::default()
-                .fg(Color::Black)
-                .bg(Color::Green)
-        )
-        .style(Style::default().fg(Color::Green));
-    f.render_widget(active_threads_bc, chunks[0]);
+            .title(title));
+    f.render_widget(alert_list, area);
+}
 
-    let slick_queue_data: Vec<(&str, u64)> = db.slick_metrics.iter()
-        .map(|x| ("", x.queue_size as u64))
-        .collect();
-    let queue_size = db.slick_metrics.back().map_or(0, |x| x.queue_size);
-    let queue_size_title = format!("Slick queue size: {} (max: {})", queue_size, db.slick_config.max_queue_size);
-    let slick_queue_bc = BarChart::default()
+fn draw_source_list<B>(f: &mut Frame<B>, app: &App, title: &str, area: Rect)
+    where B: Backend,
+{
+    let source_items = app.sources.iter().map(|s| {
+        let updated = s.last_success.map_or("never".to_owned(), |t| t.format("%H:%M:%S").to_string());
+        match &s.error {
+            Some(e) => Text::styled(format!("{}: {} (last read {})", s.name, e.message.lines().next().unwrap_or_default(), updated), Style::default().fg(Color::Red)),
+            None => Text::styled(format!("{} ok (read at {})", s.name, updated), Style::default().fg(Color::Green)),
+        }
+    });
+    let source_list = List::new(source_items)
         .block(Block::default()
             .borders(Borders::ALL)
             .title_style(Style::default().fg(Color::Cyan))
-            .title(&queue_size_title))
-        .data(&slick_queue_data)
-        .max(db.slick_config.max_queue_size as u64)
-        .bar_width(3)
-        .bar_gap(1)
-        .value_style(
-            Style::default()
-                .fg(Color::Black)
-                .bg(Color::Blue)
-        )
-        .style(Style::default().fg(Color::Blue));
-    f.render_widget(slick_queue_bc, chunks[1]);
-}
-
-fn hikari_chart<F>(db: &SlickTab, f: F) -> Vec<(f64, f64)>
-    where F: Fn(&HikariMetrics) -> i32, {
-    db.hikari_metrics.iter().enumerate()
-        .map(|(i, x)| (i as f64, f(x) as f64))
-        .collect()
+            .title(title));
+    f.render_widget(source_list, area);
 }
 
-fn draw_hikari_graphs<B>(f: &mut Frame<B>, db: &SlickTab, area: Rect)
+/// Widgets of a dashboard from the config file, in rows of the given heights and widths
+fn draw_dashboard_tab<B>(f: &mut Frame<B>, app: &App, viewport: &Viewport, tab: &DashboardTab, area: Rect)
     where B: Backend,
 {
-    let total_chart: Vec<(f64, f64)> = hikari_chart(db, |x| x.total);
-    let active_chart: Vec<(f64, f64)> = hikari_chart(db, |x| x.active);
-    let idle_chart: Vec<(f64, f64)> = hikari_chart(db, |x| x.idle);
-    let waiting_chart: Vec<(f64, f64)> = hikari_chart(db, |x| x.waiting);
-
-    let datasets = [
-        Dataset::default()
-            .name("total")
-            .marker(Marker::Braille)
-            .style(Style::default().fg(Color::Blue))
-            .data(&total_chart),
-        Dataset::default()
-            .name("active")
-            .marker(Marker::Braille)
-            .style(Style::default().fg(Color::Red))
-            .data(&active_chart),
-        Dataset::default()
-            .name("waiting")
-            .marker(Marker::Braille)
-            .style(Style::default().fg(Color::Yellow))
-            .data(&waiting_chart),
-        Dataset::default()
-            .name("idle")
-            .marker(Marker::Braille)
-            .style(Style::default().fg(Color::Green))
-            .data(&idle_chart)
-    ];
-
-    let max_connections = db.hikari_metrics.back().map_or(99, |x| x.total);
-    let total_connections = db.hikari_metrics.back().map_or(0, |x| x.total);
-    let active_connections = db.hikari_metrics.back().map_or(0, |x| x.active);
-    let waiting_connections = db.hikari_metrics.back().map_or(0, |x| x.waiting);
-    let idle_connections = db.hikari_metrics.back().map_or(0, |x| x.idle);
-
-    let title = format!(
-        "HikariCP (total={}, active={}, idle={}, waiting={})",
-        total_connections,
-        active_connections,
-        idle_connections,
-        waiting_connections
-    );
-    let label = &["0".to_owned(), ((max_connections as f64) / 2.0).to_string(), max_connections.to_string()];
-    let c = Chart::default()
-        .block(
-            Block::default()
-                .title(&title)
-                .title_style(Style::default().fg(Color::Cyan))
-                .borders(Borders::ALL)
-        )
-        .x_axis(
-            Axis::default()
-                .style(Style::default().fg(Color::Gray))
-                .labels_style(Style::default().modifier(Modifier::ITALIC))
-                .bounds([0.0, SlickTab::MAX_HIKARI_MEASURES as f64])
-                .labels(&["older", "recent"])
-        )
-        .y_axis(
-            Axis::default()
-                .style(Style::default().fg(Color::Gray))
-                .labels_style(Style::default().modifier(Modifier::ITALIC))
-                .bounds([-1.0, (max_connections + 1) as f64])
-                .labels(label)
-        )
-        .datasets(&datasets);
-    f.render_widget(c, area);
+    let heights = dashboard::percentages(&tab.settings.rows.iter().map(|r| r.height).collect::<Vec<_>>());
+    let row_areas = Layout::default()
+        .constraints(heights.iter().map(|h| Constraint::Percentage(*h)).collect::<Vec<_>>())
+        .split(area);
+    let mut series = tab.series.iter();
+    for (row, row_area) in tab.settings.rows.iter().zip(row_areas) {
+        let widths = dashboard::percentages(&row.widgets.iter().map(|w| w.width).collect::<Vec<_>>());
+        let widget_areas = Layout::default()
+            .constraints(widths.iter().map(|w| Constraint::Percentage(*w)).collect::<Vec<_>>())
+            .direction(Direction::Horizontal)
+            .split(row_area);
+        for ((widget, widget_series), widget_area) in row.widgets.iter().zip(&mut series).zip(widget_areas) {
+            let title = widget.title();
+            match &widget.kind {
+                WidgetKind::Chart { .. } => draw_metric_chart(f, viewport, &title, widget_series, "", widget_area),
+                WidgetKind::Values { metric } => draw_metric_values(f, app, metric, &title, widget_area),
+                WidgetKind::Alerts => draw_alert_list(f, app, &title, widget_area),
+                WidgetKind::Sources => draw_source_list(f, app, &title, widget_area),
+                WidgetKind::Errors => draw_errors_tab(f, &app.errors, widget_area),
+            }
+        }
+    }
 }
 
-
-fn draw_zio_tab<B>(f: &mut Frame<B>, zmx: &mut ZMXTab, area: Rect)
+/// Latest value of every series of a metric
+fn draw_metric_values<B>(f: &mut Frame<B>, app: &App, metric: &str, title: &str, area: Rect)
     where B: Backend,
 {
-    let chunks = Layout::default()
-        .constraints([Constraint::Min(7), Constraint::Length(3)].as_ref())
-        .split(area);
-    draw_fiber_list(f, zmx, chunks[0]);
-    draw_text(f, chunks[1]);
+    let rows: Vec<Vec<String>> = app.dashboard_values.iter()
+        .filter(|v| v.metric == metric)
+        .map(|v| vec![if v.series.is_empty() { metric.to_owned() } else { v.series.to_owned() }, format_value(v.value)])
+        .collect();
+    let header = ["series", "value"];
+    report::capture_table(title, &header, &rows);
+    let table = Table::new(header.iter(), rows.iter().map(|r| Row::Data(r.iter())))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title(title))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Percentage(70), Constraint::Percentage(30)]);
+    f.render_widget(table, area);
 }
 
-fn fiber_count_chart<F>(db: &ZMXTab, f: F) -> Vec<(f64, f64)>
-    where F: Fn(&FiberCount) -> i32, {
-    db.fiber_counts.iter().enumerate()
-        .map(|(i, x)| (i as f64, f(x) as f64))
-        .collect()
+fn draw_errors_tab<B>(f: &mut Frame<B>, tab: &ErrorLogTab, area: Rect)
+    where B: Backend,
+{
+    let height = area.height.saturating_sub(3) as usize;
+    let rows: Vec<Vec<String>> = tab.errors.iter().rev().skip(tab.scroll).take(height)
+        .map(|e| vec![
+            e.first_seen.format("%H:%M:%S").to_string(),
+            if e.count > 1 { format!("x{} until {}", e.count, e.last_seen.format("%H:%M:%S")) } else { String::new() },
+            e.source.to_owned(),
+            e.message.to_owned(),
+        ])
+        .collect();
+    let title = if tab.scroll > 0 {
+        format!("Errors - {} errors back", tab.scroll)
+    } else {
+        format!("Errors ({}, <x> export, arrows and <PageUp>/<PageDown> scroll)", tab.errors.len())
+    };
+    let header = ["time", "repeated", "source", "message"];
+    report::capture_table("Errors", &header, &rows);
+    let table = Table::new(header.iter(), rows.iter().map(|r| Row::StyledData(r.iter(), Style::default().fg(Color::Red))))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title(&title))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Length(8), Constraint::Length(18), Constraint::Length(12), Constraint::Min(20)]);
+    f.render_widget(table, area);
 }
 
-fn draw_fiber_list<B>(f: &mut Frame<B>, zmx: &mut ZMXTab, area: Rect)
+fn draw_alerts_tab<B>(f: &mut Frame<B>, app: &App, area: Rect)
     where B: Backend,
 {
-    let constraints = vec![Constraint::Percentage(100)];
+    let alerts = match &app.alert_rules {
+        Some(a) => a,
+        None => return,
+    };
     let chunks = Layout::default()
-        .constraints(constraints)
-        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(alerts.rules.len() as u16 + 3), Constraint::Min(0)].as_ref())
         .split(area);
-    {
-        let chunks = Layout::default()
-            .constraints([Constraint::Percentage(100)].as_ref())
-            .split(chunks[0]);
-        {
-            let chunks = Layout::default()
-                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-                .direction(Direction::Horizontal)
-                .split(chunks[0]);
-            {
-                let chunks = Layout::default()
-                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-                    .split(chunks[0]);
-
-                let items = zmx.fibers.items.iter().map(|i| Text::raw(i));
-
-                let list = List::new(items)
-                    .block(Block::default()
-                        .borders(Borders::ALL)
-                        .title_style(Style::default().fg(Color::Cyan))
-                        .title("Fibers (press <Enter> to take a snapshot)"))
-                    .highlight_style(Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))
-                    .highlight_symbol(">");
-                f.render_stateful_widget(list, chunks[0], &mut zmx.fibers.state);
-
-                let running_chart: Vec<(f64, f64)> = fiber_count_chart(zmx, |x| x.running);
-                let done_chart: Vec<(f64, f64)> = fiber_count_chart(zmx, |x| x.done);
-                let finishing_chart: Vec<(f64, f64)> = fiber_count_chart(zmx, |x| x.finishing);
-                let suspended_chart: Vec<(f64, f64)> = fiber_count_chart(zmx, |x| x.suspended);
-
-                let datasets = [
-                    Dataset::default()
-                        .name("running")
-                        .marker(Marker::Braille)
-                        .style(Style::default().fg(Color::Green))
-                        .data(&running_chart),
-                    Dataset::default()
-                        .name("done")
-                        .marker(Marker::Braille)
-                        .style(Style::default().fg(Color::LightBlue))
-                        .data(&done_chart),
-                    Dataset::default()
-                        .name("finishing")
-                        .marker(Marker::Braille)
-                        .style(Style::default().fg(Color::White))
-                        .data(&finishing_chart),
-                    Dataset::default()
-                        .name("suspended")
-                        .marker(Marker::Braille)
-                        .style(Style::default().fg(Color::Yellow))
-                        .data(&suspended_chart)
-                ];
 
-                let max_fibers = zmx.fiber_counts.iter().map(|x| x.total()).max().unwrap_or(0);
-                let total_fibers = zmx.fiber_counts.back().map_or(0, |x| x.total());
-                let running_fibers = zmx.fiber_counts.back().map_or(0, |x| x.running);
-                let done_fibers = zmx.fiber_counts.back().map_or(0, |x| x.done);
-                let finishing_fibers = zmx.fiber_counts.back().map_or(0, |x| x.finishing);
-                let suspended_fibers = zmx.fiber_counts.back().map_or(0, |x| x.suspended);
-
-                let title = format!(
-                    "Fibers (total={}, running={}, done={}, finishing={}, suspended={})",
-                    total_fibers,
-                    running_fibers,
-                    done_fibers,
-                    finishing_fibers,
-                    suspended_fibers
-                );
-                let label = &["0".to_owned(), ((max_fibers as f64) / 2.0).to_string(), max_fibers.to_string()];
-                let c = Chart::default()
-                    .block(
-                        Block::default()
-                            .title(&title)
-                            .title_style(Style::default().fg(Color::Cyan))
-                            .borders(Borders::ALL)
-                    )
-                    .x_axis(
-                        Axis::default()
-                            .style(Style::default().fg(Color::Gray))
-                            .labels_style(Style::default().modifier(Modifier::ITALIC))
-                            .bounds([0.0, ZMXTab::MAX_FIBER_COUNT_MEASURES as f64])
-                            .labels(&["older", "recent"])
-                    )
-                    .y_axis(
-                        Axis::default()
-                            .style(Style::default().fg(Color::Gray))
-                            .labels_style(Style::default().modifier(Modifier::ITALIC))
-                            .bounds([-1.0, (max_fibers + 1) as f64])
-                            .labels(label)
-                    )
-                    .datasets(&datasets);
-                f.render_widget(c, chunks[1]);
-            }
+    let rules: Vec<(Vec<String>, Style)> = alerts.rules.iter().enumerate()
+        .map(|(i, r)| {
+            let firing: Vec<&str> = alerts.firing.iter().filter(|(f, _)| *f == i).map(|(_, e)| e.message.as_str()).collect();
+            let (state, style) = if firing.is_empty() {
+                ("ok".to_owned(), Style::default().fg(Color::Green))
+            } else {
+                (format!("FIRING {}", firing.join(", ")), Style::default().fg(Color::Red).modifier(Modifier::BOLD))
+            };
+            let condition = match &r.series {
+                Some(s) => format!("{}{{{}}} {} {}", r.metric, s, r.comparison, r.threshold),
+                None => format!("{} {} {}", r.metric, r.comparison, r.threshold),
+            };
+            (vec![r.name.to_owned(), condition, format!("{}s", r.duration), state], style)
+        })
+        .collect();
+    let header = ["rule", "condition", "for", "state"];
+    report::capture_table("Alert rules", &header, rules.iter().map(|(r, _)| r));
+    let table = Table::new(header.iter(), rules.iter().map(|(r, style)| Row::StyledData(r.iter(), *style)))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("Rules"))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Length(20), Constraint::Length(40), Constraint::Length(6), Constraint::Min(20)]);
+    f.render_widget(table, chunks[0]);
 
-            let text = [Text::raw(zmx.selected_fiber_dump.0.to_owned())];
-
-            let p = Paragraph::new(text.iter())
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .title("Fiber dump (press <PageUp>/<PageDown> to scroll)")
-                        .title_style(Style::default().fg(Color::Cyan)),
-                )
-                .wrap(true)
-                .scroll(zmx.scroll);
-            f.render_widget(p, chunks[1]);
-        }
-    }
+    let height = chunks[1].height.saturating_sub(3) as usize;
+    let history: Vec<(Vec<String>, Style)> = alerts.history.iter().rev().take(height)
+        .map(|e| {
+            let (state, color) = if e.firing { ("firing", Color::Red) } else { ("resolved", Color::Green) };
+            (vec![e.timestamp.format("%H:%M:%S").to_string(), e.rule.to_owned(), state.to_owned(), e.message.to_owned()], Style::default().fg(color))
+        })
+        .collect();
+    let header = ["time", "rule", "state", "message"];
+    report::capture_table("Alert history", &header, history.iter().map(|(r, _)| r));
+    let table = Table::new(header.iter(), history.iter().map(|(r, style)| Row::StyledData(r.iter(), *style)))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("History"))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Length(8), Constraint::Length(20), Constraint::Length(8), Constraint::Min(20)]);
+    f.render_widget(table, chunks[1]);
 }
 
-fn draw_actor_tree_tab<B>(f: &mut Frame<B>, tab: &mut AkkaActorTreeTab, area: Rect)
+fn draw_actor_tree_tab<B>(f: &mut Frame<B>, viewport: &Viewport, tab: &mut AkkaActorTreeTab, area: Rect)
     where B: Backend,
 {
     let chunks = Layout::default()
@@ -361,43 +2425,266 @@ fn draw_actor_tree_tab<B>(f: &mut Frame<B>, tab: &mut AkkaActorTreeTab, area: Re
         let chunks = Layout::default()
             .constraints([Constraint::Percentage(70), Constraint::Percentage(30)].as_ref())
             .split(chunks[0]);
-        draw_actor_tree(f, tab, chunks[0]);
-        draw_actor_count_chart(f, tab, chunks[1]);
+        let top = Layout::default()
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)].as_ref())
+            .direction(Direction::Horizontal)
+            .split(chunks[0]);
+        draw_actor_tree(f, tab, top[0]);
+        let has_watches = !tab.watches.is_empty();
+        let panel_count = 1 + has_watches as u32 + tab.has_mailboxes as u32 + tab.has_dead_letters as u32;
+        let side = Layout::default()
+            .constraints(vec![Constraint::Ratio(1, panel_count); panel_count as usize])
+            .split(top[1]);
+        let mut panels = side.iter();
+        if has_watches {
+            draw_actor_watches(f, tab, *panels.next().unwrap());
+        }
+        draw_subtree_counts(f, tab, *panels.next().unwrap());
+        if tab.has_mailboxes {
+            draw_top_mailboxes(f, tab, *panels.next().unwrap());
+        }
+        if tab.has_dead_letters {
+            draw_dead_letters(f, tab, *panels.next().unwrap());
+        }
+
+        let chart_count = 1 + tab.has_dead_letters as u32 + 2 * tab.has_dispatchers as u32;
+        let charts = Layout::default()
+            .constraints(vec![Constraint::Ratio(1, chart_count); chart_count as usize])
+            .direction(Direction::Horizontal)
+            .split(chunks[1]);
+        draw_actor_count_chart(f, tab, charts[0]);
+        if tab.has_dead_letters {
+            draw_dead_letters_chart(f, tab, charts[1]);
+        }
+        if tab.has_dispatchers {
+            draw_dispatcher_charts(f, viewport, tab, charts[chart_count as usize - 2], charts[chart_count as usize - 1]);
+        }
     }
-    draw_text(f, chunks[1]);
+    draw_text(f, chunks[1], Some(tab.status_message.as_deref().unwrap_or("Press <x> to export the actor tree as DOT and JSON files, <f> to show/hide excluded actors, <w> to watch the selected actor")));
 }
 
 
 fn draw_actor_tree<B>(f: &mut Frame<B>, tab: &mut AkkaActorTreeTab, area: Rect)
     where B: Backend,
 {
-    let items = tab.actors.items.iter().map(|i| Text::raw(i));
+    let chunks = Layout::default()
+        .constraints([Constraint::Min(5), Constraint::Length(7)].as_ref())
+        .split(area);
+    draw_actor_details(f, tab, chunks[1]);
+    let area = chunks[0];
+
+    tab.actors.on_draw(list_area(area));
+    let changes = &tab.actor_changes;
+    let watches = &tab.watches;
+    let watched = |path: &str| watches.iter().any(|w| matches_actor_pattern(path, &w.pattern));
+    let mailboxes = if tab.has_mailboxes { Some(&tab.actor_stats) } else { None };
+    let width = tab.actor_label_width;
+    // only the lines drawn are formatted, trees can have tens of thousands of actors
+    let visible = tab.actors.visible();
+    let items = tab.actors.items[visible.clone()].iter().zip(tab.actor_paths[visible].iter()).map(|(i, path)| {
+        let line = match mailboxes {
+            Some(sizes) => format!("{:width$} {:>8}", i, sizes.get(path).and_then(|s| s.mailbox).map_or("-".to_owned(), |s| s.to_string()), width = width),
+            None => i.to_owned(),
+        };
+        // not only told by the color
+        let line = match changes.get(path) {
+            Some((ActorChange::Appeared, _)) => format!("{} (new)", line),
+            Some((ActorChange::Disappeared, _)) => format!("{} (gone)", line),
+            None => line,
+        };
+        let style = match changes.get(path) {
+            Some((ActorChange::Appeared, _)) => Style::default().fg(Color::Green),
+            Some((ActorChange::Disappeared, _)) => Style::default().fg(Color::Red),
+            None if watched(path) => Style::default().fg(Color::Magenta),
+            None => Style::default(),
+        };
+        if watched(path) {
+            Text::styled(line, style.modifier(Modifier::BOLD))
+        } else {
+            Text::styled(line, style)
+        }
+    });
 
+    let title = match tab.tree_fetch {
+        Some((_, started)) => {
+            let elapsed = started.elapsed().as_secs();
+            format!("Actors {} reloading the tree for {}s, press <Esc> to cancel", ['|', '/', '-', '\\'][elapsed as usize % 4], elapsed)
+        }
+        None => "Actors (press <Enter> to reload the tree, <Space> to collapse/expand, <c>/<e> to collapse/expand all)".to_owned(),
+    };
     let list = List::new(items)
         .block(Block::default()
             .borders(Borders::ALL)
             .title_style(Style::default().fg(Color::Cyan))
-            .title("Actors (press <Enter> to reload the tree)"))
+            .title(&title))
         .highlight_style(Style::default().fg(Color::Yellow).modifier(Modifier::BOLD))
         .highlight_symbol(">");
 
-    f.render_stateful_widget(list, area, &mut tab.actors.state);
+    f.render_stateful_widget(list, area, &mut tab.actors.visible_state());
+    draw_list_scrollbar(f, &tab.actors, area);
+}
+
+fn draw_actor_details<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, area: Rect)
+    where B: Backend,
+{
+    let text: Vec<Text> = match tab.selected_actor_details() {
+        None => vec![Text::raw("Select an actor to see its details")],
+        Some(d) => {
+            let mut lines = vec![
+                format!("Path: {}\n", d.path),
+                format!("Parent: {}\n", d.parent.unwrap_or("-")),
+                format!("Children: {} ({} descendants)\n", d.children, d.descendants),
+                match d.first_seen {
+                    Some(t) => format!("First seen: {}\n", t.format("%Y-%m-%d %H:%M:%S")),
+                    None => "First seen: - (gone)\n".to_owned(),
+                },
+            ];
+            if let Some(stats) = d.stats {
+                let or_dash = |v: Option<u64>| v.map_or("-".to_owned(), |v| v.to_string());
+                lines.push(format!(
+                    "Mailbox: {}, messages: {}, processing time: {}\n",
+                    or_dash(stats.mailbox),
+                    or_dash(stats.messages),
+                    stats.processing_time_ms.map_or("-".to_owned(), |t| format!("{:.2}ms", t))
+                ));
+            }
+            lines.into_iter().map(Text::raw).collect()
+        }
+    };
+    let p = Paragraph::new(text.iter())
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("Actor details"));
+    f.render_widget(p, area);
+}
+
+fn draw_actor_watches<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, area: Rect)
+    where B: Backend,
+{
+    let rows: Vec<Vec<String>> = tab.watches.iter()
+        .map(|w| vec![
+            w.pattern.to_owned(),
+            w.matches.to_string(),
+            w.presence.iter().map(|p| if *p { '█' } else { '·' }).collect(),
+        ])
+        .collect();
+    let header = ["watched", "actors", "presence on reloads"];
+    report::capture_table("Watched actors", &header, &rows);
+    let table = Table::new(header.iter(), rows.iter().map(|r| Row::Data(r.iter())))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("Watched actors"))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Percentage(40), Constraint::Percentage(15), Constraint::Percentage(45)]);
+    f.render_widget(table, area);
+}
+
+fn draw_subtree_counts<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, area: Rect)
+    where B: Backend,
+{
+    let rows: Vec<Vec<String>> = tab.displayed_subtree_counts().iter()
+        .map(|(path, count, change)| vec![path.to_owned(), count.to_string(), format!("{:+}", change)])
+        .collect();
+    let header = ["subtree", "actors", "change"];
+    report::capture_table("Actors by subtree", &header, &rows);
+    let table = Table::new(header.iter(), rows.iter().map(|r| Row::Data(r.iter())))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("Actors by subtree"))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Percentage(60), Constraint::Percentage(20), Constraint::Percentage(20)]);
+    f.render_widget(table, area);
+}
+
+fn draw_top_mailboxes<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, area: Rect)
+    where B: Backend,
+{
+    let rows: Vec<Vec<String>> = tab.top_mailboxes().iter()
+        .map(|(path, size)| vec![path.to_string(), size.to_string()])
+        .collect();
+    let header = ["actor", "mailbox"];
+    report::capture_table("Biggest mailboxes", &header, &rows);
+    let table = Table::new(header.iter(), rows.iter().map(|r| Row::Data(r.iter())))
+        .block(Block::default()
+            .borders(Borders::ALL)
+            .title_style(Style::default().fg(Color::Cyan))
+            .title("Biggest mailboxes"))
+        .header_style(Style::default().fg(Color::Yellow))
+        .widths(&[Constraint::Percentage(80), Constraint::Percentage(20)]);
+    f.render_widget(table, area);
+}
+
+fn draw_dead_letters<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, area: Rect)
+    where B: Backend,
+{
+    // most recent dead letters go first
+    let text: Vec<Text> = tab.dead_letters.iter().rev()
+        .map(|d| Text::raw(format!(
+            "{} {} -> {}: {}\n",
+            d.timestamp.format("%H:%M:%S%.3f"),
+            d.sender,
+            d.recipient,
+            d.message
+        )))
+        .collect();
+
+    let title = format!("Dead letters: {} (press <PageUp>/<PageDown> to scroll)", tab.dead_letters.len());
+    let p = Paragraph::new(text.iter())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(&title)
+                .title_style(Style::default().fg(Color::Cyan)),
+        )
+        .scroll(tab.dead_letters_scroll);
+    f.render_widget(p, area);
+}
+
+fn draw_dead_letters_chart<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, area: Rect)
+    where B: Backend,
+{
+    let data: Vec<(&str, u64)> = tab.dead_letter_counts.iter()
+        .map(|(_, c)| ("", *c))
+        .collect();
+    let title = format!(
+        "Dead letters per tick: {} (total: {})",
+        tab.dead_letter_counts.back().map_or(0, |(_, c)| *c),
+        tab.dead_letter_total.unwrap_or(0)
+    );
+    draw_bar_chart(f, area, &title, &data, None, Color::Red);
 }
 
 fn draw_actor_count_chart<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, area: Rect)
     where B: Backend,
 {
-    let data: Vec<(&str, u64)> = tab.actor_counts.iter()
+    let counts = tab.displayed_actor_counts();
+    let data: Vec<(&str, u64)> = counts.iter()
         .map(|x| ("", x.to_owned()))
         .collect();
 
-    let title = format!("Running actors: {}", tab.actor_counts.back().unwrap_or(&0));
+    let mut title = match tab.actor_counts.back() {
+        Some(c) if c.excluded > 0 && !tab.show_excluded =>
+            format!("Running actors: {} ({} excluded)", counts.last().unwrap_or(&0), c.excluded),
+        _ => format!("Running actors: {}", counts.last().unwrap_or(&0)),
+    };
+    if let Some(rate) = tab.actor_growth_per_minute() {
+        title.push_str(&format!(", {:+.1}/min", rate));
+    }
+    let border_style = if tab.count_alert.is_some() {
+        Style::default().fg(Color::Red)
+    } else {
+        Style::default()
+    };
     let count_bc = BarChart::default()
         .block(Block::default()
             .borders(Borders::ALL)
+            .border_style(border_style)
             .title_style(Style::default().fg(Color::Cyan))
             .title(&title))
-        .data(&data)
+        .data(latest_bars(&data, area))
         .bar_width(3)
         .bar_gap(1)
         .value_style(
@@ -408,3 +2695,80 @@ fn draw_actor_count_chart<B>(f: &mut Frame<B>, tab: &AkkaActorTreeTab, area: Rec
         .style(Style::default().fg(Color::Green));
     f.render_widget(count_bc, area);
 }
+
+#[cfg(test)]
+mod tests {
+    use tui::backend::TestBackend;
+    use tui::Terminal;
+
+    use panopticon_core::error::FetchError;
+    use panopticon_core::fetcher::{FetcherResponse, Sources};
+
+    use crate::app::App;
+    use crate::config::Config;
+    use crate::ui::{format_value, LastFrame, nice_ceiling, numbered_lines, redraw, Redraw};
+
+    #[test]
+    fn only_the_status_bar_is_drawn_again_when_nothing_else_changed() {
+        let mut app = App::new("before", &Sources::default(), &Config::default());
+        let mut terminal = Terminal::new(TestBackend::new(80, 8)).unwrap();
+        let mut last = LastFrame::default();
+        let line = |t: &Terminal<TestBackend>, y: u16| (0..80).map(|x| t.backend().buffer().get(x, y).symbol.as_str()).collect::<String>();
+        redraw(&mut terminal, &mut app, &mut last, Redraw::All).unwrap();
+        assert!(line(&terminal, 0).contains("before"));
+
+        app.title = "after";
+        app.on_fetcher_response(&FetcherResponse::PrometheusMetrics(Err(FetchError::timeout("timed out"))));
+        redraw(&mut terminal, &mut app, &mut last, Redraw::StatusBar).unwrap();
+        assert!(line(&terminal, 0).contains("before"));
+        assert!(line(&terminal, 7).contains("Prometheus: timed out"));
+
+        redraw(&mut terminal, &mut app, &mut last, Redraw::All).unwrap();
+        assert!(line(&terminal, 0).contains("after"));
+    }
+
+    #[test]
+    fn the_frame_shown_when_frozen_is_kept_until_unfrozen() {
+        let mut app = App::new("before", &Sources::default(), &Config::default());
+        let mut terminal = Terminal::new(TestBackend::new(80, 8)).unwrap();
+        let mut last = LastFrame::default();
+        let line = |t: &Terminal<TestBackend>, y: u16| (0..80).map(|x| t.backend().buffer().get(x, y).symbol.as_str()).collect::<String>();
+        redraw(&mut terminal, &mut app, &mut last, Redraw::All).unwrap();
+
+        app.toggle_freeze();
+        app.title = "after";
+        app.on_fetcher_response(&FetcherResponse::PrometheusMetrics(Err(FetchError::timeout("timed out"))));
+        redraw(&mut terminal, &mut app, &mut last, Redraw::All).unwrap();
+        assert!(line(&terminal, 0).contains("before"));
+        assert!(line(&terminal, 7).contains("FROZEN, 1 updates since <z>"));
+
+        app.toggle_freeze();
+        redraw(&mut terminal, &mut app, &mut last, Redraw::All).unwrap();
+        assert!(line(&terminal, 0).contains("after"));
+    }
+
+    #[test]
+    fn chart_scale_is_rounded_up() {
+        assert_eq!(nice_ceiling(0.0), 1.0);
+        assert_eq!(nice_ceiling(7.0), 10.0);
+        assert_eq!(nice_ceiling(10.0), 10.0);
+        assert_eq!(nice_ceiling(130.0), 200.0);
+        assert_eq!(nice_ceiling(0.3), 0.5);
+    }
+
+    #[test]
+    fn chart_values_are_formatted_compactly() {
+        assert_eq!(format_value(5.0), "5");
+        assert_eq!(format_value(2.5), "2.5");
+        assert_eq!(format_value(0.25), "0.25");
+        assert_eq!(format_value(1.10), "1.1");
+    }
+
+    #[test]
+    fn dump_lines_are_numbered() {
+        let dump = (1..=10).map(|i| format!("line {}", i)).collect::<Vec<String>>().join("\n");
+        let numbered = numbered_lines(&dump);
+        let lines: Vec<&str> = numbered.lines().collect();
+        assert_eq!((lines[0], lines[9]), (" 1 │ line 1", "10 │ line 10"));
+    }
+}
@@ -0,0 +1,68 @@
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::widgets::Widget;
+
+///
+/// Redraws what's already in the area with ASCII lookalikes of box-drawing, block and braille characters.
+///
+/// Drawn last, for terminals and fonts that show those as tofu. Other characters are left alone.
+pub struct Ascii;
+
+impl Widget for Ascii {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buf.get_mut(x, y);
+                if let Some(ascii) = cell.symbol.chars().next().and_then(to_ascii) {
+                    cell.set_char(ascii);
+                }
+            }
+        }
+    }
+}
+
+fn to_ascii(c: char) -> Option<char> {
+    let ascii = match c {
+        '─' | '━' | '═' | '┄' | '┅' | '╌' | '╍' => '-',
+        '│' | '┃' | '║' | '┆' | '┇' | '╎' | '╏' => '|',
+        // corners and junctions
+        '┌'..='╋' | '╒'..='╬' | '╭'..='╰' => '+',
+        // sparkline bars and gauges, from the lowest
+        '▁' | '▂' => '_',
+        '▃' | '▄' | '▅' => '=',
+        '▀'..='▟' => '#',
+        '•' | '●' => '*',
+        '·' => '.',
+        '\u{2800}' => ' ',
+        // chart lines
+        '\u{2801}'..='\u{28ff}' => '.',
+        '▲' | '↑' => '^',
+        '▼' | '↓' => 'v',
+        '→' | '▶' => '>',
+        '←' | '◀' => '<',
+        'µ' => 'u',
+        '…' => '~',
+        _ => return None,
+    };
+    Some(ascii)
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::buffer::Buffer;
+    use tui::layout::Rect;
+    use tui::widgets::Widget;
+
+    use crate::widgets::ascii::Ascii;
+
+    #[test]
+    fn glyphs_are_drawn_as_ascii() {
+        let area = Rect::new(0, 0, 12, 3);
+        let mut buf = Buffer::empty(area);
+        buf.set_string(0, 0, "┌─Fibers───┐", Default::default());
+        buf.set_string(0, 1, "│├─#2 ⣀⡠ █▂│", Default::default());
+        buf.set_string(0, 2, "└──────────┘", Default::default());
+        Ascii.render(area, &mut buf);
+        assert_eq!(buf, Buffer::with_lines(vec!["+-Fibers---+", "|+-#2 .. #_|", "+----------+"]));
+    }
+}
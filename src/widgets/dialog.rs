@@ -0,0 +1,86 @@
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::{Color, Modifier, Style};
+use tui::widgets::{Block, Borders, Clear, Paragraph, Text, Widget};
+
+///
+/// Modal question over the middle of the area, answered with <y> or <n>.
+///
+/// Shown before heavyweight or destructive actions, with what they cost or risk in a warning below the question.
+pub struct ConfirmationDialog<'a> {
+    title: &'a str,
+    question: &'a str,
+    warning: Option<&'a str>,
+}
+
+impl<'a> ConfirmationDialog<'a> {
+    const ANSWERS: &'static str = "<y> confirm   <n>/<Esc> cancel";
+
+    pub fn new(title: &'a str, question: &'a str) -> ConfirmationDialog<'a> {
+        ConfirmationDialog { title, question, warning: None }
+    }
+
+    pub fn warning(mut self, warning: Option<&'a str>) -> ConfirmationDialog<'a> {
+        self.warning = warning;
+        self
+    }
+
+    /// Lines of the text wrapped within the width, roughly as tui wraps words
+    fn wrapped_lines(text: &str, width: u16) -> u16 {
+        let width = width.max(1) as usize;
+        (text.chars().count().max(1) as f64 / width as f64).ceil() as u16
+    }
+}
+
+impl<'a> Widget for ConfirmationDialog<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let paragraphs = [Some(self.question), self.warning, Some(ConfirmationDialog::ANSWERS)];
+        let longest = paragraphs.iter().flatten().map(|p| p.chars().count() as u16).max().unwrap_or(0);
+        let width = (longest + 4).min(area.width.saturating_sub(4)).max(ConfirmationDialog::ANSWERS.len() as u16 + 4).min(area.width);
+        let inner_width = width.saturating_sub(2);
+        // a blank line before the answers
+        let lines: u16 = paragraphs.iter().flatten().map(|p| ConfirmationDialog::wrapped_lines(p, inner_width)).sum::<u16>() + 1;
+        let height = (lines + 2).min(area.height);
+        let popup = Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height);
+
+        let mut text = vec![Text::raw(format!("{}\n", self.question))];
+        if let Some(w) = self.warning {
+            text.push(Text::styled(format!("{}\n", w), Style::default().fg(Color::Red)));
+        }
+        text.push(Text::styled(format!("\n{}", ConfirmationDialog::ANSWERS), Style::default().fg(Color::Yellow)));
+        Clear.render(popup, buf);
+        Paragraph::new(text.iter())
+            .block(Block::default()
+                .borders(Borders::ALL)
+                .title(self.title)
+                .title_style(Style::default().fg(Color::Red).modifier(Modifier::BOLD)))
+            .wrap(true)
+            .render(popup, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::buffer::Buffer;
+    use tui::layout::Rect;
+    use tui::widgets::Widget;
+
+    use crate::widgets::dialog::ConfirmationDialog;
+
+    #[test]
+    fn dialog_fits_the_question_warning_and_answers() {
+        let area = Rect::new(0, 0, 60, 12);
+        let mut buf = Buffer::empty(area);
+        ConfirmationDialog::new("Heap dump", "Dump heap to /tmp/heap.hprof?")
+            .warning(Some("The JVM is paused while the heap is written"))
+            .render(area, &mut buf);
+        let lines: Vec<String> = (0..area.height)
+            .map(|y| (0..area.width).map(|x| buf.get(x, y).symbol.clone()).collect::<String>().trim().to_owned())
+            .collect();
+        assert_eq!(lines[3], "┌Heap dump────────────────────────────────────┐");
+        assert_eq!(lines[4], "│Dump heap to /tmp/heap.hprof?                │");
+        assert_eq!(lines[5], "│The JVM is paused while the heap is written  │");
+        assert_eq!(lines[7], "│<y> confirm   <n>/<Esc> cancel               │");
+        assert_eq!(lines[8], "└─────────────────────────────────────────────┘");
+    }
+}
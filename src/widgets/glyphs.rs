@@ -0,0 +1,48 @@
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::Color;
+use tui::widgets::Widget;
+
+///
+/// Redraws the points of the series of a chart already in the area with a glyph of their own, told by their color,
+/// so that series can be told apart without colors too.
+///
+/// Charts only draw braille or dot markers, which look the same for every series.
+pub struct SeriesGlyphs<'a>(pub &'a [(Color, char)]);
+
+impl<'a> Widget for SeriesGlyphs<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let cell = buf.get_mut(x, y);
+                let is_point = matches!(cell.symbol.chars().next(), Some('\u{2801}'..='\u{28ff}') | Some('•'));
+                if let Some((_, glyph)) = self.0.iter().find(|(color, _)| is_point && cell.style.fg == *color) {
+                    cell.set_char(*glyph);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::buffer::Buffer;
+    use tui::layout::Rect;
+    use tui::style::{Color, Style};
+    use tui::widgets::Widget;
+
+    use crate::widgets::glyphs::SeriesGlyphs;
+
+    #[test]
+    fn points_are_redrawn_with_the_glyph_of_their_series() {
+        let area = Rect::new(0, 0, 6, 1);
+        let mut buf = Buffer::empty(area);
+        buf.set_string(0, 0, "⣀⡠", Style::default().fg(Color::Green));
+        buf.set_string(2, 0, "⠉", Style::default().fg(Color::Yellow));
+        buf.set_string(3, 0, "⠉", Style::default().fg(Color::Gray));
+        buf.set_string(4, 0, "ok", Style::default().fg(Color::Green));
+        SeriesGlyphs(&[(Color::Green, '*'), (Color::Yellow, 'o')]).render(area, &mut buf);
+        let symbols: String = buf.content.iter().map(|c| c.symbol.as_str()).collect();
+        assert_eq!(symbols, "**o⠉ok");
+    }
+}
@@ -0,0 +1,63 @@
+///
+/// Distribution of samples over equally sized buckets, from 0 to a maximum.
+///
+/// Unlike a time series, it shows how often a value was reached over the whole window, so short
+/// spikes stand out even when they are outnumbered by quiet samples.
+#[derive(Debug, PartialEq)]
+pub struct Histogram {
+    /// Label and number of samples of each bucket
+    pub buckets: Vec<(String, u64)>,
+    pub samples: u64,
+}
+
+impl Histogram {
+    /// Samples above `max` are counted in the last bucket. If `max` is unknown (0), the biggest sample is used.
+    pub fn new(samples: &[u64], max: u64, buckets: usize) -> Histogram {
+        let max = if max > 0 { max } else { samples.iter().cloned().max().unwrap_or(0) };
+        let width = ((max + 1) as f64 / buckets.max(1) as f64).ceil() as u64;
+        let count = ((max + 1) as f64 / width as f64).ceil() as usize;
+
+        let mut data: Vec<(String, u64)> = (0..count as u64)
+            .map(|i| {
+                let low = i * width;
+                let high = (low + width - 1).min(max);
+                let label = if low == high { low.to_string() } else { format!("{}-{}", low, high) };
+                (label, 0)
+            })
+            .collect();
+        for s in samples {
+            let i = ((s / width) as usize).min(count - 1);
+            data[i].1 += 1;
+        }
+        Histogram { buckets: data, samples: samples.len() as u64 }
+    }
+
+    pub fn data(&self) -> Vec<(&str, u64)> {
+        self.buckets.iter().map(|(label, n)| (label.as_str(), *n)).collect()
+    }
+
+    /// Percentage of samples in the last bucket
+    pub fn top_percentage(&self) -> f64 {
+        match self.buckets.last() {
+            Some((_, n)) if self.samples > 0 => *n as f64 * 100.0 / self.samples as f64,
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::widgets::histogram::Histogram;
+
+    #[test]
+    fn samples_are_counted_per_bucket() {
+        let h = Histogram::new(&[0, 1, 5, 9, 10, 10, 12], 10, 4);
+        assert_eq!(h.data(), vec![("0-2", 2), ("3-5", 1), ("6-8", 0), ("9-10", 4)]);
+        assert_eq!(h.top_percentage(), 400.0 / 7.0);
+
+        let h = Histogram::new(&[0, 2, 2], 0, 8);
+        assert_eq!(h.data(), vec![("0", 1), ("1", 0), ("2", 2)]);
+
+        assert_eq!(Histogram::new(&[], 0, 8).data(), vec![("0", 0)]);
+    }
+}
@@ -1 +1,10 @@
+pub mod ascii;
+pub mod dialog;
+pub mod glyphs;
+pub mod histogram;
+pub mod mouse;
+pub mod palette;
+pub mod scrollbar;
+pub mod split;
 pub mod tree;
+pub mod viewport;
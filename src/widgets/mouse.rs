@@ -0,0 +1,51 @@
+use tui::layout::Rect;
+
+/// Whether the cell at the column and row is within the area
+pub fn contains(area: Rect, column: u16, row: u16) -> bool {
+    column >= area.left() && column < area.right() && row >= area.top() && row < area.bottom()
+}
+
+///
+/// Areas of the titles of a bordered `Tabs` widget, laid out the way tui does.
+///
+/// Every title is preceded by a space and followed by a space and a one column divider.
+/// Titles that don't fit aren't drawn, so they get no area.
+pub fn tab_title_areas(area: Rect, titles: &[String]) -> Vec<Rect> {
+    let inner = Rect::new(area.x + 1, area.y + 1, area.width.saturating_sub(2), area.height.saturating_sub(2));
+    let mut areas = vec![];
+    if inner.height == 0 {
+        return areas;
+    }
+    let mut x = inner.left();
+    for title in titles {
+        x += 1;
+        if x > inner.right() {
+            break;
+        }
+        let width = (title.chars().count() as u16).min(inner.right() - x);
+        areas.push(Rect::new(x, inner.top(), width, 1));
+        x += width + 2;
+        if x >= inner.right() {
+            break;
+        }
+    }
+    areas
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::layout::Rect;
+
+    use crate::widgets::mouse::{contains, tab_title_areas};
+
+    #[test]
+    fn tab_titles_are_hit() {
+        let titles = vec!["ZMX".to_owned(), "Slick".to_owned(), "Logs".to_owned()];
+        let areas = tab_title_areas(Rect::new(0, 0, 14, 3), &titles);
+        // │ ZMX │ Slick│
+        assert_eq!(areas, vec![Rect::new(2, 1, 3, 1), Rect::new(8, 1, 5, 1)]);
+        assert!(contains(areas[1], 8, 1));
+        assert!(!contains(areas[1], 13, 1));
+        assert!(!contains(areas[1], 8, 2));
+    }
+}
@@ -0,0 +1,75 @@
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::{Color, Modifier};
+use tui::widgets::Widget;
+
+///
+/// Redraws what's already in the area with a high-contrast palette that doesn't tell red from green.
+///
+/// Green turns blue and red turns bold orange, which stay apart with any kind of color blindness,
+/// other colors get brighter versions so that chart lines stand out on dark backgrounds.
+/// Drawn last, so that every tab gets it.
+pub struct HighContrast;
+
+impl HighContrast {
+    const BLUE: Color = Color::Indexed(33);
+    const ORANGE: Color = Color::Indexed(208);
+    const PINK: Color = Color::Indexed(213);
+    const SKY_BLUE: Color = Color::Indexed(117);
+    const YELLOW: Color = Color::Indexed(226);
+    const GRAY: Color = Color::Indexed(250);
+}
+
+impl Widget for HighContrast {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        for y in area.top()..area.bottom() {
+            for x in area.left()..area.right() {
+                let style = &mut buf.get_mut(x, y).style;
+                if matches!(style.fg, Color::Red | Color::LightRed) || matches!(style.bg, Color::Red | Color::LightRed) {
+                    style.modifier |= Modifier::BOLD;
+                }
+                style.fg = high_contrast(style.fg);
+                if style.bg != Color::Reset {
+                    style.bg = high_contrast(style.bg);
+                    // light backgrounds need dark text
+                    style.fg = Color::Black;
+                }
+            }
+        }
+    }
+}
+
+fn high_contrast(color: Color) -> Color {
+    match color {
+        Color::Green | Color::LightGreen => HighContrast::BLUE,
+        Color::Red | Color::LightRed => HighContrast::ORANGE,
+        Color::Magenta | Color::LightMagenta => HighContrast::PINK,
+        Color::Blue | Color::LightBlue => HighContrast::SKY_BLUE,
+        Color::Yellow | Color::LightYellow => HighContrast::YELLOW,
+        Color::DarkGray | Color::Gray => HighContrast::GRAY,
+        c => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tui::buffer::Buffer;
+    use tui::layout::Rect;
+    use tui::style::{Color, Modifier, Style};
+    use tui::widgets::Widget;
+
+    use crate::widgets::palette::HighContrast;
+
+    #[test]
+    fn red_and_green_are_told_apart() {
+        let area = Rect::new(0, 0, 4, 1);
+        let mut buf = Buffer::empty(area);
+        buf.set_string(0, 0, "ok", Style::default().fg(Color::Green));
+        buf.set_string(2, 0, "!", Style::default().fg(Color::Red));
+        buf.set_string(3, 0, " ", Style::default().fg(Color::White).bg(Color::Red));
+        HighContrast.render(area, &mut buf);
+        assert_eq!(buf.get(0, 0).style, Style::default().fg(HighContrast::BLUE));
+        assert_eq!(buf.get(2, 0).style, Style::default().fg(HighContrast::ORANGE).modifier(Modifier::BOLD));
+        assert_eq!(buf.get(3, 0).style, Style::default().fg(Color::Black).bg(HighContrast::ORANGE).modifier(Modifier::BOLD));
+    }
+}
@@ -0,0 +1,82 @@
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::Style;
+use tui::widgets::Widget;
+
+///
+/// Scrollbar drawn over the right border of a bordered block, with a position label on its bottom border.
+///
+/// The thumb only shows up once the content doesn't fit, and is sized to the part of it that's visible.
+pub struct Scrollbar<'a> {
+    /// Index of the first visible line or item
+    offset: usize,
+    length: usize,
+    label: Option<&'a str>,
+    style: Style,
+}
+
+impl<'a> Scrollbar<'a> {
+    pub fn new(offset: usize, length: usize) -> Scrollbar<'a> {
+        Scrollbar { offset, length, label: None, style: Style::default() }
+    }
+
+    pub fn label(mut self, label: &'a str) -> Scrollbar<'a> {
+        self.label = Some(label);
+        self
+    }
+
+    pub fn style(mut self, style: Style) -> Scrollbar<'a> {
+        self.style = style;
+        self
+    }
+}
+
+impl<'a> Widget for Scrollbar<'a> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        if area.width < 2 || area.height < 3 {
+            return;
+        }
+        let track = area.height - 2;
+        if let Some((start, size)) = thumb(track, self.offset, self.length) {
+            for y in start..start + size {
+                buf.get_mut(area.right() - 1, area.top() + 1 + y).set_symbol("█").set_style(self.style);
+            }
+        }
+        if let Some(label) = self.label {
+            let label = format!(" {} ", label);
+            let width = label.chars().count() as u16;
+            // leaves the corners and some of the border around
+            if width + 4 <= area.width {
+                buf.set_string(area.right() - 2 - width, area.bottom() - 1, &label, self.style);
+            }
+        }
+    }
+}
+
+/// Start and size of the thumb within a track of `track` rows, `None` if all of the content fits
+fn thumb(track: u16, offset: usize, length: usize) -> Option<(u16, u16)> {
+    let visible = track as usize;
+    if length <= visible || track == 0 {
+        return None;
+    }
+    let size = (visible * visible / length).max(1) as u16;
+    let offset = offset.min(length - visible);
+    let start = ((track - size) as usize * offset + (length - visible) / 2) / (length - visible);
+    Some((start as u16, size))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::widgets::scrollbar::thumb;
+
+    #[test]
+    fn thumb_is_sized_and_placed_by_the_visible_part() {
+        assert_eq!(thumb(10, 0, 10), None);
+        assert_eq!(thumb(10, 0, 40), Some((0, 2)));
+        assert_eq!(thumb(10, 15, 40), Some((4, 2)));
+        assert_eq!(thumb(10, 30, 40), Some((8, 2)));
+        assert_eq!(thumb(10, 100, 40), Some((8, 2)));
+        assert_eq!(thumb(4, 500, 1000), Some((2, 1)));
+        assert_eq!(thumb(4, 996, 1000), Some((3, 1)));
+    }
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+///
+/// Sizes of panes, as offsets in percentage points from the layout of each tab.
+///
+/// Offsets apply to every tab with such panes, so that e.g. wider fiber and thread lists go together.
+/// They're kept in the `layout` section of the config file.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct Splits {
+    /// Width of lists (fibers, threads, traces) next to the dumps they pick
+    pub list: i16,
+    /// Height of charts above tables and lists
+    pub charts: i16,
+}
+
+impl Splits {
+    const STEP: i16 = 5;
+    const MIN_PERCENT: i16 = 10;
+    const MAX_PERCENT: i16 = 90;
+
+    pub fn list_percent(&self, default: u16) -> u16 {
+        Splits::percent(default, self.list)
+    }
+
+    pub fn charts_percent(&self, default: u16) -> u16 {
+        Splits::percent(default, self.charts)
+    }
+
+    pub fn resize_list(&mut self, grow: bool) {
+        Splits::resize(&mut self.list, grow);
+    }
+
+    pub fn resize_charts(&mut self, grow: bool) {
+        Splits::resize(&mut self.charts, grow);
+    }
+
+    fn percent(default: u16, offset: i16) -> u16 {
+        (default as i16 + offset).clamp(Splits::MIN_PERCENT, Splits::MAX_PERCENT) as u16
+    }
+
+    // offsets beyond what any pane can take would have to be undone before anything changes again
+    fn resize(offset: &mut i16, grow: bool) {
+        let max = Splits::MAX_PERCENT - Splits::MIN_PERCENT;
+        *offset = (*offset + if grow { Splits::STEP } else { -Splits::STEP }).clamp(-max, max);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::widgets::split::Splits;
+
+    #[test]
+    fn panes_are_resized_within_bounds() {
+        let mut splits = Splits::default();
+        assert_eq!(splits.list_percent(45), 45);
+        splits.resize_list(true);
+        splits.resize_charts(false);
+        assert_eq!((splits.list_percent(45), splits.list_percent(35), splits.charts_percent(60)), (50, 40, 55));
+        for _ in 0..20 {
+            splits.resize_list(true);
+        }
+        assert_eq!((splits.list, splits.list_percent(35)), (80, 90));
+        splits.resize_list(false);
+        assert_eq!(splits.list_percent(35), 90);
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::hash_map::HashMap;
-use crate::zio::model::Fiber;
-use crate::akka::model::ActorTreeNode;
+use panopticon_core::zio::model::Fiber;
+use panopticon_core::akka::model::ActorTreeNode;
+use panopticon_core::traces::model::{format_micros, Span};
 
 pub trait TreeWidgetNode {
     fn id(&self) -> usize;
@@ -36,6 +37,21 @@ impl TreeWidgetNode for ActorTreeNode {
     }
 }
 
+impl TreeWidgetNode for Span {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn parent_id(&self) -> Option<usize> {
+        self.parent
+    }
+
+    fn label(&self) -> String {
+        let error = if self.error { " ERROR" } else { "" };
+        format!("{} [{}] {} at +{}{}", self.operation, self.service, format_micros(self.duration), format_micros(self.offset), error)
+    }
+}
+
 ///
 /// Given a list of items returns a list of formatted labels ready to be printed as a tree.
 ///
@@ -142,6 +158,25 @@ fn list_tree_nodes<T: Clone + TreeWidgetNode>(
     }
 }
 
+///
+/// Builds a path of labels (e.g. `user/parent/child`) for every item, keyed by item id.
+///
+/// Unlike ids, which may be assigned anew on every refresh, paths identify nodes across refreshes.
+pub fn node_paths<T: TreeWidgetNode>(items: &[T]) -> HashMap<usize, String> {
+    let by_id: HashMap<usize, &T> = items.iter().map(|i| (i.id(), i)).collect();
+    items.iter().map(|i| {
+        let mut labels = vec![i.label()];
+        let mut parent = i.parent_id();
+        // the length check guards against cycles
+        while let Some(p) = parent.and_then(|id| by_id.get(&id)).filter(|_| labels.len() <= items.len()) {
+            labels.push(p.label());
+            parent = p.parent_id();
+        }
+        labels.reverse();
+        (i.id(), labels.join("/"))
+    }).collect()
+}
+
 ///
 /// Converts a list of items into a Map of (parent -> list of children).
 /// The parent can be None, which indicates that the children are root nodes.
@@ -0,0 +1,202 @@
+/// Samples kept for time charts, an hour at the default tick rate, so that zooming out and panning back has history to show
+pub const HISTORY_SAMPLES: usize = 1800;
+
+///
+/// Time window shown by metric charts.
+///
+/// By default charts show all the retained samples. Zooming in narrows the window to a fixed span
+/// ending at the most recent sample, which can then be panned back through the history.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Viewport {
+    /// Span of the window in seconds, `None` to show everything
+    pub span: Option<u32>,
+    /// How far back (in seconds) the end of the window is from the most recent sample
+    pub offset: u32,
+    /// Labeled moments drawn across all the charts, the oldest first
+    pub markers: Vec<ChartMarker>,
+}
+
+/// A vertical line on the charts, e.g. where a new version got deployed
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChartMarker {
+    /// Seconds since the epoch, like the x of chart samples
+    pub x: f64,
+    pub label: String,
+}
+
+impl Viewport {
+    /// Zoom levels, from the widest
+    const SPANS: [u32; 6] = [1800, 600, 300, 120, 60, 30];
+    /// Panning further back than this is pointless, as histories don't go back much further
+    const MAX_OFFSET: u32 = 3600;
+    /// Way more than fit in the legends, the oldest are dropped beyond it
+    const MAX_MARKERS: usize = 100;
+
+    pub fn zoom_in(&mut self) {
+        self.span = match self.span {
+            None => Some(Viewport::SPANS[0]),
+            Some(s) => Some(Viewport::SPANS.iter().cloned().find(|x| *x < s).unwrap_or(s)),
+        };
+    }
+
+    pub fn zoom_out(&mut self) {
+        self.span = match self.span {
+            None => None,
+            Some(s) => Viewport::SPANS.iter().cloned().rev().find(|x| *x > s),
+        };
+        if self.span.is_none() {
+            self.offset = 0;
+        }
+    }
+
+    /// Moves the window back by half of its span
+    pub fn pan_back(&mut self) {
+        if let Some(s) = self.span {
+            self.offset = (self.offset + s / 2).min(Viewport::MAX_OFFSET);
+        }
+    }
+
+    pub fn pan_forward(&mut self) {
+        if let Some(s) = self.span {
+            self.offset = self.offset.saturating_sub(s / 2);
+        }
+    }
+
+    /// Shows everything again, markers are kept
+    pub fn reset(&mut self) {
+        self.span = None;
+        self.offset = 0;
+    }
+
+    pub fn add_marker(&mut self, x: f64, label: &str) {
+        if self.markers.len() == Viewport::MAX_MARKERS {
+            self.markers.remove(0);
+        }
+        self.markers.push(ChartMarker { x, label: label.to_owned() });
+    }
+
+    /// Markers within the time range shown
+    pub fn markers_within(&self, min_x: f64, max_x: f64) -> impl Iterator<Item=&ChartMarker> {
+        self.markers.iter().filter(move |m| m.x >= min_x && m.x <= max_x)
+    }
+
+    ///
+    /// Time range to display, given the range of sample times (all in seconds).
+    ///
+    /// The window is kept within the samples, so panning stops at the oldest one, and spans longer than the
+    /// samples go back show all of them.
+    pub fn window(&self, min_x: f64, max_x: f64) -> (f64, f64) {
+        match self.span {
+            None => (min_x, max_x),
+            Some(span) => {
+                let span = (span as f64).min(max_x - min_x);
+                let end = (max_x - self.offset as f64).max(min_x + span).min(max_x);
+                (end - span, end)
+            }
+        }
+    }
+
+    /// Short description for chart titles, e.g. `5m` or `5m, 2m30s ago`
+    pub fn describe(&self) -> Option<String> {
+        self.span.map(|s| {
+            if self.offset == 0 {
+                format_duration(s)
+            } else {
+                format!("{}, {} ago", format_duration(s), format_duration(self.offset))
+            }
+        })
+    }
+}
+
+///
+/// Points of a series thinned out to at most two per bucket, for charts narrower than the series is long.
+///
+/// The range is split into equally wide buckets, each keeping its lowest and highest point in time order,
+/// so that spikes survive however long the window is.
+pub fn downsample(points: &[(f64, f64)], min_x: f64, max_x: f64, buckets: usize) -> Vec<(f64, f64)> {
+    if points.len() <= buckets * 2 || buckets == 0 || max_x <= min_x {
+        return points.to_vec();
+    }
+    let width = (max_x - min_x) / buckets as f64;
+    let bucket = |x: f64| (((x - min_x) / width) as usize).min(buckets - 1);
+    let mut thinned: Vec<(f64, f64)> = vec![];
+    let mut start = 0;
+    while start < points.len() {
+        let b = bucket(points[start].0);
+        let end = points[start..].iter().position(|p| bucket(p.0) != b).map_or(points.len(), |i| start + i);
+        let range = &points[start..end];
+        let lowest = range.iter().enumerate().min_by(|a, b| a.1.1.total_cmp(&b.1.1)).map(|(i, _)| i).unwrap_or(0);
+        let highest = range.iter().enumerate().max_by(|a, b| a.1.1.total_cmp(&b.1.1)).map(|(i, _)| i).unwrap_or(0);
+        thinned.push(range[lowest.min(highest)]);
+        if lowest != highest {
+            thinned.push(range[lowest.max(highest)]);
+        }
+        start = end;
+    }
+    thinned
+}
+
+fn format_duration(secs: u32) -> String {
+    match (secs / 60, secs % 60) {
+        (0, s) => format!("{}s", s),
+        (m, 0) => format!("{}m", m),
+        (m, s) => format!("{}m{}s", m, s),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::widgets::viewport::{downsample, Viewport};
+
+    #[test]
+    fn viewport_zooms_and_pans_within_history() {
+        let mut viewport = Viewport::default();
+        assert_eq!(viewport.window(100.0, 1000.0), (100.0, 1000.0));
+
+        viewport.zoom_in();
+        viewport.zoom_in();
+        viewport.zoom_in();
+        assert_eq!(viewport.span, Some(300));
+        assert_eq!(viewport.window(100.0, 1000.0), (700.0, 1000.0));
+
+        viewport.pan_back();
+        assert_eq!(viewport.window(100.0, 1000.0), (550.0, 850.0));
+        assert_eq!(viewport.describe(), Some("5m, 2m30s ago".to_owned()));
+
+        viewport.pan_back();
+        viewport.pan_back();
+        viewport.pan_back();
+        assert_eq!(viewport.window(100.0, 1000.0), (100.0, 400.0));
+
+        // history shorter than the span
+        assert_eq!(viewport.window(900.0, 1000.0), (900.0, 1000.0));
+
+        viewport.zoom_out();
+        viewport.zoom_out();
+        viewport.zoom_out();
+        assert_eq!(viewport, Viewport::default());
+    }
+
+    #[test]
+    fn markers_outlive_resets() {
+        let mut viewport = Viewport::default();
+        viewport.add_marker(500.0, "deployed v2.3.1");
+        viewport.add_marker(900.0, "incident");
+        viewport.zoom_in();
+        viewport.reset();
+        let (min_x, max_x) = viewport.window(600.0, 1000.0);
+        assert_eq!(viewport.markers_within(min_x, max_x).map(|m| m.label.as_str()).collect::<Vec<_>>(), ["incident"]);
+        assert_eq!(viewport.markers.len(), 2);
+    }
+
+    #[test]
+    fn long_series_are_downsampled_keeping_spikes() {
+        let points: Vec<(f64, f64)> = (0..100).map(|i| (i as f64, if i == 42 { 50.0 } else { (i % 3) as f64 })).collect();
+        assert_eq!(downsample(&points, 0.0, 99.0, 50).len(), 100);
+
+        let thinned = downsample(&points, 0.0, 100.0, 10);
+        assert_eq!(thinned.len(), 20);
+        assert_eq!(&thinned[..2], &[(0.0, 0.0), (8.0, 2.0)]);
+        assert_eq!(&thinned[8..10], &[(42.0, 50.0), (45.0, 0.0)]);
+    }
+}
@@ -1,4 +0,0 @@
-pub mod model;
-pub mod zmx;
-mod dump_parser;
-mod tests;